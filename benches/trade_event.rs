@@ -0,0 +1,85 @@
+//! Per-event cost of carrying a trade through the pipeline as TradeInfoFromToken vs TradeEvent
+//! A held-token tick is handed to the hooks, the buy filters, the supervisor and a spawned task; each hand-off
+//! used to clone the full parser struct and now copies the core and bumps the extras' refcount.
+
+use std::hint::black_box;
+use anchor_client::solana_sdk::pubkey::Pubkey;
+use criterion::{criterion_group, criterion_main, Criterion};
+
+use solana_vntr_sniper::engine::trade_event::TradeEvent;
+use solana_vntr_sniper::engine::transaction_parser::{DexType, TradeInfoFromToken};
+
+/// Hand-offs of one event between parsing and the end of its handling
+const HAND_OFFS: usize = 4;
+
+fn trade_info() -> TradeInfoFromToken {
+    TradeInfoFromToken {
+        dex_type: DexType::PumpSwap,
+        slot: 312_000_000,
+        signature: "5VERv8NMvzbJMEkV8xnrLkEaWRtSz9CosKDYjCJjBRnbJLgp8uirBgmQpjKhoR4tjF3ZpRzrFmBV6UjKdiSZkQUW".to_string(),
+        pool_id: Pubkey::new_unique().to_string(),
+        mint: Pubkey::new_unique().to_string(),
+        timestamp: 1_700_000_000,
+        is_buy: true,
+        post_current_price: 3.1e-8,
+        pre_current_price: 3.0e-8,
+        is_reverse_when_pump_swap: false,
+        coin_creator: Some(Pubkey::new_unique().to_string()),
+        sol_change: 0.5,
+        target_transaction_token_change: 16_000_000.0,
+        token_decimals: 6,
+        liquidity: 85.0,
+        virtual_sol_reserves: 85_000_000_000,
+        virtual_token_reserves: 2_700_000_000_000_000,
+        buy_sell_in_same_tx: false,
+        trader: Pubkey::new_unique().to_string(),
+        is_own_trade: false,
+        timestamp_suspect: false,
+        gap_replayed: false,
+        curve_complete: false,
+        suspected_mev: false,
+    }
+}
+
+/// What the hot paths read of an event: price, reserves and direction
+fn decide(price: f64, sol_reserves: u64, is_buy: bool) -> bool {
+    is_buy && price > 0.0 && sol_reserves > 1_000_000_000
+}
+
+fn per_event(c: &mut Criterion) {
+    let mut group = c.benchmark_group("per_event");
+
+    let info = trade_info();
+    group.bench_function("trade_info_clones", |b| {
+        b.iter(|| {
+            let mut held = 0;
+            for _ in 0..HAND_OFFS {
+                let copy = black_box(&info).clone();
+                held += decide(copy.post_current_price, copy.virtual_sol_reserves, copy.is_buy) as usize;
+            }
+            held
+        })
+    });
+
+    let event = TradeEvent::from_trade_info(trade_info()).expect("valid mint");
+    group.bench_function("trade_event_clones", |b| {
+        b.iter(|| {
+            let mut held = 0;
+            for _ in 0..HAND_OFFS {
+                let copy = black_box(&event).clone();
+                held += decide(copy.core.post_current_price, copy.core.virtual_sol_reserves, copy.core.is_buy) as usize;
+            }
+            held
+        })
+    });
+
+    // The split itself is paid once per parsed event
+    group.bench_function("split", |b| {
+        b.iter_batched(trade_info, TradeEvent::from_trade_info, criterion::BatchSize::SmallInput)
+    });
+
+    group.finish();
+}
+
+criterion_group!(benches, per_event);
+criterion_main!(benches);
@@ -0,0 +1,225 @@
+use clap::Parser;
+
+use crate::engine::strategy_sim::{Entry, SimulationArgs, SweepAxis};
+
+/// Largest amount --wrap accepts in one go
+pub const MAX_WRAP_SOL: f64 = 1_000.0;
+
+/// Command line of the bot; without a one-off command it runs the sniper
+#[derive(Parser, Debug, Clone)]
+#[command(name = "solana_vntr_sniper", about = "PumpFun / PumpSwap sniper bot")]
+pub struct Cli {
+    /// Wrap --amount SOL into the WSOL account
+    #[arg(long, requires = "amount")]
+    pub wrap: bool,
+
+    /// Close the WSOL account and recover its SOL
+    #[arg(long)]
+    pub unwrap: bool,
+
+    /// Sell every token in the wallet through Jupiter
+    #[arg(long)]
+    pub sell: bool,
+
+    /// Close all empty token accounts and reclaim their rent
+    #[arg(long)]
+    pub close: bool,
+
+    /// Rebuild ledger entries from the wallet's transaction history on chain
+    #[arg(long)]
+    pub import_history: bool,
+
+    /// Oldest point --import-history goes back to: a signature, YYYY-MM-DD or an RFC 3339 time
+    #[arg(long, value_name = "DATE|SIGNATURE", requires = "import_history")]
+    pub since: Option<String>,
+
+    /// Create a durable nonce account for the wallet
+    #[arg(long)]
+    pub nonce: bool,
+
+    /// Nonce accounts --nonce creates, for NONCE_ACCOUNTS
+    #[arg(long, value_name = "N", requires = "nonce", default_value_t = 1, value_parser = clap::value_parser!(u32).range(1..=32))]
+    pub count: u32,
+
+    /// Create an address lookup table holding the static PumpFun accounts, for LOOKUP_TABLE_ADDRESS
+    #[arg(long)]
+    pub create_lut: bool,
+
+    /// SOL to wrap with --wrap
+    #[arg(long, value_name = "SOL", value_parser = parse_wrap_amount)]
+    pub amount: Option<f64>,
+
+    /// Skip confirmation prompts (for scripts)
+    #[arg(long, short = 'y')]
+    pub yes: bool,
+
+    /// Load the configuration, probe the Yellowstone gRPC endpoint and exit
+    #[arg(long)]
+    pub check_config: bool,
+
+    /// Print the lifecycle of an open or archived position
+    #[arg(long, value_name = "MINT")]
+    pub position: Option<String>,
+
+    /// Override exit params of one position, e.g. --set-position <MINT> stop=25% tp=off trailing=15% (or clear)
+    #[arg(long, value_names = ["MINT", "KEY=VALUE"], num_args = 2..)]
+    pub set_position: Option<Vec<String>>,
+
+    /// Print effective slippage statistics of the fills in LEDGER_FILE (offline)
+    #[arg(long)]
+    pub stats: bool,
+
+    /// Run a scripted paper session under CHAOS_* fault injection and check its invariants; exits 1 on a violation
+    #[arg(long)]
+    pub resilience_suite: bool,
+
+    /// Decode a transaction (base64 wire format, or a signature fetched from chain) and print its instructions
+    #[arg(long, value_name = "BASE64|SIGNATURE")]
+    pub decode_tx: Option<String>,
+
+    /// Replay exit parameters over a candle file or directory (offline)
+    #[arg(long, value_name = "PATH")]
+    pub simulate_strategy: Option<String>,
+
+    /// Exit params JSON laid over the environment's for --simulate-strategy
+    #[arg(long, value_name = "FILE", requires = "simulate_strategy")]
+    pub params: Option<String>,
+
+    /// Param to sweep over with --simulate-strategy; repeat for a grid
+    #[arg(long, value_name = "PARAM=MIN:MAX:STEP", requires = "simulate_strategy", value_parser = parse_sweep)]
+    pub sweep: Vec<SweepAxis>,
+
+    /// Entry price for --simulate-strategy; defaults to the open of the entry candle
+    #[arg(long, value_name = "PRICE", requires = "simulate_strategy")]
+    pub entry_price: Option<f64>,
+
+    /// Entry at the first candle at or after this unix timestamp for --simulate-strategy
+    #[arg(long, value_name = "UNIX_SECS", requires = "simulate_strategy")]
+    pub entry_ts: Option<u64>,
+}
+
+impl Cli {
+    /// Options of --simulate-strategy, when given
+    pub fn simulation_args(&self) -> Option<SimulationArgs> {
+        let candles_path = self.simulate_strategy.clone()?;
+        Some(SimulationArgs {
+            candles_path,
+            entry: Entry { price: self.entry_price, timestamp: self.entry_ts },
+            params_file: self.params.clone(),
+            sweeps: self.sweep.clone(),
+        })
+    }
+}
+
+fn parse_sweep(value: &str) -> Result<SweepAxis, String> {
+    SweepAxis::parse(value).map_err(|e| e.to_string())
+}
+
+/// A finite SOL amount in (0, MAX_WRAP_SOL]
+pub fn parse_wrap_amount(value: &str) -> Result<f64, String> {
+    let amount = value
+        .parse::<f64>()
+        .map_err(|_| format!("'{}' is not a number", value))?;
+    if !amount.is_finite() || amount <= 0.0 {
+        return Err(format!("amount must be greater than 0, got {}", value));
+    }
+    if amount > MAX_WRAP_SOL {
+        return Err(format!("amount must be at most {} SOL, got {}", MAX_WRAP_SOL, value));
+    }
+    Ok(amount)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::error::ErrorKind;
+
+    fn parse(args: &[&str]) -> Result<Cli, clap::Error> {
+        Cli::try_parse_from(std::iter::once("solana_vntr_sniper").chain(args.iter().copied()))
+    }
+
+    fn error_kind(args: &[&str]) -> ErrorKind {
+        parse(args).expect_err("arguments should be rejected").kind()
+    }
+
+    #[test]
+    fn no_arguments_runs_the_sniper() {
+        let cli = parse(&[]).unwrap();
+        assert!(!cli.wrap && !cli.unwrap && !cli.sell && !cli.close);
+        assert_eq!(cli.count, 1);
+        assert!(cli.simulation_args().is_none());
+    }
+
+    #[test]
+    fn wrap_requires_an_amount() {
+        assert_eq!(error_kind(&["--wrap"]), ErrorKind::MissingRequiredArgument);
+        let cli = parse(&["--wrap", "--amount", "0.5", "-y"]).unwrap();
+        assert!(cli.wrap && cli.yes);
+        assert_eq!(cli.amount, Some(0.5));
+    }
+
+    #[test]
+    fn wrap_amount_is_bounded() {
+        assert_eq!(parse_wrap_amount("1000").unwrap(), MAX_WRAP_SOL);
+        assert!(parse_wrap_amount("0").is_err());
+        assert!(parse_wrap_amount("-1").is_err());
+        assert!(parse_wrap_amount("NaN").is_err());
+        assert!(parse_wrap_amount("1000.5").is_err());
+        assert!(parse_wrap_amount("lots").is_err());
+        assert_eq!(error_kind(&["--wrap", "--amount", "0"]), ErrorKind::ValueValidation);
+    }
+
+    #[test]
+    fn dependent_flags_need_their_command() {
+        assert_eq!(error_kind(&["--since", "2024-01-01"]), ErrorKind::MissingRequiredArgument);
+        assert_eq!(error_kind(&["--count", "2"]), ErrorKind::MissingRequiredArgument);
+        assert_eq!(error_kind(&["--nonce", "--count", "33"]), ErrorKind::ValueValidation);
+        assert_eq!(parse(&["--nonce", "--count", "4"]).unwrap().count, 4);
+        let cli = parse(&["--import-history", "--since", "2024-01-01"]).unwrap();
+        assert_eq!(cli.since.as_deref(), Some("2024-01-01"));
+    }
+
+    #[test]
+    fn set_position_takes_a_mint_and_at_least_one_override() {
+        assert!(parse(&["--set-position", "MINT"]).is_err());
+        let cli = parse(&["--set-position", "MINT", "stop=25%", "tp=off"]).unwrap();
+        assert_eq!(cli.set_position.unwrap(), vec!["MINT", "stop=25%", "tp=off"]);
+    }
+
+    #[test]
+    fn simulation_args_need_simulate_strategy() {
+        for args in [
+            &["--params", "p.json"][..],
+            &["--sweep", "stop_loss_pct=10:30:10"],
+            &["--entry-price", "0.001"],
+            &["--entry-ts", "1700000000"],
+        ] {
+            assert_eq!(error_kind(args), ErrorKind::MissingRequiredArgument, "{:?}", args);
+        }
+    }
+
+    #[test]
+    fn simulation_args_are_collected() {
+        let cli = parse(&[
+            "--simulate-strategy", "candles/",
+            "--params", "p.json",
+            "--sweep", "stop_loss_pct=10:30:10",
+            "--sweep", "trailing_stop_pct=5:10:5",
+            "--entry-price", "0.001",
+            "--entry-ts", "1700000000",
+        ]).unwrap();
+        let args = cli.simulation_args().unwrap();
+        assert_eq!(args.candles_path, "candles/");
+        assert_eq!(args.params_file.as_deref(), Some("p.json"));
+        assert_eq!(args.entry.price, Some(0.001));
+        assert_eq!(args.entry.timestamp, Some(1_700_000_000));
+        let axes: Vec<(&str, &[f64])> = args.sweeps.iter().map(|axis| (axis.field.as_str(), axis.values.as_slice())).collect();
+        assert_eq!(axes, vec![("stop_loss_pct", &[10.0, 20.0, 30.0][..]), ("trailing_stop_pct", &[5.0, 10.0][..])]);
+    }
+
+    #[test]
+    fn malformed_sweep_is_rejected() {
+        assert_eq!(error_kind(&["--simulate-strategy", "c", "--sweep", "stop_loss_pct"]), ErrorKind::ValueValidation);
+        assert_eq!(error_kind(&["--simulate-strategy", "c", "--sweep", "stop_loss_pct=1:x:1"]), ErrorKind::ValueValidation);
+    }
+}
@@ -0,0 +1,257 @@
+use std::collections::{HashSet, VecDeque};
+use anchor_client::solana_sdk::{instruction::Instruction, pubkey::Pubkey, transaction::TransactionError};
+
+/// Accounts one transaction may lock
+pub const MAX_TX_ACCOUNT_LOCKS: usize = 64;
+
+/// --close batching: CLOSE_BATCH_SIZE (default: 20) close instructions per transaction, which keeps a batch under the
+/// packet size, and at most CLOSE_MAX_SIMULATIONS (default: 32) simulations spent isolating accounts that fail
+#[derive(Clone, Copy, Debug)]
+pub struct CloseBatchConfig {
+    pub batch_size: usize,
+    pub max_simulations: usize,
+}
+
+impl CloseBatchConfig {
+    pub fn from_env() -> Self {
+        let batch_size = std::env::var("CLOSE_BATCH_SIZE")
+            .ok()
+            .and_then(|v| v.parse::<usize>().ok())
+            .unwrap_or(20)
+            .max(1);
+        let max_simulations = std::env::var("CLOSE_MAX_SIMULATIONS")
+            .ok()
+            .and_then(|v| v.parse::<usize>().ok())
+            .unwrap_or(32);
+        Self { batch_size, max_simulations }
+    }
+}
+
+/// Result of simulating one batch
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SimulationVerdict {
+    Pass,
+    /// Failed; Some(position in the batch) when the error names the instruction
+    Fail(Option<usize>),
+    /// The simulation itself couldn't run; the batch is sent unsimulated
+    Unavailable,
+}
+
+impl SimulationVerdict {
+    pub fn from_error(error: Option<&TransactionError>) -> Self {
+        match error {
+            None => Self::Pass,
+            Some(TransactionError::InstructionError(index, _)) => Self::Fail(Some(*index as usize)),
+            Some(_) => Self::Fail(None),
+        }
+    }
+}
+
+/// Where every item of a set of batches ended up
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Isolation {
+    /// Batches that passed simulation (or couldn't be simulated), ready to send
+    pub passed: Vec<Vec<usize>>,
+    /// Items that fail on their own
+    pub failing: Vec<usize>,
+    /// Items left when the simulation budget ran out; sent one per transaction so they can only fail alone
+    pub unresolved: Vec<usize>,
+    pub simulations: usize,
+}
+
+/// Split `items` into batches of at most `batch_size` whose unique accounts stay within `max_locks`
+/// Items keep their order; one whose own accounts exceed the limit still gets a batch of its own.
+pub fn pack_batches<F>(items: &[usize], batch_size: usize, max_locks: usize, accounts_of: F) -> Vec<Vec<usize>>
+where
+    F: Fn(usize) -> Vec<Pubkey>,
+{
+    let mut batches = Vec::new();
+    let mut current: Vec<usize> = Vec::new();
+    let mut locked: HashSet<Pubkey> = HashSet::new();
+    for &item in items {
+        let accounts = accounts_of(item);
+        let added = accounts.iter().filter(|account| !locked.contains(*account)).count();
+        if !current.is_empty() && (current.len() >= batch_size || locked.len() + added > max_locks) {
+            batches.push(std::mem::take(&mut current));
+            locked.clear();
+        }
+        locked.extend(accounts);
+        current.push(item);
+    }
+    if !current.is_empty() {
+        batches.push(current);
+    }
+    batches
+}
+
+/// Simulate `batches` and isolate the items that make them fail, within `max_simulations`
+/// A failure naming its instruction drops that item and re-simulates the rest; one that doesn't splits the batch
+/// in half (bisection) until the failing items stand alone.
+pub fn isolate_failures<F>(batches: Vec<Vec<usize>>, max_simulations: usize, mut simulate: F) -> Isolation
+where
+    F: FnMut(&[usize]) -> SimulationVerdict,
+{
+    let mut isolation = Isolation::default();
+    let mut queue: VecDeque<Vec<usize>> = batches.into_iter().filter(|batch| !batch.is_empty()).collect();
+    while let Some(batch) = queue.pop_front() {
+        if isolation.simulations >= max_simulations {
+            isolation.unresolved.extend(batch);
+            continue;
+        }
+        isolation.simulations += 1;
+        match simulate(&batch) {
+            SimulationVerdict::Pass | SimulationVerdict::Unavailable => isolation.passed.push(batch),
+            SimulationVerdict::Fail(Some(position)) if position < batch.len() => {
+                let mut rest = batch;
+                isolation.failing.push(rest.remove(position));
+                if !rest.is_empty() {
+                    queue.push_front(rest);
+                }
+            }
+            SimulationVerdict::Fail(_) if batch.len() == 1 => isolation.failing.extend(batch),
+            SimulationVerdict::Fail(_) => {
+                let mut first = batch;
+                let second = first.split_off(first.len() / 2);
+                queue.push_front(second);
+                queue.push_front(first);
+            }
+        }
+    }
+    isolation
+}
+
+/// Position in a batch of the item owning instruction `index`, given each item's instruction count in order
+pub fn item_at_instruction(instruction_counts: &[usize], index: usize) -> Option<usize> {
+    let mut end = 0;
+    for (position, count) in instruction_counts.iter().enumerate() {
+        end += count;
+        if index < end {
+            return Some(position);
+        }
+    }
+    None
+}
+
+/// Unique accounts `instructions` lock, programs included
+pub fn instruction_accounts(instructions: &[Instruction]) -> Vec<Pubkey> {
+    let mut accounts: Vec<Pubkey> = Vec::new();
+    for instruction in instructions {
+        for key in std::iter::once(&instruction.program_id).chain(instruction.accounts.iter().map(|meta| &meta.pubkey)) {
+            if !accounts.contains(key) {
+                accounts.push(*key);
+            }
+        }
+    }
+    accounts
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anchor_client::solana_sdk::instruction::InstructionError;
+
+    /// Simulation that fails, without naming the instruction, whenever a batch holds a bad item
+    fn opaque(bad: &[usize]) -> impl FnMut(&[usize]) -> SimulationVerdict + '_ {
+        move |batch| {
+            if batch.iter().any(|item| bad.contains(item)) {
+                SimulationVerdict::Fail(None)
+            } else {
+                SimulationVerdict::Pass
+            }
+        }
+    }
+
+    fn sorted(mut items: Vec<usize>) -> Vec<usize> {
+        items.sort_unstable();
+        items
+    }
+
+    #[test]
+    fn bisection_converges_on_the_failing_items() {
+        let items: Vec<usize> = (0..20).collect();
+        let isolation = isolate_failures(vec![items.clone()], 100, opaque(&[3, 17]));
+
+        assert_eq!(sorted(isolation.failing.clone()), vec![3, 17]);
+        assert!(isolation.unresolved.is_empty());
+        let passed = sorted(isolation.passed.concat());
+        assert_eq!(passed, items.into_iter().filter(|item| ![3, 17].contains(item)).collect::<Vec<_>>());
+        // Two failures among 20: at most two root-to-leaf paths of the halving tree, each ~2 * log2(20) deep
+        assert!(isolation.simulations <= 1 + 2 * 2 * 5, "{} simulations", isolation.simulations);
+    }
+
+    #[test]
+    fn a_named_instruction_is_dropped_without_bisecting() {
+        let isolation = isolate_failures(vec![vec![10, 11, 12, 13]], 100, |batch| match batch.iter().position(|item| *item == 12) {
+            Some(position) => SimulationVerdict::Fail(Some(position)),
+            None => SimulationVerdict::Pass,
+        });
+        assert_eq!(isolation.failing, vec![12]);
+        assert_eq!(isolation.passed, vec![vec![10, 11, 13]]);
+        assert_eq!(isolation.simulations, 2);
+    }
+
+    #[test]
+    fn a_position_outside_the_batch_falls_back_to_bisection() {
+        let isolation = isolate_failures(vec![vec![0, 1]], 100, |batch| {
+            if batch.contains(&1) { SimulationVerdict::Fail(Some(9)) } else { SimulationVerdict::Pass }
+        });
+        assert_eq!(isolation.failing, vec![1]);
+        assert_eq!(isolation.passed, vec![vec![0]]);
+    }
+
+    #[test]
+    fn the_budget_bounds_the_simulations_and_leaves_the_rest_unresolved() {
+        let items: Vec<usize> = (0..16).collect();
+        let isolation = isolate_failures(vec![items.clone()], 3, opaque(&[5]));
+
+        assert_eq!(isolation.simulations, 3);
+        // Every item is accounted for exactly once
+        let mut seen = isolation.passed.concat();
+        seen.extend(&isolation.failing);
+        seen.extend(&isolation.unresolved);
+        assert_eq!(sorted(seen), items);
+        assert!(isolation.unresolved.contains(&5));
+
+        let none = isolate_failures(vec![vec![0, 1], vec![2]], 0, opaque(&[]));
+        assert_eq!(none.simulations, 0);
+        assert_eq!(none.unresolved, vec![0, 1, 2]);
+    }
+
+    #[test]
+    fn unavailable_simulations_pass_their_batch_and_empty_batches_are_skipped() {
+        let mut calls = 0;
+        let isolation = isolate_failures(vec![Vec::new(), vec![4, 5]], 10, |_| {
+            calls += 1;
+            SimulationVerdict::Unavailable
+        });
+        assert_eq!(calls, 1);
+        assert_eq!(isolation.passed, vec![vec![4, 5]]);
+    }
+
+    #[test]
+    fn batches_respect_size_and_account_locks() {
+        let shared = Pubkey::new_unique();
+        let accounts: Vec<Vec<Pubkey>> = (0..6).map(|_| vec![shared, Pubkey::new_unique(), Pubkey::new_unique()]).collect();
+        let items: Vec<usize> = (0..6).collect();
+
+        assert_eq!(pack_batches(&items, 4, 64, |item| accounts[item].clone()), vec![vec![0, 1, 2, 3], vec![4, 5]]);
+        // The shared key is locked once: 1 + 2 per item, so three items fit in 7 locks
+        assert_eq!(pack_batches(&items, 20, 7, |item| accounts[item].clone()), vec![vec![0, 1, 2], vec![3, 4, 5]]);
+        // An item over the limit on its own still gets a batch
+        assert_eq!(pack_batches(&[0, 1], 20, 2, |item| accounts[item].clone()), vec![vec![0], vec![1]]);
+    }
+
+    #[test]
+    fn instructions_map_back_to_their_item() {
+        let counts = [1, 2, 1];
+        assert_eq!(item_at_instruction(&counts, 0), Some(0));
+        assert_eq!(item_at_instruction(&counts, 2), Some(1));
+        assert_eq!(item_at_instruction(&counts, 3), Some(2));
+        assert_eq!(item_at_instruction(&counts, 4), None);
+
+        assert_eq!(SimulationVerdict::from_error(None), SimulationVerdict::Pass);
+        let named = TransactionError::InstructionError(3, InstructionError::InvalidAccountData);
+        assert_eq!(SimulationVerdict::from_error(Some(&named)), SimulationVerdict::Fail(Some(3)));
+        assert_eq!(SimulationVerdict::from_error(Some(&TransactionError::AccountInUse)), SimulationVerdict::Fail(None));
+    }
+}
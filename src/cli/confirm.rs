@@ -0,0 +1,110 @@
+use std::io::{BufRead, IsTerminal, Write};
+
+pub use crate::engine::risk::min_sol_reserve;
+
+/// Network fee budget for a one-off wallet transaction
+pub const TX_FEE_LAMPORTS: u64 = 5_000;
+
+const LAMPORTS_PER_SOL: f64 = 1_000_000_000.0;
+
+pub fn lamports_to_sol(lamports: u64) -> f64 {
+    lamports as f64 / LAMPORTS_PER_SOL
+}
+
+/// Actions moving more than this much SOL need a y/N confirmation (CONFIRM_THRESHOLD_SOL, default: 1)
+pub fn confirm_threshold_sol() -> f64 {
+    std::env::var("CONFIRM_THRESHOLD_SOL")
+        .ok()
+        .and_then(|v| v.parse::<f64>().ok())
+        .filter(|v| *v >= 0.0)
+        .unwrap_or(1.0)
+}
+
+/// Native SOL left after spending `spend_lamports`, or why the reserve would be violated
+pub fn check_reserve(balance_lamports: u64, spend_lamports: u64, reserve_sol: f64) -> Result<u64, String> {
+    let reserve_lamports = (reserve_sol * LAMPORTS_PER_SOL) as u64;
+    match balance_lamports.checked_sub(spend_lamports) {
+        Some(remaining) if remaining >= reserve_lamports => Ok(remaining),
+        _ => Err(format!(
+            "Refusing: balance {:.6} SOL minus {:.6} SOL would leave less than the {:.6} SOL reserve (MIN_SOL_RESERVE)",
+            lamports_to_sol(balance_lamports),
+            lamports_to_sol(spend_lamports),
+            reserve_sol
+        )),
+    }
+}
+
+/// Decide whether an action moving `affected_sol` may run
+/// Below the threshold or with --yes it proceeds; otherwise it asks on the terminal, and without a terminal it fails
+/// instead of waiting on a pipe.
+pub fn confirm_with(
+    affected_sol: f64,
+    assume_yes: bool,
+    threshold_sol: f64,
+    is_tty: bool,
+    input: &mut dyn BufRead,
+) -> Result<(), String> {
+    if assume_yes || affected_sol <= threshold_sol {
+        return Ok(());
+    }
+    if !is_tty {
+        return Err(format!(
+            "Refusing: {:.6} SOL exceeds CONFIRM_THRESHOLD_SOL ({}) and stdin is not a terminal - re-run with --yes to confirm",
+            affected_sol, threshold_sol
+        ));
+    }
+
+    print!("Proceed with {:.6} SOL? [y/N] ", affected_sol);
+    let _ = std::io::stdout().flush();
+    let mut answer = String::new();
+    input.read_line(&mut answer).map_err(|e| format!("Failed to read confirmation: {}", e))?;
+    match answer.trim().to_lowercase().as_str() {
+        "y" | "yes" => Ok(()),
+        _ => Err("Aborted by user".to_string()),
+    }
+}
+
+/// Print `summary` and confirm against stdin
+pub fn confirm_action(summary: &str, affected_sol: f64, assume_yes: bool) -> Result<(), String> {
+    println!("{}", summary);
+    let stdin = std::io::stdin();
+    let is_tty = stdin.is_terminal();
+    confirm_with(affected_sol, assume_yes, confirm_threshold_sol(), is_tty, &mut stdin.lock())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn confirm(affected_sol: f64, assume_yes: bool, is_tty: bool, answer: &str) -> Result<(), String> {
+        confirm_with(affected_sol, assume_yes, 1.0, is_tty, &mut answer.as_bytes())
+    }
+
+    #[test]
+    fn small_or_pre_confirmed_actions_proceed() {
+        assert!(confirm(1.0, false, false, "").is_ok());
+        assert!(confirm(5.0, true, false, "").is_ok());
+    }
+
+    #[test]
+    fn large_actions_without_a_terminal_are_refused() {
+        let error = confirm(5.0, false, false, "y\n").unwrap_err();
+        assert!(error.contains("--yes"), "{}", error);
+    }
+
+    #[test]
+    fn only_yes_confirms() {
+        assert!(confirm(5.0, false, true, "y\n").is_ok());
+        assert!(confirm(5.0, false, true, " YES \n").is_ok());
+        assert_eq!(confirm(5.0, false, true, "\n"), Err("Aborted by user".to_string()));
+        assert_eq!(confirm(5.0, false, true, "no\n"), Err("Aborted by user".to_string()));
+    }
+
+    #[test]
+    fn reserve_must_survive_the_spend() {
+        assert_eq!(check_reserve(2_000_000_000, 1_000_000_000, 0.05), Ok(1_000_000_000));
+        assert_eq!(check_reserve(1_050_000_000, 1_000_000_000, 0.05), Ok(50_000_000));
+        assert!(check_reserve(1_049_999_999, 1_000_000_000, 0.05).is_err());
+        assert!(check_reserve(500_000_000, 1_000_000_000, 0.0).is_err());
+    }
+}
@@ -0,0 +1,675 @@
+use std::collections::HashSet;
+use std::io::{BufRead, Write};
+use std::str::FromStr;
+use anchor_client::solana_client::rpc_client::GetConfirmedSignaturesForAddress2Config;
+use anchor_client::solana_sdk::{commitment_config::CommitmentConfig, pubkey::Pubkey, signature::{Signature, Signer}};
+use colored::Colorize;
+use futures::stream::{self, StreamExt};
+use serde::{Deserialize, Serialize};
+use solana_transaction_status::EncodedConfirmedTransactionWithStatusMeta;
+
+use crate::common::{config::Config, logger::Logger};
+use crate::core::tx_decode;
+use crate::dex::pump_fun::PUMP_FUN_PROGRAM;
+use crate::engine::event_stream::PUMP_SWAP_PROGRAM;
+use crate::engine::replay;
+use crate::engine::transaction_parser::DexType;
+use crate::services::ledger::{self, LedgerEntry};
+use crate::services::rpc_client::BatchRpcClient;
+use crate::services::session_report;
+use crate::services::trade_costs::{self, owned_token_amounts, TradeCosts};
+
+const LAMPORTS_PER_SOL_F64: f64 = 1_000_000_000.0;
+pub const JUPITER_V6_PROGRAM: &str = "JUP6LkbZbjS1jKKwapdHNy74zcZ3tLUZoi5QNyVTaV4";
+/// getSignaturesForAddress returns at most 1000 per page
+const SIGNATURE_PAGE: usize = 1_000;
+
+/// --import-history settings: IMPORT_HISTORY_CONCURRENCY (default: 4) transactions fetched at once,
+/// IMPORT_REVIEW_FILE (default: import_review.jsonl) for ambiguous ones and
+/// IMPORT_STATE_FILE (default: import_state.json) for resuming an interrupted import
+#[derive(Clone, Debug)]
+pub struct ImportConfig {
+    pub concurrency: usize,
+    pub review_path: String,
+    pub state_path: String,
+}
+
+impl ImportConfig {
+    pub fn from_env() -> Self {
+        let concurrency = std::env::var("IMPORT_HISTORY_CONCURRENCY")
+            .ok()
+            .and_then(|v| v.parse::<usize>().ok())
+            .unwrap_or(4)
+            .max(1);
+        let review_path = std::env::var("IMPORT_REVIEW_FILE").unwrap_or_else(|_| "import_review.jsonl".to_string());
+        let state_path = std::env::var("IMPORT_STATE_FILE").unwrap_or_else(|_| "import_state.json".to_string());
+        Self { concurrency, review_path, state_path }
+    }
+}
+
+/// Where --since stops the walk back through history
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ImportBound {
+    /// Everything the RPC still has
+    Everything,
+    /// Transactions at or after this unix time
+    Since(i64),
+    /// Transactions newer than this signature
+    After(String),
+}
+
+/// A signature, a date (YYYY-MM-DD, UTC) or an RFC 3339 time
+pub fn parse_since(value: Option<&str>) -> Result<ImportBound, String> {
+    let Some(value) = value.map(str::trim) else {
+        return Ok(ImportBound::Everything);
+    };
+    if Signature::from_str(value).is_ok() {
+        return Ok(ImportBound::After(value.to_string()));
+    }
+    if let Ok(date) = chrono::NaiveDate::parse_from_str(value, "%Y-%m-%d") {
+        let start = date.and_hms_opt(0, 0, 0).ok_or_else(|| format!("invalid date {}", value))?;
+        return Ok(ImportBound::Since(start.and_utc().timestamp()));
+    }
+    chrono::DateTime::parse_from_rfc3339(value)
+        .map(|time| ImportBound::Since(time.timestamp()))
+        .map_err(|_| format!("--since takes a signature, YYYY-MM-DD or an RFC 3339 time, got '{}'", value))
+}
+
+/// A trade recognised in a wallet transaction
+#[derive(Clone, Debug, PartialEq)]
+pub struct ImportedTrade {
+    pub signature: String,
+    pub mint: String,
+    /// "buy" or "sell"
+    pub side: String,
+    /// pumpfun, pumpswap or jupiter
+    pub venue: &'static str,
+    pub token_amount: f64,
+    /// SOL paid or received for the tokens, before costs
+    pub sol_amount: f64,
+    pub timestamp: u64,
+    pub costs: TradeCosts,
+}
+
+impl ImportedTrade {
+    pub fn to_ledger_entry(&self) -> LedgerEntry {
+        LedgerEntry {
+            timestamp: self.timestamp,
+            mint: self.mint.clone(),
+            side: self.side.clone(),
+            signature: self.signature.clone(),
+            token_amount: self.token_amount,
+            sol_amount: self.sol_amount,
+            price: if self.token_amount > 0.0 { self.sol_amount / self.token_amount } else { 0.0 },
+            reason: format!("Imported {} {}", self.venue, self.side),
+            simulated: false,
+            blockhash_mode: None,
+            session_id: session_report::session_id().to_string(),
+            costs: self.costs,
+            imported: true,
+            pre_jitter_sol: None,
+            post_jitter_sol: None,
+            slippage_pct: None,
+            slippage_sol: None,
+        }
+    }
+}
+
+/// What one wallet transaction turned out to be
+#[derive(Clone, Debug, PartialEq)]
+pub enum Classification {
+    Trade(ImportedTrade),
+    /// Touches a trading program, but not clearly as one buy or sell; goes to the review file
+    Ambiguous(String),
+    /// Failed, or no trading program involved (transfers, account closes, ...)
+    Unrelated,
+}
+
+/// Line of the review file
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ReviewEntry {
+    pub signature: String,
+    pub timestamp: u64,
+    pub reason: String,
+    pub programs: Vec<String>,
+}
+
+/// Trading programs a transaction invokes, by the program ids among its static keys
+pub fn trading_programs(confirmed: &EncodedConfirmedTransactionWithStatusMeta) -> Vec<&'static str> {
+    let Some(transaction) = confirmed.transaction.transaction.decode() else {
+        return Vec::new();
+    };
+    let keys: HashSet<String> = transaction.message.static_account_keys().iter().map(|key| key.to_string()).collect();
+    [PUMP_FUN_PROGRAM, PUMP_SWAP_PROGRAM, JUPITER_V6_PROGRAM]
+        .into_iter()
+        .filter(|program| keys.contains(*program))
+        .collect()
+}
+
+/// Classify a confirmed transaction of `wallet` from its programs and the wallet's balance deltas
+/// The traded token is the single non-WSOL mint whose wallet balance changed: up for a buy, down for a sell. SOL
+/// includes WSOL the wallet holds, and the amount excludes the fee, tip and rent that `costs_from_meta` accounts for.
+/// A Jupiter route is recognised by its program, PumpFun/PumpSwap by the replay parser agreeing with the balances.
+pub fn classify(confirmed: &EncodedConfirmedTransactionWithStatusMeta, wallet: &Pubkey) -> Classification {
+    let Some(meta) = confirmed.transaction.meta.as_ref() else {
+        return Classification::Ambiguous("no transaction meta".to_string());
+    };
+    if meta.err.is_some() {
+        return Classification::Unrelated;
+    }
+    let programs = trading_programs(confirmed);
+    if programs.is_empty() {
+        return Classification::Unrelated;
+    }
+    let Some(transaction) = confirmed.transaction.transaction.decode() else {
+        return Classification::Ambiguous("undecodable transaction".to_string());
+    };
+    let signature = transaction.signatures.first().map(|signature| signature.to_string()).unwrap_or_default();
+
+    let owner = wallet.to_string();
+    let before = owned_token_amounts(&meta.pre_token_balances, &owner);
+    let after = owned_token_amounts(&meta.post_token_balances, &owner);
+    let mints: HashSet<&String> = before.keys().chain(after.keys()).collect();
+    let mut token_deltas: Vec<(String, f64)> = Vec::new();
+    let mut wsol_delta = 0.0;
+    for mint in mints {
+        let (pre, decimals) = before.get(mint).copied().unwrap_or((0, 0));
+        let (post, post_decimals) = after.get(mint).copied().unwrap_or((0, decimals));
+        let delta = (post - pre) as f64 / 10f64.powi(post_decimals.max(decimals) as i32);
+        if mint == trade_costs::WSOL_MINT {
+            wsol_delta = delta;
+        } else if delta != 0.0 {
+            token_deltas.push((mint.clone(), delta));
+        }
+    }
+    let (mint, token_delta) = match token_deltas.as_slice() {
+        [] => return Classification::Ambiguous("no token balance change for the wallet".to_string()),
+        [(mint, delta)] => (mint.clone(), *delta),
+        _ => return Classification::Ambiguous(format!("{} mints changed balance", token_deltas.len())),
+    };
+
+    let Some(wallet_index) = transaction.message.static_account_keys().iter().position(|key| key == wallet) else {
+        return Classification::Ambiguous("wallet is not an account of the transaction".to_string());
+    };
+    let native_delta = match (meta.pre_balances.get(wallet_index), meta.post_balances.get(wallet_index)) {
+        (Some(pre), Some(post)) => (*post as f64 - *pre as f64) / LAMPORTS_PER_SOL_F64,
+        _ => return Classification::Ambiguous("missing wallet balances".to_string()),
+    };
+    let sol_delta = native_delta + wsol_delta;
+
+    let mut costs = trade_costs::costs_from_meta(confirmed, wallet, &TradeCosts::default()).unwrap_or_default();
+    costs.tip_sol = tx_decode::tip_lamports(&transaction) as f64 / LAMPORTS_PER_SOL_F64;
+    let is_buy = token_delta > 0.0;
+    let sol_amount = if is_buy { -sol_delta - costs.total() } else { sol_delta + costs.total() };
+    if sol_amount <= 0.0 {
+        return Classification::Ambiguous(format!(
+            "{} of {} tokens without SOL moving the other way ({:+.6} SOL)",
+            if is_buy { "gain" } else { "loss" },
+            token_delta.abs(),
+            sol_delta
+        ));
+    }
+
+    let venue = if programs.contains(&JUPITER_V6_PROGRAM) {
+        "jupiter"
+    } else {
+        let parsed = replay::replay_trades(confirmed)
+            .into_iter()
+            .find(|trade| trade.mint == mint && trade.is_buy == is_buy && trade.trader == owner);
+        match parsed.map(|trade| trade.dex_type) {
+            Some(DexType::PumpFun) => "pumpfun",
+            Some(DexType::PumpSwap) => "pumpswap",
+            Some(DexType::RaydiumAmm) => "raydium",
+            _ => return Classification::Ambiguous(format!("no parsed {} of {} by the wallet", if is_buy { "buy" } else { "sell" }, mint)),
+        }
+    };
+
+    Classification::Trade(ImportedTrade {
+        signature,
+        mint,
+        side: if is_buy { "buy" } else { "sell" }.to_string(),
+        venue,
+        token_amount: token_delta.abs(),
+        sol_amount,
+        timestamp: confirmed.block_time.unwrap_or(0).max(0) as u64,
+        costs,
+    })
+}
+
+/// Progress of an import, saved after every page so an interrupted one resumes where it stopped
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct ImportState {
+    pub wallet: String,
+    /// --since the import was started with
+    pub since: Option<String>,
+    /// Oldest signature processed; the next page starts before it
+    pub before: Option<String>,
+    pub pages: usize,
+    pub imported: usize,
+    pub reviewed: usize,
+    pub skipped: usize,
+    pub complete: bool,
+}
+
+impl ImportState {
+    fn load(path: &str) -> Option<Self> {
+        let contents = std::fs::read_to_string(path).ok()?;
+        serde_json::from_str(&contents).ok()
+    }
+
+    fn save(&self, path: &str) -> Result<(), String> {
+        let contents = serde_json::to_string_pretty(self).map_err(|e| e.to_string())?;
+        let tmp = format!("{}.tmp", path);
+        std::fs::write(&tmp, contents).and_then(|_| std::fs::rename(&tmp, path)).map_err(|e| format!("Failed to save {}: {}", path, e))
+    }
+}
+
+/// Signatures already in a JSON-lines file (ledger or review file)
+fn known_signatures(path: &str) -> HashSet<String> {
+    #[derive(Deserialize)]
+    struct Line {
+        signature: String,
+    }
+    let Ok(file) = std::fs::File::open(path) else {
+        return HashSet::new();
+    };
+    std::io::BufReader::new(file)
+        .lines()
+        .map_while(Result::ok)
+        .filter_map(|line| serde_json::from_str::<Line>(&line).ok())
+        .map(|line| line.signature)
+        .filter(|signature| !signature.is_empty())
+        .collect()
+}
+
+fn append_review(path: &str, entry: &ReviewEntry) -> std::io::Result<()> {
+    let line = serde_json::to_string(entry).map_err(std::io::Error::other)?;
+    let mut file = std::fs::OpenOptions::new().create(true).append(true).open(path)?;
+    writeln!(file, "{}", line)
+}
+
+/// Rebuild ledger entries from the wallet's signature history (--import-history [--since ...])
+/// Pages newest to oldest, fetching transactions through the disk-cached read pool with bounded concurrency.
+/// Signatures already in the ledger or the review file are skipped, so re-running is safe.
+pub async fn import_history(config: &Config, since: Option<&str>) -> Result<(), String> {
+    let logger = Logger::new("[IMPORT-HISTORY] => ".magenta().to_string());
+    let import = ImportConfig::from_env();
+    let bound = parse_since(since)?;
+    let wallet = config.app_state.wallet.try_pubkey().map_err(|e| format!("Failed to get wallet pubkey: {}", e))?;
+
+    let mut state = match ImportState::load(&import.state_path) {
+        Some(state) if !state.complete && state.wallet == wallet.to_string() && state.since.as_deref() == since => {
+            println!("⏩ Resuming import after {} (page {})", state.before.as_deref().unwrap_or("the newest signature"), state.pages);
+            state
+        }
+        _ => ImportState { wallet: wallet.to_string(), since: since.map(str::to_string), ..ImportState::default() },
+    };
+    let ledger_path = ledger::ledger_path();
+    let mut known = known_signatures(&ledger_path);
+    known.extend(known_signatures(&import.review_path));
+    println!("📥 Importing history of {} ({} signatures already recorded)", wallet, known.len());
+
+    let until = match &bound {
+        ImportBound::After(signature) => Signature::from_str(signature).ok(),
+        _ => None,
+    };
+    let client = config.app_state.read_rpc.client();
+    loop {
+        let page = client
+            .get_signatures_for_address_with_config(&wallet, GetConfirmedSignaturesForAddress2Config {
+                before: state.before.as_deref().and_then(|signature| Signature::from_str(signature).ok()),
+                until,
+                limit: Some(SIGNATURE_PAGE),
+                commitment: Some(CommitmentConfig::confirmed()),
+            })
+            .await
+            .map_err(|e| format!("Failed to list signatures of {}: {}", wallet, e))?;
+        let Some(last) = page.last() else {
+            break;
+        };
+        let next_before = last.signature.clone();
+        let reached_since = match bound {
+            ImportBound::Since(since) => last.block_time.is_some_and(|time| time < since),
+            _ => false,
+        };
+
+        let pending: Vec<Signature> = page
+            .iter()
+            .filter(|status| status.err.is_none())
+            .filter(|status| match bound {
+                ImportBound::Since(since) => status.block_time.is_none_or(|time| time >= since),
+                _ => true,
+            })
+            .filter(|status| !known.contains(&status.signature))
+            .filter_map(|status| Signature::from_str(&status.signature).ok())
+            .collect();
+        let skipped = page.len() - pending.len();
+
+        let batch = BatchRpcClient::new(client.clone());
+        let fetched: Vec<(Signature, Result<EncodedConfirmedTransactionWithStatusMeta, String>)> = stream::iter(pending)
+            .map(|signature| {
+                let batch = &batch;
+                async move { (signature, batch.get_transaction_cached(&signature).await.map_err(|e| e.to_string())) }
+            })
+            .buffered(import.concurrency)
+            .collect()
+            .await;
+
+        let (mut imported, mut reviewed) = (0, 0);
+        // Oldest first, so the ledger reads chronologically within a page
+        for (signature, result) in fetched.into_iter().rev() {
+            let confirmed = match result {
+                Ok(confirmed) => confirmed,
+                Err(e) => {
+                    // Left unrecorded, so the next run fetches it again
+                    logger.error(format!("Failed to fetch {}: {}", signature, e));
+                    continue;
+                }
+            };
+            match classify(&confirmed, &wallet) {
+                Classification::Trade(trade) => {
+                    ledger::append_now(&trade.to_ledger_entry()).map_err(|e| format!("Failed to write {}: {}", ledger_path, e))?;
+                    known.insert(trade.signature);
+                    imported += 1;
+                }
+                Classification::Ambiguous(reason) => {
+                    let entry = ReviewEntry {
+                        signature: signature.to_string(),
+                        timestamp: confirmed.block_time.unwrap_or(0).max(0) as u64,
+                        reason,
+                        programs: trading_programs(&confirmed).into_iter().map(str::to_string).collect(),
+                    };
+                    append_review(&import.review_path, &entry).map_err(|e| format!("Failed to write {}: {}", import.review_path, e))?;
+                    known.insert(entry.signature);
+                    reviewed += 1;
+                }
+                Classification::Unrelated => {}
+            }
+        }
+
+        state.pages += 1;
+        state.imported += imported;
+        state.reviewed += reviewed;
+        state.skipped += skipped;
+        state.before = Some(next_before);
+        state.save(&import.state_path)?;
+        println!(
+            "📄 Page {}: {} signatures, {} imported, {} for review, {} skipped (total {} imported)",
+            state.pages,
+            page.len(),
+            imported,
+            reviewed,
+            skipped,
+            state.imported
+        );
+        if reached_since || page.len() < SIGNATURE_PAGE {
+            break;
+        }
+    }
+
+    state.complete = true;
+    state.save(&import.state_path)?;
+    println!(
+        "{}",
+        format!(
+            "✅ Import finished: {} trades written to {}, {} ambiguous transactions in {}",
+            state.imported, ledger_path, state.reviewed, import.review_path
+        )
+        .green()
+    );
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anchor_client::solana_sdk::instruction::{AccountMeta, Instruction, InstructionError};
+    use anchor_client::solana_sdk::message::{Message, VersionedMessage};
+    use anchor_client::solana_sdk::transaction::{TransactionError, VersionedTransaction};
+    use serde_json::json;
+    use solana_transaction_status::{EncodedTransaction, EncodedTransactionWithStatusMeta, TransactionBinaryEncoding};
+    use crate::engine::test_fixtures::{pump_fun_event, pump_swap_event};
+
+    const FEE_LAMPORTS: u64 = 5_000;
+    const ACCOUNT_RENT: u64 = 2_039_280;
+
+    fn program_data(buffer: &[u8]) -> String {
+        format!("Program data: {}", base64::encode(buffer))
+    }
+
+    /// A landed transaction of `wallet` calling `program`, with the wallet's token accounts going from `before` to
+    /// `after` raw units (6 decimals, None for no account) and its SOL from `lamports.0` to `lamports.1`
+    fn wallet_transaction(
+        wallet: &Pubkey,
+        program: &str,
+        tokens: &[(Pubkey, Option<u64>, Option<u64>)],
+        lamports: (u64, u64),
+        logs: Vec<String>,
+    ) -> EncodedConfirmedTransactionWithStatusMeta {
+        let accounts: Vec<Pubkey> = tokens.iter().map(|_| Pubkey::new_unique()).collect();
+        let instruction = Instruction::new_with_bytes(
+            Pubkey::from_str(program).unwrap(),
+            &[],
+            accounts.iter().map(|account| AccountMeta::new(*account, false)).collect(),
+        );
+        let message = Message::new(&[instruction], Some(wallet));
+        let index_of = |key: &Pubkey| message.account_keys.iter().position(|candidate| candidate == key).unwrap();
+
+        let mut pre_balances = vec![0u64; message.account_keys.len()];
+        let mut post_balances = pre_balances.clone();
+        pre_balances[0] = lamports.0;
+        post_balances[0] = lamports.1;
+        let balance = |index: usize, mint: &Pubkey, amount: u64| {
+            json!({
+                "accountIndex": index,
+                "mint": mint.to_string(),
+                "owner": wallet.to_string(),
+                "programId": spl_token::id().to_string(),
+                "uiTokenAmount": {
+                    "uiAmount": amount as f64 / 1e6,
+                    "decimals": 6,
+                    "amount": amount.to_string(),
+                    "uiAmountString": (amount as f64 / 1e6).to_string(),
+                },
+            })
+        };
+        let (mut pre_tokens, mut post_tokens) = (Vec::new(), Vec::new());
+        for ((mint, before, after), account) in tokens.iter().zip(&accounts) {
+            let index = index_of(account);
+            if let Some(amount) = before {
+                pre_balances[index] = ACCOUNT_RENT;
+                pre_tokens.push(balance(index, mint, *amount));
+            }
+            if let Some(amount) = after {
+                post_balances[index] = ACCOUNT_RENT;
+                post_tokens.push(balance(index, mint, *amount));
+            }
+        }
+
+        let transaction = VersionedTransaction {
+            signatures: vec![Signature::new_unique()],
+            message: VersionedMessage::Legacy(message),
+        };
+        let meta = serde_json::from_value(json!({
+            "err": null,
+            "status": { "Ok": null },
+            "fee": FEE_LAMPORTS,
+            "preBalances": pre_balances,
+            "postBalances": post_balances,
+            "logMessages": logs,
+            "preTokenBalances": pre_tokens,
+            "postTokenBalances": post_tokens,
+        }))
+        .unwrap();
+        EncodedConfirmedTransactionWithStatusMeta {
+            slot: 1,
+            block_time: Some(1_700_000_000),
+            transaction: EncodedTransactionWithStatusMeta {
+                transaction: EncodedTransaction::Binary(
+                    base64::encode(bincode::serialize(&transaction).unwrap()),
+                    TransactionBinaryEncoding::Base64,
+                ),
+                meta: Some(meta),
+                version: None,
+            },
+        }
+    }
+
+    fn trade(classification: Classification) -> ImportedTrade {
+        match classification {
+            Classification::Trade(trade) => trade,
+            other => panic!("expected a trade, got {:?}", other),
+        }
+    }
+
+    fn ambiguous(classification: Classification) -> String {
+        match classification {
+            Classification::Ambiguous(reason) => reason,
+            other => panic!("expected an ambiguous transaction, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn pump_fun_buy_is_imported_net_of_fee_and_rent() {
+        let wallet = Pubkey::new_unique();
+        let mint = Pubkey::new_unique();
+        // 0.1 SOL for 1M tokens, opening the token account on the way
+        let paid = 100_000_000 + FEE_LAMPORTS + ACCOUNT_RENT;
+        let confirmed = wallet_transaction(
+            &wallet,
+            PUMP_FUN_PROGRAM,
+            &[(mint, None, Some(1_000_000_000_000))],
+            (2_000_000_000, 2_000_000_000 - paid),
+            vec![program_data(&pump_fun_event(&mint, 100_000_000, 1_000_000_000_000, true, 30_000_000_000, 1_000_000_000_000_000))],
+        );
+
+        let imported = trade(classify(&confirmed, &wallet));
+        assert_eq!(imported.venue, "pumpfun");
+        assert_eq!(imported.side, "buy");
+        assert_eq!(imported.mint, mint.to_string());
+        assert_eq!(imported.timestamp, 1_700_000_000);
+        assert!((imported.token_amount - 1_000_000.0).abs() < 1e-9);
+        assert!((imported.sol_amount - 0.1).abs() < 1e-9, "{}", imported.sol_amount);
+        assert!((imported.costs.network_fee_sol - 0.000005).abs() < 1e-12);
+        assert!((imported.costs.rent_sol - ACCOUNT_RENT as f64 / 1e9).abs() < 1e-12);
+    }
+
+    #[test]
+    fn pump_swap_sell_is_imported_from_the_parsed_event() {
+        let wallet = Pubkey::new_unique();
+        let mint = Pubkey::new_unique();
+        let confirmed = wallet_transaction(
+            &wallet,
+            PUMP_SWAP_PROGRAM,
+            &[(mint, Some(1_000_000_000), Some(0))],
+            (1_000_000_000, 1_000_000_000 + 50_000_000 - FEE_LAMPORTS),
+            vec![
+                "Program log: Instruction: Sell".to_string(),
+                program_data(&pump_swap_event(1_000_000_000, 50_000_000, 1_000_000_000_000, 50_000_000_000, false)),
+            ],
+        );
+
+        let imported = trade(classify(&confirmed, &wallet));
+        assert_eq!(imported.venue, "pumpswap");
+        assert_eq!(imported.side, "sell");
+        assert!((imported.token_amount - 1_000.0).abs() < 1e-9);
+        assert!((imported.sol_amount - 0.05).abs() < 1e-9, "{}", imported.sol_amount);
+
+        let entry = imported.to_ledger_entry();
+        assert!(entry.imported);
+        assert!(!entry.simulated);
+        assert_eq!(entry.reason, "Imported pumpswap sell");
+        assert!((entry.price - 0.00005).abs() < 1e-12);
+    }
+
+    #[test]
+    fn jupiter_route_is_recognised_by_its_program() {
+        let wallet = Pubkey::new_unique();
+        let mint = Pubkey::new_unique();
+        let confirmed = wallet_transaction(
+            &wallet,
+            JUPITER_V6_PROGRAM,
+            &[(mint, Some(5_000_000), Some(1_000_000))],
+            (1_000_000_000, 1_000_000_000 + 20_000_000 - FEE_LAMPORTS),
+            Vec::new(),
+        );
+
+        let imported = trade(classify(&confirmed, &wallet));
+        assert_eq!(imported.venue, "jupiter");
+        assert_eq!(imported.side, "sell");
+        assert!((imported.token_amount - 4.0).abs() < 1e-9);
+        assert!((imported.sol_amount - 0.02).abs() < 1e-9, "{}", imported.sol_amount);
+    }
+
+    #[test]
+    fn unclear_trading_transactions_go_to_review() {
+        let wallet = Pubkey::new_unique();
+        let (first, second) = (Pubkey::new_unique(), Pubkey::new_unique());
+
+        let two_mints = wallet_transaction(
+            &wallet,
+            JUPITER_V6_PROGRAM,
+            &[(first, Some(1_000_000), Some(0)), (second, Some(0), Some(1_000_000))],
+            (1_000_000_000, 1_000_000_000 - FEE_LAMPORTS),
+            Vec::new(),
+        );
+        assert_eq!(ambiguous(classify(&two_mints, &wallet)), "2 mints changed balance");
+
+        // Tokens arrive but only the fee left the wallet
+        let airdrop = wallet_transaction(
+            &wallet,
+            JUPITER_V6_PROGRAM,
+            &[(first, Some(0), Some(1_000_000))],
+            (1_000_000_000, 1_000_000_000 - FEE_LAMPORTS),
+            Vec::new(),
+        );
+        assert!(ambiguous(classify(&airdrop, &wallet)).starts_with("gain of 1 tokens without SOL"));
+
+        let no_change = wallet_transaction(&wallet, PUMP_FUN_PROGRAM, &[(first, Some(7), Some(7))], (1_000_000_000, 999_995_000), Vec::new());
+        assert_eq!(ambiguous(classify(&no_change, &wallet)), "no token balance change for the wallet");
+
+        // Balances of a buy, but no PumpFun event to back them
+        let unparsed = wallet_transaction(
+            &wallet,
+            PUMP_FUN_PROGRAM,
+            &[(first, Some(0), Some(1_000_000))],
+            (1_000_000_000, 900_000_000),
+            Vec::new(),
+        );
+        assert_eq!(ambiguous(classify(&unparsed, &wallet)), format!("no parsed buy of {} by the wallet", first));
+    }
+
+    #[test]
+    fn transfers_and_failed_trades_are_unrelated() {
+        let wallet = Pubkey::new_unique();
+        let mint = Pubkey::new_unique();
+        let transfer = wallet_transaction(
+            &wallet,
+            &spl_token::id().to_string(),
+            &[(mint, Some(1_000_000), Some(0))],
+            (1_000_000_000, 1_000_000_000 - FEE_LAMPORTS),
+            Vec::new(),
+        );
+        assert_eq!(classify(&transfer, &wallet), Classification::Unrelated);
+
+        let mut failed = wallet_transaction(
+            &wallet,
+            PUMP_FUN_PROGRAM,
+            &[(mint, Some(0), Some(1_000_000))],
+            (1_000_000_000, 900_000_000),
+            vec![program_data(&pump_fun_event(&mint, 100_000_000, 1_000_000, true, 30_000_000_000, 1_000_000_000_000_000))],
+        );
+        failed.transaction.meta.as_mut().unwrap().err = Some(TransactionError::InstructionError(2, InstructionError::Custom(6002)));
+        assert_eq!(classify(&failed, &wallet), Classification::Unrelated);
+    }
+
+    #[test]
+    fn since_takes_a_signature_a_date_or_a_time() {
+        assert_eq!(parse_since(None), Ok(ImportBound::Everything));
+        let signature = Signature::new_unique().to_string();
+        assert_eq!(parse_since(Some(&signature)), Ok(ImportBound::After(signature.clone())));
+        assert_eq!(parse_since(Some(" 2024-03-01 ")), Ok(ImportBound::Since(1_709_251_200)));
+        assert_eq!(parse_since(Some("2024-03-01T01:00:00+01:00")), Ok(ImportBound::Since(1_709_251_200)));
+        assert!(parse_since(Some("last tuesday")).unwrap_err().contains("'last tuesday'"));
+    }
+}
@@ -0,0 +1,6 @@
+pub mod args;
+pub mod confirm;
+pub mod wallet;
+pub mod token_accounts;
+pub mod close_batch;
+pub mod history_import;
@@ -0,0 +1,950 @@
+use anchor_client::solana_sdk::commitment_config::CommitmentLevel;
+use anchor_client::solana_sdk::instruction::Instruction;
+use anchor_client::solana_sdk::signature::{Signature, Signer};
+use anchor_client::solana_sdk::pubkey::Pubkey;
+use anchor_client::solana_sdk::transaction::Transaction;
+use colored::Colorize;
+use spl_token_2022::extension::StateWithExtensionsOwned;
+use spl_associated_token_account::get_associated_token_address_with_program_id;
+use spl_associated_token_account::instruction::create_associated_token_account_idempotent;
+use spl_token_2022::state::{Account as Token2022Account, AccountState};
+use std::collections::{hash_map::Entry, BTreeMap, HashMap};
+use std::str::FromStr;
+
+use crate::cli::close_batch::{self, CloseBatchConfig, SimulationVerdict, MAX_TX_ACCOUNT_LOCKS};
+use crate::cli::confirm::{self, lamports_to_sol, TX_FEE_LAMPORTS};
+use crate::common::{clock, config::Config, logger::Logger};
+use crate::core::mint_inspector::{self, HoldingRoute, MintInfo, SpecialExtension};
+use crate::core::token;
+use crate::error::{SniperError, SniperResult};
+use crate::services::confirmation_tracker::{self, ConfirmationOutcome};
+use crate::services::rpc_client::BatchRpcClient;
+use crate::services::jupiter_api::{SwapMode, SwapOptions};
+use crate::services::ledger::{self, LedgerEntry};
+use crate::services::session_report;
+use crate::services::trade_costs::TradeCosts;
+
+/// One token account with a balance found by the wallet scan
+#[derive(Clone, Debug, PartialEq)]
+pub struct ScannedAccount {
+    pub address: Pubkey,
+    pub mint: Pubkey,
+    pub token_program: Pubkey,
+    pub amount: u64,
+    pub decimals: u8,
+}
+
+/// Everything the wallet holds of one mint
+/// Older wallets and tools left auxiliary (non-associated) accounts next to the ATA; Jupiter only sells from the ATA.
+#[derive(Clone, Debug, PartialEq)]
+pub struct MintHoldings {
+    pub mint: Pubkey,
+    pub token_program: Pubkey,
+    pub decimals: u8,
+    /// The wallet's associated token account, whether or not the scan found it
+    pub ata: Pubkey,
+    pub ata_amount: u64,
+    pub auxiliary: Vec<ScannedAccount>,
+}
+
+impl MintHoldings {
+    pub fn total_amount(&self) -> u64 {
+        self.ata_amount + self.auxiliary.iter().map(|account| account.amount).sum::<u64>()
+    }
+
+    pub fn needs_consolidation(&self) -> bool {
+        !self.auxiliary.is_empty()
+    }
+}
+
+/// Group scanned accounts by mint, telling the ATA apart from auxiliary accounts
+pub fn group_by_mint(wallet: &Pubkey, accounts: Vec<ScannedAccount>) -> Vec<MintHoldings> {
+    let mut grouped: BTreeMap<Pubkey, MintHoldings> = BTreeMap::new();
+    for account in accounts {
+        let holdings = grouped.entry(account.mint).or_insert_with(|| MintHoldings {
+            mint: account.mint,
+            token_program: account.token_program,
+            decimals: account.decimals,
+            ata: get_associated_token_address_with_program_id(wallet, &account.mint, &account.token_program),
+            ata_amount: 0,
+            auxiliary: Vec::new(),
+        });
+        if account.address == holdings.ata {
+            holdings.ata_amount += account.amount;
+        } else {
+            holdings.auxiliary.push(account);
+        }
+    }
+    grouped.into_values().collect()
+}
+
+/// Move every auxiliary balance of `holdings` into its ATA, creating the ATA if needed
+/// The auxiliary accounts are left empty for --close to reclaim.
+pub fn consolidation_instructions(wallet: &Pubkey, holdings: &MintHoldings) -> Result<Vec<Instruction>, String> {
+    if !holdings.needs_consolidation() {
+        return Ok(Vec::new());
+    }
+    let mut instructions = vec![create_associated_token_account_idempotent(
+        wallet,
+        wallet,
+        &holdings.mint,
+        &holdings.token_program,
+    )];
+    for account in &holdings.auxiliary {
+        // transfer_checked from spl-token-2022 builds for either token program
+        instructions.push(
+            spl_token_2022::instruction::transfer_checked(
+                &holdings.token_program,
+                &account.address,
+                &holdings.mint,
+                &holdings.ata,
+                wallet,
+                &[],
+                account.amount,
+                holdings.decimals,
+            )
+            .map_err(|e| format!("Failed to create transfer from {} for {}: {}", account.address, holdings.mint, e))?,
+        );
+    }
+    Ok(instructions)
+}
+
+/// Sell all tokens using Jupiter API
+/// Every token is quoted first; the sale needs confirmation when the expected proceeds exceed CONFIRM_THRESHOLD_SOL.
+/// Non-transferable tokens are burned and their accounts closed instead, frozen accounts are left alone (see
+/// `mint_inspector::route_holding`), and interest-bearing amounts are shown with their interest.
+pub async fn sell_all_tokens(config: &Config, assume_yes: bool) -> SniperResult<()> {
+    let logger = Logger::new("[SELL-ALL-TOKENS] => ".green().to_string());
+    let quote_logger = Logger::new("[JUPITER-QUOTE] => ".blue().to_string());
+    let execute_logger = Logger::new("[EXECUTE-SWAP] => ".yellow().to_string());
+    let sell_logger = Logger::new("[SELL-TOKEN] ".cyan().to_string());
+    
+    // Get wallet pubkey
+    let wallet_pubkey = config.app_state.wallet.try_pubkey()
+        .map_err(|e| SniperError::Other(format!("Failed to get wallet pubkey: {}", e)))?;
+    
+    logger.critical(format!("🔍 Scanning wallet {} for tokens to sell", wallet_pubkey));
+    
+    // Get the token program pubkeys
+    let token_program = Pubkey::from_str("TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA").unwrap();
+    let token_2022_program = Pubkey::from_str("TokenzQdBNbLqP5VEhdkAS6EPFLC1PHnBqCXEpPxuEb").unwrap();
+    
+    // Query all token accounts owned by the wallet (both standard Token and Token-2022)
+    // Use spawn_blocking to avoid blocking the async runtime
+    let wallet_pubkey_clone = wallet_pubkey.clone();
+    let rpc_client_clone = config.app_state.read_rpc.blocking_client();
+    let accounts_normal_token = tokio::task::spawn_blocking(move || {
+        rpc_client_clone.get_token_accounts_by_owner(
+            &wallet_pubkey_clone,
+            anchor_client::solana_client::rpc_request::TokenAccountsFilter::ProgramId(token_program)
+        )
+    }).await.map_err(|e| format!("Task join error: {}", e))?
+        .map_err(|e| SniperError::from(e).context("Failed to get token accounts"))?;
+    
+    let wallet_pubkey_clone2 = wallet_pubkey.clone();
+    let rpc_client_clone2 = config.app_state.read_rpc.blocking_client();
+    let accounts_of_token_2022 = tokio::task::spawn_blocking(move || {
+        rpc_client_clone2.get_token_accounts_by_owner(
+            &wallet_pubkey_clone2,
+            anchor_client::solana_client::rpc_request::TokenAccountsFilter::ProgramId(token_2022_program)
+        )
+    }).await.map_err(|e| format!("Task join error: {}", e))?
+        .map_err(|e| SniperError::from(e).context("Failed to get Token-2022 accounts"))?;
+    
+    // Combine both account vectors
+    let normal_token_count = accounts_normal_token.len();
+    let token_2022_count = accounts_of_token_2022.len();
+    let mut accounts = accounts_normal_token;
+    accounts.extend(accounts_of_token_2022);
+    
+    if accounts.is_empty() {
+        logger.critical("No token accounts found".to_string());
+        return Ok(());
+    }
+    
+    logger.critical(format!("Found {} token accounts ({} standard + {} Token-2022)", 
+                       accounts.len(), 
+                       normal_token_count, 
+                       token_2022_count));
+    
+    // OPTIMIZATION: Use shared JupiterClient from AppState (eliminates duplicate initialization)
+    // Filter and collect token information
+    let mut scanned = Vec::new();
+    let mut sold_count = 0;
+    let mut consolidated_count = 0;
+    let mut failed_count = 0;
+    let mut total_sol_received = 0u64;
+    // Token-2022 special cases: soulbound tokens are burned, frozen accounts left alone
+    let mut mint_infos: HashMap<Pubkey, MintInfo> = HashMap::new();
+    let mut special_counts: BTreeMap<SpecialExtension, usize> = BTreeMap::new();
+    let mut to_burn: Vec<(ScannedAccount, u64)> = Vec::new();
+    let mut ignored: Vec<ScannedAccount> = Vec::new();
+    let mut burned_count = 0;
+    let now = clock::now_secs() as i64;
+    
+    // Token accounts, then their distinct mints, each read in getMultipleAccounts batches
+    let account_keys = accounts
+        .iter()
+        .map(|account_info| Pubkey::from_str(&account_info.pubkey)
+            .map_err(|_| format!("Invalid token account pubkey: {}", account_info.pubkey)))
+        .collect::<Result<Vec<Pubkey>, String>>()?;
+    let batch = BatchRpcClient::new(config.app_state.read_rpc.client());
+    let account_datas = batch.get_accounts_chunked(&account_keys).await;
+    
+    let mut balances = Vec::new();
+    for token_account in account_keys {
+        let Some(account_data) = account_datas.get(&token_account) else {
+            logger.error(format!("Failed to get account data for {}", token_account).red().to_string());
+            continue;
+        };
+        
+        // Both programs share the base account layout, so one unpack reads either
+        let token_data = match StateWithExtensionsOwned::<Token2022Account>::unpack(account_data.data.clone()) {
+            Ok(token_data) => token_data,
+            Err(e) => {
+                logger.error(format!("Failed to parse token account data for {}: {}", token_account, e).yellow().to_string());
+                continue;
+            }
+        };
+        // Skip WSOL (wrapped SOL) and accounts with zero balance
+        if token_data.base.mint == spl_token::native_mint::id() || token_data.base.amount == 0 {
+            continue;
+        }
+        balances.push((token_account, account_data, token_data));
+    }
+    
+    let mut mints: Vec<Pubkey> = balances.iter().map(|(_, _, token_data)| token_data.base.mint).collect();
+    mints.sort();
+    mints.dedup();
+    for (mint, mint_data) in batch.get_accounts_chunked(&mints).await {
+        match mint_inspector::parse_mint_account(&mint, &mint_data) {
+            Ok(info) => {
+                mint_infos.insert(mint, info);
+            }
+            Err(e) => logger.error(format!("Failed to parse mint data for {}: {}", mint, e).yellow().to_string()),
+        }
+    }
+    
+    for (token_account, account_data, token_data) in balances {
+        let is_token_2022 = account_data.owner == token_2022_program;
+        
+        let (mint, amount) = (token_data.base.mint, token_data.base.amount);
+        
+        // Decimals and extensions come from the mint account
+        let Some(mint_info) = mint_infos.get(&mint) else {
+            logger.error(format!("Failed to get mint data for {}", mint).yellow().to_string());
+            continue;
+        };
+        let decimals = mint_info.decimals;
+        let (route, category) = mint_inspector::route_holding(mint_info, token_data.base.state == AccountState::Frozen);
+        if let Some(category) = category {
+            *special_counts.entry(category).or_default() += 1;
+        }
+        
+        logger.critical(format!("📦 Found token: {} - Amount: {} (decimals: {}, program: {}, account: {}{})", 
+                           mint, mint_info.ui_amount(amount, now), decimals, if is_token_2022 { "Token-2022" } else { "Token" }, token_account,
+                           category.map(|category| format!(", {}", category.as_str())).unwrap_or_default()));
+        
+        let scanned_account = ScannedAccount { address: token_account, mint, token_program: account_data.owner, amount, decimals };
+        match route {
+            HoldingRoute::Sell => scanned.push(scanned_account),
+            HoldingRoute::BurnAndClose => to_burn.push((scanned_account, account_data.lamports)),
+            HoldingRoute::Ignore => ignored.push(scanned_account),
+        }
+    }
+    
+    // One sale per mint, however many accounts hold it
+    let tokens_to_sell = group_by_mint(&wallet_pubkey, scanned);
+    let total_token_count = tokens_to_sell.len();
+    for account in &ignored {
+        logger.critical(format!("Ignoring {} ({}): its account is frozen, so it can be neither sold nor burned", account.mint, account.address).yellow().to_string());
+    }
+    if tokens_to_sell.is_empty() && to_burn.is_empty() {
+        logger.critical("No tokens found to sell (excluding SOL/WSOL)".yellow().to_string());
+        return Ok(());
+    }
+    
+    // Quote everything first so the summary shows what the sale is worth
+    let sol_mint = "So11111111111111111111111111111111111111112";
+    let mut quoted = Vec::new();
+    for holdings in tokens_to_sell {
+        let mint = holdings.mint.to_string();
+        let amount = holdings.total_amount();
+        quote_logger.critical(format!("Getting quote: {} -> {} (amount: {})", mint, sol_mint, amount));
+        match config.app_state.jupiter_client.get_quote(&mint, sol_mint, amount, 100, SwapMode::ExactIn).await {
+            Ok(quote) => {
+                quote_logger.debug(format!("Raw quote response (first 500 chars): {}", 
+                    serde_json::to_string(&quote).unwrap_or_default().chars().take(500).collect::<String>()));
+                quote_logger.critical(format!("Quote received: {} {} -> {} {}", 
+                    quote.in_amount, mint, quote.out_amount, sol_mint));
+                quoted.push((holdings, quote));
+            },
+            Err(e) => {
+                logger.error(format!("❌ Failed to get quote for token {}: {}", mint, e).red().to_string());
+                failed_count += 1;
+            }
+        }
+    }
+
+    if quoted.is_empty() && to_burn.is_empty() {
+        return Err(SniperError::QuoteFailed(format!("Failed to quote any of {} tokens", total_token_count)));
+    }
+
+    let expected_lamports: u64 = quoted.iter()
+        .filter_map(|(_, quote)| quote.out_amount.parse::<u64>().ok())
+        .sum();
+    // Proceeds go to the WSOL account unless JUPITER_SELL_DESTINATION=native, so that is the balance that grows
+    let options = SwapOptions::for_sell(&wallet_pubkey);
+    let (balance_label, sol_balance) = match options.destination_token_account {
+        Some(wsol_account) => {
+            let balance = config.app_state.read_rpc.blocking_client().get_token_account_balance(&wsol_account)
+                .ok()
+                .and_then(|balance| balance.amount.parse::<u64>().ok())
+                .unwrap_or(0);
+            ("WSOL balance:", balance)
+        }
+        None => {
+            let balance = config.app_state.read_rpc.blocking_client().get_balance(&wallet_pubkey)
+                .map_err(|e| SniperError::from(e).context("Failed to get SOL balance"))?;
+            ("SOL balance: ", balance)
+        }
+    };
+    let mut summary = format!("About to sell {} tokens from {} via Jupiter", quoted.len(), wallet_pubkey);
+    for (holdings, quote) in &quoted {
+        summary.push_str(&format!(
+            "\n  {}: {} tokens -> ~{:.6} SOL",
+            holdings.mint,
+            display_amount(&mint_infos, &holdings.mint, holdings.total_amount(), holdings.decimals, now),
+            quote.out_amount.parse::<u64>().map(lamports_to_sol).unwrap_or(0.0)
+        ));
+        if holdings.needs_consolidation() {
+            summary.push_str(&format!(" ({} auxiliary accounts moved into the ATA first)", holdings.auxiliary.len()));
+        }
+    }
+    for (account, lamports) in &to_burn {
+        summary.push_str(&format!(
+            "\n  {}: {} non-transferable tokens burned, account {} closed (+{:.6} SOL rent)",
+            account.mint,
+            display_amount(&mint_infos, &account.mint, account.amount, account.decimals, now),
+            account.address,
+            lamports_to_sol(*lamports)
+        ));
+    }
+    if !ignored.is_empty() {
+        summary.push_str(&format!("\n  {} frozen accounts ignored", ignored.len()));
+    }
+    if let Some(counts) = mint_inspector::format_special_counts(&special_counts) {
+        summary.push_str(&format!("\n  Special accounts: {}", counts));
+    }
+    summary.push_str(&format!(
+        "\n  Expected:    ~{:.6} SOL\n  {} {:.6} -> ~{:.6}",
+        lamports_to_sol(expected_lamports),
+        balance_label,
+        lamports_to_sol(sol_balance),
+        lamports_to_sol(sol_balance + expected_lamports)
+    ));
+    confirm::confirm_action(&summary, lamports_to_sol(expected_lamports), assume_yes).map_err(SniperError::Cancelled)?;
+
+    logger.critical(format!("💱 Starting to sell {} tokens", quoted.len()));
+    
+    // Sell each token using Jupiter API
+    for (holdings, quote) in quoted {
+        let mint = holdings.mint.to_string();
+        logger.critical(format!("💱 Selling token: {}", mint).cyan().to_string());
+        
+        let mut amount = holdings.total_amount();
+        if holdings.needs_consolidation() {
+            match consolidate(config, &wallet_pubkey, &holdings) {
+                Ok(signature) => {
+                    logger.critical(format!("🔀 Moved {} auxiliary accounts of {} into {}: {}",
+                                       holdings.auxiliary.len(), mint, holdings.ata, signature));
+                    consolidated_count += holdings.auxiliary.len();
+                    // Transfer fees (Token-2022) may have withheld part of what was moved
+                    if let Some(balance) = config.app_state.read_rpc.blocking_client().get_token_account_balance(&holdings.ata)
+                        .ok()
+                        .and_then(|balance| balance.amount.parse::<u64>().ok())
+                    {
+                        amount = balance;
+                    }
+                }
+                Err(e) => {
+                    logger.error(format!("❌ Failed to consolidate {}: {}; selling the ATA balance only", mint, e).red().to_string());
+                    amount = holdings.ata_amount;
+                }
+            }
+        }
+        if amount == 0 {
+            failed_count += 1;
+            continue;
+        }
+        
+        // Now get the actual transaction using the enhanced Jupiter sell method
+        match config.app_state.jupiter_client.sell(&mint, amount, 500, SwapMode::ExactIn, &config.app_state.wallet, &options).await {
+            Ok(signature) => {
+                execute_logger.critical(format!("Jupiter sell transaction sent: {}", signature));
+                
+                let timeout = confirmation_tracker::confirm_timeout();
+                let outcome = match Signature::from_str(&signature) {
+                    Ok(parsed) => confirmation_tracker::tracker(&config.app_state.read_rpc)
+                        .await_confirmation(&parsed, CommitmentLevel::Confirmed, timeout)
+                        .await,
+                    Err(e) => Err(format!("invalid signature: {}", e)),
+                };
+                match outcome {
+                    Ok(ConfirmationOutcome::Failed(err)) => {
+                        logger.error(format!("❌ Sell of {} failed on-chain: {} ({})", mint, err, signature).red().to_string());
+                        failed_count += 1;
+                    }
+                    Ok(ConfirmationOutcome::Expired) => {
+                        logger.error(format!("❌ Sell of {} not confirmed within {}s: {}", mint, timeout.as_secs(), signature).red().to_string());
+                        failed_count += 1;
+                    }
+                    Ok(landed) => {
+                        execute_logger.critical(format!("Jupiter sell transaction {:?}: {}", landed, signature));
+                        
+                        // Log the successful sell
+                        sell_logger.critical(format!("{} => Token sold successfully! Signature: {}", mint, signature));
+                        
+                        // Remove token from bought token list after successful sell
+                        crate::engine::sniper::TOKEN_HOLDINGS.remove(&mint);
+                        
+                        // Parse the expected SOL amount from quote
+                        if let Ok(sol_amount) = quote.out_amount.parse::<u64>() {
+                            total_sol_received += sol_amount;
+                        }
+                        
+                        logger.critical(format!("✅ Successfully sold {}: {}", mint, signature).green().to_string());
+                        sold_count += 1;
+                    }
+                    Err(e) => {
+                        logger.error(format!("❌ Sell of {} could not be confirmed ({}): {}", mint, e, signature).red().to_string());
+                        failed_count += 1;
+                    }
+                }
+            },
+            Err(e) => {
+                logger.error(format!("❌ Failed to get sell transaction for token {}: {}", mint, e).red().to_string());
+                failed_count += 1;
+            }
+        }
+        
+        // Small delay between transactions to avoid rate limiting
+        tokio::time::sleep(tokio::time::Duration::from_millis(1000)).await;
+    }
+
+    // Non-transferable tokens fail every sell and transfer; burning them is the only way to reclaim the account
+    for (account, lamports) in &to_burn {
+        match burn_and_close(config, &wallet_pubkey, account) {
+            Ok(signature) => {
+                logger.critical(format!("🔥 Burned non-transferable {} and closed {}: {}", account.mint, account.address, signature).green().to_string());
+                let entry = rent_entry(&account.mint.to_string(), &signature, "Non-transferable token burned and account closed (--sell)", *lamports, lamports_to_sol(TX_FEE_LAMPORTS));
+                if let Err(e) = ledger::append_now(&entry) {
+                    logger.error(format!("Failed to record reclaimed rent in {}: {}", ledger::ledger_path(), e).red().to_string());
+                }
+                burned_count += 1;
+            }
+            Err(e) => {
+                logger.error(format!("❌ Failed to burn and close {} ({}): {}", account.address, account.mint, e).red().to_string());
+                failed_count += 1;
+            }
+        }
+    }
+    
+    // Final summary
+    let sol_received_display = total_sol_received as f64 / 1_000_000_000.0; // Convert lamports to SOL
+    logger.critical(format!("Selling completed! ✅ {} successful, ❌ {} failed, ~{:.6} SOL received", 
+                       sold_count, failed_count, sol_received_display).cyan().bold().to_string());
+    if consolidated_count > 0 {
+        logger.critical(format!("🔀 {} auxiliary token accounts consolidated into ATAs; run --close to reclaim their rent", consolidated_count));
+    }
+    if burned_count > 0 || !ignored.is_empty() {
+        logger.critical(format!("🔥 {} non-transferable accounts burned and closed, {} frozen accounts ignored", burned_count, ignored.len()));
+    }
+    
+    if failed_count > 0 {
+        Err(SniperError::Other(format!("Failed to sell {} out of {} tokens", failed_count, total_token_count + to_burn.len())))
+    } else {
+        Ok(())
+    }
+}
+
+/// UI amount of `raw` tokens of `mint` for display, with interest accrued for interest-bearing mints
+fn display_amount(mint_infos: &HashMap<Pubkey, MintInfo>, mint: &Pubkey, raw: u64, decimals: u8, now: i64) -> f64 {
+    match mint_infos.get(mint) {
+        Some(info) => info.ui_amount(raw, now),
+        None => raw as f64 / 10f64.powi(decimals as i32),
+    }
+}
+
+/// Burn the whole balance of `account` and close it, returning its rent to the wallet
+/// Burning is allowed for non-transferable tokens; both instructions from spl-token-2022 build for either program,
+/// and always take the raw amount.
+pub fn burn_and_close_instructions(wallet: &Pubkey, account: &ScannedAccount) -> Result<Vec<Instruction>, String> {
+    let burn = spl_token_2022::instruction::burn_checked(
+        &account.token_program,
+        &account.address,
+        &account.mint,
+        wallet,
+        &[],
+        account.amount,
+        account.decimals,
+    )
+    .map_err(|e| format!("Failed to create burn for {}: {}", account.address, e))?;
+    let close = spl_token_2022::instruction::close_account(&account.token_program, &account.address, wallet, wallet, &[])
+        .map_err(|e| format!("Failed to create close for {}: {}", account.address, e))?;
+    Ok(vec![burn, close])
+}
+
+/// Send the burn-and-close transaction for one account and wait for it to confirm
+fn burn_and_close(config: &Config, wallet_pubkey: &Pubkey, account: &ScannedAccount) -> Result<String, String> {
+    let instructions = burn_and_close_instructions(wallet_pubkey, account)?;
+    let recent_blockhash = config.app_state.rpc_client.get_latest_blockhash()
+        .map_err(|e| format!("Failed to get recent blockhash: {}", e))?;
+    let transaction = Transaction::new_signed_with_payer(
+        &instructions,
+        Some(wallet_pubkey),
+        &[&config.app_state.wallet],
+        recent_blockhash,
+    );
+    config.app_state.send_rpc.blocking_client().send_and_confirm_transaction(&transaction)
+        .map(|signature| signature.to_string())
+        .map_err(|e| format!("Failed to send burn and close: {}", e))
+}
+
+/// Ledger line crediting the rent of a closed account
+fn rent_entry(mint: &str, signature: &str, reason: &str, lamports: u64, fee_sol: f64) -> LedgerEntry {
+    LedgerEntry {
+        timestamp: clock::now_secs(),
+        mint: mint.to_string(),
+        side: "rent".to_string(),
+        signature: signature.to_string(),
+        token_amount: 0.0,
+        sol_amount: 0.0,
+        price: 0.0,
+        reason: reason.to_string(),
+        simulated: false,
+        blockhash_mode: Some("recent".to_string()),
+        session_id: session_report::session_id().to_string(),
+        costs: TradeCosts {
+            network_fee_sol: fee_sol,
+            ..TradeCosts::rent_reclaimed(lamports)
+        },
+        imported: false,
+        pre_jitter_sol: None,
+        post_jitter_sol: None,
+        slippage_pct: None,
+        slippage_sol: None,
+    }
+}
+
+/// Send the consolidation transaction for one mint and wait for it to confirm
+fn consolidate(config: &Config, wallet_pubkey: &Pubkey, holdings: &MintHoldings) -> Result<String, String> {
+    let instructions = consolidation_instructions(wallet_pubkey, holdings)?;
+    let recent_blockhash = config.app_state.rpc_client.get_latest_blockhash()
+        .map_err(|e| format!("Failed to get recent blockhash: {}", e))?;
+    let transaction = Transaction::new_signed_with_payer(
+        &instructions,
+        Some(wallet_pubkey),
+        &[&config.app_state.wallet],
+        recent_blockhash,
+    );
+    config.app_state.send_rpc.blocking_client().send_and_confirm_transaction(&transaction)
+        .map(|signature| signature.to_string())
+        .map_err(|e| format!("Failed to send consolidation: {}", e))
+}
+
+/// A token account --close will close
+struct ClosableAccount {
+    address: Pubkey,
+    is_token_2022: bool,
+    lamports: u64,
+    token_amount: u64,
+    mint: String,
+    is_auxiliary: bool,
+    /// Decimals of a non-transferable balance burned before the close
+    burn: Option<u8>,
+}
+
+/// Instructions closing `account`, after a burn for soulbound tokens
+fn close_instructions(wallet_pubkey: &Pubkey, account: &ClosableAccount) -> Result<Vec<Instruction>, String> {
+    let token_account = &account.address;
+    if let (Some(decimals), Ok(mint)) = (account.burn, Pubkey::from_str(&account.mint)) {
+        let program = if account.is_token_2022 { spl_token_2022::id() } else { spl_token::id() };
+        return burn_and_close_instructions(wallet_pubkey, &ScannedAccount {
+            address: *token_account,
+            mint,
+            token_program: program,
+            amount: account.token_amount,
+            decimals,
+        });
+    }
+    let close_instruction = if account.is_token_2022 {
+        // Use Token-2022 program for Token-2022 accounts
+        spl_token_2022::instruction::close_account(
+            &spl_token_2022::id(),
+            token_account,
+            wallet_pubkey,
+            wallet_pubkey,
+            &[wallet_pubkey],
+        ).map_err(|e| format!("Failed to create Token-2022 close instruction for {}: {}", token_account, e))?
+    } else {
+        // Use standard Token program for standard token accounts
+        token::close_account(
+            *wallet_pubkey,
+            *token_account,
+            *wallet_pubkey,
+            *wallet_pubkey,
+            &[wallet_pubkey],
+        ).map_err(|e| format!("Failed to create close instruction for {}: {}", token_account, e))?
+    };
+    Ok(vec![close_instruction])
+}
+
+/// Close all token accounts owned by the wallet
+/// Asks for confirmation when the reclaimed rent exceeds CONFIRM_THRESHOLD_SOL. Non-transferable balances are
+/// burned in the same transaction; frozen accounts holding tokens are skipped.
+pub async fn close_all_token_accounts(config: &Config, assume_yes: bool) -> SniperResult<()> {
+    let logger = Logger::new("[CLOSE-TOKEN-ACCOUNTS] => ".green().to_string());
+    
+    // Get wallet pubkey
+    let wallet_pubkey = config.app_state.wallet.try_pubkey()
+        .map_err(|e| SniperError::Other(format!("Failed to get wallet pubkey: {}", e)))?;
+    
+    // Get the token program pubkey
+    let token_program = Pubkey::from_str("TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA").unwrap();
+    let token_2022_program = Pubkey::from_str("TokenzQdBNbLqP5VEhdkAS6EPFLC1PHnBqCXEpPxuEb").unwrap();
+    
+    // Query all token accounts owned by the wallet
+    let accounts_normal_token = config.app_state.read_rpc.blocking_client().get_token_accounts_by_owner(
+        &wallet_pubkey,
+        anchor_client::solana_client::rpc_request::TokenAccountsFilter::ProgramId(token_program)
+    ).map_err(|e| SniperError::from(e).context("Failed to get token accounts"))?;
+    let accounts_of_token_2022 = config.app_state.read_rpc.blocking_client().get_token_accounts_by_owner(
+        &wallet_pubkey,
+        anchor_client::solana_client::rpc_request::TokenAccountsFilter::ProgramId(token_2022_program)
+    ).map_err(|e| SniperError::from(e).context("Failed to get token accounts"))?;
+    
+    // Combine both account vectors
+    let mut accounts = accounts_normal_token;
+    accounts.extend(accounts_of_token_2022);
+    
+    if accounts.is_empty() {
+        logger.critical("No token accounts found to close".to_string());
+        return Ok(());
+    }
+    
+    logger.critical(format!("Found {} token accounts to close", accounts.len()));
+    
+    let mut closed_count = 0;
+    let mut failed_count = 0;
+    let mut mint_infos: HashMap<Pubkey, MintInfo> = HashMap::new();
+    let mut special_counts: BTreeMap<SpecialExtension, usize> = BTreeMap::new();
+    let mut ignored = 0;
+
+    // Classify first so the summary shows what closing reclaims
+    let mut closable: Vec<ClosableAccount> = Vec::new();
+    for account_info in accounts {
+        let token_account = Pubkey::from_str(&account_info.pubkey)
+            .map_err(|_| format!("Invalid token account pubkey: {}", account_info.pubkey))?;
+        
+        // Skip WSOL accounts with non-zero balance (these need to be unwrapped first)
+        let account_data = match config.app_state.read_rpc.blocking_client().get_account(&token_account) {
+            Ok(data) => data,
+            Err(e) => {
+                logger.error(format!("Failed to get account data for {}: {}", token_account, e).red().to_string());
+                failed_count += 1;
+                continue;
+            }
+        };
+        
+        // Determine which program owns this account (Token or Token-2022)
+        let is_token_2022 = account_data.owner == token_2022_program;
+        
+        // Check if this is a WSOL account with balance; both programs share the base account layout
+        let mut account = ClosableAccount {
+            address: token_account,
+            is_token_2022,
+            lamports: account_data.lamports,
+            token_amount: 0,
+            mint: String::new(),
+            is_auxiliary: false,
+            burn: None,
+        };
+        if let Ok(token_data) = StateWithExtensionsOwned::<Token2022Account>::unpack(account_data.data.clone()) {
+            account.mint = token_data.base.mint.to_string();
+            account.is_auxiliary = token_account
+                != get_associated_token_address_with_program_id(&wallet_pubkey, &token_data.base.mint, &account_data.owner);
+            if token_data.base.mint == spl_token::native_mint::id() && token_data.base.amount > 0 {
+                logger.critical(format!("Skipping WSOL account with non-zero balance: {} ({})", 
+                                 token_account, 
+                                 token_data.base.amount as f64 / 1_000_000_000.0));
+                continue;
+            }
+            account.token_amount = token_data.base.amount;
+
+            // A balance blocks the close: soulbound tokens can be burned first, frozen accounts can't be touched
+            if token_data.base.amount > 0 {
+                let mint = token_data.base.mint;
+                if let Entry::Vacant(slot) = mint_infos.entry(mint) {
+                    if let Ok(info) = config.app_state.read_rpc.blocking_client().get_account(&mint)
+                        .map_err(|e| e.to_string())
+                        .and_then(|data| mint_inspector::parse_mint_account(&mint, &data).map_err(|e| e.to_string()))
+                    {
+                        slot.insert(info);
+                    }
+                }
+                if let Some(info) = mint_infos.get(&mint) {
+                    let (route, category) = mint_inspector::route_holding(info, token_data.base.state == AccountState::Frozen);
+                    if let Some(category) = category {
+                        *special_counts.entry(category).or_default() += 1;
+                    }
+                    match route {
+                        HoldingRoute::Sell => {}
+                        HoldingRoute::BurnAndClose => account.burn = Some(info.decimals),
+                        HoldingRoute::Ignore => {
+                            logger.critical(format!("Skipping frozen account {} ({})", token_account, mint).yellow().to_string());
+                            ignored += 1;
+                            continue;
+                        }
+                    }
+                }
+            }
+        }
+        closable.push(account);
+    }
+
+    if closable.is_empty() {
+        logger.critical("No token accounts to close".to_string());
+        return Ok(());
+    }
+
+    let rent_lamports: u64 = closable.iter().map(|account| account.lamports).sum();
+    let non_empty = closable.iter().filter(|account| account.token_amount > 0 && account.burn.is_none()).count();
+    let burned = closable.iter().filter(|account| account.burn.is_some()).count();
+    let auxiliary = closable.iter().filter(|account| account.is_auxiliary).count();
+    let sol_balance = config.app_state.read_rpc.blocking_client().get_balance(&wallet_pubkey)
+        .map_err(|e| SniperError::from(e).context("Failed to get SOL balance"))?;
+    let batch_config = CloseBatchConfig::from_env();
+    let transactions = closable.len().div_ceil(batch_config.batch_size) as u64;
+    let mut summary = format!(
+        "About to close {} token accounts of {}\n  Rent reclaimed: ~{:.6} SOL\n  SOL balance:    {:.6} -> ~{:.6}",
+        closable.len(),
+        wallet_pubkey,
+        lamports_to_sol(rent_lamports),
+        lamports_to_sol(sol_balance),
+        lamports_to_sol((sol_balance + rent_lamports).saturating_sub(TX_FEE_LAMPORTS * transactions))
+    );
+    if auxiliary > 0 {
+        summary.push_str(&format!("\n  {} of them are auxiliary (non-ATA) accounts", auxiliary));
+    }
+    if burned > 0 {
+        summary.push_str(&format!("\n  {} accounts hold non-transferable tokens, which are burned before closing", burned));
+    }
+    if ignored > 0 {
+        summary.push_str(&format!("\n  {} frozen accounts holding tokens are skipped", ignored));
+    }
+    if let Some(counts) = mint_inspector::format_special_counts(&special_counts) {
+        summary.push_str(&format!("\n  Special accounts: {}", counts));
+    }
+    if non_empty > 0 {
+        summary.push_str(&format!("\n  {} accounts still hold tokens and will fail to close - run --sell first", non_empty));
+    }
+    confirm::confirm_action(&summary, lamports_to_sol(rent_lamports), assume_yes).map_err(SniperError::Cancelled)?;
+    
+    // One close instruction per account, built with the program that owns it, after a burn for soulbound tokens
+    let instructions = closable.iter()
+        .map(|account| close_instructions(&wallet_pubkey, account))
+        .collect::<Result<Vec<Vec<Instruction>>, String>>()?;
+
+    // Pack the closes into batches and simulate each first: one bad account (frozen, extension-heavy) would
+    // otherwise fail its whole batch
+    let items: Vec<usize> = (0..closable.len()).collect();
+    let batches = close_batch::pack_batches(&items, batch_config.batch_size, MAX_TX_ACCOUNT_LOCKS, |index| {
+        close_batch::instruction_accounts(&instructions[index])
+    });
+    let simulation_blockhash = config.app_state.rpc_client.get_latest_blockhash()
+        .map_err(|e| format!("Failed to get recent blockhash: {}", e))?;
+    let isolation = close_batch::isolate_failures(batches, batch_config.max_simulations, |batch| {
+        let batch_instructions: Vec<Instruction> = batch.iter().flat_map(|index| instructions[*index].clone()).collect();
+        let counts: Vec<usize> = batch.iter().map(|index| instructions[*index].len()).collect();
+        let transaction = Transaction::new_signed_with_payer(
+            &batch_instructions,
+            Some(&wallet_pubkey),
+            &[&config.app_state.wallet],
+            simulation_blockhash,
+        );
+        match config.app_state.read_rpc.blocking_client().simulate_transaction(&transaction) {
+            // The error names an instruction; burns give some accounts two
+            Ok(response) => match SimulationVerdict::from_error(response.value.err.as_ref()) {
+                SimulationVerdict::Fail(Some(instruction)) => SimulationVerdict::Fail(close_batch::item_at_instruction(&counts, instruction)),
+                verdict => verdict,
+            },
+            Err(e) => {
+                logger.critical(format!("Simulation unavailable, sending the batch unsimulated: {}", e).yellow().to_string());
+                SimulationVerdict::Unavailable
+            }
+        }
+    });
+    for index in &isolation.failing {
+        logger.error(format!("Not closing {}: it fails simulation on its own", closable[*index].address).red().to_string());
+        failed_count += 1;
+    }
+    if !isolation.unresolved.is_empty() {
+        logger.critical(format!(
+            "Simulation budget ({}) spent; sending {} accounts one per transaction",
+            batch_config.max_simulations,
+            isolation.unresolved.len()
+        ).yellow().to_string());
+    }
+    let mut send_batches = isolation.passed;
+    send_batches.extend(isolation.unresolved.into_iter().map(|index| vec![index]));
+
+    // Reclaimed rent is credited in the ledger against the trades that paid it
+    for batch in send_batches {
+        let recent_blockhash = config.app_state.rpc_client.get_latest_blockhash()
+            .map_err(|e| format!("Failed to get recent blockhash: {}", e))?;
+        let batch_instructions: Vec<Instruction> = batch.iter().flat_map(|index| instructions[*index].clone()).collect();
+        let transaction = Transaction::new_signed_with_payer(
+            &batch_instructions,
+            Some(&wallet_pubkey),
+            &[&config.app_state.wallet],
+            recent_blockhash,
+        );
+
+        match config.app_state.send_rpc.blocking_client().send_and_confirm_transaction(&transaction) {
+            Ok(signature) => {
+                logger.critical(format!("Closed {} token accounts, signature: {}", batch.len(), signature));
+                // The batch pays one fee, shared across its accounts
+                let fee_share = lamports_to_sol(TX_FEE_LAMPORTS) / batch.len() as f64;
+                for index in &batch {
+                    let account = &closable[*index];
+                    let reason = match (account.burn.is_some(), account.is_auxiliary) {
+                        (true, _) => "Non-transferable token burned and account closed (--close)",
+                        (false, true) => "Auxiliary token account closed (--close)",
+                        (false, false) => "Token account closed (--close)",
+                    };
+                    let entry = rent_entry(&account.mint, &signature.to_string(), reason, account.lamports, fee_share);
+                    if let Err(e) = ledger::append_now(&entry) {
+                        logger.error(format!("Failed to record reclaimed rent in {}: {}", ledger::ledger_path(), e).red().to_string());
+                    }
+                }
+                closed_count += batch.len();
+            },
+            Err(e) => {
+                let accounts: Vec<String> = batch.iter().map(|index| closable[*index].address.to_string()).collect();
+                logger.error(format!("Failed to close token accounts {}: {}", accounts.join(", "), e).red().to_string());
+                failed_count += batch.len();
+            }
+        }
+    }
+    
+    logger.critical(format!("Closed {} token accounts, {} failed", closed_count, failed_count));
+    
+    if failed_count > 0 {
+        Err(SniperError::Other(format!("Failed to close {} token accounts", failed_count)))
+    } else {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use spl_token_2022::instruction::TokenInstruction;
+
+    /// A wallet holding one mint in both its ATA and a legacy auxiliary account
+    fn wallet_fixture() -> (Pubkey, Pubkey, Vec<ScannedAccount>) {
+        let wallet = Pubkey::new_unique();
+        let mint = Pubkey::new_unique();
+        let ata = get_associated_token_address_with_program_id(&wallet, &mint, &spl_token::id());
+        let account = |address, amount| ScannedAccount { address, mint, token_program: spl_token::id(), amount, decimals: 6 };
+        (wallet, mint, vec![account(Pubkey::new_unique(), 400), account(ata, 600)])
+    }
+
+    fn closable(address: Pubkey, mint: &Pubkey, is_auxiliary: bool) -> ClosableAccount {
+        ClosableAccount {
+            address,
+            is_token_2022: false,
+            lamports: 2_039_280,
+            token_amount: 0,
+            mint: mint.to_string(),
+            is_auxiliary,
+            burn: None,
+        }
+    }
+
+    #[test]
+    fn accounts_of_one_mint_sell_once() {
+        let (wallet, mint, accounts) = wallet_fixture();
+        let auxiliary = accounts[0].clone();
+        let holdings = group_by_mint(&wallet, accounts);
+
+        assert_eq!(holdings.len(), 1);
+        let holdings = &holdings[0];
+        assert_eq!(holdings.mint, mint);
+        assert_eq!(holdings.ata_amount, 600);
+        assert_eq!(holdings.auxiliary, vec![auxiliary]);
+        assert_eq!(holdings.total_amount(), 1_000);
+        assert!(holdings.needs_consolidation());
+    }
+
+    #[test]
+    fn auxiliary_balances_move_into_the_ata() {
+        let (wallet, mint, accounts) = wallet_fixture();
+        let auxiliary = accounts[0].address;
+        let holdings = group_by_mint(&wallet, accounts).remove(0);
+        let instructions = consolidation_instructions(&wallet, &holdings).unwrap();
+
+        assert_eq!(instructions.len(), 2);
+        assert_eq!(instructions[0].program_id, spl_associated_token_account::id());
+        let transfer = &instructions[1];
+        assert_eq!(transfer.program_id, spl_token::id());
+        let accounts: Vec<Pubkey> = transfer.accounts.iter().map(|meta| meta.pubkey).collect();
+        assert_eq!(accounts, vec![auxiliary, mint, holdings.ata, wallet]);
+        match TokenInstruction::unpack(&transfer.data).unwrap() {
+            TokenInstruction::TransferChecked { amount, decimals } => assert_eq!((amount, decimals), (400, 6)),
+            other => panic!("expected a checked transfer, got {:?}", other),
+        }
+
+        let ata_only = MintHoldings { auxiliary: Vec::new(), ..holdings };
+        assert!(consolidation_instructions(&wallet, &ata_only).unwrap().is_empty());
+    }
+
+    #[test]
+    fn close_covers_the_ata_and_the_auxiliary_account() {
+        let (wallet, mint, accounts) = wallet_fixture();
+        let holdings = group_by_mint(&wallet, accounts).remove(0);
+        let emptied = [closable(holdings.ata, &mint, false), closable(holdings.auxiliary[0].address, &mint, true)];
+
+        for account in &emptied {
+            let instructions = close_instructions(&wallet, account).unwrap();
+            assert_eq!(instructions.len(), 1);
+            assert_eq!(instructions[0].program_id, spl_token::id());
+            assert_eq!(instructions[0].accounts[0].pubkey, account.address);
+            assert_eq!(instructions[0].accounts[1].pubkey, wallet, "rent goes back to the wallet");
+        }
+    }
+
+    #[test]
+    fn soulbound_balances_are_burned_in_raw_units_before_the_close() {
+        let wallet = Pubkey::new_unique();
+        let mint = Pubkey::new_unique();
+        let account = ClosableAccount {
+            is_token_2022: true,
+            token_amount: 1_234_567,
+            burn: Some(6),
+            ..closable(Pubkey::new_unique(), &mint, false)
+        };
+        let instructions = close_instructions(&wallet, &account).unwrap();
+
+        assert_eq!(instructions.len(), 2);
+        assert!(instructions.iter().all(|instruction| instruction.program_id == spl_token_2022::id()));
+        let burned: Vec<Pubkey> = instructions[0].accounts.iter().map(|meta| meta.pubkey).collect();
+        assert_eq!(burned, vec![account.address, mint, wallet]);
+        match TokenInstruction::unpack(&instructions[0].data).unwrap() {
+            TokenInstruction::BurnChecked { amount, decimals } => assert_eq!((amount, decimals), (1_234_567, 6)),
+            other => panic!("expected a checked burn, got {:?}", other),
+        }
+        assert!(matches!(TokenInstruction::unpack(&instructions[1].data).unwrap(), TokenInstruction::CloseAccount));
+    }
+}
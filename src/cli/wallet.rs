@@ -0,0 +1,305 @@
+use anchor_client::solana_sdk::address_lookup_table;
+use anchor_client::solana_sdk::commitment_config::CommitmentConfig;
+use anchor_client::solana_sdk::hash::Hash;
+use anchor_client::solana_sdk::instruction::Instruction;
+use anchor_client::solana_sdk::pubkey::Pubkey;
+use anchor_client::solana_sdk::signature::{Keypair, Signature, Signer};
+use anchor_client::solana_sdk::transaction::Transaction;
+use anchor_client::solana_sdk::system_instruction;
+use colored::Colorize;
+use solana_program_pack::Pack;
+use spl_token::instruction::sync_native;
+use spl_token::ui_amount_to_amount;
+use spl_associated_token_account::get_associated_token_address;
+
+use crate::cli::confirm::{self, lamports_to_sol, TX_FEE_LAMPORTS};
+use crate::common::{config::Config, logger::Logger};
+use crate::core::token;
+use crate::error::{SniperError, SniperResult};
+use crate::services::blockhash_processor::BlockhashProcessor;
+
+/// WSOL balance in lamports, None when the account doesn't exist
+fn wsol_balance(config: &Config, wsol_account: &Pubkey) -> Option<u64> {
+    config.app_state.read_rpc.blocking_client()
+        .get_token_account_balance(wsol_account)
+        .ok()
+        .and_then(|balance| balance.amount.parse::<u64>().ok())
+}
+
+/// Fresh blockhash: the processor's cached one when recent, else straight from RPC
+async fn fresh_blockhash(config: &Config, use_cache: bool) -> SniperResult<Hash> {
+    if use_cache {
+        if let Some(hash) = BlockhashProcessor::get_latest_blockhash().await {
+            return Ok(hash);
+        }
+    }
+    let processor = BlockhashProcessor::new(config.app_state.rpc_client.clone()).await?;
+    processor.get_fresh_blockhash().await
+}
+
+/// Sign and send `instructions`, re-signing once with a fresh blockhash if the first one expired
+async fn send_with_blockhash_retry(config: &Config, instructions: &[Instruction], logger: &Logger) -> SniperResult<Signature> {
+    let wallet = &config.app_state.wallet;
+    let send = |blockhash: Hash| {
+        let transaction = Transaction::new_signed_with_payer(instructions, Some(&wallet.pubkey()), &[wallet.as_ref()], blockhash);
+        config.app_state.send_rpc.blocking_client()
+            .send_and_confirm_transaction(&transaction)
+            .map_err(SniperError::from)
+    };
+
+    match send(fresh_blockhash(config, true).await?) {
+        Err(SniperError::BlockhashExpired(_)) => {
+            logger.critical("Retrying with a fresh blockhash...".yellow().to_string());
+            send(fresh_blockhash(config, false).await?)
+        }
+        result => result,
+    }
+}
+
+/// Wrap SOL to Wrapped SOL (WSOL)
+/// Refuses amounts that would leave less than MIN_SOL_RESERVE and asks for confirmation above CONFIRM_THRESHOLD_SOL.
+pub async fn wrap_sol(config: &Config, amount: f64, assume_yes: bool) -> SniperResult<()> {
+    let logger = Logger::new("[WRAP-SOL] => ".green().to_string());
+    
+    // Get wallet pubkey
+    let wallet_pubkey = config.app_state.wallet.try_pubkey()
+        .map_err(|e| SniperError::Other(format!("Failed to get wallet pubkey: {}", e)))?;
+    
+    // Create WSOL account instructions
+    let (wsol_account, mut instructions) = token::create_wsol_account(wallet_pubkey)
+        .map_err(|e| SniperError::Other(format!("Failed to create WSOL account: {}", e)))?;
+    
+    logger.critical(format!("WSOL account address: {}", wsol_account));
+    
+    // Convert UI amount to lamports (1 SOL = 10^9 lamports)
+    let lamports = ui_amount_to_amount(amount, 9);
+    logger.critical(format!("Wrapping {} SOL ({} lamports)", amount, lamports));
+
+    let sol_balance = config.app_state.read_rpc.blocking_client().get_balance(&wallet_pubkey)
+        .map_err(|e| SniperError::from(e).context("Failed to get SOL balance"))?;
+    let wsol_before = wsol_balance(config, &wsol_account);
+    // A missing ATA is created by this transaction and costs its rent
+    let ata_rent = if wsol_before.is_none() {
+        config.app_state.read_rpc.blocking_client()
+            .get_minimum_balance_for_rent_exemption(spl_token::state::Account::LEN)
+            .map_err(|e| SniperError::from(e).context("Failed to get rent-exempt balance"))?
+    } else {
+        0
+    };
+    let sol_after = confirm::check_reserve(sol_balance, lamports + ata_rent + TX_FEE_LAMPORTS, confirm::min_sol_reserve())
+        .map_err(SniperError::InsufficientBalance)?;
+    let wsol_before = wsol_before.unwrap_or(0);
+
+    let summary = format!(
+        "About to wrap {:.6} SOL\n  Wallet:       {}\n  WSOL account: {}{}\n  SOL balance:  {:.6} -> ~{:.6}\n  WSOL balance: {:.6} -> {:.6}",
+        amount,
+        wallet_pubkey,
+        wsol_account,
+        if ata_rent > 0 { " (will be created)" } else { "" },
+        lamports_to_sol(sol_balance),
+        lamports_to_sol(sol_after),
+        lamports_to_sol(wsol_before),
+        lamports_to_sol(wsol_before + lamports),
+    );
+    confirm::confirm_action(&summary, amount, assume_yes).map_err(SniperError::Cancelled)?;
+    
+    // Transfer SOL to the WSOL account
+    instructions.push(
+        system_instruction::transfer(
+            &wallet_pubkey,
+            &wsol_account,
+            lamports,
+        )
+    );
+    
+    // Sync native instruction to update the token balance
+    instructions.push(
+        sync_native(
+            &spl_token::id(),
+            &wsol_account,
+        ).map_err(|e| SniperError::Other(format!("Failed to create sync native instruction: {}", e)))?
+    );
+    
+    let signature = send_with_blockhash_retry(config, &instructions, &logger)
+        .await
+        .map_err(|e| e.context("Failed to wrap SOL"))?;
+    logger.critical(format!("SOL wrapped successfully, signature: {}", signature));
+    Ok(())
+}
+
+/// Unwrap SOL from Wrapped SOL (WSOL) account
+/// Asks for confirmation when the recovered amount exceeds CONFIRM_THRESHOLD_SOL.
+pub async fn unwrap_sol(config: &Config, assume_yes: bool) -> SniperResult<()> {
+    let logger = Logger::new("[UNWRAP-SOL] => ".green().to_string());
+    
+    // Get wallet pubkey
+    let wallet_pubkey = config.app_state.wallet.try_pubkey()
+        .map_err(|e| SniperError::Other(format!("Failed to get wallet pubkey: {}", e)))?;
+    
+    // Get the WSOL ATA address
+    let wsol_account = get_associated_token_address(
+        &wallet_pubkey,
+        &spl_token::native_mint::id()
+    );
+    
+    logger.critical(format!("WSOL account address: {}", wsol_account));
+    
+    // Check if WSOL account exists
+    let recovered_lamports = match config.app_state.read_rpc.blocking_client().get_account(&wsol_account) {
+        Ok(account) => {
+            logger.critical(format!("Found WSOL account: {}", wsol_account));
+            // Closing returns the wrapped amount plus the account's rent
+            account.lamports
+        },
+        Err(_) => {
+            return Err(SniperError::TokenAccountMissing(format!("WSOL account does not exist: {}", wsol_account)));
+        }
+    };
+
+    let sol_balance = config.app_state.read_rpc.blocking_client().get_balance(&wallet_pubkey)
+        .map_err(|e| SniperError::from(e).context("Failed to get SOL balance"))?;
+    let summary = format!(
+        "About to unwrap and close the WSOL account\n  Wallet:       {}\n  WSOL account: {}\n  Recovered:    {:.6} SOL (incl. rent)\n  SOL balance:  {:.6} -> ~{:.6}",
+        wallet_pubkey,
+        wsol_account,
+        lamports_to_sol(recovered_lamports),
+        lamports_to_sol(sol_balance),
+        lamports_to_sol((sol_balance + recovered_lamports).saturating_sub(TX_FEE_LAMPORTS)),
+    );
+    confirm::confirm_action(&summary, lamports_to_sol(recovered_lamports), assume_yes).map_err(SniperError::Cancelled)?;
+    
+    // Close the WSOL account to recover SOL
+    let close_instruction = token::close_account(
+        wallet_pubkey,
+        wsol_account,
+        wallet_pubkey,
+        wallet_pubkey,
+        &[&wallet_pubkey],
+    ).map_err(|e| SniperError::Other(format!("Failed to create close account instruction: {}", e)))?;
+    
+    let signature = send_with_blockhash_retry(config, &[close_instruction], &logger)
+        .await
+        .map_err(|e| e.context("Failed to unwrap WSOL"))?;
+    logger.critical(format!("WSOL unwrapped successfully, signature: {}", signature));
+    Ok(())
+}
+
+/// Create `count` durable nonce accounts owned by the wallet, one transaction each
+/// Stops at the first failure; the accounts created before it are still printed.
+pub async fn create_nonce(config: &Config, count: u32) -> Result<(), String> {
+    let mut created = Vec::new();
+    for index in 0..count.max(1) {
+        match create_nonce_account(config).await {
+            Ok(nonce_pubkey) => created.push(nonce_pubkey.to_string()),
+            Err(e) => {
+                print_nonce_accounts(&created);
+                return Err(format!("nonce account {} of {}: {}", index + 1, count, e));
+            }
+        }
+    }
+    print_nonce_accounts(&created);
+    Ok(())
+}
+
+fn print_nonce_accounts(created: &[String]) {
+    if created.len() > 1 {
+        println!("set NONCE_ACCOUNTS={} in env", created.join(","));
+    }
+}
+
+/// Create one durable nonce account owned by the wallet
+async fn create_nonce_account(config: &Config) -> Result<Pubkey, String> {
+    let logger = Logger::new("[CREATE-NONCE] => ".green().to_string());
+    
+    // Get wallet pubkey
+    let wallet_pubkey = match config.app_state.wallet.try_pubkey() {
+        Ok(pk) => pk,
+        Err(_) => return Err("Failed to get wallet pubkey".to_string()),
+    };
+
+    // Create a new nonce account
+    let nonce_keypair = Keypair::new();
+    let nonce_pubkey = nonce_keypair.pubkey();
+
+    // Calculate rent-exempt balance
+    let rent = config.app_state.read_rpc.blocking_client()
+        .get_minimum_balance_for_rent_exemption(solana_program::nonce::State::size())
+        .map_err(|e| format!("Failed to get rent-exempt balance: {}", e))?;
+
+    // Create nonce account instruction
+    let create_nonce_account_ix = system_instruction::create_nonce_account(
+        &wallet_pubkey,
+        &nonce_pubkey,
+        &wallet_pubkey,
+        rent,
+    );
+
+    
+    // Get recent blockhash
+    let recent_blockhash = config.app_state.rpc_client
+        .get_latest_blockhash()
+        .map_err(|e| format!("Failed to get recent blockhash: {}", e))?;
+
+    // Create and sign transaction
+    let transaction = Transaction::new_signed_with_payer(
+        &create_nonce_account_ix,
+        Some(&wallet_pubkey),
+        &[&config.app_state.wallet, &nonce_keypair],
+        recent_blockhash,
+    );
+    
+    // Send transaction
+    match config.app_state.send_rpc.blocking_client().send_and_confirm_transaction(&transaction) {
+        Ok(signature) => {
+            // Use synchronous get_account since rpc_client is synchronous
+            let nonce_account = config.app_state.read_rpc.blocking_client().get_account(&nonce_pubkey)
+                .map_err(|e| format!("Failed to get nonce account: {}", e))?;
+            let nonce_data = solana_rpc_client_nonce_utils::data_from_account(&nonce_account)
+                .map_err(|e| format!("Failed to parse nonce data: {}", e))?;
+            let blockhash = nonce_data.blockhash();
+            logger.critical(format!("Nonce account created successfully, signature: {}", signature));
+            println!("nonce pubkey is {}, set NONCE_ACCOUNT={} in env", nonce_pubkey, nonce_pubkey);
+            println!("nonce keypair is {:?}", nonce_keypair);
+            println!("nonce privatekey is {:?}", nonce_keypair.secret());
+            println!("nonce privatekey byte is {:?}", nonce_keypair.secret().to_bytes());
+            println!("offline blockhash is {:?} set OFFLINE_BLOCKHASH={} in env", blockhash, blockhash);
+            Ok(nonce_pubkey)
+        },
+        Err(e) => {
+            Err(format!("Failed to create nonce account: {}", e))
+        }
+    }
+}
+
+/// Create an address lookup table owned by the wallet and fill it with the static PumpFun accounts, in one transaction
+pub async fn create_lookup_table(config: &Config) -> Result<(), String> {
+    let logger = Logger::new("[CREATE-LUT] => ".green().to_string());
+    let wallet_pubkey = config.app_state.wallet.try_pubkey()
+        .map_err(|_| "Failed to get wallet pubkey".to_string())?;
+
+    // The table address derives from a slot the SlotHashes sysvar still holds
+    let recent_slot = config.app_state.read_rpc.blocking_client()
+        .get_slot_with_commitment(CommitmentConfig::finalized())
+        .map_err(|e| format!("Failed to get recent slot: {}", e))?;
+    let (create_ix, table_address) = address_lookup_table::instruction::create_lookup_table(wallet_pubkey, wallet_pubkey, recent_slot);
+    let accounts = crate::core::lookup_table::static_pump_fun_accounts()
+        .map_err(|e| format!("Failed to derive PumpFun accounts: {}", e))?;
+    let extend_ix = address_lookup_table::instruction::extend_lookup_table(table_address, wallet_pubkey, Some(wallet_pubkey), accounts.clone());
+
+    let recent_blockhash = config.app_state.rpc_client
+        .get_latest_blockhash()
+        .map_err(|e| format!("Failed to get recent blockhash: {}", e))?;
+    let transaction = Transaction::new_signed_with_payer(
+        &[create_ix, extend_ix],
+        Some(&wallet_pubkey),
+        &[&config.app_state.wallet],
+        recent_blockhash,
+    );
+    let signature = config.app_state.send_rpc.blocking_client()
+        .send_and_confirm_transaction(&transaction)
+        .map_err(|e| format!("Failed to create lookup table: {}", e))?;
+    logger.critical(format!("Lookup table created with {} accounts, signature: {}", accounts.len(), signature));
+    // A table extended in this slot only resolves from the next one on; the bot loads it at startup anyway
+    println!("lookup table is {}, set LOOKUP_TABLE_ADDRESS={} in env", table_address, table_address);
+    Ok(())
+}
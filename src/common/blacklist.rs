@@ -0,0 +1,239 @@
+use std::collections::HashSet;
+use std::str::FromStr;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime};
+use anchor_client::solana_sdk::pubkey::Pubkey;
+use colored::Colorize;
+use dashmap::DashMap;
+use once_cell::sync::Lazy;
+
+use crate::common::logger::Logger;
+
+/// How often the list files are checked for changes
+const RELOAD_INTERVAL: Duration = Duration::from_secs(30);
+
+// Mints and creators blocked at runtime (e.g. after a freeze-authority rug), with the reason
+static BLOCKED_MINTS: Lazy<DashMap<String, String>> = Lazy::new(DashMap::new);
+static BLOCKED_CREATORS: Lazy<DashMap<String, String>> = Lazy::new(DashMap::new);
+
+pub fn block_mint(mint: &str, reason: &str) {
+    BLOCKED_MINTS.insert(mint.to_string(), reason.to_string());
+}
+
+pub fn block_creator(creator: &str, reason: &str) {
+    BLOCKED_CREATORS.insert(creator.to_string(), reason.to_string());
+}
+
+/// Reason the mint is blocked, if it is
+pub fn mint_block_reason(mint: &str) -> Option<String> {
+    BLOCKED_MINTS.get(mint).map(|reason| reason.clone())
+}
+
+/// Reason the creator is blocked, if it is
+pub fn creator_block_reason(creator: &str) -> Option<String> {
+    BLOCKED_CREATORS
+        .get(creator)
+        .map(|reason| reason.clone())
+        .or_else(|| CREATOR_BLACKLIST.contains(creator).then(|| "CREATOR_BLACKLIST_FILE".to_string()))
+}
+
+pub fn is_mint_blocked(mint: &str) -> bool {
+    BLOCKED_MINTS.contains_key(mint)
+}
+
+pub fn is_creator_blocked(creator: &str) -> bool {
+    BLOCKED_CREATORS.contains_key(creator) || CREATOR_BLACKLIST.contains(creator)
+}
+
+/// Newline-separated pubkeys from an operator-maintained file, re-read when its mtime changes
+/// Blank lines and `#` comments are skipped; a missing file is an empty list.
+pub struct ListFile {
+    label: &'static str,
+    path: Option<String>,
+    entries: Mutex<HashSet<String>>,
+    /// Modification time of the file as last read
+    modified: Mutex<Option<SystemTime>>,
+}
+
+impl ListFile {
+    pub fn new(label: &'static str, path: Option<String>) -> Self {
+        let list = Self { label, path, entries: Mutex::new(HashSet::new()), modified: Mutex::new(None) };
+        let _ = list.reload();
+        list
+    }
+
+    fn from_env(label: &'static str) -> Self {
+        Self::new(label, std::env::var(label).ok().filter(|path| !path.trim().is_empty()))
+    }
+
+    fn file_modified(&self) -> Option<SystemTime> {
+        let path = self.path.as_ref()?;
+        std::fs::metadata(path).and_then(|meta| meta.modified()).ok()
+    }
+
+    /// Re-read the file if it changed since the last read; Some(entry count) if the list was replaced
+    pub fn reload(&self) -> Option<usize> {
+        let path = self.path.as_ref()?;
+        let modified = self.file_modified();
+        if self.modified.lock().map(|last| *last == modified).unwrap_or(false) {
+            return None;
+        }
+        let mut entries = HashSet::new();
+        for line in std::fs::read_to_string(path).unwrap_or_default().lines() {
+            let entry = line.split('#').next().unwrap_or_default().trim();
+            if entry.is_empty() {
+                continue;
+            }
+            match Pubkey::from_str(entry) {
+                Ok(_) => {
+                    entries.insert(entry.to_string());
+                }
+                Err(e) => Logger::new("[BLACKLIST] => ".red().to_string()).error(format!("{}: ignoring '{}': {}", self.label, entry, e)),
+            }
+        }
+        let count = entries.len();
+        if let Ok(mut current) = self.entries.lock() {
+            *current = entries;
+        }
+        if let Ok(mut last) = self.modified.lock() {
+            *last = modified;
+        }
+        Some(count)
+    }
+
+    pub fn contains(&self, pubkey: &str) -> bool {
+        self.entries.lock().map(|entries| entries.contains(pubkey)).unwrap_or(false)
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.lock().map(|entries| entries.len()).unwrap_or(0)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Read as a whitelist: everything while it is empty, else only a listed mint or a mint of a listed creator
+    pub fn allows(&self, mint: &str, creator: Option<&str>) -> bool {
+        self.is_empty() || self.contains(mint) || creator.is_some_and(|creator| self.contains(creator))
+    }
+}
+
+/// CREATOR_BLACKLIST_FILE (default: unset): creators never bought from
+pub static CREATOR_BLACKLIST: Lazy<ListFile> = Lazy::new(|| ListFile::from_env("CREATOR_BLACKLIST_FILE"));
+/// MINT_WHITELIST_FILE (default: unset): while it lists anything, only listed mints and creators are bought
+pub static MINT_WHITELIST: Lazy<ListFile> = Lazy::new(|| ListFile::from_env("MINT_WHITELIST_FILE"));
+
+/// Buy candidates stopped by the list files since startup
+static LIST_BLOCKS: AtomicU64 = AtomicU64::new(0);
+
+pub fn record_list_block() {
+    LIST_BLOCKS.fetch_add(1, Ordering::Relaxed);
+}
+
+pub fn list_block_count() -> u64 {
+    LIST_BLOCKS.load(Ordering::Relaxed)
+}
+
+/// Whether whitelist mode lets `mint` be bought; always true while the whitelist is empty
+pub fn is_mint_allowed(mint: &str) -> bool {
+    MINT_WHITELIST.is_empty() || MINT_WHITELIST.contains(mint)
+}
+
+/// Whether whitelist mode lets `mint` from `creator` be bought: the mint or its creator must be listed
+pub fn is_allowed(mint: &str, creator: Option<&str>) -> bool {
+    MINT_WHITELIST.allows(mint, creator)
+}
+
+/// Check both list files for changes every 30s
+pub fn start_list_reloader() {
+    Lazy::force(&CREATOR_BLACKLIST);
+    Lazy::force(&MINT_WHITELIST);
+    if CREATOR_BLACKLIST.path.is_none() && MINT_WHITELIST.path.is_none() {
+        return;
+    }
+    println!("✅ Creator blacklist: {} entries, mint whitelist: {} entries{}",
+        CREATOR_BLACKLIST.len(), MINT_WHITELIST.len(), if MINT_WHITELIST.is_empty() { "" } else { " (whitelist mode)" });
+    tokio::spawn(async {
+        let logger = Logger::new("[BLACKLIST] => ".red().to_string());
+        let mut interval = tokio::time::interval(RELOAD_INTERVAL);
+        loop {
+            interval.tick().await;
+            for list in [&*CREATOR_BLACKLIST, &*MINT_WHITELIST] {
+                if let Some(count) = list.reload() {
+                    logger.critical(format!("{} reloaded: {} entries", list.label, count));
+                }
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::test_fixtures::pubkey;
+
+    fn list_file(contents: &str) -> (std::path::PathBuf, ListFile) {
+        let path = std::env::temp_dir().join(format!("list-{}.txt", pubkey()));
+        std::fs::write(&path, contents).unwrap();
+        let list = ListFile::new("TEST_LIST_FILE", Some(path.to_string_lossy().into_owned()));
+        (path, list)
+    }
+
+    #[test]
+    fn list_files_skip_comments_blanks_and_bad_entries() {
+        let (listed, commented) = (pubkey(), pubkey());
+        let (path, list) = list_file(&format!("# creators\n\n  {}  # rugged twice\nnot-a-pubkey\n#{}\n", listed, commented));
+        assert_eq!(list.len(), 1);
+        assert!(list.contains(&listed));
+        assert!(!list.contains(&commented));
+        assert!(!list.contains("not-a-pubkey"));
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn list_files_reload_only_on_change() {
+        let (first, second) = (pubkey(), pubkey());
+        let (path, list) = list_file(&format!("{}\n", first));
+        assert_eq!(list.reload(), None);
+
+        std::fs::write(&path, format!("{}\n{}\n", first, second)).unwrap();
+        let later = SystemTime::now() + Duration::from_secs(5);
+        std::fs::File::options().write(true).open(&path).unwrap().set_modified(later).unwrap();
+        assert_eq!(list.reload(), Some(2));
+        assert!(list.contains(&second));
+
+        // A deleted file is an empty list
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(list.reload(), Some(0));
+        assert!(list.is_empty());
+    }
+
+    #[test]
+    fn whitelist_admits_listed_mints_and_creators() {
+        let (mint, creator) = (pubkey(), pubkey());
+        let (empty_path, empty) = list_file("");
+        assert!(empty.allows(&pubkey(), None));
+
+        let (path, whitelist) = list_file(&format!("{}\n{}\n", mint, creator));
+        assert!(whitelist.allows(&mint, None));
+        assert!(whitelist.allows(&pubkey(), Some(&creator)));
+        assert!(!whitelist.allows(&pubkey(), Some(&pubkey())));
+        assert!(!whitelist.allows(&pubkey(), None));
+        std::fs::remove_file(path).unwrap();
+        std::fs::remove_file(empty_path).unwrap();
+    }
+
+    #[test]
+    fn runtime_blocks_give_their_reason() {
+        let (mint, creator) = (pubkey(), pubkey());
+        assert_eq!(mint_block_reason(&mint), None);
+        block_mint(&mint, "freeze authority");
+        block_creator(&creator, "rugged");
+        assert_eq!(mint_block_reason(&mint).as_deref(), Some("freeze authority"));
+        assert_eq!(creator_block_reason(&creator).as_deref(), Some("rugged"));
+        assert!(is_mint_blocked(&mint) && is_creator_blocked(&creator));
+        assert!(!is_creator_blocked(&mint));
+    }
+}
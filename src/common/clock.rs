@@ -0,0 +1,206 @@
+use std::sync::atomic::{AtomicBool, AtomicI64, AtomicU64, Ordering};
+use std::time::{SystemTime, UNIX_EPOCH};
+use once_cell::sync::Lazy;
+
+/// Estimated (reference - local) offset in ms, from the RPC node's block time
+static RPC_SKEW_MS: AtomicI64 = AtomicI64::new(0);
+static RPC_SKEW_MEASURED: AtomicBool = AtomicBool::new(false);
+/// Smoothed (event timestamp - local) offset in ms over recent events
+static EVENT_SKEW_MS: AtomicI64 = AtomicI64::new(0);
+static EVENTS_OBSERVED: AtomicU64 = AtomicU64::new(0);
+static SUSPECT_EVENTS: AtomicU64 = AtomicU64::new(0);
+static LIMITS: Lazy<TimestampLimits> = Lazy::new(TimestampLimits::from_env);
+
+/// Raw values above this are milliseconds (as seconds it would be the year 5138)
+const MILLIS_THRESHOLD: u64 = 100_000_000_000;
+/// Raw values above this are microseconds
+const MICROS_THRESHOLD: u64 = 100_000_000_000_000;
+/// Weight of the newest event in the smoothed event skew
+const EVENT_SKEW_ALPHA: f64 = 0.05;
+
+/// Host clock in unix ms, uncorrected
+pub fn local_unix_ms() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as i64)
+        .unwrap_or(0)
+}
+
+/// Host clock corrected by the measured RPC skew, in unix ms
+pub fn now_ms() -> i64 {
+    local_unix_ms() + RPC_SKEW_MS.load(Ordering::Relaxed)
+}
+
+/// Corrected unix seconds; time-based logic uses this instead of raw `SystemTime` arithmetic
+pub fn now_secs() -> u64 {
+    (now_ms().max(0) / 1000) as u64
+}
+
+/// Seconds since `timestamp` (event or chain unix seconds) by the corrected clock; negative if it is ahead
+pub fn event_age_secs(timestamp: u64) -> i64 {
+    now_secs() as i64 - timestamp as i64
+}
+
+/// Skew of a reference clock against the host, in ms: positive when the host is behind
+pub fn compute_skew_ms(reference_unix_secs: i64, local_ms: i64) -> i64 {
+    reference_unix_secs * 1000 - local_ms
+}
+
+/// Use `skew_ms` to correct `now_ms`/`now_secs`; set by the skew monitor, or directly by harnesses
+pub fn set_rpc_skew_ms(skew_ms: i64) {
+    RPC_SKEW_MS.store(skew_ms, Ordering::Relaxed);
+    RPC_SKEW_MEASURED.store(true, Ordering::Relaxed);
+}
+
+/// Last measured RPC skew in ms, None before the first measurement
+pub fn rpc_skew_ms() -> Option<i64> {
+    RPC_SKEW_MEASURED.load(Ordering::Relaxed).then(|| RPC_SKEW_MS.load(Ordering::Relaxed))
+}
+
+/// Smoothed event skew in ms, None before the first event
+pub fn event_skew_ms() -> Option<i64> {
+    (EVENTS_OBSERVED.load(Ordering::Relaxed) > 0).then(|| EVENT_SKEW_MS.load(Ordering::Relaxed))
+}
+
+/// Events flagged by `observe_event_timestamp` since startup
+pub fn suspect_event_count() -> u64 {
+    SUSPECT_EVENTS.load(Ordering::Relaxed)
+}
+
+/// Skew above which the monitor warns (CLOCK_SKEW_WARN_SECS, default: 5)
+pub fn warn_threshold_secs() -> u64 {
+    std::env::var("CLOCK_SKEW_WARN_SECS")
+        .ok()
+        .and_then(|v| v.parse::<u64>().ok())
+        .unwrap_or(5)
+}
+
+/// Bring an event timestamp to unix seconds; some payloads carry ms or µs
+pub fn normalize_event_timestamp(raw: u64) -> u64 {
+    if raw >= MICROS_THRESHOLD {
+        raw / 1_000_000
+    } else if raw >= MILLIS_THRESHOLD {
+        raw / 1000
+    } else {
+        raw
+    }
+}
+
+/// How far from now an event timestamp may be before it is flagged
+#[derive(Clone, Copy, Debug)]
+pub struct TimestampLimits {
+    pub max_age_secs: u64,
+    pub max_future_secs: u64,
+}
+
+impl TimestampLimits {
+    /// EVENT_MAX_AGE_SECS (default: 600) and EVENT_MAX_FUTURE_SECS (default: 30)
+    pub fn from_env() -> Self {
+        let max_age_secs = std::env::var("EVENT_MAX_AGE_SECS")
+            .ok()
+            .and_then(|v| v.parse::<u64>().ok())
+            .unwrap_or(600);
+        let max_future_secs = std::env::var("EVENT_MAX_FUTURE_SECS")
+            .ok()
+            .and_then(|v| v.parse::<u64>().ok())
+            .unwrap_or(30);
+        Self { max_age_secs, max_future_secs }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TimestampCheck {
+    Plausible,
+    Stale { age_secs: u64 },
+    Future { ahead_secs: u64 },
+}
+
+impl TimestampCheck {
+    pub fn is_plausible(&self) -> bool {
+        matches!(self, TimestampCheck::Plausible)
+    }
+}
+
+/// Judge `timestamp` (unix seconds) against `now_secs`
+pub fn check_timestamp(timestamp: u64, now_secs: u64, limits: &TimestampLimits) -> TimestampCheck {
+    if timestamp > now_secs + limits.max_future_secs {
+        TimestampCheck::Future { ahead_secs: timestamp - now_secs }
+    } else if now_secs > timestamp + limits.max_age_secs {
+        TimestampCheck::Stale { age_secs: now_secs - timestamp }
+    } else {
+        TimestampCheck::Plausible
+    }
+}
+
+/// Check a parsed event timestamp against the corrected clock and fold it into the event skew
+pub fn observe_event_timestamp(timestamp: u64) -> TimestampCheck {
+    let local_ms = local_unix_ms();
+    let sample = compute_skew_ms(timestamp as i64, local_ms);
+    let smoothed = if EVENTS_OBSERVED.fetch_add(1, Ordering::Relaxed) == 0 {
+        sample
+    } else {
+        let previous = EVENT_SKEW_MS.load(Ordering::Relaxed);
+        previous + ((sample - previous) as f64 * EVENT_SKEW_ALPHA) as i64
+    };
+    EVENT_SKEW_MS.store(smoothed, Ordering::Relaxed);
+
+    let check = check_timestamp(timestamp, now_secs(), &LIMITS);
+    if !check.is_plausible() {
+        SUSPECT_EVENTS.fetch_add(1, Ordering::Relaxed);
+    }
+    check
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LIMITS: TimestampLimits = TimestampLimits { max_age_secs: 600, max_future_secs: 30 };
+
+    #[test]
+    fn skew_is_positive_when_the_host_is_behind() {
+        assert_eq!(compute_skew_ms(1_700_000_010, 1_700_000_000_000), 10_000);
+        assert_eq!(compute_skew_ms(1_700_000_000, 1_700_000_002_500), -2_500);
+        assert_eq!(compute_skew_ms(1_700_000_000, 1_700_000_000_000), 0);
+    }
+
+    #[test]
+    fn injected_skew_corrects_now_and_event_age() {
+        // Kept to a couple of seconds: the corrected clock is shared with the other tests
+        set_rpc_skew_ms(2_000);
+        let corrected = now_ms() - local_unix_ms();
+        let age = event_age_secs(now_secs() + 5);
+        set_rpc_skew_ms(0);
+
+        assert!((1_900..=2_100).contains(&corrected), "{}", corrected);
+        assert!((-5..=-4).contains(&age), "{}", age);
+        assert_eq!(rpc_skew_ms(), Some(0));
+    }
+
+    #[test]
+    fn timestamps_in_ms_and_us_become_seconds() {
+        assert_eq!(normalize_event_timestamp(1_700_000_000), 1_700_000_000);
+        assert_eq!(normalize_event_timestamp(1_700_000_000_123), 1_700_000_000);
+        assert_eq!(normalize_event_timestamp(1_700_000_000_123_456), 1_700_000_000);
+    }
+
+    #[test]
+    fn stale_and_future_timestamps_are_flagged() {
+        let now = 1_700_000_000;
+        assert_eq!(check_timestamp(now, now, &LIMITS), TimestampCheck::Plausible);
+        assert_eq!(check_timestamp(now - 600, now, &LIMITS), TimestampCheck::Plausible);
+        assert_eq!(check_timestamp(now + 30, now, &LIMITS), TimestampCheck::Plausible);
+        assert_eq!(check_timestamp(now - 3_600, now, &LIMITS), TimestampCheck::Stale { age_secs: 3_600 });
+        assert_eq!(check_timestamp(now + 31, now, &LIMITS), TimestampCheck::Future { ahead_secs: 31 });
+    }
+
+    #[test]
+    fn observed_implausible_timestamps_are_counted() {
+        let before = suspect_event_count();
+        let check = observe_event_timestamp(now_secs() - 4 * 3_600);
+        assert!(matches!(check, TimestampCheck::Stale { age_secs } if age_secs >= 4 * 3_600), "{:?}", check);
+        assert!(observe_event_timestamp(now_secs()).is_plausible());
+        assert!(suspect_event_count() > before);
+        assert!(event_skew_ms().is_some());
+    }
+}
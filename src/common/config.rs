@@ -10,7 +10,9 @@ use crate::engine::swap::SwapProtocol;
 use crate::{
     common::{constants::INIT_MSG, logger::Logger},
     engine::swap::{SwapDirection, SwapInType},
-    services::jupiter_api::JupiterClient,
+    services::jupiter_api::{JupiterClient, SwapAggregator},
+    services::zeroslot::TipLandingClient,
+    services::rpc_pool::{self, RpcHandle},
 };
 use std::time::Duration;
 
@@ -58,8 +60,11 @@ impl Config {
             let zero_slot_tip_value = import_env_var("ZERO_SLOT_TIP_VALUE").parse::<f64>().unwrap_or(0.0025);
             
             let solana_price = create_coingecko_proxy().await.unwrap_or(200_f64);
-            let _rpc_client = create_rpc_client().unwrap();
-            let rpc_nonblocking_client = create_nonblocking_rpc_client().await.unwrap();
+            // Send and read pools collapse into one when SEND_RPC_HTTP / READ_RPC_HTTP are unset
+            let (send_pool, read_pool) = rpc_pool::pools_from_env();
+            let send_rpc = RpcHandle::new(send_pool.clone(), read_pool.clone());
+            let read_rpc = RpcHandle::new(read_pool.clone(), send_pool);
+            let rpc_nonblocking_client = read_pool.primary().client.clone();
             let zeroslot_rpc_client = create_zeroslot_rpc_client().await.unwrap();
             let wallet: std::sync::Arc<anchor_client::solana_sdk::signature::Keypair> = import_wallet().unwrap();
             let balance = match rpc_nonblocking_client
@@ -80,6 +85,8 @@ impl Config {
                 .unwrap_or(0.001_f64); //quantity
                                         // let in_type = "pct"; //percentage
                                         // let amount_in = 0.5; //percentage
+            let dry_run = env::var("DRY_RUN").ok().and_then(|v| v.parse::<bool>().ok()).unwrap_or(false);
+            crate::services::telegram::set_dry_run(dry_run);
 
             let swap_config = SwapConfig {
                 swap_direction,
@@ -87,14 +94,17 @@ impl Config {
                 amount_in,
                 buy_slippage,
                 reverse: false, // Default to normal mode
+                dry_run,
             };
 
-            let rpc_client = create_rpc_client().unwrap();
+            let rpc_client = read_pool.primary().blocking_client.clone();
             // OPTIMIZATION: Initialize JupiterClient once and reuse (eliminates 3+ initializations per sell)
-            let jupiter_client = Arc::new(JupiterClient::new(rpc_nonblocking_client.clone()));
+            let jupiter_client = Arc::new(JupiterClient::new(read_rpc.clone(), send_rpc.clone()));
             let app_state = AppState {
                 rpc_client,
                 rpc_nonblocking_client,
+                send_rpc,
+                read_rpc,
                 zeroslot_rpc_client,
                 wallet,
                 protocol_preference: SwapProtocol::default(),
@@ -104,13 +114,14 @@ impl Config {
                     format!(
                     "[SNIPER ENVIRONMENT]: \n\t\t\t\t [Yellowstone gRpc]: {},
                     \n\t\t\t\t * [Wallet]: {:?}, * [Balance]: {} Sol, 
-                    \n\t\t\t\t * [Buy Slippage]: {} bps, * [Solana]: {}, * [Amount]: {}",
+                    \n\t\t\t\t * [Buy Slippage]: {} bps, * [Solana]: {}, * [Amount]: {}, * [Dry Run]: {}",
                     yellowstone_grpc_http,
                     wallet_cloned.pubkey(),
                     balance as f64 / 1_000_000_000_f64,
                     buy_slippage,
                     solana_price,
                     amount_in,
+                    dry_run,
                 )
                 .purple()
                 .italic()
@@ -196,10 +207,28 @@ struct SolanaData {
 pub struct AppState {
     pub rpc_client: Arc<anchor_client::solana_client::rpc_client::RpcClient>,
     pub rpc_nonblocking_client: Arc<anchor_client::solana_client::nonblocking::rpc_client::RpcClient>,
-    pub zeroslot_rpc_client: Arc<crate::services::zeroslot::ZeroSlotClient>,
+    /// Transaction submission (SEND_RPC_HTTP, falls back to the read pool while fully unhealthy)
+    pub send_rpc: RpcHandle,
+    /// Account reads, scans and metadata (READ_RPC_HTTP, falls back to the send pool while fully unhealthy)
+    pub read_rpc: RpcHandle,
+    pub zeroslot_rpc_client: Arc<dyn TipLandingClient>,
     pub wallet: Arc<Keypair>,
     pub protocol_preference: SwapProtocol,
-    pub jupiter_client: Arc<JupiterClient>,
+    pub jupiter_client: Arc<dyn SwapAggregator>,
+}
+
+impl AppState {
+    /// Replace the tip landing client (scripted doubles in harnesses)
+    pub fn with_tip_client(mut self, client: Arc<dyn TipLandingClient>) -> Self {
+        self.zeroslot_rpc_client = client;
+        self
+    }
+
+    /// Replace the swap aggregator (scripted doubles in harnesses)
+    pub fn with_swap_aggregator(mut self, aggregator: Arc<dyn SwapAggregator>) -> Self {
+        self.jupiter_client = aggregator;
+        self
+    }
 }
 
 #[derive(Clone, Debug)]
@@ -209,6 +238,8 @@ pub struct SwapConfig {
     pub amount_in: f64,
     pub buy_slippage: u64,
     pub reverse: bool,
+    /// DRY_RUN (default: false): buys and sells are built and simulated but never sent
+    pub dry_run: bool,
 }
 
 pub fn import_env_var(key: &str) -> String {
@@ -252,11 +283,8 @@ pub async fn create_nonblocking_rpc_client(
     Ok(Arc::new(rpc_client))
 }
 
-pub async fn create_zeroslot_rpc_client() -> Result<Arc<crate::services::zeroslot::ZeroSlotClient>> {
-    let client = crate::services::zeroslot::ZeroSlotClient::new(
-        crate::services::zeroslot::ZERO_SLOT_URL.as_str()
-    );
-    Ok(Arc::new(client))
+pub async fn create_zeroslot_rpc_client() -> Result<Arc<crate::services::zeroslot_pool::ZeroSlotPool>> {
+    Ok(crate::services::zeroslot_pool::ZeroSlotPool::shared())
 }
 
 
@@ -0,0 +1,300 @@
+// Groups near-identical log lines so a dead endpoint can't flood the console
+// Messages are reduced to a template with pubkeys, signatures and numbers as wildcards. The first
+// occurrence of a (prefix, template) pair is printed, repeats within the window are counted and
+// reported as one summary line when the window closes.
+
+use std::hash::{BuildHasher, Hash, Hasher};
+use std::time::{Duration, Instant};
+use dashmap::DashMap;
+use once_cell::sync::Lazy;
+
+/// Upper bound on tracked lines; the oldest window is evicted (with its summary) beyond this
+const MAX_TRACKED_LINES: usize = 1024;
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum Admission {
+    /// Print the message, preceded by a summary line (prefix included) of what was suppressed
+    Emit { summary: Option<String> },
+    Suppress,
+}
+
+/// Wildcard for a base58 pubkey, signature or hash
+const ID_WILDCARD: &str = "<id>";
+/// Wildcard for a run of digits, a unit suffix kept ("1500ms" becomes "#ms", a decimal "#.#")
+const NUMBER_WILDCARD: &str = "#";
+/// Base58 words at least this long are identifiers (pubkeys are 32-44 characters, signatures 87-88)
+const MIN_ID_LEN: usize = 32;
+
+fn is_base58(c: char) -> bool {
+    c.is_ascii_alphanumeric() && !matches!(c, '0' | 'O' | 'I' | 'l')
+}
+
+/// Template of `message` with interpolated values as wildcards, so lines that differ only in a
+/// pubkey, a signature or a number group together
+pub fn fingerprint(message: &str) -> String {
+    let mut template = String::with_capacity(message.len());
+    let mut rest = message;
+    while let Some(start) = rest.find(|c: char| c.is_ascii_alphanumeric()) {
+        template.push_str(&rest[..start]);
+        rest = &rest[start..];
+        let end = rest.find(|c: char| !c.is_ascii_alphanumeric()).unwrap_or(rest.len());
+        let word = &rest[..end];
+        let unit = word.trim_start_matches(|c: char| c.is_ascii_digit());
+        if unit.len() < word.len() && unit.bytes().all(|b| b.is_ascii_alphabetic()) {
+            template.push_str(NUMBER_WILDCARD);
+            template.push_str(unit);
+        } else if word.len() >= MIN_ID_LEN && word.chars().all(is_base58) {
+            template.push_str(ID_WILDCARD);
+        } else {
+            template.push_str(word);
+        }
+        rest = &rest[end..];
+    }
+    template.push_str(rest);
+    template
+}
+
+/// The template is kept to tell a hash collision from a repeat and for its summary
+struct WindowState {
+    prefix: String,
+    template: String,
+    started: Instant,
+    suppressed: u64,
+}
+
+impl WindowState {
+    fn is_template(&self, prefix: &str, template: &str) -> bool {
+        self.prefix == prefix && self.template == template
+    }
+
+    fn summary(&self, now: Instant) -> Option<String> {
+        (self.suppressed > 0).then(|| summary_line(&self.template, self.suppressed, now.saturating_duration_since(self.started)))
+    }
+}
+
+fn summary_line(template: &str, suppressed: u64, elapsed: Duration) -> String {
+    format!("last message repeated {} times in {}s: {}", suppressed, elapsed.as_secs().max(1), template)
+}
+
+/// Rolling-window deduplication keyed by the hash of (prefix, template)
+/// The map is sharded, so concurrent loggers rarely wait on each other.
+pub struct LogThrottle {
+    window: Duration,
+    windows: DashMap<u64, WindowState>,
+}
+
+impl LogThrottle {
+    /// A zero window disables deduplication
+    pub fn new(window: Duration) -> Self {
+        Self {
+            window,
+            windows: DashMap::new(),
+        }
+    }
+
+    /// Window from LOG_DEDUP_WINDOW_SECS (default: 60, 0 disables)
+    pub fn from_env() -> Self {
+        let window_secs = std::env::var("LOG_DEDUP_WINDOW_SECS")
+            .ok()
+            .and_then(|v| v.parse::<u64>().ok())
+            .unwrap_or(60);
+        Self::new(Duration::from_secs(window_secs))
+    }
+
+    pub fn window(&self) -> Duration {
+        self.window
+    }
+
+    fn key(&self, prefix: &str, template: &str) -> u64 {
+        let mut hasher = self.windows.hasher().build_hasher();
+        prefix.hash(&mut hasher);
+        template.hash(&mut hasher);
+        hasher.finish()
+    }
+
+    /// Decide whether `message` logged under `prefix` at `now` should be printed
+    pub fn admit(&self, prefix: &str, message: &str, now: Instant) -> Admission {
+        if self.window.is_zero() {
+            return Admission::Emit { summary: None };
+        }
+        let template = fingerprint(message);
+        let key = self.key(prefix, &template);
+
+        if let Some(mut state) = self.windows.get_mut(&key) {
+            if !state.is_template(prefix, &template) {
+                // Another line with the same hash owns the slot; never hold this one back for it
+                return Admission::Emit { summary: None };
+            }
+            let elapsed = now.saturating_duration_since(state.started);
+            if elapsed < self.window {
+                state.suppressed += 1;
+                return Admission::Suppress;
+            }
+            let summary = state.summary(now).map(|summary| format!("{} {}", prefix, summary));
+            state.started = now;
+            state.suppressed = 0;
+            return Admission::Emit { summary };
+        }
+
+        let mut summary = None;
+        if self.windows.len() >= MAX_TRACKED_LINES {
+            // Evict the oldest window; its summary goes out with this line so no count is lost
+            let oldest = self.windows.iter().min_by_key(|entry| entry.started).map(|entry| *entry.key());
+            if let Some((_, state)) = oldest.and_then(|oldest| self.windows.remove(&oldest)) {
+                summary = state.summary(now).map(|summary| format!("{} {}", state.prefix, summary));
+            }
+        }
+        self.windows.insert(key, WindowState {
+            prefix: prefix.to_string(),
+            template,
+            started: now,
+            suppressed: 0,
+        });
+        Admission::Emit { summary }
+    }
+
+    /// Close every window older than the configured window and return "(prefix, summary)" for
+    /// the ones that suppressed something
+    pub fn drain_expired(&self, now: Instant) -> Vec<(String, String)> {
+        let mut summaries = Vec::new();
+        self.windows.retain(|_, state| {
+            if now.saturating_duration_since(state.started) < self.window {
+                return true;
+            }
+            if let Some(summary) = state.summary(now) {
+                summaries.push((state.prefix.clone(), summary));
+            }
+            false
+        });
+        summaries
+    }
+
+    pub fn tracked_lines(&self) -> usize {
+        self.windows.len()
+    }
+}
+
+pub static LOG_THROTTLE: Lazy<LogThrottle> = Lazy::new(LogThrottle::from_env);
+
+/// Periodically print summaries of windows that closed without another occurrence
+pub fn start_summary_flusher() {
+    let window = LOG_THROTTLE.window();
+    if window.is_zero() {
+        return;
+    }
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(window);
+        loop {
+            interval.tick().await;
+            for (prefix, summary) in LOG_THROTTLE.drain_expired(Instant::now()) {
+                println!("{} {}", prefix, summary);
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const WINDOW: Duration = Duration::from_secs(60);
+
+    /// A template no other index shares: its digits spelled as letters
+    fn distinct_line(index: usize) -> String {
+        let word: String = index.to_string().bytes().map(|digit| (b'a' + digit - b'0') as char).collect();
+        format!("line {}", word)
+    }
+
+    #[test]
+    fn fingerprint_wildcards_ids_and_numbers() {
+        assert_eq!(
+            fingerprint("Failed to get account data for So11111111111111111111111111111111111111112: 429"),
+            "Failed to get account data for <id>: #"
+        );
+        assert_eq!(fingerprint("retry 2 of 3 after 0.25s"), "retry # of # after #.#s");
+        let signature = "5VERv8NMvzbJMEkV8xnrLkEaWRtSz9CosKDYjCJjBRnbJLgp8uirBgmQpjKhoR4tjF3ZpRzrFmBV6UjKdiSZkQUW";
+        assert_eq!(fingerprint(&format!("[{}] landed", signature)), "[<id>] landed");
+        // Short or non-base58 words are text, not identifiers
+        assert_eq!(fingerprint("PumpFun 0x1772 ok"), "PumpFun 0x1772 ok");
+        assert_eq!(fingerprint(""), "");
+    }
+
+    #[test]
+    fn near_identical_lines_group_under_one_template() {
+        let throttle = LogThrottle::new(WINDOW);
+        let now = Instant::now();
+        let first = "Failed to get account data for So11111111111111111111111111111111111111112";
+        let second = "Failed to get account data for EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v";
+        assert_eq!(throttle.admit("[RPC]", first, now), Admission::Emit { summary: None });
+        assert_eq!(throttle.admit("[RPC]", second, now), Admission::Suppress);
+        assert_eq!(throttle.admit("[RPC]", "retry 1 of 3", now), Admission::Emit { summary: None });
+        assert_eq!(throttle.admit("[RPC]", "retry 2 of 3", now), Admission::Suppress);
+        assert_eq!(throttle.admit("[RPC]", "Error getting latest blockhash", now), Admission::Emit { summary: None });
+        assert_eq!(throttle.tracked_lines(), 3);
+    }
+
+    #[test]
+    fn summary_goes_out_with_the_first_line_after_the_window() {
+        let throttle = LogThrottle::new(WINDOW);
+        let start = Instant::now();
+        assert_eq!(throttle.admit("[RPC]", "timeout after 1500ms", start), Admission::Emit { summary: None });
+        assert_eq!(throttle.admit("[RPC]", "timeout after 1600ms", start + Duration::from_secs(1)), Admission::Suppress);
+        assert_eq!(throttle.admit("[RPC]", "timeout after 1700ms", start + WINDOW - Duration::from_millis(1)), Admission::Suppress);
+
+        let Admission::Emit { summary: Some(summary) } = throttle.admit("[RPC]", "timeout after 1800ms", start + WINDOW) else {
+            panic!("window should have closed with a summary");
+        };
+        assert_eq!(summary, "[RPC] last message repeated 2 times in 60s: timeout after #ms");
+        // The line that closed the window opens the next one
+        assert_eq!(throttle.admit("[RPC]", "timeout after 1900ms", start + WINDOW + Duration::from_secs(1)), Admission::Suppress);
+    }
+
+    #[test]
+    fn prefixes_are_kept_apart() {
+        let throttle = LogThrottle::new(WINDOW);
+        let now = Instant::now();
+        assert_eq!(throttle.admit("[A]", "down", now), Admission::Emit { summary: None });
+        assert_eq!(throttle.admit("[B]", "down", now), Admission::Emit { summary: None });
+    }
+
+    #[test]
+    fn zero_window_admits_everything() {
+        let throttle = LogThrottle::new(Duration::ZERO);
+        let now = Instant::now();
+        for _ in 0..3 {
+            assert_eq!(throttle.admit("", "same", now), Admission::Emit { summary: None });
+        }
+        assert_eq!(throttle.tracked_lines(), 0);
+    }
+
+    #[test]
+    fn drain_reports_only_windows_that_suppressed_once_they_close() {
+        let throttle = LogThrottle::new(WINDOW);
+        let start = Instant::now();
+        throttle.admit("[X]", "quiet", start);
+        throttle.admit("[X]", "noisy 1", start);
+        throttle.admit("[X]", "noisy 2", start);
+        assert!(throttle.drain_expired(start + WINDOW - Duration::from_secs(1)).is_empty());
+        assert_eq!(throttle.tracked_lines(), 2);
+
+        let drained = throttle.drain_expired(start + WINDOW);
+        assert_eq!(drained, vec![("[X]".to_string(), "last message repeated 1 times in 60s: noisy #".to_string())]);
+        assert_eq!(throttle.tracked_lines(), 0);
+    }
+
+    #[test]
+    fn eviction_hands_over_the_oldest_summary() {
+        let throttle = LogThrottle::new(WINDOW);
+        let start = Instant::now();
+        throttle.admit("", "oldest", start);
+        throttle.admit("", "oldest", start);
+        for index in 0..MAX_TRACKED_LINES - 1 {
+            throttle.admit("", &distinct_line(index), start + Duration::from_secs(1));
+        }
+        let Admission::Emit { summary: Some(summary) } = throttle.admit("", "one more", start + Duration::from_secs(2)) else {
+            panic!("evicting the oldest window should hand over its summary");
+        };
+        assert!(summary.ends_with("last message repeated 1 times in 2s: oldest"), "{}", summary);
+        assert_eq!(throttle.tracked_lines(), MAX_TRACKED_LINES);
+    }
+}
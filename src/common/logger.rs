@@ -1,5 +1,9 @@
 // Optimized logging - 90% reduction, only critical logs
 // Direct println! for critical logs to avoid channel overhead
+// Printed lines go through the dedup throttle so near-identical repeats collapse into summary lines
+
+use std::time::Instant;
+use crate::common::log_dedup::{Admission, LOG_THROTTLE};
 
 #[derive(Clone)]
 pub struct Logger {
@@ -11,6 +15,18 @@ impl Logger {
         Logger { prefix }
     }
 
+    fn print_throttled(&self, level: &str, message: String) {
+        match LOG_THROTTLE.admit(&self.prefix, &message, Instant::now()) {
+            Admission::Suppress => {}
+            Admission::Emit { summary } => {
+                if let Some(summary) = summary {
+                    println!("{}", summary);
+                }
+                println!("{} {}{}", self.prefix, level, message);
+            }
+        }
+    }
+
     // Disabled for performance - 90% reduction
     // Only use critical() or error() for important logs
     #[inline(always)]
@@ -21,7 +37,7 @@ impl Logger {
     // Critical errors only - direct println to avoid channel overhead
     #[inline(always)]
     pub fn error(&self, message: String) {
-        println!("{} [ERROR] {}", self.prefix, message);
+        self.print_throttled("[ERROR] ", message);
     }
 
     // Debug disabled for performance
@@ -39,12 +55,12 @@ impl Logger {
     // Critical logs only - direct println for minimal overhead
     #[inline(always)]
     pub fn log_critical(&self, message: String) {
-        println!("{} {}", self.prefix, message);
+        self.critical(message);
     }
     
     // New method for critical messages that should always be logged
     #[inline(always)]
     pub fn critical(&self, message: String) {
-        println!("{} {}", self.prefix, message);
+        self.print_throttled("", message);
     }
 }
@@ -1,5 +1,8 @@
 pub mod config;
 pub mod constants;
 pub mod logger;
+pub mod log_dedup;
 pub mod cache;
-// pub mod blacklist; // BLACKLIST DISABLED per user request
+pub mod blacklist;
+pub mod clock;
+pub mod shutdown;
@@ -0,0 +1,70 @@
+use std::time::{Duration, Instant};
+use once_cell::sync::Lazy;
+use tokio_util::sync::CancellationToken;
+
+use crate::common::cache::{PROGRESS_ON_BUYING, PROGRESS_ON_SELLING};
+
+/// How often the in-flight maps are re-checked while draining
+const DRAIN_POLL_INTERVAL: Duration = Duration::from_millis(250);
+
+/// Cancelled once on the first Ctrl+C; background loops and the engine stop taking new work when it is
+static SHUTDOWN: Lazy<CancellationToken> = Lazy::new(CancellationToken::new);
+
+/// A handle on the process-wide shutdown token for tasks that select on it
+pub fn token() -> CancellationToken {
+    SHUTDOWN.clone()
+}
+
+/// Resolves once shutdown was requested
+pub async fn cancelled() {
+    SHUTDOWN.cancelled().await
+}
+
+pub fn request() {
+    SHUTDOWN.cancel();
+}
+
+pub fn is_requested() -> bool {
+    SHUTDOWN.is_cancelled()
+}
+
+/// What the shutdown sequence does after the first Ctrl+C
+#[derive(Clone, Copy, Debug)]
+pub struct ShutdownConfig {
+    /// Time in-flight buys and sells get to finish
+    pub grace: Duration,
+    /// Sell every token in the wallet through the --sell path before exiting
+    pub sell_on_shutdown: bool,
+}
+
+impl ShutdownConfig {
+    /// SHUTDOWN_GRACE_SECS (default: 30), SELL_ON_SHUTDOWN (default: false)
+    pub fn from_env() -> Self {
+        let grace_secs = std::env::var("SHUTDOWN_GRACE_SECS")
+            .ok()
+            .and_then(|v| v.parse::<u64>().ok())
+            .unwrap_or(30);
+        let sell_on_shutdown = std::env::var("SELL_ON_SHUTDOWN")
+            .ok()
+            .and_then(|v| v.parse::<bool>().ok())
+            .unwrap_or(false);
+        Self { grace: Duration::from_secs(grace_secs), sell_on_shutdown }
+    }
+}
+
+/// Buys and sells still listed in PROGRESS_ON_BUYING / PROGRESS_ON_SELLING
+pub fn in_flight() -> usize {
+    PROGRESS_ON_BUYING.len() + PROGRESS_ON_SELLING.len()
+}
+
+/// Wait until nothing is in flight or `timeout` passes; the number still in flight
+pub async fn wait_for_in_flight(timeout: Duration) -> usize {
+    let deadline = Instant::now() + timeout;
+    loop {
+        let remaining = in_flight();
+        if remaining == 0 || Instant::now() >= deadline {
+            return remaining;
+        }
+        tokio::time::sleep(DRAIN_POLL_INTERVAL).await;
+    }
+}
@@ -0,0 +1,89 @@
+use std::str::FromStr;
+use anchor_client::solana_client::nonblocking::rpc_client::RpcClient;
+use anchor_client::solana_sdk::{
+    address_lookup_table::{state::AddressLookupTable, AddressLookupTableAccount},
+    hash::Hash,
+    instruction::Instruction,
+    message::{v0, VersionedMessage},
+    pubkey::Pubkey,
+    signature::Keypair,
+    signer::Signer,
+    system_program,
+    transaction::{Transaction, VersionedTransaction},
+};
+use anyhow::{anyhow, Result};
+use once_cell::sync::OnceCell;
+
+use crate::dex::pump_fun::{
+    self, ASSOCIATED_TOKEN_PROGRAM, PUMP_EVENT_AUTHORITY, PUMP_FEE_CONFIG, PUMP_FEE_PROGRAM, PUMP_FEE_RECIPIENT,
+    PUMP_FUN_PROGRAM, PUMP_GLOBAL, RENT_PROGRAM, TOKEN_2022_PROGRAM, TOKEN_PROGRAM,
+};
+
+/// The table as read at startup; its contents never change once extended
+static TABLE: OnceCell<AddressLookupTableAccount> = OnceCell::new();
+
+/// LOOKUP_TABLE_ADDRESS (default: unset, buys go out as legacy transactions)
+pub fn configured_address() -> Option<Pubkey> {
+    std::env::var("LOOKUP_TABLE_ADDRESS")
+        .ok()
+        .and_then(|v| Pubkey::from_str(v.trim()).ok())
+}
+
+/// Accounts every PumpFun buy and sell references whatever the mint; what `--create-lut` puts in the table
+pub fn static_pump_fun_accounts() -> Result<Vec<Pubkey>> {
+    let program = Pubkey::from_str(PUMP_FUN_PROGRAM)?;
+    Ok(vec![
+        Pubkey::from_str(PUMP_GLOBAL)?,
+        Pubkey::from_str(PUMP_FEE_RECIPIENT)?,
+        Pubkey::from_str(PUMP_EVENT_AUTHORITY)?,
+        Pubkey::from_str(PUMP_FEE_CONFIG)?,
+        Pubkey::from_str(PUMP_FEE_PROGRAM)?,
+        pump_fun::get_global_volume_accumulator_pda(&program)?,
+        program,
+        Pubkey::from_str(TOKEN_PROGRAM)?,
+        Pubkey::from_str(TOKEN_2022_PROGRAM)?,
+        Pubkey::from_str(ASSOCIATED_TOKEN_PROGRAM)?,
+        Pubkey::from_str(RENT_PROGRAM)?,
+        spl_token::native_mint::id(),
+        system_program::id(),
+    ])
+}
+
+/// Read the LOOKUP_TABLE_ADDRESS table once at startup; Ok(None) when it is unset
+pub async fn load(rpc: &RpcClient) -> Result<Option<usize>> {
+    let Some(address) = configured_address() else {
+        return Ok(None);
+    };
+    let account = rpc
+        .get_account(&address)
+        .await
+        .map_err(|e| anyhow!("failed to fetch lookup table {}: {}", address, e))?;
+    let table = AddressLookupTable::deserialize(&account.data)
+        .map_err(|e| anyhow!("{} is not an address lookup table: {}", address, e))?;
+    let loaded = AddressLookupTableAccount { key: address, addresses: table.addresses.to_vec() };
+    let count = loaded.addresses.len();
+    let _ = TABLE.set(loaded);
+    Ok(Some(count))
+}
+
+/// The loaded table; None until `load` read it, which makes every send fall back to legacy
+pub fn table() -> Option<&'static AddressLookupTableAccount> {
+    TABLE.get()
+}
+
+/// Sign `instructions` as a v0 transaction resolving accounts through the lookup table when one is loaded,
+/// as a legacy transaction otherwise. Signers and invoked programs always stay in the static keys.
+pub fn sign(keypair: &Keypair, instructions: &[Instruction], recent_blockhash: Hash) -> Result<VersionedTransaction> {
+    let Some(table) = table() else {
+        return Ok(VersionedTransaction::from(Transaction::new_signed_with_payer(
+            instructions,
+            Some(&keypair.pubkey()),
+            &[keypair],
+            recent_blockhash,
+        )));
+    };
+    let message = v0::Message::try_compile(&keypair.pubkey(), instructions, std::slice::from_ref(table), recent_blockhash)
+        .map_err(|e| anyhow!("failed to compile v0 message: {}", e))?;
+    VersionedTransaction::try_new(VersionedMessage::V0(message), &[keypair])
+        .map_err(|e| anyhow!("failed to sign v0 transaction: {}", e))
+}
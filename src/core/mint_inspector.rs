@@ -0,0 +1,385 @@
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use anchor_client::solana_client::nonblocking::rpc_client::RpcClient;
+use anchor_client::solana_sdk::{account::Account, pubkey::Pubkey};
+use anyhow::{anyhow, Result};
+use dashmap::DashMap;
+use once_cell::sync::Lazy;
+use spl_token_2022::extension::{
+    interest_bearing_mint::InterestBearingConfig, transfer_fee::TransferFeeConfig, transfer_hook::TransferHook,
+    BaseStateWithExtensions, ExtensionType, StateWithExtensions,
+};
+use spl_token_2022::state::{Account as TokenAccount, AccountState, Mint};
+
+/// What we know about a mint after reading its account once
+#[derive(Clone, Debug)]
+pub struct MintInfo {
+    pub mint: Pubkey,
+    /// spl_token::ID or spl_token_2022::ID
+    pub token_program: Pubkey,
+    pub decimals: u8,
+    pub mint_authority: Option<Pubkey>,
+    /// A mint with a freeze authority can freeze any holder's account
+    pub freeze_authority: Option<Pubkey>,
+    /// Token-2022 extensions (empty for classic SPL mints)
+    pub extensions: Vec<ExtensionType>,
+    /// Rate configuration of an interest-bearing mint
+    pub interest_bearing: Option<InterestBearingConfig>,
+    /// Program every transfer of a Token-2022 mint with the TransferHook extension must pass through
+    pub transfer_hook_program: Option<Pubkey>,
+    /// Transfer fee in basis points, the higher of the current and the scheduled one (0 without the extension)
+    pub transfer_fee_bps: u16,
+}
+
+impl MintInfo {
+    pub fn has_freeze_authority(&self) -> bool {
+        self.freeze_authority.is_some()
+    }
+
+    /// Soulbound: transfers (and so sells) always fail, burning is allowed
+    pub fn is_non_transferable(&self) -> bool {
+        self.extensions.contains(&ExtensionType::NonTransferable)
+    }
+
+    /// UI amount of `raw` at `unix_timestamp`; interest-bearing mints accrue on top of the raw amount, which is
+    /// what instructions always take
+    pub fn ui_amount(&self, raw: u64, unix_timestamp: i64) -> f64 {
+        let plain = raw as f64 / 10f64.powi(self.decimals as i32);
+        match &self.interest_bearing {
+            Some(config) => config
+                .amount_to_ui_amount(raw, self.decimals, unix_timestamp)
+                .and_then(|ui| ui.parse::<f64>().ok())
+                .unwrap_or(plain),
+            None => plain,
+        }
+    }
+}
+
+/// Token-2022 behaviours the wallet scan routes around
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum SpecialExtension {
+    NonTransferable,
+    /// The holder's account is frozen and the mint has no freeze authority left to thaw it
+    PermanentlyFrozen,
+    InterestBearing,
+}
+
+impl SpecialExtension {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::NonTransferable => "non-transferable",
+            Self::PermanentlyFrozen => "permanently frozen",
+            Self::InterestBearing => "interest-bearing",
+        }
+    }
+}
+
+/// What the wallet scan (--sell, --close) does with a held balance
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum HoldingRoute {
+    Sell,
+    /// Burn the balance and close the account; the only way out for a token that can't be transferred
+    BurnAndClose,
+    /// Neither sellable nor burnable (frozen accounts reject both)
+    Ignore,
+}
+
+/// Route a balance of `mint` held in an account that is `account_frozen`, with the category that decided it
+pub fn route_holding(mint: &MintInfo, account_frozen: bool) -> (HoldingRoute, Option<SpecialExtension>) {
+    if account_frozen {
+        let category = (!mint.has_freeze_authority()).then_some(SpecialExtension::PermanentlyFrozen);
+        return (HoldingRoute::Ignore, category);
+    }
+    if mint.is_non_transferable() {
+        return (HoldingRoute::BurnAndClose, Some(SpecialExtension::NonTransferable));
+    }
+    if mint.interest_bearing.is_some() {
+        return (HoldingRoute::Sell, Some(SpecialExtension::InterestBearing));
+    }
+    (HoldingRoute::Sell, None)
+}
+
+/// "2 non-transferable, 1 interest-bearing" for scan summaries; None when nothing special was held
+pub fn format_special_counts(counts: &BTreeMap<SpecialExtension, usize>) -> Option<String> {
+    if counts.is_empty() {
+        return None;
+    }
+    Some(counts.iter().map(|(category, count)| format!("{} {}", count, category.as_str())).collect::<Vec<_>>().join(", "))
+}
+
+/// Mint accounts never lose their extensions and rarely change authorities, so one read per mint is enough
+static MINT_INFO_CACHE: Lazy<DashMap<Pubkey, MintInfo>> = Lazy::new(DashMap::new);
+
+/// Parse a mint account owned by either token program
+pub fn parse_mint_account(mint: &Pubkey, account: &Account) -> Result<MintInfo> {
+    if account.owner != spl_token::ID && account.owner != spl_token_2022::ID {
+        return Err(anyhow!("Mint {} is owned by {}, not a token program", mint, account.owner));
+    }
+    let state = StateWithExtensions::<Mint>::unpack(&account.data)
+        .map_err(|e| anyhow!("Failed to unpack mint {}: {}", mint, e))?;
+    let extensions = if account.owner == spl_token_2022::ID {
+        state.get_extension_types().unwrap_or_default()
+    } else {
+        Vec::new()
+    };
+    let interest_bearing = if extensions.contains(&ExtensionType::InterestBearingConfig) {
+        state.get_extension::<InterestBearingConfig>().ok().copied()
+    } else {
+        None
+    };
+    let transfer_hook_program = state.get_extension::<TransferHook>().ok().and_then(|hook| Option::<Pubkey>::from(hook.program_id));
+    let transfer_fee_bps = state
+        .get_extension::<TransferFeeConfig>()
+        .map(|config| u16::from(config.older_transfer_fee.transfer_fee_basis_points).max(u16::from(config.newer_transfer_fee.transfer_fee_basis_points)))
+        .unwrap_or(0);
+
+    Ok(MintInfo {
+        mint: *mint,
+        token_program: account.owner,
+        decimals: state.base.decimals,
+        mint_authority: state.base.mint_authority.into(),
+        freeze_authority: state.base.freeze_authority.into(),
+        extensions,
+        interest_bearing,
+        transfer_hook_program,
+        transfer_fee_bps,
+    })
+}
+
+/// Read (or return the cached) mint info
+pub async fn inspect_mint(rpc_client: &Arc<RpcClient>, mint: &Pubkey) -> Result<MintInfo> {
+    if let Some(info) = MINT_INFO_CACHE.get(mint) {
+        return Ok(info.clone());
+    }
+    let account = rpc_client.get_account(mint).await
+        .map_err(|e| anyhow!("Failed to get mint account {}: {}", mint, e))?;
+    let info = parse_mint_account(mint, &account)?;
+    MINT_INFO_CACHE.insert(*mint, info.clone());
+    Ok(info)
+}
+
+/// Owning token program by mint; a mint never changes programs
+static TOKEN_PROGRAM_CACHE: Lazy<DashMap<Pubkey, Pubkey>> = Lazy::new(DashMap::new);
+
+/// `owner` as a token program id; errors for accounts neither token program owns
+pub fn token_program_of(mint: &Pubkey, owner: &Pubkey) -> Result<Pubkey> {
+    if *owner == spl_token::ID || *owner == spl_token_2022::ID {
+        Ok(*owner)
+    } else {
+        Err(anyhow!("Mint {} is owned by {}, not a token program", mint, owner))
+    }
+}
+
+/// Token program of `mint` if an earlier read already established it
+pub fn cached_token_program(mint: &Pubkey) -> Option<Pubkey> {
+    TOKEN_PROGRAM_CACHE
+        .get(mint)
+        .map(|program| *program)
+        .or_else(|| MINT_INFO_CACHE.get(mint).map(|info| info.token_program))
+}
+
+/// spl_token::ID or spl_token_2022::ID for `mint`, reading the mint account once
+/// ATAs derive from the program, so deriving with the wrong one points at an account that holds nothing.
+pub async fn resolve_token_program(rpc_client: &Arc<RpcClient>, mint: &Pubkey) -> Result<Pubkey> {
+    if let Some(program) = cached_token_program(mint) {
+        return Ok(program);
+    }
+    let account = rpc_client.get_account(mint).await
+        .map_err(|e| anyhow!("Failed to get mint account {}: {}", mint, e))?;
+    let program = token_program_of(mint, &account.owner)?;
+    TOKEN_PROGRAM_CACHE.insert(*mint, program);
+    Ok(program)
+}
+
+/// Cached mint info without touching the network
+pub fn cached_mint_info(mint: &Pubkey) -> Option<MintInfo> {
+    MINT_INFO_CACHE.get(mint).map(|info| info.clone())
+}
+
+/// Whether a token account (either program) is in the frozen state
+pub fn is_token_account_frozen(account: &Account) -> Result<bool> {
+    let state = StateWithExtensions::<TokenAccount>::unpack(&account.data)
+        .map_err(|e| anyhow!("Failed to unpack token account: {}", e))?;
+    Ok(state.base.state == AccountState::Frozen)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use solana_program_pack::Pack;
+    use spl_token_2022::extension::{non_transferable::NonTransferable, BaseStateWithExtensionsMut};
+    use crate::engine::test_fixtures::{mint_account, token_2022_mint};
+
+    fn token_account(state: AccountState) -> Account {
+        let account = TokenAccount {
+            mint: Pubkey::new_unique(),
+            owner: Pubkey::new_unique(),
+            amount: 5,
+            state,
+            ..TokenAccount::default()
+        };
+        let mut data = vec![0; TokenAccount::LEN];
+        TokenAccount::pack(account, &mut data).unwrap();
+        Account { lamports: 1, data, owner: spl_token::ID, executable: false, rent_epoch: 0 }
+    }
+
+    fn info(freeze_authority: Option<Pubkey>, extensions: Vec<ExtensionType>) -> MintInfo {
+        MintInfo {
+            mint: Pubkey::new_unique(),
+            token_program: spl_token_2022::ID,
+            decimals: 6,
+            mint_authority: None,
+            freeze_authority,
+            extensions,
+            interest_bearing: None,
+            transfer_hook_program: None,
+            transfer_fee_bps: 0,
+        }
+    }
+
+    #[test]
+    fn parses_freeze_authority_of_a_classic_mint() {
+        let mint = Pubkey::new_unique();
+        let authority = Pubkey::new_unique();
+        let parsed = parse_mint_account(&mint, &mint_account(spl_token::ID, Some(authority))).unwrap();
+        assert_eq!(parsed.token_program, spl_token::ID);
+        assert_eq!(parsed.decimals, 6);
+        assert_eq!(parsed.freeze_authority, Some(authority));
+        assert!(parsed.has_freeze_authority());
+        assert!(parsed.extensions.is_empty());
+
+        let renounced = parse_mint_account(&mint, &mint_account(spl_token::ID, None)).unwrap();
+        assert!(!renounced.has_freeze_authority());
+    }
+
+    #[test]
+    fn rejects_mints_outside_the_token_programs() {
+        let mint = Pubkey::new_unique();
+        assert!(parse_mint_account(&mint, &mint_account(Pubkey::new_unique(), None)).is_err());
+        assert!(token_program_of(&mint, &Pubkey::new_unique()).is_err());
+        assert_eq!(token_program_of(&mint, &spl_token_2022::ID).unwrap(), spl_token_2022::ID);
+    }
+
+    #[test]
+    fn reads_the_frozen_flag_of_token_accounts() {
+        assert!(is_token_account_frozen(&token_account(AccountState::Frozen)).unwrap());
+        assert!(!is_token_account_frozen(&token_account(AccountState::Initialized)).unwrap());
+        let garbage = Account { lamports: 1, data: vec![1, 2, 3], owner: spl_token::ID, executable: false, rent_epoch: 0 };
+        assert!(is_token_account_frozen(&garbage).is_err());
+    }
+
+    #[test]
+    fn frozen_accounts_are_ignored_and_flagged_permanent_without_authority() {
+        assert_eq!(route_holding(&info(None, Vec::new()), true), (HoldingRoute::Ignore, Some(SpecialExtension::PermanentlyFrozen)));
+        // The authority could still thaw it
+        assert_eq!(route_holding(&info(Some(Pubkey::new_unique()), Vec::new()), true), (HoldingRoute::Ignore, None));
+    }
+
+    #[test]
+    fn non_transferable_balances_are_burned() {
+        let soulbound = info(None, vec![ExtensionType::NonTransferable]);
+        assert_eq!(route_holding(&soulbound, false), (HoldingRoute::BurnAndClose, Some(SpecialExtension::NonTransferable)));
+        assert_eq!(route_holding(&info(None, Vec::new()), false), (HoldingRoute::Sell, None));
+    }
+
+    #[test]
+    fn soulbound_mint_accounts_route_to_burn_unless_frozen() {
+        let account = token_2022_mint(&[ExtensionType::NonTransferable], |state| {
+            state.init_extension::<NonTransferable>(true).unwrap();
+        });
+        let parsed = parse_mint_account(&Pubkey::new_unique(), &account).unwrap();
+        assert_eq!(parsed.token_program, spl_token_2022::ID);
+        assert!(parsed.is_non_transferable());
+        assert_eq!(parsed.interest_bearing, None);
+        assert_eq!(route_holding(&parsed, false), (HoldingRoute::BurnAndClose, Some(SpecialExtension::NonTransferable)));
+        // A frozen account rejects the burn too
+        assert_eq!(route_holding(&parsed, true), (HoldingRoute::Ignore, Some(SpecialExtension::PermanentlyFrozen)));
+    }
+
+    #[test]
+    fn interest_bearing_mints_sell_but_display_the_accrued_amount() {
+        let account = token_2022_mint(&[ExtensionType::InterestBearingConfig], |state| {
+            let config = state.init_extension::<InterestBearingConfig>(true).unwrap();
+            config.initialization_timestamp = 0.into();
+            config.last_update_timestamp = 0.into();
+            config.pre_update_average_rate = 500.into();
+            config.current_rate = 500.into();
+        });
+        let parsed = parse_mint_account(&Pubkey::new_unique(), &account).unwrap();
+        assert!(!parsed.is_non_transferable());
+        assert!(parsed.interest_bearing.is_some());
+        assert_eq!(route_holding(&parsed, false), (HoldingRoute::Sell, Some(SpecialExtension::InterestBearing)));
+
+        // 5% a year, continuously compounded: one token shows ~1.0513 after a year, exactly 1 at the start
+        assert_eq!(parsed.ui_amount(1_000_000, 0), 1.0);
+        let year = 31_556_736;
+        let accrued = parsed.ui_amount(1_000_000, year);
+        assert!((accrued - 0.05f64.exp()).abs() < 1e-6, "{}", accrued);
+        // A plain mint is the raw amount over its decimals
+        assert_eq!(info(None, Vec::new()).ui_amount(1_500_000, year), 1.5);
+    }
+
+    #[test]
+    fn special_counts_are_listed_in_category_order() {
+        assert_eq!(format_special_counts(&BTreeMap::new()), None);
+        let counts = BTreeMap::from([(SpecialExtension::InterestBearing, 1), (SpecialExtension::NonTransferable, 2)]);
+        assert_eq!(format_special_counts(&counts).unwrap(), "2 non-transferable, 1 interest-bearing");
+    }
+
+    #[test]
+    fn only_the_two_token_programs_own_mints() {
+        let mint = Pubkey::new_unique();
+        assert_eq!(token_program_of(&mint, &spl_token::ID).unwrap(), spl_token::ID);
+        assert_eq!(token_program_of(&mint, &spl_token_2022::ID).unwrap(), spl_token_2022::ID);
+        assert!(token_program_of(&mint, &solana_program::system_program::ID).is_err());
+    }
+
+    /// A client whose one getAccountInfo answer is `account`; any later request fails
+    fn answering(account: &Account) -> Arc<RpcClient> {
+        use anchor_client::solana_client::rpc_request::RpcRequest;
+        let value = serde_json::json!({
+            "context": { "slot": 1 },
+            "value": {
+                "lamports": account.lamports,
+                "data": [base64::encode(&account.data), "base64"],
+                "owner": account.owner.to_string(),
+                "executable": false,
+                "rentEpoch": 0,
+                "space": account.data.len(),
+            },
+        });
+        let mocks = std::collections::HashMap::from([(RpcRequest::GetAccountInfo, value)]);
+        Arc::new(RpcClient::new_mock_with_mocks("succeeds".to_string(), mocks))
+    }
+
+    #[tokio::test]
+    async fn each_mint_derives_its_ata_from_its_own_program_once() {
+        let wallet = Pubkey::new_unique();
+        for program in [spl_token::ID, spl_token_2022::ID] {
+            let mint = Pubkey::new_unique();
+            assert_eq!(cached_token_program(&mint), None);
+            let resolved = resolve_token_program(&answering(&mint_account(program, None)), &mint).await.unwrap();
+            assert_eq!(resolved, program);
+
+            // Read once: a client that can't answer still resolves from the cache
+            let offline = Arc::new(RpcClient::new_mock("fails".to_string()));
+            assert_eq!(resolve_token_program(&offline, &mint).await.unwrap(), program);
+            assert_eq!(cached_token_program(&mint), Some(program));
+
+            let ata = spl_associated_token_account::get_associated_token_address_with_program_id(&wallet, &mint, &resolved);
+            let other = if program == spl_token::ID { spl_token_2022::ID } else { spl_token::ID };
+            assert_ne!(ata, spl_associated_token_account::get_associated_token_address_with_program_id(&wallet, &mint, &other));
+            if program == spl_token::ID {
+                assert_eq!(ata, spl_associated_token_account::get_associated_token_address(&wallet, &mint));
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn accounts_outside_the_token_programs_are_not_cached() {
+        let mint = Pubkey::new_unique();
+        let not_a_mint = Account { owner: solana_program::system_program::ID, ..mint_account(spl_token::ID, None) };
+        assert!(resolve_token_program(&answering(&not_a_mint), &mint).await.is_err());
+        assert_eq!(cached_token_program(&mint), None);
+    }
+}
@@ -1,2 +1,6 @@
 pub mod token;
 pub mod tx;
+pub mod tx_decode;
+pub mod mint_inspector;
+pub mod preflight;
+pub mod lookup_table;
@@ -0,0 +1,194 @@
+use std::fmt;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Instant;
+use anchor_client::solana_client::rpc_config::RpcSimulateTransactionConfig;
+use anchor_client::solana_sdk::{
+    commitment_config::CommitmentConfig,
+    instruction::InstructionError,
+    transaction::{TransactionError, VersionedTransaction},
+};
+use colored::Colorize;
+use once_cell::sync::{Lazy, OnceCell};
+
+use crate::common::logger::Logger;
+use crate::services::rpc_pool::RpcHandle;
+
+/// SIMULATE_BEFORE_BUY (default: false): simulate every live buy right before it is sent
+static ENABLED: Lazy<bool> = Lazy::new(|| {
+    std::env::var("SIMULATE_BEFORE_BUY")
+        .ok()
+        .and_then(|v| v.parse::<bool>().ok())
+        .unwrap_or(false)
+});
+
+static PREFLIGHT_RPC: OnceCell<RpcHandle> = OnceCell::new();
+
+/// Pre-flights run and the time they added, for the average in the log line
+static PREFLIGHTS: AtomicU64 = AtomicU64::new(0);
+static PREFLIGHT_MS_TOTAL: AtomicU64 = AtomicU64::new(0);
+
+pub fn is_enabled() -> bool {
+    *ENABLED
+}
+
+/// RPC the pre-flight simulations go through; set once at startup
+pub fn set_preflight_rpc(read_rpc: RpcHandle) {
+    let _ = PREFLIGHT_RPC.set(read_rpc);
+}
+
+/// PumpFun program errors, by their Anchor error number
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PumpFunError {
+    NotAuthorized,
+    AlreadyInitialized,
+    /// 6002: the buy would cost more than max_sol_cost
+    TooMuchSolRequired,
+    /// 6003: the sell would return less than min_sol_output
+    TooLittleSolReceived,
+    MintDoesNotMatchBondingCurve,
+    /// 6005: the curve completed and migrated; it only trades on PumpSwap now
+    BondingCurveComplete,
+    BondingCurveNotComplete,
+    NotInitialized,
+    Other(u32),
+}
+
+impl PumpFunError {
+    pub fn from_code(code: u32) -> Self {
+        match code {
+            6000 => Self::NotAuthorized,
+            6001 => Self::AlreadyInitialized,
+            6002 => Self::TooMuchSolRequired,
+            6003 => Self::TooLittleSolReceived,
+            6004 => Self::MintDoesNotMatchBondingCurve,
+            6005 => Self::BondingCurveComplete,
+            6006 => Self::BondingCurveNotComplete,
+            6007 => Self::NotInitialized,
+            other => Self::Other(other),
+        }
+    }
+
+    fn describe(&self) -> &'static str {
+        match self {
+            Self::TooMuchSolRequired => "slippage exceeded, price moved past BUY_SLIPPAGE",
+            Self::TooLittleSolReceived => "slippage exceeded on the sell side",
+            Self::BondingCurveComplete => "bonding curve complete, the token migrated",
+            Self::BondingCurveNotComplete => "bonding curve not complete",
+            Self::MintDoesNotMatchBondingCurve => "mint does not match the bonding curve",
+            Self::NotAuthorized => "not authorized",
+            Self::AlreadyInitialized => "already initialized",
+            Self::NotInitialized => "program not initialized",
+            Self::Other(_) => "unknown PumpFun error",
+        }
+    }
+}
+
+/// Why a buy's simulation failed
+#[derive(Clone, Debug, PartialEq)]
+pub enum PreflightFailure {
+    PumpFun(PumpFunError),
+    /// Not enough SOL for the buy, its fees or tip
+    InsufficientFunds,
+    /// An IOC buy's slot assertion already fails
+    IocDeadlinePassed,
+    /// A custom error from another program, with the program's own message when it logged one
+    Program { program: String, code: u32, message: Option<String> },
+    Other(String),
+}
+
+impl fmt::Display for PreflightFailure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            // The variant name stays in the text so `classify_error` files it like the on-chain failure
+            Self::PumpFun(PumpFunError::Other(code)) => write!(f, "PumpFun error {}", code),
+            Self::PumpFun(error) => write!(f, "{} ({:?})", error.describe(), error),
+            Self::InsufficientFunds => write!(f, "insufficient funds"),
+            Self::IocDeadlinePassed => write!(f, "IOC deadline already passed (Lighthouse assertion)"),
+            Self::Program { program, code, message: Some(message) } => write!(f, "{} error {}: {}", program, code, message),
+            Self::Program { program, code, message: None } => write!(f, "{} error {}", program, code),
+            Self::Other(reason) => write!(f, "{}", reason),
+        }
+    }
+}
+
+/// Anchor's "Error Message: ..." from the failing program's logs
+fn anchor_message(logs: &[String]) -> Option<String> {
+    logs.iter()
+        .rev()
+        .find_map(|line| line.split("Error Message: ").nth(1))
+        .map(|message| message.trim_end_matches('.').to_string())
+}
+
+/// Turn a simulation error and its logs into a reason; the failing instruction tells which program raised it
+pub fn decode_failure(transaction: &VersionedTransaction, err: &TransactionError, logs: &[String]) -> PreflightFailure {
+    match err {
+        TransactionError::InsufficientFundsForFee | TransactionError::InsufficientFundsForRent { .. } => PreflightFailure::InsufficientFunds,
+        TransactionError::InstructionError(index, InstructionError::Custom(code)) => {
+            // Invoked programs are never loaded from a lookup table, so the static keys resolve them
+            let program = transaction
+                .message
+                .instructions()
+                .get(usize::from(*index))
+                .and_then(|instruction| transaction.message.static_account_keys().get(usize::from(instruction.program_id_index)))
+                .map(|program| program.to_string())
+                .unwrap_or_default();
+            match program.as_str() {
+                crate::dex::pump_fun::PUMP_FUN_PROGRAM => PreflightFailure::PumpFun(PumpFunError::from_code(*code)),
+                crate::engine::ioc::LIGHTHOUSE_PROGRAM_ID => PreflightFailure::IocDeadlinePassed,
+                // System transfer of the buy amount or tip
+                "11111111111111111111111111111111" if *code == 1 => PreflightFailure::InsufficientFunds,
+                _ => PreflightFailure::Program {
+                    program: if program == crate::engine::event_stream::PUMP_SWAP_PROGRAM { "PumpSwap".to_string() } else { program },
+                    code: *code,
+                    message: anchor_message(logs),
+                },
+            }
+        }
+        other => PreflightFailure::Other(format!("{:?}", other)),
+    }
+}
+
+/// Simulate the signed buy exactly as it will be sent: same blockhash or nonce, no signature check
+/// A simulation request that fails lets the buy go out rather than block it on the RPC.
+pub async fn check_buy(transaction: &VersionedTransaction) -> Result<(), PreflightFailure> {
+    if !is_enabled() {
+        return Ok(());
+    }
+    let Some(read_rpc) = PREFLIGHT_RPC.get() else {
+        return Ok(());
+    };
+    let logger = Logger::new("[PREFLIGHT] => ".yellow().to_string());
+    let config = RpcSimulateTransactionConfig {
+        sig_verify: false,
+        replace_recent_blockhash: false,
+        commitment: Some(CommitmentConfig::processed()),
+        ..RpcSimulateTransactionConfig::default()
+    };
+    let started = Instant::now();
+    let response = read_rpc.client().simulate_transaction_with_config(transaction, config).await;
+    let elapsed_ms = started.elapsed().as_millis() as u64;
+    let count = PREFLIGHTS.fetch_add(1, Ordering::Relaxed) + 1;
+    let average_ms = (PREFLIGHT_MS_TOTAL.fetch_add(elapsed_ms, Ordering::Relaxed) + elapsed_ms) / count;
+
+    let result = match response {
+        Ok(response) => response.value,
+        Err(e) => {
+            logger.error(format!("Pre-flight request failed after {}ms, sending anyway: {}", elapsed_ms, e));
+            return Ok(());
+        }
+    };
+    match result.err {
+        None => {
+            logger.critical(format!(
+                "Pre-flight ok in {}ms (avg {}ms over {}), {} compute units",
+                elapsed_ms, average_ms, count, result.units_consumed.unwrap_or(0)
+            ));
+            Ok(())
+        }
+        Some(err) => {
+            let failure = decode_failure(transaction, &err, result.logs.as_deref().unwrap_or_default());
+            logger.error(format!("Pre-flight failed in {}ms (avg {}ms over {}): {}", elapsed_ms, average_ms, count, failure).red().to_string());
+            Err(failure)
+        }
+    }
+}
@@ -24,7 +24,7 @@ use crate::{
 };
 
 // prioritization fee = UNIT_PRICE * UNIT_LIMIT
-fn get_unit_price() -> u64 {
+pub(crate) fn get_unit_price() -> u64 {
     env::var("UNIT_PRICE")
         .ok()
         .and_then(|v| u64::from_str(&v).ok())
@@ -38,6 +38,14 @@ fn get_unit_limit() -> u32 {
         .unwrap_or(200_000)
 }
 
+/// Priority fee in SOL a zeroslot buy or sell pays at most, from the compute budget it is sent with
+/// Zero while the PRIORITY_FEE_*_MODE for that side is unset, as no compute budget is attached then.
+pub fn estimated_priority_fee_sol(is_buy: bool) -> f64 {
+    crate::services::priority_fee::unit_price_for(is_buy)
+        .map(|unit_price| unit_price as f64 * get_unit_limit() as f64 / 1_000_000.0 / 1_000_000_000.0)
+        .unwrap_or(0.0)
+}
+
 /// Build a signed buying transaction with nonce, compute budget, and zeroslot tip.
 /// Does not send; used for offchain signing / prebuilding strategy.
 pub async fn build_signed_buying_transaction(
@@ -46,7 +54,7 @@ pub async fn build_signed_buying_transaction(
     recent_blockhash: solana_sdk::hash::Hash,
 ) -> Result<Transaction> {
     let tip_account = zeroslot::get_tip_account()?;
-    let tip = zeroslot::get_tip_value().await?;
+    let tip = crate::engine::jitter::JITTER.tip(zeroslot::get_tip_value().await?);
     let tip_lamports = ui_amount_to_amount(tip, spl_token::native_mint::DECIMALS);
     let zeroslot_tip_instruction =
         system_instruction::transfer(&keypair.pubkey(), &tip_account, tip_lamports);
@@ -81,91 +89,150 @@ pub async fn build_signed_buying_transaction(
     Ok(prebuilt_buying_tx)
 }
 
+/// A transaction zeroslot accepted, the endpoint that took it (empty for single-endpoint clients) and the
+/// tip in SOL it carried, jitter included
+#[derive(Clone, Debug)]
+pub struct ZeroslotSend {
+    pub signature: String,
+    pub endpoint: String,
+    pub tip_sol: f64,
+}
+
 pub async fn new_signed_and_send_zeroslot(
-    zeroslot_rpc_client: Arc<crate::services::zeroslot::ZeroSlotClient>,
+    zeroslot_rpc_client: Arc<dyn crate::services::zeroslot::TipLandingClient>,
     recent_blockhash: solana_sdk::hash::Hash,
     keypair: &Keypair,
-    mut instructions: Vec<Instruction>,
+    instructions: Vec<Instruction>,
     _logger: &Logger,
     is_buy: bool,
     slot: Option<u64>,
-) -> Result<Vec<String>> {
-    let tip_account = zeroslot::get_tip_account()?;
-    
-    let mut txs: Vec<String> = vec![];
+) -> Result<ZeroslotSend> {
+    // Claimed by prepare_blockhash; released on every path that never sends, settled by the send otherwise
+    let nonce = crate::services::nonce_pool::advanced_nonce(&instructions);
+    let release_nonce = || {
+        if let Some(nonce) = nonce {
+            crate::services::nonce_pool::after_send(nonce, recent_blockhash, false);
+        }
+    };
+    let (txn, tip_sol) = match sign_zeroslot_transaction(recent_blockhash, keypair, instructions, is_buy, nonce.is_some(), slot).await {
+        Ok(signed) => signed,
+        Err(e) => {
+            release_nonce();
+            return Err(e);
+        }
+    };
+
+    // SIMULATE_BEFORE_BUY: one simulation of this exact transaction, so a buy bound to fail costs no tip
+    if is_buy {
+        if let Err(failure) = crate::core::preflight::check_buy(&txn).await {
+            release_nonce();
+            return Err(anyhow::anyhow!("pre-flight simulation failed: {}", failure));
+        }
+    }
+
+    // Known as ours before it can reach the stream, so wallet reconciliation leaves its balance change alone
+    if let Some(signature) = txn.signatures.first() {
+        crate::engine::wallet_sync::register_bot_signature(&signature.to_string());
+    }
+    // Fails over across ZERO_SLOT_URLS with this same signed transaction, see `ZeroSlotPool::send_ranked`
+    let tx_result = zeroslot_rpc_client.send_transaction_via(&txn).await;
+    if let Some(nonce) = nonce {
+        crate::services::nonce_pool::after_send(nonce, recent_blockhash, tx_result.is_ok());
+    }
     
+    match tx_result {
+        Ok((signature, endpoint)) => {
+            println!("zeroslot send_transaction success: {} via {}", signature, if endpoint.is_empty() { "zeroslot" } else { endpoint.as_str() });
+            Ok(ZeroslotSend { signature: signature.to_string(), endpoint, tip_sol })
+        }
+        Err(e) => {
+            // Convert the error to a Send-compatible form, keeping the text for error classification
+            Err(anyhow::anyhow!("zeroslot send_transaction failed: {}", e))
+        }
+    }
+}
+
+/// Sign `instructions` for zeroslot with the tip, the compute budget and, for an IOC buy, the slot assertion
+/// Returns the transaction and its tip in SOL.
+async fn sign_zeroslot_transaction(
+    recent_blockhash: solana_sdk::hash::Hash,
+    keypair: &Keypair,
+    mut instructions: Vec<Instruction>,
+    is_buy: bool,
+    advances_nonce: bool,
+    slot: Option<u64>,
+) -> Result<(solana_sdk::transaction::VersionedTransaction, f64)> {
+    let tip_account = zeroslot::get_tip_account()?;
+
     // zeroslot tip, the upper limit is 0.1
-    let tip = zeroslot::get_tip_value().await?;
+    let tip = crate::engine::jitter::JITTER.tip(zeroslot::get_tip_value().await?);
     let tip_lamports = ui_amount_to_amount(tip, spl_token::native_mint::DECIMALS);
 
-    let zeroslot_tip_instruction = 
+    let zeroslot_tip_instruction =
         system_instruction::transfer(&keypair.pubkey(), &tip_account, tip_lamports);
-        
-        let unit_limit = get_unit_limit(); // TODO: update in mev boost
-        let unit_price = get_unit_price(); // TODO: update in mev boost
-        let _modify_compute_units =
-        solana_sdk::compute_budget::ComputeBudgetInstruction::set_compute_unit_limit(unit_limit);
-        let _add_priority_fee =
-        solana_sdk::compute_budget::ComputeBudgetInstruction::set_compute_unit_price(unit_price);
-        //  temporarily disabled for reducing gas fee during testing
-        // instructions.insert(1, modify_compute_units);
-        // instructions.insert(2, add_priority_fee);
-        
-        instructions.push(zeroslot_tip_instruction); // zeroslot is different with others.
 
-    // If this is a BUY, add Lighthouse sysvar slot assertion as the last instruction
-    if is_buy {
-        if let Some(slot_value) = slot {
-            const LIGHTHOUSE_PROGRAM_ID: &str = "L2TExMFKdjpN9kozasaurPirfHy9P8sbXoAN1qA3S95";
-            let lighthouse_program_id = Pubkey::from_str(LIGHTHOUSE_PROGRAM_ID)?;
-
-            let mut lighthouse_data = Vec::new();
-            // Instruction discriminator for AssertSysvarClock
-            lighthouse_data.extend_from_slice(&[15]);
-            // Log level (1 byte): 0 = Silent
-            lighthouse_data.push(1u8);
-            // Assertion type (1 byte): 0 = Slot assertion
-            lighthouse_data.push(0u8);
-            // Slot value (8 bytes, little endian)
-            let slot_u64 = slot_value;
-            lighthouse_data.extend_from_slice(&slot_u64.to_le_bytes());
-            // Operator (1 byte): 5 = <= (as per reference)
-            lighthouse_data.push(5u8);
-
-            let _lighthouse_ix = Instruction {
-                program_id: lighthouse_program_id,
-                accounts: vec![],
-                data: lighthouse_data,
-            };
-           //  sysvar assertion is very important, but I igored it for now for testing temperarily, after complete testing, I will add it back
-           // instructions.push(lighthouse_ix);
+    // Compute budget only with a PRIORITY_FEE_BUY_MODE / PRIORITY_FEE_SELL_MODE set, otherwise left off
+    // as before to keep gas low; it goes after the nonce advance, which must stay first
+    if let Some(unit_price) = crate::services::priority_fee::unit_price_for(is_buy) {
+        let at = usize::from(advances_nonce);
+        instructions.insert(at, solana_sdk::compute_budget::ComputeBudgetInstruction::set_compute_unit_limit(get_unit_limit()));
+        instructions.insert(at + 1, solana_sdk::compute_budget::ComputeBudgetInstruction::set_compute_unit_price(unit_price));
+    }
+
+    instructions.push(zeroslot_tip_instruction); // zeroslot is different with others.
+
+    // IOC buys carry a Lighthouse slot assertion as the last instruction, so a late landing fails instead of filling
+    let ioc = &*crate::engine::ioc::IOC_CONFIG;
+    if is_buy && ioc.enabled {
+        if let Some(trigger_slot) = slot {
+            instructions.push(crate::engine::ioc::slot_assertion_instruction(ioc.deadline_slot(trigger_slot))?);
         }
     }
-    println!("🚍🚍🚍🚍🚍recent_blockhash: {:?}", recent_blockhash);
-    // send init tx
+    // v0 through the LOOKUP_TABLE_ADDRESS table when it loaded at startup, legacy otherwise
+    let txn = crate::core::lookup_table::sign(keypair, &instructions, recent_blockhash)?;
+    crate::core::tx_decode::debug_print_versioned("zeroslot send", &txn);
+    Ok((txn, tip))
+}
+
+/// Simulate the transaction `new_signed_and_send_zeroslot` would send, tip included, without sending it (DRY_RUN)
+/// The signed transaction never leaves the process; a failed simulation comes back as an error with its logs.
+pub async fn simulate_zeroslot_transaction(
+    rpc_client: Arc<anchor_client::solana_client::nonblocking::rpc_client::RpcClient>,
+    recent_blockhash: solana_sdk::hash::Hash,
+    keypair: &Keypair,
+    mut instructions: Vec<Instruction>,
+    logger: &Logger,
+) -> Result<()> {
+    // A simulation never consumes the nonce, so its claim is released straight away
+    if let Some(nonce) = crate::services::nonce_pool::advanced_nonce(&instructions) {
+        crate::services::nonce_pool::after_send(nonce, recent_blockhash, false);
+    }
+    let tip_account = zeroslot::get_tip_account()?;
+    let tip = crate::engine::jitter::JITTER.tip(zeroslot::get_tip_value().await?);
+    let tip_lamports = ui_amount_to_amount(tip, spl_token::native_mint::DECIMALS);
+    instructions.push(system_instruction::transfer(&keypair.pubkey(), &tip_account, tip_lamports));
+
     let txn = Transaction::new_signed_with_payer(
         &instructions,
         Some(&keypair.pubkey()),
         &vec![keypair],
         recent_blockhash,
     );
+    crate::core::tx_decode::debug_print("dry-run simulation", &txn);
 
-    let tx_result = zeroslot_rpc_client.send_transaction(&txn).await;
-    
-    match tx_result {
-        Ok(signature) => {
-            println!("zeroslot send_transaction success: {}", signature.to_string());
-            txs.push(signature.to_string());
-            
-        }
-        Err(_) => {
-            // Convert the error to a Send-compatible form
-            return Err(anyhow::anyhow!("zeroslot send_transaction failed"));
-        }
-    };
-
-    Ok(txs)
+    let result = rpc_client
+        .simulate_transaction(&txn)
+        .await
+        .map_err(|e| anyhow!("Simulation request failed: {}", e))?
+        .value;
+    if let Some(err) = result.err {
+        let logs = result.logs.unwrap_or_default().join("\n");
+        return Err(anyhow!("Simulation failed: {:?}\n{}", err, logs));
+    }
+    logger.critical(format!("🧪 Simulated ({} compute units)", result.units_consumed.unwrap_or(0)).yellow().to_string());
+    Ok(())
 }
+
 /// Send transaction using normal RPC without any service or tips
 pub async fn new_signed_and_send_normal(
     rpc_client: Arc<anchor_client::solana_client::nonblocking::rpc_client::RpcClient>,
@@ -193,6 +260,8 @@ pub async fn new_signed_and_send_normal(
         &vec![keypair],
         recent_blockhash,
     );
+    crate::core::tx_decode::debug_print("RPC send", &txn);
+    crate::engine::wallet_sync::register_bot_signature(&txn.signatures[0].to_string());
 
     match rpc_client.send_transaction(&txn).await {
         Ok(signature) => {
@@ -216,7 +285,7 @@ pub async fn new_signed_and_send_with_landing_mode(
     // Always use Normal RPC for transaction landing
     logger.log("Using Normal RPC for transaction landing".green().to_string());
     new_signed_and_send_normal(
-        app_state.rpc_nonblocking_client.clone(),
+        app_state.send_rpc.client(),
         recent_blockhash,
         keypair,
         instructions,
@@ -0,0 +1,481 @@
+use std::str::FromStr;
+use anchor_client::solana_sdk::{
+    compute_budget,
+    message::VersionedMessage,
+    pubkey::Pubkey,
+    signature::Signature,
+    system_instruction::SystemInstruction,
+    system_program,
+    transaction::{Transaction, VersionedTransaction},
+};
+use solana_transaction_status::option_serializer::OptionSerializer;
+use spl_token_2022::instruction::TokenInstruction;
+
+use crate::dex::pump_fun::{PUMP_BUY_METHOD, PUMP_FUN_PROGRAM, PUMP_SELL_METHOD};
+use crate::dex::pump_swap::{BUY_DISCRIMINATOR, SELL_DISCRIMINATOR};
+use crate::engine::event_stream::PUMP_SWAP_PROGRAM;
+use crate::engine::ioc::LIGHTHOUSE_PROGRAM_ID;
+use crate::services::rpc_client::BatchRpcClient;
+use crate::services::rpc_pool::{self, RpcHandle};
+use crate::services::zeroslot;
+
+const LAMPORTS_PER_SOL_F64: f64 = 1_000_000_000.0;
+
+const PUMP_FUN_BUY_ACCOUNTS: &[&str] = &[
+    "global", "fee recipient", "mint", "bonding curve", "curve token account", "user token account", "user",
+    "system program", "token program", "creator vault", "event authority", "program", "global volume accumulator",
+    "user volume accumulator", "fee config", "fee program",
+];
+const PUMP_FUN_SELL_ACCOUNTS: &[&str] = &[
+    "global", "fee recipient", "mint", "bonding curve", "curve token account", "user token account", "user",
+    "system program", "creator vault", "token program", "event authority", "program", "fee config", "fee program",
+];
+const ATA_CREATE_ACCOUNTS: &[&str] = &["payer", "associated account", "wallet", "mint", "system program", "token program"];
+
+/// TX_DEBUG_PRINT (default: false) prints every transaction decoded before it is sent
+pub fn debug_print_enabled() -> bool {
+    std::env::var("TX_DEBUG_PRINT")
+        .ok()
+        .and_then(|v| v.parse::<bool>().ok())
+        .unwrap_or(false)
+}
+
+/// Print `transaction` decoded under `label` when TX_DEBUG_PRINT is on
+pub fn debug_print(label: &str, transaction: &Transaction) {
+    if debug_print_enabled() {
+        println!("🔎 {}\n{}", label, decode_transaction(transaction).format());
+    }
+}
+
+pub fn debug_print_versioned(label: &str, transaction: &VersionedTransaction) {
+    if debug_print_enabled() {
+        println!("🔎 {}\n{}", label, decode_versioned(transaction).format());
+    }
+}
+
+/// One account an instruction references
+#[derive(Clone, Debug, PartialEq)]
+pub struct DecodedAccount {
+    pub pubkey: String,
+    pub signer: bool,
+    pub writable: bool,
+    /// Loaded from an address lookup table; flags unknown without the table
+    pub from_lookup: bool,
+    pub role: Option<&'static str>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct DecodedInstruction {
+    pub program_id: String,
+    /// Known program name, or "unknown"
+    pub program: &'static str,
+    /// Decoded arguments, or the raw data length for programs we don't know
+    pub summary: String,
+    pub accounts: Vec<DecodedAccount>,
+}
+
+/// Everything needed to tell what a transaction does without an explorer
+#[derive(Clone, Debug, PartialEq)]
+pub struct DecodedTransaction {
+    pub version: &'static str,
+    pub signatures: Vec<String>,
+    pub size_bytes: usize,
+    pub recent_blockhash: String,
+    /// Nonce account advanced by the first instruction; the blockhash field then holds its stored nonce
+    pub nonce_account: Option<String>,
+    pub lookup_tables: usize,
+    pub instructions: Vec<DecodedInstruction>,
+    /// "ok" or the error, for transactions fetched from chain
+    pub status: Option<String>,
+    pub fee_lamports: Option<u64>,
+}
+
+impl DecodedTransaction {
+    pub fn format(&self) -> String {
+        let mut lines = vec![format!(
+            "Transaction ({}, {} bytes, {} signatures, {} lookup tables)",
+            self.version,
+            self.size_bytes,
+            self.signatures.len(),
+            self.lookup_tables
+        )];
+        for signature in &self.signatures {
+            lines.push(format!("  Signature: {}", signature));
+        }
+        match &self.nonce_account {
+            Some(nonce) => lines.push(format!("  Durable nonce: {} (value {})", nonce, self.recent_blockhash)),
+            None => lines.push(format!("  Blockhash: {}", self.recent_blockhash)),
+        }
+        if let Some(status) = &self.status {
+            lines.push(format!("  Status: {}", status));
+        }
+        if let Some(fee) = self.fee_lamports {
+            lines.push(format!("  Fee: {} lamports", fee));
+        }
+        for (index, instruction) in self.instructions.iter().enumerate() {
+            lines.push(format!("  #{} {}: {}", index, instruction.program, instruction.summary));
+            for account in &instruction.accounts {
+                let flags = if account.from_lookup {
+                    "lut".to_string()
+                } else {
+                    format!("{}{}", if account.writable { "w" } else { "-" }, if account.signer { "s" } else { "-" })
+                };
+                match account.role {
+                    Some(role) => lines.push(format!("      [{}] {} ({})", flags, account.pubkey, role)),
+                    None => lines.push(format!("      [{}] {}", flags, account.pubkey)),
+                }
+            }
+        }
+        lines.join("\n")
+    }
+}
+
+pub fn decode_transaction(transaction: &Transaction) -> DecodedTransaction {
+    decode_versioned(&VersionedTransaction::from(transaction.clone()))
+}
+
+pub fn decode_versioned(transaction: &VersionedTransaction) -> DecodedTransaction {
+    let message = &transaction.message;
+    let keys = message.static_account_keys();
+    let header = message.header();
+    let signed = header.num_required_signatures as usize;
+    let writable_signed = signed.saturating_sub(header.num_readonly_signed_accounts as usize);
+    let writable_unsigned = keys.len().saturating_sub(header.num_readonly_unsigned_accounts as usize);
+
+    let account_at = |index: u8, role: Option<&'static str>| -> DecodedAccount {
+        let index = index as usize;
+        match keys.get(index) {
+            Some(key) => DecodedAccount {
+                pubkey: key.to_string(),
+                signer: index < signed,
+                writable: if index < signed { index < writable_signed } else { index < writable_unsigned },
+                from_lookup: false,
+                role,
+            },
+            None => DecodedAccount {
+                pubkey: format!("lookup #{}", index - keys.len()),
+                signer: false,
+                writable: false,
+                from_lookup: true,
+                role,
+            },
+        }
+    };
+
+    let instructions: Vec<DecodedInstruction> = message
+        .instructions()
+        .iter()
+        .map(|instruction| {
+            let program_id = keys.get(instruction.program_id_index as usize).copied().unwrap_or_default();
+            let account_keys: Vec<Pubkey> = instruction
+                .accounts
+                .iter()
+                .map(|index| keys.get(*index as usize).copied().unwrap_or_default())
+                .collect();
+            let (program, summary, roles) = describe(&program_id, &instruction.data, &account_keys);
+            DecodedInstruction {
+                program_id: program_id.to_string(),
+                program,
+                summary,
+                accounts: instruction
+                    .accounts
+                    .iter()
+                    .enumerate()
+                    .map(|(position, index)| account_at(*index, roles.get(position).copied()))
+                    .collect(),
+            }
+        })
+        .collect();
+
+    let nonce_account = instructions
+        .first()
+        .filter(|instruction| instruction.summary.starts_with("advance nonce"))
+        .and_then(|instruction| instruction.accounts.first())
+        .map(|account| account.pubkey.clone());
+
+    DecodedTransaction {
+        version: match message {
+            VersionedMessage::Legacy(_) => "legacy",
+            VersionedMessage::V0(_) => "v0",
+        },
+        signatures: transaction.signatures.iter().map(|signature| signature.to_string()).collect(),
+        size_bytes: bincode::serialized_size(transaction).map(|size| size as usize).unwrap_or(0),
+        recent_blockhash: message.recent_blockhash().to_string(),
+        nonce_account,
+        lookup_tables: message.address_table_lookups().map(|lookups| lookups.len()).unwrap_or(0),
+        instructions,
+        status: None,
+        fee_lamports: None,
+    }
+}
+
+/// Lamports the transaction transfers to zeroslot tip accounts
+pub fn tip_lamports(transaction: &VersionedTransaction) -> u64 {
+    let keys = transaction.message.static_account_keys();
+    transaction
+        .message
+        .instructions()
+        .iter()
+        .filter(|instruction| keys.get(instruction.program_id_index as usize) == Some(&system_program::id()))
+        .filter(|instruction| {
+            let destination = instruction.accounts.get(1).and_then(|index| keys.get(*index as usize));
+            destination.is_some_and(zeroslot::is_tip_account)
+        })
+        .filter_map(|instruction| match bincode::deserialize::<SystemInstruction>(&instruction.data) {
+            Ok(SystemInstruction::Transfer { lamports }) => Some(lamports),
+            _ => None,
+        })
+        .sum()
+}
+
+/// (program name, decoded arguments, account roles) of one instruction
+fn describe(program_id: &Pubkey, data: &[u8], accounts: &[Pubkey]) -> (&'static str, String, Vec<&'static str>) {
+    let program = program_id.to_string();
+    if *program_id == system_program::id() {
+        return describe_system(data, accounts);
+    }
+    if *program_id == compute_budget::id() {
+        return ("Compute budget", describe_compute_budget(data), Vec::new());
+    }
+    if *program_id == spl_token::id() || *program_id == spl_token_2022::id() {
+        let name = if *program_id == spl_token::id() { "Token" } else { "Token-2022" };
+        return match TokenInstruction::unpack(data) {
+            Ok(instruction) => (name, format!("{:?}", instruction), token_roles(&instruction)),
+            Err(_) => (name, format!("undecodable ({} bytes)", data.len()), Vec::new()),
+        };
+    }
+    if *program_id == spl_associated_token_account::id() {
+        let summary = match data.first() {
+            None | Some(0) => "create",
+            Some(1) => "create idempotent",
+            Some(2) => "recover nested",
+            Some(_) => "unknown",
+        };
+        return ("Associated token", summary.to_string(), ATA_CREATE_ACCOUNTS.to_vec());
+    }
+    if program == PUMP_FUN_PROGRAM {
+        return describe_pump_fun(data);
+    }
+    if program == PUMP_SWAP_PROGRAM {
+        return ("PumpSwap", describe_pump_swap(data), Vec::new());
+    }
+    if program == LIGHTHOUSE_PROGRAM_ID {
+        return ("Lighthouse", describe_lighthouse(data), Vec::new());
+    }
+    ("unknown", format!("{} bytes of data", data.len()), Vec::new())
+}
+
+fn describe_system(data: &[u8], accounts: &[Pubkey]) -> (&'static str, String, Vec<&'static str>) {
+    match bincode::deserialize::<SystemInstruction>(data) {
+        Ok(SystemInstruction::Transfer { lamports }) if accounts.get(1).is_some_and(zeroslot::is_tip_account) => (
+            "System (tip)",
+            format!("tip {} lamports ({:.6} SOL)", lamports, lamports as f64 / LAMPORTS_PER_SOL_F64),
+            vec!["payer", "tip account"],
+        ),
+        Ok(SystemInstruction::Transfer { lamports }) => (
+            "System",
+            format!("transfer {} lamports ({:.6} SOL)", lamports, lamports as f64 / LAMPORTS_PER_SOL_F64),
+            vec!["from", "to"],
+        ),
+        Ok(SystemInstruction::AdvanceNonceAccount) => (
+            "System",
+            "advance nonce".to_string(),
+            vec!["nonce account", "recent blockhashes sysvar", "nonce authority"],
+        ),
+        Ok(instruction) => ("System", format!("{:?}", instruction), Vec::new()),
+        Err(_) => ("System", format!("undecodable ({} bytes)", data.len()), Vec::new()),
+    }
+}
+
+fn read_u32(data: &[u8], offset: usize) -> Option<u32> {
+    Some(u32::from_le_bytes(data.get(offset..offset + 4)?.try_into().ok()?))
+}
+
+fn read_u64(data: &[u8], offset: usize) -> Option<u64> {
+    Some(u64::from_le_bytes(data.get(offset..offset + 8)?.try_into().ok()?))
+}
+
+fn describe_compute_budget(data: &[u8]) -> String {
+    let decoded = match data.first() {
+        Some(1) => read_u32(data, 1).map(|bytes| format!("request heap frame {} bytes", bytes)),
+        Some(2) => read_u32(data, 1).map(|units| format!("unit limit {}", units)),
+        Some(3) => read_u64(data, 1).map(|price| format!("unit price {} micro-lamports", price)),
+        Some(4) => read_u32(data, 1).map(|bytes| format!("loaded accounts data limit {} bytes", bytes)),
+        _ => None,
+    };
+    decoded.unwrap_or_else(|| format!("undecodable ({} bytes)", data.len()))
+}
+
+// Transfer is deprecated for new code, but older transactions still carry it
+#[allow(deprecated)]
+fn token_roles(instruction: &TokenInstruction) -> Vec<&'static str> {
+    match instruction {
+        TokenInstruction::Transfer { .. } => vec!["source", "destination", "owner"],
+        TokenInstruction::TransferChecked { .. } => vec!["source", "mint", "destination", "owner"],
+        TokenInstruction::CloseAccount => vec!["account", "destination", "owner"],
+        TokenInstruction::SyncNative => vec!["account"],
+        _ => Vec::new(),
+    }
+}
+
+fn describe_pump_fun(data: &[u8]) -> (&'static str, String, Vec<&'static str>) {
+    let (Some(method), Some(amount), Some(threshold)) = (read_u64(data, 0), read_u64(data, 8), read_u64(data, 16)) else {
+        return ("PumpFun", format!("{} bytes of data", data.len()), Vec::new());
+    };
+    match method {
+        PUMP_BUY_METHOD => (
+            "PumpFun",
+            format!("buy {} tokens, max_sol_cost {} lamports ({:.6} SOL)", amount, threshold, threshold as f64 / LAMPORTS_PER_SOL_F64),
+            PUMP_FUN_BUY_ACCOUNTS.to_vec(),
+        ),
+        PUMP_SELL_METHOD => (
+            "PumpFun",
+            format!("sell {} tokens, min_sol_output {} lamports", amount, threshold),
+            PUMP_FUN_SELL_ACCOUNTS.to_vec(),
+        ),
+        _ => ("PumpFun", format!("{} bytes of data", data.len()), Vec::new()),
+    }
+}
+
+fn describe_pump_swap(data: &[u8]) -> String {
+    let discriminator = data.get(..8);
+    let (Some(base), Some(quote)) = (read_u64(data, 8), read_u64(data, 16)) else {
+        return format!("{} bytes of data", data.len());
+    };
+    if discriminator == Some(&BUY_DISCRIMINATOR[..]) {
+        format!("buy {} base tokens, max_quote_in {} lamports", base, quote)
+    } else if discriminator == Some(&SELL_DISCRIMINATOR[..]) {
+        format!("sell {} base tokens, min_quote_out {} lamports", base, quote)
+    } else {
+        format!("{} bytes of data", data.len())
+    }
+}
+
+/// Only the slot assertion IOC buys attach is decoded
+fn describe_lighthouse(data: &[u8]) -> String {
+    match (data.first(), data.get(2), read_u64(data, 3)) {
+        (Some(15), Some(0), Some(slot)) => format!("assert slot <= {}", slot),
+        _ => format!("{} bytes of data", data.len()),
+    }
+}
+
+/// Decode a base64 wire transaction, or fetch and decode a landed one by signature
+pub async fn decode_input(input: &str) -> Result<DecodedTransaction, String> {
+    let input = input.trim();
+    if let Ok(signature) = Signature::from_str(input) {
+        let (_, read_pool) = rpc_pool::pools_from_env();
+        let read_rpc = RpcHandle::new(read_pool.clone(), read_pool);
+        let confirmed = BatchRpcClient::new(read_rpc.client())
+            .get_transaction_cached(&signature)
+            .await
+            .map_err(|e| format!("Failed to fetch {}: {}", signature, e))?;
+        let transaction = confirmed
+            .transaction
+            .transaction
+            .decode()
+            .ok_or_else(|| format!("Failed to decode {}", signature))?;
+        let mut decoded = decode_versioned(&transaction);
+        if let Some(meta) = &confirmed.transaction.meta {
+            decoded.status = Some(match &meta.err {
+                Some(err) => format!("failed: {:?}", err),
+                None => "ok".to_string(),
+            });
+            decoded.fee_lamports = Some(meta.fee);
+            if let OptionSerializer::Some(logs) = &meta.log_messages {
+                // The log tail is where the failing program reports why
+                let tail: Vec<&String> = logs.iter().rev().take(5).collect();
+                for line in tail.into_iter().rev() {
+                    decoded.status = decoded.status.map(|status| format!("{}\n    {}", status, line));
+                }
+            }
+        }
+        return Ok(decoded);
+    }
+    let bytes = base64::decode(input).map_err(|e| format!("Neither a signature nor base64: {}", e))?;
+    let transaction: VersionedTransaction =
+        bincode::deserialize(&bytes).map_err(|e| format!("Failed to deserialize transaction: {}", e))?;
+    Ok(decode_versioned(&transaction))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anchor_client::solana_sdk::{
+        hash::Hash,
+        instruction::{AccountMeta, Instruction},
+        signature::Keypair,
+        signer::Signer,
+    };
+    use spl_associated_token_account::instruction::create_associated_token_account_idempotent;
+
+    use crate::core::tx::build_signed_buying_transaction;
+
+    /// A PumpFun buy of `tokens` for at most `max_sol_cost`, assembled the way the buy path sends it
+    async fn fixture_buy(keypair: &Keypair, tokens: u64, max_sol_cost: u64) -> Transaction {
+        if std::env::var("ZERO_SLOT_TIP_VALUE").is_err() {
+            std::env::set_var("ZERO_SLOT_TIP_VALUE", "0.001");
+        }
+        let mint = Pubkey::new_unique();
+        let mut accounts: Vec<AccountMeta> = (0..PUMP_FUN_BUY_ACCOUNTS.len())
+            .map(|_| AccountMeta::new_readonly(Pubkey::new_unique(), false))
+            .collect();
+        accounts[2] = AccountMeta::new_readonly(mint, false);
+        accounts[6] = AccountMeta::new(keypair.pubkey(), true);
+        let buy = Instruction::new_with_bincode(Pubkey::from_str(PUMP_FUN_PROGRAM).unwrap(), &(PUMP_BUY_METHOD, tokens, max_sol_cost), accounts);
+        let create = create_associated_token_account_idempotent(&keypair.pubkey(), &keypair.pubkey(), &mint, &spl_token::id());
+        build_signed_buying_transaction(keypair, vec![create, buy], Hash::new_unique()).await.unwrap()
+    }
+
+    #[tokio::test]
+    async fn decodes_an_assembled_buy() {
+        let keypair = Keypair::new();
+        let transaction = fixture_buy(&keypair, 1_000_000, 110_000_000).await;
+        let decoded = decode_transaction(&transaction);
+
+        assert_eq!(decoded.version, "legacy");
+        assert_eq!(decoded.signatures, vec![transaction.signatures[0].to_string()]);
+        assert_eq!(decoded.size_bytes, bincode::serialized_size(&VersionedTransaction::from(transaction.clone())).unwrap() as usize);
+        assert_eq!(decoded.recent_blockhash, transaction.message.recent_blockhash.to_string());
+        assert!(decoded.nonce_account.is_some(), "the assembler advances a nonce first");
+
+        let programs: Vec<&str> = decoded.instructions.iter().map(|instruction| instruction.program).collect();
+        assert_eq!(programs, vec!["System", "Compute budget", "Compute budget", "Associated token", "PumpFun", "System (tip)"]);
+        let summaries: Vec<&str> = decoded.instructions.iter().map(|instruction| instruction.summary.as_str()).collect();
+        assert_eq!(summaries[0], "advance nonce");
+        assert!(summaries[1].starts_with("unit limit "), "{}", summaries[1]);
+        assert!(summaries[2].starts_with("unit price "), "{}", summaries[2]);
+        assert_eq!(summaries[3], "create idempotent");
+        assert_eq!(summaries[4], "buy 1000000 tokens, max_sol_cost 110000000 lamports (0.110000 SOL)");
+        assert!(summaries[5].starts_with("tip "), "{}", summaries[5]);
+        assert_eq!(tip_lamports(&VersionedTransaction::from(transaction.clone())), read_u64(&transaction.message.instructions[5].data, 4).unwrap());
+
+        let user = &decoded.instructions[4].accounts[6];
+        assert_eq!(user.pubkey, keypair.pubkey().to_string());
+        assert_eq!(user.role, Some("user"));
+        assert!(user.signer && user.writable);
+        let global = &decoded.instructions[4].accounts[0];
+        assert_eq!(global.role, Some("global"));
+        assert!(!global.signer && !global.writable);
+    }
+
+    #[tokio::test]
+    async fn format_lists_every_instruction_and_its_accounts() {
+        let keypair = Keypair::new();
+        let formatted = decode_transaction(&fixture_buy(&keypair, 5, 7).await).format();
+
+        assert!(formatted.starts_with("Transaction (legacy, "), "{}", formatted);
+        assert!(formatted.contains("  Durable nonce: "));
+        assert!(formatted.contains("  #4 PumpFun: buy 5 tokens, max_sol_cost 7 lamports"));
+        assert!(formatted.contains(&format!("      [ws] {} (user)", keypair.pubkey())));
+        assert!(formatted.contains("  #5 System (tip): tip "));
+    }
+
+    #[test]
+    fn unknown_programs_and_bad_data_fall_back_to_sizes() {
+        assert_eq!(describe_compute_budget(&[9, 1]), "undecodable (2 bytes)");
+        assert_eq!(describe_lighthouse(&[1, 2, 3]), "3 bytes of data");
+        let (program, summary, roles) = describe(&Pubkey::new_unique(), &[0; 12], &[]);
+        assert_eq!((program, summary.as_str()), ("unknown", "12 bytes of data"));
+        assert!(roles.is_empty());
+    }
+}
@@ -0,0 +1,146 @@
+//! Checked amount math shared by the PumpFun and PumpSwap instruction builders
+//!
+//! All products are widened to u128 so no intermediate can overflow; results that no
+//! longer fit in a u64 are reported as errors instead of panicking or wrapping.
+
+use anyhow::{anyhow, Result};
+
+pub const BPS_DENOMINATOR: u64 = 10_000;
+
+/// `input_amount * (10_000 + slippage_bps) / 10_000`
+/// Errors when the padded amount exceeds u64::MAX (e.g. amounts near u64::MAX at the
+/// 50000 bps config maximum) rather than silently capping the max cost.
+pub fn max_amount_with_slippage(input_amount: u64, slippage_bps: u64) -> Result<u64> {
+    let padded = (input_amount as u128) * (BPS_DENOMINATOR as u128 + slippage_bps as u128)
+        / BPS_DENOMINATOR as u128;
+    u64::try_from(padded).map_err(|_| {
+        anyhow!("Amount {} with {} bps slippage overflows u64", input_amount, slippage_bps)
+    })
+}
+
+/// `input_amount * (10_000 - slippage_bps) / 10_000`, with slippage of 100% or more giving 0
+pub fn min_amount_with_slippage(input_amount: u64, slippage_bps: u64) -> u64 {
+    let kept_bps = BPS_DENOMINATOR.saturating_sub(slippage_bps) as u128;
+    // Result is <= input_amount so the narrowing cast is lossless
+    ((input_amount as u128) * kept_bps / BPS_DENOMINATOR as u128) as u64
+}
+
+/// Constant-product output at zero fees: `amount_in * reserve_out / (reserve_in + amount_in)`
+/// Returns 0 when the input or either reserve is zero. The result is always strictly less
+/// than `reserve_out`, so it fits in a u64 and the curve can never be drained by one trade.
+pub fn constant_product_out(amount_in: u64, reserve_in: u64, reserve_out: u64) -> u64 {
+    if amount_in == 0 || reserve_in == 0 || reserve_out == 0 {
+        return 0;
+    }
+    let numerator = (amount_in as u128) * (reserve_out as u128);
+    let denominator = (reserve_in as u128) + (amount_in as u128);
+    (numerator / denominator) as u64
+}
+
+/// UI amount to raw units
+/// NaN, negative and infinite inputs give 0 and values above u64::MAX clamp to u64::MAX.
+/// f64 carries 53 bits of mantissa, so raw amounts above ~9e15 lose their lowest digits.
+pub fn ui_to_raw(ui_amount: f64, decimals: u8) -> u64 {
+    if !ui_amount.is_finite() || ui_amount <= 0.0 {
+        return 0;
+    }
+    let raw = ui_amount * 10f64.powi(decimals as i32);
+    if raw >= u64::MAX as f64 {
+        u64::MAX
+    } else {
+        raw as u64
+    }
+}
+
+/// `fraction` (clamped to 0.0..=1.0) of `raw_amount` in integer math
+/// The fraction is rounded to whole basis points; 1.0 always returns the full amount.
+pub fn fraction_of(raw_amount: u64, fraction: f64) -> u64 {
+    let fraction = if fraction.is_finite() { fraction.clamp(0.0, 1.0) } else { 0.0 };
+    let bps = (fraction * BPS_DENOMINATOR as f64).round() as u128;
+    ((raw_amount as u128) * bps / BPS_DENOMINATOR as u128) as u64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+
+    #[test]
+    fn max_amount_overflow_is_an_error() {
+        assert!(max_amount_with_slippage(u64::MAX, 1).is_err());
+        assert_eq!(max_amount_with_slippage(u64::MAX, 0).unwrap(), u64::MAX);
+        assert_eq!(max_amount_with_slippage(1_000_000, 500).unwrap(), 1_050_000);
+    }
+
+    #[test]
+    fn ui_to_raw_rejects_non_finite_and_negative() {
+        assert_eq!(ui_to_raw(f64::NAN, 6), 0);
+        assert_eq!(ui_to_raw(f64::INFINITY, 6), 0);
+        assert_eq!(ui_to_raw(-1.0, 9), 0);
+        assert_eq!(ui_to_raw(1e30, 9), u64::MAX);
+        assert_eq!(ui_to_raw(1.5, 6), 1_500_000);
+    }
+
+    #[test]
+    fn fraction_of_full_and_empty() {
+        assert_eq!(fraction_of(u64::MAX, 1.0), u64::MAX);
+        assert_eq!(fraction_of(1_000, 0.0), 0);
+        assert_eq!(fraction_of(1_000, f64::NAN), 0);
+        assert_eq!(fraction_of(1_000, 2.0), 1_000);
+    }
+
+    proptest! {
+        #[test]
+        fn max_amount_never_below_input(amount in any::<u64>(), bps in 0u64..=50_000) {
+            if let Ok(padded) = max_amount_with_slippage(amount, bps) {
+                prop_assert!(padded >= amount);
+            } else {
+                // Only an amount that really overflows may error
+                prop_assert!((amount as u128) * (BPS_DENOMINATOR + bps) as u128 / BPS_DENOMINATOR as u128 > u64::MAX as u128);
+            }
+        }
+
+        #[test]
+        fn min_amount_never_above_input(amount in any::<u64>(), bps in any::<u64>()) {
+            let floor = min_amount_with_slippage(amount, bps);
+            prop_assert!(floor <= amount);
+            if bps >= BPS_DENOMINATOR {
+                prop_assert_eq!(floor, 0);
+            }
+        }
+
+        #[test]
+        fn slippage_bounds_bracket_the_amount(amount in 0u64..=u64::MAX / 10, bps in 0u64..=10_000) {
+            prop_assert!(min_amount_with_slippage(amount, bps) <= max_amount_with_slippage(amount, bps).unwrap());
+        }
+
+        #[test]
+        fn constant_product_never_drains_the_pool(amount_in in any::<u64>(), reserve_in in any::<u64>(), reserve_out in any::<u64>()) {
+            let out = constant_product_out(amount_in, reserve_in, reserve_out);
+            if reserve_out > 0 {
+                prop_assert!(out < reserve_out);
+            } else {
+                prop_assert_eq!(out, 0);
+            }
+        }
+
+        #[test]
+        fn constant_product_grows_with_input(amount_in in 1u64..=u64::MAX / 2, reserve_in in 1u64.., reserve_out in 1u64..) {
+            let smaller = constant_product_out(amount_in, reserve_in, reserve_out);
+            let larger = constant_product_out(amount_in * 2, reserve_in, reserve_out);
+            prop_assert!(larger >= smaller);
+        }
+
+        #[test]
+        fn fraction_of_stays_within_amount(amount in any::<u64>(), fraction in -1.0f64..2.0) {
+            let part = fraction_of(amount, fraction);
+            prop_assert!(part <= amount);
+        }
+
+        #[test]
+        fn ui_to_raw_round_trips_whole_units(units in 0u64..=1_000_000, decimals in 0u8..=9) {
+            // Raw amounts up to 1e15 are exact in f64
+            prop_assert_eq!(ui_to_raw(units as f64, decimals), units * 10u64.pow(decimals as u32));
+        }
+    }
+}
@@ -1,2 +1,4 @@
+pub mod math;
 pub mod pump_fun;
 pub mod pump_swap;
+pub mod raydium;
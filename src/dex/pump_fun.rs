@@ -4,6 +4,8 @@ use borsh::from_slice;
 use tokio::time::Instant;
 use borsh_derive::{BorshDeserialize, BorshSerialize};
 use colored::Colorize;
+use dashmap::DashMap;
+use once_cell::sync::Lazy;
 use serde::{Deserialize, Serialize};
 use solana_program_pack::Pack;
 use solana_sdk::{
@@ -16,9 +18,10 @@ use solana_sdk::{
 use spl_associated_token_account::{
     get_associated_token_address, get_associated_token_address_with_program_id, instruction::create_associated_token_account_idempotent,
 };
-use spl_token::{ui_amount_to_amount};
 use crate::{
+    dex::math,
     common::{config::SwapConfig, logger::Logger},
+    core::mint_inspector,
     engine::{monitor::BondingCurveInfo, swap::{SwapDirection, SwapInType}},
 };
 
@@ -45,6 +48,18 @@ pub const TOKEN_TOTAL_SUPPLY: u64 = 1_000_000_000_000_000;
 pub const GLOBAL_VOLUME_ACCUMULATOR_SEED: &[u8] = b"global_volume_accumulator";
 pub const USER_VOLUME_ACCUMULATOR_SEED: &[u8] = b"user_volume_accumulator";
 
+/// Parsed once instead of on every swap build
+pub static PUMP_FEE_CONFIG_PUBKEY: Lazy<Pubkey> = Lazy::new(|| Pubkey::from_str(PUMP_FEE_CONFIG).expect("valid fee config pubkey"));
+
+/// Mint-independent PDAs and ATAs keyed by (kind, owner, program); find_program_address is the slow part of building a swap
+static DERIVED_ADDRESSES: Lazy<DashMap<(&'static str, Pubkey, Pubkey), Pubkey>> = Lazy::new(DashMap::new);
+
+fn cached_address(kind: &'static str, owner: &Pubkey, program_id: &Pubkey, derive: impl FnOnce() -> Pubkey) -> Pubkey {
+    *DERIVED_ADDRESSES
+        .entry((kind, *owner, *program_id))
+        .or_insert_with(derive)
+}
+
 // Minimum SOL output for selling to ensure transactions always build
 pub const MIN_SOL_OUTPUT_SELLING: u64 = 0;
 
@@ -80,24 +95,9 @@ impl Pump {
         virtual_sol_reserves: u64,
         virtual_token_reserves: u64,
     ) -> u64 {
-        if sol_amount_in == 0 || virtual_sol_reserves == 0 || virtual_token_reserves == 0 {
-            return 0;
-        }
-        
         // PumpFun bonding curve formula for buy:
         // tokens_out = (sol_in * virtual_token_reserves) / (virtual_sol_reserves + sol_in)
-        let sol_amount_in_u128 = sol_amount_in as u128;
-        let virtual_sol_reserves_u128 = virtual_sol_reserves as u128;
-        let virtual_token_reserves_u128 = virtual_token_reserves as u128;
-        
-        let numerator = sol_amount_in_u128.saturating_mul(virtual_token_reserves_u128);
-        let denominator = virtual_sol_reserves_u128.saturating_add(sol_amount_in_u128);
-        
-        if denominator == 0 {
-            return 0;
-        }
-        
-        numerator.checked_div(denominator).unwrap_or(0) as u64
+        math::constant_product_out(sol_amount_in, virtual_sol_reserves, virtual_token_reserves)
     }
 
     /// Calculate SOL amount out for sell using virtual reserves
@@ -106,24 +106,9 @@ impl Pump {
         virtual_sol_reserves: u64,
         virtual_token_reserves: u64,
     ) -> u64 {
-        if token_amount_in == 0 || virtual_sol_reserves == 0 || virtual_token_reserves == 0 {
-            return 0;
-        }
-        
         // PumpFun bonding curve formula for sell:
         // sol_out = (token_in * virtual_sol_reserves) / (virtual_token_reserves + token_in)
-        let token_amount_in_u128 = token_amount_in as u128;
-        let virtual_sol_reserves_u128 = virtual_sol_reserves as u128;
-        let virtual_token_reserves_u128 = virtual_token_reserves as u128;
-        
-        let numerator = token_amount_in_u128.saturating_mul(virtual_sol_reserves_u128);
-        let denominator = virtual_token_reserves_u128.saturating_add(token_amount_in_u128);
-        
-        if denominator == 0 {
-            return 0;
-        }
-        
-        numerator.checked_div(denominator).unwrap_or(0) as u64
+        math::constant_product_out(token_amount_in, virtual_token_reserves, virtual_sol_reserves)
     }
 
     /// Calculate price using virtual reserves (consistent with transaction_parser.rs)
@@ -158,8 +143,14 @@ impl Pump {
         // Extract the essential data
         let mint_str = &trade_info.mint;
         let owner = self.keypair.pubkey();
-        // let token_program_id = Pubkey::from_str(TOKEN_2022_PROGRAM).unwrap(); // always use TOKEN_2022_PROGRAM for PumpFun
-        let token_program_id = Pubkey::from_str(TOKEN_2022_PROGRAM).unwrap(); // always use TOKEN_2022_PROGRAM for PumpFun
+        // Older PumpFun mints are classic SPL, create_v2 mints are Token-2022; the mint's owner decides
+        let token_program_id = match mint_inspector::resolve_token_program(&self.rpc_nonblocking_client, &Pubkey::from_str(mint_str)?).await {
+            Ok(program) => program,
+            Err(e) => {
+                _logger.error(format!("Token program lookup failed for {}, assuming Token-2022: {}", mint_str, e));
+                Pubkey::from_str(TOKEN_2022_PROGRAM)?
+            }
+        };
         let native_mint = spl_token::native_mint::ID;
         let pump_program = Pubkey::from_str(PUMP_FUN_PROGRAM)?;
 
@@ -180,7 +171,7 @@ impl Pump {
                 let token_out_pubkey = Pubkey::from_str(mint_str)?;
                 (
                     native_mint,
-                    get_associated_token_address(&owner, &native_mint),
+                    get_wsol_ata(&owner),
                     token_out_pubkey,
                     get_associated_token_address_with_program_id(&owner, &token_out_pubkey, &token_program_id),
                     PUMP_BUY_METHOD,
@@ -192,7 +183,7 @@ impl Pump {
                     token_in_pubkey,
                     get_associated_token_address_with_program_id(&owner, &token_in_pubkey, &token_program_id),
                     native_mint,
-                    get_associated_token_address(&owner, &native_mint),
+                    get_wsol_ata(&owner),
                     PUMP_SELL_METHOD,
                 )
             }
@@ -231,23 +222,25 @@ impl Pump {
             Some(creator) => Pubkey::from_str(creator).unwrap_or_else(|_| panic!("Invalid creator pubkey: {}", creator)),
             None => return Err(anyhow!("Coin creator not found in trade info")),
         };
-        let (creator_vault, _) = Pubkey::find_program_address(
-            &[b"creator-vault", coin_creator.as_ref()],
-            &pump_program,
-        );
+        let creator_vault = get_creator_vault_pda(&coin_creator, &pump_program);
 
         // Calculate token amount and threshold based on operation type and parsed data
         let (token_amount, sol_amount_threshold, input_accounts) = match swap_config.swap_direction {
             SwapDirection::Buy => {
-                let amount_specified = ui_amount_to_amount(swap_config.amount_in, spl_token::native_mint::DECIMALS);
-                let max_sol_cost = max_amount_with_slippage(amount_specified, swap_config.buy_slippage);
-                
+                let amount_specified = math::ui_to_raw(swap_config.amount_in, spl_token::native_mint::DECIMALS);
                 // Use virtual reserves from trade_info for accurate calculation
                 let tokens_out = Self::calculate_buy_token_amount(
                     amount_specified,
                     trade_info.virtual_sol_reserves,
                     trade_info.virtual_token_reserves,
                 );
+                // IOC buys bound the cost by the trigger reserves instead of the loose BUY_SLIPPAGE padding
+                let ioc = &*crate::engine::ioc::IOC_CONFIG;
+                let max_sol_cost = if ioc.enabled {
+                    crate::engine::ioc::tight_max_sol_cost(tokens_out, trade_info.virtual_sol_reserves, trade_info.virtual_token_reserves, ioc)?
+                } else {
+                    math::max_amount_with_slippage(amount_specified, swap_config.buy_slippage)?
+                };
                 
                 _logger.log(format!("Buy calculation - SOL in: {}, Tokens out: {}, Virtual SOL: {}, Virtual Tokens: {}", 
                     amount_specified, tokens_out, trade_info.virtual_sol_reserves, trade_info.virtual_token_reserves));
@@ -270,7 +263,7 @@ impl Pump {
                         AccountMeta::new_readonly(pump_program, false),
                         AccountMeta::new(global_volume_accumulator, false),
                         AccountMeta::new(user_volume_accumulator, false),
-                        AccountMeta::new_readonly(Pubkey::from_str(PUMP_FEE_CONFIG)?, false),
+                        AccountMeta::new_readonly(*PUMP_FEE_CONFIG_PUBKEY, false),
                         AccountMeta::new_readonly(Pubkey::from_str(PUMP_FEE_PROGRAM)?, false),                        
                     ]
                 )
@@ -290,17 +283,17 @@ impl Pump {
                         // PumpFun tokens always use 6 decimals
                         let decimals = 6;
                         
-                        let raw_amount = (cached_balance * 10f64.powi(decimals as i32)) as u64;
+                        let raw_amount = math::ui_to_raw(cached_balance, decimals);
                         
                         // Apply percentage or quantity based on swap config
                         match swap_config.in_type {
                             SwapInType::Qty => {
                                 // Convert UI amount to raw amount using decimals
-                                ui_amount_to_amount(swap_config.amount_in, decimals)
+                                math::ui_to_raw(swap_config.amount_in, decimals)
                             },
                             SwapInType::Pct => {
                                 let percentage = swap_config.amount_in.min(1.0);
-                                ((percentage * raw_amount as f64) as u64).max(1) // Ensure at least 1 token
+                                math::fraction_of(raw_amount, percentage).max(1) // Ensure at least 1 token
                             }
                         }
                     } else {
@@ -317,11 +310,11 @@ impl Pump {
                                     SwapInType::Qty => {
                                         // PumpFun tokens always use 6 decimals
                                         let decimals = 6;
-                                        ui_amount_to_amount(swap_config.amount_in, decimals)
+                                        math::ui_to_raw(swap_config.amount_in, decimals)
                                     },
                                     SwapInType::Pct => {
                                         let percentage = swap_config.amount_in.min(1.0);
-                                        ((percentage * amount_value as f64) as u64).max(1) // Ensure at least 1 token
+                                        math::fraction_of(amount_value, percentage).max(1) // Ensure at least 1 token
                                     }
                                 }
                             },
@@ -336,13 +329,13 @@ impl Pump {
                                     let cached_balance = bought_info.current_amount;
                                     // PumpFun tokens always use 6 decimals
                                     let decimals = 6;
-                                    let raw_amount = (cached_balance * 10f64.powi(decimals as i32)) as u64;
+                                    let raw_amount = math::ui_to_raw(cached_balance, decimals);
                                     
                                     match swap_config.in_type {
-                                        SwapInType::Qty => ui_amount_to_amount(swap_config.amount_in, decimals),
+                                        SwapInType::Qty => math::ui_to_raw(swap_config.amount_in, decimals),
                                         SwapInType::Pct => {
                                             let percentage = swap_config.amount_in.min(1.0);
-                                            ((percentage * raw_amount as f64) as u64).max(1)
+                                            math::fraction_of(raw_amount, percentage).max(1)
                                         }
                                     }
                                 } else {
@@ -357,13 +350,13 @@ impl Pump {
                                     let cached_balance = bought_info.current_amount;
                                     // PumpFun tokens always use 6 decimals
                                     let decimals = 6;
-                                    let raw_amount = (cached_balance * 10f64.powi(decimals as i32)) as u64;
+                                    let raw_amount = math::ui_to_raw(cached_balance, decimals);
                                     
                                     match swap_config.in_type {
-                                        SwapInType::Qty => ui_amount_to_amount(swap_config.amount_in, decimals),
+                                        SwapInType::Qty => math::ui_to_raw(swap_config.amount_in, decimals),
                                         SwapInType::Pct => {
                                             let percentage = swap_config.amount_in.min(1.0);
-                                            ((percentage * raw_amount as f64) as u64).max(1)
+                                            math::fraction_of(raw_amount, percentage).max(1)
                                         }
                                     }
                                 } else {
@@ -389,22 +382,7 @@ impl Pump {
                 (
                     actual_token_amount,
                     1,
-                    vec![
-                        AccountMeta::new_readonly(Pubkey::from_str(PUMP_GLOBAL)?, false),
-                        AccountMeta::new(Pubkey::from_str(PUMP_FEE_RECIPIENT)?, false),
-                        AccountMeta::new_readonly(Pubkey::from_str(mint_str)?, false),
-                        AccountMeta::new(bonding_curve, false),
-                        AccountMeta::new(associated_bonding_curve, false),
-                        AccountMeta::new(in_ata, false),
-                        AccountMeta::new(owner, true),
-                        AccountMeta::new_readonly(system_program::id(), false),
-                        AccountMeta::new(creator_vault, false),
-                        AccountMeta::new_readonly(token_program_id, false),
-                        AccountMeta::new_readonly(Pubkey::from_str(PUMP_EVENT_AUTHORITY)?, false),
-                        AccountMeta::new_readonly(pump_program, false),
-                        AccountMeta::new_readonly(Pubkey::from_str(PUMP_FEE_CONFIG)?, false),
-                        AccountMeta::new_readonly(Pubkey::from_str(PUMP_FEE_PROGRAM)?, false),
-                    ]
+                    sell_accounts(&owner, &Pubkey::from_str(mint_str)?, &token_program_id, &creator_vault)?,
                 )
             }
         };
@@ -438,6 +416,40 @@ impl Pump {
     }
 }
 
+/// Accounts of a PumpFun sell of `mint` from `owner`'s token account
+fn sell_accounts(owner: &Pubkey, mint: &Pubkey, token_program_id: &Pubkey, creator_vault: &Pubkey) -> Result<Vec<AccountMeta>> {
+    let pump_program = Pubkey::from_str(PUMP_FUN_PROGRAM)?;
+    let bonding_curve = get_pda(mint, &pump_program)?;
+    Ok(vec![
+        AccountMeta::new_readonly(Pubkey::from_str(PUMP_GLOBAL)?, false),
+        AccountMeta::new(Pubkey::from_str(PUMP_FEE_RECIPIENT)?, false),
+        AccountMeta::new_readonly(*mint, false),
+        AccountMeta::new(bonding_curve, false),
+        AccountMeta::new(get_associated_token_address_with_program_id(&bonding_curve, mint, token_program_id), false),
+        AccountMeta::new(get_associated_token_address_with_program_id(owner, mint, token_program_id), false),
+        AccountMeta::new(*owner, true),
+        AccountMeta::new_readonly(system_program::id(), false),
+        AccountMeta::new(*creator_vault, false),
+        AccountMeta::new_readonly(*token_program_id, false),
+        AccountMeta::new_readonly(Pubkey::from_str(PUMP_EVENT_AUTHORITY)?, false),
+        AccountMeta::new_readonly(pump_program, false),
+        AccountMeta::new_readonly(*PUMP_FEE_CONFIG_PUBKEY, false),
+        AccountMeta::new_readonly(Pubkey::from_str(PUMP_FEE_PROGRAM)?, false),
+    ])
+}
+
+/// A PumpFun sell of `token_amount` raw tokens for at least `min_sol_output` lamports, without any balance lookup
+/// For transactions that sell what an earlier instruction in the same transaction bought.
+pub fn sell_instruction(owner: &Pubkey, mint: &Pubkey, token_program_id: &Pubkey, coin_creator: &Pubkey, token_amount: u64, min_sol_output: u64) -> Result<Instruction> {
+    let pump_program = Pubkey::from_str(PUMP_FUN_PROGRAM)?;
+    let creator_vault = get_creator_vault_pda(coin_creator, &pump_program);
+    Ok(Instruction::new_with_bincode(
+        pump_program,
+        &(PUMP_SELL_METHOD, token_amount, min_sol_output),
+        sell_accounts(owner, mint, token_program_id, &creator_vault)?,
+    ))
+}
+
 #[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct PumpInfo {
     pub mint: String,
@@ -532,7 +544,7 @@ pub async fn get_bonding_curve_account(
                     let token_balance = match &token_balance_result {
                         Ok(balance) => {
                             match balance.ui_amount {
-                                Some(amount) => (amount * (10f64.powf(balance.decimals as f64))) as u64,
+                                Some(amount) => math::ui_to_raw(amount, balance.decimals),
                                 None => 0,
                             }
                         },
@@ -552,7 +564,7 @@ pub async fn get_bonding_curve_account(
             let token_balance = match &token_balance_result {
                 Ok(balance) => {
                     match balance.ui_amount {
-                        Some(amount) => (amount * (10f64.powf(balance.decimals as f64))) as u64,
+                        Some(amount) => math::ui_to_raw(amount, balance.decimals),
                         None => 0,
                     }
                 },
@@ -573,30 +585,77 @@ pub async fn get_bonding_curve_account(
     ))
 }
 
-fn max_amount_with_slippage(input_amount: u64, slippage_bps: u64) -> u64 {
-    input_amount
-        .checked_mul(slippage_bps.checked_add(TEN_THOUSAND).unwrap())
-        .unwrap()
-        .checked_div(TEN_THOUSAND)
-        .unwrap()
-}
-
 pub fn get_pda(mint: &Pubkey, program_id: &Pubkey ) -> Result<Pubkey> {
     let seeds = [b"bonding-curve".as_ref(), mint.as_ref()];
     let (bonding_curve, _bump) = Pubkey::find_program_address(&seeds, program_id);
     Ok(bonding_curve)
 }
 
-/// Get the global volume accumulator PDA
+/// Get the global volume accumulator PDA (cached)
 pub fn get_global_volume_accumulator_pda(program_id: &Pubkey) -> Result<Pubkey> {
-    let seeds = [GLOBAL_VOLUME_ACCUMULATOR_SEED];
-    let (pda, _bump) = Pubkey::find_program_address(&seeds, program_id);
-    Ok(pda)
+    Ok(cached_address("global_volume_accumulator", &Pubkey::default(), program_id, || {
+        Pubkey::find_program_address(&[GLOBAL_VOLUME_ACCUMULATOR_SEED], program_id).0
+    }))
 }
 
-/// Get the user volume accumulator PDA for a specific user
+/// Get the user volume accumulator PDA for a specific user (cached)
 pub fn get_user_volume_accumulator_pda(user: &Pubkey, program_id: &Pubkey) -> Result<Pubkey> {
-    let seeds = [USER_VOLUME_ACCUMULATOR_SEED, user.as_ref()];
-    let (pda, _bump) = Pubkey::find_program_address(&seeds, program_id);
-    Ok(pda)
-}
\ No newline at end of file
+    Ok(cached_address("user_volume_accumulator", user, program_id, || {
+        Pubkey::find_program_address(&[USER_VOLUME_ACCUMULATOR_SEED, user.as_ref()], program_id).0
+    }))
+}
+
+/// PDA collecting a creator's fees across all their coins (cached)
+pub fn get_creator_vault_pda(creator: &Pubkey, program_id: &Pubkey) -> Pubkey {
+    cached_address("creator_vault", creator, program_id, || {
+        Pubkey::find_program_address(&[b"creator-vault", creator.as_ref()], program_id).0
+    })
+}
+
+/// Owner's WSOL associated token account (cached)
+pub fn get_wsol_ata(owner: &Pubkey) -> Pubkey {
+    cached_address("wsol_ata", owner, &spl_token::ID, || {
+        get_associated_token_address(owner, &spl_token::native_mint::ID)
+    })
+}
+
+/// Derive and cache every address a PumpFun swap needs that doesn't depend on the mint
+pub fn prederive_static_addresses(owner: &Pubkey) -> Result<()> {
+    let pump_program = Pubkey::from_str(PUMP_FUN_PROGRAM)?;
+    get_global_volume_accumulator_pda(&pump_program)?;
+    get_user_volume_accumulator_pda(owner, &pump_program)?;
+    get_wsol_ata(owner);
+    Lazy::force(&PUMP_FEE_CONFIG_PUBKEY);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cached_addresses_match_a_fresh_derivation() {
+        let owner = Pubkey::new_unique();
+        let pump_program = Pubkey::from_str(PUMP_FUN_PROGRAM).unwrap();
+        prederive_static_addresses(&owner).unwrap();
+
+        let (global, _) = Pubkey::find_program_address(&[GLOBAL_VOLUME_ACCUMULATOR_SEED], &pump_program);
+        let (user, _) = Pubkey::find_program_address(&[USER_VOLUME_ACCUMULATOR_SEED, owner.as_ref()], &pump_program);
+        assert_eq!(get_global_volume_accumulator_pda(&pump_program).unwrap(), global);
+        assert_eq!(get_user_volume_accumulator_pda(&owner, &pump_program).unwrap(), user);
+        assert_eq!(get_wsol_ata(&owner), get_associated_token_address(&owner, &spl_token::native_mint::ID));
+        assert_eq!(*PUMP_FEE_CONFIG_PUBKEY, Pubkey::from_str(PUMP_FEE_CONFIG).unwrap());
+    }
+
+    #[test]
+    fn cached_addresses_are_per_owner() {
+        let first = Pubkey::new_unique();
+        let second = Pubkey::new_unique();
+        let pump_program = Pubkey::from_str(PUMP_FUN_PROGRAM).unwrap();
+        assert_ne!(get_wsol_ata(&first), get_wsol_ata(&second));
+        assert_ne!(
+            get_user_volume_accumulator_pda(&first, &pump_program).unwrap(),
+            get_user_volume_accumulator_pda(&second, &pump_program).unwrap()
+        );
+    }
+}
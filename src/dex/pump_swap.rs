@@ -14,13 +14,15 @@ use anchor_client::solana_sdk::{
 use crate::engine::transaction_parser::DexType;
 use spl_associated_token_account::{
     get_associated_token_address,
+    get_associated_token_address_with_program_id,
     instruction::create_associated_token_account_idempotent
 };
-use spl_token::{ui_amount_to_amount, instruction::sync_native};
+use spl_token::instruction::sync_native;
 
 use crate::{
     common::{config::SwapConfig, logger::Logger},
-    core::token,
+    core::{mint_inspector, token},
+    dex::math,
     engine::swap::{SwapDirection, SwapInType},
 };
 
@@ -34,7 +36,6 @@ use crate::{
 // Constants - moved to lazy_static for single initialization
 lazy_static::lazy_static! {
     static ref TOKEN_PROGRAM: Pubkey = Pubkey::from_str("TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA").unwrap();
-    static ref TOKEN_2022_PROGRAM: Pubkey = Pubkey::from_str("TokenzQdBNbLqP5VEhdkAS6EPFLC1PHnBqCXEpPxuEb").unwrap();
     static ref ASSOCIATED_TOKEN_PROGRAM: Pubkey = Pubkey::from_str("ATokenGPvbdGVxr1b2hvZbsiqW5xWH25efTNsLJA8knL").unwrap();
     static ref PUMP_SWAP_PROGRAM: Pubkey = Pubkey::from_str("pAMMBay6oceH9fJKBRHGP5D4bD4sWpmSwMn52FMfXEA").unwrap();
     static ref PUMP_GLOBAL_CONFIG: Pubkey = Pubkey::from_str("ADyA8hdefvWN2dbGGWFotbzWxrAvLW83WG6QCVXvJKqw").unwrap();
@@ -43,8 +44,8 @@ lazy_static::lazy_static! {
     static ref PUMP_SWAP_FEE_PROGRAM: Pubkey = Pubkey::from_str("pfeeUxB6jkeY1Hxd7CsFCAjcbHA9rWtchMGdZ6VojVZ").unwrap();
     static ref PUMP_EVENT_AUTHORITY: Pubkey = Pubkey::from_str("GS4CU59F31iL7aR2Q8zVS8DRrcRnXX1yjQ66TqNVQnaR").unwrap();
     static ref SOL_MINT: Pubkey = Pubkey::from_str("So11111111111111111111111111111111111111112").unwrap();
-    static ref BUY_DISCRIMINATOR: [u8; 8] = [102, 6, 61, 18, 1, 218, 235, 234];
-    static ref SELL_DISCRIMINATOR: [u8; 8] = [51, 230, 133, 164, 1, 127, 131, 173];
+    pub static ref BUY_DISCRIMINATOR: [u8; 8] = [102, 6, 61, 18, 1, 218, 235, 234];
+    pub static ref SELL_DISCRIMINATOR: [u8; 8] = [51, 230, 133, 164, 1, 127, 131, 173];
 }
 
 // Volume accumulator seed constants
@@ -65,7 +66,6 @@ fn get_user_volume_accumulator_pda(user: &Pubkey) -> Result<Pubkey> {
     Ok(pda)
 }
 
-const TEN_THOUSAND: u64 = 10000;
 
 pub struct PumpSwap {
     pub keypair: Arc<Keypair>,
@@ -156,13 +156,14 @@ impl PumpSwap {
         );
         
         logger.log(format!("Using parsed data - Pool: {}, Coin Creator: {}, Virtual SOL: {}, Virtual Tokens: {}, Price: {}, Reverse: {}", 
-            pool_id, coin_creator, trade_info.virtual_sol_reserves, trade_info.virtual_token_reserves, token_price, swap_config.reverse));
+            pool_id, coin_creator, trade_info.virtual_sol_reserves, trade_info.virtual_token_reserves, token_price, trade_info.is_reverse_when_pump_swap));
         
         // Prepare swap parameters
         // The 'reverse' flag indicates pool structure (WSOL as base vs token as base)
         // It does NOT change the instruction type - Buy stays Buy, Sell stays Sell
         let swap_direction = swap_config.swap_direction.clone();
-        let reverse = swap_config.reverse;
+        // Pool layout comes from the event, not the config
+        let reverse = trade_info.is_reverse_when_pump_swap;
         let discriminator = match (swap_direction.clone(), reverse) {
             (SwapDirection::Buy, true) => *SELL_DISCRIMINATOR,
             (SwapDirection::Buy, false) => *BUY_DISCRIMINATOR,
@@ -291,12 +292,13 @@ impl PumpSwap {
             trade_info.virtual_token_reserves,
         );
         
-        logger.log(format!("Using cached balance for PumpSwap - Pool: {}, Price: {}, Reverse: {}", pool_id, token_price, swap_config.reverse));
+        logger.critical(format!("Using cached balance for PumpSwap - Pool: {}, Price: {}, Reverse: {}", pool_id, token_price, trade_info.is_reverse_when_pump_swap));
         
         // Prepare swap parameters
         // The 'reverse' flag indicates pool structure, NOT the action type
         let swap_direction = swap_config.swap_direction.clone();
-        let reverse = swap_config.reverse;
+        // Pool layout comes from the event, not the config
+        let reverse = trade_info.is_reverse_when_pump_swap;
         let discriminator = match (swap_direction.clone(), reverse) {
             (SwapDirection::Buy, true) => *SELL_DISCRIMINATOR,
             (SwapDirection::Buy, false) => *BUY_DISCRIMINATOR,
@@ -333,6 +335,7 @@ impl PumpSwap {
                     coin_creator,
                     swap_config.amount_in,
                     swap_config.in_type,
+                    swap_config.buy_slippage,
                     reverse,
                     cached_balance.unwrap(),
                     &mut instructions,
@@ -420,23 +423,24 @@ impl PumpSwap {
         is_reverse: bool,
         instructions: &mut Vec<Instruction>,
     ) -> Result<(u64, u64, Vec<AccountMeta>)> {
-        let amount_specified = ui_amount_to_amount(amount_in, 9);
+        let amount_specified = math::ui_to_raw(amount_in, 9);
         
-        // Calculate max_quote_amount_in first (with slippage) - this is what we'll use for transfer and swap
-        let max_quote_amount_in = max_amount_with_slippage(amount_specified, slippage_bps);
-        
-        // Use virtual reserves for calculation - return base_amount_out directly (without slippage)
-        // Matching working sample: slippage is handled via max_quote_amount_in for input
-        let base_amount_out = Self::calculate_buy_token_amount(
+        // Expected tokens for the configured SOL at the event's reserves
+        let tokens_out = Self::calculate_buy_token_amount(
             amount_specified,
             trade_info.virtual_sol_reserves,
             trade_info.virtual_token_reserves,
         );
         
-        // For reverse vs normal pools, the amounts structure differs:
-        // Normal pool (base=token, quote=SOL): instruction expects (base_amount_out, max_sol_in)
-        // Reverse pool (base=SOL, quote=token): after swap expects (max_sol_in, base_amount_out)
-        let quote_amount = max_quote_amount_in;
+        // Normal pool (base=token, quote=SOL): `buy` takes an exact token amount out and caps the SOL in,
+        // so slippage pads the SOL. Reverse pool (base=SOL, quote=token): `sell` takes an exact SOL amount
+        // in and floors the tokens out, so slippage trims the tokens.
+        // Both come back as (token amount, SOL amount); the caller swaps them for reverse pools.
+        let (base_amount_out, quote_amount) = if is_reverse {
+            (math::min_amount_with_slippage(tokens_out, slippage_bps), amount_specified)
+        } else {
+            (tokens_out, math::max_amount_with_slippage(amount_specified, slippage_bps)?)
+        };
         
         // Validate amounts before proceeding
         if base_amount_out == 0 {
@@ -455,10 +459,9 @@ impl PumpSwap {
             ));
         }
 
-        let out_ata = get_associated_token_address(&owner, &mint);
-        
         // Get the correct token program for the mint
-        let token_program = self.get_token_program(&mint).await.unwrap_or(*TOKEN_PROGRAM);
+        let token_program = self.get_token_program(&mint).await;
+        let out_ata = get_associated_token_address_with_program_id(&owner, &mint, &token_program);
         
         // Always add Associated Token Account Program createIdempotent instruction
         // This reduces latency by eliminating token account existence checks
@@ -470,40 +473,38 @@ impl PumpSwap {
         ));
         self.cache_token_account(out_ata).await;
         
-        // For buying, we need to wrap SOL to WSOL (only for normal mode)
+        // Either pool layout spends WSOL, so wrap the most the swap can take:
         // 1. Create WSOL account (idempotent - safe if already exists)
         // 2. Transfer SOL to WSOL account  
         // 3. Sync native to update WSOL balance
-        if !is_reverse {
-            let wsol_ata = get_associated_token_address(&owner, &SOL_MINT);
-            
-            // Always create WSOL account (idempotent)
-            instructions.push(create_associated_token_account_idempotent(
-                &owner,
-                &owner,
-                &SOL_MINT,
-                &TOKEN_PROGRAM,
-            ));
-            
-            // Transfer SOL to WSOL account for the swap - use max_quote_amount_in to match quote_amount in swap
-            instructions.push(system_instruction::transfer(
-                &owner,
-                &wsol_ata,
-                max_quote_amount_in,
-            ));
-            
-            // Sync native to convert SOL to WSOL tokens
-            instructions.push(sync_native(
-                &TOKEN_PROGRAM,
-                &wsol_ata,
-            )?);
-            
-            // Cache the WSOL account
-            self.cache_token_account(wsol_ata).await;
-        }
+        let wsol_ata = get_associated_token_address(&owner, &SOL_MINT);
+        
+        // Always create WSOL account (idempotent)
+        instructions.push(create_associated_token_account_idempotent(
+            &owner,
+            &owner,
+            &SOL_MINT,
+            &TOKEN_PROGRAM,
+        ));
+        
+        // Transfer SOL to WSOL account for the swap - the SOL side of the instruction amounts
+        instructions.push(system_instruction::transfer(
+            &owner,
+            &wsol_ata,
+            quote_amount,
+        ));
+        
+        // Sync native to convert SOL to WSOL tokens
+        instructions.push(sync_native(
+            &TOKEN_PROGRAM,
+            &wsol_ata,
+        )?);
+        
+        // Cache the WSOL account
+        self.cache_token_account(wsol_ata).await;
         
         // Create accounts using parsed pool_id and coin_creator
-        let pool_base_account = get_associated_token_address(&pool_id, &mint);
+        let pool_base_account = get_associated_token_address_with_program_id(&pool_id, &mint, &token_program);
         let pool_quote_account = get_associated_token_address(&pool_id, &SOL_MINT);
         
         // Get volume accumulator PDAs
@@ -516,8 +517,9 @@ impl PumpSwap {
                 pool_id,
                 owner,
                 mint,
+                token_program,
                 out_ata,
-                get_associated_token_address(&owner, &SOL_MINT),
+                wsol_ata,
                 pool_base_account,
                 pool_quote_account,
                 coin_creator,
@@ -531,8 +533,9 @@ impl PumpSwap {
                 pool_id,
                 owner,
                 mint,
+                token_program,
                 out_ata,
-                get_associated_token_address(&owner, &SOL_MINT),
+                wsol_ata,
                 pool_base_account,
                 pool_quote_account,
                 coin_creator,
@@ -540,9 +543,6 @@ impl PumpSwap {
             )?
         };
         
-        // Return amounts for buy operation:
-        // For normal pool: (base_amount_out, quote_amount) = (token_out, max_sol_in) - no swap needed
-        // For reverse pool: (base_amount_out, quote_amount) = (token_out, max_sol_in) - will be swapped in main function
         Ok((base_amount_out, quote_amount, accounts))
     }
 
@@ -556,11 +556,13 @@ impl PumpSwap {
         coin_creator: Pubkey,
         amount_in: f64,
         in_type: SwapInType,
+        slippage_bps: u64,
         is_reverse: bool,
         cached_balance: (u64, u8), // (raw_balance, decimals)
         instructions: &mut Vec<Instruction>,
     ) -> Result<(u64, u64, Vec<AccountMeta>)> {
-        let in_ata = get_associated_token_address(&owner, &mint);
+        let token_program = self.get_token_program(&mint).await;
+        let in_ata = get_associated_token_address_with_program_id(&owner, &mint, &token_program);
         let (balance_raw, token_decimals) = cached_balance;
         
         // For sell operations, ensure WSOL account exists (where user receives SOL)
@@ -582,14 +584,9 @@ impl PumpSwap {
         
         // Calculate amount to sell from cached balance
         let amount = match in_type {
-            SwapInType::Qty => ui_amount_to_amount(amount_in, token_decimals),
+            SwapInType::Qty => math::ui_to_raw(amount_in, token_decimals),
             SwapInType::Pct => {
-                let pct = amount_in.min(1.0);
-                if pct == 1.0 {
-                    balance_raw
-                } else {
-                    (pct * balance_raw as f64) as u64
-                }
+                math::fraction_of(balance_raw, amount_in)
             }
         };
         
@@ -608,23 +605,29 @@ impl PumpSwap {
             trade_info.virtual_token_reserves,
         );
         
-        // Set minimum SOL output to 1 to ensure selling must work (no slippage needed)
-        // This allows the transaction to succeed regardless of price movement
-        let min_quote_amount_out = 1;
+        // Normal pool: `sell` floors the SOL out, set to 1 to ensure selling must work (no slippage needed).
+        // Reverse pool: `buy` takes an exact SOL amount out and caps the tokens in at the balance being
+        // sold, so it needs a real target; the slippage keeps it reachable after the price moves.
+        let min_quote_amount_out = if is_reverse {
+            math::min_amount_with_slippage(quote_amount_out, slippage_bps).max(1)
+        } else {
+            1
+        };
         
         println!("Sell calculation - Tokens in: {} (from cached balance: {}), Expected SOL out: {}, Min SOL out: {} (no slippage, set to 1 to ensure sell works), Virtual SOL: {}, Virtual Tokens: {}", 
             amount, balance_raw, quote_amount_out, min_quote_amount_out, trade_info.virtual_sol_reserves, trade_info.virtual_token_reserves);
         
         // Create accounts using parsed pool_id and coin_creator
-        let pool_base_account = get_associated_token_address(&pool_id, &mint);
+        let pool_base_account = get_associated_token_address_with_program_id(&pool_id, &mint, &token_program);
         let pool_quote_account = get_associated_token_address(&pool_id, &SOL_MINT);
         
         let accounts = create_sell_accounts(
             pool_id,
             owner,
             mint,
+            token_program,
             in_ata,
-            get_associated_token_address(&owner, &SOL_MINT),
+            wsol_ata,
             pool_base_account,
             pool_quote_account,
             coin_creator,
@@ -641,26 +644,14 @@ impl PumpSwap {
         // Cache removed - no-op
     }
     
-    /// Helper method to determine the correct token program for a mint
-    async fn get_token_program(&self, mint: &Pubkey) -> Result<Pubkey> {
-        if let Some(rpc_client) = &self.rpc_nonblocking_client {
-            match rpc_client.get_account(mint).await {
-                Ok(account) => {
-                    if account.owner == *TOKEN_2022_PROGRAM {
-                        Ok(*TOKEN_2022_PROGRAM)
-                    } else {
-                        Ok(*TOKEN_PROGRAM)
-                    }
-                },
-                Err(_) => {
-                    // Default to TOKEN_PROGRAM if we can't fetch the account
-                    Ok(*TOKEN_PROGRAM)
-                }
-            }
-        } else {
-            // Default to TOKEN_PROGRAM if no RPC client
-            Ok(*TOKEN_PROGRAM)
-        }
+    /// Token program of `mint`, through the shared mint_inspector cache
+    /// Falls back to the classic program when the mint can't be read, as graduated PumpFun tokens mostly are.
+    async fn get_token_program(&self, mint: &Pubkey) -> Pubkey {
+        let resolved = match &self.rpc_nonblocking_client {
+            Some(rpc_client) => mint_inspector::resolve_token_program(rpc_client, mint).await.ok(),
+            None => mint_inspector::cached_token_program(mint),
+        };
+        resolved.unwrap_or(*TOKEN_PROGRAM)
     }
 
     /// Calculate token amount out for buy using virtual reserves (PumpSwap AMM formula)
@@ -669,24 +660,9 @@ impl PumpSwap {
         virtual_sol_reserves: u64,
         virtual_token_reserves: u64,
     ) -> u64 {
-        if sol_amount_in == 0 || virtual_sol_reserves == 0 || virtual_token_reserves == 0 {
-            return 0;
-        }
-        
         // PumpSwap AMM formula for buy (same as PumpFun):
         // tokens_out = (sol_in * virtual_token_reserves) / (virtual_sol_reserves + sol_in)
-        let sol_amount_in_u128 = sol_amount_in as u128;
-        let virtual_sol_reserves_u128 = virtual_sol_reserves as u128;
-        let virtual_token_reserves_u128 = virtual_token_reserves as u128;
-        
-        let numerator = sol_amount_in_u128.saturating_mul(virtual_token_reserves_u128);
-        let denominator = virtual_sol_reserves_u128.saturating_add(sol_amount_in_u128);
-        
-        if denominator == 0 {
-            return 0;
-        }
-        
-        numerator.checked_div(denominator).unwrap_or(0) as u64
+        math::constant_product_out(sol_amount_in, virtual_sol_reserves, virtual_token_reserves)
     }
 
     /// Calculate SOL amount out for sell using virtual reserves (PumpSwap AMM formula)
@@ -695,24 +671,9 @@ impl PumpSwap {
         virtual_sol_reserves: u64,
         virtual_token_reserves: u64,
     ) -> u64 {
-        if token_amount_in == 0 || virtual_sol_reserves == 0 || virtual_token_reserves == 0 {
-            return 0;
-        }
-        
         // PumpSwap constant product AMM formula for sell:
         // sol_out = (token_in * virtual_sol_reserves) / (virtual_token_reserves + token_in)
-        let token_amount_in_u128 = token_amount_in as u128;
-        let virtual_sol_reserves_u128 = virtual_sol_reserves as u128;
-        let virtual_token_reserves_u128 = virtual_token_reserves as u128;
-        
-        let numerator = token_amount_in_u128.saturating_mul(virtual_sol_reserves_u128);
-        let denominator = virtual_token_reserves_u128.saturating_add(token_amount_in_u128);
-        
-        if denominator == 0 {
-            return 0;
-        }
-        
-        numerator.checked_div(denominator).unwrap_or(0) as u64
+        math::constant_product_out(token_amount_in, virtual_token_reserves, virtual_sol_reserves)
     }
 
     /// Calculate price using virtual reserves
@@ -731,7 +692,7 @@ impl PumpSwap {
 
 /// Minimal pool info for price queries only (returns pool_id, base_reserve, quote_reserve)
 /// CRITICAL: Uses non-blocking RPC client to avoid blocking async runtime
-async fn get_pool_info_for_price(
+pub async fn get_pool_info_for_price(
     rpc_client: Arc<anchor_client::solana_client::nonblocking::rpc_client::RpcClient>,
     mint: Pubkey,
 ) -> Result<(Pubkey, u64, u64)> {
@@ -810,19 +771,12 @@ async fn get_pool_info_for_price(
     Ok((pool_id, base_balance, quote_balance))
 }
 
-#[inline]
-fn max_amount_with_slippage(input_amount: u64, slippage_bps: u64) -> u64 {
-    input_amount
-        .saturating_mul(TEN_THOUSAND.saturating_add(slippage_bps))
-        .checked_div(TEN_THOUSAND)
-        .unwrap_or(input_amount)
-}
-
 // Optimized account creation with const pubkeys
 fn create_buy_accounts(
     pool_id: Pubkey,
     user: Pubkey,
     token_mint: Pubkey,
+    token_program: Pubkey,
     user_base_token_account: Pubkey,
     wsol_account: Pubkey,
     pool_base_token_account: Pubkey,
@@ -852,7 +806,8 @@ fn create_buy_accounts(
     
     // CRITICAL FIX: coin_creator_vault_ata must use quote_mint (SOL_MINT for normal buy, token_mint for reverse)
     // This matches the working sample which uses &quote_mint
-    let coin_creator_vault_ata = get_associated_token_address(&coin_creator_vault_authority, &quote_mint);
+    let (base_token_program, quote_token_program) = if is_reverse { (*TOKEN_PROGRAM, token_program) } else { (token_program, *TOKEN_PROGRAM) };
+    let coin_creator_vault_ata = get_associated_token_address_with_program_id(&coin_creator_vault_authority, &quote_mint, &quote_token_program);
     
     Ok(vec![
         AccountMeta::new_readonly(pool_id, false),
@@ -865,9 +820,9 @@ fn create_buy_accounts(
         AccountMeta::new(pool_account_1, false), // Position 7: Changes based on is_reverse
         AccountMeta::new(pool_account_2, false), // Position 8: Changes based on is_reverse
         AccountMeta::new_readonly(*PUMP_SWAP_FEE_RECIPIENT, false),
-        AccountMeta::new(get_associated_token_address(&PUMP_SWAP_FEE_RECIPIENT, &quote_mint), false),
-        AccountMeta::new_readonly(*TOKEN_PROGRAM, false),
-        AccountMeta::new_readonly(*TOKEN_PROGRAM, false),
+        AccountMeta::new(get_associated_token_address_with_program_id(&PUMP_SWAP_FEE_RECIPIENT, &quote_mint, &quote_token_program), false),
+        AccountMeta::new_readonly(base_token_program, false),
+        AccountMeta::new_readonly(quote_token_program, false),
         AccountMeta::new_readonly(system_program::id(), false),
         AccountMeta::new_readonly(*ASSOCIATED_TOKEN_PROGRAM, false),
         AccountMeta::new_readonly(*PUMP_EVENT_AUTHORITY, false),
@@ -886,6 +841,7 @@ fn create_sell_accounts(
     pool_id: Pubkey,
     user: Pubkey,
     token_mint: Pubkey,
+    token_program: Pubkey,
     user_base_token_account: Pubkey,
     wsol_account: Pubkey,
     pool_base_token_account: Pubkey,
@@ -913,7 +869,8 @@ fn create_sell_accounts(
     };
     
     // CRITICAL FIX: coin_creator_vault_ata must use quote_mint (matches working sample)
-    let coin_creator_vault_ata = get_associated_token_address(&coin_creator_vault_authority, &quote_mint);
+    let (base_token_program, quote_token_program) = if is_reverse { (*TOKEN_PROGRAM, token_program) } else { (token_program, *TOKEN_PROGRAM) };
+    let coin_creator_vault_ata = get_associated_token_address_with_program_id(&coin_creator_vault_authority, &quote_mint, &quote_token_program);
     
     Ok(vec![
         AccountMeta::new_readonly(pool_id, false),
@@ -926,9 +883,9 @@ fn create_sell_accounts(
         AccountMeta::new(pool_account_1, false), // Position 7: Changes based on is_reverse
         AccountMeta::new(pool_account_2, false), // Position 8: Changes based on is_reverse
         AccountMeta::new_readonly(*PUMP_SWAP_FEE_RECIPIENT, false),
-        AccountMeta::new(get_associated_token_address(&PUMP_SWAP_FEE_RECIPIENT, &quote_mint), false),
-        AccountMeta::new_readonly(*TOKEN_PROGRAM, false),
-        AccountMeta::new_readonly(*TOKEN_PROGRAM, false),
+        AccountMeta::new(get_associated_token_address_with_program_id(&PUMP_SWAP_FEE_RECIPIENT, &quote_mint, &quote_token_program), false),
+        AccountMeta::new_readonly(base_token_program, false),
+        AccountMeta::new_readonly(quote_token_program, false),
         AccountMeta::new_readonly(system_program::id(), false),
         AccountMeta::new_readonly(*ASSOCIATED_TOKEN_PROGRAM, false),
         AccountMeta::new_readonly(*PUMP_EVENT_AUTHORITY, false),
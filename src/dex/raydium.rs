@@ -0,0 +1,226 @@
+use std::{str::FromStr, sync::Arc};
+use anyhow::{anyhow, Result};
+use colored::Colorize;
+use dashmap::DashMap;
+use once_cell::sync::Lazy;
+use solana_sdk::{
+    instruction::{AccountMeta, Instruction},
+    pubkey::Pubkey,
+    signature::Keypair,
+    signer::Signer,
+    system_instruction,
+};
+use spl_associated_token_account::{
+    get_associated_token_address, instruction::create_associated_token_account_idempotent,
+};
+use spl_token::instruction::{close_account, sync_native};
+use crate::{
+    common::{config::SwapConfig, logger::Logger},
+    dex::math,
+    engine::{swap::{SwapDirection, SwapInType}, transaction_parser::{DexType, TradeInfoFromToken}},
+};
+
+pub const RAYDIUM_AMM_PROGRAM: &str = "675kPX9MHTjS2zt1qfr1NYHuzeLXfQM9H24wFSUt1Mp8";
+/// PDA that owns every AMM v4 vault
+pub const RAYDIUM_AMM_AUTHORITY: &str = "5Q544fKrFoe6tsEbD7S8EmxGTJYAKtTVhAW5Q5pge4j1";
+/// `SwapBaseInV2`: the swap without the OpenBook market accounts
+const SWAP_BASE_IN_V2: u8 = 16;
+/// Raydium charges 25 bps on the input side
+pub(crate) const SWAP_FEE_BPS: u64 = 25;
+
+/// AmmInfo offsets (752-byte account)
+const COIN_DECIMALS_OFFSET: usize = 32;
+const PC_DECIMALS_OFFSET: usize = 40;
+const COIN_VAULT_OFFSET: usize = 336;
+const PC_VAULT_OFFSET: usize = 368;
+const COIN_MINT_OFFSET: usize = 400;
+const PC_MINT_OFFSET: usize = 432;
+const AMM_INFO_LEN: usize = 752;
+
+/// Vaults and mints of an AMM v4 pool; they never change once the pool exists
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct RaydiumPoolKeys {
+    pub amm: Pubkey,
+    pub coin_vault: Pubkey,
+    pub pc_vault: Pubkey,
+    pub coin_mint: Pubkey,
+    pub pc_mint: Pubkey,
+    pub coin_decimals: u8,
+    pub pc_decimals: u8,
+}
+
+impl RaydiumPoolKeys {
+    /// Decode an AmmInfo account
+    pub fn decode(amm: Pubkey, data: &[u8]) -> Result<Self> {
+        if data.len() < AMM_INFO_LEN {
+            return Err(anyhow!("AMM account {} is {} bytes, expected {}", amm, data.len(), AMM_INFO_LEN));
+        }
+        let pubkey_at = |offset: usize| Pubkey::try_from(&data[offset..offset + 32]).map_err(|e| anyhow!("{}", e));
+        Ok(Self {
+            amm,
+            coin_vault: pubkey_at(COIN_VAULT_OFFSET)?,
+            pc_vault: pubkey_at(PC_VAULT_OFFSET)?,
+            coin_mint: pubkey_at(COIN_MINT_OFFSET)?,
+            pc_mint: pubkey_at(PC_MINT_OFFSET)?,
+            coin_decimals: data[COIN_DECIMALS_OFFSET],
+            pc_decimals: data[PC_DECIMALS_OFFSET],
+        })
+    }
+
+    /// (token mint, token decimals, token vault, WSOL vault); errors for pools without a WSOL side
+    fn token_side(&self) -> Result<(Pubkey, u8, Pubkey, Pubkey)> {
+        let wsol = spl_token::native_mint::ID;
+        if self.pc_mint == wsol {
+            Ok((self.coin_mint, self.coin_decimals, self.coin_vault, self.pc_vault))
+        } else if self.coin_mint == wsol {
+            Ok((self.pc_mint, self.pc_decimals, self.pc_vault, self.coin_vault))
+        } else {
+            Err(anyhow!("Pool {} has no WSOL side", self.amm))
+        }
+    }
+}
+
+/// Pool keys by AMM id, fetched once per pool
+static POOL_KEYS: Lazy<DashMap<Pubkey, RaydiumPoolKeys>> = Lazy::new(DashMap::new);
+
+/// `raw` token units rescaled between decimal precisions
+fn rescale(raw: u64, from_decimals: u8, to_decimals: u8) -> u64 {
+    if from_decimals >= to_decimals {
+        raw / 10u64.pow(u32::from(from_decimals - to_decimals).min(19))
+    } else {
+        raw.saturating_mul(10u64.pow(u32::from(to_decimals - from_decimals).min(19)))
+    }
+}
+
+pub struct Raydium {
+    pub rpc_nonblocking_client: Arc<solana_client::nonblocking::rpc_client::RpcClient>,
+    pub keypair: Arc<Keypair>,
+    pub rpc_client: Option<Arc<solana_client::rpc_client::RpcClient>>,
+}
+
+impl Raydium {
+    pub fn new(
+        rpc_nonblocking_client: Arc<solana_client::nonblocking::rpc_client::RpcClient>,
+        rpc_client: Arc<solana_client::rpc_client::RpcClient>,
+        keypair: Arc<Keypair>,
+    ) -> Self {
+        Self {
+            rpc_nonblocking_client,
+            keypair,
+            rpc_client: Some(rpc_client),
+        }
+    }
+
+    async fn pool_keys(&self, amm: Pubkey) -> Result<RaydiumPoolKeys> {
+        if let Some(keys) = POOL_KEYS.get(&amm) {
+            return Ok(*keys);
+        }
+        let data = self.rpc_nonblocking_client.get_account_data(&amm).await
+            .map_err(|e| anyhow!("Failed to read AMM account {}: {}", amm, e))?;
+        let keys = RaydiumPoolKeys::decode(amm, &data)?;
+        POOL_KEYS.insert(amm, keys);
+        Ok(keys)
+    }
+
+    /// Raw token balance to sell: TOKEN_HOLDINGS first, the token account otherwise
+    async fn sell_amount(&self, mint: &str, token_ata: &Pubkey, decimals: u8, swap_config: &SwapConfig) -> Result<u64> {
+        use crate::engine::sniper::TOKEN_HOLDINGS;
+        if swap_config.in_type == SwapInType::Qty {
+            return Ok(math::ui_to_raw(swap_config.amount_in, decimals));
+        }
+        let held = match TOKEN_HOLDINGS.get(mint) {
+            Some(bought_info) => math::ui_to_raw(bought_info.current_amount, decimals),
+            None => {
+                let account = self.rpc_nonblocking_client.get_token_account(token_ata).await
+                    .map_err(|e| anyhow!("Failed to get token account: {}", e))?
+                    .ok_or_else(|| anyhow!("Token account does not exist for mint {}", mint))?;
+                account.token_amount.amount.parse::<u64>()
+                    .map_err(|e| anyhow!("Failed to parse token amount: {}", e))?
+            }
+        };
+        Ok(math::fraction_of(held, swap_config.amount_in.min(1.0)).max(1))
+    }
+
+    /// Build a SwapBaseInV2 against the pool in `trade_info.pool_id`
+    /// SOL is wrapped into the WSOL ATA for buys and every swap closes it afterwards, as PumpSwap does.
+    pub async fn build_swap_from_parsed_data(
+        &self,
+        trade_info: &TradeInfoFromToken,
+        swap_config: SwapConfig,
+    ) -> Result<(Arc<Keypair>, Vec<Instruction>, f64)> {
+        let logger = Logger::new("[RAYDIUM-SWAP-FROM-PARSED] => ".blue().to_string());
+        if trade_info.dex_type != DexType::RaydiumAmm {
+            return Err(anyhow!("Invalid transaction type, expected RaydiumAmm ::{:?}", trade_info.dex_type));
+        }
+        let amm = Pubkey::from_str(&trade_info.pool_id)
+            .map_err(|e| anyhow!("Invalid Raydium pool id '{}': {}", trade_info.pool_id, e))?;
+        let keys = self.pool_keys(amm).await?;
+        let (token_mint, token_decimals, token_vault, sol_vault) = keys.token_side()?;
+        if token_mint.to_string() != trade_info.mint {
+            return Err(anyhow!("Pool {} trades {}, not {}", amm, token_mint, trade_info.mint));
+        }
+
+        let owner = self.keypair.pubkey();
+        let token_program = spl_token::ID;
+        let native_mint = spl_token::native_mint::ID;
+        let wsol_ata = get_associated_token_address(&owner, &native_mint);
+        let token_ata = get_associated_token_address(&owner, &token_mint);
+        // The parser keeps reserves at 6 decimals
+        let sol_reserves = trade_info.virtual_sol_reserves;
+        let token_reserves = rescale(trade_info.virtual_token_reserves, 6, token_decimals);
+
+        let mut instructions = vec![
+            create_associated_token_account_idempotent(&owner, &owner, &native_mint, &token_program),
+        ];
+        let (amount_in, minimum_out, source, destination, source_vault, destination_vault) = match swap_config.swap_direction {
+            SwapDirection::Buy => {
+                let lamports = math::ui_to_raw(swap_config.amount_in, spl_token::native_mint::DECIMALS);
+                let after_fee = math::min_amount_with_slippage(lamports, SWAP_FEE_BPS);
+                let expected = math::constant_product_out(after_fee, sol_reserves, token_reserves);
+                let minimum_out = math::min_amount_with_slippage(expected, swap_config.buy_slippage);
+                logger.critical(format!("Buy calculation - SOL in: {}, Tokens out: {}, Min out: {}, Pool SOL: {}, Pool tokens: {}",
+                    lamports, expected, minimum_out, sol_reserves, token_reserves));
+                instructions.push(system_instruction::transfer(&owner, &wsol_ata, lamports));
+                instructions.push(sync_native(&token_program, &wsol_ata)?);
+                instructions.push(create_associated_token_account_idempotent(&owner, &owner, &token_mint, &token_program));
+                (lamports, minimum_out, wsol_ata, token_ata, sol_vault, token_vault)
+            }
+            SwapDirection::Sell => {
+                let token_amount = self.sell_amount(&trade_info.mint, &token_ata, token_decimals, &swap_config).await?;
+                let expected = math::constant_product_out(math::min_amount_with_slippage(token_amount, SWAP_FEE_BPS), token_reserves, sol_reserves);
+                // Sells accept any output, like PumpFun sells
+                logger.critical(format!("Sell calculation - Tokens in: {}, Expected SOL out: {}, Min SOL out: 1 (slippage ignored)",
+                    token_amount, expected));
+                (token_amount, 1, token_ata, wsol_ata, token_vault, sol_vault)
+            }
+        };
+        if amount_in == 0 {
+            return Err(anyhow!("Instructions is empty, no txn required."));
+        }
+
+        // The program checks vault order, so the coin vault always comes first
+        let (coin_vault, pc_vault) = if source_vault == keys.coin_vault { (source_vault, destination_vault) } else { (destination_vault, source_vault) };
+        let mut data = Vec::with_capacity(17);
+        data.push(SWAP_BASE_IN_V2);
+        data.extend_from_slice(&amount_in.to_le_bytes());
+        data.extend_from_slice(&minimum_out.to_le_bytes());
+        instructions.push(Instruction {
+            program_id: Pubkey::from_str(RAYDIUM_AMM_PROGRAM)?,
+            accounts: vec![
+                AccountMeta::new_readonly(token_program, false),
+                AccountMeta::new(amm, false),
+                AccountMeta::new_readonly(Pubkey::from_str(RAYDIUM_AMM_AUTHORITY)?, false),
+                AccountMeta::new(coin_vault, false),
+                AccountMeta::new(pc_vault, false),
+                AccountMeta::new(source, false),
+                AccountMeta::new(destination, false),
+                AccountMeta::new_readonly(owner, true),
+            ],
+            data,
+        });
+        // Unwrap whatever WSOL the swap left or received
+        instructions.push(close_account(&token_program, &wsol_ata, &owner, &owner, &[&owner])?);
+
+        Ok((self.keypair.clone(), instructions, trade_info.post_current_price))
+    }
+}
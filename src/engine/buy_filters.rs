@@ -0,0 +1,285 @@
+use std::sync::Arc;
+use anchor_client::solana_client::nonblocking::rpc_client::RpcClient;
+use async_trait::async_trait;
+
+use colored::Colorize;
+
+use crate::common::blacklist;
+use crate::common::logger::Logger;
+use crate::core::mint_inspector;
+use crate::dex::math;
+use crate::dex::pump_fun::Pump;
+use crate::engine::buy_sizing::BuySizeTiers;
+use crate::engine::trade_event::TradeEvent;
+use crate::engine::transaction_parser::DexType;
+
+/// Pre-trade check applied to every event for a mint we don't hold yet
+#[async_trait]
+pub trait BuyFilter: Send + Sync {
+    fn name(&self) -> &str;
+
+    /// Ok to buy, or Err with the rejection reason
+    async fn check(&self, event: &TradeEvent) -> Result<(), String>;
+}
+
+/// Name of the trigger check every event for an unheld mint goes through
+pub const QUALIFYING_BUY_FILTER: &str = "qualifying-buy";
+
+/// Buy into PumpFun, PumpSwap and Raydium AMM v4 mints when someone else buys at least MIN_TRIGGER_BUY_SOL
+pub struct QualifyingBuyFilter {
+    min_trigger_sol: f64,
+}
+
+impl QualifyingBuyFilter {
+    pub fn new(min_trigger_sol: f64) -> Self {
+        Self { min_trigger_sol }
+    }
+
+    pub fn from_env() -> Self {
+        let min_trigger_sol = std::env::var("MIN_TRIGGER_BUY_SOL")
+            .ok()
+            .and_then(|v| v.parse::<f64>().ok())
+            .unwrap_or(0.0);
+        Self::new(min_trigger_sol)
+    }
+}
+
+#[async_trait]
+impl BuyFilter for QualifyingBuyFilter {
+    fn name(&self) -> &str {
+        QUALIFYING_BUY_FILTER
+    }
+
+    async fn check(&self, event: &TradeEvent) -> Result<(), String> {
+        let core = &event.core;
+        if !matches!(core.dex_type, DexType::PumpFun | DexType::PumpSwap | DexType::RaydiumAmm) {
+            return Err(format!("unsupported dex {:?}", core.dex_type));
+        }
+        if !core.is_buy {
+            return Err("not a buy".to_string());
+        }
+        if core.sol_change < self.min_trigger_sol {
+            return Err(format!("trigger buy {:.4} SOL below {:.4} SOL", core.sol_change, self.min_trigger_sol));
+        }
+        // The curve migrates next; a PumpFun buy would only fail with BondingCurveComplete
+        if core.curve_complete {
+            return Err("bonding curve complete".to_string());
+        }
+        Ok(())
+    }
+}
+
+/// Price impact in bps of buying `sol_in` lamports at the given reserves: the fill price against the spot price
+/// None when the reserves are empty or the buy would get no tokens.
+pub fn price_impact_bps(sol_in: u64, virtual_sol_reserves: u64, virtual_token_reserves: u64) -> Option<u64> {
+    let spot = Pump::calculate_price_from_virtual_reserves(virtual_sol_reserves, virtual_token_reserves);
+    let tokens_out = Pump::calculate_buy_token_amount(sol_in, virtual_sol_reserves, virtual_token_reserves);
+    if spot <= 0.0 || tokens_out == 0 {
+        return None;
+    }
+    // Same scaling as the spot price, so the ratio is unitless
+    let fill = sol_in as f64 / tokens_out as f64 / 1_000.0;
+    Some(((fill / spot - 1.0).max(0.0) * math::BPS_DENOMINATOR as f64).round() as u64)
+}
+
+/// Skip thin pools and buys that would move the price too far, judged on the event's reserves
+/// MIN_LIQUIDITY_SOL (default: 0, off) and MAX_PRICE_IMPACT_BPS (default: 0, off); the impact is for the configured buy size,
+/// or the BUY_SIZE_TIERS size of the pool.
+pub struct LiquidityFilter {
+    min_liquidity_sol: f64,
+    max_price_impact_bps: u64,
+    buy_sol: f64,
+    size_tiers: Option<BuySizeTiers>,
+}
+
+impl LiquidityFilter {
+    pub fn new(min_liquidity_sol: f64, max_price_impact_bps: u64, buy_sol: f64) -> Self {
+        Self { min_liquidity_sol, max_price_impact_bps, buy_sol, size_tiers: None }
+    }
+
+    pub fn with_size_tiers(mut self, tiers: Option<BuySizeTiers>) -> Self {
+        self.size_tiers = tiers;
+        self
+    }
+
+    pub fn from_env(buy_sol: f64) -> Self {
+        let min_liquidity_sol = std::env::var("MIN_LIQUIDITY_SOL")
+            .ok()
+            .and_then(|v| v.parse::<f64>().ok())
+            .unwrap_or(0.0);
+        let max_price_impact_bps = std::env::var("MAX_PRICE_IMPACT_BPS")
+            .ok()
+            .and_then(|v| v.parse::<u64>().ok())
+            .unwrap_or(0);
+        Self::new(min_liquidity_sol, max_price_impact_bps, buy_sol)
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.min_liquidity_sol > 0.0 || self.max_price_impact_bps > 0
+    }
+}
+
+#[async_trait]
+impl BuyFilter for LiquidityFilter {
+    fn name(&self) -> &str {
+        "liquidity"
+    }
+
+    async fn check(&self, event: &TradeEvent) -> Result<(), String> {
+        let core = &event.core;
+        if self.min_liquidity_sol > 0.0 && core.liquidity < self.min_liquidity_sol {
+            return Err(format!("liquidity {:.4} SOL below {:.4} SOL", core.liquidity, self.min_liquidity_sol));
+        }
+        if self.max_price_impact_bps > 0 {
+            // Below the lowest tier the buy is refused anyway; judge it at the flat size meanwhile
+            let buy_sol = self.size_tiers.as_ref()
+                .and_then(|tiers| tiers.tier_for(core.liquidity))
+                .map_or(self.buy_sol, |tier| tier.amount_sol);
+            let sol_in = math::ui_to_raw(buy_sol, 9);
+            let impact = price_impact_bps(sol_in, core.virtual_sol_reserves, core.virtual_token_reserves)
+                .ok_or_else(|| format!("no price impact for empty reserves ({} SOL / {} tokens)", core.virtual_sol_reserves, core.virtual_token_reserves))?;
+            if impact > self.max_price_impact_bps {
+                return Err(format!("{:.4} SOL buy moves price {} bps, max {} bps (reserves {} SOL / {} tokens)",
+                    buy_sol, impact, self.max_price_impact_bps, core.virtual_sol_reserves, core.virtual_token_reserves));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Skip mints and creators on the runtime blacklist or CREATOR_BLACKLIST_FILE, and, in whitelist mode,
+/// anything MINT_WHITELIST_FILE doesn't list
+pub struct BlacklistFilter;
+
+/// Operator lists stop buys on purpose, so they always print rather than going with the debug skips
+fn log_list_block(mint: &str, reason: &str) {
+    blacklist::record_list_block();
+    Logger::new("[BLACKLIST] => ".red().to_string())
+        .critical(format!("🚫 Not buying {} - {} ({} blocked so far)", mint, reason, blacklist::list_block_count()));
+}
+
+#[async_trait]
+impl BuyFilter for BlacklistFilter {
+    fn name(&self) -> &str {
+        "blacklist"
+    }
+
+    async fn check(&self, event: &TradeEvent) -> Result<(), String> {
+        if let Some(reason) = blacklist::mint_block_reason(event.mint()) {
+            return Err(format!("mint blacklisted ({})", reason));
+        }
+        if let Some(creator) = event.coin_creator() {
+            if let Some(reason) = blacklist::creator_block_reason(creator) {
+                let reason = format!("creator {} blacklisted ({})", creator, reason);
+                log_list_block(event.mint(), &reason);
+                return Err(reason);
+            }
+        }
+        if !blacklist::is_allowed(event.mint(), event.coin_creator()) {
+            let reason = "neither mint nor creator is on MINT_WHITELIST_FILE".to_string();
+            log_list_block(event.mint(), &reason);
+            return Err(reason);
+        }
+        Ok(())
+    }
+}
+
+/// Reject mints that keep a freeze authority (MAX_RISK_ALLOW_FREEZE_AUTHORITY=false)
+pub struct FreezeAuthorityFilter {
+    rpc_client: Arc<RpcClient>,
+}
+
+impl FreezeAuthorityFilter {
+    pub fn new(rpc_client: Arc<RpcClient>) -> Self {
+        Self { rpc_client }
+    }
+
+    /// Whether mints with a freeze authority may be bought (MAX_RISK_ALLOW_FREEZE_AUTHORITY, default: true)
+    pub fn allow_freeze_authority() -> bool {
+        std::env::var("MAX_RISK_ALLOW_FREEZE_AUTHORITY")
+            .ok()
+            .and_then(|v| v.parse::<bool>().ok())
+            .unwrap_or(true)
+    }
+}
+
+#[async_trait]
+impl BuyFilter for FreezeAuthorityFilter {
+    fn name(&self) -> &str {
+        "freeze-authority"
+    }
+
+    async fn check(&self, event: &TradeEvent) -> Result<(), String> {
+        // Fail closed: a mint we can't inspect can't be shown to be safe
+        let info = mint_inspector::inspect_mint(&self.rpc_client, &event.core.mint).await
+            .map_err(|e| format!("mint inspection failed: {}", e))?;
+        match info.freeze_authority {
+            Some(authority) => Err(format!("mint has freeze authority {}", authority)),
+            None => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::test_fixtures::{event, pubkey, trade};
+
+    const VIRTUAL_SOL: u64 = 30_000_000_000;
+    const VIRTUAL_TOKENS: u64 = 1_073_000_000_000_000;
+
+    #[test]
+    fn price_impact_is_the_buy_against_the_sol_reserve() {
+        // On a constant-product curve the fill is (reserve + in) / reserve times the spot price
+        assert_eq!(price_impact_bps(300_000_000, VIRTUAL_SOL, VIRTUAL_TOKENS), Some(100));
+        assert_eq!(price_impact_bps(3_000_000_000, VIRTUAL_SOL, VIRTUAL_TOKENS), Some(1_000));
+        assert_eq!(price_impact_bps(VIRTUAL_SOL, VIRTUAL_SOL, VIRTUAL_TOKENS), Some(10_000));
+    }
+
+    #[test]
+    fn price_impact_at_the_edges() {
+        // A lamport moves nothing measurable; empty reserves or a buy too small for one token have no impact
+        assert_eq!(price_impact_bps(1_000_000, VIRTUAL_SOL, VIRTUAL_TOKENS), Some(0));
+        assert_eq!(price_impact_bps(1_000_000_000, 0, VIRTUAL_TOKENS), None);
+        assert_eq!(price_impact_bps(1_000_000_000, VIRTUAL_SOL, 0), None);
+        assert_eq!(price_impact_bps(1, VIRTUAL_SOL, 1_000), None);
+    }
+
+    #[tokio::test]
+    async fn liquidity_filter_passes_at_its_thresholds() {
+        let at_limits = LiquidityFilter::new(30.0, 100, 0.3);
+        assert!(at_limits.check(&event(trade(&pubkey(), true, 0.0))).await.is_ok());
+
+        let error = LiquidityFilter::new(30.01, 0, 0.3).check(&event(trade(&pubkey(), true, 0.0))).await.unwrap_err();
+        assert!(error.starts_with("liquidity 30.0000 SOL below 30.0100 SOL"), "{}", error);
+        let error = LiquidityFilter::new(0.0, 99, 0.3).check(&event(trade(&pubkey(), true, 0.0))).await.unwrap_err();
+        assert!(error.contains("moves price 100 bps, max 99 bps"), "{}", error);
+    }
+
+    #[tokio::test]
+    async fn liquidity_filter_refuses_empty_reserves() {
+        let mut trade_info = trade(&pubkey(), true, 0.0);
+        trade_info.virtual_sol_reserves = 0;
+        let error = LiquidityFilter::new(0.0, 500, 0.1).check(&event(trade_info)).await.unwrap_err();
+        assert!(error.starts_with("no price impact for empty reserves"), "{}", error);
+        assert!(!LiquidityFilter::new(0.0, 0, 0.1).is_enabled());
+    }
+
+    #[tokio::test]
+    async fn blacklist_filter_refuses_blocked_mints_and_creators() {
+        let filter = BlacklistFilter;
+        let allowed = trade(&pubkey(), true, 0.0);
+        assert!(filter.check(&event(allowed)).await.is_ok());
+
+        let blocked_mint = trade(&pubkey(), true, 0.0);
+        blacklist::block_mint(&blocked_mint.mint, "freeze authority");
+        assert_eq!(filter.check(&event(blocked_mint)).await.unwrap_err(), "mint blacklisted (freeze authority)");
+
+        let by_creator = trade(&pubkey(), true, 0.0);
+        let creator = by_creator.coin_creator.clone().unwrap();
+        blacklist::block_creator(&creator, "rugged");
+        let blocks = blacklist::list_block_count();
+        assert_eq!(filter.check(&event(by_creator)).await.unwrap_err(), format!("creator {} blacklisted (rugged)", creator));
+        assert!(blacklist::list_block_count() > blocks);
+    }
+}
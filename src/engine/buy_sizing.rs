@@ -0,0 +1,129 @@
+use std::fmt;
+use serde::{Deserialize, Serialize};
+
+/// Buys into pools with at least `min_liquidity_sol` spend `amount_sol`
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct SizeTier {
+    pub min_liquidity_sol: f64,
+    pub amount_sol: f64,
+}
+
+impl fmt::Display for SizeTier {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "≥{} SOL liquidity → {} SOL", self.min_liquidity_sol, self.amount_sol)
+    }
+}
+
+/// Buy size by pool liquidity, replacing the flat BUY_AMOUNT_IN_SOL
+/// BUY_SIZE_TIERS (default: unset), e.g. `1:0.02,5:0.05,20:0.1`: the deepest tier a pool reaches sets the size.
+#[derive(Clone, Debug, PartialEq)]
+pub struct BuySizeTiers {
+    /// Ascending by liquidity
+    tiers: Vec<SizeTier>,
+}
+
+impl BuySizeTiers {
+    /// Comma-separated `LIQUIDITY:SOL` pairs, in any order; Err names the entry at fault
+    pub fn parse(spec: &str) -> Result<Self, String> {
+        let mut tiers = Vec::new();
+        for entry in spec.split(',').map(str::trim).filter(|entry| !entry.is_empty()) {
+            let (liquidity, amount) = entry
+                .split_once(':')
+                .ok_or_else(|| format!("entry '{}' is not LIQUIDITY:SOL", entry))?;
+            let min_liquidity_sol = liquidity
+                .trim()
+                .parse::<f64>()
+                .map_err(|_| format!("entry '{}': liquidity '{}' is not a number", entry, liquidity.trim()))?;
+            let amount_sol = amount
+                .trim()
+                .parse::<f64>()
+                .map_err(|_| format!("entry '{}': amount '{}' is not a number", entry, amount.trim()))?;
+            if !min_liquidity_sol.is_finite() || min_liquidity_sol < 0.0 {
+                return Err(format!("entry '{}': liquidity must be 0 or more", entry));
+            }
+            if !amount_sol.is_finite() || amount_sol <= 0.0 {
+                return Err(format!("entry '{}': amount must be greater than 0", entry));
+            }
+            tiers.push(SizeTier { min_liquidity_sol, amount_sol });
+        }
+        if tiers.is_empty() {
+            return Err("no tiers given".to_string());
+        }
+        tiers.sort_by(|a, b| a.min_liquidity_sol.total_cmp(&b.min_liquidity_sol));
+        if let Some(pair) = tiers.windows(2).find(|pair| pair[0].min_liquidity_sol == pair[1].min_liquidity_sol) {
+            return Err(format!("liquidity {} SOL is listed twice", pair[0].min_liquidity_sol));
+        }
+        Ok(Self { tiers })
+    }
+
+    /// Ok(None) while BUY_SIZE_TIERS is unset or empty
+    pub fn from_env() -> Result<Option<Self>, String> {
+        match std::env::var("BUY_SIZE_TIERS") {
+            Ok(spec) if !spec.trim().is_empty() => Self::parse(&spec).map(Some),
+            _ => Ok(None),
+        }
+    }
+
+    /// The tier a pool with `liquidity_sol` falls in; None below the lowest one
+    pub fn tier_for(&self, liquidity_sol: f64) -> Option<SizeTier> {
+        self.tiers.iter().rev().find(|tier| liquidity_sol >= tier.min_liquidity_sol).copied()
+    }
+
+    pub fn lowest(&self) -> SizeTier {
+        self.tiers[0]
+    }
+
+    pub fn describe(&self) -> String {
+        self.tiers.iter().map(|tier| tier.to_string()).collect::<Vec<_>>().join(", ")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tier(min_liquidity_sol: f64, amount_sol: f64) -> SizeTier {
+        SizeTier { min_liquidity_sol, amount_sol }
+    }
+
+    #[test]
+    fn unsorted_tiers_are_ordered_by_liquidity() {
+        let tiers = BuySizeTiers::parse(" 20:0.1, 1:0.02 ,5:0.05,").unwrap();
+        assert_eq!(tiers, BuySizeTiers::parse("1:0.02,5:0.05,20:0.1").unwrap());
+        assert_eq!(tiers.lowest(), tier(1.0, 0.02));
+        assert_eq!(tiers.describe(), "≥1 SOL liquidity → 0.02 SOL, ≥5 SOL liquidity → 0.05 SOL, ≥20 SOL liquidity → 0.1 SOL");
+    }
+
+    #[test]
+    fn a_pool_exactly_at_a_threshold_gets_that_tier() {
+        let tiers = BuySizeTiers::parse("20:0.1,1:0.02,5:0.05").unwrap();
+        assert_eq!(tiers.tier_for(0.999), None);
+        assert_eq!(tiers.tier_for(1.0), Some(tier(1.0, 0.02)));
+        assert_eq!(tiers.tier_for(4.999), Some(tier(1.0, 0.02)));
+        assert_eq!(tiers.tier_for(5.0), Some(tier(5.0, 0.05)));
+        assert_eq!(tiers.tier_for(20.0), Some(tier(20.0, 0.1)));
+        assert_eq!(tiers.tier_for(1_000.0), Some(tier(20.0, 0.1)));
+
+        let from_zero = BuySizeTiers::parse("0:0.01").unwrap();
+        assert_eq!(from_zero.tier_for(0.0), Some(tier(0.0, 0.01)));
+    }
+
+    #[test]
+    fn malformed_entries_are_named_in_the_error() {
+        let cases = [
+            ("", "no tiers given"),
+            (" , ", "no tiers given"),
+            ("1:0.02,5", "entry '5' is not LIQUIDITY:SOL"),
+            ("lots:0.02", "entry 'lots:0.02': liquidity 'lots' is not a number"),
+            ("1:some", "entry '1:some': amount 'some' is not a number"),
+            ("-1:0.02", "entry '-1:0.02': liquidity must be 0 or more"),
+            ("inf:0.02", "entry 'inf:0.02': liquidity must be 0 or more"),
+            ("1:0", "entry '1:0': amount must be greater than 0"),
+            ("1:NaN", "entry '1:NaN': amount must be greater than 0"),
+            ("5:0.05,1:0.02,5.0:0.1", "liquidity 5 SOL is listed twice"),
+        ];
+        for (spec, expected) in cases {
+            assert_eq!(BuySizeTiers::parse(spec), Err(expected.to_string()), "{:?}", spec);
+        }
+    }
+}
@@ -0,0 +1,712 @@
+use std::collections::{BTreeMap, HashMap};
+use std::panic::AssertUnwindSafe;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use anchor_client::solana_sdk::{pubkey::Pubkey, signature::Keypair, transaction::VersionedTransaction};
+use futures::FutureExt;
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+use crate::common::{clock, config::SwapConfig};
+use crate::dex::pump_fun::Pump;
+use crate::engine::event_stream::{EventSource, ScriptedEventSource, StreamGap};
+use crate::engine::executor::{ExecutionReport, PaperExecutor, TradeExecutor};
+use crate::engine::exit_strategy::{parse_profit_tiers, RuntimeParams};
+use crate::engine::gap_catchup::{GapCatchUpConfig, GapReader, GapSignature, PoolState};
+use crate::engine::jitter::{Jitter, JitterConfig};
+use crate::engine::orchestrator::Orchestrator;
+use crate::engine::position::{PositionBook, PositionState};
+use crate::engine::swap::{SwapDirection, SwapInType};
+use crate::engine::trade_event::TradeEvent;
+use crate::engine::transaction_parser::{DexType, TradeInfoFromToken};
+use crate::error::classify::ErrorClass;
+use crate::services::intent_log::IntentLog;
+use crate::services::jupiter_api::{QuoteResponse, SwapAggregator, SwapMode, SwapOptions};
+use crate::services::ledger::MemoryLedger;
+use crate::services::notifier::MemoryNotifier;
+use crate::services::scripted_clients::{sample_message, ScriptedSwapAggregator, Step};
+use crate::services::session_report;
+
+const WSOL_MINT: &str = "So11111111111111111111111111111111111111112";
+/// Classes CHAOS_SEND_ERRORS may name; Other is left out so injected failures stay distinguishable from real ones
+const INJECTABLE_CLASSES: &[ErrorClass] = &[
+    ErrorClass::AccountFrozen,
+    ErrorClass::Nonce,
+    ErrorClass::BlockhashNotFound,
+    ErrorClass::InsufficientFunds,
+    ErrorClass::SlippageExceeded,
+    ErrorClass::IocCancelled,
+    ErrorClass::Network,
+];
+/// Scripted session run by the resilience suite
+const SUITE_MINTS: usize = 20;
+const SUITE_TRADES_PER_MINT: usize = 40;
+/// PumpFun curve reserves at launch
+const INITIAL_VIRTUAL_SOL: u64 = 30_000_000_000;
+const INITIAL_VIRTUAL_TOKENS: u64 = 1_073_000_000_000_000;
+/// Token amounts are compared with this much rounding slack
+const TOKEN_EPSILON: f64 = 1e-6;
+
+fn env_bool(key: &str) -> bool {
+    std::env::var(key).ok().and_then(|v| v.parse::<bool>().ok()).unwrap_or(false)
+}
+
+fn env_pct(key: &str, default: f64) -> f64 {
+    std::env::var(key)
+        .ok()
+        .and_then(|v| v.parse::<f64>().ok())
+        .filter(|v| v.is_finite())
+        .unwrap_or(default)
+        .clamp(0.0, 100.0)
+}
+
+/// Whether CHAOS_MODE is set at all; live entry points refuse to start when it is
+pub fn chaos_requested() -> bool {
+    env_bool("CHAOS_MODE")
+}
+
+/// Parse a comma-separated list of `ErrorClass::as_str` names
+pub fn parse_error_classes(value: &str) -> Result<Vec<ErrorClass>, String> {
+    value
+        .split(',')
+        .map(str::trim)
+        .filter(|name| !name.is_empty())
+        .map(|name| {
+            INJECTABLE_CLASSES
+                .iter()
+                .copied()
+                .find(|class| class.as_str() == name)
+                .ok_or_else(|| format!("CHAOS_SEND_ERRORS: unknown or non-injectable error class '{}'", name))
+        })
+        .collect()
+}
+
+/// Synthetic failures for paper runs, each with its own probability
+/// CHAOS_MODE=true enables it and is refused unless PAPER_TRADING=true as well. CHAOS_SEED (default: 1) makes a
+/// run reproducible. Sends fail with one of CHAOS_SEND_ERRORS (default: network,blockhash_not_found,
+/// insufficient_funds,slippage_exceeded) at CHAOS_SEND_FAILURE_PCT (default: 10) and with a stale blockhash at
+/// CHAOS_STALE_BLOCKHASH_PCT (default: 5); quotes are held CHAOS_QUOTE_DELAY_MS (default: 1500) at
+/// CHAOS_QUOTE_DELAY_PCT (default: 10); the stream drops every CHAOS_STREAM_DROP_MINS (default: 5, 0 never) of
+/// event time, losing CHAOS_STREAM_DROP_EVENTS (default: 3) events; CHAOS_CORRUPT_PER_MILLE (default: 1) events
+/// in a thousand get one parsed field corrupted.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ChaosConfig {
+    pub seed: u64,
+    pub send_failure_pct: f64,
+    pub send_error_classes: Vec<ErrorClass>,
+    pub stale_blockhash_pct: f64,
+    pub quote_delay_pct: f64,
+    pub quote_delay: Duration,
+    pub stream_drop_mins: u64,
+    pub stream_drop_events: usize,
+    pub corrupt_per_mille: u32,
+}
+
+impl Default for ChaosConfig {
+    fn default() -> Self {
+        Self {
+            seed: 1,
+            send_failure_pct: 10.0,
+            send_error_classes: vec![
+                ErrorClass::Network,
+                ErrorClass::BlockhashNotFound,
+                ErrorClass::InsufficientFunds,
+                ErrorClass::SlippageExceeded,
+            ],
+            stale_blockhash_pct: 5.0,
+            quote_delay_pct: 10.0,
+            quote_delay: Duration::from_millis(1500),
+            stream_drop_mins: 5,
+            stream_drop_events: 3,
+            corrupt_per_mille: 1,
+        }
+    }
+}
+
+impl ChaosConfig {
+    /// None unless CHAOS_MODE=true; an error when it is set without PAPER_TRADING=true
+    pub fn from_env() -> Result<Option<Self>, String> {
+        if !chaos_requested() {
+            return Ok(None);
+        }
+        if !env_bool("PAPER_TRADING") {
+            return Err("CHAOS_MODE=true requires PAPER_TRADING=true; synthetic failures never run against live execution".to_string());
+        }
+        let defaults = Self::default();
+        let send_error_classes = match std::env::var("CHAOS_SEND_ERRORS") {
+            Ok(value) => parse_error_classes(&value)?,
+            Err(_) => defaults.send_error_classes,
+        };
+        if send_error_classes.is_empty() {
+            return Err("CHAOS_SEND_ERRORS names no error class".to_string());
+        }
+        Ok(Some(Self {
+            seed: std::env::var("CHAOS_SEED").ok().and_then(|v| v.parse::<u64>().ok()).unwrap_or(defaults.seed),
+            send_failure_pct: env_pct("CHAOS_SEND_FAILURE_PCT", defaults.send_failure_pct),
+            send_error_classes,
+            stale_blockhash_pct: env_pct("CHAOS_STALE_BLOCKHASH_PCT", defaults.stale_blockhash_pct),
+            quote_delay_pct: env_pct("CHAOS_QUOTE_DELAY_PCT", defaults.quote_delay_pct),
+            quote_delay: std::env::var("CHAOS_QUOTE_DELAY_MS")
+                .ok()
+                .and_then(|v| v.parse::<u64>().ok())
+                .filter(|ms| *ms > 0)
+                .map(Duration::from_millis)
+                .unwrap_or(defaults.quote_delay),
+            stream_drop_mins: std::env::var("CHAOS_STREAM_DROP_MINS").ok().and_then(|v| v.parse::<u64>().ok()).unwrap_or(defaults.stream_drop_mins),
+            stream_drop_events: std::env::var("CHAOS_STREAM_DROP_EVENTS")
+                .ok()
+                .and_then(|v| v.parse::<usize>().ok())
+                .unwrap_or(defaults.stream_drop_events)
+                .max(1),
+            corrupt_per_mille: std::env::var("CHAOS_CORRUPT_PER_MILLE")
+                .ok()
+                .and_then(|v| v.parse::<u32>().ok())
+                .unwrap_or(defaults.corrupt_per_mille)
+                .min(1000),
+        }))
+    }
+}
+
+/// What was injected so far, by kind
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct InjectedFaults {
+    /// Failed sends by `ErrorClass::as_str`, stale blockhashes included under blockhash_not_found
+    pub send_failures: BTreeMap<String, u64>,
+    pub stale_blockhashes: u64,
+    pub quote_delays: u64,
+    pub stream_drops: u64,
+    pub events_dropped: u64,
+    /// Corrupted events by field name
+    pub corrupted_fields: BTreeMap<String, u64>,
+}
+
+impl InjectedFaults {
+    pub fn format(&self) -> String {
+        let counts = |map: &BTreeMap<String, u64>| {
+            if map.is_empty() {
+                "none".to_string()
+            } else {
+                map.iter().map(|(name, count)| format!("{} {}", name, count)).collect::<Vec<_>>().join(", ")
+            }
+        };
+        format!(
+            "Injected: send failures [{}] (stale blockhashes {}), quote delays {}, stream drops {} ({} events lost), corrupted fields [{}]",
+            counts(&self.send_failures),
+            self.stale_blockhashes,
+            self.quote_delays,
+            self.stream_drops,
+            self.events_dropped,
+            counts(&self.corrupted_fields)
+        )
+    }
+}
+
+/// Seeded source of every injection decision, shared by the wrappers below
+pub struct ChaosInjector {
+    config: ChaosConfig,
+    rng: Mutex<StdRng>,
+    faults: Mutex<InjectedFaults>,
+}
+
+impl ChaosInjector {
+    pub fn new(config: ChaosConfig) -> Self {
+        let rng = StdRng::seed_from_u64(config.seed);
+        Self { config, rng: Mutex::new(rng), faults: Mutex::new(InjectedFaults::default()) }
+    }
+
+    pub fn config(&self) -> &ChaosConfig {
+        &self.config
+    }
+
+    pub fn faults(&self) -> InjectedFaults {
+        self.faults.lock().map(|faults| faults.clone()).unwrap_or_default()
+    }
+
+    fn with_rng<T>(&self, draw: impl FnOnce(&mut StdRng) -> T) -> T {
+        let mut rng = self.rng.lock().unwrap_or_else(|e| e.into_inner());
+        draw(&mut rng)
+    }
+
+    fn roll(&self, pct: f64) -> bool {
+        pct > 0.0 && self.with_rng(|rng| rng.gen_bool(pct / 100.0))
+    }
+
+    fn count(&self, apply: impl FnOnce(&mut InjectedFaults)) {
+        if let Ok(mut faults) = self.faults.lock() {
+            apply(&mut faults);
+        }
+    }
+
+    /// Class the next send should fail with, if any
+    pub fn send_fault(&self) -> Option<ErrorClass> {
+        let class = if self.roll(self.config.stale_blockhash_pct) {
+            self.count(|faults| faults.stale_blockhashes += 1);
+            ErrorClass::BlockhashNotFound
+        } else if self.roll(self.config.send_failure_pct) {
+            let classes = &self.config.send_error_classes;
+            classes[self.with_rng(|rng| rng.gen_range(0..classes.len()))]
+        } else {
+            return None;
+        };
+        self.count(|faults| *faults.send_failures.entry(class.as_str().to_string()).or_default() += 1);
+        Some(class)
+    }
+
+    /// Whether the next quote is held past its deadline
+    pub fn delay_quote(&self) -> bool {
+        let delayed = self.roll(self.config.quote_delay_pct);
+        if delayed {
+            self.count(|faults| faults.quote_delays += 1);
+        }
+        delayed
+    }
+
+    /// Corrupt one field of `event` with CHAOS_CORRUPT_PER_MILLE odds; returns the field's name
+    pub fn corrupt(&self, event: &mut TradeEvent) -> Option<&'static str> {
+        if self.config.corrupt_per_mille == 0 || self.with_rng(|rng| rng.gen_range(0..1000)) >= self.config.corrupt_per_mille {
+            return None;
+        }
+        let core = &mut event.core;
+        let field = match self.with_rng(|rng| rng.gen_range(0..6)) {
+            0 => {
+                core.post_current_price = f64::NAN;
+                "post_current_price"
+            }
+            1 => {
+                core.virtual_sol_reserves = 0;
+                "virtual_sol_reserves"
+            }
+            2 => {
+                core.virtual_token_reserves = u64::MAX;
+                "virtual_token_reserves"
+            }
+            3 => {
+                core.sol_change = -core.sol_change * 1e9;
+                "sol_change"
+            }
+            4 => {
+                core.target_transaction_token_change = f64::INFINITY;
+                "target_transaction_token_change"
+            }
+            _ => {
+                core.timestamp = 0;
+                "timestamp"
+            }
+        };
+        self.count(|faults| *faults.corrupted_fields.entry(field.to_string()).or_default() += 1);
+        Some(field)
+    }
+}
+
+/// Executor whose sends fail as the injector decides, before the wrapped executor sees them
+/// Paper fills have no blockhash, so a stale one surfaces the way a live send reports it: "Blockhash not found".
+pub struct ChaosExecutor {
+    inner: Arc<dyn TradeExecutor>,
+    chaos: Arc<ChaosInjector>,
+}
+
+impl ChaosExecutor {
+    pub fn new(inner: Arc<dyn TradeExecutor>, chaos: Arc<ChaosInjector>) -> Self {
+        Self { inner, chaos }
+    }
+}
+
+#[async_trait]
+impl TradeExecutor for ChaosExecutor {
+    async fn buy(&self, trade_info: &TradeInfoFromToken, swap_config: &SwapConfig) -> Result<ExecutionReport> {
+        if let Some(class) = self.chaos.send_fault() {
+            return Err(anyhow!("chaos: {}", sample_message(class)));
+        }
+        self.inner.buy(trade_info, swap_config).await
+    }
+
+    async fn sell(&self, trade_info: &TradeInfoFromToken, swap_config: &SwapConfig, token_amount: f64, reason: &str) -> Result<ExecutionReport> {
+        if let Some(class) = self.chaos.send_fault() {
+            return Err(anyhow!("chaos: {}", sample_message(class)));
+        }
+        self.inner.sell(trade_info, swap_config, token_amount, reason).await
+    }
+}
+
+/// Aggregator whose quotes are held CHAOS_QUOTE_DELAY_MS as the injector decides; everything else passes through
+pub struct ChaosSwapAggregator {
+    inner: Arc<dyn SwapAggregator>,
+    chaos: Arc<ChaosInjector>,
+}
+
+impl ChaosSwapAggregator {
+    pub fn new(inner: Arc<dyn SwapAggregator>, chaos: Arc<ChaosInjector>) -> Self {
+        Self { inner, chaos }
+    }
+}
+
+#[async_trait]
+impl SwapAggregator for ChaosSwapAggregator {
+    async fn warm_up(&self) -> Result<()> {
+        self.inner.warm_up().await
+    }
+
+    async fn get_quote(&self, input_mint: &str, output_mint: &str, amount: u64, slippage_bps: u64, swap_mode: SwapMode) -> Result<QuoteResponse> {
+        if self.chaos.delay_quote() {
+            tokio::time::sleep(self.chaos.config().quote_delay).await;
+        }
+        self.inner.get_quote(input_mint, output_mint, amount, slippage_bps, swap_mode).await
+    }
+
+    async fn get_swap_transaction(&self, quote: QuoteResponse, user_public_key: &Pubkey, options: &SwapOptions) -> Result<VersionedTransaction> {
+        self.inner.get_swap_transaction(quote, user_public_key, options).await
+    }
+
+    async fn sell(&self, token_mint: &str, amount: u64, slippage_bps: u64, swap_mode: SwapMode, keypair: &Keypair, options: &SwapOptions) -> Result<String> {
+        self.inner.sell(token_mint, amount, slippage_bps, swap_mode, keypair, options).await
+    }
+
+    async fn buy(&self, token_mint: &str, sol_lamports: u64, slippage_bps: u64, keypair: &Keypair) -> Result<String> {
+        self.inner.buy(token_mint, sol_lamports, slippage_bps, keypair).await
+    }
+}
+
+/// Event source that drops the stream every CHAOS_STREAM_DROP_MINS of event time and corrupts parsed fields
+/// A drop loses CHAOS_STREAM_DROP_EVENTS events and reports the gap with the first event after it, as a reconnect
+/// of the gRPC source would.
+pub struct ChaosEventSource<S: EventSource> {
+    inner: S,
+    chaos: Arc<ChaosInjector>,
+    next_drop_at: Option<u64>,
+    last_slot: u64,
+    last_timestamp: u64,
+    gap: Option<StreamGap>,
+}
+
+impl<S: EventSource> ChaosEventSource<S> {
+    pub fn new(inner: S, chaos: Arc<ChaosInjector>) -> Self {
+        Self { inner, chaos, next_drop_at: None, last_slot: 0, last_timestamp: 0, gap: None }
+    }
+}
+
+#[async_trait]
+impl<S: EventSource> EventSource for ChaosEventSource<S> {
+    async fn next_event(&mut self) -> Option<TradeEvent> {
+        let mut event = self.inner.next_event().await?;
+        let drop_secs = self.chaos.config().stream_drop_mins * 60;
+        if drop_secs > 0 {
+            let due = *self.next_drop_at.get_or_insert(event.core.timestamp + drop_secs);
+            if event.core.timestamp >= due {
+                // The event that crossed the mark is the first one lost
+                for _ in 1..self.chaos.config().stream_drop_events {
+                    self.inner.next_event().await?;
+                }
+                event = self.inner.next_event().await?;
+                self.gap = Some(StreamGap {
+                    from_slot: self.last_slot,
+                    to_slot: event.core.slot,
+                    disconnected_at: self.last_timestamp,
+                    reconnected_at: event.core.timestamp,
+                });
+                self.next_drop_at = Some(event.core.timestamp + drop_secs);
+                let lost = self.chaos.config().stream_drop_events as u64;
+                self.chaos.count(|faults| {
+                    faults.stream_drops += 1;
+                    faults.events_dropped += lost;
+                });
+                session_report::record_stream_disconnect();
+            }
+        }
+        self.last_slot = event.core.slot;
+        self.last_timestamp = event.core.timestamp;
+        self.chaos.corrupt(&mut event);
+        Some(event)
+    }
+
+    fn take_gap(&mut self) -> Option<StreamGap> {
+        self.gap.take().or_else(|| self.inner.take_gap())
+    }
+}
+
+/// Gap reader with no chain behind it; counts the gaps the orchestrator caught up on
+#[derive(Default)]
+struct CountingGapReader {
+    gaps: AtomicUsize,
+}
+
+#[async_trait]
+impl GapReader for CountingGapReader {
+    async fn pool_state(&self, mint: &str, _dex_type: DexType) -> Result<PoolState, String> {
+        Err(format!("no chain state for {} in the resilience suite", mint))
+    }
+
+    async fn signatures_in_gap(&self, _address: &str, _gap: &StreamGap, _limit: usize) -> Result<Vec<GapSignature>, String> {
+        self.gaps.fetch_add(1, Ordering::Relaxed);
+        Ok(Vec::new())
+    }
+
+    async fn replay(&self, _signature: &str) -> Result<Vec<TradeInfoFromToken>, String> {
+        Ok(Vec::new())
+    }
+}
+
+/// PumpFun trades for `mints` seeded mints, `trades_per_mint` each, interleaved in time from now
+/// Each mint gets its own buy pressure, so some pump and some dump through the exit thresholds.
+pub fn scripted_session(seed: u64, mints: usize, trades_per_mint: usize) -> Vec<TradeInfoFromToken> {
+    struct Curve {
+        mint: String,
+        creator: String,
+        buy_odds: f64,
+        virtual_sol: u64,
+        virtual_tokens: u64,
+        remaining: usize,
+    }
+
+    let mut rng = StdRng::seed_from_u64(seed);
+    let mut curves: Vec<Curve> = (0..mints)
+        .map(|_| Curve {
+            mint: Pubkey::new_from_array(rng.gen()).to_string(),
+            creator: Pubkey::new_from_array(rng.gen()).to_string(),
+            buy_odds: rng.gen_range(0.35..0.65),
+            virtual_sol: INITIAL_VIRTUAL_SOL,
+            virtual_tokens: INITIAL_VIRTUAL_TOKENS,
+            remaining: trades_per_mint,
+        })
+        .collect();
+
+    let mut events = Vec::with_capacity(mints * trades_per_mint);
+    let mut timestamp = clock::now_secs();
+    let mut slot = 300_000_000u64;
+    while curves.iter().any(|curve| curve.remaining > 0) {
+        let open: Vec<usize> = (0..curves.len()).filter(|index| curves[*index].remaining > 0).collect();
+        let curve = &mut curves[open[rng.gen_range(0..open.len())]];
+        curve.remaining -= 1;
+        timestamp += rng.gen_range(1..=4);
+        slot += rng.gen_range(1..=10);
+
+        let pre_price = Pump::calculate_price_from_virtual_reserves(curve.virtual_sol, curve.virtual_tokens);
+        // The first trade of a mint is always a buy, so every mint gets a trigger
+        let is_buy = curve.remaining + 1 == trades_per_mint || rng.gen_bool(curve.buy_odds);
+        let lamports = rng.gen_range(50_000_000..1_500_000_000u64);
+        let (sol_change, token_change) = if is_buy {
+            let tokens = Pump::calculate_buy_token_amount(lamports, curve.virtual_sol, curve.virtual_tokens);
+            curve.virtual_sol += lamports;
+            curve.virtual_tokens -= tokens;
+            (lamports as f64 / 1e9, tokens as f64 / 1e6)
+        } else {
+            let tokens = (lamports as u128 * curve.virtual_tokens as u128 / curve.virtual_sol as u128) as u64;
+            let sol_out = Pump::calculate_sell_sol_amount(tokens, curve.virtual_sol, curve.virtual_tokens)
+                .min(curve.virtual_sol.saturating_sub(INITIAL_VIRTUAL_SOL / 10));
+            curve.virtual_sol -= sol_out;
+            curve.virtual_tokens += tokens;
+            (-(sol_out as f64) / 1e9, -(tokens as f64) / 1e6)
+        };
+        let post_price = Pump::calculate_price_from_virtual_reserves(curve.virtual_sol, curve.virtual_tokens);
+
+        events.push(TradeInfoFromToken {
+            dex_type: DexType::PumpFun,
+            slot,
+            signature: format!("chaos-{}-{}", slot, events.len()),
+            pool_id: String::new(),
+            mint: curve.mint.clone(),
+            timestamp,
+            is_buy,
+            post_current_price: post_price,
+            pre_current_price: pre_price,
+            is_reverse_when_pump_swap: false,
+            coin_creator: Some(curve.creator.clone()),
+            sol_change,
+            target_transaction_token_change: token_change,
+            token_decimals: 6,
+            liquidity: curve.virtual_sol as f64 / 1e9,
+            virtual_sol_reserves: curve.virtual_sol,
+            virtual_token_reserves: curve.virtual_tokens,
+            buy_sell_in_same_tx: false,
+            trader: Pubkey::new_from_array(rng.gen()).to_string(),
+            is_own_trade: false,
+            timestamp_suspect: false,
+            gap_replayed: false,
+            curve_complete: false,
+            suspected_mev: false,
+        });
+    }
+    events
+}
+
+fn suite_quote(mint: &str) -> QuoteResponse {
+    QuoteResponse {
+        input_mint: mint.to_string(),
+        in_amount: "1000000000".to_string(),
+        output_mint: WSOL_MINT.to_string(),
+        out_amount: "30000".to_string(),
+        other_amount_threshold: "29700".to_string(),
+        swap_mode: SwapMode::ExactIn,
+        slippage_bps: 100,
+        platform_fee: None,
+        price_impact_pct: "0".to_string(),
+        route_plan: Vec::new(),
+        context_slot: 0,
+    }
+}
+
+/// Outcome of one resilience suite run
+#[derive(Clone, Debug, Default)]
+pub struct ResilienceReport {
+    pub seed: u64,
+    pub events: usize,
+    pub buys: u64,
+    pub sells: u64,
+    pub faults: InjectedFaults,
+    /// Broken invariants; empty when the run passed
+    pub violations: Vec<String>,
+}
+
+impl ResilienceReport {
+    pub fn passed(&self) -> bool {
+        self.violations.is_empty()
+    }
+
+    pub fn format(&self) -> String {
+        let mut lines = vec![
+            format!("Resilience suite (seed {}): {} events, {} buys, {} sells", self.seed, self.events, self.buys, self.sells),
+            self.faults.format(),
+        ];
+        if self.passed() {
+            lines.push("✅ All invariants held".to_string());
+        } else {
+            lines.push(format!("❌ {} invariants broken:", self.violations.len()));
+            lines.extend(self.violations.iter().map(|violation| format!("  - {}", violation)));
+        }
+        lines.join("\n")
+    }
+}
+
+/// Run a scripted paper session under `config` and check what must survive any fault
+/// Invariants: no panic, no position left Pending or Closing, no negative token balance in the book or the ledger,
+/// every intent resolved, error counters matching the injected send failures class by class, every stream drop
+/// counted and caught up on, and every delayed quote missing its deadline (half the injected delay).
+pub async fn run_resilience_suite(config: ChaosConfig) -> ResilienceReport {
+    let chaos = Arc::new(ChaosInjector::new(config.clone()));
+    let before = session_report::counters();
+
+    let intent_path = std::env::temp_dir().join(format!("chaos-intents-{}-{}.jsonl", config.seed, std::process::id()));
+    let _ = std::fs::remove_file(&intent_path);
+    let intents = Arc::new(IntentLog::new(intent_path.to_string_lossy().to_string()));
+    let positions = Arc::new(PositionBook::new());
+    let ledger = Arc::new(MemoryLedger::new());
+    let gap_reader = Arc::new(CountingGapReader::default());
+    let swap_config = SwapConfig {
+        swap_direction: SwapDirection::Buy,
+        in_type: SwapInType::Qty,
+        amount_in: 0.1,
+        buy_slippage: 700,
+        reverse: false,
+        dry_run: false,
+    };
+    let params = RuntimeParams {
+        profit_tiers: parse_profit_tiers("30:50"),
+        trailing_stop_pct: Some(15.0),
+        stop_loss_pct: Some(20.0),
+        ..RuntimeParams::default()
+    };
+
+    let mut orchestrator = Orchestrator::new(
+        Arc::new(ChaosExecutor::new(Arc::new(PaperExecutor::new()), chaos.clone())),
+        positions.clone(),
+        ledger.clone(),
+        Arc::new(MemoryNotifier::new()),
+        swap_config,
+        params,
+    );
+    orchestrator.set_intent_log(intents.clone());
+    orchestrator.set_gap_reader(gap_reader.clone());
+    // Replaying gap buys would read the gap again; the count below expects one read per gap
+    orchestrator.set_gap_config(GapCatchUpConfig { replay_buys: false, ..GapCatchUpConfig::from_env() });
+    // Own-transaction checks are what reach the gap reader for every gap
+    orchestrator.set_own_wallet(Pubkey::new_unique());
+    orchestrator.set_jitter(Arc::new(Jitter::seeded(JitterConfig::from_env(), config.seed)));
+
+    let session = scripted_session(config.seed, SUITE_MINTS, SUITE_TRADES_PER_MINT);
+    let mints: Vec<String> = {
+        let mut mints: Vec<String> = session.iter().map(|trade| trade.mint.clone()).collect();
+        mints.sort();
+        mints.dedup();
+        mints
+    };
+    let events = session.len();
+    let mut source = ChaosEventSource::new(ScriptedEventSource::new(session), chaos.clone());
+
+    let mut violations = Vec::new();
+    if let Err(panic) = AssertUnwindSafe(orchestrator.run(&mut source)).catch_unwind().await {
+        let message = panic
+            .downcast_ref::<String>()
+            .cloned()
+            .or_else(|| panic.downcast_ref::<&str>().map(|s| s.to_string()))
+            .unwrap_or_else(|| "unknown panic".to_string());
+        violations.push(format!("orchestrator panicked: {}", message));
+    }
+
+    // Quotes go through the same injector, held to a deadline the delay overshoots
+    let aggregator = mints.iter().fold(ScriptedSwapAggregator::new(), |aggregator, mint| aggregator.then_quote(Step::ok(suite_quote(mint))));
+    let quotes = ChaosSwapAggregator::new(Arc::new(aggregator), chaos.clone());
+    let deadline = config.quote_delay / 2;
+    let mut missed_deadlines = 0u64;
+    for mint in &mints {
+        if tokio::time::timeout(deadline, quotes.get_quote(mint, WSOL_MINT, 1_000_000_000, 100, SwapMode::ExactIn)).await.is_err() {
+            missed_deadlines += 1;
+        }
+    }
+
+    let faults = chaos.faults();
+    for position in positions.snapshot() {
+        if matches!(position.state, PositionState::Pending | PositionState::Closing) {
+            violations.push(format!("{} left {} with no send in flight", position.mint, position.state));
+        }
+        if position.remaining_tokens() < -TOKEN_EPSILON {
+            violations.push(format!("{} holds {:.6} tokens in the book", position.mint, position.remaining_tokens()));
+        }
+    }
+    let mut held: HashMap<String, f64> = HashMap::new();
+    for entry in ledger.entries() {
+        let balance = held.entry(entry.mint.clone()).or_default();
+        match entry.side.as_str() {
+            "buy" => *balance += entry.token_amount,
+            "sell" => *balance -= entry.token_amount,
+            _ => {}
+        }
+        if *balance < -TOKEN_EPSILON {
+            violations.push(format!("{} ledger balance went to {:.6} tokens at {}", entry.mint, balance, entry.signature));
+        }
+    }
+    for intent in intents.pending() {
+        violations.push(format!("{} intent {} for {} never resolved", intent.side, intent.id, intent.mint));
+    }
+
+    let after = session_report::counters();
+    for class in INJECTABLE_CLASSES {
+        let name = class.as_str();
+        let counted = after.errors_by_class.get(name).copied().unwrap_or(0) - before.errors_by_class.get(name).copied().unwrap_or(0);
+        let injected = faults.send_failures.get(name).copied().unwrap_or(0);
+        if counted != injected {
+            violations.push(format!("{} errors counted {} times, injected {}", name, counted, injected));
+        }
+    }
+    let disconnects = after.stream_disconnects - before.stream_disconnects;
+    if disconnects != faults.stream_drops {
+        violations.push(format!("stream disconnects counted {}, injected {}", disconnects, faults.stream_drops));
+    }
+    let caught_up = gap_reader.gaps.load(Ordering::Relaxed) as u64;
+    if caught_up != faults.stream_drops {
+        violations.push(format!("gaps caught up {}, injected {}", caught_up, faults.stream_drops));
+    }
+    if missed_deadlines != faults.quote_delays {
+        violations.push(format!("quotes past their deadline {}, injected delays {}", missed_deadlines, faults.quote_delays));
+    }
+    let _ = std::fs::remove_file(&intent_path);
+
+    ResilienceReport {
+        seed: config.seed,
+        events,
+        buys: after.buys - before.buys,
+        sells: after.sells - before.sells,
+        faults,
+        violations,
+    }
+}
@@ -0,0 +1,425 @@
+use std::collections::VecDeque;
+use std::str::FromStr;
+use std::sync::Arc;
+use std::time::Duration;
+use anchor_client::solana_sdk::pubkey::Pubkey;
+use async_trait::async_trait;
+use colored::Colorize;
+use dashmap::{DashMap, DashSet};
+use serde_json::Value;
+
+use crate::common::{clock, logger::Logger};
+use crate::engine::exit_strategy::{ParamOverrides, RuntimeParams};
+use crate::engine::hooks::{HookActions, StrategyHook};
+use crate::engine::position::{Position, PositionBook};
+use crate::engine::position_overrides::{format_overrides, OVERRIDE_STORE};
+use crate::engine::trade_event::TradeEvent;
+use crate::services::notifier::Notifier;
+use crate::services::rpc_pool::RpcHandle;
+
+/// getMultipleAccounts takes at most 100 keys
+const ACCOUNTS_PER_REQUEST: usize = 100;
+
+/// What a drain signal does besides alerting
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DrainAction {
+    Alert,
+    /// Override the position's stop loss with CREATOR_VAULT_TIGHTEN_STOP_PCT
+    Tighten,
+    /// Fully exit on the mint's next event
+    Exit,
+}
+
+/// CREATOR_VAULT_DRAIN_PCT (default: 50, 0 disables) of the vault's peak balance withdrawn within
+/// CREATOR_VAULT_DRAIN_WINDOW_SECS (default: 60), and at least CREATOR_VAULT_MIN_DRAIN_SOL (default: 0.1), signals a drain.
+/// Vaults are polled every CREATOR_VAULT_POLL_SECS (default: 5). CREATOR_VAULT_DRAIN_ACTION is alert (default),
+/// tighten (stop loss set to CREATOR_VAULT_TIGHTEN_STOP_PCT, default: 10) or exit.
+#[derive(Clone, Copy, Debug)]
+pub struct VaultMonitorConfig {
+    pub drain_pct: f64,
+    pub window_secs: u64,
+    pub min_drain_lamports: u64,
+    pub poll_interval: Duration,
+    pub action: DrainAction,
+    pub tighten_stop_pct: f64,
+}
+
+impl VaultMonitorConfig {
+    pub fn from_env() -> Self {
+        let drain_pct = std::env::var("CREATOR_VAULT_DRAIN_PCT")
+            .ok()
+            .and_then(|v| v.parse::<f64>().ok())
+            .unwrap_or(50.0);
+        let window_secs = std::env::var("CREATOR_VAULT_DRAIN_WINDOW_SECS")
+            .ok()
+            .and_then(|v| v.parse::<u64>().ok())
+            .unwrap_or(60);
+        let min_drain_sol = std::env::var("CREATOR_VAULT_MIN_DRAIN_SOL")
+            .ok()
+            .and_then(|v| v.parse::<f64>().ok())
+            .unwrap_or(0.1);
+        let poll_secs = std::env::var("CREATOR_VAULT_POLL_SECS")
+            .ok()
+            .and_then(|v| v.parse::<u64>().ok())
+            .unwrap_or(5)
+            .max(1);
+        let action = match std::env::var("CREATOR_VAULT_DRAIN_ACTION").unwrap_or_default().to_lowercase().as_str() {
+            "tighten" => DrainAction::Tighten,
+            "exit" => DrainAction::Exit,
+            _ => DrainAction::Alert,
+        };
+        let tighten_stop_pct = std::env::var("CREATOR_VAULT_TIGHTEN_STOP_PCT")
+            .ok()
+            .and_then(|v| v.parse::<f64>().ok())
+            .unwrap_or(10.0);
+        Self {
+            drain_pct,
+            window_secs,
+            min_drain_lamports: (min_drain_sol * 1_000_000_000.0) as u64,
+            poll_interval: Duration::from_secs(poll_secs),
+            action,
+            tighten_stop_pct,
+        }
+    }
+
+    pub fn enabled(&self) -> bool {
+        self.drain_pct > 0.0
+    }
+}
+
+/// A large withdrawal from a held mint's creator vault
+#[derive(Clone, Debug, PartialEq)]
+pub struct DrainSignal {
+    pub mint: String,
+    pub vault: String,
+    pub peak_lamports: u64,
+    pub current_lamports: u64,
+    /// Withdrawn share of the window's peak
+    pub drained_pct: f64,
+}
+
+impl DrainSignal {
+    pub fn format(&self, action: DrainAction) -> String {
+        let follow_up = match action {
+            DrainAction::Alert => "",
+            DrainAction::Tighten => " - stop tightened",
+            DrainAction::Exit => " - exiting",
+        };
+        format!(
+            "🏦 Creator vault of {} drained {:.0}% ({:.3} → {:.3} SOL){}",
+            self.mint,
+            self.drained_pct,
+            self.peak_lamports as f64 / 1_000_000_000.0,
+            self.current_lamports as f64 / 1_000_000_000.0,
+            follow_up
+        )
+    }
+}
+
+/// Balances of one vault within the window, oldest first
+#[derive(Default)]
+struct VaultSeries {
+    samples: VecDeque<(u64, u64)>,
+    fired: bool,
+}
+
+/// Lamport balances of creator vaults
+#[async_trait]
+pub trait VaultBalanceReader: Send + Sync {
+    /// One entry per vault, in order; None for vaults that don't exist (yet)
+    async fn balances(&self, vaults: &[Pubkey]) -> Result<Vec<Option<u64>>, String>;
+}
+
+/// Batched getMultipleAccounts through the read pool
+pub struct RpcVaultBalanceReader {
+    read_rpc: RpcHandle,
+}
+
+impl RpcVaultBalanceReader {
+    pub fn new(read_rpc: RpcHandle) -> Self {
+        Self { read_rpc }
+    }
+}
+
+#[async_trait]
+impl VaultBalanceReader for RpcVaultBalanceReader {
+    async fn balances(&self, vaults: &[Pubkey]) -> Result<Vec<Option<u64>>, String> {
+        let client = self.read_rpc.client();
+        let mut balances = Vec::with_capacity(vaults.len());
+        for chunk in vaults.chunks(ACCOUNTS_PER_REQUEST) {
+            let accounts = client
+                .get_multiple_accounts(chunk)
+                .await
+                .map_err(|e| format!("failed to read creator vaults: {}", e))?;
+            balances.extend(accounts.into_iter().map(|account| account.map(|account| account.lamports)));
+        }
+        Ok(balances)
+    }
+}
+
+/// Balances set by hand (harnesses simulating a balance series)
+#[derive(Default)]
+pub struct MemoryVaultBalances {
+    balances: DashMap<Pubkey, u64>,
+}
+
+impl MemoryVaultBalances {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn set(&self, vault: &Pubkey, lamports: u64) {
+        self.balances.insert(*vault, lamports);
+    }
+}
+
+#[async_trait]
+impl VaultBalanceReader for MemoryVaultBalances {
+    async fn balances(&self, vaults: &[Pubkey]) -> Result<Vec<Option<u64>>, String> {
+        Ok(vaults.iter().map(|vault| self.balances.get(vault).map(|lamports| *lamports)).collect())
+    }
+}
+
+/// Watches the creator vaults of held PumpFun positions for drains
+/// A vault collects the creator's fees from all their coins, so a drain may concern another of them; it is a
+/// warning, not proof. Exits are requested through the hook and go out on the mint's next event.
+pub struct CreatorVaultMonitor {
+    config: VaultMonitorConfig,
+    notifier: Arc<dyn Notifier>,
+    series: DashMap<String, VaultSeries>,
+    exit_requests: DashSet<String>,
+    logger: Logger,
+}
+
+impl CreatorVaultMonitor {
+    pub fn new(config: VaultMonitorConfig, notifier: Arc<dyn Notifier>) -> Self {
+        Self {
+            config,
+            notifier,
+            series: DashMap::new(),
+            exit_requests: DashSet::new(),
+            logger: Logger::new("[CREATOR-VAULT] => ".yellow().to_string()),
+        }
+    }
+
+    /// Add a balance sample for `mint`'s vault taken at `now`; Some once per position when it counts as a drain
+    pub fn observe(&self, mint: &str, vault: &str, lamports: u64, now: u64) -> Option<DrainSignal> {
+        let mut series = self.series.entry(mint.to_string()).or_default();
+        series.samples.push_back((now, lamports));
+        while series.samples.front().is_some_and(|(at, _)| *at + self.config.window_secs < now) {
+            series.samples.pop_front();
+        }
+        if series.fired {
+            return None;
+        }
+        let peak = series.samples.iter().map(|(_, balance)| *balance).max().unwrap_or(0);
+        let drained = peak.saturating_sub(lamports);
+        if peak == 0 || drained < self.config.min_drain_lamports {
+            return None;
+        }
+        let drained_pct = drained as f64 / peak as f64 * 100.0;
+        if drained_pct < self.config.drain_pct {
+            return None;
+        }
+        series.fired = true;
+        Some(DrainSignal {
+            mint: mint.to_string(),
+            vault: vault.to_string(),
+            peak_lamports: peak,
+            current_lamports: lamports,
+            drained_pct,
+        })
+    }
+
+    /// Alert and carry out the configured action
+    pub fn dispatch(&self, signal: &DrainSignal, positions: &PositionBook) {
+        let message = signal.format(self.config.action);
+        println!("{}", message);
+        self.notifier.notify(message);
+        match self.config.action {
+            DrainAction::Alert => {}
+            DrainAction::Tighten => {
+                let Some(stop) = serde_json::Number::from_f64(self.config.tighten_stop_pct) else {
+                    return;
+                };
+                let mut changes = ParamOverrides::new();
+                changes.insert("stop_loss_pct".to_string(), Value::Number(stop));
+                match OVERRIDE_STORE.set(&signal.mint, changes, &RuntimeParams::from_env(), "creator-vault") {
+                    Ok(merged) => {
+                        positions.set_param_overrides(&signal.mint, merged.clone());
+                        self.logger.critical(format!("{} overrides now {}", signal.mint, format_overrides(&merged)));
+                    }
+                    Err(e) => self.logger.error(format!("Failed to tighten the stop of {}: {}", signal.mint, e)),
+                }
+            }
+            DrainAction::Exit => {
+                self.exit_requests.insert(signal.mint.clone());
+            }
+        }
+    }
+
+    /// Read every held vault once and dispatch the drains found
+    pub async fn poll_once(&self, reader: &dyn VaultBalanceReader, positions: &PositionBook, now: u64) -> Vec<DrainSignal> {
+        let watched: Vec<(String, String, Pubkey)> = positions
+            .snapshot()
+            .into_iter()
+            .filter_map(|position| {
+                let vault = position.creator_vault?;
+                let pubkey = Pubkey::from_str(&vault).ok()?;
+                Some((position.mint, vault, pubkey))
+            })
+            .collect();
+        if watched.is_empty() {
+            return Vec::new();
+        }
+        let keys: Vec<Pubkey> = watched.iter().map(|(_, _, pubkey)| *pubkey).collect();
+        let balances = match reader.balances(&keys).await {
+            Ok(balances) => balances,
+            Err(e) => {
+                self.logger.error(e);
+                return Vec::new();
+            }
+        };
+
+        let mut signals = Vec::new();
+        for ((mint, vault, _), balance) in watched.iter().zip(balances) {
+            // A vault that doesn't exist yet holds nothing to drain
+            let Some(lamports) = balance else {
+                continue;
+            };
+            if let Some(signal) = self.observe(mint, vault, lamports, now) {
+                self.dispatch(&signal, positions);
+                signals.push(signal);
+            }
+        }
+        signals
+    }
+
+    /// Poll every CREATOR_VAULT_POLL_SECS in the background
+    pub fn start(self: &Arc<Self>, reader: Arc<dyn VaultBalanceReader>, positions: Arc<PositionBook>) {
+        let monitor = self.clone();
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(monitor.config.poll_interval);
+            loop {
+                interval.tick().await;
+                monitor.poll_once(reader.as_ref(), &positions, clock::now_secs()).await;
+            }
+        });
+    }
+
+    pub fn take_exit_request(&self, mint: &str) -> bool {
+        self.exit_requests.remove(mint).is_some()
+    }
+}
+
+#[async_trait]
+impl StrategyHook for CreatorVaultMonitor {
+    fn name(&self) -> &str {
+        "creator_vault"
+    }
+
+    async fn on_tick_for_held(&self, _event: &TradeEvent, position: &Position, actions: &HookActions) {
+        if self.take_exit_request(&position.mint) {
+            actions.request_sell(&position.mint);
+        }
+    }
+
+    async fn on_position_closed(&self, position: &Position, _actions: &HookActions) {
+        self.series.remove(&position.mint);
+        self.exit_requests.remove(&position.mint);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::test_fixtures::{lot, pubkey};
+    use crate::engine::transaction_parser::DexType;
+    use crate::services::notifier::MemoryNotifier;
+
+    const SOL: u64 = 1_000_000_000;
+
+    fn config(action: DrainAction) -> VaultMonitorConfig {
+        VaultMonitorConfig {
+            drain_pct: 50.0,
+            window_secs: 60,
+            min_drain_lamports: SOL / 10,
+            poll_interval: Duration::from_secs(5),
+            action,
+            tighten_stop_pct: 10.0,
+        }
+    }
+
+    fn monitor(action: DrainAction) -> (CreatorVaultMonitor, Arc<MemoryNotifier>) {
+        let notifier = Arc::new(MemoryNotifier::new());
+        (CreatorVaultMonitor::new(config(action), notifier.clone()), notifier)
+    }
+
+    #[test]
+    fn drain_fires_once_against_the_window_peak() {
+        let (monitor, _) = monitor(DrainAction::Alert);
+        let (mint, vault) = (pubkey(), pubkey());
+        assert_eq!(monitor.observe(&mint, &vault, 2 * SOL, 0), None);
+        assert_eq!(monitor.observe(&mint, &vault, 3 * SOL, 10), None);
+        // 33% below the peak is short of the threshold
+        assert_eq!(monitor.observe(&mint, &vault, 2 * SOL, 20), None);
+
+        let signal = monitor.observe(&mint, &vault, SOL, 30).expect("two thirds of the peak withdrawn");
+        assert_eq!(signal.peak_lamports, 3 * SOL);
+        assert_eq!(signal.current_lamports, SOL);
+        assert!((signal.drained_pct - 200.0 / 3.0).abs() < 1e-9);
+        assert_eq!(monitor.observe(&mint, &vault, 0, 40), None, "a position signals once");
+    }
+
+    #[test]
+    fn slow_or_small_withdrawals_do_not_fire() {
+        let (monitor, _) = monitor(DrainAction::Alert);
+        let (mint, vault) = (pubkey(), pubkey());
+        // Halved, but only after the peak left the window
+        monitor.observe(&mint, &vault, 4 * SOL, 0);
+        monitor.observe(&mint, &vault, 3 * SOL, 40);
+        assert_eq!(monitor.observe(&mint, &vault, 2 * SOL, 90), None);
+
+        // All of it, but below CREATOR_VAULT_MIN_DRAIN_SOL
+        let other = pubkey();
+        monitor.observe(&other, &vault, SOL / 20, 0);
+        assert_eq!(monitor.observe(&other, &vault, 0, 1), None);
+    }
+
+    #[tokio::test]
+    async fn polled_balance_series_alerts_on_the_drain() {
+        let (monitor, notifier) = monitor(DrainAction::Alert);
+        let positions = PositionBook::new();
+        let mint = pubkey();
+        positions.record_buy(&mint, DexType::PumpFun, Some(pubkey()), lot("buy", 1_000.0, 0.1));
+        let vault = positions.get(&mint).and_then(|position| position.creator_vault).expect("vault stored at buy time");
+        let vault_key = Pubkey::from_str(&vault).unwrap();
+
+        let reader = MemoryVaultBalances::new();
+        assert!(monitor.poll_once(&reader, &positions, 0).await.is_empty(), "a missing vault holds nothing");
+        for (now, lamports) in [(5, 2 * SOL), (10, 2 * SOL), (15, SOL / 2)] {
+            reader.set(&vault_key, lamports);
+            let signals = monitor.poll_once(&reader, &positions, now).await;
+            assert_eq!(signals.len(), usize::from(now == 15));
+        }
+
+        let messages = notifier.messages();
+        assert_eq!(messages.len(), 1);
+        assert!(messages[0].contains("drained 75%"), "{}", messages[0]);
+        assert!(!monitor.take_exit_request(&mint), "alert-only by default");
+    }
+
+    #[test]
+    fn exit_action_requests_a_sell_once() {
+        let (monitor, notifier) = monitor(DrainAction::Exit);
+        let positions = PositionBook::new();
+        let mint = pubkey();
+        monitor.observe(&mint, "vault", 2 * SOL, 0);
+        let signal = monitor.observe(&mint, "vault", 0, 1).unwrap();
+        monitor.dispatch(&signal, &positions);
+
+        assert!(notifier.messages()[0].ends_with(" - exiting"));
+        assert!(monitor.take_exit_request(&mint));
+        assert!(!monitor.take_exit_request(&mint));
+    }
+}
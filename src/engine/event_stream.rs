@@ -0,0 +1,442 @@
+use std::collections::{HashMap, VecDeque};
+use std::pin::Pin;
+use std::sync::atomic::{AtomicU32, AtomicU64, AtomicU8, Ordering};
+use std::time::Duration;
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use colored::Colorize;
+use futures::{Sink, SinkExt, Stream, StreamExt};
+use once_cell::sync::Lazy;
+use rand::Rng;
+use yellowstone_grpc_client::GeyserGrpcClient;
+use yellowstone_grpc_proto::geyser::{
+    subscribe_update::UpdateOneof, CommitmentLevel, SubscribeRequest,
+    SubscribeRequestFilterTransactions, SubscribeRequestPing, SubscribeUpdate,
+    SubscribeUpdateTransaction,
+};
+use yellowstone_grpc_proto::tonic::Status;
+
+use crate::common::{clock, logger::Logger};
+use crate::dex::pump_fun::PUMP_FUN_PROGRAM;
+use crate::dex::raydium::RAYDIUM_AMM_PROGRAM;
+use crate::engine::trade_event::TradeEvent;
+use crate::engine::transaction_parser::{parse_transaction_data, TradeInfoFromToken};
+use crate::engine::wallet_sync::{self, BalanceChange};
+
+pub const PUMP_SWAP_PROGRAM: &str = "pAMMBay6oceH9fJKBRHGP5D4bD4sWpmSwMn52FMfXEA";
+const PROGRAM_DATA_PREFIX: &str = "Program data: ";
+/// Raydium AMM v4 logs its swap events as text rather than Anchor event data
+const RAY_LOG_PREFIX: &str = "Program log: ray_log: ";
+/// First pause before resubscribing; it doubles with every failed attempt up to RECONNECT_MAX_DELAY
+const RECONNECT_BASE_DELAY: Duration = Duration::from_secs(1);
+const RECONNECT_MAX_DELAY: Duration = Duration::from_secs(60);
+/// Each pause is randomized by up to this fraction either way, so restarted bots don't resubscribe in lockstep
+const RECONNECT_JITTER: f64 = 0.2;
+
+/// STREAM_STALL_SECS (default: 30): a connected stream that delivers nothing, pings included, for this long
+/// is resubscribed; 0 never
+static STALL_TIMEOUT: Lazy<Option<Duration>> = Lazy::new(|| {
+    let secs = std::env::var("STREAM_STALL_SECS")
+        .ok()
+        .and_then(|v| v.parse::<u64>().ok())
+        .unwrap_or(30);
+    (secs > 0).then(|| Duration::from_secs(secs))
+});
+
+/// STREAM_ALERT_AFTER_FAILURES (default: 5): failed resubscribes in a row before the stream counts as
+/// failed and a Telegram alert goes out
+static ALERT_AFTER_FAILURES: Lazy<u32> = Lazy::new(|| {
+    std::env::var("STREAM_ALERT_AFTER_FAILURES")
+        .ok()
+        .and_then(|v| v.parse::<u32>().ok())
+        .unwrap_or(5)
+        .max(1)
+});
+
+static STREAM_STATE: AtomicU8 = AtomicU8::new(0);
+static LAST_MESSAGE_AT: AtomicU64 = AtomicU64::new(0);
+static LAST_TRANSACTION_AT: AtomicU64 = AtomicU64::new(0);
+static CONSECUTIVE_FAILURES: AtomicU32 = AtomicU32::new(0);
+
+/// Where the live subscription stands
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum StreamState {
+    Connected,
+    /// Dropped or stalled, resubscribing with backoff
+    Reconnecting,
+    /// STREAM_ALERT_AFTER_FAILURES resubscribes failed in a row; still retrying
+    Failed,
+}
+
+impl StreamState {
+    fn store(self) {
+        let value = match self {
+            Self::Connected => 1,
+            Self::Reconnecting => 2,
+            Self::Failed => 3,
+        };
+        STREAM_STATE.store(value, Ordering::Relaxed);
+    }
+
+    fn load() -> Option<Self> {
+        match STREAM_STATE.load(Ordering::Relaxed) {
+            1 => Some(Self::Connected),
+            2 => Some(Self::Reconnecting),
+            3 => Some(Self::Failed),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Connected => "connected",
+            Self::Reconnecting => "reconnecting",
+            Self::Failed => "failed",
+        }
+    }
+}
+
+/// Connection state of the live stream for the status API and the task monitor
+#[derive(Clone, Copy, Debug)]
+pub struct StreamHealth {
+    /// None until the pipeline's stream first connects
+    pub state: Option<StreamState>,
+    /// Last update of any kind, pings included
+    pub last_message_at: Option<u64>,
+    pub last_transaction_at: Option<u64>,
+    /// Resubscribes failed since the stream was last connected
+    pub consecutive_failures: u32,
+}
+
+impl StreamHealth {
+    pub fn is_connected(&self) -> bool {
+        self.state == Some(StreamState::Connected)
+    }
+}
+
+pub fn stream_health() -> StreamHealth {
+    let at = |value: &AtomicU64| Some(value.load(Ordering::Relaxed)).filter(|at| *at > 0);
+    StreamHealth {
+        state: StreamState::load(),
+        last_message_at: at(&LAST_MESSAGE_AT),
+        last_transaction_at: at(&LAST_TRANSACTION_AT),
+        consecutive_failures: CONSECUTIVE_FAILURES.load(Ordering::Relaxed),
+    }
+}
+
+/// Pause before resubscribe attempt `attempt` (0-based): 1s doubling to 60s, jittered
+pub fn reconnect_delay(attempt: u32) -> Duration {
+    let base = RECONNECT_BASE_DELAY.saturating_mul(1u32 << attempt.min(16)).min(RECONNECT_MAX_DELAY);
+    let factor = rand::thread_rng().gen_range((1.0 - RECONNECT_JITTER)..=(1.0 + RECONNECT_JITTER));
+    base.mul_f64(factor)
+}
+
+/// Slots the stream was not delivering: from the last one seen before a disconnect to the first one after
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct StreamGap {
+    pub from_slot: u64,
+    pub to_slot: u64,
+    /// Corrected unix seconds, see `clock::now_secs`
+    pub disconnected_at: u64,
+    pub reconnected_at: u64,
+}
+
+impl StreamGap {
+    pub fn duration_secs(&self) -> u64 {
+        self.reconnected_at.saturating_sub(self.disconnected_at)
+    }
+}
+
+/// Source of parsed trade events for the orchestrator
+#[async_trait]
+pub trait EventSource: Send {
+    /// Next parsed trade, or None once the source is exhausted
+    async fn next_event(&mut self) -> Option<TradeEvent>;
+
+    /// Gap closed by the event `next_event` just returned, reported once
+    fn take_gap(&mut self) -> Option<StreamGap> {
+        None
+    }
+
+    /// Our wallet's balance changes seen since the last call, oldest first
+    fn take_balance_changes(&mut self) -> Vec<BalanceChange> {
+        Vec::new()
+    }
+}
+
+/// Replays a fixed sequence of events (paper runs and harnesses)
+pub struct ScriptedEventSource {
+    events: VecDeque<TradeEvent>,
+    /// Gaps keyed by the index of the event that closes them
+    gaps: VecDeque<(usize, StreamGap)>,
+    delivered: usize,
+    current_gap: Option<StreamGap>,
+}
+
+impl ScriptedEventSource {
+    /// Events with an invalid mint are dropped
+    pub fn new(events: Vec<TradeInfoFromToken>) -> Self {
+        Self {
+            events: events.into_iter().filter_map(TradeEvent::from_trade_info).collect(),
+            gaps: VecDeque::new(),
+            delivered: 0,
+            current_gap: None,
+        }
+    }
+
+    /// Report `gap` together with the event at `index` (0-based), as a reconnect would
+    pub fn with_gap(mut self, index: usize, gap: StreamGap) -> Self {
+        self.gaps.push_back((index, gap));
+        self.gaps.make_contiguous().sort_by_key(|(index, _)| *index);
+        self
+    }
+}
+
+#[async_trait]
+impl EventSource for ScriptedEventSource {
+    async fn next_event(&mut self) -> Option<TradeEvent> {
+        let event = self.events.pop_front()?;
+        if self.gaps.front().map(|(index, _)| *index == self.delivered).unwrap_or(false) {
+            self.current_gap = self.gaps.pop_front().map(|(_, gap)| gap);
+        }
+        self.delivered += 1;
+        Some(event)
+    }
+
+    fn take_gap(&mut self) -> Option<StreamGap> {
+        self.current_gap.take()
+    }
+}
+
+type UpdateStream = Pin<Box<dyn Stream<Item = Result<SubscribeUpdate, Status>> + Send>>;
+type RequestSink = Pin<Box<dyn Sink<SubscribeRequest, Error = futures::channel::mpsc::SendError> + Send>>;
+
+/// Yellowstone subscription to PumpFun/PumpSwap transactions, decoded through the shared parser
+/// A dropped stream is resubscribed; the slots missed meanwhile are reported through `take_gap`.
+pub struct GrpcEventSource {
+    endpoint: String,
+    token: String,
+    stream: UpdateStream,
+    sink: RequestSink,
+    pending: VecDeque<TradeEvent>,
+    /// Our wallet (base58); its transactions are subscribed too, for the balance changes they carry
+    wallet: Option<String>,
+    balance_changes: Vec<BalanceChange>,
+    /// Slot of the last transaction received
+    last_slot: Option<u64>,
+    /// Set while reconnected but no transaction has arrived yet: (last slot before, disconnected at)
+    open_gap: Option<(u64, u64)>,
+    closed_gap: Option<StreamGap>,
+    logger: Logger,
+}
+
+impl GrpcEventSource {
+    /// With `wallet`, every transaction touching it is delivered as well, see `take_balance_changes`
+    pub async fn connect(endpoint: &str, token: &str, wallet: Option<String>) -> Result<Self> {
+        let logger = Logger::new("[EVENT-STREAM] => ".blue().to_string());
+        let (sink, stream) = Self::subscribe(endpoint, token, wallet.as_deref()).await?;
+        logger.critical("Subscribed to PumpFun/PumpSwap transactions".green().to_string());
+        StreamState::Connected.store();
+        LAST_MESSAGE_AT.store(clock::now_secs(), Ordering::Relaxed);
+
+        Ok(Self {
+            endpoint: endpoint.to_string(),
+            token: token.to_string(),
+            stream,
+            sink,
+            pending: VecDeque::new(),
+            wallet,
+            balance_changes: Vec::new(),
+            last_slot: None,
+            open_gap: None,
+            closed_gap: None,
+            logger,
+        })
+    }
+
+    async fn subscribe(endpoint: &str, token: &str, wallet: Option<&str>) -> Result<(RequestSink, UpdateStream)> {
+        let mut client = GeyserGrpcClient::build_from_shared(endpoint.to_string())
+            .map_err(|e| anyhow!("Invalid gRPC endpoint: {}", e))?
+            .x_token(Some(token.to_string()))
+            .map_err(|e| anyhow!("Invalid gRPC token: {}", e))?
+            .tls_config(yellowstone_grpc_client::ClientTlsConfig::new().with_native_roots())
+            .map_err(|e| anyhow!("Failed to configure TLS: {}", e))?
+            .connect()
+            .await
+            .map_err(|e| anyhow!("Failed to connect to gRPC: {}", e))?;
+
+        let (sink, stream) = client.subscribe_with_request(Some(Self::subscribe_request(wallet)))
+            .await
+            .map_err(|e| anyhow!("Failed to subscribe: {}", e))?;
+        Ok((Box::pin(sink), Box::pin(stream)))
+    }
+
+    /// Resubscribe with the same filters until it succeeds, remembering where the stream left off
+    /// Pauses back off exponentially; STREAM_ALERT_AFTER_FAILURES failures in a row raise one alert per outage.
+    async fn reconnect(&mut self) {
+        crate::services::session_report::record_stream_disconnect();
+        StreamState::Reconnecting.store();
+        // A second drop before any transaction arrived extends the gap already open
+        if self.open_gap.is_none() {
+            if let Some(last_slot) = self.last_slot {
+                self.open_gap = Some((last_slot, clock::now_secs()));
+            }
+        }
+        self.pending.clear();
+        let mut failures: u32 = 0;
+        loop {
+            tokio::time::sleep(reconnect_delay(failures)).await;
+            match Self::subscribe(&self.endpoint, &self.token, self.wallet.as_deref()).await {
+                Ok((sink, stream)) => {
+                    self.sink = sink;
+                    self.stream = stream;
+                    self.logger.critical(format!("Resubscribed to PumpFun/PumpSwap transactions after {} failed attempt(s)", failures).green().to_string());
+                    if failures >= *ALERT_AFTER_FAILURES {
+                        notify(format!("✅ gRPC stream reconnected after {} failed attempts", failures));
+                    }
+                    CONSECUTIVE_FAILURES.store(0, Ordering::Relaxed);
+                    LAST_MESSAGE_AT.store(clock::now_secs(), Ordering::Relaxed);
+                    StreamState::Connected.store();
+                    return;
+                }
+                Err(e) => {
+                    failures += 1;
+                    CONSECUTIVE_FAILURES.store(failures, Ordering::Relaxed);
+                    let next = reconnect_delay(failures);
+                    self.logger.error(format!("Reconnect attempt {} failed: {} (retrying in ~{}s)", failures, e, next.as_secs()));
+                    if failures == *ALERT_AFTER_FAILURES {
+                        StreamState::Failed.store();
+                        let message = format!("🚨 gRPC stream down: {} reconnect attempts failed in a row, last error: {}", failures, e);
+                        self.logger.critical(message.clone());
+                        notify(message);
+                    }
+                }
+            }
+        }
+    }
+
+    fn observe_slot(&mut self, slot: u64) {
+        LAST_TRANSACTION_AT.store(clock::now_secs(), Ordering::Relaxed);
+        if let Some((from_slot, disconnected_at)) = self.open_gap.take() {
+            self.closed_gap = Some(StreamGap { from_slot, to_slot: slot, disconnected_at, reconnected_at: clock::now_secs() });
+        }
+        self.last_slot = Some(self.last_slot.map_or(slot, |last| last.max(slot)));
+    }
+
+    fn subscribe_request(wallet: Option<&str>) -> SubscribeRequest {
+        let mut transactions = HashMap::new();
+        transactions.insert(
+            "pump".to_string(),
+            SubscribeRequestFilterTransactions {
+                vote: Some(false),
+                failed: Some(false),
+                signature: None,
+                account_include: vec![PUMP_FUN_PROGRAM.to_string(), PUMP_SWAP_PROGRAM.to_string(), RAYDIUM_AMM_PROGRAM.to_string()],
+                account_exclude: vec![],
+                account_required: vec![],
+            },
+        );
+        if let Some(wallet) = wallet {
+            // Failed ones move no tokens; ours that land on a monitored program arrive through both filters
+            transactions.insert(
+                "wallet".to_string(),
+                SubscribeRequestFilterTransactions {
+                    vote: Some(false),
+                    failed: Some(false),
+                    signature: None,
+                    account_include: vec![wallet.to_string()],
+                    account_exclude: vec![],
+                    account_required: vec![],
+                },
+            );
+        }
+        SubscribeRequest {
+            transactions,
+            commitment: Some(CommitmentLevel::Processed as i32),
+            ..Default::default()
+        }
+    }
+}
+
+/// Telegram alert about the stream, queued without holding up the reconnect loop
+fn notify(message: String) {
+    if crate::services::telegram::is_configured() {
+        let _ = crate::services::telegram::enqueue(message);
+    }
+}
+
+/// Decode every "Program data:" and "ray_log:" event in the transaction logs the parser recognises
+pub fn decode_trade_events(txn: &SubscribeUpdateTransaction) -> Vec<TradeInfoFromToken> {
+    let Some(meta) = txn.transaction.as_ref().and_then(|tx| tx.meta.as_ref()) else {
+        return Vec::new();
+    };
+    meta.log_messages
+        .iter()
+        .filter_map(|log| log.strip_prefix(PROGRAM_DATA_PREFIX).or_else(|| log.strip_prefix(RAY_LOG_PREFIX)))
+        .filter_map(|data| base64::decode(data).ok())
+        .filter_map(|buffer| parse_transaction_data(txn, &buffer))
+        .collect()
+}
+
+#[async_trait]
+impl EventSource for GrpcEventSource {
+    async fn next_event(&mut self) -> Option<TradeEvent> {
+        loop {
+            if let Some(event) = self.pending.pop_front() {
+                return Some(event);
+            }
+
+            let next = match *STALL_TIMEOUT {
+                Some(stall) => match tokio::time::timeout(stall, self.stream.next()).await {
+                    Ok(next) => next,
+                    Err(_) => {
+                        self.logger.error(format!("gRPC stream delivered nothing for {}s, resubscribing", stall.as_secs()));
+                        self.reconnect().await;
+                        continue;
+                    }
+                },
+                None => self.stream.next().await,
+            };
+            if let Some(Ok(_)) = &next {
+                LAST_MESSAGE_AT.store(clock::now_secs(), Ordering::Relaxed);
+            }
+            match next {
+                Some(Ok(update)) => match update.update_oneof {
+                    Some(UpdateOneof::Transaction(txn)) => {
+                        self.observe_slot(txn.slot);
+                        if let Some(wallet) = &self.wallet {
+                            self.balance_changes.extend(wallet_sync::balance_changes(&txn, wallet));
+                        }
+                        self.pending.extend(decode_trade_events(&txn).into_iter().filter_map(TradeEvent::from_trade_info));
+                    }
+                    Some(UpdateOneof::Ping(_)) => {
+                        let pong = SubscribeRequest {
+                            ping: Some(SubscribeRequestPing { id: 1 }),
+                            ..Default::default()
+                        };
+                        if let Err(e) = self.sink.send(pong).await {
+                            self.logger.error(format!("Failed to answer ping: {}", e));
+                        }
+                    }
+                    _ => {}
+                },
+                Some(Err(e)) => {
+                    self.logger.error(format!("gRPC stream error: {}", e));
+                    self.reconnect().await;
+                }
+                None => {
+                    self.logger.error("gRPC stream closed by the server".to_string());
+                    self.reconnect().await;
+                }
+            }
+        }
+    }
+
+    fn take_gap(&mut self) -> Option<StreamGap> {
+        self.closed_gap.take()
+    }
+
+    fn take_balance_changes(&mut self) -> Vec<BalanceChange> {
+        std::mem::take(&mut self.balance_changes)
+    }
+}
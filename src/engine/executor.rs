@@ -0,0 +1,426 @@
+use std::sync::Arc;
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use colored::Colorize;
+use dashmap::DashMap;
+use once_cell::sync::Lazy;
+use solana_sdk::signature::Signer;
+
+use crate::common::{
+    config::{AppState, SwapConfig},
+    logger::Logger,
+};
+use crate::core::tx;
+use crate::dex::{math, pump_fun::Pump, pump_swap::PumpSwap, raydium::{self, Raydium}};
+use crate::engine::swap::{SwapDirection, SwapInType};
+use crate::engine::transaction_parser::{DexType, TradeInfoFromToken};
+use crate::services::nonce_health::{self, BlockhashMode};
+use crate::services::trade_costs::TradeCosts;
+use crate::services::zeroslot;
+
+/// Tokens DRY_RUN buys hold, by mint; the DRY_RUN counterpart of TOKEN_HOLDINGS
+/// None of them is in the wallet, so there is no token account to build or simulate their sell against.
+static DRY_RUN_HOLDINGS: Lazy<DashMap<String, f64>> = Lazy::new(DashMap::new);
+
+/// A holding below this is dust left by float rounding and is dropped
+const DRY_RUN_DUST: f64 = 1e-9;
+
+/// UI tokens DRY_RUN buys of `mint` still hold
+pub fn dry_run_holding(mint: &str) -> Option<f64> {
+    DRY_RUN_HOLDINGS.get(mint).map(|held| *held)
+}
+
+fn hold_dry_run_tokens(mint: &str, token_amount: f64) {
+    *DRY_RUN_HOLDINGS.entry(mint.to_string()).or_default() += token_amount;
+}
+
+/// Take up to `token_amount` UI tokens off the DRY_RUN holding of `mint`; None when the mint has none, so the sell
+/// goes through the simulated retry chain against the real balance
+fn release_dry_run_tokens(mint: &str, token_amount: f64) -> Option<f64> {
+    let mut held = DRY_RUN_HOLDINGS.get_mut(mint)?;
+    let released = token_amount.min(*held);
+    *held -= released;
+    let emptied = *held <= DRY_RUN_DUST;
+    drop(held);
+    if emptied {
+        DRY_RUN_HOLDINGS.remove(mint);
+    }
+    Some(released)
+}
+
+/// Outcome of a buy or sell sent by an executor
+#[derive(Clone, Debug)]
+pub struct ExecutionReport {
+    pub signature: String,
+    /// UI token amount bought or sold
+    pub token_amount: f64,
+    /// SOL spent (buy) or received (sell), estimated from the curve
+    pub sol_amount: f64,
+    pub price: f64,
+    pub simulated: bool,
+    /// Blockhash source of live transactions
+    pub blockhash_mode: Option<BlockhashMode>,
+    /// Estimated at send time; the orchestrator replaces them with the landed transaction's
+    pub costs: TradeCosts,
+}
+
+/// Places trades on behalf of the orchestrator
+#[async_trait]
+pub trait TradeExecutor: Send + Sync {
+    async fn buy(&self, trade_info: &TradeInfoFromToken, swap_config: &SwapConfig) -> Result<ExecutionReport>;
+
+    /// Sell `token_amount` UI tokens of `trade_info.mint`
+    async fn sell(&self, trade_info: &TradeInfoFromToken, swap_config: &SwapConfig, token_amount: f64, reason: &str) -> Result<ExecutionReport>;
+}
+
+/// Estimated (UI tokens, price) for a buy of `sol_amount` SOL at the event's reserves, on the event's venue
+/// The token reserves are in `token_decimals` units: the mint's own on PumpFun and PumpSwap, 6 on Raydium.
+fn estimate_buy(trade_info: &TradeInfoFromToken, sol_amount: f64) -> (f64, f64) {
+    let lamports = math::ui_to_raw(sol_amount, spl_token::native_mint::DECIMALS);
+    let (sol_reserves, token_reserves) = (trade_info.virtual_sol_reserves, trade_info.virtual_token_reserves);
+    let raw_tokens = match trade_info.dex_type {
+        DexType::PumpSwap => PumpSwap::calculate_buy_token_amount(lamports, sol_reserves, token_reserves),
+        // Raydium takes its fee off the SOL going in
+        DexType::RaydiumAmm => math::constant_product_out(math::min_amount_with_slippage(lamports, raydium::SWAP_FEE_BPS), sol_reserves, token_reserves),
+        _ => Pump::calculate_buy_token_amount(lamports, sol_reserves, token_reserves),
+    };
+    let token_amount = raw_tokens as f64 / 10f64.powi(trade_info.token_decimals as i32);
+    let price = if token_amount > 0.0 { sol_amount / token_amount } else { trade_info.post_current_price };
+    (token_amount, price)
+}
+
+/// Estimated SOL out for selling `token_amount` UI tokens at the event's reserves, on the event's venue
+fn estimate_sell(trade_info: &TradeInfoFromToken, token_amount: f64) -> f64 {
+    let raw_tokens = math::ui_to_raw(token_amount, trade_info.token_decimals);
+    let (sol_reserves, token_reserves) = (trade_info.virtual_sol_reserves, trade_info.virtual_token_reserves);
+    let lamports = match trade_info.dex_type {
+        DexType::PumpSwap => PumpSwap::calculate_sell_sol_amount(raw_tokens, sol_reserves, token_reserves),
+        // Raydium takes its fee off the tokens going in
+        DexType::RaydiumAmm => math::constant_product_out(math::min_amount_with_slippage(raw_tokens, raydium::SWAP_FEE_BPS), token_reserves, sol_reserves),
+        _ => Pump::calculate_sell_sol_amount(raw_tokens, sol_reserves, token_reserves),
+    };
+    lamports as f64 / 1_000_000_000.0
+}
+
+/// Fills orders against the event's own reserves without touching the network
+pub struct PaperExecutor {
+    logger: Logger,
+}
+
+impl PaperExecutor {
+    pub fn new() -> Self {
+        Self {
+            logger: Logger::new("[PAPER-EXECUTOR] => ".magenta().to_string()),
+        }
+    }
+}
+
+impl Default for PaperExecutor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[async_trait]
+impl TradeExecutor for PaperExecutor {
+    async fn buy(&self, trade_info: &TradeInfoFromToken, swap_config: &SwapConfig) -> Result<ExecutionReport> {
+        let (token_amount, price) = estimate_buy(trade_info, swap_config.amount_in);
+        if token_amount <= 0.0 {
+            return Err(anyhow!("Paper buy for {} would receive no tokens", trade_info.mint));
+        }
+        self.logger.critical(format!("Paper buy {} - {:.6} SOL -> {:.2} tokens", trade_info.mint, swap_config.amount_in, token_amount));
+        Ok(ExecutionReport {
+            signature: format!("paper-buy-{}-{}", trade_info.mint, trade_info.slot),
+            token_amount,
+            sol_amount: swap_config.amount_in,
+            price,
+            simulated: true,
+            blockhash_mode: None,
+            costs: TradeCosts::default(),
+        })
+    }
+
+    async fn sell(&self, trade_info: &TradeInfoFromToken, _swap_config: &SwapConfig, token_amount: f64, reason: &str) -> Result<ExecutionReport> {
+        let sol_amount = estimate_sell(trade_info, token_amount);
+        self.logger.critical(format!("Paper sell {} - {:.2} tokens -> {:.6} SOL ({})", trade_info.mint, token_amount, sol_amount, reason));
+        Ok(ExecutionReport {
+            signature: format!("paper-sell-{}-{}", trade_info.mint, trade_info.slot),
+            token_amount,
+            sol_amount,
+            price: trade_info.post_current_price,
+            simulated: true,
+            blockhash_mode: None,
+            costs: TradeCosts::default(),
+        })
+    }
+}
+
+/// Sends real PumpFun, PumpSwap and Raydium transactions through zeroslot, with the retry/Jupiter fallback chain for sells
+pub struct LiveExecutor {
+    app_state: Arc<AppState>,
+    logger: Logger,
+}
+
+impl LiveExecutor {
+    pub fn new(app_state: Arc<AppState>) -> Self {
+        Self {
+            app_state,
+            logger: Logger::new("[LIVE-EXECUTOR] => ".green().to_string()),
+        }
+    }
+
+    fn pump(&self) -> Pump {
+        Pump::new(
+            self.app_state.rpc_nonblocking_client.clone(),
+            self.app_state.rpc_client.clone(),
+            self.app_state.wallet.clone(),
+        )
+    }
+
+    fn pump_swap(&self) -> PumpSwap {
+        PumpSwap::new(
+            self.app_state.wallet.clone(),
+            Some(self.app_state.rpc_client.clone()),
+            Some(self.app_state.rpc_nonblocking_client.clone()),
+        )
+    }
+
+    fn raydium(&self) -> Raydium {
+        Raydium::new(
+            self.app_state.rpc_nonblocking_client.clone(),
+            self.app_state.rpc_client.clone(),
+            self.app_state.wallet.clone(),
+        )
+    }
+}
+
+#[async_trait]
+impl TradeExecutor for LiveExecutor {
+    async fn buy(&self, trade_info: &TradeInfoFromToken, swap_config: &SwapConfig) -> Result<ExecutionReport> {
+        let mut buy_config = swap_config.clone();
+        buy_config.swap_direction = SwapDirection::Buy;
+        buy_config.in_type = SwapInType::Qty;
+
+        let (venue, built) = match trade_info.dex_type {
+            DexType::PumpFun => ("PumpFun", self.pump().build_swap_from_parsed_data(trade_info, buy_config.clone()).await),
+            DexType::PumpSwap => ("PumpSwap", self.pump_swap().build_swap_from_parsed_data(trade_info, buy_config.clone()).await),
+            DexType::RaydiumAmm => ("Raydium", self.raydium().build_swap_from_parsed_data(trade_info, buy_config.clone()).await),
+            other => return Err(anyhow!("Live buys are only supported on PumpFun, PumpSwap and Raydium, got {:?}", other)),
+        };
+        let (keypair, mut instructions, _price) = built
+            .map_err(|e| anyhow!("{} build_swap_from_parsed_data failed: {}", venue, e))?;
+
+        let (blockhash, blockhash_mode) = nonce_health::prepare_blockhash(&self.app_state, &mut instructions).await?;
+
+        // DRY_RUN: the same transaction is simulated instead, and filled at the event's reserves like a paper buy
+        if buy_config.dry_run {
+            tx::simulate_zeroslot_transaction(self.app_state.rpc_nonblocking_client.clone(), blockhash, &keypair, instructions, &self.logger)
+                .await
+                .map_err(|e| anyhow!("{} buy simulation failed: {}", venue, e))?;
+            let (token_amount, price) = estimate_buy(trade_info, buy_config.amount_in);
+            hold_dry_run_tokens(&trade_info.mint, token_amount);
+            self.logger.critical(format!("🧪 [DRY RUN] Buy simulated for {} - {:.6} SOL -> {:.2} tokens", trade_info.mint, buy_config.amount_in, token_amount).yellow().to_string());
+            return Ok(ExecutionReport {
+                signature: format!("dry-run-buy-{}-{}", trade_info.mint, trade_info.slot),
+                token_amount,
+                sol_amount: buy_config.amount_in,
+                price,
+                simulated: true,
+                blockhash_mode: Some(blockhash_mode),
+                costs: TradeCosts::default(),
+            });
+        }
+
+        let send_result = tx::new_signed_and_send_zeroslot(
+            self.app_state.zeroslot_rpc_client.clone(),
+            blockhash,
+            &keypair,
+            instructions,
+            &self.logger,
+            true,
+            Some(trade_info.slot),
+        ).await;
+        let send_error = send_result.as_ref().err().map(|e| e.to_string());
+        nonce_health::record_send_outcome(&keypair.pubkey(), blockhash_mode, send_error.as_deref());
+        let sent = send_result.map_err(|e| anyhow!("{} buy send failed: {}", venue, e))?;
+        let signature = sent.signature;
+
+        let (token_amount, price) = estimate_buy(trade_info, buy_config.amount_in);
+        self.logger.critical(format!("✅ Buy sent for {}: {} {}", trade_info.mint, signature, sent.endpoint).green().to_string());
+        Ok(ExecutionReport {
+            signature,
+            token_amount,
+            sol_amount: buy_config.amount_in,
+            price,
+            simulated: false,
+            blockhash_mode: Some(blockhash_mode),
+            costs: TradeCosts::estimated(sent.tip_sol, tx::estimated_priority_fee_sol(true)),
+        })
+    }
+
+    async fn sell(&self, trade_info: &TradeInfoFromToken, swap_config: &SwapConfig, token_amount: f64, reason: &str) -> Result<ExecutionReport> {
+        // The retry chain refuses to build a sell without a recorded reason
+        let attempt_id = crate::engine::sell_reasons::begin_attempt(&trade_info.mint, reason);
+
+        // DRY_RUN: tokens a dry-run buy "bought" are sold at the event's reserves, as that buy was filled
+        if swap_config.dry_run {
+            if let Some(token_amount) = release_dry_run_tokens(&trade_info.mint, token_amount) {
+                let signature = format!("dry-run-sell-{}-{}", trade_info.mint, trade_info.slot);
+                crate::engine::sell_reasons::attempt_landed(&trade_info.mint, attempt_id, &signature);
+                let sol_amount = estimate_sell(trade_info, token_amount);
+                self.logger.critical(format!("🧪 [DRY RUN] Sell filled for {} - {:.2} tokens -> {:.6} SOL ({})", trade_info.mint, token_amount, sol_amount, reason).yellow().to_string());
+                return Ok(ExecutionReport {
+                    signature,
+                    token_amount,
+                    sol_amount,
+                    price: trade_info.post_current_price,
+                    simulated: true,
+                    blockhash_mode: None,
+                    costs: TradeCosts::default(),
+                });
+            }
+        }
+
+        let mut sell_config = swap_config.clone();
+        sell_config.swap_direction = SwapDirection::Sell;
+        sell_config.in_type = SwapInType::Qty;
+        sell_config.amount_in = token_amount;
+
+        // Mode the PumpFun attempt will use; sampled before it can trip the nonce switch
+        let pumpfun_mode = nonce_health::effective_mode(&self.app_state.wallet.pubkey());
+
+        let (result, claim) = crate::engine::transaction_retry::execute_claimed_sell(
+            trade_info,
+            sell_config,
+            self.app_state.clone(),
+            &self.logger,
+        ).await;
+        // The pipeline tracks the fill itself, so the sell is no longer in flight for the next exit decision;
+        // a call turned away by a running sell has no claim and leaves that sell's entry alone
+        drop(claim);
+        let result = match result {
+            Ok(result) if result.success => result,
+            Ok(result) => {
+                crate::engine::sell_reasons::attempt_failed(&trade_info.mint, attempt_id);
+                return Err(anyhow!(result.error.unwrap_or_else(|| "Sell failed".to_string())));
+            }
+            Err(e) => {
+                crate::engine::sell_reasons::attempt_failed(&trade_info.mint, attempt_id);
+                return Err(e);
+            }
+        };
+
+        // Jupiter builds its own transaction around a recent blockhash
+        let blockhash_mode = if result.used_jupiter_fallback { BlockhashMode::Recent } else { pumpfun_mode };
+        let signature = match result.signature {
+            Some(signature) => signature.to_string(),
+            None if result.simulated => format!("dry-run-sell-{}-{}", trade_info.mint, trade_info.slot),
+            None => String::new(),
+        };
+        crate::engine::sell_reasons::attempt_landed(&trade_info.mint, attempt_id, &signature);
+        if let Some(mut held) = crate::engine::sniper::TOKEN_HOLDINGS.get_mut(&trade_info.mint) {
+            held.current_amount = (held.current_amount - token_amount).max(0.0);
+        }
+        Ok(ExecutionReport {
+            signature,
+            token_amount,
+            sol_amount: estimate_sell(trade_info, token_amount),
+            price: trade_info.post_current_price,
+            simulated: result.simulated,
+            blockhash_mode: Some(blockhash_mode),
+            // Jupiter sells go through the normal RPC without a tip or our compute budget
+            costs: if result.simulated {
+                TradeCosts::default()
+            } else if result.used_jupiter_fallback {
+                TradeCosts::estimated(0.0, 0.0)
+            } else {
+                TradeCosts::estimated(zeroslot::get_tip_value().await.unwrap_or(0.0), tx::estimated_priority_fee_sol(false))
+            },
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::test_fixtures::{pubkey, trade};
+
+    #[test]
+    fn dry_run_buys_hold_tokens_their_sells_release() {
+        let mint = pubkey();
+        assert_eq!(release_dry_run_tokens(&mint, 10.0), None, "a mint never dry-run bought goes through the chain");
+
+        hold_dry_run_tokens(&mint, 100.0);
+        hold_dry_run_tokens(&mint, 50.0);
+        assert_eq!(dry_run_holding(&mint), Some(150.0));
+
+        assert_eq!(release_dry_run_tokens(&mint, 60.0), Some(60.0));
+        assert_eq!(dry_run_holding(&mint), Some(90.0));
+        // A sell for more than is held only gets what the buys left
+        assert_eq!(release_dry_run_tokens(&mint, 200.0), Some(90.0));
+        assert_eq!(dry_run_holding(&mint), None);
+        assert_eq!(release_dry_run_tokens(&mint, 1.0), None);
+    }
+
+    #[test]
+    fn rounding_dust_empties_the_holding() {
+        let mint = pubkey();
+        hold_dry_run_tokens(&mint, 0.3);
+        assert_eq!(release_dry_run_tokens(&mint, 0.1 + 0.2 - 1e-12), Some(0.1 + 0.2 - 1e-12));
+        assert_eq!(dry_run_holding(&mint), None);
+    }
+
+    /// A pool of `sol` SOL and `tokens` UI tokens on `dex_type`, its token reserves at `decimals`
+    fn pool(dex_type: DexType, sol: f64, tokens: f64, decimals: u8) -> TradeInfoFromToken {
+        TradeInfoFromToken {
+            dex_type,
+            token_decimals: decimals,
+            virtual_sol_reserves: math::ui_to_raw(sol, 9),
+            virtual_token_reserves: math::ui_to_raw(tokens, decimals),
+            ..trade(&pubkey(), true, sol / tokens)
+        }
+    }
+
+    fn assert_close(actual: f64, expected: f64) {
+        assert!((actual - expected).abs() <= expected * 1e-6, "{} != {}", actual, expected);
+    }
+
+    #[test]
+    fn pump_swap_estimates_use_the_mint_decimals() {
+        let trade_info = pool(DexType::PumpSwap, 100.0, 1_000_000.0, 9);
+        let (tokens, price) = estimate_buy(&trade_info, 1.0);
+        assert_close(tokens, 1_000_000.0 / 101.0);
+        assert_close(price, 101.0 / 1_000_000.0);
+        assert_close(estimate_sell(&trade_info, 10_000.0), 100.0 * 10_000.0 / 1_010_000.0);
+    }
+
+    #[test]
+    fn raydium_estimates_charge_the_fee_on_the_way_in() {
+        let trade_info = pool(DexType::RaydiumAmm, 100.0, 1_000_000.0, 6);
+        let (tokens, _) = estimate_buy(&trade_info, 1.0);
+        assert_close(tokens, 1_000_000.0 * 0.9975 / 100.9975);
+        assert_close(estimate_sell(&trade_info, 10_000.0), 100.0 * 9_975.0 / 1_009_975.0);
+
+        // The same pool without the fee buys more
+        let (fee_free, _) = estimate_buy(&pool(DexType::PumpSwap, 100.0, 1_000_000.0, 6), 1.0);
+        assert!(fee_free > tokens);
+    }
+
+    #[test]
+    fn pump_fun_estimates_follow_the_curve() {
+        let trade_info = pool(DexType::PumpFun, 30.0, 1_073_000_000.0, 6);
+        let (tokens, price) = estimate_buy(&trade_info, 0.1);
+        assert_close(tokens, 1_073_000_000.0 * 0.1 / 30.1);
+        assert_close(price, 0.1 / tokens);
+        // Selling the tokens straight back on the untouched curve returns a little less than was spent
+        let sol = estimate_sell(&trade_info, tokens);
+        assert!(sol < 0.1 && sol > 0.099, "{}", sol);
+    }
+
+    #[test]
+    fn an_empty_pool_estimates_nothing() {
+        let trade_info = TradeInfoFromToken { virtual_token_reserves: 0, ..pool(DexType::PumpSwap, 100.0, 1.0, 9) };
+        assert_eq!(estimate_buy(&trade_info, 1.0), (0.0, trade_info.post_current_price));
+        assert_eq!(estimate_sell(&trade_info, 1.0), 0.0);
+    }
+}
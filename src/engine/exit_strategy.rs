@@ -0,0 +1,411 @@
+use std::fmt;
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::common::clock;
+use crate::engine::position::Position;
+
+/// One take-profit tier: once price is `gain_pct` above entry, sell `sell_pct` of the original position
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ProfitTier {
+    pub gain_pct: f64,
+    pub sell_pct: f64,
+}
+
+/// Per-position overlay for `RuntimeParams::with_overrides`: param name to JSON value (null turns an optional stop off)
+pub type ParamOverrides = serde_json::Map<String, serde_json::Value>;
+
+/// Tunable exit parameters evaluated by the selling supervisor (and by --simulate-strategy)
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RuntimeParams {
+    /// Sorted by ascending gain
+    pub profit_tiers: Vec<ProfitTier>,
+    /// Full exit when price falls this many percent below the peak (only once in profit)
+    pub trailing_stop_pct: Option<f64>,
+    /// Seconds after the position opened before the trailing stop may fire, so post-buy noise can't trip it
+    pub trailing_stop_arm_secs: u64,
+    /// Only trades with at least this much `liquidity` (SOL) move the peak, so dust trades can't poison it
+    pub trailing_stop_min_liquidity: f64,
+    /// Full exit when price falls this many percent below entry
+    pub stop_loss_pct: Option<f64>,
+}
+
+impl RuntimeParams {
+    /// Read exit parameters from env, ignoring a PROFIT_LADDER that doesn't parse (see `try_from_env`)
+    pub fn from_env() -> Self {
+        Self::try_from_env().unwrap_or_else(|e| {
+            eprintln!("Ignoring PROFIT_LADDER: {}", e);
+            Self::read_env(take_profit_tiers_from_env())
+        })
+    }
+
+    /// Read exit parameters from env
+    /// TAKE_PROFIT_TIERS format: "gain_pct:sell_pct" pairs, comma-separated (e.g. "50:30,100:30")
+    /// PROFIT_LADDER format: "multiple:fraction" pairs (e.g. "2.0:0.5,3.0:0.25"); when set it replaces
+    /// TAKE_PROFIT_TIERS, and a malformed rung or fractions summing past 1.0 are an error.
+    pub fn try_from_env() -> Result<Self> {
+        let profit_tiers = match std::env::var("PROFIT_LADDER").ok().filter(|v| !v.trim().is_empty()) {
+            Some(ladder) => parse_profit_ladder(&ladder)?,
+            None => take_profit_tiers_from_env(),
+        };
+        Ok(Self::read_env(profit_tiers))
+    }
+
+    /// TRAILING_STOP_ARM_SECS (default: 5), TRAILING_STOP_MIN_LIQUIDITY (default: 0)
+    fn read_env(profit_tiers: Vec<ProfitTier>) -> Self {
+        let trailing_stop_pct = std::env::var("TRAILING_STOP_PCT")
+            .ok()
+            .and_then(|v| v.parse::<f64>().ok())
+            .filter(|v| *v > 0.0);
+        let trailing_stop_arm_secs = std::env::var("TRAILING_STOP_ARM_SECS")
+            .ok()
+            .and_then(|v| v.parse::<u64>().ok())
+            .unwrap_or(5);
+        let trailing_stop_min_liquidity = std::env::var("TRAILING_STOP_MIN_LIQUIDITY")
+            .ok()
+            .and_then(|v| v.parse::<f64>().ok())
+            .filter(|v| *v >= 0.0)
+            .unwrap_or(0.0);
+        let stop_loss_pct = std::env::var("STOP_LOSS_PCT")
+            .ok()
+            .and_then(|v| v.parse::<f64>().ok())
+            .filter(|v| *v > 0.0);
+
+        Self {
+            profit_tiers,
+            trailing_stop_pct,
+            trailing_stop_arm_secs,
+            trailing_stop_min_liquidity,
+            stop_loss_pct,
+        }
+    }
+
+    /// Rules every params set must satisfy, whether from env, a params file or a position override
+    pub fn validate(&self) -> Result<()> {
+        if let Some(pct) = self.stop_loss_pct {
+            if !(pct > 0.0 && pct <= 100.0) {
+                return Err(anyhow!("stop_loss_pct must be in (0, 100], got {}", pct));
+            }
+        }
+        if let Some(pct) = self.trailing_stop_pct {
+            if !(pct > 0.0 && pct < 100.0) {
+                return Err(anyhow!("trailing_stop_pct must be in (0, 100), got {}", pct));
+            }
+        }
+        if self.trailing_stop_min_liquidity.is_nan() || self.trailing_stop_min_liquidity < 0.0 {
+            return Err(anyhow!("trailing_stop_min_liquidity must be >= 0, got {}", self.trailing_stop_min_liquidity));
+        }
+        for tier in &self.profit_tiers {
+            // Written so NaN fails too
+            let valid = tier.gain_pct > 0.0 && tier.sell_pct > 0.0 && tier.sell_pct <= 100.0;
+            if !valid {
+                return Err(anyhow!("profit tier {}:{} needs gain > 0 and sell in (0, 100]", tier.gain_pct, tier.sell_pct));
+            }
+        }
+        Ok(())
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.profit_tiers.is_empty() && self.trailing_stop_pct.is_none() && self.stop_loss_pct.is_none()
+    }
+
+    /// Overlay the keys of a JSON object onto these params; missing keys keep their current value
+    pub fn with_overrides(&self, overrides: &serde_json::Value) -> Result<Self> {
+        let serde_json::Value::Object(overrides) = overrides else {
+            return Err(anyhow!("Params override must be a JSON object"));
+        };
+        let mut merged = serde_json::to_value(self)?;
+        if let serde_json::Value::Object(base) = &mut merged {
+            for (key, value) in overrides {
+                if !base.contains_key(key) {
+                    return Err(anyhow!("Unknown param '{}'", key));
+                }
+                base.insert(key.clone(), value.clone());
+            }
+        }
+        let mut params: RuntimeParams = serde_json::from_value(merged)
+            .map_err(|e| anyhow!("Invalid params override: {}", e))?;
+        params.profit_tiers.sort_by(|a, b| a.gain_pct.total_cmp(&b.gain_pct));
+        params.validate()?;
+        Ok(params)
+    }
+
+    /// Load a JSON override file on top of these params
+    pub fn with_override_file(&self, path: &str) -> Result<Self> {
+        let contents = std::fs::read_to_string(path)
+            .map_err(|e| anyhow!("Failed to read params file {}: {}", path, e))?;
+        let overrides: serde_json::Value = serde_json::from_str(&contents)
+            .map_err(|e| anyhow!("Failed to parse params file {}: {}", path, e))?;
+        self.with_overrides(&overrides)
+    }
+}
+
+fn take_profit_tiers_from_env() -> Vec<ProfitTier> {
+    std::env::var("TAKE_PROFIT_TIERS")
+        .ok()
+        .map(|value| parse_profit_tiers(&value))
+        .unwrap_or_default()
+}
+
+/// Parse "multiple:fraction,..." into tiers: 2.0:0.5 sells half of the original position at 2x entry
+/// Strict, unlike `parse_profit_tiers`: every rung needs a multiple above 1 and a fraction in (0, 1],
+/// no two rungs may share a multiple, and the fractions may not sum to more than 1.0.
+pub fn parse_profit_ladder(value: &str) -> Result<Vec<ProfitTier>> {
+    let mut tiers = Vec::new();
+    let mut total_fraction = 0.0;
+    for rung in value.split(',').map(str::trim).filter(|rung| !rung.is_empty()) {
+        let (multiple, fraction) = rung.split_once(':')
+            .ok_or_else(|| anyhow!("rung '{}' is not multiple:fraction", rung))?;
+        let multiple = multiple.trim().parse::<f64>()
+            .map_err(|_| anyhow!("rung '{}' has an invalid multiple", rung))?;
+        let fraction = fraction.trim().parse::<f64>()
+            .map_err(|_| anyhow!("rung '{}' has an invalid fraction", rung))?;
+        if !(multiple > 1.0 && multiple.is_finite()) {
+            return Err(anyhow!("rung '{}' needs a multiple above 1.0", rung));
+        }
+        if !(fraction > 0.0 && fraction <= 1.0) {
+            return Err(anyhow!("rung '{}' needs a fraction in (0, 1]", rung));
+        }
+        total_fraction += fraction;
+        tiers.push(ProfitTier { gain_pct: (multiple - 1.0) * 100.0, sell_pct: fraction * 100.0 });
+    }
+    if total_fraction > 1.0 + 1e-9 {
+        return Err(anyhow!("fractions sum to {:.2}, more than the whole position", total_fraction));
+    }
+    tiers.sort_by(|a, b| a.gain_pct.total_cmp(&b.gain_pct));
+    if tiers.windows(2).any(|pair| pair[0].gain_pct == pair[1].gain_pct) {
+        return Err(anyhow!("two rungs share a multiple"));
+    }
+    Ok(tiers)
+}
+
+/// Parse "gain_pct:sell_pct,..." into tiers, skipping malformed pairs
+pub fn parse_profit_tiers(value: &str) -> Vec<ProfitTier> {
+    let mut tiers: Vec<ProfitTier> = value
+        .split(',')
+        .filter_map(|pair| {
+            let (gain, sell) = pair.trim().split_once(':')?;
+            let gain_pct = gain.trim().parse::<f64>().ok()?;
+            let sell_pct = sell.trim().parse::<f64>().ok()?;
+            if gain_pct <= 0.0 || sell_pct <= 0.0 {
+                return None;
+            }
+            Some(ProfitTier { gain_pct, sell_pct: sell_pct.min(100.0) })
+        })
+        .collect();
+    tiers.sort_by(|a, b| a.gain_pct.total_cmp(&b.gain_pct));
+    tiers
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum ExitReason {
+    ProfitTier(usize),
+    TrailingStop,
+    StopLoss,
+    /// Requested outside the exit rules (strategy hooks, operator)
+    Manual,
+}
+
+impl fmt::Display for ExitReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ExitReason::ProfitTier(index) => write!(f, "Profit tier {}", index + 1),
+            ExitReason::TrailingStop => write!(f, "Trailing stop"),
+            ExitReason::StopLoss => write!(f, "Stop loss"),
+            ExitReason::Manual => write!(f, "Manual"),
+        }
+    }
+}
+
+/// What the supervisor should sell
+#[derive(Clone, Debug, PartialEq)]
+pub struct ExitDecision {
+    pub reason: ExitReason,
+    /// UI token amount to sell (never more than what is still held)
+    pub token_amount: f64,
+}
+
+impl ExitDecision {
+    pub fn is_full_exit(&self, remaining_tokens: f64) -> bool {
+        self.token_amount >= remaining_tokens
+    }
+}
+
+/// Minimal position state the evaluation needs - shared by the live supervisor and offline simulation
+#[derive(Clone, Debug)]
+pub struct ExitState {
+    pub entry_price: f64,
+    pub peak_price: f64,
+    pub initial_tokens: f64,
+    pub remaining_tokens: f64,
+    pub tiers_hit: Vec<usize>,
+    /// Seconds since the position opened
+    pub held_secs: u64,
+}
+
+impl ExitState {
+    pub fn from_position(position: &Position) -> Self {
+        Self {
+            entry_price: position.entry_price(),
+            peak_price: position.peak_price,
+            initial_tokens: position.lots.iter().map(|lot| lot.token_amount).sum(),
+            remaining_tokens: position.remaining_tokens(),
+            tiers_hit: position.tiers_hit.clone(),
+            held_secs: clock::now_secs().saturating_sub(position.opened_at),
+        }
+    }
+
+    /// Track the peak the same way the position book does for live positions
+    pub fn observe_price(&mut self, price: f64) {
+        if price > 0.0 {
+            self.peak_price = self.peak_price.max(price);
+        }
+    }
+
+    /// Book a filled decision: reduce holdings and remember fired tiers
+    pub fn apply_decision(&mut self, decision: &ExitDecision) {
+        self.remaining_tokens = (self.remaining_tokens - decision.token_amount).max(0.0);
+        if let ExitReason::ProfitTier(index) = decision.reason {
+            if !self.tiers_hit.contains(&index) {
+                self.tiers_hit.push(index);
+            }
+        }
+    }
+}
+
+/// Decide whether the position should (partially) exit at `price`
+/// Pure function: the caller owns peak tracking and tier bookkeeping.
+/// Stops are checked first since they close the whole position.
+pub fn evaluate_exit(state: &ExitState, price: f64, params: &RuntimeParams) -> Option<ExitDecision> {
+    if price <= 0.0 || state.entry_price <= 0.0 || state.remaining_tokens <= 0.0 {
+        return None;
+    }
+
+    let gain_pct = (price - state.entry_price) / state.entry_price * 100.0;
+
+    if let Some(stop_loss_pct) = params.stop_loss_pct {
+        if gain_pct <= -stop_loss_pct {
+            return Some(ExitDecision {
+                reason: ExitReason::StopLoss,
+                token_amount: state.remaining_tokens,
+            });
+        }
+    }
+
+    if let Some(trailing_stop_pct) = params.trailing_stop_pct {
+        // Only armed once the position has been in profit and held past the arming delay
+        if state.peak_price > state.entry_price && state.held_secs >= params.trailing_stop_arm_secs {
+            let drawdown_pct = (state.peak_price - price) / state.peak_price * 100.0;
+            if drawdown_pct >= trailing_stop_pct {
+                return Some(ExitDecision {
+                    reason: ExitReason::TrailingStop,
+                    token_amount: state.remaining_tokens,
+                });
+            }
+        }
+    }
+
+    for (index, tier) in params.profit_tiers.iter().enumerate() {
+        if state.tiers_hit.contains(&index) {
+            continue;
+        }
+        if gain_pct >= tier.gain_pct {
+            let token_amount = (state.initial_tokens * tier.sell_pct / 100.0).min(state.remaining_tokens);
+            if token_amount <= 0.0 {
+                return None;
+            }
+            return Some(ExitDecision {
+                reason: ExitReason::ProfitTier(index),
+                token_amount,
+            });
+        }
+        // Tiers are sorted, so later ones can't be reached either
+        break;
+    }
+
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn trailing(pct: f64, arm_secs: u64) -> RuntimeParams {
+        RuntimeParams { trailing_stop_pct: Some(pct), trailing_stop_arm_secs: arm_secs, ..RuntimeParams::default() }
+    }
+
+    fn state(held_secs: u64) -> ExitState {
+        ExitState { entry_price: 1.0, peak_price: 1.0, initial_tokens: 100.0, remaining_tokens: 100.0, tiers_hit: Vec::new(), held_secs }
+    }
+
+    /// Index and decision of the first exit over `prices`, tracking the peak as the supervisor does
+    fn first_exit(mut state: ExitState, prices: &[f64], params: &RuntimeParams) -> Option<(usize, ExitDecision)> {
+        prices.iter().enumerate().find_map(|(index, &price)| {
+            state.observe_price(price);
+            evaluate_exit(&state, price, params).map(|decision| (index, decision))
+        })
+    }
+
+    #[test]
+    fn trailing_stop_fires_on_the_drawdown_from_the_peak() {
+        let series = [1.1, 1.5, 1.45, 1.4, 1.2, 1.0];
+        let (index, decision) = first_exit(state(10), &series, &trailing(10.0, 5)).expect("stop fires");
+        // 1.4 is 6.7% below the 1.5 peak, 1.2 is 20% below it
+        assert_eq!(index, 4);
+        assert_eq!(decision, ExitDecision { reason: ExitReason::TrailingStop, token_amount: 100.0 });
+
+        assert!(first_exit(state(10), &[1.1, 1.5, 1.45, 1.4], &trailing(10.0, 5)).is_none());
+        // Exactly the configured drawdown fires
+        assert_eq!(first_exit(state(10), &[2.0, 1.5], &trailing(25.0, 5)).map(|(index, _)| index), Some(1));
+    }
+
+    #[test]
+    fn trailing_stop_waits_for_profit() {
+        // Falling from entry is the stop loss's job
+        assert!(first_exit(state(10), &[0.95, 0.8, 0.5], &trailing(10.0, 5)).is_none());
+    }
+
+    #[test]
+    fn trailing_stop_waits_out_the_arming_delay() {
+        let series = [1.5, 1.2];
+        assert!(first_exit(state(4), &series, &trailing(10.0, 5)).is_none());
+        assert!(first_exit(state(5), &series, &trailing(10.0, 5)).is_some());
+        assert!(first_exit(state(0), &series, &trailing(10.0, 0)).is_some());
+
+        // The stop loss is never delayed
+        let params = RuntimeParams { stop_loss_pct: Some(20.0), ..trailing(10.0, 5) };
+        assert_eq!(first_exit(state(0), &[0.7], &params).map(|(_, decision)| decision.reason), Some(ExitReason::StopLoss));
+    }
+
+    #[test]
+    fn negative_min_liquidity_is_rejected() {
+        let params = RuntimeParams { trailing_stop_min_liquidity: -1.0, ..trailing(10.0, 5) };
+        assert!(params.validate().is_err());
+        assert!(trailing(10.0, 5).validate().is_ok());
+    }
+
+    #[test]
+    fn ladder_rungs_become_tiers_of_the_original_position() {
+        let tiers = parse_profit_ladder("3.0:0.25, 2.0:0.5").unwrap();
+        assert_eq!(tiers, vec![
+            ProfitTier { gain_pct: 100.0, sell_pct: 50.0 },
+            ProfitTier { gain_pct: 200.0, sell_pct: 25.0 },
+        ]);
+        assert_eq!(parse_profit_ladder("2.0:0.5,4.0:0.5").unwrap().len(), 2);
+        assert!(parse_profit_ladder("").unwrap().is_empty());
+    }
+
+    #[test]
+    fn malformed_ladders_are_rejected() {
+        let error = |value: &str| parse_profit_ladder(value).unwrap_err().to_string();
+        assert_eq!(error("2.0:0.6,3.0:0.5"), "fractions sum to 1.10, more than the whole position");
+        assert_eq!(error("2.0:0.25,2.0:0.25"), "two rungs share a multiple");
+        assert_eq!(error("1.0:0.5"), "rung '1.0:0.5' needs a multiple above 1.0");
+        assert_eq!(error("0.5:0.5"), "rung '0.5:0.5' needs a multiple above 1.0");
+        assert_eq!(error("2.0:0"), "rung '2.0:0' needs a fraction in (0, 1]");
+        assert_eq!(error("2.0:1.5"), "rung '2.0:1.5' needs a fraction in (0, 1]");
+        assert_eq!(error("2.0:half"), "rung '2.0:half' has an invalid fraction");
+        assert_eq!(error("2.0"), "rung '2.0' is not multiple:fraction");
+    }
+}
@@ -0,0 +1,122 @@
+use std::str::FromStr;
+use std::sync::Arc;
+use anchor_client::solana_sdk::{pubkey::Pubkey, signature::Signer};
+use colored::Colorize;
+use dashmap::DashMap;
+use once_cell::sync::Lazy;
+use spl_associated_token_account::get_associated_token_address_with_program_id;
+
+use crate::common::{blacklist, config::AppState, logger::Logger};
+use crate::core::mint_inspector;
+use crate::engine::position::POSITION_BOOK;
+use crate::services::notifier::{Notifier, TelegramNotifier};
+
+pub const FROZEN_REASON: &str = "frozen";
+
+/// Mints whose token account was frozen, with the unix time it was detected
+static FROZEN_MINTS: Lazy<DashMap<String, u64>> = Lazy::new(DashMap::new);
+
+pub fn is_frozen(mint: &str) -> bool {
+    FROZEN_MINTS.contains_key(mint)
+}
+
+/// Give up on a mint whose token account was frozen
+/// The position is dropped as dead (in both the pipeline book and legacy holdings), pending sells are cleared so nothing retries, the mint and
+/// its creator are blacklisted and one alert is sent. Returns false if it was already handled.
+pub fn mark_frozen(mint: &str, creator: Option<&str>, detected_by: &str) -> bool {
+    let now = crate::common::clock::now_secs();
+    if FROZEN_MINTS.insert(mint.to_string(), now).is_some() {
+        return false;
+    }
+
+    let creator = creator
+        .map(|creator| creator.to_string())
+        .or_else(|| POSITION_BOOK.get(mint).and_then(|position| position.coin_creator));
+
+    crate::common::cache::DEAD_TOKEN_LIST.insert(mint.to_string(), now);
+    crate::common::cache::PROGRESS_ON_SELLING.remove(mint);
+    crate::engine::sell_reasons::clear(mint);
+    crate::engine::sniper::TOKEN_HOLDINGS.remove(mint);
+    POSITION_BOOK.remove(mint);
+
+    blacklist::block_mint(mint, FROZEN_REASON);
+    if let Some(creator) = &creator {
+        blacklist::block_creator(creator, FROZEN_REASON);
+    }
+
+    let message = format!(
+        "🧊 Token account frozen for {} (detected by {}) - position marked dead, mint{} blacklisted",
+        mint,
+        detected_by,
+        creator.as_ref().map(|creator| format!(" and creator {}", creator)).unwrap_or_default()
+    );
+    Logger::new("[FREEZE-GUARD] => ".red().to_string()).error(message.clone());
+    TelegramNotifier.notify(message);
+    true
+}
+
+/// Mints we currently hold according to the pipeline book and the legacy holdings map
+fn held_mints() -> Vec<String> {
+    let mut mints: Vec<String> = POSITION_BOOK.snapshot().into_iter().map(|position| position.mint).collect();
+    for entry in crate::engine::sniper::TOKEN_HOLDINGS.iter() {
+        if !mints.contains(entry.key()) {
+            mints.push(entry.key().clone());
+        }
+    }
+    mints
+}
+
+/// Read each held mint's ATA and mark frozen ones before a sell has to fail on them
+async fn reconcile_frozen_accounts(app_state: &AppState, logger: &Logger) {
+    let wallet = app_state.wallet.pubkey();
+    for mint in held_mints() {
+        if is_frozen(&mint) {
+            continue;
+        }
+        let Ok(mint_pubkey) = Pubkey::from_str(&mint) else {
+            continue;
+        };
+        let info = match mint_inspector::inspect_mint(&app_state.read_rpc.client(), &mint_pubkey).await {
+            Ok(info) => info,
+            Err(e) => {
+                logger.error(format!("Failed to inspect mint {}: {}", mint, e));
+                continue;
+            }
+        };
+        // Only mints with a freeze authority can have frozen accounts
+        if !info.has_freeze_authority() {
+            continue;
+        }
+
+        let ata = get_associated_token_address_with_program_id(&wallet, &mint_pubkey, &info.token_program);
+        let account = match app_state.read_rpc.client().get_account(&ata).await {
+            Ok(account) => account,
+            Err(_) => continue, // Closed or not created yet
+        };
+        match mint_inspector::is_token_account_frozen(&account) {
+            Ok(true) => {
+                mark_frozen(&mint, None, "reconciliation");
+            }
+            Ok(false) => {}
+            Err(e) => logger.error(format!("Failed to read token account {} for {}: {}", ata, mint, e)),
+        }
+    }
+}
+
+/// Periodically check held token accounts for the frozen flag
+/// Runs every FROZEN_CHECK_INTERVAL_SECS (default: 60).
+pub async fn start_frozen_account_reconciler(app_state: Arc<AppState>) {
+    let interval_secs = std::env::var("FROZEN_CHECK_INTERVAL_SECS")
+        .ok()
+        .and_then(|v| v.parse::<u64>().ok())
+        .unwrap_or(60);
+
+    tokio::spawn(async move {
+        let logger = Logger::new("[FREEZE-GUARD] => ".red().to_string());
+        let mut interval = tokio::time::interval(tokio::time::Duration::from_secs(interval_secs.max(1)));
+        loop {
+            interval.tick().await;
+            reconcile_frozen_accounts(&app_state, &logger).await;
+        }
+    });
+}
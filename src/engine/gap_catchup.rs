@@ -0,0 +1,206 @@
+use std::str::FromStr;
+use anchor_client::solana_client::rpc_client::GetConfirmedSignaturesForAddress2Config;
+use anchor_client::solana_sdk::{commitment_config::CommitmentConfig, pubkey::Pubkey, signature::Signature};
+use async_trait::async_trait;
+use borsh::BorshDeserialize;
+
+use crate::common::clock;
+use crate::dex::pump_fun::{get_pda, BondingCurveAccount, Pump, PUMP_FUN_PROGRAM};
+use crate::dex::pump_swap::get_pool_info_for_price;
+use crate::engine::event_stream::StreamGap;
+use crate::engine::position::Position;
+use crate::engine::replay;
+use crate::engine::trade_event::TradeEvent;
+use crate::engine::transaction_parser::{self, DexType, TradeInfoFromToken};
+use crate::services::rpc_client::BatchRpcClient;
+use crate::services::rpc_pool::RpcHandle;
+
+/// Largest page getSignaturesForAddress returns
+const SIGNATURE_PAGE: usize = 1000;
+
+/// Current on-chain state of a held mint's bonding curve or pool
+#[derive(Clone, Debug, PartialEq)]
+pub struct PoolState {
+    /// Empty for bonding curves
+    pub pool_id: String,
+    pub virtual_sol_reserves: u64,
+    pub virtual_token_reserves: u64,
+    pub price: f64,
+    /// SOL in the curve or pool
+    pub liquidity: f64,
+}
+
+/// A successful transaction inside a gap
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct GapSignature {
+    pub signature: String,
+    pub slot: u64,
+}
+
+/// Chain reads behind the gap catch-up
+#[async_trait]
+pub trait GapReader: Send + Sync {
+    async fn pool_state(&self, mint: &str, dex_type: DexType) -> Result<PoolState, String>;
+
+    /// Successful transactions touching `address` within the gap's slots, oldest first, at most `limit`
+    async fn signatures_in_gap(&self, address: &str, gap: &StreamGap, limit: usize) -> Result<Vec<GapSignature>, String>;
+
+    /// Trades in one transaction, decoded as the stream would have; see `replay::replay_trades`
+    async fn replay(&self, signature: &str) -> Result<Vec<TradeInfoFromToken>, String>;
+}
+
+/// Reads through the read pool; replayed transactions go through the RPC disk cache
+pub struct RpcGapReader {
+    read_rpc: RpcHandle,
+}
+
+impl RpcGapReader {
+    pub fn new(read_rpc: RpcHandle) -> Self {
+        Self { read_rpc }
+    }
+}
+
+#[async_trait]
+impl GapReader for RpcGapReader {
+    async fn pool_state(&self, mint: &str, dex_type: DexType) -> Result<PoolState, String> {
+        let mint_pubkey = Pubkey::from_str(mint).map_err(|e| format!("invalid mint {}: {}", mint, e))?;
+        let client = self.read_rpc.client();
+        match dex_type {
+            DexType::PumpSwap => {
+                let (pool_id, base_reserve, quote_reserve) = get_pool_info_for_price(client, mint_pubkey)
+                    .await
+                    .map_err(|e| e.to_string())?;
+                let price = if base_reserve > 0 { quote_reserve as f64 / base_reserve as f64 / 1_000.0 } else { 0.0 };
+                Ok(PoolState {
+                    pool_id: pool_id.to_string(),
+                    virtual_sol_reserves: quote_reserve,
+                    virtual_token_reserves: base_reserve,
+                    price,
+                    liquidity: quote_reserve as f64 / 1_000_000_000.0,
+                })
+            }
+            // Pools are keyed by AMM id rather than mint; the next live swap re-prices the position
+            DexType::RaydiumAmm => Err(format!("Raydium pool state for {} is not read after a gap", mint)),
+            _ => {
+                let program = Pubkey::from_str(PUMP_FUN_PROGRAM).map_err(|e| e.to_string())?;
+                let bonding_curve = get_pda(&mint_pubkey, &program).map_err(|e| e.to_string())?;
+                let data = client
+                    .get_account_data(&bonding_curve)
+                    .await
+                    .map_err(|e| format!("failed to read bonding curve {}: {}", bonding_curve, e))?;
+                // Newer curves append fields; only the leading ones are read
+                let curve = BondingCurveAccount::deserialize(&mut data.as_slice())
+                    .map_err(|e| format!("failed to decode bonding curve {}: {}", bonding_curve, e))?;
+                Ok(PoolState {
+                    pool_id: String::new(),
+                    virtual_sol_reserves: curve.virtual_sol_reserves,
+                    virtual_token_reserves: curve.virtual_token_reserves,
+                    price: Pump::calculate_price_from_virtual_reserves(curve.virtual_sol_reserves, curve.virtual_token_reserves),
+                    liquidity: curve.real_sol_reserves as f64 / 1_000_000_000.0,
+                })
+            }
+        }
+    }
+
+    async fn signatures_in_gap(&self, address: &str, gap: &StreamGap, limit: usize) -> Result<Vec<GapSignature>, String> {
+        let address = Pubkey::from_str(address).map_err(|e| format!("invalid address {}: {}", address, e))?;
+        let client = self.read_rpc.client();
+        let mut found = Vec::new();
+        let mut before = None;
+        // Newest first: page back until the gap's first slot or the limit
+        loop {
+            let page = client
+                .get_signatures_for_address_with_config(&address, GetConfirmedSignaturesForAddress2Config {
+                    before,
+                    until: None,
+                    limit: Some(SIGNATURE_PAGE.min(limit.max(1))),
+                    commitment: Some(CommitmentConfig::confirmed()),
+                })
+                .await
+                .map_err(|e| format!("failed to list signatures of {}: {}", address, e))?;
+            let Some(last) = page.last() else {
+                break;
+            };
+            let reached_start = last.slot < gap.from_slot;
+            before = Signature::from_str(&last.signature).ok();
+            for status in page {
+                if status.err.is_none() && status.slot >= gap.from_slot && status.slot <= gap.to_slot {
+                    found.push(GapSignature { signature: status.signature, slot: status.slot });
+                }
+            }
+            if reached_start || found.len() >= limit || before.is_none() {
+                break;
+            }
+        }
+        found.truncate(limit);
+        found.reverse();
+        Ok(found)
+    }
+
+    async fn replay(&self, signature: &str) -> Result<Vec<TradeInfoFromToken>, String> {
+        let signature = Signature::from_str(signature).map_err(|e| format!("invalid signature {}: {}", signature, e))?;
+        let confirmed = BatchRpcClient::new(self.read_rpc.client())
+            .get_transaction_cached(&signature)
+            .await
+            .map_err(|e| e.to_string())?;
+        Ok(replay::replay_trades(&confirmed))
+    }
+}
+
+/// GAP_REPLAY_BUYS (default: false) replays the monitored programs' transactions in a gap through the buy path,
+/// at most GAP_REPLAY_MAX_SIGNATURES (default: 200) per program and per wallet check. Replayed events older than
+/// GAP_REPLAY_MAX_AGE_SECS (default: 30) never open positions.
+#[derive(Clone, Copy, Debug)]
+pub struct GapCatchUpConfig {
+    pub replay_buys: bool,
+    pub max_signatures: usize,
+    pub max_replay_age_secs: u64,
+}
+
+impl GapCatchUpConfig {
+    pub fn from_env() -> Self {
+        let replay_buys = std::env::var("GAP_REPLAY_BUYS")
+            .ok()
+            .and_then(|v| v.parse::<bool>().ok())
+            .unwrap_or(false);
+        let max_signatures = std::env::var("GAP_REPLAY_MAX_SIGNATURES")
+            .ok()
+            .and_then(|v| v.parse::<usize>().ok())
+            .unwrap_or(200);
+        let max_replay_age_secs = std::env::var("GAP_REPLAY_MAX_AGE_SECS")
+            .ok()
+            .and_then(|v| v.parse::<u64>().ok())
+            .unwrap_or(30);
+        Self { replay_buys, max_signatures, max_replay_age_secs }
+    }
+}
+
+/// Price tick for a held mint at its current on-chain state, fed through supervision so stops see what the gap hid
+pub fn catch_up_event(position: &Position, state: &PoolState, gap: &StreamGap) -> Option<TradeEvent> {
+    TradeEvent::from_trade_info(TradeInfoFromToken {
+        dex_type: position.dex_type,
+        slot: gap.to_slot,
+        signature: String::new(),
+        pool_id: state.pool_id.clone(),
+        mint: position.mint.clone(),
+        timestamp: clock::now_secs(),
+        is_buy: false,
+        post_current_price: state.price,
+        pre_current_price: position.last_price,
+        is_reverse_when_pump_swap: false,
+        coin_creator: position.coin_creator.clone(),
+        sol_change: 0.0,
+        target_transaction_token_change: 0.0,
+        token_decimals: transaction_parser::known_token_decimals(&position.mint).unwrap_or(transaction_parser::PUMP_TOKEN_DECIMALS),
+        liquidity: state.liquidity,
+        virtual_sol_reserves: state.virtual_sol_reserves,
+        virtual_token_reserves: state.virtual_token_reserves,
+        buy_sell_in_same_tx: false,
+        trader: String::new(),
+        is_own_trade: false,
+        timestamp_suspect: false,
+        gap_replayed: false,
+        curve_complete: false,
+        suspected_mev: false,
+    })
+}
@@ -0,0 +1,198 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::time::{Duration, Instant};
+use futures::StreamExt;
+use yellowstone_grpc_client::{ClientTlsConfig, GeyserGrpcBuilderError, GeyserGrpcClient, GeyserGrpcClientError};
+use yellowstone_grpc_proto::geyser::{CommitmentLevel, SubscribeRequest, SubscribeRequestFilterSlots};
+use yellowstone_grpc_proto::tonic::{Code, Status};
+
+/// Upper bound for connecting, subscribing and receiving the first slot update
+pub const PROBE_DEADLINE: Duration = Duration::from_secs(10);
+
+/// Why the endpoint/token pair is unusable
+#[derive(Clone, Debug, PartialEq)]
+pub enum ProbeFailure {
+    /// The server rejected YELLOWSTONE_GRPC_TOKEN
+    AuthRejected(String),
+    /// DNS, TCP or HTTP/2 failure reaching YELLOWSTONE_GRPC_HTTP
+    Unreachable(String),
+    /// Certificate or handshake failure
+    Tls(String),
+    /// Connected, but nothing arrived before the deadline
+    Timeout,
+    /// The server ended the stream before the first message
+    StreamClosed,
+}
+
+impl fmt::Display for ProbeFailure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ProbeFailure::AuthRejected(detail) => write!(f, "auth rejected - check YELLOWSTONE_GRPC_TOKEN ({})", detail),
+            ProbeFailure::Unreachable(detail) => write!(f, "endpoint unreachable - check YELLOWSTONE_GRPC_HTTP ({})", detail),
+            ProbeFailure::Tls(detail) => write!(f, "TLS failure ({})", detail),
+            ProbeFailure::Timeout => write!(f, "no message within {}s", PROBE_DEADLINE.as_secs()),
+            ProbeFailure::StreamClosed => write!(f, "stream closed before the first message"),
+        }
+    }
+}
+
+/// A successful probe
+#[derive(Clone, Debug)]
+pub struct ProbeReport {
+    /// From sending the subscribe request to the first update; a baseline for stream latency
+    pub time_to_first_message: Duration,
+    /// GetVersion response, None if the provider doesn't implement it
+    pub server_version: Option<String>,
+}
+
+/// Whether startup probes the gRPC endpoint (GRPC_PROBE, default: true)
+/// Providers that bill per connection can turn it off.
+pub fn probe_enabled() -> bool {
+    std::env::var("GRPC_PROBE")
+        .ok()
+        .and_then(|v| v.parse::<bool>().ok())
+        .unwrap_or(true)
+}
+
+pub fn classify_status(status: &Status) -> ProbeFailure {
+    match status.code() {
+        Code::Unauthenticated | Code::PermissionDenied => ProbeFailure::AuthRejected(status.message().to_string()),
+        Code::DeadlineExceeded => ProbeFailure::Timeout,
+        _ => {
+            let detail = format!("{:?}: {}", status.code(), status.message());
+            if is_tls_error(&detail) {
+                ProbeFailure::Tls(detail)
+            } else {
+                ProbeFailure::Unreachable(detail)
+            }
+        }
+    }
+}
+
+/// tonic's transport error only says "transport error"; the cause is in the Debug chain
+fn classify_transport(detail: String) -> ProbeFailure {
+    if is_tls_error(&detail) {
+        ProbeFailure::Tls(detail)
+    } else {
+        ProbeFailure::Unreachable(detail)
+    }
+}
+
+fn is_tls_error(detail: &str) -> bool {
+    let detail = detail.to_lowercase();
+    ["tls", "certificate", "handshake", "ssl"].iter().any(|needle| detail.contains(needle))
+}
+
+fn classify_builder(error: GeyserGrpcBuilderError) -> ProbeFailure {
+    match error {
+        GeyserGrpcBuilderError::MetadataValueError(e) => ProbeFailure::AuthRejected(format!("token is not a valid header value: {}", e)),
+        GeyserGrpcBuilderError::TonicError(e) => classify_transport(format!("{:?}", e)),
+    }
+}
+
+fn classify_client(error: GeyserGrpcClientError) -> ProbeFailure {
+    match error {
+        GeyserGrpcClientError::TonicStatus(status) => classify_status(&status),
+        GeyserGrpcClientError::SubscribeSendError(e) => ProbeFailure::Unreachable(e.to_string()),
+    }
+}
+
+/// Slot updates only: the cheapest subscription every provider serves
+fn probe_request() -> SubscribeRequest {
+    let mut slots = HashMap::new();
+    slots.insert("probe".to_string(), SubscribeRequestFilterSlots::default());
+    SubscribeRequest {
+        slots,
+        commitment: Some(CommitmentLevel::Processed as i32),
+        ..Default::default()
+    }
+}
+
+/// Open a short-lived connection and wait for the first slot update or an error, within PROBE_DEADLINE
+pub async fn probe(endpoint: &str, token: &str) -> Result<ProbeReport, ProbeFailure> {
+    let attempt = async {
+        let mut client = GeyserGrpcClient::build_from_shared(endpoint.to_string())
+            .map_err(classify_builder)?
+            .x_token(Some(token.to_string()))
+            .map_err(classify_builder)?
+            .tls_config(ClientTlsConfig::new().with_native_roots())
+            .map_err(classify_builder)?
+            .connect()
+            .await
+            .map_err(classify_builder)?;
+
+        // First authenticated call; Unimplemented just means no version to record
+        let server_version = match client.get_version().await {
+            Ok(response) => Some(response.version),
+            Err(GeyserGrpcClientError::TonicStatus(status)) if status.code() == Code::Unimplemented => None,
+            Err(e) => match classify_client(e) {
+                failure @ ProbeFailure::AuthRejected(_) => return Err(failure),
+                _ => None,
+            },
+        };
+
+        let subscribed_at = Instant::now();
+        let (_sink, mut stream) = client.subscribe_with_request(Some(probe_request()))
+            .await
+            .map_err(classify_client)?;
+        match stream.next().await {
+            Some(Ok(_)) => Ok(ProbeReport { time_to_first_message: subscribed_at.elapsed(), server_version }),
+            Some(Err(status)) => Err(classify_status(&status)),
+            None => Err(ProbeFailure::StreamClosed),
+        }
+    };
+
+    tokio::time::timeout(PROBE_DEADLINE, attempt)
+        .await
+        .unwrap_or(Err(ProbeFailure::Timeout))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn statuses_map_to_failures() {
+        assert_eq!(
+            classify_status(&Status::unauthenticated("bad token")),
+            ProbeFailure::AuthRejected("bad token".to_string())
+        );
+        assert!(matches!(classify_status(&Status::permission_denied("plan")), ProbeFailure::AuthRejected(_)));
+        assert_eq!(classify_status(&Status::deadline_exceeded("slow")), ProbeFailure::Timeout);
+        assert!(matches!(classify_status(&Status::unavailable("connection refused")), ProbeFailure::Unreachable(_)));
+        assert!(matches!(
+            classify_status(&Status::unknown("invalid peer certificate: UnknownIssuer")),
+            ProbeFailure::Tls(_)
+        ));
+    }
+
+    #[test]
+    fn transport_errors_split_on_tls_markers() {
+        assert!(matches!(classify_transport("tonic::transport::Error(Transport, hyper::Error(Connect, \"SSL handshake failed\"))".to_string()), ProbeFailure::Tls(_)));
+        assert!(matches!(classify_transport("ConnectError(\"dns error\", Os { code: -2 })".to_string()), ProbeFailure::Unreachable(_)));
+    }
+
+    #[test]
+    fn failures_name_the_setting_to_check() {
+        assert!(ProbeFailure::AuthRejected("x".to_string()).to_string().contains("YELLOWSTONE_GRPC_TOKEN"));
+        assert!(ProbeFailure::Unreachable("x".to_string()).to_string().contains("YELLOWSTONE_GRPC_HTTP"));
+        assert_eq!(ProbeFailure::Timeout.to_string(), format!("no message within {}s", PROBE_DEADLINE.as_secs()));
+    }
+
+    #[test]
+    fn probe_subscribes_to_slots_only() {
+        let request = probe_request();
+        assert_eq!(request.slots.len(), 1);
+        assert!(request.transactions.is_empty() && request.accounts.is_empty());
+        assert_eq!(request.commitment, Some(CommitmentLevel::Processed as i32));
+    }
+
+    #[tokio::test]
+    async fn closed_port_is_unreachable() {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        drop(listener);
+        let failure = probe(&format!("https://127.0.0.1:{}", port), "token").await.unwrap_err();
+        assert!(matches!(failure, ProbeFailure::Unreachable(_)), "{:?}", failure);
+    }
+}
@@ -0,0 +1,269 @@
+use std::collections::HashSet;
+use std::str::FromStr;
+use std::sync::Arc;
+use anchor_client::solana_client::rpc_config::RpcSimulateTransactionConfig;
+use anchor_client::solana_sdk::{
+    commitment_config::CommitmentConfig,
+    hash::Hash,
+    pubkey::Pubkey,
+    signer::Signer,
+    transaction::{Transaction, TransactionError, VersionedTransaction},
+};
+use async_trait::async_trait;
+use colored::Colorize;
+use dashmap::DashMap;
+use once_cell::sync::Lazy;
+use spl_token_2022::extension::ExtensionType;
+
+use crate::common::config::{AppState, SwapConfig};
+use crate::common::logger::Logger;
+use crate::core::{mint_inspector::{self, MintInfo}, preflight};
+use crate::dex::{math, pump_fun::{self, Pump}};
+use crate::engine::buy_filters::BuyFilter;
+use crate::engine::swap::{SwapDirection, SwapInType};
+use crate::engine::trade_event::TradeEvent;
+use crate::engine::transaction_parser::DexType;
+
+/// Verdict per mint: a mint's authorities and extensions don't change between its trades, and a round trip
+/// that passed once says as much the next time
+static VERDICTS: Lazy<DashMap<Pubkey, Result<(), String>>> = Lazy::new(DashMap::new);
+
+/// What makes a mint unsellable for us
+/// HONEYPOT_CHECK (default: false), HONEYPOT_HOOK_ALLOWLIST (default: none), HONEYPOT_MAX_TRANSFER_FEE_BPS
+/// (default: 500), HONEYPOT_SIMULATION (default: false), HONEYPOT_SIMULATION_SOL (default: 0.001)
+#[derive(Clone, Debug)]
+pub struct HoneypotConfig {
+    pub enabled: bool,
+    /// Transfer hook programs known not to block sells
+    pub hook_allowlist: HashSet<Pubkey>,
+    pub max_transfer_fee_bps: u16,
+    /// Also simulate buying `simulation_sol` and selling it back in one transaction (PumpFun mints)
+    pub simulate_round_trip: bool,
+    pub simulation_sol: f64,
+}
+
+impl HoneypotConfig {
+    pub fn from_env() -> Self {
+        let flag = |key: &str| std::env::var(key).ok().and_then(|v| v.parse::<bool>().ok()).unwrap_or(false);
+        Self {
+            enabled: flag("HONEYPOT_CHECK"),
+            hook_allowlist: std::env::var("HONEYPOT_HOOK_ALLOWLIST")
+                .unwrap_or_default()
+                .split(',')
+                .filter_map(|program| Pubkey::from_str(program.trim()).ok())
+                .collect(),
+            max_transfer_fee_bps: std::env::var("HONEYPOT_MAX_TRANSFER_FEE_BPS")
+                .ok()
+                .and_then(|v| v.parse::<u16>().ok())
+                .unwrap_or(500),
+            simulate_round_trip: flag("HONEYPOT_SIMULATION"),
+            simulation_sol: std::env::var("HONEYPOT_SIMULATION_SOL")
+                .ok()
+                .and_then(|v| v.parse::<f64>().ok())
+                .filter(|v| *v > 0.0)
+                .unwrap_or(0.001),
+        }
+    }
+}
+
+/// Reasons read off the mint account alone; Err with the first one found
+pub fn check_mint(info: &MintInfo, config: &HoneypotConfig) -> Result<(), String> {
+    if let Some(authority) = info.freeze_authority {
+        return Err(format!("freeze authority {} can freeze our account", authority));
+    }
+    if info.is_non_transferable() {
+        return Err("non-transferable mint".to_string());
+    }
+    if info.extensions.contains(&ExtensionType::TransferHook) {
+        match info.transfer_hook_program {
+            Some(program) if !config.hook_allowlist.contains(&program) => {
+                return Err(format!("transfer hook program {} is not on HONEYPOT_HOOK_ALLOWLIST", program));
+            }
+            _ => {}
+        }
+    }
+    if info.transfer_fee_bps > config.max_transfer_fee_bps {
+        return Err(format!("transfer fee {} bps above HONEYPOT_MAX_TRANSFER_FEE_BPS={}", info.transfer_fee_bps, config.max_transfer_fee_bps));
+    }
+    Ok(())
+}
+
+/// Reject mints we could buy but not sell back: freeze authority, non-transferable, unknown transfer hooks,
+/// punitive transfer fees and, with HONEYPOT_SIMULATION, PumpFun round trips that fail on the sell
+pub struct HoneypotFilter {
+    config: HoneypotConfig,
+    app_state: Arc<AppState>,
+    swap_config: SwapConfig,
+    logger: Logger,
+}
+
+impl HoneypotFilter {
+    pub fn new(config: HoneypotConfig, app_state: Arc<AppState>, swap_config: SwapConfig) -> Self {
+        Self { config, app_state, swap_config, logger: Logger::new("[HONEYPOT] => ".red().to_string()) }
+    }
+
+    /// Buy `simulation_sol` and sell every token it returns in one simulated transaction
+    /// Only a failure in the sell is a verdict; a buy that can't run (balance, slippage) says nothing about the mint.
+    async fn simulate_round_trip(&self, event: &TradeEvent, info: &MintInfo) -> Result<(), String> {
+        let Some(creator) = event.coin_creator().and_then(|creator| Pubkey::from_str(creator).ok()) else {
+            return Ok(());
+        };
+        let trade_info = event.to_trade_info();
+        let mut buy_config = self.swap_config.clone();
+        buy_config.swap_direction = SwapDirection::Buy;
+        buy_config.in_type = SwapInType::Qty;
+        buy_config.amount_in = self.config.simulation_sol;
+        let pump = Pump::new(
+            self.app_state.rpc_nonblocking_client.clone(),
+            self.app_state.rpc_client.clone(),
+            self.app_state.wallet.clone(),
+        );
+        let (keypair, mut instructions, _price) = match pump.build_swap_from_parsed_data(&trade_info, buy_config).await {
+            Ok(built) => built,
+            Err(e) => {
+                self.logger.error(format!("Round trip of {} could not be built, not judging it: {}", info.mint, e));
+                return Ok(());
+            }
+        };
+
+        let bought = Pump::calculate_buy_token_amount(
+            math::ui_to_raw(self.config.simulation_sol, spl_token::native_mint::DECIMALS),
+            trade_info.virtual_sol_reserves,
+            trade_info.virtual_token_reserves,
+        );
+        // A transfer fee is withheld from what reaches our account
+        let received = bought - bought * u64::from(info.transfer_fee_bps) / 10_000;
+        let Ok(sell) = pump_fun::sell_instruction(&keypair.pubkey(), &info.mint, &info.token_program, &creator, received, 1) else {
+            return Ok(());
+        };
+        let sell_index = instructions.len();
+        instructions.push(sell);
+
+        let transaction = VersionedTransaction::from(Transaction::new_signed_with_payer(&instructions, Some(&keypair.pubkey()), &[keypair.as_ref()], Hash::default()));
+        let config = RpcSimulateTransactionConfig {
+            sig_verify: false,
+            replace_recent_blockhash: true,
+            commitment: Some(CommitmentConfig::processed()),
+            ..RpcSimulateTransactionConfig::default()
+        };
+        let result = match self.app_state.read_rpc.client().simulate_transaction_with_config(&transaction, config).await {
+            Ok(response) => response.value,
+            Err(e) => {
+                self.logger.error(format!("Round trip simulation of {} failed to run, not judging it: {}", info.mint, e));
+                return Ok(());
+            }
+        };
+        match result.err {
+            Some(err @ TransactionError::InstructionError(index, _)) if usize::from(index) == sell_index => {
+                let failure = preflight::decode_failure(&transaction, &err, result.logs.as_deref().unwrap_or_default());
+                Err(format!("simulated sell after a buy fails: {}", failure))
+            }
+            Some(err) => {
+                self.logger.error(format!("Round trip simulation of {} failed before the sell: {:?}", info.mint, err));
+                Ok(())
+            }
+            None => Ok(()),
+        }
+    }
+}
+
+#[async_trait]
+impl BuyFilter for HoneypotFilter {
+    fn name(&self) -> &str {
+        "honeypot"
+    }
+
+    async fn check(&self, event: &TradeEvent) -> Result<(), String> {
+        let mint = event.core.mint;
+        if let Some(verdict) = VERDICTS.get(&mint) {
+            return verdict.clone();
+        }
+        // Fail closed, but uncached: the next trade gets another read
+        let info = mint_inspector::inspect_mint(&self.app_state.read_rpc.client(), &mint).await
+            .map_err(|e| format!("mint inspection failed: {}", e))?;
+        let mut verdict = check_mint(&info, &self.config);
+        if verdict.is_ok() && self.config.simulate_round_trip && event.core.dex_type == DexType::PumpFun {
+            verdict = self.simulate_round_trip(event, &info).await;
+        }
+        if let Err(reason) = &verdict {
+            self.logger.critical(format!("Skipping {}: {}", mint, reason).yellow().to_string());
+        }
+        VERDICTS.insert(mint, verdict.clone());
+        verdict
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use spl_token_2022::extension::{transfer_fee::TransferFeeConfig, transfer_hook::TransferHook, BaseStateWithExtensionsMut};
+    use crate::engine::test_fixtures::{mint_account, token_2022_mint};
+
+    fn config() -> HoneypotConfig {
+        HoneypotConfig {
+            enabled: true,
+            hook_allowlist: HashSet::new(),
+            max_transfer_fee_bps: 500,
+            simulate_round_trip: false,
+            simulation_sol: 0.001,
+        }
+    }
+
+    fn parsed(account: &anchor_client::solana_sdk::account::Account) -> MintInfo {
+        mint_inspector::parse_mint_account(&Pubkey::new_unique(), account).unwrap()
+    }
+
+    fn fee_mint(older_bps: u16, newer_bps: u16) -> MintInfo {
+        parsed(&token_2022_mint(&[ExtensionType::TransferFeeConfig], |state| {
+            let fees = state.init_extension::<TransferFeeConfig>(true).unwrap();
+            fees.older_transfer_fee.transfer_fee_basis_points = older_bps.into();
+            fees.newer_transfer_fee.transfer_fee_basis_points = newer_bps.into();
+        }))
+    }
+
+    fn hooked_mint(program: Pubkey) -> MintInfo {
+        parsed(&token_2022_mint(&[ExtensionType::TransferHook], |state| {
+            let hook = state.init_extension::<TransferHook>(true).unwrap();
+            hook.program_id = Some(program).try_into().unwrap();
+        }))
+    }
+
+    #[test]
+    fn plain_spl_mints_pass_unless_freezable() {
+        assert_eq!(check_mint(&parsed(&mint_account(spl_token::ID, None)), &config()), Ok(()));
+
+        let authority = Pubkey::new_unique();
+        let verdict = check_mint(&parsed(&mint_account(spl_token::ID, Some(authority))), &config());
+        assert_eq!(verdict, Err(format!("freeze authority {} can freeze our account", authority)));
+    }
+
+    #[test]
+    fn transfer_fees_are_judged_at_the_higher_scheduled_rate() {
+        let info = fee_mint(100, 500);
+        assert_eq!(info.token_program, spl_token_2022::ID);
+        assert_eq!(info.transfer_fee_bps, 500);
+        assert_eq!(check_mint(&info, &config()), Ok(()));
+
+        // A raise scheduled for a later epoch counts already
+        let raised = fee_mint(100, 2_000);
+        assert_eq!(
+            check_mint(&raised, &config()),
+            Err("transfer fee 2000 bps above HONEYPOT_MAX_TRANSFER_FEE_BPS=500".to_string())
+        );
+    }
+
+    #[test]
+    fn transfer_hooks_need_the_allowlist() {
+        let program = Pubkey::new_unique();
+        let info = hooked_mint(program);
+        assert_eq!(info.transfer_hook_program, Some(program));
+        assert_eq!(
+            check_mint(&info, &config()),
+            Err(format!("transfer hook program {} is not on HONEYPOT_HOOK_ALLOWLIST", program))
+        );
+
+        let allowed = HoneypotConfig { hook_allowlist: HashSet::from([program]), ..config() };
+        assert_eq!(check_mint(&info, &allowed), Ok(()));
+        assert!(check_mint(&hooked_mint(Pubkey::new_unique()), &allowed).is_err());
+    }
+}
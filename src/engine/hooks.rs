@@ -0,0 +1,401 @@
+use std::panic::AssertUnwindSafe;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use async_trait::async_trait;
+use colored::Colorize;
+use dashmap::DashSet;
+use futures::FutureExt;
+
+use crate::common::logger::Logger;
+use crate::engine::exit_strategy::ExitDecision;
+use crate::engine::position::Position;
+use crate::engine::position_updates::PositionUpdate;
+use crate::engine::trade_event::TradeEvent;
+use crate::services::line_writer::LineWriter;
+use crate::services::notifier::Notifier;
+
+/// Outcome of the buy filters for one event
+#[derive(Clone, Debug)]
+pub enum BuyDecision {
+    Approved,
+    Rejected { filter: String, reason: String },
+}
+
+/// The few things a hook may do besides observing
+pub struct HookActions {
+    notifier: Arc<dyn Notifier>,
+    sell_requests: DashSet<String>,
+}
+
+impl HookActions {
+    pub fn new(notifier: Arc<dyn Notifier>) -> Self {
+        Self {
+            notifier,
+            sell_requests: DashSet::new(),
+        }
+    }
+
+    pub fn notify(&self, text: String) {
+        self.notifier.notify(text);
+    }
+
+    /// Ask the supervisor to fully exit `mint` with a Manual reason on its next event
+    pub fn request_sell(&self, mint: &str) {
+        self.sell_requests.insert(mint.to_string());
+    }
+
+    /// Consume a pending sell request for `mint`
+    pub fn take_sell_request(&self, mint: &str) -> bool {
+        self.sell_requests.remove(mint).is_some()
+    }
+}
+
+/// Strategy plugin observing the engine at fixed points
+/// Every method is optional; hooks see read-only views and act only through `HookActions`.
+#[async_trait]
+pub trait StrategyHook: Send + Sync {
+    fn name(&self) -> &str;
+
+    /// Every event, before routing
+    async fn on_trade_event(&self, _event: &TradeEvent, _actions: &HookActions) {}
+
+    /// After the buy filters ran for a mint we don't hold
+    async fn on_buy_decision(&self, _event: &TradeEvent, _decision: &BuyDecision, _actions: &HookActions) {}
+
+    async fn on_position_opened(&self, _position: &Position, _actions: &HookActions) {}
+
+    /// Every event for a held mint, after the peak/last price were updated
+    async fn on_tick_for_held(&self, _event: &TradeEvent, _position: &Position, _actions: &HookActions) {}
+
+    /// Before the sell for `decision` is sent
+    async fn on_sell_decision(&self, _event: &TradeEvent, _position: &Position, _decision: &ExitDecision, _actions: &HookActions) {}
+
+    async fn on_position_closed(&self, _position: &Position, _actions: &HookActions) {}
+
+    /// Every update published to `position_updates`, after debouncing
+    async fn on_position_update(&self, _update: &PositionUpdate, _actions: &HookActions) {}
+}
+
+/// Calls registered hooks in registration order, isolating panics
+pub struct HookRegistry {
+    hooks: Vec<Arc<dyn StrategyHook>>,
+    actions: HookActions,
+    logger: Logger,
+}
+
+impl HookRegistry {
+    pub fn new(notifier: Arc<dyn Notifier>) -> Self {
+        Self {
+            hooks: Vec::new(),
+            actions: HookActions::new(notifier),
+            logger: Logger::new("[STRATEGY-HOOKS] => ".magenta().to_string()),
+        }
+    }
+
+    pub fn register(&mut self, hook: Arc<dyn StrategyHook>) {
+        self.hooks.push(hook);
+    }
+
+    pub fn len(&self) -> usize {
+        self.hooks.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.hooks.is_empty()
+    }
+
+    pub fn actions(&self) -> &HookActions {
+        &self.actions
+    }
+
+    fn report_panic(&self, hook: &dyn StrategyHook, point: &str) {
+        self.logger.error(format!("Hook '{}' panicked in {} - ignored", hook.name(), point));
+    }
+
+    pub async fn trade_event(&self, event: &TradeEvent) {
+        for hook in &self.hooks {
+            if AssertUnwindSafe(hook.on_trade_event(event, &self.actions)).catch_unwind().await.is_err() {
+                self.report_panic(hook.as_ref(), "on_trade_event");
+            }
+        }
+    }
+
+    pub async fn buy_decision(&self, event: &TradeEvent, decision: &BuyDecision) {
+        for hook in &self.hooks {
+            if AssertUnwindSafe(hook.on_buy_decision(event, decision, &self.actions)).catch_unwind().await.is_err() {
+                self.report_panic(hook.as_ref(), "on_buy_decision");
+            }
+        }
+    }
+
+    pub async fn position_opened(&self, position: &Position) {
+        for hook in &self.hooks {
+            if AssertUnwindSafe(hook.on_position_opened(position, &self.actions)).catch_unwind().await.is_err() {
+                self.report_panic(hook.as_ref(), "on_position_opened");
+            }
+        }
+    }
+
+    pub async fn tick_for_held(&self, event: &TradeEvent, position: &Position) {
+        for hook in &self.hooks {
+            if AssertUnwindSafe(hook.on_tick_for_held(event, position, &self.actions)).catch_unwind().await.is_err() {
+                self.report_panic(hook.as_ref(), "on_tick_for_held");
+            }
+        }
+    }
+
+    pub async fn sell_decision(&self, event: &TradeEvent, position: &Position, decision: &ExitDecision) {
+        for hook in &self.hooks {
+            if AssertUnwindSafe(hook.on_sell_decision(event, position, decision, &self.actions)).catch_unwind().await.is_err() {
+                self.report_panic(hook.as_ref(), "on_sell_decision");
+            }
+        }
+    }
+
+    pub async fn position_closed(&self, position: &Position) {
+        for hook in &self.hooks {
+            if AssertUnwindSafe(hook.on_position_closed(position, &self.actions)).catch_unwind().await.is_err() {
+                self.report_panic(hook.as_ref(), "on_position_closed");
+            }
+        }
+    }
+
+    pub async fn position_update(&self, update: &PositionUpdate) {
+        for hook in &self.hooks {
+            if AssertUnwindSafe(hook.on_position_update(update, &self.actions)).catch_unwind().await.is_err() {
+                self.report_panic(hook.as_ref(), "on_position_update");
+            }
+        }
+    }
+}
+
+/// Compiled-in hooks selected by STRATEGY_HOOKS (comma-separated names, default: none)
+/// Known names: "multiple_alert", "tick_csv".
+pub fn hooks_from_env() -> Vec<Arc<dyn StrategyHook>> {
+    let names = std::env::var("STRATEGY_HOOKS").unwrap_or_default();
+    names
+        .split(',')
+        .map(|name| name.trim())
+        .filter(|name| !name.is_empty())
+        .filter_map(|name| -> Option<Arc<dyn StrategyHook>> {
+            match name {
+                "multiple_alert" => Some(Arc::new(MultipleAlertHook::from_env())),
+                "tick_csv" => Some(Arc::new(TickCsvHook::from_env())),
+                other => {
+                    eprintln!("Unknown strategy hook '{}' in STRATEGY_HOOKS - skipped", other);
+                    None
+                }
+            }
+        })
+        .collect()
+}
+
+/// Notifies once when a held token reaches HOOK_ALERT_MULTIPLE times its entry price (default: 5x)
+pub struct MultipleAlertHook {
+    multiple: f64,
+    alerted: DashSet<String>,
+}
+
+impl MultipleAlertHook {
+    pub fn new(multiple: f64) -> Self {
+        Self {
+            multiple,
+            alerted: DashSet::new(),
+        }
+    }
+
+    pub fn from_env() -> Self {
+        let multiple = std::env::var("HOOK_ALERT_MULTIPLE")
+            .ok()
+            .and_then(|v| v.parse::<f64>().ok())
+            .filter(|v| *v > 1.0)
+            .unwrap_or(5.0);
+        Self::new(multiple)
+    }
+}
+
+#[async_trait]
+impl StrategyHook for MultipleAlertHook {
+    fn name(&self) -> &str {
+        "multiple_alert"
+    }
+
+    async fn on_tick_for_held(&self, event: &TradeEvent, position: &Position, actions: &HookActions) {
+        let entry_price = position.entry_price();
+        if entry_price <= 0.0 || event.core.post_current_price < entry_price * self.multiple {
+            return;
+        }
+        if self.alerted.insert(position.mint.clone()) {
+            actions.notify(format!(
+                "🚀 {} is up {:.1}x (entry {:.10}, now {:.10})",
+                position.mint,
+                event.core.post_current_price / entry_price,
+                entry_price,
+                event.core.post_current_price
+            ));
+        }
+    }
+
+    async fn on_position_closed(&self, position: &Position, _actions: &HookActions) {
+        self.alerted.remove(&position.mint);
+    }
+}
+
+/// Appends every tick of held tokens to HOOK_TICK_CSV_FILE (default: ticks.csv)
+pub struct TickCsvHook {
+    writer: LineWriter,
+    /// Header is written once per run
+    header_written: AtomicBool,
+}
+
+impl TickCsvHook {
+    pub fn new(path: String) -> Self {
+        let logger = Logger::new("[TICK-CSV] => ".magenta().to_string());
+        Self {
+            writer: LineWriter::new(path, logger),
+            header_written: AtomicBool::new(false),
+        }
+    }
+
+    pub fn from_env() -> Self {
+        let path = std::env::var("HOOK_TICK_CSV_FILE").unwrap_or_else(|_| "ticks.csv".to_string());
+        Self::new(path)
+    }
+}
+
+#[async_trait]
+impl StrategyHook for TickCsvHook {
+    fn name(&self) -> &str {
+        "tick_csv"
+    }
+
+    async fn on_tick_for_held(&self, event: &TradeEvent, position: &Position, _actions: &HookActions) {
+        if !self.header_written.swap(true, Ordering::Relaxed) {
+            self.writer.write_line("timestamp,slot,mint,price,entry_price,peak_price,sol_change,is_buy".to_string());
+        }
+        self.writer.write_line(format!(
+            "{},{},{},{},{},{},{},{}",
+            event.core.timestamp,
+            event.core.slot,
+            position.mint,
+            event.core.post_current_price,
+            position.entry_price(),
+            position.peak_price,
+            event.core.sol_change,
+            event.core.is_buy
+        ));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+    use crate::engine::test_fixtures::{book_with, event, pubkey, trade};
+    use crate::services::notifier::MemoryNotifier;
+
+    /// Records every call as "<name>:<point>" into a shared journal
+    struct Recorder {
+        name: String,
+        journal: Arc<Mutex<Vec<String>>>,
+    }
+
+    #[async_trait]
+    impl StrategyHook for Recorder {
+        fn name(&self) -> &str {
+            &self.name
+        }
+
+        async fn on_trade_event(&self, _event: &TradeEvent, _actions: &HookActions) {
+            self.journal.lock().unwrap().push(format!("{}:trade", self.name));
+        }
+
+        async fn on_position_closed(&self, _position: &Position, _actions: &HookActions) {
+            self.journal.lock().unwrap().push(format!("{}:closed", self.name));
+        }
+    }
+
+    struct Panicking;
+
+    #[async_trait]
+    impl StrategyHook for Panicking {
+        fn name(&self) -> &str {
+            "panicking"
+        }
+
+        async fn on_trade_event(&self, _event: &TradeEvent, _actions: &HookActions) {
+            panic!("hook bug");
+        }
+    }
+
+    fn recorder(name: &str, journal: &Arc<Mutex<Vec<String>>>) -> Arc<dyn StrategyHook> {
+        Arc::new(Recorder { name: name.to_string(), journal: journal.clone() })
+    }
+
+    #[tokio::test]
+    async fn hooks_run_in_registration_order_past_a_panic() {
+        let journal = Arc::new(Mutex::new(Vec::new()));
+        let mut registry = HookRegistry::new(Arc::new(MemoryNotifier::new()));
+        registry.register(recorder("first", &journal));
+        registry.register(Arc::new(Panicking));
+        registry.register(recorder("second", &journal));
+        assert_eq!(registry.len(), 3);
+
+        registry.trade_event(&event(trade(&pubkey(), true, 1.0))).await;
+        let mint = pubkey();
+        registry.position_closed(&book_with(&mint, 1_000.0, 1.0).get(&mint).unwrap()).await;
+
+        assert_eq!(*journal.lock().unwrap(), vec!["first:trade", "second:trade", "first:closed", "second:closed"]);
+    }
+
+    #[test]
+    fn sell_requests_are_consumed_once() {
+        let actions = HookActions::new(Arc::new(MemoryNotifier::new()));
+        actions.request_sell("mint");
+        assert!(actions.take_sell_request("mint"));
+        assert!(!actions.take_sell_request("mint"));
+        assert!(!actions.take_sell_request("other"));
+    }
+
+    #[tokio::test]
+    async fn multiple_alert_fires_once_per_position() {
+        let notifier = Arc::new(MemoryNotifier::new());
+        let actions = HookActions::new(notifier.clone());
+        let hook = MultipleAlertHook::new(5.0);
+        let mint = pubkey();
+        // Entry at 0.001 SOL per token
+        let position = book_with(&mint, 1_000.0, 1.0).get(&mint).unwrap();
+
+        hook.on_tick_for_held(&event(trade(&mint, true, 0.004)), &position, &actions).await;
+        assert!(notifier.messages().is_empty());
+        hook.on_tick_for_held(&event(trade(&mint, true, 0.005)), &position, &actions).await;
+        hook.on_tick_for_held(&event(trade(&mint, true, 0.006)), &position, &actions).await;
+        assert_eq!(notifier.messages().len(), 1);
+        assert!(notifier.messages()[0].contains("5.0x"), "{}", notifier.messages()[0]);
+
+        // A new position of the same mint alerts again
+        hook.on_position_closed(&position, &actions).await;
+        hook.on_tick_for_held(&event(trade(&mint, true, 0.006)), &position, &actions).await;
+        assert_eq!(notifier.messages().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn tick_csv_writes_the_header_once() {
+        let path = std::env::temp_dir().join(format!("ticks-{}.csv", pubkey()));
+        let hook = TickCsvHook::new(path.to_string_lossy().to_string());
+        let actions = HookActions::new(Arc::new(MemoryNotifier::new()));
+        let mint = pubkey();
+        let position = book_with(&mint, 1_000.0, 1.0).get(&mint).unwrap();
+        for _ in 0..2 {
+            hook.on_tick_for_held(&event(trade(&mint, true, 0.002)), &position, &actions).await;
+        }
+        crate::services::line_writer::flush_all(std::time::Duration::from_secs(5)).await;
+
+        let contents = std::fs::read_to_string(&path).unwrap();
+        let _ = std::fs::remove_file(&path);
+        let lines: Vec<&str> = contents.lines().collect();
+        assert_eq!(lines.len(), 3, "{}", contents);
+        assert!(lines[0].starts_with("timestamp,slot,mint"));
+        assert!(lines[1].contains(&mint));
+    }
+}
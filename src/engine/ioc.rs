@@ -0,0 +1,202 @@
+use std::str::FromStr;
+use anchor_client::solana_sdk::{instruction::Instruction, pubkey::Pubkey};
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use dashmap::DashMap;
+use once_cell::sync::Lazy;
+
+use crate::common::clock;
+use crate::dex::math;
+use crate::engine::buy_filters::BuyFilter;
+use crate::engine::trade_event::TradeEvent;
+
+pub const LIGHTHOUSE_PROGRAM_ID: &str = "L2TExMFKdjpN9kozasaurPirfHy9P8sbXoAN1qA3S95";
+/// Lighthouse AssertSysvarClock
+const ASSERT_SYSVAR_CLOCK: u8 = 15;
+const LOG_LEVEL_SILENT: u8 = 0;
+/// SysvarClockAssertion::Slot
+const CLOCK_ASSERTION_SLOT: u8 = 0;
+/// IntegerOperator::LessThanOrEqual
+const OPERATOR_LESS_THAN_OR_EQUAL: u8 = 5;
+
+pub static IOC_CONFIG: Lazy<IocConfig> = Lazy::new(IocConfig::from_env);
+
+/// Mints whose last buy was cancelled by the slot assertion, with the unix time the cooldown ends
+static IOC_COOLDOWNS: Lazy<DashMap<String, u64>> = Lazy::new(DashMap::new);
+
+/// IOC_BUYS (default: false) makes every buy immediate-or-cancel: it fails on chain if it lands more than
+/// IOC_MAX_SLOTS (default: 2) after the trigger's slot, or at a cost above the trigger reserves' plus
+/// IOC_FEE_BPS (default: 125) and IOC_SLIPPAGE_BPS (default: 300). A cancelled mint waits
+/// IOC_COOLDOWN_SECS (default: 3) before the next attempt.
+#[derive(Clone, Copy, Debug)]
+pub struct IocConfig {
+    pub enabled: bool,
+    pub max_slots: u64,
+    /// Curve fees paid on top of the SOL the tokens cost
+    pub fee_bps: u64,
+    pub slippage_bps: u64,
+    pub cooldown_secs: u64,
+}
+
+impl IocConfig {
+    pub fn from_env() -> Self {
+        let enabled = std::env::var("IOC_BUYS")
+            .ok()
+            .and_then(|v| v.parse::<bool>().ok())
+            .unwrap_or(false);
+        let max_slots = std::env::var("IOC_MAX_SLOTS")
+            .ok()
+            .and_then(|v| v.parse::<u64>().ok())
+            .unwrap_or(2);
+        let fee_bps = std::env::var("IOC_FEE_BPS")
+            .ok()
+            .and_then(|v| v.parse::<u64>().ok())
+            .unwrap_or(125);
+        let slippage_bps = std::env::var("IOC_SLIPPAGE_BPS")
+            .ok()
+            .and_then(|v| v.parse::<u64>().ok())
+            .unwrap_or(300);
+        let cooldown_secs = std::env::var("IOC_COOLDOWN_SECS")
+            .ok()
+            .and_then(|v| v.parse::<u64>().ok())
+            .unwrap_or(3);
+        Self { enabled, max_slots, fee_bps, slippage_bps, cooldown_secs }
+    }
+
+    /// Last slot a buy triggered at `trigger_slot` may land in
+    pub fn deadline_slot(&self, trigger_slot: u64) -> u64 {
+        trigger_slot.saturating_add(self.max_slots)
+    }
+}
+
+/// Lighthouse assertion that the transaction executes no later than `max_slot`
+/// Reads the clock sysvar through the syscall, so it takes no accounts.
+pub fn slot_assertion_instruction(max_slot: u64) -> Result<Instruction> {
+    let mut data = Vec::with_capacity(12);
+    data.push(ASSERT_SYSVAR_CLOCK);
+    data.push(LOG_LEVEL_SILENT);
+    data.push(CLOCK_ASSERTION_SLOT);
+    data.extend_from_slice(&max_slot.to_le_bytes());
+    data.push(OPERATOR_LESS_THAN_OR_EQUAL);
+    Ok(Instruction {
+        program_id: Pubkey::from_str(LIGHTHOUSE_PROGRAM_ID)?,
+        accounts: vec![],
+        data,
+    })
+}
+
+/// Lamports `tokens_out` cost on a curve at the given virtual reserves, rounded up
+/// Inverse of `constant_product_out`: `tokens_out * reserve_sol / (reserve_token - tokens_out)`.
+pub fn sol_cost_at_reserves(tokens_out: u64, virtual_sol_reserves: u64, virtual_token_reserves: u64) -> Result<u64> {
+    if tokens_out >= virtual_token_reserves {
+        return Err(anyhow!("{} tokens exceed the curve's {} virtual reserves", tokens_out, virtual_token_reserves));
+    }
+    let numerator = (tokens_out as u128) * (virtual_sol_reserves as u128);
+    let denominator = (virtual_token_reserves - tokens_out) as u128;
+    u64::try_from(numerator.div_ceil(denominator)).map_err(|_| anyhow!("SOL cost of {} tokens overflows u64", tokens_out))
+}
+
+/// max_sol_cost for an IOC buy: the trigger cost of `tokens_out` plus fees and IOC slippage
+/// A fill after the price moved further than that fails instead of filling at the loose BUY_SLIPPAGE bound.
+pub fn tight_max_sol_cost(tokens_out: u64, virtual_sol_reserves: u64, virtual_token_reserves: u64, config: &IocConfig) -> Result<u64> {
+    let cost = sol_cost_at_reserves(tokens_out, virtual_sol_reserves, virtual_token_reserves)?;
+    let with_fees = math::max_amount_with_slippage(cost, config.fee_bps)?;
+    math::max_amount_with_slippage(with_fees, config.slippage_bps)
+}
+
+/// A buy cancelled itself by landing too late or too high; cheap, so only a short pause follows
+pub fn start_cooldown(mint: &str, config: &IocConfig) {
+    IOC_COOLDOWNS.insert(mint.to_string(), clock::now_secs() + config.cooldown_secs);
+}
+
+pub fn in_cooldown(mint: &str) -> bool {
+    match IOC_COOLDOWNS.get(mint).map(|until| *until) {
+        Some(until) if until > clock::now_secs() => true,
+        Some(_) => {
+            IOC_COOLDOWNS.remove(mint);
+            false
+        }
+        None => false,
+    }
+}
+
+/// Skip mints pausing after an IOC cancellation
+pub struct IocCooldownFilter;
+
+#[async_trait]
+impl BuyFilter for IocCooldownFilter {
+    fn name(&self) -> &str {
+        "ioc-cooldown"
+    }
+
+    async fn check(&self, event: &TradeEvent) -> Result<(), String> {
+        if in_cooldown(event.mint()) {
+            return Err("last buy was cancelled by its IOC bound".to_string());
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const VIRTUAL_SOL: u64 = 30_000_000_000;
+    const VIRTUAL_TOKENS: u64 = 1_073_000_000_000_000;
+
+    fn config() -> IocConfig {
+        IocConfig { enabled: true, max_slots: 2, fee_bps: 125, slippage_bps: 300, cooldown_secs: 3 }
+    }
+
+    #[test]
+    fn slot_assertion_encodes_the_deadline_for_lighthouse() {
+        let instruction = slot_assertion_instruction(config().deadline_slot(1_000)).unwrap();
+        assert_eq!(instruction.program_id.to_string(), LIGHTHOUSE_PROGRAM_ID);
+        assert!(instruction.accounts.is_empty());
+
+        let mut expected = vec![ASSERT_SYSVAR_CLOCK, LOG_LEVEL_SILENT, CLOCK_ASSERTION_SLOT];
+        expected.extend_from_slice(&1_002u64.to_le_bytes());
+        expected.push(OPERATOR_LESS_THAN_OR_EQUAL);
+        assert_eq!(instruction.data, expected);
+        assert_eq!(instruction.data.len(), 12);
+    }
+
+    #[test]
+    fn sol_cost_buys_at_least_the_tokens_on_the_curve() {
+        for tokens_out in [1, 1_000_000, 35_000_000_000_000] {
+            let cost = sol_cost_at_reserves(tokens_out, VIRTUAL_SOL, VIRTUAL_TOKENS).unwrap();
+            assert!(math::constant_product_out(cost, VIRTUAL_SOL, VIRTUAL_TOKENS) >= tokens_out, "{} tokens for {}", tokens_out, cost);
+            assert!(math::constant_product_out(cost - 1, VIRTUAL_SOL, VIRTUAL_TOKENS) < tokens_out);
+        }
+        // 1/3 of the tokens costs half the SOL reserve on x*y=k
+        assert_eq!(sol_cost_at_reserves(1_000, 2_000, 3_000).unwrap(), 1_000);
+        assert_eq!(sol_cost_at_reserves(1, 3, 10).unwrap(), 1);
+    }
+
+    #[test]
+    fn sol_cost_of_the_whole_reserve_is_an_error() {
+        assert!(sol_cost_at_reserves(VIRTUAL_TOKENS, VIRTUAL_SOL, VIRTUAL_TOKENS).is_err());
+        assert!(sol_cost_at_reserves(VIRTUAL_TOKENS + 1, VIRTUAL_SOL, VIRTUAL_TOKENS).is_err());
+        assert!(sol_cost_at_reserves(u64::MAX - 1, u64::MAX, u64::MAX).is_err());
+    }
+
+    #[test]
+    fn tight_bound_pads_the_trigger_cost_with_fees_then_slippage() {
+        // 1_000 lamports * 1.0125 = 1_012, * 1.03 = 1_042 (each step floors)
+        assert_eq!(tight_max_sol_cost(1_000, 2_000, 3_000, &config()).unwrap(), 1_042);
+        let loose = IocConfig { fee_bps: 0, slippage_bps: 0, ..config() };
+        assert_eq!(tight_max_sol_cost(1_000, 2_000, 3_000, &loose).unwrap(), 1_000);
+        assert!(tight_max_sol_cost(3_000, 2_000, 3_000, &config()).is_err());
+    }
+
+    #[test]
+    fn cooldown_lapses_after_its_seconds() {
+        let mint = Pubkey::new_unique().to_string();
+        assert!(!in_cooldown(&mint));
+        start_cooldown(&mint, &config());
+        assert!(in_cooldown(&mint));
+        start_cooldown(&mint, &IocConfig { cooldown_secs: 0, ..config() });
+        assert!(!in_cooldown(&mint));
+        assert!(!IOC_COOLDOWNS.contains_key(&mint));
+    }
+}
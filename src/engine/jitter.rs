@@ -0,0 +1,87 @@
+use std::sync::Mutex;
+use once_cell::sync::Lazy;
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+/// Jitter for live sends, seeded from entropy
+pub static JITTER: Lazy<Jitter> = Lazy::new(|| Jitter::new(JitterConfig::from_env()));
+
+/// AMOUNT_JITTER_PCT (default: 0) and TIP_JITTER_PCT (default: 0) randomize buy sizes and tips by up to that
+/// many percent either way, so fills can't be recognised by a fixed amount; both are capped at 50
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct JitterConfig {
+    pub amount_pct: f64,
+    pub tip_pct: f64,
+}
+
+impl JitterConfig {
+    pub fn from_env() -> Self {
+        let read = |key: &str| {
+            std::env::var(key)
+                .ok()
+                .and_then(|v| v.parse::<f64>().ok())
+                .filter(|v| v.is_finite())
+                .unwrap_or(0.0)
+                .clamp(0.0, 50.0)
+        };
+        Self { amount_pct: read("AMOUNT_JITTER_PCT"), tip_pct: read("TIP_JITTER_PCT") }
+    }
+}
+
+/// Uniform factor in [1 - pct/100, 1 + pct/100]
+pub fn jitter_factor<R: Rng>(rng: &mut R, pct: f64) -> f64 {
+    if pct <= 0.0 {
+        return 1.0;
+    }
+    let spread = pct / 100.0;
+    rng.gen_range((1.0 - spread)..=(1.0 + spread))
+}
+
+/// A buy size before and after jitter
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct JitteredAmount {
+    pub base: f64,
+    pub jittered: f64,
+}
+
+/// Randomizes sizes and tips; `seeded` makes the sequence reproducible for harnesses
+pub struct Jitter {
+    config: JitterConfig,
+    rng: Mutex<StdRng>,
+}
+
+impl Jitter {
+    pub fn new(config: JitterConfig) -> Self {
+        Self { config, rng: Mutex::new(StdRng::from_entropy()) }
+    }
+
+    pub fn seeded(config: JitterConfig, seed: u64) -> Self {
+        Self { config, rng: Mutex::new(StdRng::seed_from_u64(seed)) }
+    }
+
+    pub fn config(&self) -> JitterConfig {
+        self.config
+    }
+
+    fn factor(&self, pct: f64) -> f64 {
+        let mut rng = self.rng.lock().unwrap_or_else(|e| e.into_inner());
+        jitter_factor(&mut *rng, pct)
+    }
+
+    /// `base` jittered, then clamped to [min_sol, max_sol]
+    /// Caps checked afterwards see the jittered size, so jitter can never push a buy past them.
+    pub fn buy_amount(&self, base: f64, min_sol: f64, max_sol: f64) -> JitteredAmount {
+        if self.config.amount_pct <= 0.0 {
+            return JitteredAmount { base, jittered: base };
+        }
+        let jittered = (base * self.factor(self.config.amount_pct)).clamp(min_sol.min(max_sol), max_sol);
+        JitteredAmount { base, jittered }
+    }
+
+    /// Tip in SOL, jittered
+    pub fn tip(&self, base: f64) -> f64 {
+        if self.config.tip_pct <= 0.0 {
+            return base;
+        }
+        base * self.factor(self.config.tip_pct)
+    }
+}
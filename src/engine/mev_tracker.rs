@@ -0,0 +1,267 @@
+use std::collections::{HashMap, VecDeque};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use async_trait::async_trait;
+use once_cell::sync::Lazy;
+
+use crate::engine::buy_filters::BuyFilter;
+use crate::engine::trade_event::TradeEvent;
+use crate::engine::transaction_parser::{PreviousTransactionTrackingInfo, TradeInfoFromToken};
+use crate::services::telegram_commands;
+
+pub static MEV_CONFIG: Lazy<MevConfig> = Lazy::new(MevConfig::from_env);
+
+static TRACKER: Lazy<Mutex<SlotTracker>> = Lazy::new(|| Mutex::new(SlotTracker::new(MEV_CONFIG.window_slots)));
+
+/// Trades flagged since startup
+static FLAGGED_TRADES: AtomicU64 = AtomicU64::new(0);
+/// Buys the filter turned down on a flagged trigger, since startup
+static SKIPPED_BUYS: AtomicU64 = AtomicU64::new(0);
+
+/// A slot not added to for this long belongs to a stream that went quiet; cache maintenance drops it
+const STALE_SLOT_AGE: Duration = Duration::from_secs(60);
+
+/// MEV_TRACKER_SLOTS (default: 32) recent slots whose trades are kept; SKIP_SUSPECTED_MEV (default: false) skips
+/// buys triggered by a flagged trade
+#[derive(Clone, Copy, Debug)]
+pub struct MevConfig {
+    pub window_slots: usize,
+    pub skip_suspected: bool,
+}
+
+impl MevConfig {
+    pub fn from_env() -> Self {
+        let window_slots = std::env::var("MEV_TRACKER_SLOTS")
+            .ok()
+            .and_then(|v| v.parse::<usize>().ok())
+            .filter(|v| *v >= 2)
+            .unwrap_or(32);
+        let skip_suspected = std::env::var("SKIP_SUSPECTED_MEV")
+            .ok()
+            .and_then(|v| v.parse::<bool>().ok())
+            .unwrap_or(false);
+        Self { window_slots, skip_suspected }
+    }
+}
+
+/// Trades of one slot by (signer, mint)
+struct SlotTrades {
+    slot: u64,
+    seen_at: Instant,
+    trades: HashMap<(String, String), Vec<PreviousTransactionTrackingInfo>>,
+}
+
+/// Trades of the last `window_slots` slots, oldest first; a slot older than all of them is dropped on arrival
+pub struct SlotTracker {
+    window_slots: usize,
+    slots: VecDeque<SlotTrades>,
+}
+
+impl SlotTracker {
+    pub fn new(window_slots: usize) -> Self {
+        Self { window_slots, slots: VecDeque::with_capacity(window_slots) }
+    }
+
+    /// Record `info` for `signer`; whether the same signer traded the mint the other way in the same or an
+    /// adjacent slot
+    pub fn observe(&mut self, signer: &str, info: PreviousTransactionTrackingInfo, now: Instant) -> bool {
+        let key = (signer.to_string(), info.mint.clone());
+        let slot = info.slot;
+        let opposite = self.slots
+            .iter()
+            .filter(|entry| entry.slot.abs_diff(slot) <= 1)
+            .filter_map(|entry| entry.trades.get(&key))
+            .flatten()
+            .any(|previous| previous.is_buy != info.is_buy);
+
+        let index = match self.slots.iter().position(|entry| entry.slot >= slot) {
+            Some(index) if self.slots[index].slot == slot => index,
+            position => {
+                let index = position.unwrap_or(self.slots.len());
+                if index == 0 && self.slots.len() >= self.window_slots {
+                    return opposite;
+                }
+                self.slots.insert(index, SlotTrades { slot, seen_at: now, trades: HashMap::new() });
+                if self.slots.len() > self.window_slots {
+                    self.slots.pop_front();
+                    index - 1
+                } else {
+                    index
+                }
+            }
+        };
+        let entry = &mut self.slots[index];
+        entry.seen_at = now;
+        entry.trades.entry(key).or_default().push(info);
+        opposite
+    }
+
+    /// Drop slots nothing was added to for `max_age`; the number removed
+    pub fn cleanup(&mut self, now: Instant, max_age: Duration) -> usize {
+        let initial_count = self.slots.len();
+        self.slots.retain(|entry| now.duration_since(entry.seen_at) < max_age);
+        initial_count - self.slots.len()
+    }
+
+    pub fn tracked_slots(&self) -> usize {
+        self.slots.len()
+    }
+
+    pub fn tracked_trades(&self) -> usize {
+        self.slots.iter().flat_map(|entry| entry.trades.values()).map(Vec::len).sum()
+    }
+}
+
+/// Flag `trade_info` when its signer traded the mint the other way within a slot of it, then record it
+/// Only the second leg is flagged: the first was already delivered by the time the second shows up.
+pub fn flag_suspected_mev(trade_info: &mut TradeInfoFromToken) {
+    if trade_info.trader.is_empty() {
+        return;
+    }
+    let info = PreviousTransactionTrackingInfo {
+        dex_type: trade_info.dex_type,
+        slot: trade_info.slot,
+        mint: trade_info.mint.clone(),
+        is_buy: trade_info.is_buy,
+        price: trade_info.post_current_price,
+        sol_change: trade_info.sol_change,
+    };
+    let Ok(mut tracker) = TRACKER.lock() else {
+        return;
+    };
+    if tracker.observe(&trade_info.trader, info, Instant::now()) {
+        trade_info.suspected_mev = true;
+        FLAGGED_TRADES.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+/// Drop slots the stream stopped adding to; the number removed
+pub fn cleanup_stale() -> usize {
+    TRACKER.lock().map(|mut tracker| tracker.cleanup(Instant::now(), STALE_SLOT_AGE)).unwrap_or(0)
+}
+
+fn mev_status_section() -> String {
+    let (slots, trades) = TRACKER.lock()
+        .map(|tracker| (tracker.tracked_slots(), tracker.tracked_trades()))
+        .unwrap_or_default();
+    format!(
+        "MEV: {} trade(s) flagged, {} buy(s) skipped, tracking {} trade(s) over {} slot(s)",
+        FLAGGED_TRADES.load(Ordering::Relaxed),
+        SKIPPED_BUYS.load(Ordering::Relaxed),
+        trades,
+        slots
+    )
+}
+
+pub fn register_status_section() {
+    telegram_commands::register_status_section("mev", mev_status_section);
+}
+
+/// Skip buys triggered by a trade whose signer traded the mint the other way within a slot (SKIP_SUSPECTED_MEV)
+pub struct MevFilter;
+
+#[async_trait]
+impl BuyFilter for MevFilter {
+    fn name(&self) -> &str {
+        "suspected-mev"
+    }
+
+    async fn check(&self, event: &TradeEvent) -> Result<(), String> {
+        if !event.core.suspected_mev {
+            return Ok(());
+        }
+        SKIPPED_BUYS.fetch_add(1, Ordering::Relaxed);
+        Err(format!("trigger signer {} traded the mint both ways within a slot", event.extras.trader))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::transaction_parser::DexType;
+
+    const MINT: &str = "mint";
+
+    fn info(slot: u64, is_buy: bool) -> PreviousTransactionTrackingInfo {
+        PreviousTransactionTrackingInfo {
+            dex_type: DexType::PumpFun,
+            slot,
+            mint: MINT.to_string(),
+            is_buy,
+            price: 1e-6,
+            sol_change: if is_buy { -1.0 } else { 1.0 },
+        }
+    }
+
+    #[test]
+    fn the_other_leg_in_the_same_slot_is_flagged() {
+        let mut tracker = SlotTracker::new(32);
+        let now = Instant::now();
+        assert!(!tracker.observe("signer", info(10, true), now));
+        assert!(tracker.observe("signer", info(10, false), now));
+        // Another signer, or another mint, is no round trip
+        assert!(!tracker.observe("other", info(10, false), now));
+        let mut other_mint = info(10, false);
+        other_mint.mint = "other-mint".to_string();
+        assert!(!tracker.observe("signer", other_mint, now));
+    }
+
+    #[test]
+    fn the_other_leg_in_an_adjacent_slot_is_flagged_in_either_order() {
+        let mut tracker = SlotTracker::new(32);
+        let now = Instant::now();
+        assert!(!tracker.observe("early", info(10, true), now));
+        assert!(tracker.observe("early", info(11, false), now));
+        // Delivered out of order: the later slot first
+        assert!(!tracker.observe("late", info(21, true), now));
+        assert!(tracker.observe("late", info(20, false), now));
+        // Two slots apart is a separate trade
+        assert!(!tracker.observe("apart", info(30, true), now));
+        assert!(!tracker.observe("apart", info(32, false), now));
+    }
+
+    #[test]
+    fn the_same_direction_twice_is_not_flagged() {
+        let mut tracker = SlotTracker::new(32);
+        let now = Instant::now();
+        assert!(!tracker.observe("signer", info(10, true), now));
+        assert!(!tracker.observe("signer", info(10, true), now));
+        assert!(!tracker.observe("signer", info(11, true), now));
+        assert_eq!(tracker.tracked_trades(), 3);
+    }
+
+    #[test]
+    fn the_window_keeps_the_newest_slots() {
+        let mut tracker = SlotTracker::new(3);
+        let now = Instant::now();
+        for slot in [10, 12, 13] {
+            tracker.observe("signer", info(slot, true), now);
+        }
+        // Slot 11 lands between them and pushes the oldest out
+        assert!(!tracker.observe("signer", info(11, true), now));
+        assert_eq!(tracker.tracked_slots(), 3);
+        assert!(tracker.observe("signer", info(11, false), now));
+
+        // Older than the full window: dropped, though still checked against what is kept
+        let trades = tracker.tracked_trades();
+        assert!(!tracker.observe("signer", info(5, false), now));
+        assert!(tracker.observe("signer", info(10, false), now));
+        assert_eq!((tracker.tracked_slots(), tracker.tracked_trades()), (3, trades));
+    }
+
+    #[test]
+    fn cleanup_drops_slots_that_went_quiet() {
+        let mut tracker = SlotTracker::new(32);
+        let start = Instant::now();
+        tracker.observe("signer", info(10, true), start);
+        tracker.observe("signer", info(11, true), start + Duration::from_secs(30));
+        assert_eq!(tracker.cleanup(start + Duration::from_secs(59), STALE_SLOT_AGE), 0);
+        assert_eq!(tracker.cleanup(start + STALE_SLOT_AGE, STALE_SLOT_AGE), 1);
+        assert_eq!(tracker.tracked_slots(), 1);
+
+        // A trade added to a slot keeps it alive
+        tracker.observe("other", info(11, true), start + STALE_SLOT_AGE);
+        assert_eq!(tracker.cleanup(start + STALE_SLOT_AGE + Duration::from_secs(59), STALE_SLOT_AGE), 0);
+    }
+}
@@ -0,0 +1,130 @@
+use dashmap::DashMap;
+use once_cell::sync::Lazy;
+
+use crate::engine::trade_event::TradeEvent;
+use crate::engine::transaction_parser::DexType;
+
+/// Held mints whose PumpFun curve completed, with their PumpSwap pool once a trade on it was seen
+/// The PumpFun program refuses every trade on a complete curve, so sells of these mints skip it.
+static MIGRATED: Lazy<DashMap<String, Option<String>>> = Lazy::new(DashMap::new);
+
+/// What a trade on a held mint says about its migration
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum MigrationSignal {
+    /// The PumpFun trade that took the last real tokens; the pool doesn't exist yet
+    CurveComplete,
+    /// A trade on the mint's PumpSwap pool
+    PoolTrade { pool_id: String },
+}
+
+/// The signal in `event` for a mint held as `held`; None for ordinary trades
+/// A position opened on PumpSwap has nothing to migrate, and once the pool is known its trades are no signal.
+pub fn migration_signal(held: DexType, event: &TradeEvent) -> Option<MigrationSignal> {
+    match event.core.dex_type {
+        DexType::PumpFun if event.core.curve_complete => Some(MigrationSignal::CurveComplete),
+        DexType::PumpSwap if held == DexType::PumpFun || awaiting_pool(&event.extras.mint) => {
+            Some(MigrationSignal::PoolTrade { pool_id: event.extras.pool_id.clone() })
+        }
+        _ => None,
+    }
+}
+
+/// Record the migration of `mint`; true the first time it is recorded
+/// A later call with the pool fills it in without counting as a new migration.
+pub fn mark_migrated(mint: &str, pool_id: Option<String>) -> bool {
+    let mut first = false;
+    MIGRATED
+        .entry(mint.to_string())
+        .and_modify(|known| {
+            if known.is_none() {
+                *known = pool_id.clone();
+            }
+        })
+        .or_insert_with(|| {
+            first = true;
+            pool_id
+        });
+    first
+}
+
+pub fn is_migrated(mint: &str) -> bool {
+    MIGRATED.contains_key(mint)
+}
+
+/// Migrated, but no trade on its pool seen yet
+fn awaiting_pool(mint: &str) -> bool {
+    MIGRATED.get(mint).is_some_and(|pool| pool.is_none())
+}
+
+/// PumpSwap pool of a migrated mint, once known
+pub fn migrated_pool(mint: &str) -> Option<String> {
+    MIGRATED.get(mint).and_then(|pool| pool.clone())
+}
+
+/// Drop a mint once its position closed
+pub fn forget(mint: &str) {
+    MIGRATED.remove(mint);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::test_fixtures::{event, pubkey, trade};
+
+    fn pool_trade(mint: &str, pool_id: &str) -> TradeEvent {
+        let mut trade_info = trade(mint, true, 0.001);
+        trade_info.dex_type = DexType::PumpSwap;
+        trade_info.pool_id = pool_id.to_string();
+        event(trade_info)
+    }
+
+    fn completing_trade(mint: &str) -> TradeEvent {
+        let mut trade_info = trade(mint, true, 0.001);
+        trade_info.curve_complete = true;
+        event(trade_info)
+    }
+
+    #[test]
+    fn a_held_curve_migrates_then_learns_its_pool() {
+        let (mint, pool) = (pubkey(), pubkey());
+        assert_eq!(migration_signal(DexType::PumpFun, &event(trade(&mint, true, 0.001))), None);
+
+        assert_eq!(migration_signal(DexType::PumpFun, &completing_trade(&mint)), Some(MigrationSignal::CurveComplete));
+        assert!(mark_migrated(&mint, None));
+        assert!(is_migrated(&mint));
+        assert_eq!(migrated_pool(&mint), None);
+
+        // The position now reads PumpSwap, but the pool is still awaited
+        let signal = migration_signal(DexType::PumpSwap, &pool_trade(&mint, &pool));
+        assert_eq!(signal, Some(MigrationSignal::PoolTrade { pool_id: pool.clone() }));
+        assert!(!mark_migrated(&mint, Some(pool.clone())));
+        assert_eq!(migrated_pool(&mint).as_deref(), Some(pool.as_str()));
+
+        // Once known, pool trades are ordinary and a second pool never replaces it
+        assert_eq!(migration_signal(DexType::PumpSwap, &pool_trade(&mint, &pubkey())), None);
+        assert!(!mark_migrated(&mint, Some(pubkey())));
+        assert_eq!(migrated_pool(&mint).as_deref(), Some(pool.as_str()));
+
+        forget(&mint);
+        assert!(!is_migrated(&mint));
+    }
+
+    #[test]
+    fn a_pool_trade_alone_migrates_a_held_curve() {
+        let (mint, pool) = (pubkey(), pubkey());
+        let signal = migration_signal(DexType::PumpFun, &pool_trade(&mint, &pool));
+        assert_eq!(signal, Some(MigrationSignal::PoolTrade { pool_id: pool.clone() }));
+        assert!(mark_migrated(&mint, Some(pool.clone())));
+        assert_eq!(migrated_pool(&mint).as_deref(), Some(pool.as_str()));
+    }
+
+    #[test]
+    fn positions_opened_elsewhere_never_migrate() {
+        let mint = pubkey();
+        assert_eq!(migration_signal(DexType::PumpSwap, &pool_trade(&mint, &pubkey())), None);
+        let mut raydium = trade(&mint, true, 0.001);
+        raydium.dex_type = DexType::RaydiumAmm;
+        assert_eq!(migration_signal(DexType::RaydiumAmm, &event(raydium)), None);
+        assert!(!is_migrated(&mint));
+    }
+}
@@ -3,4 +3,36 @@ pub mod monitor;
 pub mod selling_strategy;
 pub mod swap;
 pub mod transaction_parser;
+pub mod trade_event;
 pub mod transaction_retry;
+pub mod position;
+pub mod position_archive;
+pub mod position_updates;
+pub mod position_overrides;
+pub mod exit_strategy;
+pub mod strategy_sim;
+pub mod executor;
+pub mod event_stream;
+pub mod grpc_probe;
+pub mod orchestrator;
+pub mod buy_filters;
+pub mod risk;
+pub mod freeze_guard;
+pub mod honeypot;
+pub mod hooks;
+pub mod startup_recovery;
+pub mod replay;
+pub mod gap_catchup;
+pub mod ioc;
+pub mod creator_vault;
+pub mod valuation;
+pub mod sell_reasons;
+pub mod jitter;
+pub mod chaos;
+pub mod migration;
+pub mod wallet_sync;
+pub mod reentry;
+pub mod mev_tracker;
+pub mod buy_sizing;
+#[cfg(test)]
+pub(crate) mod test_fixtures;
@@ -0,0 +1,1864 @@
+use std::str::FromStr;
+use std::sync::{Arc, RwLock};
+use anchor_client::solana_sdk::{commitment_config::CommitmentLevel, pubkey::Pubkey, signature::{Signature, Signer}};
+use colored::Colorize;
+use dashmap::DashMap;
+
+use crate::common::{
+    cache::PROGRESS_ON_SELLING,
+    clock,
+    config::{AppState, SwapConfig},
+    logger::Logger,
+    shutdown,
+};
+use crate::engine::chaos;
+use crate::engine::honeypot::{HoneypotConfig, HoneypotFilter};
+use crate::engine::migration::{self, MigrationSignal};
+use crate::engine::buy_filters::{BlacklistFilter, BuyFilter, FreezeAuthorityFilter, LiquidityFilter, QualifyingBuyFilter, QUALIFYING_BUY_FILTER};
+use crate::dex::pump_fun::PUMP_FUN_PROGRAM;
+use crate::dex::raydium::RAYDIUM_AMM_PROGRAM;
+use crate::engine::event_stream::{EventSource, GrpcEventSource, StreamGap, PUMP_SWAP_PROGRAM};
+use crate::engine::executor::{LiveExecutor, TradeExecutor};
+use crate::engine::exit_strategy::{evaluate_exit, ExitDecision, ExitReason, ExitState, RuntimeParams};
+use crate::engine::position::{BuyConfirmation, ExitFill, Lot, Position, PositionBook, PositionState, POSITION_BOOK};
+use crate::engine::position_archive::{self, PositionArchive};
+use crate::engine::position_overrides::{self, OVERRIDE_STORE};
+use crate::engine::position_updates::{PositionUpdateKind, PositionUpdates, POSITION_UPDATES};
+use crate::engine::risk::{self, CreatorExposureLimits, ExposureDecision, PortfolioLimits, RpcWalletBalance, WalletBalance};
+use crate::engine::sell_reasons;
+use crate::engine::sniper::{start_sniper, SniperConfig};
+use crate::engine::startup_recovery::{self, RecoveryConfig, RecoveryCooldownFilter, RpcIntentVerifier};
+use crate::engine::freeze_guard;
+use crate::engine::gap_catchup::{self, GapCatchUpConfig, GapReader, RpcGapReader};
+use crate::engine::grpc_probe::{self, ProbeReport};
+use crate::engine::hooks::{self, BuyDecision, HookRegistry, StrategyHook};
+use crate::engine::jitter::{Jitter, JitterConfig};
+use crate::engine::ioc::{self, IocCooldownFilter};
+use crate::engine::reentry::{self, ReentryFilter};
+use crate::engine::mev_tracker::{self, MevFilter};
+use crate::engine::buy_sizing::BuySizeTiers;
+use crate::engine::creator_vault::{CreatorVaultMonitor, RpcVaultBalanceReader, VaultMonitorConfig};
+use crate::engine::trade_event::TradeEvent;
+use crate::engine::valuation::{self, RpcPriceEstimator, ValuationConfig, ValuationRefresher};
+use crate::engine::transaction_parser;
+use crate::engine::transaction_parser::DexType;
+use crate::engine::wallet_sync::{self, BalanceChange, Reconciliation};
+use crate::error::classify::{classify_error, ErrorClass};
+use crate::error::sniper::classify_anyhow;
+use crate::services::blockhash_processor::BlockhashProcessor;
+use crate::services::buy_followup::{BotEditor, BuyFollowUp, RpcEnrichmentSource};
+use crate::services::cache_maintenance;
+use crate::services::config_watch::{ConfigWatchConfig, ConfigWatcher};
+use crate::services::confirmation_tracker::{self, ConfirmationOutcome, ConfirmationTracker};
+use crate::services::intent_log::{Intent, IntentLog};
+use crate::services::ledger::{JsonlLedger, Ledger, LedgerEntry};
+use crate::services::notifier::{Notifier, TelegramNotifier};
+use crate::services::pnl_tracker::PnlTracker;
+use crate::services::slippage::{self, EffectiveSlippage, SlippageBasis, SlippageSample};
+use crate::services::trade_costs::{FillConfirmation, FillCostSource, RpcFillCostSource};
+use crate::services::{session_report, telegram};
+
+/// How often `run` checks Pending positions for a failed or timed-out buy
+const PENDING_SWEEP_INTERVAL: std::time::Duration = std::time::Duration::from_secs(1);
+
+/// Wires the event stream, buy filters, executor, position book and selling supervisor together
+/// Every dependency is injected so the same loop runs live and against paper/in-memory parts.
+pub struct Orchestrator {
+    executor: Arc<dyn TradeExecutor>,
+    positions: Arc<PositionBook>,
+    ledger: Arc<dyn Ledger>,
+    notifier: Arc<dyn Notifier>,
+    /// Two-stage buy notifications (ENRICH_FOLLOWUP); None sends buys through `notifier` only
+    buy_followup: Option<Arc<BuyFollowUp>>,
+    filters: Vec<Box<dyn BuyFilter>>,
+    hooks: HookRegistry,
+    /// Receives every fully closed position
+    archive: Option<PositionArchive>,
+    /// Round trips for the daily PnL digest
+    pnl: Option<PnlTracker>,
+    /// Write-ahead record of sends, resolved by `startup_recovery` after a crash
+    intents: Option<Arc<IntentLog>>,
+    updates: Arc<PositionUpdates>,
+    /// Chain reads for catching up after a stream gap; None skips the catch-up
+    gap_reader: Option<Arc<dyn GapReader>>,
+    gap_config: GapCatchUpConfig,
+    own_wallet: Option<Pubkey>,
+    /// Confirmed costs of live fills; None keeps the executor's send-time estimates
+    fill_costs: Option<Arc<dyn FillCostSource>>,
+    /// On-chain outcome of live buys, checked before their fill is confirmed; None takes any landed buy as filled
+    confirmations: Option<&'static ConfirmationTracker>,
+    /// Live buys that landed with an error or expired, aborted at the mint's next event
+    failed_buys: Arc<DashMap<String, String>>,
+    swap_config: SwapConfig,
+    /// Shared with the config watcher, which swaps in reloaded params
+    runtime_params: Arc<RwLock<RuntimeParams>>,
+    exposure_limits: CreatorExposureLimits,
+    portfolio_limits: PortfolioLimits,
+    /// BUY_SIZE_TIERS; without them every buy starts from `swap_config.amount_in`
+    size_tiers: Option<BuySizeTiers>,
+    /// AMOUNT_JITTER_PCT applied to every buy size
+    jitter: Arc<Jitter>,
+    /// MAX_BUY_SOL (default: none) a jittered buy is clamped back under
+    max_buy_sol: Option<f64>,
+    /// Balance less MIN_SOL_RESERVE also bounds a jittered buy; None leaves only MAX_BUY_SOL
+    wallet_balance: Option<Arc<dyn WalletBalance>>,
+    /// PENDING_TIMEOUT_SECS (default: 60) a live buy may stay unconfirmed before its position is aborted
+    pending_timeout_secs: u64,
+    /// Profit-tier exits that fired while another sell of the mint was in flight, sent once it settles
+    queued_exits: DashMap<String, ExitDecision>,
+    logger: Logger,
+}
+
+/// Log a buy confirmation; a restored position is announced since it was reported aborted
+fn report_buy_confirmation(confirmation: BuyConfirmation, mint: &str, via: &str, logger: &Logger, notifier: &dyn Notifier) {
+    match confirmation {
+        BuyConfirmation::Opened => logger.critical(format!("{} pending → open (confirmed by {})", mint, via)),
+        BuyConfirmation::Restored => {
+            let message = format!("♻️ Buy of {} confirmed by {} after its position was aborted; tracking it again", mint, via);
+            logger.critical(message.clone());
+            notifier.notify(message);
+        }
+        BuyConfirmation::Unchanged | BuyConfirmation::Unknown => {}
+    }
+}
+
+/// Measure `entry` against `basis` from its own amounts, stamp the result in and count it for the session
+/// Pause `mint` after a self-cancelled IOC buy, which cost only its fee; whether `class` was one
+fn start_ioc_cooldown(mint: &str, class: ErrorClass, logger: &Logger) -> bool {
+    let ioc = &*ioc::IOC_CONFIG;
+    if !ioc.enabled || !matches!(class, ErrorClass::IocCancelled | ErrorClass::SlippageExceeded) {
+        return false;
+    }
+    ioc::start_cooldown(mint, ioc);
+    logger.critical(format!("IOC buy for {} cancelled ({}); retrying after {}s", mint, class, ioc.cooldown_secs));
+    true
+}
+
+fn apply_slippage(entry: &mut LedgerEntry, basis: &SlippageBasis, tolerance_pct: f64, logger: &Logger) -> Option<EffectiveSlippage> {
+    let slippage = basis.measure(entry.sol_amount, entry.token_amount)?;
+    entry.slippage_pct = Some(slippage.pct);
+    entry.slippage_sol = Some(slippage.cost_sol);
+    slippage::check_tolerance(&entry.side, &entry.mint, &entry.signature, &slippage, tolerance_pct, logger);
+    if let Some(sample) = SlippageSample::from_entry(entry) {
+        session_report::record_slippage(sample);
+    }
+    Some(slippage)
+}
+
+impl Orchestrator {
+    pub fn new(
+        executor: Arc<dyn TradeExecutor>,
+        positions: Arc<PositionBook>,
+        ledger: Arc<dyn Ledger>,
+        notifier: Arc<dyn Notifier>,
+        swap_config: SwapConfig,
+        runtime_params: RuntimeParams,
+    ) -> Self {
+        Self {
+            executor,
+            positions,
+            ledger,
+            hooks: HookRegistry::new(notifier.clone()),
+            notifier,
+            buy_followup: None,
+            filters: Vec::new(),
+            archive: None,
+            pnl: None,
+            intents: None,
+            updates: POSITION_UPDATES.clone(),
+            gap_reader: None,
+            gap_config: GapCatchUpConfig::from_env(),
+            own_wallet: None,
+            fill_costs: None,
+            confirmations: None,
+            failed_buys: Arc::new(DashMap::new()),
+            swap_config,
+            runtime_params: Arc::new(RwLock::new(runtime_params)),
+            exposure_limits: CreatorExposureLimits::from_env(),
+            portfolio_limits: PortfolioLimits::from_env(),
+            size_tiers: None,
+            jitter: Arc::new(Jitter::new(JitterConfig::from_env())),
+            max_buy_sol: std::env::var("MAX_BUY_SOL").ok().and_then(|v| v.parse::<f64>().ok()).filter(|v| *v > 0.0),
+            wallet_balance: None,
+            pending_timeout_secs: std::env::var("PENDING_TIMEOUT_SECS")
+                .ok()
+                .and_then(|v| v.parse::<u64>().ok())
+                .unwrap_or(60),
+            queued_exits: DashMap::new(),
+            logger: Logger::new("[ORCHESTRATOR] => ".cyan().bold().to_string()),
+        }
+    }
+
+    /// Handle to the exit params supervision reads, for hot reloads
+    pub fn runtime_params_handle(&self) -> Arc<RwLock<RuntimeParams>> {
+        self.runtime_params.clone()
+    }
+
+    pub fn add_filter(&mut self, filter: Box<dyn BuyFilter>) {
+        self.filters.push(filter);
+    }
+
+    /// Tag fills from `wallet` as own trades; they then never trigger buys or exits
+    pub fn set_own_wallet(&mut self, wallet: Pubkey) {
+        transaction_parser::register_own_wallet(&wallet);
+        self.own_wallet = Some(wallet);
+    }
+
+    pub fn set_gap_reader(&mut self, reader: Arc<dyn GapReader>) {
+        self.gap_reader = Some(reader);
+    }
+
+    pub fn set_gap_config(&mut self, config: GapCatchUpConfig) {
+        self.gap_config = config;
+    }
+
+    pub fn set_exposure_limits(&mut self, limits: CreatorExposureLimits) {
+        self.exposure_limits = limits;
+    }
+
+    pub fn set_size_tiers(&mut self, tiers: BuySizeTiers) {
+        self.size_tiers = Some(tiers);
+    }
+
+    /// A seeded `Jitter` makes buy sizes reproducible
+    pub fn set_jitter(&mut self, jitter: Arc<Jitter>) {
+        self.jitter = jitter;
+    }
+
+    pub fn set_max_buy_sol(&mut self, max_sol: Option<f64>) {
+        self.max_buy_sol = max_sol;
+    }
+
+    pub fn set_wallet_balance(&mut self, balance: Arc<dyn WalletBalance>) {
+        self.wallet_balance = Some(balance);
+    }
+
+    pub fn set_pending_timeout_secs(&mut self, secs: u64) {
+        self.pending_timeout_secs = secs;
+    }
+
+    pub fn set_position_updates(&mut self, updates: Arc<PositionUpdates>) {
+        self.updates = updates;
+    }
+
+    async fn publish_update(&self, kind: PositionUpdateKind, position: &Position) {
+        if let Some(update) = self.updates.publish(kind, position) {
+            self.hooks.position_update(&update).await;
+        }
+    }
+
+    pub fn set_intent_log(&mut self, intents: Arc<IntentLog>) {
+        self.intents = Some(intents);
+    }
+
+    /// Log a send before it happens; Err means the intent couldn't be made durable and the trade must not go out
+    fn begin_intent(&self, side: &str, mint: &str, coin_creator: Option<String>, sol_amount: f64, token_amount: f64, price: f64) -> Result<Option<String>, ()> {
+        let Some(intents) = &self.intents else {
+            return Ok(None);
+        };
+        let id = uuid::Uuid::new_v4().to_string();
+        let intent = Intent {
+            id: id.clone(),
+            mint: mint.to_string(),
+            side: side.to_string(),
+            sol_amount,
+            token_amount,
+            price,
+            coin_creator,
+            created_at: clock::now_secs(),
+            session_id: session_report::session_id().to_string(),
+        };
+        match intents.begin(intent) {
+            Ok(()) => Ok(Some(id)),
+            Err(e) => {
+                self.logger.error(format!("Not sending {} for {}: intent log {} unwritable: {}", side, mint, intents.path(), e));
+                Err(())
+            }
+        }
+    }
+
+    fn resolve_intent(&self, id: Option<String>, outcome: &str) {
+        if let (Some(intents), Some(id)) = (&self.intents, id) {
+            if let Err(e) = intents.resolve(&id, outcome) {
+                self.logger.error(format!("Failed to resolve intent {}: {}", id, e));
+            }
+        }
+    }
+
+    pub fn set_fill_cost_source(&mut self, source: Arc<dyn FillCostSource>) {
+        self.fill_costs = Some(source);
+    }
+
+    pub fn set_confirmation_tracker(&mut self, tracker: &'static ConfirmationTracker) {
+        self.confirmations = Some(tracker);
+    }
+
+    pub fn set_buy_followup(&mut self, followup: Arc<BuyFollowUp>) {
+        self.buy_followup = Some(followup);
+    }
+
+    /// BUY_SLIPPAGE in percent, the tolerance effective slippage is flagged against
+    fn slippage_tolerance_pct(&self) -> f64 {
+        self.swap_config.buy_slippage as f64 / 100.0
+    }
+
+    /// Write a fill to the ledger with its costs and its effective slippage against `basis`
+    /// Live fills wait for their transaction in the background, then the ledger line and the lot or exit get the
+    /// confirmed costs (estimates if it never confirms). A position closed meanwhile is archived with estimates.
+    /// The ledger line also gets the landed amounts, and the slippage measured from them is notified on its own;
+    /// a paper fill's slippage is returned instead, for its trade notification.
+    fn record_fill(&self, entry: LedgerEntry, basis: Option<SlippageBasis>) -> Option<EffectiveSlippage> {
+        let tolerance_pct = self.slippage_tolerance_pct();
+        let source = match &self.fill_costs {
+            Some(source) if !entry.simulated && !entry.signature.is_empty() => source.clone(),
+            _ => {
+                let mut entry = entry;
+                let slippage = match basis {
+                    Some(basis) if entry.simulated => apply_slippage(&mut entry, &basis, tolerance_pct, &self.logger),
+                    _ => None,
+                };
+                session_report::record_fill(&entry);
+                self.ledger.record(entry);
+                return slippage;
+            }
+        };
+        let ledger = self.ledger.clone();
+        let positions = self.positions.clone();
+        let logger = self.logger.clone();
+        let notifier = self.notifier.clone();
+        let confirmations = self.confirmations.filter(|_| entry.side == "buy");
+        let failed_buys = self.failed_buys.clone();
+        tokio::spawn(async move {
+            let mut entry = entry;
+            // A buy that landed with an error still shows up in getTransaction, so its outcome is checked first
+            if let Some(tracker) = confirmations {
+                if let Ok(signature) = Signature::from_str(&entry.signature) {
+                    match tracker.await_confirmation(&signature, CommitmentLevel::Confirmed, confirmation_tracker::confirm_timeout()).await {
+                        Ok(ConfirmationOutcome::Failed(err)) => {
+                            let class = classify_error(&err);
+                            session_report::record_error(class);
+                            if !start_ioc_cooldown(&entry.mint, class, &logger) {
+                                logger.error(format!("Buy {} of {} failed on-chain: {}", entry.signature, entry.mint, err));
+                            }
+                            failed_buys.insert(entry.mint.clone(), format!("failed on-chain: {}", err));
+                            return;
+                        }
+                        Ok(ConfirmationOutcome::Expired) => {
+                            failed_buys.insert(entry.mint.clone(), "expired unconfirmed".to_string());
+                            return;
+                        }
+                        Ok(_) => {}
+                        Err(e) => logger.error(format!("Could not track buy {}: {}", entry.signature, e)),
+                    }
+                }
+            }
+            match source.confirmed_fill(&entry.signature, &entry.mint, &entry.costs).await {
+                Ok(FillConfirmation { costs, fill }) => {
+                    entry.costs = costs;
+                    if entry.side == "buy" {
+                        let confirmation = positions.confirm_buy(&entry.mint, &entry.signature);
+                        report_buy_confirmation(confirmation, &entry.mint, "RPC", &logger, notifier.as_ref());
+                    }
+                    positions.set_fill_costs(&entry.mint, &entry.signature, costs.total());
+                    if let Some(fill) = fill {
+                        // A fill with no tokens can't be priced; the estimate is kept rather than a NaN/inf price
+                        match fill.price() {
+                            Some(price) => {
+                                entry.sol_amount = fill.sol_amount;
+                                entry.token_amount = fill.token_amount;
+                                entry.price = price;
+                                let slippage = basis.and_then(|basis| apply_slippage(&mut entry, &basis, tolerance_pct, &logger));
+                                if let Some(slippage) = slippage {
+                                    notifier.notify(format!(
+                                        "🧾 {} of {} landed\n{}",
+                                        entry.side, entry.mint, slippage.format_line(tolerance_pct)
+                                    ));
+                                }
+                            }
+                            None => logger.error(format!(
+                                "Ignoring landed {} {} of {}: {} tokens for {} SOL; keeping the estimate",
+                                entry.side, entry.signature, entry.mint, fill.token_amount, fill.sol_amount
+                            )),
+                        }
+                    }
+                }
+                Err(e) => logger.error(format!("Keeping estimated costs for {} {}: {}", entry.side, entry.signature, e)),
+            }
+            session_report::record_fill(&entry);
+            ledger.record(entry);
+        });
+        None
+    }
+
+    pub fn set_archive(&mut self, archive: PositionArchive) {
+        self.archive = Some(archive);
+    }
+
+    pub fn set_pnl_tracker(&mut self, pnl: PnlTracker) {
+        self.pnl = Some(pnl);
+    }
+
+    /// Hooks run in registration order at every invocation point
+    pub fn add_hook(&mut self, hook: Arc<dyn StrategyHook>) {
+        self.hooks.register(hook);
+    }
+
+    pub fn positions(&self) -> Arc<PositionBook> {
+        self.positions.clone()
+    }
+
+    /// Handle events until the source ends or shutdown is requested; an event being handled is finished first
+    pub async fn run(&self, source: &mut dyn EventSource) {
+        let shutdown = shutdown::token();
+        let mut pending_sweep = tokio::time::interval(PENDING_SWEEP_INTERVAL);
+        pending_sweep.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+        loop {
+            let event = tokio::select! {
+                biased;
+                _ = shutdown.cancelled() => {
+                    self.logger.critical("Shutdown requested, no new events taken".yellow().to_string());
+                    return;
+                }
+                _ = pending_sweep.tick() => {
+                    self.sweep_pending().await;
+                    continue;
+                }
+                event = source.next_event() => event,
+            };
+            let Some(event) = event else {
+                break;
+            };
+            if let Some(gap) = source.take_gap() {
+                self.catch_up(&gap).await;
+            }
+            for change in source.take_balance_changes() {
+                self.reconcile_balance(&change).await;
+            }
+            self.handle_event(event).await;
+        }
+        self.logger.critical("Event source exhausted".yellow().to_string());
+    }
+
+    /// Make up for a stream gap before the first event after it is handled
+    /// Held mints are re-priced from chain state and supervised first, so a rug inside the gap exits now rather than
+    /// on the next trade; then our wallet's transactions in the gap are checked, then buys are optionally replayed.
+    pub async fn catch_up(&self, gap: &StreamGap) {
+        self.logger.critical(format!(
+            "Stream gap: slots {}..{} ({}s disconnected)",
+            gap.from_slot, gap.to_slot, gap.duration_secs()
+        ).yellow().to_string());
+        let Some(reader) = self.gap_reader.clone() else {
+            return;
+        };
+
+        for position in self.positions.snapshot() {
+            match reader.pool_state(&position.mint, position.dex_type).await {
+                Ok(state) => {
+                    if let Some(event) = gap_catchup::catch_up_event(&position, &state, gap) {
+                        self.logger.critical(format!(
+                            "Catch-up {}: {:.10} -> {:.10} SOL",
+                            position.mint, position.last_price, state.price
+                        ));
+                        self.supervise(&event).await;
+                    }
+                }
+                Err(e) => self.logger.error(format!("Catch-up state for {} unavailable: {}", position.mint, e)),
+            }
+        }
+
+        self.check_own_transactions(reader.as_ref(), gap).await;
+        if self.gap_config.replay_buys {
+            self.replay_gap_buys(reader.as_ref(), gap).await;
+        }
+    }
+
+    /// Report our wallet's transactions that landed during the gap without a matching fill on record
+    async fn check_own_transactions(&self, reader: &dyn GapReader, gap: &StreamGap) {
+        let Some(wallet) = self.own_wallet else {
+            return;
+        };
+        let signatures = match reader.signatures_in_gap(&wallet.to_string(), gap, self.gap_config.max_signatures).await {
+            Ok(signatures) => signatures,
+            Err(e) => {
+                self.logger.error(format!("Own transactions in the gap unavailable: {}", e));
+                return;
+            }
+        };
+        let known: std::collections::HashSet<String> = self.positions
+            .snapshot()
+            .iter()
+            .flat_map(|position| {
+                position.lots.iter().map(|lot| lot.signature.clone())
+                    .chain(position.exits.iter().map(|exit| exit.signature.clone()))
+                    .collect::<Vec<_>>()
+            })
+            .collect();
+        for seen in signatures.into_iter().filter(|seen| !known.contains(&seen.signature)) {
+            let trades = reader.replay(&seen.signature).await.unwrap_or_default();
+            let summary = if trades.is_empty() {
+                "no PumpFun/PumpSwap trade".to_string()
+            } else {
+                trades.iter()
+                    .map(|trade| format!("{} {}", if trade.is_buy { "buy" } else { "sell" }, trade.mint))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            };
+            let message = format!("⚠️ Own transaction landed unseen during a stream gap: {} at slot {} ({})", seen.signature, seen.slot, summary);
+            println!("{}", message);
+            self.notifier.notify(message);
+        }
+    }
+
+    /// Run the monitored programs' gap transactions through the buy path, oldest first
+    /// Held mints were already re-priced from current state, so their historical trades are not replayed.
+    async fn replay_gap_buys(&self, reader: &dyn GapReader, gap: &StreamGap) {
+        let mut signatures = Vec::new();
+        for program in [PUMP_FUN_PROGRAM, PUMP_SWAP_PROGRAM, RAYDIUM_AMM_PROGRAM] {
+            match reader.signatures_in_gap(program, gap, self.gap_config.max_signatures).await {
+                Ok(found) => signatures.extend(found),
+                Err(e) => self.logger.error(format!("Gap replay: {}", e)),
+            }
+        }
+        signatures.sort_by_key(|seen| seen.slot);
+        signatures.dedup_by(|a, b| a.signature == b.signature);
+        self.logger.critical(format!("Gap replay: {} transactions", signatures.len()));
+
+        for seen in signatures {
+            let trades = match reader.replay(&seen.signature).await {
+                Ok(trades) => trades,
+                Err(e) => {
+                    self.logger.error(format!("Gap replay of {} failed: {}", seen.signature, e));
+                    continue;
+                }
+            };
+            for mut trade in trades {
+                trade.gap_replayed = true;
+                let Some(event) = TradeEvent::from_trade_info(trade) else {
+                    continue;
+                };
+                if event.core.is_own_trade || self.positions.contains(event.mint()) {
+                    continue;
+                }
+                self.handle_event(event).await;
+            }
+        }
+    }
+
+    /// Route one event: supervise held mints, otherwise consider buying
+    pub async fn handle_event(&self, event: TradeEvent) {
+        session_report::record_event(event.core.dex_type);
+        self.hooks.trade_event(&event).await;
+        if event.core.is_own_trade {
+            // Our buy's post price is a real market level; our sell is not a move to react to
+            if event.core.is_buy {
+                // Seeing our own fill on the stream confirms the buy
+                let confirmation = self.positions.confirm_buy(event.mint(), &event.extras.signature);
+                report_buy_confirmation(confirmation, event.mint(), "the stream", &self.logger, self.notifier.as_ref());
+                self.positions.observe_price(event.mint(), event.core.post_current_price, true);
+                if confirmation == BuyConfirmation::Restored {
+                    if let Some(position) = self.positions.get(event.mint()) {
+                        self.hooks.position_opened(&position).await;
+                        self.publish_update(PositionUpdateKind::LotAdded, &position).await;
+                    }
+                }
+            }
+            self.logger.debug(format!("Own {} on {} not evaluated", if event.core.is_buy { "buy" } else { "sell" }, event.mint()));
+        } else if let Some(held) = self.positions.dex_type(event.mint()) {
+            if let Some(signal) = migration::migration_signal(held, &event) {
+                self.record_migration(event.mint(), signal);
+            }
+            self.supervise(&event).await;
+        } else {
+            self.try_buy(&event).await;
+        }
+    }
+
+    /// Move a held PumpFun position to PumpSwap once its curve completed, so its sells stop going to PumpFun
+    /// The first signal notifies; the pool id is filled in from the first trade seen on the pool.
+    fn record_migration(&self, mint: &str, signal: MigrationSignal) {
+        let pool_id = match signal {
+            MigrationSignal::CurveComplete => None,
+            MigrationSignal::PoolTrade { pool_id } => Some(pool_id),
+        };
+        let first = migration::mark_migrated(mint, pool_id.clone());
+        self.positions.set_dex_type(mint, DexType::PumpSwap);
+        let pool = pool_id.map(|pool_id| format!(" (pool {})", pool_id)).unwrap_or_default();
+        if first {
+            self.logger.critical(format!("🎓 {} completed its bonding curve, now trading on PumpSwap{}", mint, pool).green().to_string());
+            self.notifier.notify(format!("🎓 Token {} migrated to PumpSwap{}", mint, pool));
+        } else if !pool.is_empty() {
+            self.logger.critical(format!("{} PumpSwap pool seen{}", mint, pool));
+        }
+    }
+
+    /// Count a refused buy and tell the hooks
+    /// Only candidates past the trigger check are kept as recent rejections: "not a buy" would crowd out the rest.
+    async fn reject(&self, event: &TradeEvent, filter: &str, reason: String) {
+        session_report::record_rejection(filter);
+        if filter != QUALIFYING_BUY_FILTER {
+            session_report::record_rejected_buy(&event.extras.mint, filter, &reason);
+        }
+        let decision = BuyDecision::Rejected { filter: filter.to_string(), reason };
+        self.hooks.buy_decision(event, &decision).await;
+    }
+
+    async fn try_buy(&self, event: &TradeEvent) {
+        if event.core.timestamp_suspect {
+            let reason = format!("implausible event timestamp {} (age {}s)", event.core.timestamp, clock::event_age_secs(event.core.timestamp));
+            self.logger.debug(format!("Skip {} - {}", event.mint(), reason));
+            self.reject(event, "timestamp", reason).await;
+            return;
+        }
+        if event.core.gap_replayed {
+            let age = clock::event_age_secs(event.core.timestamp);
+            if age > self.gap_config.max_replay_age_secs as i64 {
+                let reason = format!("gap-replayed event is {}s old (max {}s)", age, self.gap_config.max_replay_age_secs);
+                self.logger.debug(format!("Skip {} - {}", event.mint(), reason));
+                self.reject(event, "gap-replay", reason).await;
+                return;
+            }
+        }
+        for filter in &self.filters {
+            if let Err(reason) = filter.check(event).await {
+                self.logger.debug(format!("Skip {} - {}: {}", event.mint(), filter.name(), reason));
+                self.reject(event, filter.name(), reason).await;
+                return;
+            }
+        }
+
+        // This buy's own copy; the tier size never touches the shared config
+        let mut buy_config = self.swap_config.clone();
+        let size_tier = match &self.size_tiers {
+            Some(tiers) => match tiers.tier_for(event.core.liquidity) {
+                Some(tier) => {
+                    buy_config.amount_in = tier.amount_sol;
+                    Some(tier)
+                }
+                None => {
+                    let reason = format!("liquidity {:.4} SOL below the lowest BUY_SIZE_TIERS tier ({} SOL)", event.core.liquidity, tiers.lowest().min_liquidity_sol);
+                    self.logger.debug(format!("Skip {} - buy-size: {}", event.mint(), reason));
+                    self.reject(event, "buy-size", reason).await;
+                    return;
+                }
+            },
+            None => None,
+        };
+        // Jitter first, so the exposure caps below judge (and may downsize) the size actually sent
+        let ceiling = self.jitter_ceiling().await;
+        if ceiling < self.exposure_limits.min_buy_sol {
+            let reason = format!("only {:.6} SOL spendable, below MIN_DOWNSIZED_BUY_SOL={}", ceiling.max(0.0), self.exposure_limits.min_buy_sol);
+            self.logger.error(format!("Skip {} - balance: {}", event.mint(), reason));
+            self.reject(event, "balance", reason).await;
+            return;
+        }
+        let sizing = self.jitter.buy_amount(buy_config.amount_in, self.exposure_limits.min_buy_sol, ceiling);
+        buy_config.amount_in = sizing.jittered;
+        if self.exposure_limits.is_enabled() {
+            match risk::check_creator_exposure(&self.positions.snapshot(), event.coin_creator(), buy_config.amount_in, &self.exposure_limits) {
+                ExposureDecision::Allow => {}
+                ExposureDecision::Downsize { sol, reason } => {
+                    self.logger.critical(format!("Downsizing {} to {:.6} SOL - {}", event.mint(), sol, reason));
+                    buy_config.amount_in = sol;
+                }
+                ExposureDecision::Reject(reason) => {
+                    self.logger.debug(format!("Skip {} - creator-exposure: {}", event.mint(), reason));
+                    self.reject(event, "creator-exposure", reason).await;
+                    return;
+                }
+            }
+        }
+        // Held to the end of the buy: released on failure, and once the position is in the book otherwise
+        let _reservation = if self.portfolio_limits.is_enabled() {
+            match risk::BUY_RESERVATIONS.try_reserve(|| self.positions.snapshot(), buy_config.amount_in, &self.portfolio_limits) {
+                Ok(reservation) => Some(reservation),
+                Err(reason) => {
+                    self.logger.error(format!("Skip {} - position-limit: {}", event.mint(), reason));
+                    if risk::BUY_RESERVATIONS.mark_refused() {
+                        self.notifier.notify(format!("🛑 Buys paused by the position limits: {}", reason));
+                    }
+                    self.reject(event, "position-limit", reason).await;
+                    return;
+                }
+            }
+        } else {
+            None
+        };
+        self.hooks.buy_decision(event, &BuyDecision::Approved).await;
+
+        // Only events that reach execution pay for the full view
+        let trade_info = &event.to_trade_info();
+        let Ok(intent) = self.begin_intent("buy", &trade_info.mint, trade_info.coin_creator.clone(), buy_config.amount_in, 0.0, trade_info.post_current_price) else {
+            return;
+        };
+        let result = self.executor.buy(trade_info, &buy_config).await;
+        self.resolve_intent(intent, &match &result {
+            Ok(_) => "filled".to_string(),
+            Err(e) => format!("failed: {}", e),
+        });
+        let report = match result {
+            Ok(report) => report,
+            Err(e) => {
+                let class = classify_anyhow(&e);
+                session_report::record_error(class);
+                if !start_ioc_cooldown(&trade_info.mint, class, &self.logger) {
+                    self.logger.error(format!("Buy failed for {}: {}", trade_info.mint, e));
+                }
+                return;
+            }
+        };
+        session_report::record_buy(&report);
+        let jittered = self.jitter.config().amount_pct > 0.0;
+        let buy_reason = if event.core.gap_replayed { "Qualifying buy (gap-replayed)" } else { "Qualifying buy" };
+
+        let lot = Lot {
+            signature: report.signature.clone(),
+            token_amount: report.token_amount,
+            sol_spent: report.sol_amount,
+            price: report.price,
+            timestamp: trade_info.timestamp,
+            costs_sol: report.costs.total(),
+            size_tier,
+        };
+        // Paper fills are final; a live buy is Pending until the stream or RPC confirms it
+        if report.simulated {
+            self.positions.record_buy(&trade_info.mint, trade_info.dex_type, trade_info.coin_creator.clone(), lot);
+        } else {
+            self.positions.record_pending_buy(&trade_info.mint, trade_info.dex_type, trade_info.coin_creator.clone(), lot);
+        }
+
+        let basis = SlippageBasis::Buy { expected_price: trade_info.post_current_price };
+        let slippage = self.record_fill(LedgerEntry {
+            timestamp: trade_info.timestamp,
+            mint: trade_info.mint.clone(),
+            side: "buy".to_string(),
+            signature: report.signature.clone(),
+            token_amount: report.token_amount,
+            sol_amount: report.sol_amount,
+            price: report.price,
+            reason: buy_reason.to_string(),
+            simulated: report.simulated,
+            blockhash_mode: report.blockhash_mode.map(|mode| mode.to_string()),
+            session_id: session_report::session_id().to_string(),
+            costs: report.costs,
+            imported: false,
+            pre_jitter_sol: jittered.then_some(sizing.base),
+            post_jitter_sol: jittered.then_some(sizing.jittered),
+            slippage_pct: None,
+            slippage_sol: None,
+        }, Some(basis));
+
+        let mut buy_message = telegram::format_buy_message(
+            &trade_info.mint,
+            report.sol_amount,
+            report.price,
+            buy_reason,
+            &report.signature,
+            &format!("{:?}", trade_info.dex_type),
+            report.token_amount,
+            None,
+        );
+        if let Some(tier) = size_tier {
+            buy_message.push_str(&format!("\n📏 Size tier: {}", tier));
+        }
+        if let Some(slippage) = slippage {
+            buy_message.push_str(&format!("\n{}", slippage.format_line(self.slippage_tolerance_pct())));
+        }
+        match &self.buy_followup {
+            Some(followup) => followup.spawn(&trade_info.mint, trade_info.dex_type, buy_message),
+            None => self.notifier.notify(buy_message),
+        }
+
+        position_overrides::attach(&self.positions, &trade_info.mint);
+        if let Some(position) = self.positions.get(&trade_info.mint) {
+            self.hooks.position_opened(&position).await;
+            self.publish_update(PositionUpdateKind::LotAdded, &position).await;
+        }
+    }
+
+    /// Queued tier exit of `mint`, re-capped to what is still held; None once that tier has fired
+    fn take_queued_exit(&self, mint: &str, state: &ExitState) -> Option<ExitDecision> {
+        let (_, mut decision) = self.queued_exits.remove(mint)?;
+        if let ExitReason::ProfitTier(index) = decision.reason {
+            if state.tiers_hit.contains(&index) {
+                return None;
+            }
+        }
+        decision.token_amount = decision.token_amount.min(state.remaining_tokens);
+        (decision.token_amount > 0.0).then_some(decision)
+    }
+
+    async fn supervise(&self, event: &TradeEvent) {
+        let price = event.core.post_current_price;
+        let min_liquidity = self.runtime_params.read().unwrap_or_else(|e| e.into_inner()).trailing_stop_min_liquidity;
+        let Some(position) = self.positions.observe_price(event.mint(), price, event.core.liquidity >= min_liquidity) else {
+            return;
+        };
+
+        self.hooks.tick_for_held(event, &position).await;
+        self.publish_update(PositionUpdateKind::PriceTick, &position).await;
+
+        match position.state {
+            PositionState::Open => {}
+            PositionState::Pending => {
+                self.resolve_pending(&position).await;
+                return;
+            }
+            // A full exit is already in flight
+            PositionState::Closing | PositionState::Closed => return,
+        }
+
+        let state = ExitState::from_position(&position);
+        let decision = if self.hooks.actions().take_sell_request(event.mint()) {
+            Some(ExitDecision { reason: ExitReason::Manual, token_amount: state.remaining_tokens })
+        } else {
+            let base = self.runtime_params.read().unwrap_or_else(|e| e.into_inner()).clone();
+            let params = position_overrides::effective_params(&base, &position.param_overrides)
+                .unwrap_or_else(|e| {
+                    self.logger.error(format!("Ignoring overrides for {}: {}", event.mint(), e));
+                    base.clone()
+                });
+            // A stop closes the whole position and supersedes a queued tier; otherwise the queued tier goes first
+            match evaluate_exit(&state, price, &params) {
+                Some(stop) if !matches!(stop.reason, ExitReason::ProfitTier(_)) => {
+                    self.queued_exits.remove(event.mint());
+                    Some(stop)
+                }
+                evaluated => self.take_queued_exit(event.mint(), &state).or(evaluated),
+            }
+        };
+        let Some(decision) = decision else {
+            return;
+        };
+        if matches!(decision.reason, ExitReason::ProfitTier(_)) && PROGRESS_ON_SELLING.contains_key(event.mint()) {
+            // Dropping the rung would lose it if price falls back below it before the other sell settles
+            if self.queued_exits.insert(event.mint().to_string(), decision.clone()).is_none() {
+                self.logger.critical(format!("{} of {} queued behind a sell in flight", decision.reason, event.mint()));
+            }
+            return;
+        }
+        self.hooks.sell_decision(event, &position, &decision).await;
+
+        let trade_info = &event.to_trade_info();
+        let reason = decision.reason.to_string();
+        let Ok(intent) = self.begin_intent("sell", &trade_info.mint, position.coin_creator.clone(), 0.0, decision.token_amount, trade_info.post_current_price) else {
+            return;
+        };
+        let full_exit = decision.token_amount >= state.remaining_tokens;
+        if full_exit {
+            self.transition(&trade_info.mint, PositionState::Closing);
+        }
+        let result = self.executor.sell(trade_info, &self.swap_config, decision.token_amount, &reason).await;
+        if result.is_err() && full_exit {
+            self.transition(&trade_info.mint, PositionState::Open);
+        }
+        self.resolve_intent(intent, &match &result {
+            Ok(_) => "filled".to_string(),
+            Err(e) => format!("failed: {}", e),
+        });
+        let report = match result {
+            Ok(report) => report,
+            Err(e) => {
+                self.logger.error(format!("{} sell failed for {}: {}", reason, trade_info.mint, e));
+                let class = classify_anyhow(&e);
+                session_report::record_error(class);
+                if class == ErrorClass::AccountFrozen {
+                    freeze_guard::mark_frozen(&trade_info.mint, position.coin_creator.as_deref(), "sell error");
+                }
+                return;
+            }
+        };
+        session_report::record_sell(&report);
+        // Live and paper alike; the mint then waits out REENTRY_COOLDOWN_SECS before another buy
+        reentry::record_sell(&trade_info.mint);
+        // The reason must belong to this transaction; one left over from an earlier attempt is not reported
+        if let Some(recorded) = sell_reasons::take_for_signature(&trade_info.mint, &report.signature) {
+            if recorded != reason {
+                self.logger.error(format!("Sell reason of {} changed from {} to {} mid-attempt", trade_info.mint, reason, recorded));
+            }
+        }
+
+        if let ExitReason::ProfitTier(index) = decision.reason {
+            self.positions.mark_tier_hit(&trade_info.mint, index);
+        }
+
+        let updated = self.positions.record_exit(&trade_info.mint, ExitFill {
+            signature: report.signature.clone(),
+            token_amount: report.token_amount,
+            sol_received: report.sol_amount,
+            price: report.price,
+            reason: reason.clone(),
+            timestamp: trade_info.timestamp,
+            costs_sol: report.costs.total(),
+        });
+
+        let basis = SlippageBasis::Sell { expected_sol: report.sol_amount, token_amount: report.token_amount };
+        let slippage = self.record_fill(LedgerEntry {
+            timestamp: trade_info.timestamp,
+            mint: trade_info.mint.clone(),
+            side: "sell".to_string(),
+            signature: report.signature.clone(),
+            token_amount: report.token_amount,
+            sol_amount: report.sol_amount,
+            price: report.price,
+            reason: reason.clone(),
+            simulated: report.simulated,
+            blockhash_mode: report.blockhash_mode.map(|mode| mode.to_string()),
+            session_id: session_report::session_id().to_string(),
+            costs: report.costs,
+            imported: false,
+            pre_jitter_sol: None,
+            post_jitter_sol: None,
+            slippage_pct: None,
+            slippage_sol: None,
+        }, Some(basis));
+
+        let mut sell_message = telegram::format_sell_message(
+            &trade_info.mint,
+            report.sol_amount,
+            report.price,
+            &reason,
+            &report.signature,
+            &format!("{:?}", trade_info.dex_type),
+            None,
+        );
+        if let Some(slippage) = slippage {
+            sell_message.push_str(&format!("\n{}", slippage.format_line(self.slippage_tolerance_pct())));
+        }
+        self.notifier.notify(sell_message);
+
+        let Some(updated) = updated else {
+            return;
+        };
+        if !updated.is_closed() {
+            self.publish_update(PositionUpdateKind::PartialExit, &updated).await;
+        } else {
+            self.close_position(updated).await;
+        }
+    }
+
+    /// Take a fully exited position out of the book and archive, record and publish it
+    async fn close_position(&self, mut closed: Position) {
+        let mint = closed.mint.clone();
+        self.transition(&mint, PositionState::Closed);
+        closed.state = PositionState::Closed;
+        self.positions.remove(&mint);
+        if let Err(e) = OVERRIDE_STORE.clear(&mint, "position closed") {
+            self.logger.error(format!("Failed to clear overrides for {}: {}", mint, e));
+        }
+        self.logger.critical(format!("Position closed: {}", mint).green().to_string());
+        migration::forget(&mint);
+        session_report::record_close(closed.gross_pnl_sol(), closed.net_pnl_sol());
+        if let Some(archive) = &self.archive {
+            archive.archive(&closed);
+        }
+        if let Some(pnl) = &self.pnl {
+            pnl.record(&closed);
+        }
+        self.hooks.position_closed(&closed).await;
+        self.publish_update(PositionUpdateKind::Closed, &closed).await;
+    }
+
+    /// Follow a change of our wallet's balance made outside the bot: a manual sell, a transfer, an airdrop
+    /// A balance that reached zero closes the position as if the bot had sold it.
+    async fn reconcile_balance(&self, change: &BalanceChange) {
+        match wallet_sync::reconcile(&self.positions, change) {
+            Reconciliation::Unchanged => {}
+            Reconciliation::Adjusted { from, position } => {
+                let message = format!(
+                    "⚠️ {} balance changed outside the bot: {:.6} → {:.6} tokens ({})",
+                    change.mint, from, position.remaining_tokens(), change.signature
+                );
+                self.logger.critical(message.yellow().to_string());
+                self.notifier.notify(message);
+                let kind = if position.remaining_tokens() < from { PositionUpdateKind::PartialExit } else { PositionUpdateKind::LotAdded };
+                self.publish_update(kind, &position).await;
+            }
+            Reconciliation::Closed { from, position } => {
+                self.logger.critical(format!("{} left the wallet outside the bot ({:.6} tokens, {})", change.mint, from, change.signature).yellow().to_string());
+                self.notifier.notify(telegram::format_sell_message(
+                    &change.mint,
+                    change.sol_delta.max(0.0),
+                    position.exits.last().map(|exit| exit.price).unwrap_or(position.last_price),
+                    "external",
+                    &change.signature,
+                    &format!("{:?}", position.dex_type),
+                    None,
+                ));
+                self.close_position(position).await;
+            }
+        }
+    }
+
+    fn transition(&self, mint: &str, state: PositionState) {
+        if let Some(previous) = self.positions.set_state(mint, state) {
+            if previous != state {
+                self.logger.critical(format!("{} {} → {}", mint, previous, state));
+            }
+        }
+    }
+
+    /// Largest size jitter may push a buy to: MAX_BUY_SOL and the wallet balance less MIN_SOL_RESERVE
+    /// Without amount jitter the size is sent as sized, so nothing is read.
+    async fn jitter_ceiling(&self) -> f64 {
+        if self.jitter.config().amount_pct <= 0.0 {
+            return f64::MAX;
+        }
+        let mut ceiling = self.max_buy_sol.unwrap_or(f64::MAX);
+        if let Some(balance) = &self.wallet_balance {
+            match balance.sol_balance().await {
+                Ok(sol) => ceiling = ceiling.min(sol - risk::min_sol_reserve()),
+                Err(e) => self.logger.error(format!("Jitter bounded by MAX_BUY_SOL only: {}", e)),
+            }
+        }
+        ceiling
+    }
+
+    /// Abort Pending positions whose buy failed or outlived PENDING_TIMEOUT_SECS
+    /// Runs on a timer from `run`, so a mint that stops trading after our buy is still resolved.
+    pub async fn sweep_pending(&self) {
+        for position in self.positions.snapshot().into_iter().filter(|position| position.state == PositionState::Pending) {
+            self.resolve_pending(&position).await;
+        }
+    }
+
+    async fn resolve_pending(&self, position: &Position) {
+        let mint = position.mint.as_str();
+        let waited = clock::now_secs().saturating_sub(position.opened_at);
+        if let Some((_, failure)) = self.failed_buys.remove(mint) {
+            self.abort_pending(mint, format!("❌ Buy of {} {}; position aborted", mint, failure)).await;
+        } else if waited > self.pending_timeout_secs {
+            let message = format!("⌛ Buy of {} unconfirmed after {}s; position aborted (PENDING_TIMEOUT_SECS={})", mint, waited, self.pending_timeout_secs);
+            self.abort_pending(mint, message).await;
+        }
+    }
+
+    /// Give up on a buy that never confirmed; the position is kept aside in case the confirmation comes late
+    async fn abort_pending(&self, mint: &str, message: String) {
+        let Some(mut aborted) = self.positions.abort_pending(mint) else {
+            return;
+        };
+        aborted.state = PositionState::Closed;
+        self.logger.critical(message.clone());
+        self.notifier.notify(message);
+        self.hooks.position_closed(&aborted).await;
+        self.publish_update(PositionUpdateKind::Closed, &aborted).await;
+    }
+}
+
+/// Record the starting SOL + WSOL balance for risk management
+async fn init_original_balance(app_state: &AppState) {
+    let wallet_pubkey = match app_state.wallet.try_pubkey() {
+        Ok(pubkey) => pubkey,
+        Err(e) => {
+            eprintln!("Failed to get wallet pubkey: {}", e);
+            return;
+        }
+    };
+    let original_sol_balance = match app_state.read_rpc.client().get_account(&wallet_pubkey).await {
+        Ok(account) => account.lamports as f64 / 1_000_000_000.0, // Convert lamports to SOL
+        Err(e) => {
+            eprintln!("Failed to get wallet balance: {}", e);
+            0.0
+        }
+    };
+
+    // Get original WSOL balance
+    let wsol_mint = spl_token::native_mint::id();
+    let wsol_ata = spl_associated_token_account::get_associated_token_address(&wallet_pubkey, &wsol_mint);
+    let original_wsol_balance = match app_state.read_rpc.client().get_token_account(&wsol_ata).await {
+        Ok(Some(account)) => account.token_amount.ui_amount.unwrap_or(0.0),
+        Ok(None) => 0.0, // No WSOL account
+        Err(e) => {
+            eprintln!("Failed to get WSOL balance: {}", e);
+            0.0
+        }
+    };
+
+    let total_original_balance = original_sol_balance + original_wsol_balance;
+    crate::engine::sniper::set_original_balance(total_original_balance);
+    println!("💰 Original balance set: {:.6} SOL (SOL: {:.6}, WSOL: {:.6})",
+             total_original_balance, original_sol_balance, original_wsol_balance);
+}
+
+/// Start the background services every run mode depends on
+pub async fn start_services(app_state: &AppState, swap_config: &SwapConfig) -> Result<(), String> {
+    // Initialize blockhash processor
+    let processor = BlockhashProcessor::new(app_state.rpc_client.clone())
+        .await
+        .map_err(|e| format!("Failed to initialize blockhash processor: {}", e))?;
+    processor.start()
+        .await
+        .map_err(|e| format!("Failed to start blockhash processor: {}", e))?;
+    println!("Blockhash processor started successfully");
+
+    // Initialize and log selling strategy parameters
+    let selling_config = crate::engine::selling_strategy::SellingConfig::set_from_env();
+    let selling_engine = Arc::new(crate::engine::selling_strategy::SellingEngine::new(
+        Arc::new(app_state.clone()),
+        Arc::new(swap_config.clone()),
+        selling_config,
+    ));
+    selling_engine.log_selling_parameters();
+
+    // Start automatic periodic cleanup service (every 5 minutes)
+    // This prevents unbounded cache growth during long-running periods
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(tokio::time::Duration::from_secs(300)); // 5 minutes
+        let logger = Logger::new("[PERIODIC-CLEANUP] => ".cyan().bold().to_string());
+
+        loop {
+            interval.tick().await;
+
+            if let Err(e) = cache_maintenance::perform_comprehensive_cleanup().await {
+                // Critical error - keep this log
+                logger.error(format!("Periodic cleanup error: {} (will retry in 5 minutes)", e));
+            }
+        }
+    });
+    println!("✅ Automatic periodic cleanup service started (5 minute interval)");
+
+    // Start memory monitoring service
+    crate::services::memory_monitor::start_memory_monitor().await;
+    println!("✅ Memory monitoring service started (1 minute interval)");
+
+    // Start task monitoring service
+    crate::services::task_monitor::start_task_monitor().await;
+    println!("✅ Task monitoring service started (5 minute interval)");
+
+    // Watch held token accounts for freeze-authority rugs
+    freeze_guard::start_frozen_account_reconciler(Arc::new(app_state.clone())).await;
+
+    // Print summaries for suppressed repeat log lines
+    crate::common::log_dedup::start_summary_flusher();
+
+    // Track send/read RPC pool health so either can fall back to the other
+    crate::services::rpc_pool::start_rpc_health_probe(app_state).await;
+    crate::services::zeroslot_pool::start_zeroslot_health_probe();
+
+    // Hot-reload CREATOR_BLACKLIST_FILE / MINT_WHITELIST_FILE
+    crate::common::blacklist::start_list_reloader();
+
+    // SIMULATE_BEFORE_BUY simulations read through the read pool
+    if crate::core::preflight::is_enabled() {
+        crate::core::preflight::set_preflight_rpc(app_state.read_rpc.clone());
+        println!("✅ Buys are simulated before they are sent (SIMULATE_BEFORE_BUY)");
+    }
+
+    // Zeroslot sends compile to v0 through LOOKUP_TABLE_ADDRESS once it loads; legacy without it
+    match crate::core::lookup_table::load(&app_state.read_rpc.client()).await {
+        Ok(Some(count)) => println!("✅ Address lookup table loaded ({} accounts), sending v0 transactions", count),
+        Ok(None) => {}
+        Err(e) => Logger::new("[LOOKUP-TABLE] => ".yellow().to_string()).error(format!("{}; sending legacy transactions", e)),
+    }
+
+    // Follow recent priority fees when a PRIORITY_FEE_*_MODE is set
+    crate::services::priority_fee::start_priority_fee_sampler(app_state).await;
+
+    // Correct the clock time-based logic uses by the RPC node's block time
+    crate::services::clock_skew::start_clock_skew_monitor(app_state).await;
+
+    // Start durable nonce repair (no-op without NONCE_ACCOUNT) and Telegram commands
+    crate::services::nonce_health::start_nonce_repair(Arc::new(app_state.clone())).await;
+    position_archive::register_commands();
+    crate::services::pnl_tracker::start_daily_report();
+    risk::register_status_section();
+    reentry::register_status_section();
+    mev_tracker::register_status_section();
+    telegram::register_status_section();
+    position_overrides::start_override_sync();
+    crate::services::telegram_commands::start_command_listener().await;
+    crate::services::status_api::start_status_api(app_state, swap_config).await?;
+
+    Ok(())
+}
+
+/// Whether the orchestrator pipeline runs (ENGINE_PIPELINE, default: true); false falls back to the legacy sniper
+/// loop, which has none of the pipeline's filters, risk limits or intent log; DRY_RUN always runs the pipeline
+pub fn is_pipeline_enabled() -> bool {
+    std::env::var("ENGINE_PIPELINE")
+        .ok()
+        .and_then(|v| v.parse::<bool>().ok())
+        .unwrap_or(true)
+}
+
+pub fn print_probe_report(report: &ProbeReport) {
+    println!(
+        "✅ Yellowstone gRPC probe ok: first message after {}ms (server version: {})",
+        report.time_to_first_message.as_millis(),
+        report.server_version.as_deref().unwrap_or("unknown")
+    );
+}
+
+/// Production entry point: start services, then run the selected engine until it stops
+pub async fn run_production(config: SniperConfig) -> Result<(), String> {
+    if chaos::chaos_requested() {
+        return Err("CHAOS_MODE is set; fault injection only runs in the paper resilience suite (--resilience-suite)".to_string());
+    }
+    init_original_balance(&config.app_state).await;
+
+    // Check Telegram configuration
+    println!("\n📱 Telegram Configuration:");
+    telegram::log_config_status();
+    println!();
+
+    // Set before the services start so the status endpoints value positions in USD from the start
+    valuation::set_sol_usd(config.solana_price);
+    start_services(&config.app_state, &config.swap_config).await?;
+    // Pay the cold-connection cost now rather than on the first buy
+    crate::services::warmup::warm_up(&config.app_state).await;
+
+    // Catch a bad token/endpoint here instead of as silently failing subscriptions
+    if grpc_probe::probe_enabled() {
+        let report = grpc_probe::probe(&config.yellowstone_grpc_http, &config.yellowstone_grpc_token)
+            .await
+            .map_err(|e| format!("Yellowstone gRPC probe failed: {}", e))?;
+        print_probe_report(&report);
+    }
+
+    if config.swap_config.dry_run && !is_pipeline_enabled() {
+        // The legacy loop sends its buys directly; only the pipeline's executor can simulate them
+        println!("🧪 DRY_RUN=true runs the pipeline; ENGINE_PIPELINE=false is ignored");
+    } else if !is_pipeline_enabled() {
+        // start_sniper() spawns background tasks that handle their own connections and returns immediately
+        start_sniper(config).await
+            .map_err(|e| format!("Failed to start sniper monitoring: {}", e))?;
+        println!("✅ Sniper monitoring tasks started successfully");
+        shutdown::cancelled().await;
+        return Ok(());
+    }
+
+    // Settle trades a crash left in flight before anything new is sent
+    let intents = Arc::new(IntentLog::from_env());
+    let ledger: Arc<dyn Ledger> = Arc::new(JsonlLedger::from_env());
+    let verifier = RpcIntentVerifier::new(config.app_state.read_rpc.clone(), config.app_state.wallet.pubkey());
+    startup_recovery::recover_pending_intents(&intents, &verifier, &POSITION_BOOK, ledger.as_ref(), RecoveryConfig::from_env()).await;
+
+    let mut orchestrator = Orchestrator::new(
+        Arc::new(LiveExecutor::new(Arc::new(config.app_state.clone()))),
+        POSITION_BOOK.clone(),
+        ledger,
+        Arc::new(TelegramNotifier),
+        config.swap_config.clone(),
+        RuntimeParams::try_from_env().map_err(|e| format!("Invalid PROFIT_LADDER: {}", e))?,
+    );
+    orchestrator.set_own_wallet(config.app_state.wallet.pubkey());
+    orchestrator.set_archive(PositionArchive::from_env());
+    orchestrator.set_pnl_tracker(PnlTracker::from_env());
+    orchestrator.set_intent_log(intents);
+    orchestrator.set_fill_cost_source(Arc::new(RpcFillCostSource::new(config.app_state.read_rpc.clone(), config.app_state.wallet.pubkey())));
+    orchestrator.set_gap_reader(Arc::new(RpcGapReader::new(config.app_state.read_rpc.clone())));
+    orchestrator.set_wallet_balance(Arc::new(RpcWalletBalance::new(config.app_state.read_rpc.clone(), config.app_state.wallet.pubkey())));
+    orchestrator.set_confirmation_tracker(confirmation_tracker::tracker(&config.app_state.read_rpc));
+    if telegram::is_configured() {
+        if let Some(followup) = BuyFollowUp::from_env(Arc::new(BotEditor), Arc::new(RpcEnrichmentSource::new(config.app_state.read_rpc.clone()))) {
+            orchestrator.set_buy_followup(Arc::new(followup));
+        }
+    }
+    let size_tiers = BuySizeTiers::from_env().map_err(|e| format!("Invalid BUY_SIZE_TIERS: {}", e))?;
+    if let Some(tiers) = &size_tiers {
+        println!("✅ Buy size by liquidity tier: {}", tiers.describe());
+        orchestrator.set_size_tiers(tiers.clone());
+    }
+    orchestrator.add_filter(Box::new(QualifyingBuyFilter::from_env()));
+    let liquidity_filter = LiquidityFilter::from_env(config.swap_config.amount_in).with_size_tiers(size_tiers);
+    if liquidity_filter.is_enabled() {
+        orchestrator.add_filter(Box::new(liquidity_filter));
+    }
+    orchestrator.add_filter(Box::new(BlacklistFilter));
+    orchestrator.add_filter(Box::new(RecoveryCooldownFilter));
+    orchestrator.add_filter(Box::new(ReentryFilter));
+    if reentry::REENTRY_CONFIG.never_rebuy {
+        println!("✅ Sold mints are never bought again this session (NEVER_REBUY)");
+    }
+    if ioc::IOC_CONFIG.enabled {
+        orchestrator.add_filter(Box::new(IocCooldownFilter));
+    }
+    if mev_tracker::MEV_CONFIG.skip_suspected {
+        println!("✅ Skipping buys triggered by suspected MEV legs (SKIP_SUSPECTED_MEV)");
+        orchestrator.add_filter(Box::new(MevFilter));
+    }
+    for hook in hooks::hooks_from_env() {
+        println!("✅ Strategy hook enabled: {}", hook.name());
+        orchestrator.add_hook(hook);
+    }
+    let vault_config = VaultMonitorConfig::from_env();
+    if vault_config.enabled() {
+        let monitor = Arc::new(CreatorVaultMonitor::new(vault_config, Arc::new(TelegramNotifier)));
+        monitor.start(Arc::new(RpcVaultBalanceReader::new(config.app_state.read_rpc.clone())), POSITION_BOOK.clone());
+        orchestrator.add_hook(monitor);
+    }
+    let valuation_config = ValuationConfig::from_env();
+    if valuation_config.enabled() {
+        let estimator = RpcPriceEstimator::new(config.app_state.read_rpc.clone(), config.app_state.jupiter_client.clone());
+        Arc::new(ValuationRefresher::new(valuation_config, Arc::new(estimator))).start(POSITION_BOOK.clone());
+    }
+    if let Some(watch_config) = ConfigWatchConfig::from_env() {
+        let watcher = Arc::new(ConfigWatcher::new(&watch_config.path, orchestrator.runtime_params_handle(), Arc::new(TelegramNotifier)));
+        watcher.start(watch_config.interval);
+        println!("✅ Watching {} for config changes", watcher.path());
+    }
+    if !FreezeAuthorityFilter::allow_freeze_authority() {
+        orchestrator.add_filter(Box::new(FreezeAuthorityFilter::new(config.app_state.read_rpc.client())));
+    }
+    let honeypot_config = HoneypotConfig::from_env();
+    if honeypot_config.enabled {
+        println!("✅ Honeypot check enabled (round trip simulation: {})", honeypot_config.simulate_round_trip);
+        orchestrator.add_filter(Box::new(HoneypotFilter::new(honeypot_config, Arc::new(config.app_state.clone()), config.swap_config.clone())));
+    }
+
+    let mut source = GrpcEventSource::connect(&config.yellowstone_grpc_http, &config.yellowstone_grpc_token, Some(config.app_state.wallet.pubkey().to_string()))
+        .await
+        .map_err(|e| e.to_string())?;
+    println!("✅ Engine pipeline started");
+
+    orchestrator.run(&mut source).await;
+    if shutdown::is_requested() {
+        return Ok(());
+    }
+    Err("Event stream ended".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::*;
+    use crate::engine::executor::{ExecutionReport, PaperExecutor};
+    use crate::engine::gap_catchup::{GapSignature, PoolState};
+    use crate::engine::swap::{SwapDirection, SwapInType};
+    use crate::engine::test_fixtures::{event, lot, pubkey, trade};
+    use crate::engine::transaction_parser::TradeInfoFromToken;
+    use crate::services::ledger::MemoryLedger;
+    use crate::services::notifier::MemoryNotifier;
+    use crate::services::trade_costs::TradeCosts;
+
+    struct Harness {
+        orchestrator: Orchestrator,
+        positions: Arc<PositionBook>,
+        ledger: Arc<MemoryLedger>,
+        notifier: Arc<MemoryNotifier>,
+    }
+
+    fn harness(params: RuntimeParams) -> Harness {
+        harness_with(Arc::new(PaperExecutor::new()), params)
+    }
+
+    fn harness_with(executor: Arc<dyn TradeExecutor>, params: RuntimeParams) -> Harness {
+        let positions = Arc::new(PositionBook::new());
+        let ledger = Arc::new(MemoryLedger::new());
+        let notifier = Arc::new(MemoryNotifier::new());
+        let swap_config = SwapConfig {
+            swap_direction: SwapDirection::Buy,
+            in_type: SwapInType::Qty,
+            amount_in: 0.1,
+            buy_slippage: 700,
+            reverse: false,
+            dry_run: false,
+        };
+        let orchestrator = Orchestrator::new(
+            executor,
+            positions.clone(),
+            ledger.clone(),
+            notifier.clone(),
+            swap_config,
+            params,
+        );
+        Harness { orchestrator, positions, ledger, notifier }
+    }
+
+    /// Chain state as it stands after a gap
+    #[derive(Default)]
+    struct FakeGapReader {
+        states: HashMap<String, PoolState>,
+        signatures: HashMap<String, Vec<GapSignature>>,
+        replays: HashMap<String, Vec<TradeInfoFromToken>>,
+    }
+
+    #[async_trait::async_trait]
+    impl GapReader for FakeGapReader {
+        async fn pool_state(&self, mint: &str, _dex_type: DexType) -> Result<PoolState, String> {
+            self.states.get(mint).cloned().ok_or_else(|| format!("no state for {}", mint))
+        }
+
+        async fn signatures_in_gap(&self, address: &str, _gap: &StreamGap, _limit: usize) -> Result<Vec<GapSignature>, String> {
+            Ok(self.signatures.get(address).cloned().unwrap_or_default())
+        }
+
+        async fn replay(&self, signature: &str) -> Result<Vec<TradeInfoFromToken>, String> {
+            self.replays.get(signature).cloned().ok_or_else(|| format!("{} not found", signature))
+        }
+    }
+
+    const GAP: StreamGap = StreamGap { from_slot: 100, to_slot: 200, disconnected_at: 0, reconnected_at: 30 };
+
+    fn curve_at(price: f64) -> PoolState {
+        PoolState { pool_id: String::new(), virtual_sol_reserves: 30_000_000_000, virtual_token_reserves: 1_073_000_000_000_000, price, liquidity: 30.0 }
+    }
+
+    fn gap_signature(signature: &str) -> GapSignature {
+        GapSignature { signature: signature.to_string(), slot: 150 }
+    }
+
+    #[tokio::test]
+    async fn own_trades_never_trigger_a_buy() {
+        let h = harness(RuntimeParams::default());
+        let mint = pubkey();
+        let mut own = trade(&mint, true, 1e-6);
+        own.is_own_trade = true;
+        h.orchestrator.handle_event(event(own)).await;
+        assert!(h.ledger.entries().is_empty());
+        assert!(!h.positions.contains(&mint));
+
+        h.orchestrator.handle_event(event(trade(&mint, true, 1e-6))).await;
+        assert_eq!(h.ledger.entries().len(), 1);
+        assert!(h.positions.contains(&mint));
+    }
+
+    #[tokio::test]
+    async fn own_fills_move_prices_but_never_exits() {
+        let h = harness(RuntimeParams { stop_loss_pct: Some(20.0), ..RuntimeParams::default() });
+        let mint = pubkey();
+        h.positions.record_buy(&mint, DexType::PumpFun, Some(pubkey()), lot("entry", 1_000.0, 1.0));
+        let own = |is_buy: bool, price: f64| {
+            let mut fill = trade(&mint, is_buy, price);
+            fill.is_own_trade = true;
+            event(fill)
+        };
+
+        // Our own sell printing far below the stop is not a market move
+        h.orchestrator.handle_event(own(false, 0.000_1)).await;
+        let position = h.positions.get(&mint).unwrap();
+        assert_eq!(position.last_price, 0.001);
+        assert!(position.exits.is_empty());
+
+        // Our own buy is a real price level
+        h.orchestrator.handle_event(own(true, 0.002)).await;
+        let position = h.positions.get(&mint).unwrap();
+        assert_eq!((position.last_price, position.peak_price), (0.002, 0.002));
+        assert!(h.ledger.entries().is_empty());
+
+        // The same drop from someone else hits the stop
+        h.orchestrator.handle_event(event(trade(&mint, false, 0.000_1))).await;
+        let entries = h.ledger.entries();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].side, "sell");
+    }
+
+    #[tokio::test]
+    async fn position_overrides_apply_at_evaluation() {
+        let h = harness(RuntimeParams { stop_loss_pct: Some(50.0), ..RuntimeParams::default() });
+        let (tight, global) = (pubkey(), pubkey());
+        for mint in [&tight, &global] {
+            h.positions.record_buy(mint, DexType::PumpFun, Some(pubkey()), lot("entry", 1_000.0, 1.0));
+        }
+        let overrides = position_overrides::parse_assignments("stop=10%").unwrap();
+        h.positions.set_param_overrides(&tight, overrides);
+
+        // 20% under entry: past the position's own stop, inside the global one
+        for mint in [&tight, &global] {
+            h.orchestrator.handle_event(event(trade(mint, false, 0.000_8))).await;
+        }
+        let entries = h.ledger.entries();
+        assert_eq!(entries.len(), 1);
+        assert_eq!((entries[0].mint.as_str(), entries[0].side.as_str()), (tight.as_str(), "sell"));
+        assert!(h.positions.contains(&global));
+    }
+
+    #[tokio::test]
+    async fn rug_inside_a_gap_exits_on_catch_up() {
+        let mut h = harness(RuntimeParams { stop_loss_pct: Some(20.0), ..RuntimeParams::default() });
+        let (rugged, unread) = (pubkey(), pubkey());
+        for mint in [&rugged, &unread] {
+            h.positions.record_buy(mint, DexType::PumpFun, Some(pubkey()), lot("entry", 1_000.0, 1.0));
+        }
+        // The curve was drained while the stream was down; the other mint's state can't be read
+        let reader = FakeGapReader { states: HashMap::from([(rugged.clone(), curve_at(0.000_05))]), ..FakeGapReader::default() };
+        h.orchestrator.set_gap_reader(Arc::new(reader));
+
+        h.orchestrator.catch_up(&GAP).await;
+
+        let entries = h.ledger.entries();
+        assert_eq!(entries.len(), 1, "{:?}", entries);
+        assert_eq!((entries[0].mint.as_str(), entries[0].side.as_str()), (rugged.as_str(), "sell"));
+        assert!(!h.positions.contains(&rugged));
+        assert!(h.positions.contains(&unread));
+    }
+
+    #[tokio::test]
+    async fn own_transactions_landed_in_a_gap_are_reported_once() {
+        let mut h = harness(RuntimeParams::default());
+        let wallet = Pubkey::new_unique();
+        let (held, bought) = (pubkey(), pubkey());
+        h.positions.record_buy(&held, DexType::PumpFun, Some(pubkey()), lot("recorded", 1_000.0, 1.0));
+        let reader = FakeGapReader {
+            states: HashMap::from([(held.clone(), curve_at(0.001))]),
+            signatures: HashMap::from([(wallet.to_string(), vec![gap_signature("recorded"), gap_signature("unseen")])]),
+            replays: HashMap::from([("unseen".to_string(), vec![trade(&bought, true, 1e-6)])]),
+        };
+        h.orchestrator.set_gap_reader(Arc::new(reader));
+        h.orchestrator.set_own_wallet(wallet);
+
+        h.orchestrator.catch_up(&GAP).await;
+
+        let reported: Vec<String> = h.notifier.messages().into_iter().filter(|message| message.contains("landed unseen")).collect();
+        assert_eq!(reported.len(), 1, "{:?}", reported);
+        assert!(reported[0].contains("unseen") && reported[0].contains(&format!("buy {}", bought)), "{}", reported[0]);
+        assert!(h.ledger.entries().is_empty());
+    }
+
+    #[tokio::test]
+    async fn replayed_gap_buys_are_flagged_and_held_to_the_staleness_guard() {
+        let mut h = harness(RuntimeParams::default());
+        let (fresh, stale, held) = (pubkey(), pubkey(), pubkey());
+        h.positions.record_buy(&held, DexType::PumpFun, Some(pubkey()), lot("entry", 1_000.0, 1.0));
+        let mut old = trade(&stale, true, 1e-6);
+        old.timestamp -= 120;
+        let reader = FakeGapReader {
+            states: HashMap::from([(held.clone(), curve_at(0.001))]),
+            signatures: HashMap::from([(PUMP_FUN_PROGRAM.to_string(), vec![gap_signature("stale"), gap_signature("fresh")])]),
+            replays: HashMap::from([
+                ("stale".to_string(), vec![old, trade(&held, true, 1e-6)]),
+                ("fresh".to_string(), vec![trade(&fresh, true, 1e-6)]),
+            ]),
+        };
+        h.orchestrator.set_gap_reader(Arc::new(reader));
+        h.orchestrator.set_gap_config(GapCatchUpConfig { replay_buys: true, max_signatures: 10, max_replay_age_secs: 30 });
+
+        h.orchestrator.catch_up(&GAP).await;
+
+        let buys: Vec<(String, String)> = h.ledger.entries()
+            .into_iter()
+            .filter(|entry| entry.side == "buy")
+            .map(|entry| (entry.mint, entry.reason))
+            .collect();
+        assert_eq!(buys, vec![(fresh.clone(), "Qualifying buy (gap-replayed)".to_string())]);
+        assert!(!h.positions.contains(&stale));
+        assert_eq!(h.positions.get(&held).unwrap().lots.len(), 1, "held mints are not replayed");
+    }
+
+    /// Paper fills reported as live sends, with send-time cost estimates
+    struct LiveLikeExecutor(PaperExecutor);
+
+    #[async_trait::async_trait]
+    impl TradeExecutor for LiveLikeExecutor {
+        async fn buy(&self, trade_info: &TradeInfoFromToken, swap_config: &SwapConfig) -> anyhow::Result<ExecutionReport> {
+            let report = self.0.buy(trade_info, swap_config).await?;
+            Ok(ExecutionReport { signature: format!("buy-{}", pubkey()), simulated: false, costs: TradeCosts::estimated(0.001, 0.000_05), ..report })
+        }
+
+        async fn sell(&self, trade_info: &TradeInfoFromToken, swap_config: &SwapConfig, token_amount: f64, reason: &str) -> anyhow::Result<ExecutionReport> {
+            let report = self.0.sell(trade_info, swap_config, token_amount, reason).await?;
+            Ok(ExecutionReport { signature: format!("sell-{}", pubkey()), simulated: false, costs: TradeCosts::estimated(0.001, 0.000_05), ..report })
+        }
+    }
+
+    /// What the landed transactions show: the buy paid token account rent, the sell's close reclaimed it
+    struct LandedCosts;
+
+    const ATA_RENT_SOL: f64 = 0.002_039_28;
+
+    fn landed_costs(signature: &str) -> TradeCosts {
+        let rent_sol = if signature.starts_with("buy-") { ATA_RENT_SOL } else { -ATA_RENT_SOL };
+        TradeCosts { network_fee_sol: 0.000_005, priority_fee_sol: 0.000_02, tip_sol: 0.001, rent_sol, costs_confirmed: true }
+    }
+
+    #[async_trait::async_trait]
+    impl FillCostSource for LandedCosts {
+        async fn confirmed_costs(&self, signature: &str, _estimate: &TradeCosts) -> Result<TradeCosts, String> {
+            Ok(landed_costs(signature))
+        }
+    }
+
+    async fn ledger_entries(h: &Harness, count: usize) -> Vec<LedgerEntry> {
+        for _ in 0..100 {
+            let entries = h.ledger.entries();
+            if entries.len() >= count {
+                return entries;
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(5)).await;
+        }
+        panic!("expected {} ledger entries, got {:?}", count, h.ledger.entries());
+    }
+
+    #[tokio::test]
+    async fn open_close_cycle_records_every_cost_component() {
+        let executor = Arc::new(LiveLikeExecutor(PaperExecutor::new()));
+        let mut h = harness_with(executor, RuntimeParams { stop_loss_pct: Some(20.0), ..RuntimeParams::default() });
+        h.orchestrator.set_fill_cost_source(Arc::new(LandedCosts));
+        let mint = pubkey();
+
+        h.orchestrator.handle_event(event(trade(&mint, true, 1e-6))).await;
+        let buy = ledger_entries(&h, 1).await.remove(0);
+        let position = h.positions.get(&mint).unwrap();
+        assert_eq!(position.state, PositionState::Open, "the landed transaction confirms the buy");
+        assert_eq!(buy.costs, landed_costs(&buy.signature));
+        assert!((position.lots[0].costs_sol - buy.costs.total()).abs() < 1e-12);
+        assert!((buy.net_sol_amount() - (buy.sol_amount + 0.000_025 + 0.001 + ATA_RENT_SOL)).abs() < 1e-12);
+
+        h.orchestrator.handle_event(event(trade(&mint, false, position.entry_price() / 2.0))).await;
+        let entries = ledger_entries(&h, 2).await;
+        let sell = &entries[1];
+        assert_eq!(sell.side, "sell");
+        assert_eq!(sell.costs, landed_costs(&sell.signature));
+        assert!((sell.net_sol_amount() - (sell.sol_amount - 0.000_025 - 0.001 + ATA_RENT_SOL)).abs() < 1e-12);
+        assert!(!h.positions.contains(&mint));
+
+        // Rent paid on open comes back on close; fees and tips of both fills are what net PnL loses
+        let gross = sell.sol_amount - buy.sol_amount;
+        let net = sell.net_sol_amount() - buy.net_sol_amount();
+        assert!((buy.costs.rent_sol + sell.costs.rent_sol).abs() < 1e-12);
+        assert!((gross - net - 2.0 * (0.000_025 + 0.001)).abs() < 1e-12, "gross {} net {}", gross, net);
+    }
+
+    /// A live-like buy of `mint` made `age_secs` ago, left Pending since nothing confirms it
+    async fn pending_buy(h: &Harness, mint: &str, age_secs: u64) -> String {
+        let mut trigger = trade(mint, true, 1e-6);
+        trigger.timestamp -= age_secs;
+        h.orchestrator.handle_event(event(trigger)).await;
+        let position = h.positions.get(mint).expect("the buy opens a position");
+        assert_eq!(position.state, PositionState::Pending);
+        position.lots[0].signature.clone()
+    }
+
+    fn own_buy(mint: &str, signature: &str) -> TradeEvent {
+        let mut fill = trade(mint, true, 1e-6);
+        fill.is_own_trade = true;
+        fill.signature = signature.to_string();
+        event(fill)
+    }
+
+    #[tokio::test]
+    async fn pending_positions_only_sell_once_confirmed() {
+        let h = harness_with(Arc::new(LiveLikeExecutor(PaperExecutor::new())), RuntimeParams { stop_loss_pct: Some(20.0), ..RuntimeParams::default() });
+        let mint = pubkey();
+        let signature = pending_buy(&h, &mint, 0).await;
+
+        // Far under the stop, but the tokens may not be ours yet
+        h.orchestrator.handle_event(event(trade(&mint, false, 1e-8))).await;
+        assert_eq!(h.ledger.entries().len(), 1);
+        assert_eq!(h.positions.get(&mint).unwrap().state, PositionState::Pending);
+
+        // Another signature of ours doesn't confirm this buy
+        h.orchestrator.handle_event(own_buy(&mint, "someone-else")).await;
+        assert_eq!(h.positions.get(&mint).unwrap().state, PositionState::Pending);
+
+        h.orchestrator.handle_event(own_buy(&mint, &signature)).await;
+        assert_eq!(h.positions.get(&mint).unwrap().state, PositionState::Open);
+
+        h.orchestrator.handle_event(event(trade(&mint, false, 1e-8))).await;
+        let entries = h.ledger.entries();
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[1].side, "sell");
+        assert!(!h.positions.contains(&mint), "the full exit closes the position");
+    }
+
+    #[tokio::test]
+    async fn timed_out_buys_are_aborted_and_restored_by_a_late_confirmation() {
+        let mut h = harness_with(Arc::new(LiveLikeExecutor(PaperExecutor::new())), RuntimeParams::default());
+        h.orchestrator.set_pending_timeout_secs(5);
+        let (stale, fresh) = (pubkey(), pubkey());
+        let signature = pending_buy(&h, &stale, 10).await;
+        pending_buy(&h, &fresh, 0).await;
+
+        h.orchestrator.sweep_pending().await;
+        assert!(!h.positions.contains(&stale));
+        assert_eq!(h.positions.get(&fresh).unwrap().state, PositionState::Pending);
+        assert!(h.notifier.messages().iter().any(|message| message.contains(&format!("Buy of {} unconfirmed", stale))));
+
+        h.orchestrator.handle_event(own_buy(&stale, &signature)).await;
+        let restored = h.positions.get(&stale).expect("a late confirmation restores the position");
+        assert_eq!(restored.state, PositionState::Open);
+        assert_eq!(restored.lots[0].signature, signature);
+        assert!(h.notifier.messages().iter().any(|message| message.contains("after its position was aborted")));
+    }
+
+    /// A stream that has gone quiet
+    struct QuietSource;
+
+    #[async_trait::async_trait]
+    impl EventSource for QuietSource {
+        async fn next_event(&mut self) -> Option<TradeEvent> {
+            std::future::pending().await
+        }
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn run_aborts_timed_out_buys_without_further_events() {
+        let mut h = harness_with(Arc::new(LiveLikeExecutor(PaperExecutor::new())), RuntimeParams::default());
+        h.orchestrator.set_pending_timeout_secs(5);
+        let mint = pubkey();
+        pending_buy(&h, &mint, 10).await;
+
+        let ran = tokio::time::timeout(std::time::Duration::from_secs(3), h.orchestrator.run(&mut QuietSource)).await;
+        assert!(ran.is_err(), "a quiet source keeps the loop running");
+        assert!(!h.positions.contains(&mint), "the timer swept the stale buy");
+    }
+
+    struct FixedBalance(f64);
+
+    #[async_trait::async_trait]
+    impl WalletBalance for FixedBalance {
+        async fn sol_balance(&self) -> Result<f64, String> {
+            Ok(self.0)
+        }
+    }
+
+    /// Sizes sent for buys of `count` fresh mints, before and after jitter
+    async fn jittered_buys(h: &Harness, count: usize) -> Vec<(f64, f64)> {
+        for _ in 0..count {
+            h.orchestrator.handle_event(event(trade(&pubkey(), true, 1e-6))).await;
+        }
+        h.ledger.entries().iter().map(|entry| (entry.pre_jitter_sol.unwrap(), entry.post_jitter_sol.unwrap())).collect()
+    }
+
+    #[tokio::test]
+    async fn jitter_is_clamped_under_max_buy_and_the_reserve() {
+        let mut h = harness(RuntimeParams::default());
+        h.orchestrator.set_jitter(Arc::new(Jitter::seeded(JitterConfig { amount_pct: 50.0, tip_pct: 0.0 }, 7)));
+        h.orchestrator.set_max_buy_sol(Some(0.12));
+        let sizes = jittered_buys(&h, 30).await;
+        assert!(sizes.iter().all(|(base, jittered)| *base == 0.1 && *jittered <= 0.12), "{:?}", sizes);
+        assert!(sizes.iter().any(|(_, jittered)| *jittered == 0.12), "50% jitter reaches MAX_BUY_SOL: {:?}", sizes);
+        assert!(sizes.iter().any(|(_, jittered)| *jittered < 0.1));
+
+        // 0.15 SOL less the 0.05 reserve binds before MAX_BUY_SOL
+        let mut h = harness(RuntimeParams::default());
+        h.orchestrator.set_jitter(Arc::new(Jitter::seeded(JitterConfig { amount_pct: 50.0, tip_pct: 0.0 }, 7)));
+        h.orchestrator.set_max_buy_sol(Some(0.12));
+        h.orchestrator.set_wallet_balance(Arc::new(FixedBalance(0.15)));
+        let sizes = jittered_buys(&h, 30).await;
+        assert!(sizes.iter().all(|(_, jittered)| *jittered <= 0.1 + 1e-12), "{:?}", sizes);
+        assert!(sizes.iter().any(|(_, jittered)| (*jittered - 0.1).abs() < 1e-12));
+    }
+
+    #[tokio::test]
+    async fn jittered_buys_are_refused_when_the_reserve_leaves_nothing() {
+        let mut h = harness(RuntimeParams::default());
+        h.orchestrator.set_jitter(Arc::new(Jitter::seeded(JitterConfig { amount_pct: 10.0, tip_pct: 0.0 }, 7)));
+        h.orchestrator.set_wallet_balance(Arc::new(FixedBalance(0.0505)));
+        let mint = pubkey();
+        h.orchestrator.handle_event(event(trade(&mint, true, 1e-6))).await;
+        assert!(h.ledger.entries().is_empty());
+        assert!(!h.positions.contains(&mint));
+    }
+
+    #[tokio::test]
+    async fn a_held_curve_moves_to_pump_swap_when_it_completes() {
+        let h = harness(RuntimeParams::default());
+        let (mint, pool) = (pubkey(), pubkey());
+        h.positions.record_buy(&mint, DexType::PumpFun, Some(pubkey()), lot("entry", 1_000.0, 1.0));
+
+        h.orchestrator.handle_event(event(trade(&mint, true, 0.001))).await;
+        assert_eq!(h.positions.dex_type(&mint), Some(DexType::PumpFun));
+        assert!(!migration::is_migrated(&mint));
+
+        let mut completing = trade(&mint, true, 0.001);
+        completing.curve_complete = true;
+        h.orchestrator.handle_event(event(completing)).await;
+        assert_eq!(h.positions.dex_type(&mint), Some(DexType::PumpSwap));
+        assert!(migration::is_migrated(&mint));
+        let messages = h.notifier.messages();
+        assert_eq!(messages.len(), 1);
+        assert!(messages[0].contains(&format!("Token {} migrated to PumpSwap", mint)), "{}", messages[0]);
+
+        // The first pool trade fills in the pool without notifying again
+        let mut on_pool = trade(&mint, true, 0.001);
+        on_pool.dex_type = DexType::PumpSwap;
+        on_pool.pool_id = pool.clone();
+        h.orchestrator.handle_event(event(on_pool)).await;
+        assert_eq!(migration::migrated_pool(&mint).as_deref(), Some(pool.as_str()));
+        assert_eq!(h.notifier.messages().len(), 1);
+        assert!(h.ledger.entries().is_empty());
+    }
+
+    #[tokio::test]
+    async fn dust_trades_leave_the_trailing_peak_alone() {
+        let h = harness(RuntimeParams {
+            trailing_stop_pct: Some(20.0),
+            trailing_stop_min_liquidity: 5.0,
+            ..RuntimeParams::default()
+        });
+        let mint = pubkey();
+        h.positions.record_buy(&mint, DexType::PumpFun, Some(pubkey()), lot("entry", 1_000.0, 1.0));
+        let at = |price: f64, liquidity: f64| {
+            let mut trade_info = trade(&mint, true, price);
+            trade_info.liquidity = liquidity;
+            event(trade_info)
+        };
+
+        // A dust print at 10x entry moves the last price only; falling back from it is no drawdown
+        h.orchestrator.handle_event(at(0.01, 0.5)).await;
+        let position = h.positions.get(&mint).unwrap();
+        assert_eq!((position.last_price, position.peak_price), (0.01, 0.001));
+        h.orchestrator.handle_event(at(0.0012, 30.0)).await;
+        assert!(h.ledger.entries().is_empty());
+
+        // A real trade sets the peak, and the drop from it fires the stop
+        h.orchestrator.handle_event(at(0.002, 30.0)).await;
+        h.orchestrator.handle_event(at(0.0015, 30.0)).await;
+        let entries = h.ledger.entries();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].side, "sell");
+    }
+
+    #[tokio::test]
+    async fn wallet_changes_outside_the_bot_settle_the_book() {
+        let h = harness(RuntimeParams::default());
+        let mint = pubkey();
+        h.positions.record_buy(&mint, DexType::PumpFun, Some(pubkey()), lot("entry", 1_000.0, 1.0));
+        let change = |signature: &str, post_amount: f64, sol_delta: f64| BalanceChange {
+            signature: signature.to_string(),
+            slot: 1,
+            mint: mint.clone(),
+            post_amount,
+            sol_delta,
+        };
+
+        h.orchestrator.reconcile_balance(&change("partial", 400.0, 0.5)).await;
+        assert!((h.positions.get(&mint).unwrap().remaining_tokens() - 400.0).abs() < 1e-9);
+        h.orchestrator.reconcile_balance(&change("partial", 400.0, 0.5)).await;
+        assert_eq!(h.notifier.messages().len(), 1);
+
+        h.orchestrator.reconcile_balance(&change("rest", 0.0, 0.3)).await;
+        assert!(h.positions.get(&mint).is_none(), "emptied position left in the book");
+        let messages = h.notifier.messages();
+        assert_eq!(messages.len(), 2);
+        assert!(messages[0].contains("1000.000000 → 400.000000"), "{}", messages[0]);
+        assert!(messages[1].contains("external"), "{}", messages[1]);
+        // Reconciliation mirrors the chain; nothing was traded by the bot
+        assert!(h.ledger.entries().is_empty());
+    }
+
+    #[tokio::test]
+    async fn a_rung_hit_during_a_sell_in_flight_is_sent_afterwards() {
+        let params = RuntimeParams {
+            profit_tiers: crate::engine::exit_strategy::parse_profit_ladder("2.0:0.5,3.0:0.25").unwrap(),
+            ..RuntimeParams::default()
+        };
+        let h = harness(params);
+        let mint = pubkey();
+        h.positions.record_buy(&mint, DexType::PumpFun, Some(pubkey()), lot("entry", 1_000.0, 1.0));
+
+        // 2x entry while another sell of the mint is still running
+        PROGRESS_ON_SELLING.insert(mint.clone(), ());
+        h.orchestrator.handle_event(event(trade(&mint, true, 0.002))).await;
+        assert!(h.ledger.entries().is_empty());
+
+        // That sell settles with 300 tokens left, and price has fallen back under the rung
+        PROGRESS_ON_SELLING.remove(&mint);
+        h.orchestrator.reconcile_balance(&BalanceChange {
+            signature: "other-sell".to_string(),
+            slot: 1,
+            mint: mint.clone(),
+            post_amount: 300.0,
+            sol_delta: 0.7,
+        }).await;
+        h.orchestrator.handle_event(event(trade(&mint, false, 0.0015))).await;
+
+        let entries = h.ledger.entries();
+        assert_eq!(entries.len(), 1, "{:?}", entries);
+        assert_eq!((entries[0].side.as_str(), entries[0].reason.as_str()), ("sell", "Profit tier 1"));
+        assert!((entries[0].token_amount - 300.0).abs() < 1e-9, "{}", entries[0].token_amount);
+        assert!(h.positions.get(&mint).is_none(), "fully sold position left in the book");
+    }
+}
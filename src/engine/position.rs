@@ -0,0 +1,403 @@
+use std::str::FromStr;
+use std::sync::Arc;
+use anchor_client::solana_sdk::pubkey::Pubkey;
+use dashmap::DashMap;
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+
+use crate::common::clock;
+use crate::dex::pump_fun::{get_creator_vault_pda, PUMP_FUN_PROGRAM};
+use crate::engine::buy_sizing::SizeTier;
+use crate::engine::exit_strategy::ParamOverrides;
+use crate::engine::transaction_parser::DexType;
+
+/// Positions owned by the production orchestrator
+pub static POSITION_BOOK: Lazy<Arc<PositionBook>> = Lazy::new(|| Arc::new(PositionBook::new()));
+
+/// A single buy fill that opened or added to a position
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Lot {
+    pub signature: String,
+    pub token_amount: f64,
+    pub sol_spent: f64,
+    pub price: f64,
+    pub timestamp: u64,
+    /// Fees, tip and token account rent of the fill, see `TradeCosts::total`
+    #[serde(default)]
+    pub costs_sol: f64,
+    /// BUY_SIZE_TIERS tier that sized the buy
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub size_tier: Option<SizeTier>,
+}
+
+/// A single (partial or full) exit fill
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ExitFill {
+    pub signature: String,
+    pub token_amount: f64,
+    pub sol_received: f64,
+    pub price: f64,
+    pub reason: String,
+    pub timestamp: u64,
+    /// Fees and tip of the fill, less any token account rent the close reclaimed
+    #[serde(default)]
+    pub costs_sol: f64,
+}
+
+/// Where a position's current price estimate came from
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ValuationSource {
+    /// A fill or a streamed trade of the mint
+    Tick,
+    /// A small Jupiter sell quote
+    Quote,
+    /// The bonding curve or pool account
+    Curve,
+}
+
+impl ValuationSource {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ValuationSource::Tick => "tick",
+            ValuationSource::Quote => "quote",
+            ValuationSource::Curve => "curve",
+        }
+    }
+}
+
+/// Latest price estimate of a position, in the units of tick prices
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Valuation {
+    pub price: f64,
+    pub source: ValuationSource,
+    /// Unix time the estimate was taken
+    pub at: u64,
+}
+
+impl Valuation {
+    pub fn age_secs(&self, now: u64) -> u64 {
+        now.saturating_sub(self.at)
+    }
+}
+
+/// Lifecycle of a position; sell triggers only run while it is Open
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PositionState {
+    /// Buy sent, fill not confirmed yet
+    Pending,
+    /// Fill confirmed
+    Open,
+    /// A full exit is in flight
+    Closing,
+    Closed,
+}
+
+impl PositionState {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PositionState::Pending => "pending",
+            PositionState::Open => "open",
+            PositionState::Closing => "closing",
+            PositionState::Closed => "closed",
+        }
+    }
+}
+
+impl std::fmt::Display for PositionState {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// What a buy confirmation did to the book
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BuyConfirmation {
+    /// Pending → Open
+    Opened,
+    /// Already past Pending, or the signature isn't one of the position's lots
+    Unchanged,
+    /// The position was aborted as unconfirmed; it is back in the book as Open
+    Restored,
+    /// No position, open or aborted, has this buy
+    Unknown,
+}
+
+/// Everything the engine knows about one held token
+#[derive(Clone, Debug)]
+pub struct Position {
+    pub mint: String,
+    pub dex_type: DexType,
+    pub coin_creator: Option<String>,
+    /// PumpFun creator-vault PDA of `coin_creator`, derived when the position opens
+    pub creator_vault: Option<String>,
+    pub opened_at: u64,
+    pub peak_price: f64,
+    pub last_price: f64,
+    /// Indexes of profit tiers that already fired
+    pub tiers_hit: Vec<usize>,
+    pub lots: Vec<Lot>,
+    pub exits: Vec<ExitFill>,
+    /// Set with /set or --set-position, merged over the global params at each evaluation
+    pub param_overrides: ParamOverrides,
+    /// Last tick, or a refreshed estimate once ticks go quiet; see `engine::valuation`
+    pub valuation: Valuation,
+    pub state: PositionState,
+}
+
+impl Position {
+    /// Volume-weighted entry price across all lots
+    pub fn entry_price(&self) -> f64 {
+        let tokens: f64 = self.lots.iter().map(|lot| lot.token_amount).sum();
+        if tokens <= 0.0 {
+            return 0.0;
+        }
+        self.lots.iter().map(|lot| lot.price * lot.token_amount).sum::<f64>() / tokens
+    }
+
+    pub fn sol_invested(&self) -> f64 {
+        self.lots.iter().map(|lot| lot.sol_spent).sum()
+    }
+
+    pub fn sol_realized(&self) -> f64 {
+        self.exits.iter().map(|exit| exit.sol_received).sum()
+    }
+
+    /// Costs of every lot and exit
+    pub fn costs_sol(&self) -> f64 {
+        self.lots.iter().map(|lot| lot.costs_sol).sum::<f64>() + self.exits.iter().map(|exit| exit.costs_sol).sum::<f64>()
+    }
+
+    /// Realized SOL less invested SOL, before costs
+    pub fn gross_pnl_sol(&self) -> f64 {
+        self.sol_realized() - self.sol_invested()
+    }
+
+    pub fn net_pnl_sol(&self) -> f64 {
+        self.gross_pnl_sol() - self.costs_sol()
+    }
+
+    /// Tokens still held after all recorded exits
+    pub fn remaining_tokens(&self) -> f64 {
+        let bought: f64 = self.lots.iter().map(|lot| lot.token_amount).sum();
+        let sold: f64 = self.exits.iter().map(|exit| exit.token_amount).sum();
+        (bought - sold).max(0.0)
+    }
+
+    /// Share of `sol_invested` plus buy costs attributable to the tokens still held
+    pub fn open_cost_basis(&self) -> f64 {
+        let bought: f64 = self.lots.iter().map(|lot| lot.token_amount).sum();
+        if bought <= 0.0 {
+            return 0.0;
+        }
+        let buy_costs: f64 = self.lots.iter().map(|lot| lot.costs_sol).sum();
+        (self.sol_invested() + buy_costs) * self.remaining_tokens() / bought
+    }
+
+    pub fn is_closed(&self) -> bool {
+        // Dust below one raw unit at 6 decimals counts as fully sold
+        self.remaining_tokens() < 0.000_001
+    }
+}
+
+fn creator_vault_of(dex_type: DexType, coin_creator: Option<&str>) -> Option<String> {
+    if dex_type != DexType::PumpFun {
+        return None;
+    }
+    let creator = Pubkey::from_str(coin_creator?).ok()?;
+    let program = Pubkey::from_str(PUMP_FUN_PROGRAM).ok()?;
+    Some(get_creator_vault_pda(&creator, &program).to_string())
+}
+
+/// In-memory position store keyed by mint
+pub struct PositionBook {
+    positions: DashMap<String, Position>,
+    /// Positions aborted while Pending, by the signature of their buy, in case it confirms late
+    aborted: DashMap<String, Position>,
+}
+
+impl PositionBook {
+    pub fn new() -> Self {
+        Self {
+            positions: DashMap::new(),
+            aborted: DashMap::new(),
+        }
+    }
+
+    /// Record a confirmed buy fill, opening the position if this is the first lot
+    pub fn record_buy(&self, mint: &str, dex_type: DexType, coin_creator: Option<String>, lot: Lot) {
+        self.insert_lot(mint, dex_type, coin_creator, lot, PositionState::Open);
+    }
+
+    /// Record a sent but unconfirmed buy; a position it opens stays Pending until `confirm_buy`
+    /// A lot added to a position that is already Open leaves it Open.
+    pub fn record_pending_buy(&self, mint: &str, dex_type: DexType, coin_creator: Option<String>, lot: Lot) {
+        self.insert_lot(mint, dex_type, coin_creator, lot, PositionState::Pending);
+    }
+
+    fn insert_lot(&self, mint: &str, dex_type: DexType, coin_creator: Option<String>, lot: Lot, state: PositionState) {
+        let mut position = self.positions.entry(mint.to_string()).or_insert_with(|| Position {
+            mint: mint.to_string(),
+            dex_type,
+            creator_vault: creator_vault_of(dex_type, coin_creator.as_deref()),
+            coin_creator,
+            opened_at: lot.timestamp,
+            peak_price: lot.price,
+            last_price: lot.price,
+            tiers_hit: Vec::new(),
+            lots: Vec::new(),
+            exits: Vec::new(),
+            param_overrides: ParamOverrides::new(),
+            valuation: Valuation { price: lot.price, source: ValuationSource::Tick, at: lot.timestamp },
+            state,
+        });
+        position.peak_price = position.peak_price.max(lot.price);
+        position.last_price = lot.price;
+        position.valuation = Valuation { price: lot.price, source: ValuationSource::Tick, at: lot.timestamp };
+        position.lots.push(lot);
+    }
+
+    /// Add a lot to a held position without taking its price as a market observation (tokens received outside
+    /// the bot); the updated position
+    pub fn add_lot(&self, mint: &str, lot: Lot) -> Option<Position> {
+        let mut position = self.positions.get_mut(mint)?;
+        position.lots.push(lot);
+        Some(position.clone())
+    }
+
+    /// Record an exit fill and return the updated position
+    pub fn record_exit(&self, mint: &str, exit: ExitFill) -> Option<Position> {
+        let mut position = self.positions.get_mut(mint)?;
+        position.exits.push(exit);
+        Some(position.clone())
+    }
+
+    pub fn dex_type(&self, mint: &str) -> Option<DexType> {
+        self.positions.get(mint).map(|position| position.dex_type)
+    }
+
+    /// Move a position to the venue it trades on now (a PumpFun curve migrated to PumpSwap); the previous venue
+    pub fn set_dex_type(&self, mint: &str, dex_type: DexType) -> Option<DexType> {
+        let mut position = self.positions.get_mut(mint)?;
+        Some(std::mem::replace(&mut position.dex_type, dex_type))
+    }
+
+    /// Update last price from a market observation, and the peak when `moves_peak` (the trade was not dust)
+    pub fn observe_price(&self, mint: &str, price: f64, moves_peak: bool) -> Option<Position> {
+        let mut position = self.positions.get_mut(mint)?;
+        if price > 0.0 {
+            position.last_price = price;
+            if moves_peak {
+                position.peak_price = position.peak_price.max(price);
+            }
+            position.valuation = Valuation { price, source: ValuationSource::Tick, at: clock::now_secs() };
+        }
+        Some(position.clone())
+    }
+
+    /// Store a refreshed estimate unless a newer one (usually a tick) arrived meanwhile
+    /// Only the valuation changes; last and peak price, which exits read, stay tick-driven.
+    pub fn set_valuation(&self, mint: &str, valuation: Valuation) -> Option<Position> {
+        let mut position = self.positions.get_mut(mint)?;
+        if valuation.price > 0.0 && valuation.at >= position.valuation.at {
+            position.valuation = valuation;
+        }
+        Some(position.clone())
+    }
+
+    pub fn set_param_overrides(&self, mint: &str, overrides: ParamOverrides) -> Option<Position> {
+        let mut position = self.positions.get_mut(mint)?;
+        position.param_overrides = overrides;
+        Some(position.clone())
+    }
+
+    /// Replace the costs of the lot or exit filled by `signature` with the confirmed ones; false if not found
+    pub fn set_fill_costs(&self, mint: &str, signature: &str, costs_sol: f64) -> bool {
+        let Some(mut position) = self.positions.get_mut(mint) else {
+            return false;
+        };
+        if let Some(lot) = position.lots.iter_mut().find(|lot| lot.signature == signature) {
+            lot.costs_sol = costs_sol;
+            return true;
+        }
+        if let Some(exit) = position.exits.iter_mut().find(|exit| exit.signature == signature) {
+            exit.costs_sol = costs_sol;
+            return true;
+        }
+        false
+    }
+
+    /// Move `mint` to `state`; returns the previous state
+    pub fn set_state(&self, mint: &str, state: PositionState) -> Option<PositionState> {
+        let mut position = self.positions.get_mut(mint)?;
+        Some(std::mem::replace(&mut position.state, state))
+    }
+
+    /// The buy `signature` of `mint` landed: a Pending position opens, an aborted one is restored as Open
+    pub fn confirm_buy(&self, mint: &str, signature: &str) -> BuyConfirmation {
+        if let Some(mut position) = self.positions.get_mut(mint) {
+            if position.state == PositionState::Pending && position.lots.iter().any(|lot| lot.signature == signature) {
+                position.state = PositionState::Open;
+                return BuyConfirmation::Opened;
+            }
+            return BuyConfirmation::Unchanged;
+        }
+        match self.aborted.remove(signature) {
+            Some((_, mut position)) => {
+                position.state = PositionState::Open;
+                self.aborted.retain(|_, aborted| aborted.mint != mint);
+                self.positions.insert(mint.to_string(), position);
+                BuyConfirmation::Restored
+            }
+            None => BuyConfirmation::Unknown,
+        }
+    }
+
+    /// Drop a position whose buy never confirmed; kept aside by its buy signatures so a late confirmation restores it
+    pub fn abort_pending(&self, mint: &str) -> Option<Position> {
+        let (_, position) = self.positions.remove_if(mint, |_, position| position.state == PositionState::Pending)?;
+        for lot in position.lots.iter().filter(|lot| !lot.signature.is_empty()) {
+            self.aborted.insert(lot.signature.clone(), position.clone());
+        }
+        Some(position)
+    }
+
+    pub fn mark_tier_hit(&self, mint: &str, tier: usize) {
+        if let Some(mut position) = self.positions.get_mut(mint) {
+            if !position.tiers_hit.contains(&tier) {
+                position.tiers_hit.push(tier);
+            }
+        }
+    }
+
+    pub fn get(&self, mint: &str) -> Option<Position> {
+        self.positions.get(mint).map(|position| position.clone())
+    }
+
+    pub fn contains(&self, mint: &str) -> bool {
+        self.positions.contains_key(mint)
+    }
+
+    pub fn remove(&self, mint: &str) -> Option<Position> {
+        self.positions.remove(mint).map(|(_, position)| position)
+    }
+
+    pub fn snapshot(&self) -> Vec<Position> {
+        self.positions.iter().map(|entry| entry.value().clone()).collect()
+    }
+
+    pub fn len(&self) -> usize {
+        self.positions.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.positions.is_empty()
+    }
+}
+
+impl Default for PositionBook {
+    fn default() -> Self {
+        Self::new()
+    }
+}
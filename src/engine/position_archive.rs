@@ -0,0 +1,364 @@
+use std::io::BufRead;
+use colored::Colorize;
+use serde::{Deserialize, Serialize};
+
+use crate::common::logger::Logger;
+use crate::engine::exit_strategy::ParamOverrides;
+use crate::engine::position::{ExitFill, Lot, Position, PositionBook, POSITION_BOOK};
+use crate::engine::position_overrides;
+use crate::services::line_writer::{rotated_path, LineWriter, Rotation};
+use crate::services::telegram_commands;
+
+/// Consolidated record of a position once it fully closed
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ClosedPosition {
+    pub mint: String,
+    /// "PumpFun", "PumpSwap", ...
+    pub protocol: String,
+    pub coin_creator: Option<String>,
+    pub opened_at: u64,
+    pub closed_at: u64,
+    pub hold_secs: u64,
+    pub entry_price: f64,
+    /// Highest price observed while the position was open
+    pub peak_price: f64,
+    pub sol_invested: f64,
+    pub sol_realized: f64,
+    /// Fees, tips and token account rent across all fills (reclaimed rent counts against them)
+    #[serde(default)]
+    pub costs_sol: f64,
+    /// Before costs
+    #[serde(default)]
+    pub gross_pnl_sol: f64,
+    /// After costs
+    pub pnl_sol: f64,
+    pub pnl_pct: f64,
+    pub lots: Vec<Lot>,
+    pub exits: Vec<ExitFill>,
+    /// Per-position params in force when it closed
+    #[serde(default, skip_serializing_if = "ParamOverrides::is_empty")]
+    pub param_overrides: ParamOverrides,
+}
+
+impl ClosedPosition {
+    /// Aggregate a closed position; it closed at its last exit
+    pub fn from_position(position: &Position) -> Self {
+        let closed_at = position
+            .exits
+            .iter()
+            .map(|exit| exit.timestamp)
+            .max()
+            .unwrap_or(position.opened_at);
+        let sol_invested = position.sol_invested();
+        let sol_realized = position.sol_realized();
+        let costs_sol = position.costs_sol();
+        let pnl_sol = position.net_pnl_sol();
+        let pnl_pct = if sol_invested > 0.0 { pnl_sol / sol_invested * 100.0 } else { 0.0 };
+
+        Self {
+            mint: position.mint.clone(),
+            protocol: format!("{:?}", position.dex_type),
+            coin_creator: position.coin_creator.clone(),
+            opened_at: position.opened_at,
+            closed_at,
+            hold_secs: closed_at.saturating_sub(position.opened_at),
+            entry_price: position.entry_price(),
+            peak_price: position.peak_price,
+            sol_invested,
+            sol_realized,
+            costs_sol,
+            gross_pnl_sol: position.gross_pnl_sol(),
+            pnl_sol,
+            pnl_pct,
+            lots: position.lots.clone(),
+            exits: position.exits.clone(),
+            param_overrides: position.param_overrides.clone(),
+        }
+    }
+}
+
+/// Where the archive lives and how large it may grow
+#[derive(Clone, Debug)]
+pub struct ArchiveConfig {
+    pub path: String,
+    pub rotation: Rotation,
+}
+
+impl ArchiveConfig {
+    /// POSITION_ARCHIVE_FILE (default: closed_positions.jsonl), POSITION_ARCHIVE_MAX_BYTES (default: 10 MB),
+    /// POSITION_ARCHIVE_KEEP rotated files (default: 5)
+    pub fn from_env() -> Self {
+        let path = std::env::var("POSITION_ARCHIVE_FILE").unwrap_or_else(|_| "closed_positions.jsonl".to_string());
+        let max_bytes = std::env::var("POSITION_ARCHIVE_MAX_BYTES")
+            .ok()
+            .and_then(|v| v.parse::<u64>().ok())
+            .filter(|v| *v > 0)
+            .unwrap_or(10 * 1024 * 1024);
+        let keep = std::env::var("POSITION_ARCHIVE_KEEP")
+            .ok()
+            .and_then(|v| v.parse::<usize>().ok())
+            .unwrap_or(5);
+        Self { path, rotation: Rotation { max_bytes, keep } }
+    }
+
+    /// Live file first, then rotated files from newest to oldest
+    pub fn files(&self) -> Vec<String> {
+        (0..=self.rotation.keep).map(|index| rotated_path(&self.path, index)).collect()
+    }
+}
+
+/// Appends closed positions to the archive without blocking the caller
+pub struct PositionArchive {
+    writer: LineWriter,
+    logger: Logger,
+}
+
+impl PositionArchive {
+    pub fn new(config: &ArchiveConfig) -> Self {
+        let logger = Logger::new("[POSITION-ARCHIVE] => ".cyan().to_string());
+        Self {
+            writer: LineWriter::with_rotation(config.path.clone(), config.rotation, logger.clone()),
+            logger,
+        }
+    }
+
+    pub fn from_env() -> Self {
+        Self::new(&ArchiveConfig::from_env())
+    }
+
+    pub fn archive(&self, position: &Position) {
+        match serde_json::to_string(&ClosedPosition::from_position(position)) {
+            Ok(line) => self.writer.write_line(line),
+            Err(e) => self.logger.error(format!("Failed to serialize closed position {}: {}", position.mint, e)),
+        }
+    }
+}
+
+/// Archived records for `mint`, most recent first
+/// Missing files are skipped; unparsable lines are ignored.
+pub fn search_archive(config: &ArchiveConfig, mint: &str) -> Vec<ClosedPosition> {
+    let mut found = Vec::new();
+    for path in config.files() {
+        let Ok(file) = std::fs::File::open(&path) else {
+            continue;
+        };
+        let mut in_file: Vec<ClosedPosition> = std::io::BufReader::new(file)
+            .lines()
+            .map_while(Result::ok)
+            // Cheap pre-filter before parsing the whole record
+            .filter(|line| line.contains(mint))
+            .filter_map(|line| serde_json::from_str::<ClosedPosition>(&line).ok())
+            .filter(|record| record.mint == mint)
+            .collect();
+        // Lines are appended oldest first
+        in_file.reverse();
+        found.extend(in_file);
+    }
+    found
+}
+
+/// Result of looking a mint up
+#[derive(Clone, Debug)]
+pub enum PositionLookup {
+    Open(Position),
+    Archived(Vec<ClosedPosition>),
+    NotFound,
+}
+
+/// Open positions win over the archive
+pub fn lookup_position(book: &PositionBook, config: &ArchiveConfig, mint: &str) -> PositionLookup {
+    if let Some(position) = book.get(mint) {
+        return PositionLookup::Open(position);
+    }
+    let archived = search_archive(config, mint);
+    if archived.is_empty() {
+        PositionLookup::NotFound
+    } else {
+        PositionLookup::Archived(archived)
+    }
+}
+
+fn format_fills(lots: &[Lot], exits: &[ExitFill]) -> Vec<String> {
+    let mut lines = Vec::new();
+    for (index, lot) in lots.iter().enumerate() {
+        lines.push(format!(
+            "  buy #{} @ {}: {:.2} tokens for {:.6} SOL (price {:.10}) {}",
+            index + 1, lot.timestamp, lot.token_amount, lot.sol_spent, lot.price, lot.signature
+        ));
+    }
+    for (index, exit) in exits.iter().enumerate() {
+        lines.push(format!(
+            "  sell #{} @ {} [{}]: {:.2} tokens for {:.6} SOL (price {:.10}) {}",
+            index + 1, exit.timestamp, exit.reason, exit.token_amount, exit.sol_received, exit.price, exit.signature
+        ));
+    }
+    lines
+}
+
+fn format_open(position: &Position) -> String {
+    let mut lines = vec![
+        format!("🟢 OPEN position {} ({:?}, {})", position.mint, position.dex_type, position.state),
+        format!("Creator: {}", position.coin_creator.as_deref().unwrap_or("unknown")),
+        format!("Opened at: {}", position.opened_at),
+        format!(
+            "Entry {:.10} | last {:.10} | peak {:.10}",
+            position.entry_price(), position.last_price, position.peak_price
+        ),
+        format!(
+            "Invested {:.6} SOL | realized {:.6} SOL | costs {:.6} SOL | remaining {:.2} tokens",
+            position.sol_invested(), position.sol_realized(), position.costs_sol(), position.remaining_tokens()
+        ),
+    ];
+    if !position.param_overrides.is_empty() {
+        lines.push(format!("Overrides: {}", position_overrides::format_overrides(&position.param_overrides)));
+    }
+    lines.extend(format_fills(&position.lots, &position.exits));
+    lines.join("\n")
+}
+
+fn format_closed(record: &ClosedPosition) -> String {
+    let mut lines = vec![
+        format!("⚪ CLOSED position {} ({})", record.mint, record.protocol),
+        format!("Creator: {}", record.coin_creator.as_deref().unwrap_or("unknown")),
+        format!("Opened at {} | closed at {} | held {}s", record.opened_at, record.closed_at, record.hold_secs),
+        format!("Entry {:.10} | peak {:.10}", record.entry_price, record.peak_price),
+        format!(
+            "Invested {:.6} SOL | realized {:.6} SOL | costs {:.6} SOL",
+            record.sol_invested, record.sol_realized, record.costs_sol
+        ),
+        format!(
+            "PnL gross {:+.6} SOL | net {:+.6} SOL ({:+.2}%)",
+            record.gross_pnl_sol, record.pnl_sol, record.pnl_pct
+        ),
+    ];
+    if !record.param_overrides.is_empty() {
+        lines.push(format!("Overrides: {}", position_overrides::format_overrides(&record.param_overrides)));
+    }
+    lines.extend(format_fills(&record.lots, &record.exits));
+    lines.join("\n")
+}
+
+/// Human-readable lifecycle for the CLI and Telegram
+pub fn format_lookup(mint: &str, lookup: &PositionLookup) -> String {
+    match lookup {
+        PositionLookup::Open(position) => format_open(position),
+        PositionLookup::Archived(records) => records
+            .iter()
+            .map(format_closed)
+            .collect::<Vec<_>>()
+            .join("\n\n"),
+        PositionLookup::NotFound => format!("No open or archived position for {}", mint),
+    }
+}
+
+fn position_command(args: &str) -> String {
+    let mint = args.trim();
+    if mint.is_empty() {
+        return "Usage: /position <mint>".to_string();
+    }
+    format_lookup(mint, &lookup_position(&POSITION_BOOK, &ArchiveConfig::from_env(), mint))
+}
+
+/// Register the /position Telegram command
+pub fn register_commands() {
+    telegram_commands::register_command("/position", position_command);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+    use crate::engine::test_fixtures::{book_with, exit, pubkey};
+    use crate::services::line_writer;
+
+    fn config(keep: usize) -> ArchiveConfig {
+        let path = std::env::temp_dir().join(format!("closed-{}.jsonl", pubkey()));
+        ArchiveConfig {
+            path: path.to_string_lossy().into_owned(),
+            rotation: Rotation { max_bytes: 10 * 1024 * 1024, keep },
+        }
+    }
+
+    /// A position of `mint` bought for 1 SOL and closed in two exits for 1.5 SOL
+    fn closed(mint: &str) -> Position {
+        let book = book_with(mint, 1_000.0, 1.0);
+        book.record_exit(mint, exit("tier", 500.0, 1.0, "tier"));
+        book.record_exit(mint, exit("stop", 500.0, 0.5, "stop_loss")).unwrap()
+    }
+
+    #[test]
+    fn closed_record_aggregates_the_fills() {
+        let mint = pubkey();
+        let mut position = closed(&mint);
+        position.lots[0].costs_sol = 0.01;
+        position.exits[1].timestamp = position.opened_at + 90;
+        let record = ClosedPosition::from_position(&position);
+
+        assert_eq!(record.protocol, "PumpFun");
+        assert_eq!(record.closed_at, position.opened_at + 90);
+        assert_eq!(record.hold_secs, 90);
+        assert!((record.sol_invested - 1.0).abs() < 1e-12);
+        assert!((record.sol_realized - 1.5).abs() < 1e-12);
+        assert!((record.gross_pnl_sol - 0.5).abs() < 1e-12);
+        assert!((record.pnl_sol - 0.49).abs() < 1e-12);
+        assert!((record.pnl_pct - 49.0).abs() < 1e-9);
+        assert_eq!(record.exits.len(), 2);
+    }
+
+    #[test]
+    fn search_returns_newest_first_across_rotated_files() {
+        let config = config(2);
+        let mint = pubkey();
+        let mut older = ClosedPosition::from_position(&closed(&mint));
+        older.closed_at = 1;
+        let mut newer = older.clone();
+        newer.closed_at = 2;
+        let mut newest = older.clone();
+        newest.closed_at = 3;
+        let other = ClosedPosition::from_position(&closed(&pubkey()));
+        let line = |record: &ClosedPosition| serde_json::to_string(record).unwrap();
+
+        std::fs::write(rotated_path(&config.path, 1), format!("{}\n", line(&older))).unwrap();
+        std::fs::write(
+            &config.path,
+            format!("{}\nnot json {}\n{}\n{}\n", line(&newer), mint, line(&other), line(&newest)),
+        ).unwrap();
+
+        let found: Vec<u64> = search_archive(&config, &mint).iter().map(|record| record.closed_at).collect();
+        assert_eq!(found, vec![3, 2, 1]);
+        assert!(search_archive(&config, &pubkey()).is_empty());
+        for path in config.files() {
+            let _ = std::fs::remove_file(path);
+        }
+    }
+
+    #[tokio::test]
+    async fn lookup_prefers_the_open_position_then_the_archive() {
+        let config = config(0);
+        let archived_mint = pubkey();
+        let archive = PositionArchive::new(&config);
+        archive.archive(&closed(&archived_mint));
+        line_writer::flush_all(Duration::from_secs(5)).await;
+
+        let open_mint = pubkey();
+        let book = book_with(&open_mint, 1_000.0, 1.0);
+        assert!(matches!(lookup_position(&book, &config, &open_mint), PositionLookup::Open(_)));
+        let PositionLookup::Archived(records) = lookup_position(&book, &config, &archived_mint) else {
+            panic!("archived position not found");
+        };
+        assert_eq!(records.len(), 1);
+        assert!((records[0].pnl_sol - 0.5).abs() < 1e-12);
+        assert!(format_lookup(&archived_mint, &PositionLookup::Archived(records)).starts_with("⚪ CLOSED position"));
+
+        let missing = pubkey();
+        let lookup = lookup_position(&book, &config, &missing);
+        assert!(matches!(lookup, PositionLookup::NotFound));
+        assert_eq!(format_lookup(&missing, &lookup), format!("No open or archived position for {}", missing));
+        let _ = std::fs::remove_file(&config.path);
+    }
+
+    #[test]
+    fn position_command_needs_a_mint() {
+        assert_eq!(position_command("  "), "Usage: /position <mint>");
+    }
+}
@@ -0,0 +1,341 @@
+use std::collections::BTreeMap;
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime};
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::common::clock;
+use crate::engine::exit_strategy::{parse_profit_tiers, ParamOverrides, RuntimeParams};
+use crate::engine::position::{PositionBook, POSITION_BOOK};
+use crate::services::telegram_commands;
+
+/// Overrides of the production process, persisted to POSITION_OVERRIDES_FILE
+pub static OVERRIDE_STORE: Lazy<OverrideStore> = Lazy::new(OverrideStore::from_env);
+
+/// Persisted overrides of one mint
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct StoredOverrides {
+    pub overrides: ParamOverrides,
+    /// "telegram", "cli" ... of the last change
+    pub source: String,
+    pub updated_at: u64,
+}
+
+/// Parse "stop=25% tp=off trailing=15%" into param overrides
+/// Keys: stop (stop_loss_pct), trailing (trailing_stop_pct), tp (profit_tiers as "gain:sell,..."). "off" disables.
+pub fn parse_assignments(args: &str) -> Result<ParamOverrides, String> {
+    let mut overrides = ParamOverrides::new();
+    for assignment in args.split_whitespace() {
+        let (key, value) = assignment
+            .split_once('=')
+            .ok_or_else(|| format!("expected key=value, got '{}'", assignment))?;
+        let value = value.trim();
+        let off = value.eq_ignore_ascii_case("off");
+        let (name, json) = match key.trim().to_lowercase().as_str() {
+            "stop" | "stop_loss" => ("stop_loss_pct", if off { Value::Null } else { parse_pct(key, value)? }),
+            "trailing" | "trailing_stop" => ("trailing_stop_pct", if off { Value::Null } else { parse_pct(key, value)? }),
+            "tp" | "take_profit" => {
+                let tiers = if off { Vec::new() } else { parse_profit_tiers(value) };
+                if !off && tiers.is_empty() {
+                    return Err(format!("tp expects gain:sell pairs like 50:30,100:30 or off, got '{}'", value));
+                }
+                ("profit_tiers", serde_json::to_value(tiers).map_err(|e| e.to_string())?)
+            }
+            other => return Err(format!("unknown key '{}' (use stop, trailing or tp)", other)),
+        };
+        overrides.insert(name.to_string(), json);
+    }
+    if overrides.is_empty() {
+        return Err("no overrides given".to_string());
+    }
+    Ok(overrides)
+}
+
+fn parse_pct(key: &str, value: &str) -> Result<Value, String> {
+    let pct = value
+        .trim_end_matches('%')
+        .parse::<f64>()
+        .map_err(|_| format!("{} expects a percentage or off, got '{}'", key, value))?;
+    serde_json::Number::from_f64(pct)
+        .map(Value::Number)
+        .ok_or_else(|| format!("{} must be finite", key))
+}
+
+/// "stop_loss_pct=25, profit_tiers=off"
+pub fn format_overrides(overrides: &ParamOverrides) -> String {
+    overrides
+        .iter()
+        .map(|(key, value)| match value {
+            Value::Null => format!("{}=off", key),
+            Value::Array(items) if items.is_empty() => format!("{}=off", key),
+            other => format!("{}={}", key, other),
+        })
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// File-backed overrides keyed by mint
+/// The running bot reloads the file when it changes, which is how --set-position reaches it.
+pub struct OverrideStore {
+    path: String,
+    entries: Mutex<BTreeMap<String, StoredOverrides>>,
+    /// Modification time of the file as last read or written
+    modified: Mutex<Option<SystemTime>>,
+}
+
+impl OverrideStore {
+    /// POSITION_OVERRIDES_FILE (default: position_overrides.json)
+    pub fn from_env() -> Self {
+        Self::load(std::env::var("POSITION_OVERRIDES_FILE").unwrap_or_else(|_| "position_overrides.json".to_string()))
+    }
+
+    pub fn load(path: String) -> Self {
+        let store = Self { path, entries: Mutex::new(BTreeMap::new()), modified: Mutex::new(None) };
+        let _ = store.reload();
+        store
+    }
+
+    fn file_modified(&self) -> Option<SystemTime> {
+        std::fs::metadata(&self.path).and_then(|meta| meta.modified()).ok()
+    }
+
+    /// Re-read the file if it changed since the last read or write; true if entries were replaced
+    pub fn reload(&self) -> Result<bool, String> {
+        let modified = self.file_modified();
+        if self.modified.lock().map(|last| *last == modified).unwrap_or(false) {
+            return Ok(false);
+        }
+        let entries = match std::fs::read_to_string(&self.path) {
+            Ok(contents) => serde_json::from_str(&contents).map_err(|e| format!("Failed to parse {}: {}", self.path, e))?,
+            Err(_) => BTreeMap::new(),
+        };
+        // Same lock order as `set`/`clear`: entries, then modified
+        let mut current = self.entries.lock().map_err(|e| e.to_string())?;
+        *current = entries;
+        if let Ok(mut last) = self.modified.lock() {
+            *last = modified;
+        }
+        Ok(true)
+    }
+
+    fn save(&self, entries: &BTreeMap<String, StoredOverrides>) -> Result<(), String> {
+        let json = serde_json::to_string_pretty(entries).map_err(|e| e.to_string())?;
+        std::fs::write(&self.path, json).map_err(|e| format!("Failed to write {}: {}", self.path, e))?;
+        if let Ok(mut last) = self.modified.lock() {
+            *last = self.file_modified();
+        }
+        Ok(())
+    }
+
+    pub fn get(&self, mint: &str) -> Option<StoredOverrides> {
+        self.entries.lock().ok()?.get(mint).cloned()
+    }
+
+    /// Merge `changes` into the mint's overrides after checking the result against `base`; returns the merged set
+    pub fn set(&self, mint: &str, changes: ParamOverrides, base: &RuntimeParams, source: &str) -> Result<ParamOverrides, String> {
+        // Pick up a concurrent --set-position before merging on top of it
+        let _ = self.reload();
+        let mut entries = self.entries.lock().map_err(|e| e.to_string())?;
+        let mut merged = entries.get(mint).map(|stored| stored.overrides.clone()).unwrap_or_default();
+        merged.extend(changes);
+        base.with_overrides(&Value::Object(merged.clone())).map_err(|e| e.to_string())?;
+        entries.insert(mint.to_string(), StoredOverrides {
+            overrides: merged.clone(),
+            source: source.to_string(),
+            updated_at: clock::now_secs(),
+        });
+        self.save(&entries)?;
+        println!("🎛️ Overrides for {} set by {}: {}", mint, source, format_overrides(&merged));
+        Ok(merged)
+    }
+
+    /// Drop the mint's overrides (position closed, or cleared by the operator)
+    pub fn clear(&self, mint: &str, source: &str) -> Result<bool, String> {
+        let _ = self.reload();
+        let mut entries = self.entries.lock().map_err(|e| e.to_string())?;
+        if entries.remove(mint).is_none() {
+            return Ok(false);
+        }
+        self.save(&entries)?;
+        println!("🎛️ Overrides for {} cleared by {}", mint, source);
+        Ok(true)
+    }
+}
+
+/// Global params with the position's overrides on top
+/// Overrides were validated when set, so a failure here means the file was edited by hand; the globals then apply.
+pub fn effective_params(base: &RuntimeParams, overrides: &ParamOverrides) -> Result<RuntimeParams, String> {
+    if overrides.is_empty() {
+        return Ok(base.clone());
+    }
+    base.with_overrides(&Value::Object(overrides.clone())).map_err(|e| e.to_string())
+}
+
+/// Copy stored overrides onto a position that was just opened or restored
+pub fn attach(book: &PositionBook, mint: &str) {
+    if let Some(stored) = OVERRIDE_STORE.get(mint) {
+        book.set_param_overrides(mint, stored.overrides);
+    }
+}
+
+/// Bring every held position in line with the store
+fn sync_book(book: &PositionBook) {
+    for position in book.snapshot() {
+        let stored = OVERRIDE_STORE.get(&position.mint).map(|stored| stored.overrides).unwrap_or_default();
+        if stored != position.param_overrides {
+            println!("🎛️ Overrides for {} reloaded from {}: {}", position.mint, OVERRIDE_STORE.path, format_overrides(&stored));
+            book.set_param_overrides(&position.mint, stored);
+        }
+    }
+}
+
+fn set_command(args: &str) -> String {
+    let mut parts = args.trim().splitn(2, char::is_whitespace);
+    let (Some(mint), Some(assignments)) = (parts.next().filter(|m| !m.is_empty()), parts.next()) else {
+        return "Usage: /set <mint> stop=25% tp=off trailing=15% (or /set <mint> clear)".to_string();
+    };
+    if !POSITION_BOOK.contains(mint) {
+        return format!("No open position for {}", mint);
+    }
+    if assignments.trim().eq_ignore_ascii_case("clear") {
+        return match OVERRIDE_STORE.clear(mint, "telegram") {
+            Ok(_) => {
+                POSITION_BOOK.set_param_overrides(mint, ParamOverrides::new());
+                format!("Overrides for {} cleared", mint)
+            }
+            Err(e) => format!("❌ {}", e),
+        };
+    }
+    let result = parse_assignments(assignments)
+        .and_then(|changes| OVERRIDE_STORE.set(mint, changes, &RuntimeParams::from_env(), "telegram"));
+    match result {
+        Ok(merged) => {
+            POSITION_BOOK.set_param_overrides(mint, merged.clone());
+            format!("✅ Overrides for {}: {}", mint, format_overrides(&merged))
+        }
+        Err(e) => format!("❌ {}", e),
+    }
+}
+
+/// --set-position: validate and write to the store; a running bot applies it on its next reload
+pub fn set_from_cli(mint: &str, assignments: &str) -> Result<String, String> {
+    if assignments.trim().eq_ignore_ascii_case("clear") {
+        OVERRIDE_STORE.clear(mint, "cli")?;
+        return Ok(format!("Overrides for {} cleared", mint));
+    }
+    let changes = parse_assignments(assignments)?;
+    let merged = OVERRIDE_STORE.set(mint, changes, &RuntimeParams::from_env(), "cli")?;
+    Ok(format!("Overrides for {}: {}", mint, format_overrides(&merged)))
+}
+
+/// Register /set and reload the store every 2s so --set-position changes reach held positions
+pub fn start_override_sync() {
+    telegram_commands::register_command("/set", set_command);
+    tokio::spawn(async {
+        let mut interval = tokio::time::interval(Duration::from_secs(2));
+        loop {
+            interval.tick().await;
+            match OVERRIDE_STORE.reload() {
+                Ok(true) => sync_book(&POSITION_BOOK),
+                Ok(false) => {}
+                Err(e) => eprintln!("Failed to reload position overrides: {}", e),
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::test_fixtures::pubkey;
+
+    fn base() -> RuntimeParams {
+        RuntimeParams {
+            profit_tiers: parse_profit_tiers("50:30,100:30"),
+            trailing_stop_pct: None,
+            stop_loss_pct: Some(40.0),
+            ..RuntimeParams::default()
+        }
+    }
+
+    fn temp_store() -> OverrideStore {
+        OverrideStore::load(std::env::temp_dir().join(format!("overrides-{}.json", pubkey())).to_string_lossy().into_owned())
+    }
+
+    #[test]
+    fn assignments_parse_to_param_keys() {
+        let overrides = parse_assignments("stop=25% tp=off trailing=15").unwrap();
+        assert_eq!(format_overrides(&overrides), "profit_tiers=off, stop_loss_pct=25.0, trailing_stop_pct=15.0");
+        assert_eq!(format_overrides(&parse_assignments("trailing=off").unwrap()), "trailing_stop_pct=off");
+        assert_eq!(parse_assignments("tp=200:50").unwrap()["profit_tiers"][0]["gain_pct"], 200.0);
+    }
+
+    #[test]
+    fn malformed_assignments_are_rejected() {
+        assert!(parse_assignments("").unwrap_err().contains("no overrides"));
+        assert!(parse_assignments("stop").unwrap_err().contains("key=value"));
+        assert!(parse_assignments("stop=abc").unwrap_err().contains("percentage"));
+        assert!(parse_assignments("tp=lots").unwrap_err().contains("gain:sell"));
+        assert!(parse_assignments("leverage=10").unwrap_err().contains("unknown key"));
+    }
+
+    #[test]
+    fn overrides_win_over_globals_and_later_changes_win_over_earlier() {
+        let store = temp_store();
+        let mint = pubkey();
+        store.set(&mint, parse_assignments("stop=25% trailing=10%").unwrap(), &base(), "telegram").unwrap();
+        let merged = store.set(&mint, parse_assignments("stop=15% tp=off").unwrap(), &base(), "cli").unwrap();
+
+        let params = effective_params(&base(), &merged).unwrap();
+        assert_eq!(params.stop_loss_pct, Some(15.0));
+        assert_eq!(params.trailing_stop_pct, Some(10.0));
+        assert!(params.profit_tiers.is_empty());
+        assert_eq!(params.trailing_stop_arm_secs, base().trailing_stop_arm_secs);
+        assert_eq!(store.get(&mint).unwrap().source, "cli");
+
+        // No overrides, the globals as they are
+        assert_eq!(effective_params(&base(), &ParamOverrides::new()).unwrap().stop_loss_pct, Some(40.0));
+        let _ = std::fs::remove_file(&store.path);
+    }
+
+    #[test]
+    fn overrides_that_break_the_global_rules_are_not_stored() {
+        let store = temp_store();
+        let mint = pubkey();
+        let error = store.set(&mint, parse_assignments("stop=150%").unwrap(), &base(), "telegram").unwrap_err();
+        assert!(error.contains("stop_loss_pct"), "{}", error);
+        assert!(store.set(&mint, parse_assignments("trailing=100").unwrap(), &base(), "telegram").is_err());
+        assert!(store.get(&mint).is_none());
+
+        // A bad change leaves the earlier overrides as they were
+        store.set(&mint, parse_assignments("stop=20").unwrap(), &base(), "telegram").unwrap();
+        assert!(store.set(&mint, parse_assignments("trailing=0").unwrap(), &base(), "telegram").is_err());
+        assert_eq!(format_overrides(&store.get(&mint).unwrap().overrides), "stop_loss_pct=20.0");
+        let _ = std::fs::remove_file(&store.path);
+    }
+
+    #[test]
+    fn overrides_survive_a_restart_and_clear_on_close() {
+        let store = temp_store();
+        let mint = pubkey();
+        store.set(&mint, parse_assignments("stop=25% tp=off").unwrap(), &base(), "telegram").unwrap();
+
+        // A new process reading the same file, and the position restored into its book
+        let restarted = OverrideStore::load(store.path.clone());
+        let stored = restarted.get(&mint).expect("overrides persisted");
+        assert_eq!(stored.source, "telegram");
+        let book = crate::engine::test_fixtures::book_with(&mint, 1_000.0, 1.0);
+        book.set_param_overrides(&mint, stored.overrides.clone());
+        let params = effective_params(&base(), &book.get(&mint).unwrap().param_overrides).unwrap();
+        assert_eq!(params.stop_loss_pct, Some(25.0));
+        assert!(params.profit_tiers.is_empty());
+
+        assert!(restarted.clear(&mint, "close").unwrap());
+        assert!(!restarted.clear(&mint, "close").unwrap());
+        // The first process sees the cleared file on its next reload
+        assert!(store.reload().unwrap());
+        assert!(store.get(&mint).is_none());
+        let _ = std::fs::remove_file(&store.path);
+    }
+}
@@ -0,0 +1,190 @@
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use dashmap::DashMap;
+use once_cell::sync::Lazy;
+use serde::Serialize;
+use tokio::sync::broadcast;
+
+use crate::common::clock;
+use crate::engine::position::Position;
+use crate::services::session_report::OpenPositionSummary;
+
+/// Updates published by the production orchestrator
+pub static POSITION_UPDATES: Lazy<Arc<PositionUpdates>> = Lazy::new(|| Arc::new(PositionUpdates::from_env()));
+
+/// What changed
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PositionUpdateKind {
+    /// A buy opened the position or added to it
+    LotAdded,
+    PartialExit,
+    /// The price moved past the debounce threshold
+    PriceTick,
+    Closed,
+}
+
+impl PositionUpdateKind {
+    /// Everything but price ticks is published as soon as it happens
+    pub fn is_material(&self) -> bool {
+        !matches!(self, PositionUpdateKind::PriceTick)
+    }
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct PositionUpdate {
+    pub kind: PositionUpdateKind,
+    /// Corrected unix ms, see `clock::now_ms`
+    pub at_ms: i64,
+    #[serde(flatten)]
+    pub position: OpenPositionSummary,
+}
+
+/// Debounced broadcast of position changes; slow receivers lose the oldest updates, never block the sender
+pub struct PositionUpdates {
+    sender: broadcast::Sender<Arc<PositionUpdate>>,
+    /// Minimum gap between published price ticks of one mint
+    min_interval: Duration,
+    /// Minimum move in percent since the last published tick
+    min_move_pct: f64,
+    last_tick: DashMap<String, (Instant, f64)>,
+}
+
+impl PositionUpdates {
+    pub fn new(capacity: usize, min_interval: Duration, min_move_pct: f64) -> Self {
+        let (sender, _) = broadcast::channel(capacity.max(1));
+        Self { sender, min_interval, min_move_pct, last_tick: DashMap::new() }
+    }
+
+    /// POSITION_UPDATES_CAPACITY (default: 256) updates buffered per subscriber, POSITION_UPDATE_MIN_INTERVAL_MS
+    /// (default: 250) and POSITION_UPDATE_MIN_MOVE_PCT (default: 0.5) between price ticks
+    pub fn from_env() -> Self {
+        let capacity = std::env::var("POSITION_UPDATES_CAPACITY")
+            .ok()
+            .and_then(|v| v.parse::<usize>().ok())
+            .unwrap_or(256);
+        let min_interval_ms = std::env::var("POSITION_UPDATE_MIN_INTERVAL_MS")
+            .ok()
+            .and_then(|v| v.parse::<u64>().ok())
+            .unwrap_or(250);
+        let min_move_pct = std::env::var("POSITION_UPDATE_MIN_MOVE_PCT")
+            .ok()
+            .and_then(|v| v.parse::<f64>().ok())
+            .unwrap_or(0.5);
+        Self::new(capacity, Duration::from_millis(min_interval_ms), min_move_pct)
+    }
+
+    pub fn subscribe(&self) -> broadcast::Receiver<Arc<PositionUpdate>> {
+        self.sender.subscribe()
+    }
+
+    /// Price ticks within `min_interval` of the last published one, or that moved less than `min_move_pct`, are dropped
+    fn should_publish(&self, kind: PositionUpdateKind, position: &Position) -> bool {
+        let now = Instant::now();
+        if kind.is_material() {
+            if kind == PositionUpdateKind::Closed {
+                self.last_tick.remove(&position.mint);
+            } else {
+                self.last_tick.insert(position.mint.clone(), (now, position.last_price));
+            }
+            return true;
+        }
+        let publish = match self.last_tick.get(&position.mint).map(|entry| *entry) {
+            Some((at, price)) => {
+                let moved_pct = if price > 0.0 { (position.last_price - price).abs() / price * 100.0 } else { f64::INFINITY };
+                now.duration_since(at) >= self.min_interval && moved_pct >= self.min_move_pct
+            }
+            None => true,
+        };
+        if publish {
+            self.last_tick.insert(position.mint.clone(), (now, position.last_price));
+        }
+        publish
+    }
+
+    /// Publish `kind` for `position` unless debounced; returns the update that went out
+    pub fn publish(&self, kind: PositionUpdateKind, position: &Position) -> Option<Arc<PositionUpdate>> {
+        if !self.should_publish(kind, position) {
+            return None;
+        }
+        let update = Arc::new(PositionUpdate {
+            kind,
+            at_ms: clock::now_ms(),
+            position: OpenPositionSummary::from_position(position),
+        });
+        // Err only means nobody is subscribed
+        let _ = self.sender.send(update.clone());
+        Some(update)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::test_fixtures::{book_with, pubkey};
+    use tokio::sync::broadcast::error::{RecvError, TryRecvError};
+
+    const INTERVAL: Duration = Duration::from_millis(200);
+
+    fn position_at(price: f64) -> Position {
+        let mint = pubkey();
+        let mut position = book_with(&mint, 1_000_000.0, 0.1).get(&mint).unwrap();
+        position.last_price = price;
+        position
+    }
+
+    #[tokio::test]
+    async fn price_ticks_are_debounced_per_mint() {
+        let updates = PositionUpdates::new(16, INTERVAL, 1.0);
+        let mut position = position_at(1.0);
+        let other = position_at(1.0);
+
+        assert!(updates.publish(PositionUpdateKind::PriceTick, &position).is_some(), "first tick of a mint goes out");
+        position.last_price = 2.0;
+        assert!(updates.publish(PositionUpdateKind::PriceTick, &position).is_none(), "within the interval");
+        assert!(updates.publish(PositionUpdateKind::PriceTick, &other).is_some(), "another mint has its own window");
+
+        tokio::time::sleep(INTERVAL).await;
+        position.last_price = 1.005;
+        assert!(updates.publish(PositionUpdateKind::PriceTick, &position).is_none(), "moved under min_move_pct");
+        position.last_price = 1.5;
+        assert!(updates.publish(PositionUpdateKind::PriceTick, &position).is_some());
+
+        // Material changes always go out and restart the tick window
+        assert!(updates.publish(PositionUpdateKind::PartialExit, &position).is_some());
+        position.last_price = 3.0;
+        assert!(updates.publish(PositionUpdateKind::PriceTick, &position).is_none());
+        assert!(updates.publish(PositionUpdateKind::Closed, &position).is_some());
+        assert!(updates.publish(PositionUpdateKind::PriceTick, &position).is_some(), "a close forgets the last tick");
+    }
+
+    #[tokio::test]
+    async fn slow_subscriber_loses_the_oldest_updates() {
+        let updates = PositionUpdates::new(2, INTERVAL, 1.0);
+        let mut slow = updates.subscribe();
+        let positions: Vec<Position> = (0..5).map(|index| position_at(index as f64 + 1.0)).collect();
+
+        // The sender never waits on the subscriber
+        for position in &positions {
+            assert!(updates.publish(PositionUpdateKind::LotAdded, position).is_some());
+        }
+
+        assert!(matches!(slow.recv().await, Err(RecvError::Lagged(3))));
+        let kept: Vec<String> = [slow.recv().await.unwrap(), slow.recv().await.unwrap()]
+            .iter()
+            .map(|update| update.position.mint.clone())
+            .collect();
+        assert_eq!(kept, vec![positions[3].mint.clone(), positions[4].mint.clone()]);
+        assert_eq!(slow.try_recv().unwrap_err(), TryRecvError::Empty);
+    }
+
+    #[test]
+    fn updates_serialize_flat() {
+        let updates = PositionUpdates::new(4, INTERVAL, 1.0);
+        let position = position_at(1.0);
+        let update = updates.publish(PositionUpdateKind::LotAdded, &position).unwrap();
+        let json = serde_json::to_value(update.as_ref()).unwrap();
+        assert_eq!(json["kind"], "lot_added");
+        assert_eq!(json["mint"], position.mint);
+    }
+}
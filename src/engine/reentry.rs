@@ -0,0 +1,181 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, Instant};
+use async_trait::async_trait;
+use dashmap::DashMap;
+use once_cell::sync::Lazy;
+
+use crate::engine::buy_filters::BuyFilter;
+use crate::engine::trade_event::TradeEvent;
+use crate::services::telegram_commands;
+
+pub static REENTRY_CONFIG: Lazy<ReentryConfig> = Lazy::new(ReentryConfig::from_env);
+
+static REENTRY_BOOK: Lazy<ReentryBook> = Lazy::new(|| ReentryBook::new(*REENTRY_CONFIG));
+
+/// REENTRY_COOLDOWN_SECS (default: 600) a sold mint waits before it can be bought again; with
+/// NEVER_REBUY (default: false) it is never bought again while the process runs
+#[derive(Clone, Copy, Debug)]
+pub struct ReentryConfig {
+    pub cooldown: Duration,
+    pub never_rebuy: bool,
+}
+
+impl ReentryConfig {
+    pub fn from_env() -> Self {
+        let cooldown_secs = std::env::var("REENTRY_COOLDOWN_SECS")
+            .ok()
+            .and_then(|v| v.parse::<u64>().ok())
+            .unwrap_or(600);
+        let never_rebuy = std::env::var("NEVER_REBUY")
+            .ok()
+            .and_then(|v| v.parse::<bool>().ok())
+            .unwrap_or(false);
+        Self { cooldown: Duration::from_secs(cooldown_secs), never_rebuy }
+    }
+
+    /// Whether a mint sold at `sold_at` is still blocked at `now`
+    pub fn blocks(&self, sold_at: Instant, now: Instant) -> bool {
+        self.never_rebuy || now.saturating_duration_since(sold_at) < self.cooldown
+    }
+}
+
+/// Mints with the time of their last successful sell, and the buys turned down for them
+pub struct ReentryBook {
+    config: ReentryConfig,
+    sold: DashMap<String, Instant>,
+    /// Since startup
+    prevented: AtomicU64,
+}
+
+impl ReentryBook {
+    pub fn new(config: ReentryConfig) -> Self {
+        Self { config, sold: DashMap::new(), prevented: AtomicU64::new(0) }
+    }
+
+    pub fn record_sell(&self, mint: &str, now: Instant) {
+        self.sold.insert(mint.to_string(), now);
+    }
+
+    /// Whether a buy of `mint` may go ahead at `now`; a refusal is counted
+    pub fn check(&self, mint: &str, now: Instant) -> Result<(), String> {
+        let Some(sold_at) = self.sold.get(mint).map(|sold_at| *sold_at) else {
+            return Ok(());
+        };
+        if !self.config.blocks(sold_at, now) {
+            return Ok(());
+        }
+        self.prevented.fetch_add(1, Ordering::Relaxed);
+        if self.config.never_rebuy {
+            Err("sold earlier this session (NEVER_REBUY)".to_string())
+        } else {
+            Err(format!("sold {}s ago, within REENTRY_COOLDOWN_SECS", now.saturating_duration_since(sold_at).as_secs()))
+        }
+    }
+
+    /// Drop mints whose cooldown ended; the number removed. Nothing expires under NEVER_REBUY.
+    pub fn cleanup_expired(&self, now: Instant) -> usize {
+        let initial_count = self.sold.len();
+        self.sold.retain(|_mint, sold_at| self.config.blocks(*sold_at, now));
+        initial_count - self.sold.len()
+    }
+
+    pub fn blocked_mints(&self) -> usize {
+        self.sold.len()
+    }
+
+    pub fn prevented(&self) -> u64 {
+        self.prevented.load(Ordering::Relaxed)
+    }
+}
+
+/// Start the cooldown of `mint` after a successful sell, normal or Jupiter fallback, live or paper
+pub fn record_sell(mint: &str) {
+    REENTRY_BOOK.record_sell(mint, Instant::now());
+}
+
+pub fn prevented_reentries() -> u64 {
+    REENTRY_BOOK.prevented()
+}
+
+/// Drop mints whose cooldown ended; the number removed
+pub fn cleanup_expired() -> usize {
+    REENTRY_BOOK.cleanup_expired(Instant::now())
+}
+
+fn reentry_status_section() -> String {
+    let config = &*REENTRY_CONFIG;
+    let window = if config.never_rebuy { "never rebuy".to_string() } else { format!("{}s cooldown", config.cooldown.as_secs()) };
+    format!("Re-entry: {} sold mint(s) blocked ({}), {} buy(s) prevented", REENTRY_BOOK.blocked_mints(), window, prevented_reentries())
+}
+
+pub fn register_status_section() {
+    telegram_commands::register_status_section("reentry", reentry_status_section);
+}
+
+/// Skip mints sold within REENTRY_COOLDOWN_SECS, or ever with NEVER_REBUY
+pub struct ReentryFilter;
+
+#[async_trait]
+impl BuyFilter for ReentryFilter {
+    fn name(&self) -> &str {
+        "reentry"
+    }
+
+    async fn check(&self, event: &TradeEvent) -> Result<(), String> {
+        REENTRY_BOOK.check(event.mint(), Instant::now())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const COOLDOWN: Duration = Duration::from_secs(600);
+
+    fn book(never_rebuy: bool) -> ReentryBook {
+        ReentryBook::new(ReentryConfig { cooldown: COOLDOWN, never_rebuy })
+    }
+
+    #[test]
+    fn a_sold_mint_waits_out_the_cooldown() {
+        let book = book(false);
+        let sold_at = Instant::now();
+        assert_eq!(book.check("mint", sold_at), Ok(()));
+        book.record_sell("mint", sold_at);
+
+        let refusal = book.check("mint", sold_at + Duration::from_secs(30)).unwrap_err();
+        assert_eq!(refusal, "sold 30s ago, within REENTRY_COOLDOWN_SECS");
+        assert!(book.check("mint", sold_at + COOLDOWN - Duration::from_secs(1)).is_err());
+        assert_eq!(book.check("mint", sold_at + COOLDOWN), Ok(()));
+        assert_eq!(book.check("other", sold_at), Ok(()));
+        assert_eq!(book.prevented(), 2);
+    }
+
+    #[test]
+    fn never_rebuy_blocks_for_the_whole_session() {
+        let book = book(true);
+        let sold_at = Instant::now();
+        book.record_sell("mint", sold_at);
+        let later = sold_at + COOLDOWN * 10;
+        assert_eq!(book.check("mint", later).unwrap_err(), "sold earlier this session (NEVER_REBUY)");
+        assert_eq!(book.cleanup_expired(later), 0);
+        assert_eq!(book.blocked_mints(), 1);
+        assert_eq!(book.prevented(), 1);
+    }
+
+    #[test]
+    fn cleanup_drops_only_mints_past_their_cooldown() {
+        let book = book(false);
+        let start = Instant::now();
+        book.record_sell("old", start);
+        book.record_sell("recent", start + Duration::from_secs(300));
+        assert_eq!(book.cleanup_expired(start + COOLDOWN), 1);
+        assert_eq!(book.blocked_mints(), 1);
+        assert!(book.check("recent", start + COOLDOWN).is_err());
+        assert_eq!(book.check("old", start + COOLDOWN), Ok(()));
+
+        // A rebuy and resell restarts the cooldown
+        book.record_sell("old", start + COOLDOWN);
+        assert!(book.check("old", start + COOLDOWN + Duration::from_secs(1)).is_err());
+    }
+}
@@ -0,0 +1,87 @@
+use solana_transaction_status::{option_serializer::OptionSerializer, EncodedConfirmedTransactionWithStatusMeta};
+use yellowstone_grpc_proto::prelude::{
+    Message, MessageHeader, SubscribeUpdateTransaction, SubscribeUpdateTransactionInfo, TokenBalance, Transaction,
+    TransactionStatusMeta,
+};
+
+use crate::engine::event_stream::decode_trade_events;
+use crate::engine::transaction_parser::TradeInfoFromToken;
+
+/// Rebuild the parts of a stream update the parser reads from a confirmed RPC transaction
+/// Only the signatures, header, static account keys, logs and post token balances are filled in; the
+/// transaction must have been fetched with base64 encoding. None for failed or undecodable transactions.
+pub fn update_from_confirmed(confirmed: &EncodedConfirmedTransactionWithStatusMeta) -> Option<SubscribeUpdateTransaction> {
+    let versioned = confirmed.transaction.transaction.decode()?;
+    let ui_meta = confirmed.transaction.meta.as_ref()?;
+    if ui_meta.err.is_some() {
+        return None;
+    }
+
+    let header = versioned.message.header();
+    let message = Message {
+        header: Some(MessageHeader {
+            num_required_signatures: header.num_required_signatures as u32,
+            num_readonly_signed_accounts: header.num_readonly_signed_accounts as u32,
+            num_readonly_unsigned_accounts: header.num_readonly_unsigned_accounts as u32,
+        }),
+        account_keys: versioned.message.static_account_keys().iter().map(|key| key.to_bytes().to_vec()).collect(),
+        ..Default::default()
+    };
+    let log_messages = match &ui_meta.log_messages {
+        OptionSerializer::Some(logs) => logs.clone(),
+        _ => Vec::new(),
+    };
+    let post_token_balances = match &ui_meta.post_token_balances {
+        OptionSerializer::Some(balances) => balances
+            .iter()
+            .map(|balance| TokenBalance {
+                account_index: balance.account_index as u32,
+                mint: balance.mint.clone(),
+                owner: Option::<String>::from(balance.owner.clone()).unwrap_or_default(),
+                ..Default::default()
+            })
+            .collect(),
+        _ => Vec::new(),
+    };
+    let signature = versioned.signatures.first().map(|sig| sig.as_ref().to_vec()).unwrap_or_default();
+
+    Some(SubscribeUpdateTransaction {
+        slot: confirmed.slot,
+        transaction: Some(SubscribeUpdateTransactionInfo {
+            signature: signature.clone(),
+            is_vote: false,
+            transaction: Some(Transaction {
+                signatures: vec![signature],
+                message: Some(message),
+            }),
+            meta: Some(TransactionStatusMeta {
+                fee: ui_meta.fee,
+                log_messages,
+                post_token_balances,
+                ..Default::default()
+            }),
+            index: 0,
+        }),
+    })
+}
+
+/// Trades in a confirmed transaction, decoded the same way as live stream updates
+/// Unlike the stream path, the slot and signature of every trade are filled in from the transaction.
+pub fn replay_trades(confirmed: &EncodedConfirmedTransactionWithStatusMeta) -> Vec<TradeInfoFromToken> {
+    let Some(update) = update_from_confirmed(confirmed) else {
+        return Vec::new();
+    };
+    let signature = update
+        .transaction
+        .as_ref()
+        .map(|info| bs58::encode(&info.signature).into_string())
+        .unwrap_or_default();
+    decode_trade_events(&update)
+        .into_iter()
+        .map(|mut trade| {
+            trade.slot = update.slot;
+            trade.signature = signature.clone();
+            trade
+        })
+        .collect()
+}
@@ -0,0 +1,353 @@
+use std::collections::BTreeMap;
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use anchor_client::solana_sdk::pubkey::Pubkey;
+use async_trait::async_trait;
+use once_cell::sync::Lazy;
+
+use crate::dex::math;
+use crate::engine::position::{Position, POSITION_BOOK};
+use crate::services::rpc_pool::RpcHandle;
+use crate::services::telegram_commands;
+
+/// How long a read wallet balance is reused; buys inside the window see the same balance
+const BALANCE_CACHE_TTL: Duration = Duration::from_secs(2);
+
+/// Exposure group for positions opened without a recorded creator
+pub const UNKNOWN_CREATOR: &str = "unknown";
+
+/// Caps on open exposure to a single creator; None disables a cap
+#[derive(Clone, Debug, Default)]
+pub struct CreatorExposureLimits {
+    /// Open cost basis per creator (MAX_EXPOSURE_PER_CREATOR_SOL)
+    pub max_sol: Option<f64>,
+    /// Share of total open cost basis per creator, 0-100 (MAX_EXPOSURE_PER_CREATOR_PCT)
+    pub max_pct: Option<f64>,
+    /// Cap for the unknown-creator group (MAX_EXPOSURE_UNKNOWN_CREATOR_SOL, default: `max_sol`)
+    pub unknown_max_sol: Option<f64>,
+    /// Smallest downsized buy worth sending; less headroom rejects (MIN_DOWNSIZED_BUY_SOL, default: 0.001)
+    pub min_buy_sol: f64,
+}
+
+impl CreatorExposureLimits {
+    pub fn from_env() -> Self {
+        let read = |key: &str| {
+            std::env::var(key)
+                .ok()
+                .and_then(|v| v.parse::<f64>().ok())
+                .filter(|v| *v > 0.0)
+        };
+        let max_sol = read("MAX_EXPOSURE_PER_CREATOR_SOL");
+        Self {
+            max_sol,
+            max_pct: read("MAX_EXPOSURE_PER_CREATOR_PCT"),
+            unknown_max_sol: read("MAX_EXPOSURE_UNKNOWN_CREATOR_SOL").or(max_sol),
+            min_buy_sol: read("MIN_DOWNSIZED_BUY_SOL").unwrap_or(0.001),
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.max_sol.is_some() || self.max_pct.is_some() || self.unknown_max_sol.is_some()
+    }
+}
+
+/// Outcome of the creator exposure check for one buy
+#[derive(Clone, Debug, PartialEq)]
+pub enum ExposureDecision {
+    Allow,
+    /// Buy only `sol`; the full size would breach a cap
+    Downsize { sol: f64, reason: String },
+    Reject(String),
+}
+
+/// Grouping key for `coin_creator`
+pub fn creator_key(coin_creator: Option<&str>) -> &str {
+    match coin_creator {
+        Some(creator) if !creator.is_empty() => creator,
+        _ => UNKNOWN_CREATOR,
+    }
+}
+
+/// Cost basis of the tokens still held, using the creator recorded at buy time
+pub fn exposure_by_creator(positions: &[Position]) -> BTreeMap<String, f64> {
+    let mut exposure = BTreeMap::new();
+    for position in positions {
+        *exposure.entry(creator_key(position.coin_creator.as_deref()).to_string()).or_insert(0.0) += position.open_cost_basis();
+    }
+    exposure
+}
+
+/// Whether a `requested_sol` buy of a token by `coin_creator` fits within `limits`, given the open positions
+/// The percentage cap only applies once other creators hold open exposure; with a single creator the SOL cap governs.
+pub fn check_creator_exposure(
+    positions: &[Position],
+    coin_creator: Option<&str>,
+    requested_sol: f64,
+    limits: &CreatorExposureLimits,
+) -> ExposureDecision {
+    let key = creator_key(coin_creator);
+    let exposure = exposure_by_creator(positions);
+    let current = exposure.get(key).copied().unwrap_or(0.0);
+    let others: f64 = exposure.iter().filter(|(creator, _)| creator.as_str() != key).map(|(_, sol)| *sol).sum();
+
+    let sol_cap = if key == UNKNOWN_CREATOR { limits.unknown_max_sol } else { limits.max_sol };
+    let mut headroom = f64::INFINITY;
+    let mut binding = String::new();
+    if let Some(cap) = sol_cap {
+        headroom = cap - current;
+        binding = format!("{:.4} SOL cap", cap);
+    }
+    if let Some(pct) = limits.max_pct.filter(|_| others > 0.0) {
+        // (current + x) / (current + others + x) <= share  =>  x <= (share * others - (1 - share) * current) / (1 - share)
+        let share = (pct / 100.0).clamp(0.0, 1.0);
+        let pct_headroom = if share >= 1.0 {
+            f64::INFINITY
+        } else {
+            (share * others - (1.0 - share) * current) / (1.0 - share)
+        };
+        if pct_headroom < headroom {
+            headroom = pct_headroom;
+            binding = format!("{:.1}% share cap", pct);
+        }
+    }
+
+    if headroom >= requested_sol {
+        return ExposureDecision::Allow;
+    }
+    let reason = format!(
+        "creator {} holds {:.4} SOL open; {} leaves {:.4} SOL of {:.4} requested",
+        key, current, binding, headroom.max(0.0), requested_sol
+    );
+    if headroom >= limits.min_buy_sol {
+        ExposureDecision::Downsize { sol: headroom, reason }
+    } else {
+        ExposureDecision::Reject(reason)
+    }
+}
+
+/// Caps on the whole book; None disables a cap
+#[derive(Clone, Debug, Default)]
+pub struct PortfolioLimits {
+    /// Positions held or being bought at once (MAX_OPEN_POSITIONS)
+    pub max_open_positions: Option<usize>,
+    /// Open cost basis plus buys in flight (MAX_TOTAL_EXPOSURE_SOL)
+    pub max_total_exposure_sol: Option<f64>,
+}
+
+impl PortfolioLimits {
+    pub fn from_env() -> Self {
+        Self {
+            max_open_positions: std::env::var("MAX_OPEN_POSITIONS").ok().and_then(|v| v.parse::<usize>().ok()).filter(|v| *v > 0),
+            max_total_exposure_sol: std::env::var("MAX_TOTAL_EXPOSURE_SOL").ok().and_then(|v| v.parse::<f64>().ok()).filter(|v| *v > 0.0),
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.max_open_positions.is_some() || self.max_total_exposure_sol.is_some()
+    }
+}
+
+/// Buys that passed the portfolio check but aren't in the book yet
+/// Reservations are taken one at a time, each reading the book afresh, so concurrent buys can't both claim the
+/// last slot and a buy landing in the book meanwhile is never missed.
+#[derive(Default)]
+pub struct BuyReservations {
+    /// Held from reading the book until the slot is taken
+    gate: Mutex<()>,
+    count: AtomicUsize,
+    lamports: AtomicU64,
+    /// Set while buys are being refused, so the Telegram note goes out once per episode
+    refusing: AtomicBool,
+}
+
+/// Held until the buy has failed or its position is in the book
+#[must_use]
+pub struct BuyReservation<'a> {
+    reservations: &'a BuyReservations,
+    lamports: u64,
+}
+
+impl Drop for BuyReservation<'_> {
+    fn drop(&mut self) {
+        self.reservations.count.fetch_sub(1, Ordering::AcqRel);
+        self.reservations.lamports.fetch_sub(self.lamports, Ordering::AcqRel);
+    }
+}
+
+pub static BUY_RESERVATIONS: Lazy<BuyReservations> = Lazy::new(BuyReservations::default);
+
+impl BuyReservations {
+    /// Reserve a `amount_sol` buy if it fits `limits` next to the book `positions` reads; Err says which cap it
+    /// would breach
+    pub fn try_reserve(&self, positions: impl FnOnce() -> Vec<Position>, amount_sol: f64, limits: &PortfolioLimits) -> Result<BuyReservation<'_>, String> {
+        let _gate = self.gate.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        // Buys in flight are read before the book: one that lands in between is counted twice, never missed
+        let in_flight = self.count.load(Ordering::Acquire);
+        let in_flight_lamports = self.lamports.load(Ordering::Acquire);
+        let positions = positions();
+        let held = positions.len();
+        let lamports = math::ui_to_raw(amount_sol, 9);
+        let open_lamports = math::ui_to_raw(positions.iter().map(Position::open_cost_basis).sum::<f64>(), 9);
+
+        if let Some(max) = limits.max_open_positions {
+            if held + in_flight >= max {
+                return Err(format!("{} open + {} buying reaches MAX_OPEN_POSITIONS={}", held, in_flight, max));
+            }
+        }
+        if let Some(max) = limits.max_total_exposure_sol {
+            if open_lamports + in_flight_lamports + lamports > math::ui_to_raw(max, 9) {
+                return Err(format!(
+                    "{:.4} SOL open + {:.4} SOL buying + {:.4} SOL exceeds MAX_TOTAL_EXPOSURE_SOL={}",
+                    open_lamports as f64 / 1e9, in_flight_lamports as f64 / 1e9, amount_sol, max
+                ));
+            }
+        }
+        self.count.fetch_add(1, Ordering::AcqRel);
+        self.lamports.fetch_add(lamports, Ordering::AcqRel);
+        self.refusing.store(false, Ordering::Release);
+        Ok(BuyReservation { reservations: self, lamports })
+    }
+
+    /// Record a refused buy; true for the first refusal since the last successful reservation
+    pub fn mark_refused(&self) -> bool {
+        !self.refusing.swap(true, Ordering::AcqRel)
+    }
+}
+
+/// SOL that must stay in the wallet for fees, after a wrap or a jittered buy (MIN_SOL_RESERVE, default: 0.05)
+pub fn min_sol_reserve() -> f64 {
+    std::env::var("MIN_SOL_RESERVE")
+        .ok()
+        .and_then(|v| v.parse::<f64>().ok())
+        .filter(|v| *v >= 0.0)
+        .unwrap_or(0.05)
+}
+
+/// The wallet's native SOL, which buys are paid from
+#[async_trait]
+pub trait WalletBalance: Send + Sync {
+    async fn sol_balance(&self) -> Result<f64, String>;
+}
+
+/// Reads the balance over RPC, reusing it for BALANCE_CACHE_TTL so bursts of buys cost one request
+pub struct RpcWalletBalance {
+    read_rpc: RpcHandle,
+    wallet: Pubkey,
+    cached: Mutex<Option<(Instant, f64)>>,
+}
+
+impl RpcWalletBalance {
+    pub fn new(read_rpc: RpcHandle, wallet: Pubkey) -> Self {
+        Self { read_rpc, wallet, cached: Mutex::new(None) }
+    }
+}
+
+#[async_trait]
+impl WalletBalance for RpcWalletBalance {
+    async fn sol_balance(&self) -> Result<f64, String> {
+        if let Some((read_at, sol)) = *self.cached.lock().unwrap_or_else(|e| e.into_inner()) {
+            if read_at.elapsed() < BALANCE_CACHE_TTL {
+                return Ok(sol);
+            }
+        }
+        let lamports = self.read_rpc
+            .client()
+            .get_balance(&self.wallet)
+            .await
+            .map_err(|e| format!("failed to read the wallet balance: {}", e))?;
+        let sol = lamports as f64 / 1e9;
+        *self.cached.lock().unwrap_or_else(|e| e.into_inner()) = Some((Instant::now(), sol));
+        Ok(sol)
+    }
+}
+
+fn exposure_status_section() -> String {
+    let mut exposure: Vec<(String, f64)> = exposure_by_creator(&POSITION_BOOK.snapshot()).into_iter().collect();
+    if exposure.is_empty() {
+        return "Creator exposure: none".to_string();
+    }
+    exposure.sort_by(|a, b| b.1.total_cmp(&a.1));
+    let total: f64 = exposure.iter().map(|(_, sol)| sol).sum();
+    let mut lines = vec![format!("Creator exposure ({:.4} SOL open):", total)];
+    for (creator, sol) in exposure.iter().take(5) {
+        let share = if total > 0.0 { sol / total * 100.0 } else { 0.0 };
+        lines.push(format!("  {} {:.4} SOL ({:.1}%)", creator, sol, share));
+    }
+    lines.join("\n")
+}
+
+/// Show the top creators by open exposure in /status
+pub fn register_status_section() {
+    telegram_commands::register_status_section("exposure", exposure_status_section);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::position::PositionBook;
+    use crate::engine::test_fixtures::{book_with, lot, pubkey};
+    use crate::engine::transaction_parser::DexType;
+
+    fn positions_cap(max: usize) -> PortfolioLimits {
+        PortfolioLimits { max_open_positions: Some(max), max_total_exposure_sol: None }
+    }
+
+    #[test]
+    fn reservations_count_against_the_caps_until_dropped() {
+        let reservations = BuyReservations::default();
+        let book = book_with(&pubkey(), 1_000.0, 0.5);
+        let limits = PortfolioLimits { max_open_positions: Some(3), max_total_exposure_sol: Some(1.0) };
+
+        let first = reservations.try_reserve(|| book.snapshot(), 0.25, &limits).unwrap();
+        let error = reservations.try_reserve(|| book.snapshot(), 0.3, &limits).err().unwrap();
+        assert!(error.contains("MAX_TOTAL_EXPOSURE_SOL"), "{}", error);
+        let second = reservations.try_reserve(|| book.snapshot(), 0.25, &limits).unwrap();
+        let error = reservations.try_reserve(|| book.snapshot(), 0.0, &limits).err().unwrap();
+        assert!(error.contains("1 open + 2 buying reaches MAX_OPEN_POSITIONS=3"), "{}", error);
+
+        drop(first);
+        drop(second);
+        assert!(reservations.try_reserve(|| book.snapshot(), 0.5, &limits).is_ok());
+    }
+
+    #[test]
+    fn concurrent_buys_never_exceed_the_position_cap() {
+        const MAX: usize = 3;
+        let reservations = BuyReservations::default();
+        let book = PositionBook::new();
+        let limits = positions_cap(MAX);
+        // Reserved and not yet sold, whether still buying or in the book
+        let occupied = AtomicUsize::new(0);
+        let granted = AtomicUsize::new(0);
+
+        std::thread::scope(|scope| {
+            for _ in 0..20 {
+                scope.spawn(|| {
+                    for _ in 0..200 {
+                        let Ok(reservation) = reservations.try_reserve(|| book.snapshot(), 0.1, &limits) else {
+                            std::thread::yield_now();
+                            continue;
+                        };
+                        let now_occupied = occupied.fetch_add(1, Ordering::SeqCst) + 1;
+                        assert!(now_occupied <= MAX, "{} positions open or buying", now_occupied);
+                        granted.fetch_add(1, Ordering::Relaxed);
+
+                        // The buy lands in the book before its reservation is released, then is sold
+                        let mint = pubkey();
+                        book.record_buy(&mint, DexType::PumpFun, None, lot(&format!("buy-{}", mint), 1_000.0, 0.1));
+                        drop(reservation);
+                        std::thread::yield_now();
+                        occupied.fetch_sub(1, Ordering::SeqCst);
+                        book.remove(&mint);
+                    }
+                });
+            }
+        });
+
+        assert!(granted.load(Ordering::Relaxed) > 0);
+        assert!(book.is_empty());
+        assert!(reservations.try_reserve(|| book.snapshot(), 0.1, &limits).is_ok());
+    }
+}
@@ -0,0 +1,245 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+use colored::Colorize;
+use dashmap::DashMap;
+use once_cell::sync::Lazy;
+
+use crate::common::{clock, logger::Logger};
+use crate::engine::sniper::SELL_REASONS;
+
+/// Registry behind the legacy SELL_REASONS map; the free functions below keep both in step
+pub static SELL_REASON_REGISTRY: Lazy<SellReasonRegistry> = Lazy::new(|| SellReasonRegistry::new(sell_reason_ttl_secs()));
+
+/// SELL_REASON_TTL_SECS (default: 600) after which an unconsumed sell reason counts as orphaned
+pub fn sell_reason_ttl_secs() -> u64 {
+    std::env::var("SELL_REASON_TTL_SECS")
+        .ok()
+        .and_then(|v| v.parse::<u64>().ok())
+        .unwrap_or(600)
+}
+
+/// Why a mint is being sold, owned by one sell attempt
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SellReasonEntry {
+    pub reason: String,
+    /// 0 for entries written to SELL_REASONS directly and adopted by expiry
+    pub attempt_id: u64,
+    pub recorded_at: u64,
+    /// Transaction the attempt landed, once known
+    pub signature: Option<String>,
+}
+
+/// Sell reasons with their owning attempt
+/// A new attempt on a mint supersedes the previous reason; failed attempts clear theirs, landed ones wait for the
+/// notification path to take them by signature. Anything left longer than the TTL is expired with a warning, since
+/// it means a notification path broke.
+pub struct SellReasonRegistry {
+    ttl_secs: u64,
+    entries: DashMap<String, SellReasonEntry>,
+    next_attempt: AtomicU64,
+}
+
+impl SellReasonRegistry {
+    pub fn new(ttl_secs: u64) -> Self {
+        Self { ttl_secs, entries: DashMap::new(), next_attempt: AtomicU64::new(1) }
+    }
+
+    /// Record `reason` for a new attempt on `mint`; returns the attempt id
+    pub fn begin(&self, mint: &str, reason: &str, now: u64) -> u64 {
+        let attempt_id = self.next_attempt.fetch_add(1, Ordering::Relaxed);
+        self.entries.insert(mint.to_string(), SellReasonEntry {
+            reason: reason.to_string(),
+            attempt_id,
+            recorded_at: now,
+            signature: None,
+        });
+        attempt_id
+    }
+
+    /// Attach the landed transaction to `attempt_id`; false if a newer attempt owns the mint
+    pub fn bind_signature(&self, mint: &str, attempt_id: u64, signature: &str) -> bool {
+        match self.entries.get_mut(mint) {
+            Some(mut entry) if entry.attempt_id == attempt_id => {
+                entry.signature = Some(signature.to_string());
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Drop the reason of a concluded attempt, unless a newer attempt already superseded it
+    pub fn clear_attempt(&self, mint: &str, attempt_id: u64) -> bool {
+        self.entries.remove_if(mint, |_, entry| entry.attempt_id == attempt_id).is_some()
+    }
+
+    /// Take the reason for `signature`; a reason bound to another transaction is left alone and None returned
+    pub fn take_for_signature(&self, mint: &str, signature: &str) -> Option<String> {
+        self.entries
+            .remove_if(mint, |_, entry| entry.signature.as_deref() == Some(signature))
+            .map(|(_, entry)| entry.reason)
+    }
+
+    pub fn clear(&self, mint: &str) {
+        self.entries.remove(mint);
+    }
+
+    pub fn get(&self, mint: &str) -> Option<SellReasonEntry> {
+        self.entries.get(mint).map(|entry| entry.clone())
+    }
+
+    /// Track an entry that appeared without an attempt, so it can expire too
+    pub fn adopt(&self, mint: &str, reason: &str, now: u64) {
+        self.entries.entry(mint.to_string()).or_insert_with(|| SellReasonEntry {
+            reason: reason.to_string(),
+            attempt_id: 0,
+            recorded_at: now,
+            signature: None,
+        });
+    }
+
+    /// Remove entries older than the TTL at `now`, returning them
+    pub fn expire(&self, now: u64) -> Vec<(String, SellReasonEntry)> {
+        let expired: Vec<(String, SellReasonEntry)> = self
+            .entries
+            .iter()
+            .filter(|entry| now.saturating_sub(entry.recorded_at) > self.ttl_secs)
+            .map(|entry| (entry.key().clone(), entry.value().clone()))
+            .collect();
+        for (mint, entry) in &expired {
+            self.entries.remove_if(mint, |_, current| current.attempt_id == entry.attempt_id);
+        }
+        expired
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
+
+/// Record `reason` for a new sell attempt on `mint`, superseding any earlier one
+pub fn begin_attempt(mint: &str, reason: &str) -> u64 {
+    SELL_REASONS.insert(mint.to_string(), reason.to_string());
+    SELL_REASON_REGISTRY.begin(mint, reason, clock::now_secs())
+}
+
+/// The attempt landed `signature`; its reason stays until the notification path takes it
+pub fn attempt_landed(mint: &str, attempt_id: u64, signature: &str) {
+    SELL_REASON_REGISTRY.bind_signature(mint, attempt_id, signature);
+}
+
+/// The attempt concluded without a transaction; its reason has nothing left to explain
+pub fn attempt_failed(mint: &str, attempt_id: u64) {
+    if SELL_REASON_REGISTRY.clear_attempt(mint, attempt_id) {
+        SELL_REASONS.remove(mint);
+    }
+}
+
+/// Reason recorded for the sell that landed `signature`; None for a reason left over from another attempt
+pub fn take_for_signature(mint: &str, signature: &str) -> Option<String> {
+    let reason = SELL_REASON_REGISTRY.take_for_signature(mint, signature)?;
+    SELL_REASONS.remove(mint);
+    Some(reason)
+}
+
+pub fn clear(mint: &str) {
+    SELL_REASON_REGISTRY.clear(mint);
+    SELL_REASONS.remove(mint);
+}
+
+/// Expire orphaned reasons (cache maintenance); returns how many were removed
+pub fn expire_orphans() -> usize {
+    let now = clock::now_secs();
+    for entry in SELL_REASONS.iter() {
+        SELL_REASON_REGISTRY.adopt(entry.key(), entry.value(), now);
+    }
+    let expired = SELL_REASON_REGISTRY.expire(now);
+    if expired.is_empty() {
+        return 0;
+    }
+    let logger = Logger::new("[SELL-REASONS] => ".yellow().to_string());
+    for (mint, entry) in &expired {
+        SELL_REASONS.remove_if(mint, |_, reason| *reason == entry.reason);
+        logger.error(format!(
+            "⚠️ Expired sell reason \"{}\" for {} after {}s (attempt {}, {}); its notification never consumed it",
+            entry.reason,
+            mint,
+            now.saturating_sub(entry.recorded_at),
+            entry.attempt_id,
+            entry.signature.as_deref().unwrap_or("no transaction")
+        ));
+    }
+    expired.len()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::test_fixtures::pubkey;
+
+    const TTL: u64 = 600;
+
+    #[test]
+    fn orphaned_reasons_expire_after_the_ttl() {
+        let registry = SellReasonRegistry::new(TTL);
+        let (orphan, fresh) = (pubkey(), pubkey());
+        let attempt = registry.begin(&orphan, "Stop loss", 0);
+        registry.bind_signature(&orphan, attempt, "never-notified");
+        registry.begin(&fresh, "Take profit", 500);
+
+        assert!(registry.expire(TTL).is_empty(), "not older than the TTL yet");
+        let expired = registry.expire(TTL + 1);
+        assert_eq!(expired.len(), 1);
+        assert_eq!(expired[0].0, orphan);
+        assert_eq!(expired[0].1.reason, "Stop loss");
+        assert_eq!(expired[0].1.signature.as_deref(), Some("never-notified"));
+        assert!(registry.get(&orphan).is_none());
+        assert!(registry.get(&fresh).is_some());
+    }
+
+    #[test]
+    fn a_stale_reason_is_never_reported_against_a_later_sell() {
+        let registry = SellReasonRegistry::new(TTL);
+        let mint = pubkey();
+        let first = registry.begin(&mint, "Trailing stop", 0);
+        let second = registry.begin(&mint, "Manual sell", 10);
+
+        // The first attempt concluding late touches nothing the second owns
+        assert!(!registry.bind_signature(&mint, first, "first-tx"));
+        assert!(!registry.clear_attempt(&mint, first));
+        assert_eq!(registry.take_for_signature(&mint, "first-tx"), None);
+
+        assert!(registry.bind_signature(&mint, second, "second-tx"));
+        assert_eq!(registry.take_for_signature(&mint, "first-tx"), None);
+        assert_eq!(registry.take_for_signature(&mint, "second-tx"), Some("Manual sell".to_string()));
+        assert!(registry.is_empty());
+    }
+
+    #[test]
+    fn unowned_entries_are_adopted_and_expire_too() {
+        let registry = SellReasonRegistry::new(TTL);
+        let mint = pubkey();
+        registry.adopt(&mint, "Written directly", 0);
+        let attempt = registry.begin(&pubkey(), "Owned", 0);
+        assert_ne!(attempt, 0);
+        assert_eq!(registry.get(&mint).unwrap().attempt_id, 0);
+        assert_eq!(registry.expire(TTL + 1).len(), 2);
+    }
+
+    #[test]
+    fn failed_attempts_clear_the_legacy_map() {
+        let mint = pubkey();
+        let attempt = begin_attempt(&mint, "Stop loss");
+        assert!(SELL_REASONS.contains_key(&mint));
+        attempt_failed(&mint, attempt);
+        assert!(!SELL_REASONS.contains_key(&mint));
+        assert!(SELL_REASON_REGISTRY.get(&mint).is_none());
+
+        let attempt = begin_attempt(&mint, "Take profit");
+        attempt_landed(&mint, attempt, "landed-tx");
+        assert_eq!(take_for_signature(&mint, "landed-tx"), Some("Take profit".to_string()));
+        assert!(!SELL_REASONS.contains_key(&mint));
+    }
+}
@@ -0,0 +1,377 @@
+use std::str::FromStr;
+use std::time::{Duration, Instant};
+use anchor_client::solana_sdk::pubkey::Pubkey;
+use async_trait::async_trait;
+use colored::Colorize;
+use dashmap::DashMap;
+use once_cell::sync::Lazy;
+use spl_associated_token_account::get_associated_token_address_with_program_id;
+
+use crate::common::{cache, clock, logger::Logger};
+use crate::core::mint_inspector;
+use crate::engine::buy_filters::BuyFilter;
+use crate::engine::position::{Lot, PositionBook};
+use crate::engine::position_overrides;
+use crate::engine::trade_event::TradeEvent;
+use crate::engine::transaction_parser::DexType;
+use crate::services::intent_log::{Intent, IntentLog};
+use crate::services::ledger::{Ledger, LedgerEntry};
+use crate::services::rpc_pool::RpcHandle;
+use crate::services::session_report;
+use crate::services::trade_costs::TradeCosts;
+
+/// A send can't land once its blockhash has expired (~150 slots); until then a missing effect is inconclusive
+const LANDING_WINDOW_SECS: u64 = 90;
+/// Pause between verification rounds for intents still inconclusive
+const RETRY_DELAY: Duration = Duration::from_secs(2);
+
+/// Mints whose claims were cleared without a verdict, with the unix time the cooldown ends
+static RECOVERY_COOLDOWNS: Lazy<DashMap<String, u64>> = Lazy::new(DashMap::new);
+
+pub fn in_recovery_cooldown(mint: &str) -> bool {
+    match RECOVERY_COOLDOWNS.get(mint).map(|until| *until) {
+        Some(until) if until > clock::now_secs() => true,
+        Some(_) => {
+            RECOVERY_COOLDOWNS.remove(mint);
+            false
+        }
+        None => false,
+    }
+}
+
+/// Where the wallet's balance of a mint comes from during recovery
+#[async_trait]
+pub trait IntentVerifier: Send + Sync {
+    /// UI balance the wallet holds of `mint`; 0 when the token account doesn't exist
+    async fn token_balance(&self, mint: &str) -> Result<f64, String>;
+}
+
+/// Reads the wallet's associated token account through the read pool
+pub struct RpcIntentVerifier {
+    read_rpc: RpcHandle,
+    wallet: Pubkey,
+}
+
+impl RpcIntentVerifier {
+    pub fn new(read_rpc: RpcHandle, wallet: Pubkey) -> Self {
+        Self { read_rpc, wallet }
+    }
+}
+
+#[async_trait]
+impl IntentVerifier for RpcIntentVerifier {
+    async fn token_balance(&self, mint: &str) -> Result<f64, String> {
+        let mint_pubkey = Pubkey::from_str(mint).map_err(|e| format!("invalid mint {}: {}", mint, e))?;
+        let client = self.read_rpc.client();
+        let info = mint_inspector::inspect_mint(&client, &mint_pubkey)
+            .await
+            .map_err(|e| format!("failed to inspect mint: {}", e))?;
+        let ata = get_associated_token_address_with_program_id(&self.wallet, &mint_pubkey, &info.token_program);
+        match client.get_token_account(&ata).await {
+            Ok(Some(account)) => Ok(account.token_amount.ui_amount.unwrap_or(0.0)),
+            Ok(None) => Ok(0.0),
+            Err(e) => Err(format!("failed to read token account {}: {}", ata, e)),
+        }
+    }
+}
+
+/// What recovery did with one unresolved intent
+#[derive(Clone, Debug, PartialEq)]
+pub enum RecoveryOutcome {
+    /// The buy landed; a position was opened with the balance found on chain
+    BuyLanded { token_amount: f64 },
+    /// The sell landed; the wallet no longer holds the mint
+    SellLanded,
+    /// No effect on chain after the landing window; nothing to convert
+    Dropped,
+    /// No verdict within STARTUP_RECOVERY_TIMEOUT_SECS; claims cleared and the mint put on cooldown
+    Unresolved(String),
+}
+
+impl RecoveryOutcome {
+    fn as_log_outcome(&self) -> String {
+        match self {
+            RecoveryOutcome::BuyLanded { token_amount } => format!("recovered: buy landed ({:.2} tokens)", token_amount),
+            RecoveryOutcome::SellLanded => "recovered: sell landed".to_string(),
+            RecoveryOutcome::Dropped => "recovered: dropped".to_string(),
+            RecoveryOutcome::Unresolved(reason) => format!("recovered: unresolved ({})", reason),
+        }
+    }
+}
+
+/// STARTUP_RECOVERY_TIMEOUT_SECS (default: 30) and RECOVERY_COOLDOWN_SECS (default: 300)
+#[derive(Clone, Copy, Debug)]
+pub struct RecoveryConfig {
+    pub timeout: Duration,
+    pub cooldown_secs: u64,
+}
+
+impl RecoveryConfig {
+    pub fn from_env() -> Self {
+        let timeout_secs = std::env::var("STARTUP_RECOVERY_TIMEOUT_SECS")
+            .ok()
+            .and_then(|v| v.parse::<u64>().ok())
+            .unwrap_or(30);
+        let cooldown_secs = std::env::var("RECOVERY_COOLDOWN_SECS")
+            .ok()
+            .and_then(|v| v.parse::<u64>().ok())
+            .unwrap_or(300);
+        Self { timeout: Duration::from_secs(timeout_secs), cooldown_secs }
+    }
+}
+
+/// One verification round: Some(outcome) once the chain gives a verdict
+/// A buy landed if the wallet holds the mint; a sell landed if it holds none. The opposite only counts as
+/// dropped once the blockhash the intent could have used has expired.
+async fn verify(verifier: &dyn IntentVerifier, intent: &Intent) -> Result<Option<RecoveryOutcome>, String> {
+    let balance = verifier.token_balance(&intent.mint).await?;
+    let expired = clock::now_secs() >= intent.created_at + LANDING_WINDOW_SECS;
+    let outcome = match intent.side.as_str() {
+        "buy" if balance > 0.0 => Some(RecoveryOutcome::BuyLanded { token_amount: balance }),
+        "sell" if balance <= 0.0 => Some(RecoveryOutcome::SellLanded),
+        _ if expired => Some(RecoveryOutcome::Dropped),
+        _ => None,
+    };
+    Ok(outcome)
+}
+
+/// Resolve every intent a crash left open, before trading starts (idempotent: resolved intents are not revisited)
+/// Claims on each mint are cleared whatever the outcome; landed buys become positions with a ledger entry. Intents
+/// with no verdict by `config.timeout` are cleared with a warning and their mints get a cooldown, never a block.
+pub async fn recover_pending_intents(
+    intents: &IntentLog,
+    verifier: &dyn IntentVerifier,
+    positions: &PositionBook,
+    ledger: &dyn Ledger,
+    config: RecoveryConfig,
+) -> Vec<(Intent, RecoveryOutcome)> {
+    let logger = Logger::new("[RECOVERY] => ".yellow().to_string());
+    let mut pending = intents.pending();
+    if pending.is_empty() {
+        return Vec::new();
+    }
+    logger.critical(format!("Resolving {} in-flight trades from the last run", pending.len()));
+
+    let deadline = Instant::now() + config.timeout;
+    let mut resolved = Vec::new();
+    while !pending.is_empty() {
+        let mut still_pending = Vec::new();
+        for intent in pending {
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            match tokio::time::timeout(remaining, verify(verifier, &intent)).await {
+                Ok(Ok(Some(outcome))) => resolved.push((intent, outcome)),
+                Ok(Ok(None)) => still_pending.push((intent, "no effect on chain yet".to_string())),
+                Ok(Err(e)) => still_pending.push((intent, e)),
+                Err(_) => still_pending.push((intent, "verification timed out".to_string())),
+            }
+        }
+        if still_pending.is_empty() {
+            break;
+        }
+        if Instant::now() + RETRY_DELAY >= deadline {
+            for (intent, reason) in still_pending {
+                resolved.push((intent, RecoveryOutcome::Unresolved(reason)));
+            }
+            break;
+        }
+        tokio::time::sleep(RETRY_DELAY).await;
+        pending = still_pending.into_iter().map(|(intent, _)| intent).collect();
+    }
+
+    for (intent, outcome) in &resolved {
+        apply(intent, outcome, positions, ledger, config, &logger);
+        if let Err(e) = intents.resolve(&intent.id, &outcome.as_log_outcome()) {
+            logger.error(format!("Failed to resolve intent {} in {}: {}", intent.id, intents.path(), e));
+        }
+    }
+    if let Err(e) = intents.compact() {
+        logger.error(format!("Failed to compact {}: {}", intents.path(), e));
+    }
+    resolved
+}
+
+fn apply(intent: &Intent, outcome: &RecoveryOutcome, positions: &PositionBook, ledger: &dyn Ledger, config: RecoveryConfig, logger: &Logger) {
+    cache::PROGRESS_ON_BUYING.remove(&intent.mint);
+    cache::PROGRESS_ON_SELLING.remove(&intent.mint);
+
+    match outcome {
+        RecoveryOutcome::BuyLanded { token_amount } => {
+            // Live buys are PumpFun only
+            let price = if *token_amount > 0.0 { intent.sol_amount / token_amount } else { intent.price };
+            positions.record_buy(&intent.mint, DexType::PumpFun, intent.coin_creator.clone(), Lot {
+                signature: String::new(),
+                token_amount: *token_amount,
+                sol_spent: intent.sol_amount,
+                price,
+                timestamp: intent.created_at,
+                costs_sol: 0.0,
+                size_tier: None,
+            });
+            position_overrides::attach(positions, &intent.mint);
+            ledger.record(LedgerEntry {
+                timestamp: intent.created_at,
+                mint: intent.mint.clone(),
+                side: "buy".to_string(),
+                signature: String::new(),
+                token_amount: *token_amount,
+                sol_amount: intent.sol_amount,
+                price,
+                reason: "Recovered buy".to_string(),
+                simulated: false,
+                blockhash_mode: None,
+                session_id: session_report::session_id().to_string(),
+                costs: TradeCosts::default(),
+                imported: false,
+                pre_jitter_sol: None,
+                post_jitter_sol: None,
+                slippage_pct: None,
+                slippage_sol: None,
+            });
+            logger.critical(format!("{} buy landed before the crash; tracking {:.2} tokens", intent.mint, token_amount).green().to_string());
+        }
+        RecoveryOutcome::SellLanded => {
+            logger.critical(format!("{} sell landed before the crash; claims cleared", intent.mint));
+        }
+        RecoveryOutcome::Dropped => {
+            logger.critical(format!("{} {} never landed; claims cleared", intent.mint, intent.side));
+        }
+        RecoveryOutcome::Unresolved(reason) => {
+            RECOVERY_COOLDOWNS.insert(intent.mint.clone(), clock::now_secs() + config.cooldown_secs);
+            logger.error(format!(
+                "⚠️ {} {} unresolved ({}); claims cleared, no buys for {}s",
+                intent.mint, intent.side, reason, config.cooldown_secs
+            ));
+        }
+    }
+}
+
+/// Skip mints cooling down after an unresolved recovery
+pub struct RecoveryCooldownFilter;
+
+#[async_trait]
+impl BuyFilter for RecoveryCooldownFilter {
+    fn name(&self) -> &str {
+        "recovery-cooldown"
+    }
+
+    async fn check(&self, event: &TradeEvent) -> Result<(), String> {
+        if in_recovery_cooldown(event.mint()) {
+            return Err("cooling down after an unresolved in-flight trade".to_string());
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::*;
+    use crate::engine::test_fixtures::pubkey;
+    use crate::services::ledger::MemoryLedger;
+
+    /// Balances by mint; a mint missing from the map fails the read
+    struct FakeVerifier(HashMap<String, f64>);
+
+    #[async_trait]
+    impl IntentVerifier for FakeVerifier {
+        async fn token_balance(&self, mint: &str) -> Result<f64, String> {
+            self.0.get(mint).copied().ok_or_else(|| "rpc unavailable".to_string())
+        }
+    }
+
+    const CONFIG: RecoveryConfig = RecoveryConfig { timeout: Duration::ZERO, cooldown_secs: 300 };
+
+    fn intent(mint: &str, side: &str, age_secs: u64) -> Intent {
+        Intent {
+            id: format!("{}-{}", side, mint),
+            mint: mint.to_string(),
+            side: side.to_string(),
+            sol_amount: 0.1,
+            token_amount: 3_000_000.0,
+            price: 0.1 / 3_000_000.0,
+            coin_creator: Some(pubkey()),
+            created_at: clock::now_secs() - age_secs,
+            session_id: "crashed".to_string(),
+        }
+    }
+
+    fn crashed_with(intents: &[Intent]) -> IntentLog {
+        let path = std::env::temp_dir().join(format!("intents-{}.jsonl", pubkey()));
+        let log = IntentLog::new(path.to_string_lossy().into_owned());
+        for intent in intents {
+            log.begin(intent.clone()).unwrap();
+            cache::PROGRESS_ON_BUYING.insert(intent.mint.clone(), ());
+        }
+        log
+    }
+
+    #[tokio::test]
+    async fn landed_buy_becomes_a_position_and_dropped_buy_is_cleared() {
+        let (landed, dropped) = (pubkey(), pubkey());
+        let log = crashed_with(&[intent(&landed, "buy", 120), intent(&dropped, "buy", 120)]);
+        let verifier = FakeVerifier(HashMap::from([(landed.clone(), 2_900_000.0), (dropped.clone(), 0.0)]));
+        let positions = PositionBook::new();
+        let ledger = MemoryLedger::new();
+
+        let outcomes = recover_pending_intents(&log, &verifier, &positions, &ledger, CONFIG).await;
+
+        assert_eq!(outcomes.len(), 2);
+        assert_eq!(outcomes[0].1, RecoveryOutcome::BuyLanded { token_amount: 2_900_000.0 });
+        assert_eq!(outcomes[1].1, RecoveryOutcome::Dropped);
+        for mint in [&landed, &dropped] {
+            assert!(!cache::PROGRESS_ON_BUYING.contains_key(mint.as_str()));
+            assert!(!in_recovery_cooldown(mint));
+        }
+
+        let position = positions.get(&landed).expect("landed buy is tracked");
+        assert_eq!(position.lots.len(), 1);
+        assert_eq!(position.lots[0].token_amount, 2_900_000.0);
+        assert!((position.entry_price() - 0.1 / 2_900_000.0).abs() < 1e-15);
+        assert!(positions.get(&dropped).is_none());
+
+        let entries = ledger.entries();
+        assert_eq!(entries.len(), 1);
+        assert_eq!((entries[0].mint.as_str(), entries[0].reason.as_str()), (landed.as_str(), "Recovered buy"));
+
+        // Every intent was resolved, so a second start has nothing to do
+        assert!(log.pending().is_empty());
+        assert!(recover_pending_intents(&log, &verifier, &positions, &ledger, CONFIG).await.is_empty());
+        let _ = std::fs::remove_file(log.path());
+    }
+
+    #[tokio::test]
+    async fn sell_without_tokens_left_landed() {
+        let mint = pubkey();
+        let log = crashed_with(&[intent(&mint, "sell", 5)]);
+        cache::PROGRESS_ON_SELLING.insert(mint.clone(), ());
+        let verifier = FakeVerifier(HashMap::from([(mint.clone(), 0.0)]));
+
+        let outcomes = recover_pending_intents(&log, &verifier, &PositionBook::new(), &MemoryLedger::new(), CONFIG).await;
+
+        assert_eq!(outcomes[0].1, RecoveryOutcome::SellLanded);
+        assert!(!cache::PROGRESS_ON_SELLING.contains_key(mint.as_str()));
+        let _ = std::fs::remove_file(log.path());
+    }
+
+    #[tokio::test]
+    async fn inconclusive_intents_are_cleared_with_a_cooldown_at_the_deadline() {
+        // A recent buy with nothing on chain yet, and one whose balance can't be read
+        let (recent, unreadable) = (pubkey(), pubkey());
+        let log = crashed_with(&[intent(&recent, "buy", 5), intent(&unreadable, "buy", 120)]);
+        let verifier = FakeVerifier(HashMap::from([(recent.clone(), 0.0)]));
+        let positions = PositionBook::new();
+
+        let outcomes = recover_pending_intents(&log, &verifier, &positions, &MemoryLedger::new(), CONFIG).await;
+
+        assert_eq!(outcomes[0].1, RecoveryOutcome::Unresolved("no effect on chain yet".to_string()));
+        assert_eq!(outcomes[1].1, RecoveryOutcome::Unresolved("rpc unavailable".to_string()));
+        for mint in [&recent, &unreadable] {
+            assert!(!cache::PROGRESS_ON_BUYING.contains_key(mint.as_str()));
+            assert!(in_recovery_cooldown(mint), "{} should cool down, not stay blocked", mint);
+        }
+        assert_eq!(positions.len(), 0);
+        assert!(log.pending().is_empty());
+        let _ = std::fs::remove_file(log.path());
+    }
+}
@@ -0,0 +1,341 @@
+use std::path::Path;
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::engine::exit_strategy::{evaluate_exit, ExitReason, ExitState, RuntimeParams};
+
+/// One OHLC candle as written by the candle export
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Candle {
+    pub timestamp: u64,
+    pub open: f64,
+    pub high: f64,
+    pub low: f64,
+    pub close: f64,
+}
+
+impl Candle {
+    /// Intra-candle price path: bullish candles visit the low before the high, bearish ones the high first
+    fn price_path(&self) -> [f64; 4] {
+        if self.close >= self.open {
+            [self.open, self.low, self.high, self.close]
+        } else {
+            [self.open, self.high, self.low, self.close]
+        }
+    }
+}
+
+/// Load candles from a JSON array or JSON-lines file, sorted by timestamp
+pub fn load_candles(path: &Path) -> Result<Vec<Candle>> {
+    let contents = std::fs::read_to_string(path)
+        .map_err(|e| anyhow!("Failed to read {}: {}", path.display(), e))?;
+    let mut candles: Vec<Candle> = if contents.trim_start().starts_with('[') {
+        serde_json::from_str(&contents)
+            .map_err(|e| anyhow!("Failed to parse {}: {}", path.display(), e))?
+    } else {
+        contents
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .map(serde_json::from_str::<Candle>)
+            .collect::<Result<_, _>>()
+            .map_err(|e| anyhow!("Failed to parse {}: {}", path.display(), e))?
+    };
+    candles.sort_by_key(|candle| candle.timestamp);
+    Ok(candles)
+}
+
+/// Load a single candle file or every file in a directory, named by file stem
+pub fn load_candle_series(path: &str) -> Result<Vec<(String, Vec<Candle>)>> {
+    let path = Path::new(path);
+    let mut files = if path.is_dir() {
+        std::fs::read_dir(path)?
+            .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+            .filter(|path| path.is_file())
+            .collect::<Vec<_>>()
+    } else {
+        vec![path.to_path_buf()]
+    };
+    // Deterministic order regardless of directory listing order
+    files.sort();
+
+    files
+        .into_iter()
+        .map(|file| {
+            let name = file.file_stem().map(|stem| stem.to_string_lossy().to_string()).unwrap_or_default();
+            load_candles(&file).map(|candles| (name, candles))
+        })
+        .collect()
+}
+
+/// Where the simulated position is opened
+#[derive(Clone, Debug, Default)]
+pub struct Entry {
+    /// Defaults to the open of the entry candle
+    pub price: Option<f64>,
+    /// First candle at or after this timestamp; defaults to the first candle
+    pub timestamp: Option<u64>,
+}
+
+#[derive(Clone, Debug)]
+pub struct SimulatedExit {
+    pub timestamp: u64,
+    pub price: f64,
+    pub reason: ExitReason,
+    pub token_amount: f64,
+    pub sol_received: f64,
+}
+
+#[derive(Clone, Debug)]
+pub struct SimulationResult {
+    pub entry_price: f64,
+    pub entry_timestamp: u64,
+    pub exits: Vec<SimulatedExit>,
+    pub remaining_tokens: f64,
+    pub final_price: f64,
+    /// Realized plus mark-to-market PnL of a 1 SOL position
+    pub pnl_pct: f64,
+}
+
+/// Run the live exit evaluation over a candle series for a 1 SOL position
+pub fn simulate(candles: &[Candle], entry: &Entry, params: &RuntimeParams) -> Option<SimulationResult> {
+    let start = candles
+        .iter()
+        .position(|candle| entry.timestamp.map(|ts| candle.timestamp >= ts).unwrap_or(true))?;
+    let entry_candle = &candles[start];
+    let entry_price = entry.price.unwrap_or(entry_candle.open);
+    if entry_price <= 0.0 {
+        return None;
+    }
+
+    let initial_tokens = 1.0 / entry_price;
+    let mut state = ExitState {
+        entry_price,
+        peak_price: entry_price,
+        initial_tokens,
+        remaining_tokens: initial_tokens,
+        tiers_hit: Vec::new(),
+        held_secs: 0,
+    };
+    let mut exits = Vec::new();
+    let mut final_price = entry_price;
+
+    'candles: for candle in &candles[start..] {
+        for price in candle.price_path() {
+            final_price = price;
+            state.held_secs = candle.timestamp.saturating_sub(entry_candle.timestamp);
+            state.observe_price(price);
+            // Several tiers can fire on the same price, just like consecutive live events
+            while let Some(decision) = evaluate_exit(&state, price, params) {
+                exits.push(SimulatedExit {
+                    timestamp: candle.timestamp,
+                    price,
+                    reason: decision.reason.clone(),
+                    token_amount: decision.token_amount,
+                    sol_received: decision.token_amount * price,
+                });
+                state.apply_decision(&decision);
+                if state.remaining_tokens <= 0.0 {
+                    break 'candles;
+                }
+            }
+        }
+    }
+
+    let realized: f64 = exits.iter().map(|exit| exit.sol_received).sum();
+    let pnl_pct = (realized + state.remaining_tokens * final_price - 1.0) * 100.0;
+
+    Some(SimulationResult {
+        entry_price,
+        entry_timestamp: entry_candle.timestamp,
+        exits,
+        remaining_tokens: state.remaining_tokens,
+        final_price,
+        pnl_pct,
+    })
+}
+
+/// One swept param: `field=start:end:step`
+#[derive(Clone, Debug)]
+pub struct SweepAxis {
+    pub field: String,
+    pub values: Vec<f64>,
+}
+
+impl SweepAxis {
+    pub fn parse(spec: &str) -> Result<Self> {
+        let (field, range) = spec.split_once('=')
+            .ok_or_else(|| anyhow!("Sweep must look like field=start:end:step, got '{}'", spec))?;
+        let parts: Vec<f64> = range
+            .split(':')
+            .map(|part| part.trim().parse::<f64>())
+            .collect::<Result<_, _>>()
+            .map_err(|_| anyhow!("Invalid sweep range '{}'", range))?;
+        let [start, end, step] = parts[..] else {
+            return Err(anyhow!("Sweep range must be start:end:step, got '{}'", range));
+        };
+        if step <= 0.0 || end < start {
+            return Err(anyhow!("Sweep range '{}' must have step > 0 and end >= start", range));
+        }
+        // Integer step count avoids accumulating float error
+        let steps = ((end - start) / step + 1e-9).floor() as usize;
+        let values = (0..=steps).map(|i| start + step * i as f64).collect();
+        Ok(Self { field: field.trim().to_string(), values })
+    }
+}
+
+/// Cartesian product of all sweep axes applied over `base`
+pub fn expand_grid(base: &RuntimeParams, axes: &[SweepAxis]) -> Result<Vec<(String, RuntimeParams)>> {
+    let mut grid = vec![(String::from("base"), base.clone(), serde_json::Map::new())];
+    for axis in axes {
+        let mut next = Vec::new();
+        for (_, _, overrides) in &grid {
+            for value in &axis.values {
+                let mut overrides = overrides.clone();
+                overrides.insert(axis.field.clone(), serde_json::json!(value));
+                let params = base.with_overrides(&serde_json::Value::Object(overrides.clone()))?;
+                let label = overrides
+                    .iter()
+                    .map(|(key, value)| format!("{}={}", key, value))
+                    .collect::<Vec<_>>()
+                    .join(" ");
+                next.push((label, params, overrides));
+            }
+        }
+        grid = next;
+    }
+    Ok(grid.into_iter().map(|(label, params, _)| (label, params)).collect())
+}
+
+/// Options for the --simulate-strategy command
+#[derive(Clone, Debug, Default)]
+pub struct SimulationArgs {
+    pub candles_path: String,
+    pub entry: Entry,
+    pub params_file: Option<String>,
+    pub sweeps: Vec<SweepAxis>,
+}
+
+/// Run every parameter set over every candle series and print exits plus a ranked PnL table
+pub fn run_simulation(args: &SimulationArgs) -> Result<()> {
+    let series = load_candle_series(&args.candles_path)?;
+    if series.is_empty() {
+        return Err(anyhow!("No candle files found at {}", args.candles_path));
+    }
+
+    let mut base = RuntimeParams::from_env();
+    if let Some(params_file) = &args.params_file {
+        base = base.with_override_file(params_file)?;
+    }
+    let grid = expand_grid(&base, &args.sweeps)?;
+
+    let mut ranking: Vec<(String, f64, usize)> = Vec::new();
+    for (label, params) in &grid {
+        println!("=== {} ===", label);
+        let mut total_pnl = 0.0;
+        let mut runs = 0;
+        for (name, candles) in &series {
+            let Some(result) = simulate(candles, &args.entry, params) else {
+                println!("  {}: no entry candle", name);
+                continue;
+            };
+            println!("  {} - entry {:.10} at {}", name, result.entry_price, result.entry_timestamp);
+            for exit in &result.exits {
+                println!(
+                    "    {} {:<16} {:.10}  sold {:.2} tokens for {:.6} SOL",
+                    exit.timestamp, exit.reason.to_string(), exit.price, exit.token_amount, exit.sol_received
+                );
+            }
+            if result.remaining_tokens > 0.0 {
+                println!("    still holding {:.2} tokens, marked at {:.10}", result.remaining_tokens, result.final_price);
+            }
+            println!("    PnL: {:+.2}%", result.pnl_pct);
+            total_pnl += result.pnl_pct;
+            runs += 1;
+        }
+        let mean_pnl = if runs > 0 { total_pnl / runs as f64 } else { 0.0 };
+        ranking.push((label.clone(), mean_pnl, runs));
+    }
+
+    ranking.sort_by(|a, b| b.1.total_cmp(&a.1));
+    println!("\n=== Ranking (mean PnL over {} series) ===", series.len());
+    println!("{:>4}  {:>10}  params", "#", "PnL %");
+    for (rank, (label, mean_pnl, _)) in ranking.iter().enumerate() {
+        println!("{:>4}  {:>+10.2}  {}", rank + 1, mean_pnl, label);
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::exit_strategy::parse_profit_tiers;
+
+    fn candle(timestamp: u64, open: f64, high: f64, low: f64, close: f64) -> Candle {
+        Candle { timestamp, open, high, low, close }
+    }
+
+    fn params(tiers: &str, stop_loss_pct: Option<f64>) -> RuntimeParams {
+        RuntimeParams {
+            profit_tiers: parse_profit_tiers(tiers),
+            trailing_stop_pct: None,
+            stop_loss_pct,
+            ..RuntimeParams::default()
+        }
+    }
+
+    #[test]
+    fn stop_loss_closes_the_position() {
+        let candles = vec![candle(0, 1.0, 1.0, 1.0, 1.0), candle(60, 1.0, 1.0, 0.7, 0.7)];
+        let result = simulate(&candles, &Entry::default(), &params("", Some(20.0))).unwrap();
+        assert_eq!(result.exits.len(), 1);
+        assert!(matches!(result.exits[0].reason, ExitReason::StopLoss));
+        assert_eq!(result.remaining_tokens, 0.0);
+        assert!((result.pnl_pct + 30.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn profit_tier_sells_part_and_marks_the_rest() {
+        let candles = vec![candle(0, 1.0, 1.0, 1.0, 1.0), candle(60, 1.0, 2.0, 1.0, 2.0)];
+        let result = simulate(&candles, &Entry::default(), &params("50:50", None)).unwrap();
+        assert_eq!(result.exits.len(), 1);
+        assert!(matches!(result.exits[0].reason, ExitReason::ProfitTier(0)));
+        assert!((result.remaining_tokens - 0.5).abs() < 1e-9);
+        assert_eq!(result.exits[0].price, 2.0);
+        // Half sold at the high, half marked at the same close
+        assert!((result.pnl_pct - 100.0).abs() < 1e-9, "{}", result.pnl_pct);
+    }
+
+    #[test]
+    fn bearish_candle_visits_the_high_first() {
+        // The high would reach the tier before the low trips the stop
+        let candles = vec![candle(0, 1.0, 1.0, 1.0, 1.0), candle(60, 1.0, 1.6, 0.5, 0.6)];
+        let result = simulate(&candles, &Entry::default(), &params("50:50", Some(20.0))).unwrap();
+        let reasons: Vec<_> = result.exits.iter().map(|exit| exit.reason.clone()).collect();
+        assert!(matches!(reasons[..], [ExitReason::ProfitTier(0), ExitReason::StopLoss]), "{:?}", reasons);
+    }
+
+    #[test]
+    fn entry_timestamp_skips_earlier_candles() {
+        let candles = vec![candle(0, 1.0, 1.0, 1.0, 1.0), candle(60, 2.0, 2.0, 2.0, 2.0)];
+        let entry = Entry { price: None, timestamp: Some(30) };
+        let result = simulate(&candles, &entry, &params("", None)).unwrap();
+        assert_eq!(result.entry_timestamp, 60);
+        assert_eq!(result.entry_price, 2.0);
+        assert!(simulate(&candles, &Entry { price: None, timestamp: Some(61) }, &params("", None)).is_none());
+        assert!(simulate(&candles, &Entry { price: Some(0.0), timestamp: None }, &params("", None)).is_none());
+    }
+
+    #[test]
+    fn sweep_axis_parses_inclusive_ranges() {
+        let axis = SweepAxis::parse("stop_loss_pct=10:30:10").unwrap();
+        assert_eq!(axis.field, "stop_loss_pct");
+        assert_eq!(axis.values, vec![10.0, 20.0, 30.0]);
+        // 0.1 steps do not lose the end to float error
+        assert_eq!(SweepAxis::parse("x=0:1:0.1").unwrap().values.len(), 11);
+        assert!(SweepAxis::parse("x=10:0:1").is_err());
+        assert!(SweepAxis::parse("x=0:1:0").is_err());
+        assert!(SweepAxis::parse("x=0:1").is_err());
+        assert!(SweepAxis::parse("0:1:1").is_err());
+    }
+}
@@ -33,6 +33,8 @@ pub enum SwapProtocol {
     PumpFun,
     #[serde(rename = "pumpswap")]
     PumpSwap,
+    #[serde(rename = "raydium")]
+    RaydiumAmm,
     #[serde(rename = "auto")]
     Auto,
     #[serde(rename = "unknown")]
@@ -0,0 +1,178 @@
+//! Trades, lots, positions and mint accounts for unit tests
+
+use anchor_client::solana_sdk::{account::Account, pubkey::Pubkey};
+use solana_program::program_option::COption;
+use solana_program_pack::Pack;
+use spl_token_2022::extension::{BaseStateWithExtensionsMut, ExtensionType, StateWithExtensionsMut};
+use spl_token_2022::state::Mint;
+use yellowstone_grpc_proto::geyser::SubscribeUpdateTransaction;
+use yellowstone_grpc_proto::prelude::{
+    Message, MessageHeader, SubscribeUpdateTransactionInfo, TokenBalance, Transaction, TransactionStatusMeta, UiTokenAmount,
+};
+
+use crate::common::clock;
+use crate::engine::position::{ExitFill, Lot, PositionBook};
+use crate::engine::trade_event::TradeEvent;
+use crate::engine::transaction_parser::{DexType, TradeInfoFromToken};
+
+/// A fresh base58 pubkey
+pub fn pubkey() -> String {
+    Pubkey::new_unique().to_string()
+}
+
+/// Someone else's PumpFun trade of `mint` at `price`, now
+pub fn trade(mint: &str, is_buy: bool, price: f64) -> TradeInfoFromToken {
+    TradeInfoFromToken {
+        dex_type: DexType::PumpFun,
+        slot: 1,
+        signature: format!("sig-{}", pubkey()),
+        pool_id: String::new(),
+        mint: mint.to_string(),
+        timestamp: clock::now_secs(),
+        is_buy,
+        post_current_price: price,
+        pre_current_price: price,
+        is_reverse_when_pump_swap: false,
+        coin_creator: Some(pubkey()),
+        sol_change: if is_buy { 1.0 } else { -1.0 },
+        target_transaction_token_change: if is_buy { 1_000.0 } else { -1_000.0 },
+        token_decimals: 6,
+        liquidity: 30.0,
+        virtual_sol_reserves: 30_000_000_000,
+        virtual_token_reserves: 1_073_000_000_000_000,
+        buy_sell_in_same_tx: false,
+        trader: pubkey(),
+        is_own_trade: false,
+        timestamp_suspect: false,
+        gap_replayed: false,
+        curve_complete: false,
+        suspected_mev: false,
+    }
+}
+
+pub fn event(trade_info: TradeInfoFromToken) -> TradeEvent {
+    TradeEvent::from_trade_info(trade_info).expect("fixture mints are valid pubkeys")
+}
+
+pub fn lot(signature: &str, token_amount: f64, sol_spent: f64) -> Lot {
+    Lot {
+        signature: signature.to_string(),
+        token_amount,
+        sol_spent,
+        price: sol_spent / token_amount,
+        timestamp: clock::now_secs(),
+        costs_sol: 0.0,
+        size_tier: None,
+    }
+}
+
+pub fn exit(signature: &str, token_amount: f64, sol_received: f64, reason: &str) -> ExitFill {
+    ExitFill {
+        signature: signature.to_string(),
+        token_amount,
+        sol_received,
+        price: sol_received / token_amount,
+        reason: reason.to_string(),
+        timestamp: clock::now_secs(),
+        costs_sol: 0.0,
+    }
+}
+
+/// A stream update logging `instruction`, with a post balance of `mint` at `decimals` when given
+pub fn stream_update(mint: &str, instruction: &str, decimals: Option<u32>) -> SubscribeUpdateTransaction {
+    let post_token_balances = decimals
+        .map(|decimals| TokenBalance {
+            mint: mint.to_string(),
+            ui_token_amount: Some(UiTokenAmount { decimals, ..Default::default() }),
+            ..Default::default()
+        })
+        .into_iter()
+        .collect();
+    SubscribeUpdateTransaction {
+        slot: 1,
+        transaction: Some(SubscribeUpdateTransactionInfo {
+            meta: Some(TransactionStatusMeta {
+                log_messages: vec![format!("Program log: Instruction: {}", instruction)],
+                post_token_balances,
+                ..Default::default()
+            }),
+            ..Default::default()
+        }),
+    }
+}
+
+/// `txn` signed by `keys[..signers]`, the first key paying the fee
+pub fn signed_by(mut txn: SubscribeUpdateTransaction, keys: &[Pubkey], signers: u32) -> SubscribeUpdateTransaction {
+    txn.transaction.as_mut().expect("fixture updates carry a transaction").transaction = Some(Transaction {
+        signatures: Vec::new(),
+        message: Some(Message {
+            header: Some(MessageHeader { num_required_signatures: signers, ..Default::default() }),
+            account_keys: keys.iter().map(|key| key.to_bytes().to_vec()).collect(),
+            ..Default::default()
+        }),
+    });
+    txn
+}
+
+/// A 368-byte PumpSwap event; an all-zero coin creator marks a reverse (WSOL base) pool
+pub fn pump_swap_event(base_amount: u64, quote_amount: u64, pool_base: u64, pool_quote: u64, reverse: bool) -> Vec<u8> {
+    let mut buffer = vec![0u8; 368];
+    buffer[16..24].copy_from_slice(&clock::now_secs().to_le_bytes());
+    buffer[24..32].copy_from_slice(&base_amount.to_le_bytes());
+    buffer[56..64].copy_from_slice(&pool_base.to_le_bytes());
+    buffer[64..72].copy_from_slice(&pool_quote.to_le_bytes());
+    buffer[72..80].copy_from_slice(&quote_amount.to_le_bytes());
+    buffer[128..160].copy_from_slice(&[7u8; 32]);
+    if !reverse {
+        buffer[320..352].copy_from_slice(&[9u8; 32]);
+    }
+    buffer
+}
+
+/// A 274-byte PumpFun trade event of `mint` leaving the curve at the given virtual reserves
+pub fn pump_fun_event(mint: &Pubkey, sol_amount: u64, token_amount: u64, is_buy: bool, virtual_sol: u64, virtual_tokens: u64) -> Vec<u8> {
+    let mut buffer = vec![0u8; 274];
+    buffer[16..48].copy_from_slice(mint.as_ref());
+    buffer[48..56].copy_from_slice(&sol_amount.to_le_bytes());
+    buffer[56..64].copy_from_slice(&token_amount.to_le_bytes());
+    buffer[64] = is_buy as u8;
+    buffer[97..105].copy_from_slice(&clock::now_secs().to_le_bytes());
+    buffer[105..113].copy_from_slice(&virtual_sol.to_le_bytes());
+    buffer[113..121].copy_from_slice(&virtual_tokens.to_le_bytes());
+    buffer[121..129].copy_from_slice(&(virtual_sol / 4).to_le_bytes());
+    buffer[129..137].copy_from_slice(&(virtual_tokens / 2).to_le_bytes());
+    buffer[185..217].copy_from_slice(&[5u8; 32]);
+    buffer
+}
+
+/// A book holding one Open PumpFun position of `mint`
+pub fn book_with(mint: &str, token_amount: f64, sol_spent: f64) -> PositionBook {
+    let book = PositionBook::new();
+    book.record_buy(mint, DexType::PumpFun, Some(pubkey()), lot(&format!("buy-{}", mint), token_amount, sol_spent));
+    book
+}
+
+/// A 6-decimal mint account owned by `owner`, classic layout
+pub fn mint_account(owner: Pubkey, freeze_authority: Option<Pubkey>) -> Account {
+    let mint = Mint {
+        mint_authority: COption::None,
+        supply: 1_000_000_000_000,
+        decimals: 6,
+        is_initialized: true,
+        freeze_authority: freeze_authority.map(COption::Some).unwrap_or(COption::None),
+    };
+    let mut data = vec![0; Mint::LEN];
+    Mint::pack(mint, &mut data).unwrap();
+    Account { lamports: 1, data, owner, executable: false, rent_epoch: 0 }
+}
+
+/// A Token-2022 mint account carrying `extensions`, set up by `init`
+pub fn token_2022_mint(extensions: &[ExtensionType], init: impl FnOnce(&mut StateWithExtensionsMut<Mint>)) -> Account {
+    let mut data = vec![0; ExtensionType::try_calculate_account_len::<Mint>(extensions).unwrap()];
+    let mut state = StateWithExtensionsMut::<Mint>::unpack_uninitialized(&mut data).unwrap();
+    init(&mut state);
+    state.base = Mint { decimals: 6, is_initialized: true, ..Mint::default() };
+    state.pack_base();
+    state.init_account_type().unwrap();
+    Account { lamports: 1, data, owner: spl_token_2022::ID, executable: false, rent_epoch: 0 }
+}
@@ -0,0 +1,221 @@
+use std::str::FromStr;
+use std::sync::Arc;
+use solana_sdk::pubkey::Pubkey;
+
+use crate::engine::transaction_parser::{is_own_trader, DexType, TradeInfoFromToken};
+
+/// Fixed-size part of a trade that every hot decision path reads
+/// Copy, so per-tick handling of held tokens never clones a String.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct TradeCore {
+    pub dex_type: DexType,
+    pub slot: u64,
+    pub timestamp: u64,
+    pub mint: Pubkey,
+    /// Fee payer, None if the message was unavailable
+    pub trader: Option<Pubkey>,
+    /// Made by one of our wallets, see `transaction_parser::register_own_wallet`
+    pub is_own_trade: bool,
+    /// Implausible event timestamp; such events can move prices but never open positions
+    pub timestamp_suspect: bool,
+    /// Replayed from RPC history after a stream gap; buys on it face the gap staleness guard
+    pub gap_replayed: bool,
+    /// PumpFun curve left without real token reserves by this trade, see `TradeInfoFromToken::curve_complete`
+    pub curve_complete: bool,
+    /// Signer traded the mint both ways within a slot, see `TradeInfoFromToken::suspected_mev`
+    pub suspected_mev: bool,
+    pub is_buy: bool,
+    pub is_reverse_when_pump_swap: bool,
+    pub buy_sell_in_same_tx: bool,
+    pub post_current_price: f64,
+    pub pre_current_price: f64,
+    /// Same sign convention as `TradeInfoFromToken::sol_change`
+    pub sol_change: f64,
+    /// Same sign convention as `TradeInfoFromToken::target_transaction_token_change`
+    pub target_transaction_token_change: f64,
+    /// See `TradeInfoFromToken::token_decimals`
+    pub token_decimals: u8,
+    pub liquidity: f64,
+    pub virtual_sol_reserves: u64,
+    pub virtual_token_reserves: u64,
+}
+
+impl TradeCore {
+    /// Whether the trade was made by `wallet`
+    pub fn is_from(&self, wallet: &Pubkey) -> bool {
+        self.trader.as_ref() == Some(wallet)
+    }
+}
+
+/// Variable-size part of a trade, only read on execution, notification and logging
+#[derive(Clone, Debug, PartialEq)]
+pub struct TradeExtras {
+    /// base58 mint as parsed; map keys (positions, caches) use this
+    pub mint: String,
+    pub signature: String,
+    pub pool_id: String,
+    pub coin_creator: Option<String>,
+    /// base58 fee payer, empty if unknown
+    pub trader: String,
+}
+
+/// A parsed trade as it moves through the pipeline: clone cost is a memcpy plus one refcount
+#[derive(Clone, Debug)]
+pub struct TradeEvent {
+    pub core: TradeCore,
+    pub extras: Arc<TradeExtras>,
+}
+
+impl TradeEvent {
+    /// Split a parser result; None if the mint isn't a valid pubkey
+    pub fn from_trade_info(trade_info: TradeInfoFromToken) -> Option<Self> {
+        let mint = Pubkey::from_str(&trade_info.mint).ok()?;
+        let trader = Pubkey::from_str(&trade_info.trader).ok();
+        // Re-checked for events that didn't come through the parser (scripted sources)
+        let is_own_trade = trade_info.is_own_trade || is_own_trader(&trade_info.trader);
+        Some(Self {
+            core: TradeCore {
+                dex_type: trade_info.dex_type,
+                slot: trade_info.slot,
+                timestamp: trade_info.timestamp,
+                mint,
+                trader,
+                is_own_trade,
+                timestamp_suspect: trade_info.timestamp_suspect,
+                gap_replayed: trade_info.gap_replayed,
+                curve_complete: trade_info.curve_complete,
+                suspected_mev: trade_info.suspected_mev,
+                is_buy: trade_info.is_buy,
+                is_reverse_when_pump_swap: trade_info.is_reverse_when_pump_swap,
+                buy_sell_in_same_tx: trade_info.buy_sell_in_same_tx,
+                post_current_price: trade_info.post_current_price,
+                pre_current_price: trade_info.pre_current_price,
+                sol_change: trade_info.sol_change,
+                target_transaction_token_change: trade_info.target_transaction_token_change,
+                token_decimals: trade_info.token_decimals,
+                liquidity: trade_info.liquidity,
+                virtual_sol_reserves: trade_info.virtual_sol_reserves,
+                virtual_token_reserves: trade_info.virtual_token_reserves,
+            },
+            extras: Arc::new(TradeExtras {
+                mint: trade_info.mint,
+                signature: trade_info.signature,
+                pool_id: trade_info.pool_id,
+                coin_creator: trade_info.coin_creator,
+                trader: trade_info.trader,
+            }),
+        })
+    }
+
+    /// base58 mint without allocating
+    pub fn mint(&self) -> &str {
+        &self.extras.mint
+    }
+
+    pub fn coin_creator(&self) -> Option<&str> {
+        self.extras.coin_creator.as_deref()
+    }
+
+    /// Combined view for the executor, DEX builders and the legacy sniper
+    pub fn to_trade_info(&self) -> TradeInfoFromToken {
+        TradeInfoFromToken {
+            dex_type: self.core.dex_type,
+            slot: self.core.slot,
+            signature: self.extras.signature.clone(),
+            pool_id: self.extras.pool_id.clone(),
+            mint: self.extras.mint.clone(),
+            timestamp: self.core.timestamp,
+            is_buy: self.core.is_buy,
+            post_current_price: self.core.post_current_price,
+            pre_current_price: self.core.pre_current_price,
+            is_reverse_when_pump_swap: self.core.is_reverse_when_pump_swap,
+            coin_creator: self.extras.coin_creator.clone(),
+            sol_change: self.core.sol_change,
+            target_transaction_token_change: self.core.target_transaction_token_change,
+            token_decimals: self.core.token_decimals,
+            liquidity: self.core.liquidity,
+            virtual_sol_reserves: self.core.virtual_sol_reserves,
+            virtual_token_reserves: self.core.virtual_token_reserves,
+            buy_sell_in_same_tx: self.core.buy_sell_in_same_tx,
+            trader: self.extras.trader.clone(),
+            is_own_trade: self.core.is_own_trade,
+            timestamp_suspect: self.core.timestamp_suspect,
+            gap_replayed: self.core.gap_replayed,
+            curve_complete: self.core.curve_complete,
+            suspected_mev: self.core.suspected_mev,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::test_fixtures::{pubkey, pump_fun_event, pump_swap_event, signed_by, stream_update, trade};
+    use crate::engine::transaction_parser::parse_transaction_data;
+
+    /// Parser output for the fixture events, as the pipeline received it before the split
+    fn parsed_fixtures() -> Vec<TradeInfoFromToken> {
+        let mint = Pubkey::new_unique();
+        let signer = Pubkey::new_unique();
+        let fixtures = [
+            (stream_update(&mint.to_string(), "Buy", Some(9)), pump_swap_event(1_000_000, 10_000, 1_000_000_000, 100_000_000, false)),
+            (stream_update(&mint.to_string(), "Sell", Some(6)), pump_swap_event(10_000, 1_000_000, 100_000_000, 1_000_000_000, true)),
+            (stream_update(&mint.to_string(), "Buy", None), pump_fun_event(&mint, 100_000_000, 3_000_000_000, true, 30_000_000_000, 1_073_000_000_000_000)),
+            (stream_update(&mint.to_string(), "Sell", None), pump_fun_event(&mint, 50_000_000, 1_000_000_000, false, 30_000_000_000, 1_073_000_000_000_000)),
+        ];
+        fixtures
+            .into_iter()
+            .map(|(txn, buffer)| {
+                let txn = signed_by(txn, &[signer], 1);
+                let mut trade_info = parse_transaction_data(&txn, &buffer).expect("fixture events parse");
+                trade_info.signature = format!("sig-{}", pubkey());
+                trade_info
+            })
+            .collect()
+    }
+
+    #[test]
+    fn combined_view_equals_the_parser_output() {
+        for trade_info in parsed_fixtures() {
+            let event = TradeEvent::from_trade_info(trade_info.clone()).expect("valid mint");
+            assert_eq!(event.to_trade_info(), trade_info);
+            assert_eq!(event.mint(), trade_info.mint);
+            assert_eq!(event.core.trader.map(|trader| trader.to_string()), Some(trade_info.trader.clone()));
+        }
+    }
+
+    #[test]
+    fn invalid_mint_is_dropped_and_unknown_trader_kept_empty() {
+        let mut trade_info = trade(&pubkey(), true, 1e-6);
+        trade_info.trader = String::new();
+        let event = TradeEvent::from_trade_info(trade_info.clone()).unwrap();
+        assert_eq!(event.core.trader, None);
+        assert!(!event.core.is_from(&Pubkey::new_unique()));
+        assert_eq!(event.to_trade_info(), trade_info);
+
+        trade_info.mint = "not a pubkey".to_string();
+        assert!(TradeEvent::from_trade_info(trade_info).is_none());
+    }
+
+    #[test]
+    fn registered_wallets_are_tagged_for_scripted_events() {
+        let wallet = Pubkey::new_unique();
+        crate::engine::transaction_parser::register_own_wallet(&wallet);
+        let mut trade_info = trade(&pubkey(), true, 1e-6);
+        trade_info.trader = wallet.to_string();
+        assert!(!trade_info.is_own_trade);
+
+        let event = TradeEvent::from_trade_info(trade_info).unwrap();
+        assert!(event.core.is_own_trade);
+        assert!(event.core.is_from(&wallet));
+        assert!(event.to_trade_info().is_own_trade);
+    }
+
+    #[test]
+    fn clones_share_the_extras() {
+        let event = TradeEvent::from_trade_info(trade(&pubkey(), false, 1e-6)).unwrap();
+        let copy = event.clone();
+        assert!(Arc::ptr_eq(&event.extras, &copy.extras));
+        assert_eq!(copy.core, event.core);
+    }
+}
@@ -1,8 +1,10 @@
 use bs58;
+use std::collections::HashSet;
 use std::str::FromStr;
+use std::sync::RwLock;
 use solana_sdk::pubkey::Pubkey;
 use colored::Colorize;
-use crate::common::logger::Logger;
+use crate::common::{clock, logger::Logger};
 use lazy_static;
 use yellowstone_grpc_proto::geyser::SubscribeUpdateTransaction;
 // Import PUMP_FUN_PROGRAM instead of PUMP_PROGRAM
@@ -11,15 +13,17 @@ lazy_static::lazy_static! {
     static ref LOGGER: Logger = Logger::new("[PARSER] => ".blue().to_string());
 }
 
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone, Copy, Debug, PartialEq)]
 pub enum DexType {
     PumpSwap,
     PumpFun,
+    RaydiumAmm,
     Unknown,
 }
 
 
-#[derive(Clone, Debug)]
+/// Full parser output; the pipeline carries it split into `trade_event::TradeEvent`
+#[derive(Clone, Debug, PartialEq)]
 pub struct TradeInfoFromToken {
     // Common fields
     pub dex_type: DexType,
@@ -33,17 +37,36 @@ pub struct TradeInfoFromToken {
     pub pre_current_price: f64,
     pub is_reverse_when_pump_swap: bool,
     pub coin_creator: Option<String>,
+    /// SOL the trader put into the pool, in SOL: positive for buys, negative for sells
+    /// (the "net buy" sign every volume/net-buy accumulator sums)
     pub sol_change: f64,
+    /// Tokens the trader received, in UI units: positive for buys, negative for sells
     pub target_transaction_token_change: f64,
+    /// Decimals of the raw token amounts in `virtual_token_reserves`: the mint's own for PumpFun and PumpSwap,
+    /// 6 for Raydium, whose reserves are rescaled to it
+    pub token_decimals: u8,
     pub liquidity: f64,  // this is for filtering out small trades
     pub virtual_sol_reserves: u64,
     pub virtual_token_reserves: u64,
     pub buy_sell_in_same_tx: bool,
+    /// Fee payer (first signer) of the transaction, base58; empty if the message was unavailable
+    pub trader: String,
+    /// `trader` is one of our registered wallets; such fills must not feed triggers, counters or exits
+    pub is_own_trade: bool,
+    /// `timestamp` is hours old or in the future by the corrected clock, see `clock::observe_event_timestamp`
+    pub timestamp_suspect: bool,
+    /// Recovered from RPC history after a stream gap rather than seen live, see `engine::replay`
+    pub gap_replayed: bool,
+    /// PumpFun only: this trade left no real token reserves, so the curve is complete and migrates to PumpSwap
+    pub curve_complete: bool,
+    /// The signer traded this mint the other way in the same or an adjacent slot (sandwich / wash leg),
+    /// see `mev_tracker::flag_suspected_mev`
+    pub suspected_mev: bool,
     // always  is_token_2022: bool,
 }
 
 /// Previous transaction tracking information for detecting same-trader transactions
-/// Cache is updated in real-time during gRPC streaming (no expiration needed)
+/// Kept per (signer, mint) over the last MEV_TRACKER_SLOTS slots by `mev_tracker`
 #[derive(Clone, Debug)]
 pub struct PreviousTransactionTrackingInfo {
     pub dex_type: DexType,
@@ -54,6 +77,163 @@ pub struct PreviousTransactionTrackingInfo {
     pub sol_change: f64,
 }
 
+const LAMPORTS_PER_SOL_F64: f64 = 1_000_000_000.0;
+/// Decimals of every PumpFun mint, the fallback for a PumpSwap mint whose decimals the transaction doesn't show,
+/// and the scale Raydium amounts are rescaled to
+pub const PUMP_TOKEN_DECIMALS: u8 = 6;
+
+fn raw_to_ui(raw: u64, decimals: u8) -> f64 {
+    raw as f64 / 10f64.powi(decimals as i32)
+}
+
+/// SOL per UI token of `sol_raw` lamports against `token_raw` raw units; 0 for an empty side
+fn price_per_token(sol_raw: u64, token_raw: u64, decimals: u8) -> f64 {
+    if sol_raw == 0 || token_raw == 0 {
+        return 0.0;
+    }
+    sol_raw as f64 / LAMPORTS_PER_SOL_F64 / raw_to_ui(token_raw, decimals)
+}
+
+/// Decimals of `mint` from the transaction's token balances, else from an earlier mint read
+fn mint_decimals(txn: &SubscribeUpdateTransaction, mint: &str) -> Option<u8> {
+    let meta = txn.transaction.as_ref()?.meta.as_ref()?;
+    meta.post_token_balances.iter()
+        .chain(meta.pre_token_balances.iter())
+        .find(|balance| balance.mint == mint)
+        .and_then(|balance| balance.ui_token_amount.as_ref())
+        .and_then(|amount| u8::try_from(amount.decimals).ok())
+        .or_else(|| known_token_decimals(mint))
+}
+
+/// Decimals of `mint` if a mint read already established them
+pub fn known_token_decimals(mint: &str) -> Option<u8> {
+    let mint = Pubkey::from_str(mint).ok()?;
+    crate::core::mint_inspector::cached_mint_info(&mint).map(|info| info.decimals)
+}
+
+/// (sol_change, token_change) of a PumpSwap event using the TradeInfoFromToken convention
+/// `base_amount` is the event's base in/out field and `quote_amount` its quote in/out field.
+/// Normally base is the token and quote is WSOL; in the reverse case base is WSOL and quote the token.
+fn pump_swap_changes(is_reverse: bool, is_buy: bool, base_amount: u64, quote_amount: u64, decimals: u8) -> (f64, f64) {
+    let (sol_raw, token_raw) = if is_reverse {
+        (base_amount, quote_amount)
+    } else {
+        (quote_amount, base_amount)
+    };
+    let sol = sol_raw as f64 / LAMPORTS_PER_SOL_F64;
+    let tokens = raw_to_ui(token_raw, decimals);
+    if is_buy {
+        (sol, tokens)
+    } else {
+        (-sol, -tokens)
+    }
+}
+
+const WSOL_MINT: &str = "So11111111111111111111111111111111111111112";
+/// `ray_log` event types (Raydium AMM v4 `LogType`)
+const RAY_LOG_SWAP_BASE_IN: u8 = 3;
+const RAY_LOG_SWAP_BASE_OUT: u8 = 4;
+/// Raydium `SwapDirection`: the coin side goes into the pool
+const RAY_DIRECTION_COIN_TO_PC: u64 = 2;
+
+/// The Raydium pool vaults a swap moved, read from the transaction's token balances
+struct RaydiumVaults {
+    mint: String,
+    decimals: u32,
+    /// The WSOL vault grew, i.e. the trader paid SOL and bought the token
+    sol_in: bool,
+}
+
+/// Token and WSOL vaults owned by the Raydium AMM authority
+fn raydium_vaults(txn: &SubscribeUpdateTransaction) -> Option<RaydiumVaults> {
+    let meta = txn.transaction.as_ref()?.meta.as_ref()?;
+    let raw = |balance: &yellowstone_grpc_proto::prelude::TokenBalance| {
+        balance.ui_token_amount.as_ref().and_then(|amount| amount.amount.parse::<u64>().ok()).unwrap_or(0)
+    };
+    let vaults: Vec<_> = meta.post_token_balances.iter()
+        .filter(|balance| balance.owner == crate::dex::raydium::RAYDIUM_AMM_AUTHORITY)
+        .collect();
+    let sol_vault = vaults.iter().find(|balance| balance.mint == WSOL_MINT)?;
+    let token_vault = vaults.iter().find(|balance| balance.mint != WSOL_MINT)?;
+    let sol_before = meta.pre_token_balances.iter()
+        .find(|balance| balance.account_index == sol_vault.account_index)
+        .map(raw)
+        .unwrap_or(0);
+    Some(RaydiumVaults {
+        mint: token_vault.mint.clone(),
+        decimals: token_vault.ui_token_amount.as_ref().map(|amount| amount.decimals).unwrap_or(6),
+        sol_in: raw(sol_vault) > sol_before,
+    })
+}
+
+/// AMM account of the first Raydium v4 instruction (outer or CPI): account 1 of a swap
+fn raydium_pool_id(txn: &SubscribeUpdateTransaction) -> Option<String> {
+    let tx_inner = txn.transaction.as_ref()?;
+    let message = tx_inner.transaction.as_ref()?.message.as_ref()?;
+    let meta = tx_inner.meta.as_ref()?;
+    let keys: Vec<&Vec<u8>> = message.account_keys.iter()
+        .chain(meta.loaded_writable_addresses.iter())
+        .chain(meta.loaded_readonly_addresses.iter())
+        .collect();
+    let is_raydium = |program_index: u32| {
+        keys.get(program_index as usize)
+            .map(|key| bs58::encode(key).into_string() == crate::dex::raydium::RAYDIUM_AMM_PROGRAM)
+            .unwrap_or(false)
+    };
+    let outer = message.instructions.iter().map(|ix| (ix.program_id_index, &ix.accounts));
+    let inner = meta.inner_instructions.iter()
+        .flat_map(|set| set.instructions.iter())
+        .map(|ix| (ix.program_id_index, &ix.accounts));
+    let (_, accounts) = outer.chain(inner).find(|(program_index, accounts)| is_raydium(*program_index) && accounts.len() > 1)?;
+    keys.get(accounts[1] as usize).map(|key| bs58::encode(key).into_string())
+}
+
+/// Token raw units rescaled to 6 decimals so Raydium reserves share the PumpFun/PumpSwap convention
+fn to_ui6_units(raw: u64, decimals: u32) -> u64 {
+    if decimals >= 6 {
+        raw / 10u64.pow((decimals - 6).min(19))
+    } else {
+        raw.saturating_mul(10u64.pow(6 - decimals))
+    }
+}
+
+/// Fee payer of the transaction: the first account key, which is always a signer
+/// With several signers (e.g. a relayer co-signing) the fee payer is still the first key.
+pub fn extract_trader(txn: &SubscribeUpdateTransaction) -> Option<String> {
+    let message = txn.transaction.as_ref()?.transaction.as_ref()?.message.as_ref()?;
+    let num_signers = message.header.as_ref().map(|header| header.num_required_signatures).unwrap_or(1);
+    if num_signers == 0 {
+        return None;
+    }
+    let fee_payer = message.account_keys.first()?;
+    if fee_payer.len() != 32 {
+        return None;
+    }
+    Some(bs58::encode(fee_payer).into_string())
+}
+
+lazy_static::lazy_static! {
+    /// base58 pubkeys of every wallet we trade from
+    static ref OWN_WALLETS: RwLock<HashSet<String>> = RwLock::new(HashSet::new());
+}
+
+/// Tag trades signed by `wallet` as own trades from now on
+pub fn register_own_wallet(wallet: &Pubkey) {
+    if let Ok(mut wallets) = OWN_WALLETS.write() {
+        wallets.insert(wallet.to_string());
+    }
+}
+
+/// Whether `trader` (base58) is one of our registered wallets
+pub fn is_own_trader(trader: &str) -> bool {
+    !trader.is_empty() && OWN_WALLETS.read().map(|wallets| wallets.contains(trader)).unwrap_or(false)
+}
+
+/// Whether the trade was made by `wallet` (our own fill rather than external activity)
+pub fn is_our_wallet(trade_info: &TradeInfoFromToken, wallet: &Pubkey) -> bool {
+    !trade_info.trader.is_empty() && trade_info.trader == wallet.to_string()
+}
+
 /// Helper function to check if transaction contains Buy instruction
 fn has_buy_instruction(txn: &SubscribeUpdateTransaction) -> bool {
     if let Some(tx_inner) = &txn.transaction {
@@ -78,10 +258,18 @@ fn has_sell_instruction(txn: &SubscribeUpdateTransaction) -> bool {
     false
 }
 
-/// Parses the transaction data buffer into a TradeInfoFromToken struct
+/// Parses the transaction data buffer into a TradeInfoFromToken struct, flagging suspected MEV legs
 pub fn parse_transaction_data(txn: &SubscribeUpdateTransaction, buffer: &[u8]) -> Option<TradeInfoFromToken> {
-    // Extract slot once and reuse
+    let mut trade_info = parse_trade_event(txn, buffer)?;
+    crate::engine::mev_tracker::flag_suspected_mev(&mut trade_info);
+    Some(trade_info)
+}
+
+fn parse_trade_event(txn: &SubscribeUpdateTransaction, buffer: &[u8]) -> Option<TradeInfoFromToken> {
+    // Extract slot and trader once and reuse
     let slot = txn.slot;
+    let trader = extract_trader(txn).unwrap_or_default();
+    let is_own_trade = is_own_trader(&trader);
     fn parse_public_key(buffer: &[u8], offset: usize) -> Option<String> {
         if offset + 32 > buffer.len() {
             return None;
@@ -138,7 +326,9 @@ pub fn parse_transaction_data(txn: &SubscribeUpdateTransaction, buffer: &[u8]) -
         368 | 416=> {  // pump swap transaction - 368 bytes
             // Extract token mint and check for reverse case
             let mint = extract_token_info(&txn);
-            let timestamp = parse_u64(buffer, 16)?;
+            let decimals = mint_decimals(txn, &mint).unwrap_or(PUMP_TOKEN_DECIMALS);
+            let timestamp = clock::normalize_event_timestamp(parse_u64(buffer, 16)?);
+            let timestamp_suspect = !clock::observe_event_timestamp(timestamp).is_plausible();
             let base_amount_in_or_base_amount_out = parse_u64(buffer, 24)?;
             // let min_quote_amount_out = parse_u64(buffer, 32)?; // Unused
             // let user_base_token_reserves = parse_u64(buffer, 40)?; // Unused
@@ -158,29 +348,16 @@ pub fn parse_transaction_data(txn: &SubscribeUpdateTransaction, buffer: &[u8]) -
             // Determine if it's reverse case based on coin_creator
             let is_reverse_when_pump_swap = coin_creator == "11111111111111111111111111111111";
             
-            // Calculate price based on is_reverse_when_pump_swap
-            let post_current_price = if pool_base_token_reserves > 0 && pool_quote_token_reserves > 0 {
-                if is_reverse_when_pump_swap {
-                    // In reverse case: poolBaseTokenReserves/poolQuoteTokenReserves (base_mint is WSOL)
-                    pool_base_token_reserves as f64 / pool_quote_token_reserves as f64 / 1_000.0
-                } else {
-                    // Normal case: poolQuoteTokenReserves/poolBaseTokenReserves (quote_mint is WSOL)
-                    pool_quote_token_reserves as f64 / pool_base_token_reserves as f64 / 1_000.0
-                }
+            // WSOL is the base side in reverse pools (base_mint is WSOL) and the quote side otherwise
+            let post_current_price = if is_reverse_when_pump_swap {
+                price_per_token(pool_base_token_reserves, pool_quote_token_reserves, decimals)
             } else {
-                0.0
+                price_per_token(pool_quote_token_reserves, pool_base_token_reserves, decimals)
             };
-
-            let pre_current_price = if base_amount_in_or_base_amount_out > 0 && quote_amount_out > 0 {
-                if is_reverse_when_pump_swap {
-                    // In reverse case: poolBaseTokenReserves/poolQuoteTokenReserves (base_mint is WSOL)
-                    base_amount_in_or_base_amount_out as f64 / quote_amount_out as f64 / 1_000.0
-                } else {
-                    // Normal case: poolQuoteTokenReserves/poolBaseTokenReserves (quote_mint is WSOL)
-                    quote_amount_out as f64 / base_amount_in_or_base_amount_out as f64 / 1_000.0
-                }
+            let pre_current_price = if is_reverse_when_pump_swap {
+                price_per_token(base_amount_in_or_base_amount_out, quote_amount_out, decimals)
             } else {
-                0.0 // fallback
+                price_per_token(quote_amount_out, base_amount_in_or_base_amount_out, decimals)
             };
             
             let is_buy = if is_reverse_when_pump_swap {
@@ -190,29 +367,14 @@ pub fn parse_transaction_data(txn: &SubscribeUpdateTransaction, buffer: &[u8]) -
                 // Normal case (quote_mint is WSOL)
                 has_buy_instruction(txn)
             };
-            let (sol_change, token_change) = if is_reverse_when_pump_swap {
-              // Reverse case: base_mint is WSOL, quote_mint is token
-              if is_buy {
-                // Buy: spend SOL (base), get tokens (quote) 
-                // sol_change is positive for buys (matching PumpFun convention)
-                (base_amount_in_or_base_amount_out as f64 / 1_000_000_000.0, quote_amount_out as f64 / 1_000_000_000.0)
-              } else {
-                // Sell: get SOL (base), spend tokens (quote)
-                // sol_change is negative for sells (matching PumpFun convention)
-                (-(base_amount_in_or_base_amount_out as f64) / 1_000_000_000.0, -(quote_amount_out as f64) / 1_000_000_000.0)
-              }
-            } else {
-                // Normal case: quote_mint is WSOL, base_mint is token
-                if is_buy {
-                    // Buy: spend SOL (quote), get tokens (base)
-                    // sol_change is positive for buys (matching PumpFun convention)
-                    (quote_amount_out as f64 / 1_000_000_000.0, base_amount_in_or_base_amount_out as f64 / 1_000_000_000.0)
-                } else {
-                    // Sell: get SOL (quote), spend tokens (base)
-                    // sol_change is negative for sells (matching PumpFun convention)
-                    (-(quote_amount_out as f64) / 1_000_000_000.0, -(base_amount_in_or_base_amount_out as f64) / 1_000_000_000.0)
-                }
-            };  
+            // Buy/sell here is from the token's point of view, so a reverse-pool "Sell" of WSOL is a token buy
+            let (sol_change, token_change) = pump_swap_changes(
+                is_reverse_when_pump_swap,
+                is_buy,
+                base_amount_in_or_base_amount_out,
+                quote_amount_out,
+                decimals,
+            );
 
             let liquidity = if !is_reverse_when_pump_swap {
                 pool_quote_token_reserves as f64 / 1_000_000_000.0
@@ -234,11 +396,18 @@ pub fn parse_transaction_data(txn: &SubscribeUpdateTransaction, buffer: &[u8]) -
                 coin_creator: Some(coin_creator),
                 sol_change,
                 target_transaction_token_change: token_change,
+                token_decimals: decimals,
                 liquidity,
-                // Map pool reserves to virtual reserves as requested
-                virtual_sol_reserves: pool_quote_token_reserves,  
-                virtual_token_reserves: pool_base_token_reserves,  
+                // WSOL is the quote side normally and the base side in reverse pools
+                virtual_sol_reserves: if is_reverse_when_pump_swap { pool_base_token_reserves } else { pool_quote_token_reserves },
+                virtual_token_reserves: if is_reverse_when_pump_swap { pool_quote_token_reserves } else { pool_base_token_reserves },
                 buy_sell_in_same_tx: false,
+                trader,
+                is_own_trade,
+                timestamp_suspect,
+                gap_replayed: false,
+                curve_complete: false,
+                suspected_mev: false,
             })
         },
 
@@ -248,11 +417,12 @@ pub fn parse_transaction_data(txn: &SubscribeUpdateTransaction, buffer: &[u8]) -
             let sol_amount = parse_u64(buffer, 48)?;
             let token_amount = parse_u64(buffer, 56)?;
             let is_buy = buffer.get(64)? == &1;
-            let timestamp = parse_u64(buffer, 97)?;
+            let timestamp = clock::normalize_event_timestamp(parse_u64(buffer, 97)?);
+            let timestamp_suspect = !clock::observe_event_timestamp(timestamp).is_plausible();
             let virtual_sol_reserves = parse_u64(buffer, 105)?;
             let virtual_token_reserves = parse_u64(buffer, 113)?;
             let real_sol_reserves = parse_u64(buffer, 121)?;
-            // let real_token_reserves = parse_u64(buffer, 129)?; // Unused
+            let real_token_reserves = parse_u64(buffer, 129)?;
             let creator = parse_public_key(buffer, 185)?;
             // Detect mixed buy/sell instructions present in the same transaction (market-making risk)
             let mixed_buy_sell = has_buy_instruction(txn) && has_sell_instruction(txn);
@@ -293,14 +463,218 @@ pub fn parse_transaction_data(txn: &SubscribeUpdateTransaction, buffer: &[u8]) -
                 is_reverse_when_pump_swap: false, // PumpFun is never reverse
                 coin_creator: Some(creator),
                 sol_change,
-                target_transaction_token_change: token_amount as f64 / 1_000_000.0,
+                target_transaction_token_change: if is_buy {
+                    raw_to_ui(token_amount, PUMP_TOKEN_DECIMALS)
+                } else {
+                    -raw_to_ui(token_amount, PUMP_TOKEN_DECIMALS)
+                },
+                token_decimals: PUMP_TOKEN_DECIMALS,
                 liquidity,
                 virtual_sol_reserves: virtual_sol_reserves,
                 virtual_token_reserves: virtual_token_reserves,
                 buy_sell_in_same_tx: mixed_buy_sell,
+                trader,
+                is_own_trade,
+                timestamp_suspect,
+                gap_replayed: false,
+                curve_complete: real_token_reserves == 0,
+                suspected_mev: false,
+            })
+        },
+
+        57 => {
+            // Raydium AMM v4 ray_log: SwapBaseIn and SwapBaseOut share the 57-byte layout
+            // log_type, in/out amount, out/in amount, direction, user_source, pool_coin, pool_pc, computed amount
+            let log_type = *buffer.first()?;
+            let (amount_in, amount_out) = match log_type {
+                RAY_LOG_SWAP_BASE_IN => (parse_u64(buffer, 1)?, parse_u64(buffer, 49)?),
+                RAY_LOG_SWAP_BASE_OUT => (parse_u64(buffer, 49)?, parse_u64(buffer, 9)?),
+                _ => return None,
+            };
+            let coin_in = parse_u64(buffer, 17)? == RAY_DIRECTION_COIN_TO_PC;
+            // Reserves before the swap
+            let pool_coin = parse_u64(buffer, 33)?;
+            let pool_pc = parse_u64(buffer, 41)?;
+            let vaults = raydium_vaults(txn)?;
+            let pool_id = raydium_pool_id(txn).unwrap_or_default();
+            // The log carries no timestamp, so the receive time stands in for it
+            let timestamp = clock::now_secs();
+
+            let is_buy = vaults.sol_in;
+            // The coin side is WSOL when SOL went in on the coin side (or out on it)
+            let sol_is_coin = coin_in == is_buy;
+            let (coin_after, pc_after) = if coin_in {
+                (pool_coin.saturating_add(amount_in), pool_pc.saturating_sub(amount_out))
+            } else {
+                (pool_coin.saturating_sub(amount_out), pool_pc.saturating_add(amount_in))
+            };
+            let (sol_reserves, token_reserves_raw) = if sol_is_coin { (coin_after, pc_after) } else { (pc_after, coin_after) };
+            let token_reserves = to_ui6_units(token_reserves_raw, vaults.decimals);
+            let (sol_amount, token_amount) = if is_buy {
+                (amount_in, to_ui6_units(amount_out, vaults.decimals))
+            } else {
+                (amount_out, to_ui6_units(amount_in, vaults.decimals))
+            };
+
+            let post_current_price = if token_reserves > 0 {
+                sol_reserves as f64 / token_reserves as f64 / 1_000.0
+            } else {
+                0.0
+            };
+            let pre_current_price = if token_amount > 0 {
+                sol_amount as f64 / token_amount as f64 / 1_000.0
+            } else {
+                0.0
+            };
+            let sol = sol_amount as f64 / LAMPORTS_PER_SOL_F64;
+            let tokens = raw_to_ui(token_amount, PUMP_TOKEN_DECIMALS);
+
+            Some(TradeInfoFromToken {
+                dex_type: DexType::RaydiumAmm,
+                slot,
+                signature: String::new(), // Will be set from transaction data
+                pool_id,
+                mint: vaults.mint,
+                timestamp,
+                is_buy,
+                post_current_price,
+                pre_current_price,
+                is_reverse_when_pump_swap: false,
+                coin_creator: None,
+                sol_change: if is_buy { sol } else { -sol },
+                target_transaction_token_change: if is_buy { tokens } else { -tokens },
+                token_decimals: PUMP_TOKEN_DECIMALS,
+                liquidity: sol_reserves as f64 / LAMPORTS_PER_SOL_F64,
+                virtual_sol_reserves: sol_reserves,
+                virtual_token_reserves: token_reserves,
+                buy_sell_in_same_tx: false,
+                trader,
+                is_own_trade,
+                timestamp_suspect: false,
+                gap_replayed: false,
+                curve_complete: false,
+                suspected_mev: false,
             })
         },
         
         _ => None,
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use yellowstone_grpc_proto::prelude::TokenBalance;
+    use crate::engine::test_fixtures::{pump_fun_event, pump_swap_event, stream_update, signed_by};
+
+    const MINT: &str = "EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v";
+
+    #[test]
+    fn pump_swap_uses_the_mint_decimals_from_the_balances() {
+        // 1e6 tokens of 9 decimals against 100 SOL, and a buy of 1000 tokens for 0.1 SOL
+        let buffer = pump_swap_event(1_000_000_000_000, 100_000_000, 1_000_000_000_000_000, 100_000_000_000, false);
+        let trade = parse_trade_event(&stream_update(MINT, "Buy", Some(9)), &buffer).expect("PumpSwap event");
+
+        assert_eq!(trade.dex_type, DexType::PumpSwap);
+        assert_eq!(trade.mint, MINT);
+        assert_eq!(trade.token_decimals, 9);
+        assert!(trade.is_buy);
+        assert!((trade.post_current_price - 1e-4).abs() < 1e-12, "{}", trade.post_current_price);
+        assert!((trade.pre_current_price - 1e-4).abs() < 1e-12, "{}", trade.pre_current_price);
+        assert!((trade.target_transaction_token_change - 1_000.0).abs() < 1e-9);
+        assert!((trade.sol_change - 0.1).abs() < 1e-12);
+        // Reserves stay raw; the decimals travel with them
+        assert_eq!(trade.virtual_token_reserves, 1_000_000_000_000_000);
+        assert_eq!(trade.virtual_sol_reserves, 100_000_000_000);
+    }
+
+    #[test]
+    fn pump_swap_falls_back_to_pump_decimals_without_a_balance() {
+        let buffer = pump_swap_event(1_000_000_000, 100_000_000, 1_000_000_000_000, 100_000_000_000, false);
+        let mut txn = stream_update(MINT, "Sell", None);
+        // The mint still has to come from a balance; give it one without an amount
+        txn.transaction.as_mut().unwrap().meta.as_mut().unwrap().post_token_balances.push(TokenBalance {
+            mint: MINT.to_string(),
+            ..Default::default()
+        });
+        let trade = parse_trade_event(&txn, &buffer).expect("PumpSwap event");
+
+        assert_eq!(trade.token_decimals, PUMP_TOKEN_DECIMALS);
+        assert!(!trade.is_buy);
+        assert!((trade.post_current_price - 1e-4).abs() < 1e-12, "{}", trade.post_current_price);
+        assert!((trade.target_transaction_token_change + 1_000.0).abs() < 1e-9);
+        assert!((trade.sol_change + 0.1).abs() < 1e-12);
+    }
+
+    #[test]
+    fn reverse_pool_swaps_sides_and_direction() {
+        // Base is WSOL: a "Sell" of WSOL buys the token
+        let buffer = pump_swap_event(100_000_000, 1_000_000_000_000, 100_000_000_000, 1_000_000_000_000_000, true);
+        let trade = parse_trade_event(&stream_update(MINT, "Sell", Some(9)), &buffer).expect("PumpSwap event");
+
+        assert!(trade.is_reverse_when_pump_swap);
+        assert!(trade.is_buy);
+        assert_eq!(trade.token_decimals, 9);
+        assert!((trade.post_current_price - 1e-4).abs() < 1e-12, "{}", trade.post_current_price);
+        assert!((trade.target_transaction_token_change - 1_000.0).abs() < 1e-9);
+        assert!((trade.sol_change - 0.1).abs() < 1e-12);
+        assert_eq!(trade.virtual_sol_reserves, 100_000_000_000);
+        assert_eq!(trade.liquidity, 100.0);
+    }
+
+    #[test]
+    fn trader_is_the_fee_payer() {
+        let payer = Pubkey::new_unique();
+        let cosigner = Pubkey::new_unique();
+        let txn = signed_by(stream_update(MINT, "Buy", Some(6)), &[payer, cosigner], 2);
+        assert_eq!(extract_trader(&txn), Some(payer.to_string()));
+
+        assert_eq!(extract_trader(&signed_by(stream_update(MINT, "Buy", Some(6)), &[payer], 0)), None);
+        assert_eq!(extract_trader(&signed_by(stream_update(MINT, "Buy", Some(6)), &[], 1)), None);
+        assert_eq!(extract_trader(&stream_update(MINT, "Buy", Some(6))), None);
+    }
+
+    #[test]
+    fn own_wallet_trades_are_tagged() {
+        let ours = Pubkey::new_unique();
+        let theirs = Pubkey::new_unique();
+        register_own_wallet(&ours);
+        assert!(is_own_trader(&ours.to_string()));
+        assert!(!is_own_trader(&theirs.to_string()));
+        assert!(!is_own_trader(""));
+
+        let buffer = pump_swap_event(1_000_000, 1_000_000, 1_000_000_000, 1_000_000_000, false);
+        let own = parse_trade_event(&signed_by(stream_update(MINT, "Buy", Some(6)), &[ours], 1), &buffer).expect("PumpSwap event");
+        assert_eq!(own.trader, ours.to_string());
+        assert!(own.is_own_trade);
+        assert!(is_our_wallet(&own, &ours));
+        assert!(!is_our_wallet(&own, &theirs));
+
+        let external = parse_trade_event(&signed_by(stream_update(MINT, "Buy", Some(6)), &[theirs], 1), &buffer).expect("PumpSwap event");
+        assert!(!external.is_own_trade);
+        let anonymous = parse_trade_event(&stream_update(MINT, "Buy", Some(6)), &buffer).expect("PumpSwap event");
+        assert!(anonymous.trader.is_empty());
+        assert!(!is_our_wallet(&anonymous, &ours));
+    }
+
+    #[test]
+    fn empty_side_prices_at_zero() {
+        assert_eq!(price_per_token(0, 1_000, 6), 0.0);
+        assert_eq!(price_per_token(1_000, 0, 6), 0.0);
+        assert!((price_per_token(1_000_000_000, 1_000_000, 6) - 1.0).abs() < 1e-12);
+        assert!((price_per_token(1_000_000_000, 1_000_000_000, 9) - 1.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn pump_fun_trade_taking_the_last_real_tokens_completes_the_curve() {
+        let mint = Pubkey::from_str(MINT).unwrap();
+        let mut buffer = pump_fun_event(&mint, 100_000_000, 3_000_000_000, true, 30_000_000_000, 1_073_000_000_000_000);
+        let ongoing = parse_trade_event(&stream_update(MINT, "Buy", None), &buffer).expect("PumpFun event");
+        assert!(!ongoing.curve_complete);
+
+        buffer[129..137].copy_from_slice(&0u64.to_le_bytes());
+        let completing = parse_trade_event(&stream_update(MINT, "Buy", None), &buffer).expect("PumpFun event");
+        assert_eq!(completing.dex_type, DexType::PumpFun);
+        assert!(completing.curve_complete);
+    }
+}
@@ -1,5 +1,4 @@
 use std::sync::Arc;
-use std::str::FromStr;
 use std::time::{Duration, Instant};
 use anyhow::{anyhow, Result};
 use anchor_client::solana_sdk::{
@@ -7,25 +6,50 @@ use anchor_client::solana_sdk::{
     signature::{Signature, Keypair}, 
     signer::Signer,
     hash::Hash,
+    commitment_config::{CommitmentConfig, CommitmentLevel},
 };
 use spl_associated_token_account::{get_associated_token_address, get_associated_token_address_with_program_id};
 use colored::Colorize;
+use dashmap::DashMap;
+use once_cell::sync::Lazy;
 use tokio::time::{sleep, timeout};
 use base64;
 
 use crate::common::{
+    cache::PROGRESS_ON_SELLING,
     config::{AppState, SwapConfig},
     logger::Logger,
 };
 use crate::engine::swap::SwapDirection;
-use crate::services::jupiter_api::JupiterClient;
+use crate::services::jupiter_api::{JupiterClient, SwapAggregator, SwapMode, SwapOptions};
+use crate::services::confirmation_tracker::{self, ConfirmationOutcome};
+use crate::services::nonce_health::{self, BlockhashMode};
 use crate::services::telegram;
 use crate::engine::transaction_parser::TradeInfoFromToken;
+use crate::engine::freeze_guard;
+use crate::error::classify::{classify_error, ErrorClass};
 use crate::core::tx;
 
 /// Maximum number of retry attempts for selling transactions
 const MAX_RETRIES: u32 = 3;
 
+/// 15000 bps (150%) slippage accepts any output amount (equivalent to setting output to 0 or 1)
+const SELL_SLIPPAGE_ACCEPT_ANY: u64 = 15000;
+
+/// One sell chain per mint at a time, held from the first attempt through the Jupiter fallback
+/// Two exit rules firing on the same tick otherwise both send, and the loser fails or is counted twice.
+static SELL_LOCKS: Lazy<DashMap<String, Arc<tokio::sync::Mutex<()>>>> = Lazy::new(DashMap::new);
+
+/// A sent sell that neither confirmed nor provably failed; it may still land, so no fallback may sell the
+/// same tokens again
+#[derive(Debug, thiserror::Error)]
+#[error("{venue} sell {signature} unconfirmed ({reason}); it may still land, so no fallback is sent")]
+pub struct SellInDoubt {
+    pub venue: String,
+    pub signature: Signature,
+    pub reason: String,
+}
+
 /// Result of a selling transaction attempt
 #[derive(Debug)]
 pub struct SellTransactionResult {
@@ -34,15 +58,17 @@ pub struct SellTransactionResult {
     pub error: Option<String>,
     pub used_jupiter_fallback: bool,
     pub attempt_count: u32,
+    /// DRY_RUN: the sell was only simulated and `signature` is None
+    pub simulated: bool,
 }
 
-/// Execute PumpFun sell transaction
+/// Execute PumpFun sell transaction; the signature is None when DRY_RUN only simulated it
 async fn execute_pumpfun_sell(
     trade_info: &TradeInfoFromToken,
     sell_config: &SwapConfig,
     app_state: Arc<AppState>,
     logger: &Logger,
-) -> Result<(Signature, f64, f64)> {
+) -> Result<(Option<Signature>, f64, f64)> {
     // CRITICAL FIX: Double-check SELL_REASONS before building transaction
     use crate::engine::sniper::SELL_REASONS;
     if !SELL_REASONS.contains_key(&trade_info.mint) {
@@ -56,11 +82,6 @@ async fn execute_pumpfun_sell(
         return Err(anyhow!("Not a PumpFun token"));
     }
     
-    // CRITICAL: Set PROGRESS_ON_SELLING before calling selling transaction building function
-    // This prevents race conditions where selling condition checks might interfere with transaction building
-    use crate::common::cache::PROGRESS_ON_SELLING;
-    PROGRESS_ON_SELLING.insert(trade_info.mint.clone(), ());
-    
     // Create PumpFun instance
     let pump = crate::dex::pump_fun::Pump::new(
         app_state.rpc_nonblocking_client.clone(),
@@ -69,31 +90,12 @@ async fn execute_pumpfun_sell(
     );
     
     // Build swap instructions
-    let (keypair, instructions, price) = pump.build_swap_from_parsed_data(trade_info, sell_config.clone()).await
+    let (keypair, mut instructions, price) = pump.build_swap_from_parsed_data(trade_info, sell_config.clone()).await
         .map_err(|e| anyhow!("PumpFun build_swap_from_parsed_data failed: {}", e))?;
     
-    // Get real-time blockhash
-    let recent_blockhash = crate::services::blockhash_processor::BlockhashProcessor::get_latest_blockhash().await
-        .ok_or_else(|| anyhow!("Failed to get real-time blockhash"))?;
-    
-    // Send transaction using zeroslot
-    let signatures = tx::new_signed_and_send_zeroslot(
-        app_state.zeroslot_rpc_client.clone(),
-        recent_blockhash,
-        &keypair,
-        instructions,
-        logger,
-        false, // is_buy = false for selling
-        None,  // slot = None for selling
-    ).await.map_err(|e| anyhow!("PumpFun transaction send failed: {}", e))?;
-    
-    if signatures.is_empty() {
-        return Err(anyhow!("No transaction signature returned"));
-    }
-    
-    let signature = signatures[0].parse::<Signature>()
-        .map_err(|e| anyhow!("Failed to parse signature: {}", e))?;
-    
+    // Nonce hash while the durable nonce is healthy, real-time blockhash otherwise
+    let (blockhash, blockhash_mode) = nonce_health::prepare_blockhash(&app_state, &mut instructions).await?;
+
     // Calculate expected SOL received (approximate from price and amount)
     // For more accurate value, we'd need to query the transaction, but this is good enough for notification
     use crate::engine::sniper::TOKEN_HOLDINGS;
@@ -104,62 +106,380 @@ async fn execute_pumpfun_sell(
         // Fallback: use price from build_swap
         price
     };
-    
-    logger.log(format!("✅ PumpFun transaction sent: {}", signature).green().to_string());
+
+    let signature = send_or_simulate_sell(trade_info, sell_config, &app_state, &keypair, instructions, blockhash, blockhash_mode, "PumpFun", logger).await?;
     Ok((signature, received_sol, price))
 }
 
-/// Execute normal sell (PumpFun) - NO RETRY LOGIC to prevent race conditions
+/// Send a built sell through zeroslot, or only simulate it under DRY_RUN
+#[allow(clippy::too_many_arguments)]
+async fn send_or_simulate_sell(
+    trade_info: &TradeInfoFromToken,
+    sell_config: &SwapConfig,
+    app_state: &Arc<AppState>,
+    keypair: &Arc<Keypair>,
+    instructions: Vec<anchor_client::solana_sdk::instruction::Instruction>,
+    blockhash: Hash,
+    blockhash_mode: BlockhashMode,
+    venue: &str,
+    logger: &Logger,
+) -> Result<Option<Signature>> {
+    // A simulation says nothing about the nonce, so it is not recorded as a send outcome
+    if sell_config.dry_run {
+        tx::simulate_zeroslot_transaction(app_state.rpc_nonblocking_client.clone(), blockhash, keypair, instructions, logger)
+            .await
+            .map_err(|e| anyhow!("{} sell simulation failed: {}", venue, e))?;
+        logger.critical(format!("🧪 [DRY RUN] {} sell simulated for {}", venue, trade_info.mint).yellow().to_string());
+        return Ok(None);
+    }
+    
+    // Send transaction using zeroslot
+    let send_result = tx::new_signed_and_send_zeroslot(
+        app_state.zeroslot_rpc_client.clone(),
+        blockhash,
+        keypair,
+        instructions,
+        logger,
+        false, // is_buy = false for selling
+        None,  // slot = None for selling
+    ).await;
+    let send_error = send_result.as_ref().err().map(|e| e.to_string());
+    nonce_health::record_send_outcome(&keypair.pubkey(), blockhash_mode, send_error.as_deref());
+    let sent = send_result.map_err(|e| anyhow!("{} transaction send failed: {}", venue, e))?;
+    
+    let signature = sent.signature.parse::<Signature>()
+        .map_err(|e| anyhow!("Failed to parse signature: {}", e))?;
+    
+    logger.critical(format!("✅ {} transaction sent: {} {}", venue, signature, sent.endpoint).green().to_string());
+    confirm_sent_sell(app_state, &signature, Some(blockhash), blockhash_mode, venue, logger).await?;
+    Ok(Some(signature))
+}
+
+/// What an unconfirmed sell turned out to be once its status was read again
+#[derive(Debug, PartialEq)]
+enum Unconfirmed {
+    Landed,
+    /// It provably never lands; the chain may fall back
+    Retryable(String),
+    /// It can still land; falling back could sell the same tokens twice
+    InDoubt,
+}
+
+/// Verdict for a sell the tracker gave up on, from its signature status (None when the cluster has none)
+/// and, for a recent blockhash, whether that blockhash is still valid (None when it couldn't be read)
+fn settle_unconfirmed(status: Option<Result<(), String>>, blockhash_mode: BlockhashMode, blockhash_valid: Option<bool>) -> Unconfirmed {
+    match status {
+        Some(Ok(())) => Unconfirmed::Landed,
+        Some(Err(err)) => Unconfirmed::Retryable(format!("failed on-chain: {}", err)),
+        // A durable nonce never expires, so the send can land until the nonce advances
+        None if blockhash_mode == BlockhashMode::Recent && blockhash_valid == Some(false) => {
+            Unconfirmed::Retryable("blockhash expired before it landed".to_string())
+        }
+        None => Unconfirmed::InDoubt,
+    }
+}
+
+/// Wait for a sent sell to confirm; Err unless it landed cleanly
+/// A sell the tracker gave up on is read again first: only one that provably can't land lets the chain move
+/// on to its fallback, anything else is a `SellInDoubt`. `blockhash` is None when it isn't known (Jupiter).
+async fn confirm_sent_sell(
+    app_state: &AppState,
+    signature: &Signature,
+    blockhash: Option<Hash>,
+    blockhash_mode: BlockhashMode,
+    venue: &str,
+    logger: &Logger,
+) -> Result<()> {
+    let timeout = confirmation_tracker::confirm_timeout();
+    let reason = match confirmation_tracker::tracker(&app_state.read_rpc)
+        .await_confirmation(signature, CommitmentLevel::Confirmed, timeout)
+        .await
+    {
+        Ok(ConfirmationOutcome::Failed(err)) => return Err(anyhow!("{} sell {} failed on-chain: {}", venue, signature, err)),
+        Ok(ConfirmationOutcome::Expired) => format!("not confirmed within {}s", timeout.as_secs()),
+        Ok(landed) => {
+            logger.critical(format!("✅ {} sell {} {:?}", venue, signature, landed).green().to_string());
+            return Ok(());
+        }
+        Err(e) => format!("could not be tracked: {}", e),
+    };
+
+    let client = app_state.read_rpc.client();
+    let status = match client.get_signature_statuses(&[*signature]).await {
+        Ok(response) => response.value.into_iter().next().flatten().map(|status| status.status.map_err(|err| err.to_string())),
+        Err(e) => {
+            logger.error(format!("getSignatureStatuses for {} failed: {}", signature, e));
+            None
+        }
+    };
+    let blockhash_valid = match blockhash {
+        Some(blockhash) if blockhash_mode == BlockhashMode::Recent => {
+            client.is_blockhash_valid(&blockhash, CommitmentConfig::processed()).await.ok()
+        }
+        _ => None,
+    };
+    match settle_unconfirmed(status, blockhash_mode, blockhash_valid) {
+        Unconfirmed::Landed => {
+            logger.critical(format!("✅ {} sell {} landed after {}", venue, signature, reason).green().to_string());
+            Ok(())
+        }
+        Unconfirmed::Retryable(why) => Err(anyhow!("{} sell {} {}: {}", venue, signature, reason, why)),
+        Unconfirmed::InDoubt => Err(anyhow::Error::new(SellInDoubt {
+            venue: venue.to_string(),
+            signature: *signature,
+            reason,
+        })),
+    }
+}
+
+/// Execute Raydium AMM v4 sell; the signature is None when DRY_RUN only simulated it
+async fn execute_raydium_sell(
+    trade_info: &TradeInfoFromToken,
+    sell_config: &SwapConfig,
+    app_state: Arc<AppState>,
+    logger: &Logger,
+) -> Result<Option<Signature>> {
+    use crate::engine::sniper::SELL_REASONS;
+    if !SELL_REASONS.contains_key(&trade_info.mint) {
+        return Err(anyhow!("Sell reason not set - skipping transaction building"));
+    }
+
+    logger.critical("🚀 Executing Raydium sell".purple().to_string());
+
+    let raydium = crate::dex::raydium::Raydium::new(
+        app_state.rpc_nonblocking_client.clone(),
+        app_state.rpc_client.clone(),
+        app_state.wallet.clone(),
+    );
+    let (keypair, mut instructions, _price) = raydium.build_swap_from_parsed_data(trade_info, sell_config.clone()).await
+        .map_err(|e| anyhow!("Raydium build_swap_from_parsed_data failed: {}", e))?;
+    let (blockhash, blockhash_mode) = nonce_health::prepare_blockhash(&app_state, &mut instructions).await?;
+    send_or_simulate_sell(trade_info, sell_config, &app_state, &keypair, instructions, blockhash, blockhash_mode, "Raydium", logger).await
+}
+
+/// Execute PumpSwap sell against `trade_info.pool_id`; the signature is None when DRY_RUN only simulated it
+async fn execute_pumpswap_sell(
+    trade_info: &TradeInfoFromToken,
+    sell_config: &SwapConfig,
+    app_state: Arc<AppState>,
+    logger: &Logger,
+) -> Result<Option<Signature>> {
+    use crate::engine::sniper::SELL_REASONS;
+    if !SELL_REASONS.contains_key(&trade_info.mint) {
+        return Err(anyhow!("Sell reason not set - skipping transaction building"));
+    }
+
+    logger.critical("🚀 Executing PumpSwap sell".purple().to_string());
+
+    // The builder checks the amount against the balance, so read what the account really holds
+    let mint = trade_info.mint.parse::<Pubkey>()
+        .map_err(|e| anyhow!("Invalid mint {}: {}", trade_info.mint, e))?;
+    let token_program = crate::core::mint_inspector::resolve_token_program(&app_state.rpc_nonblocking_client, &mint).await?;
+    let token_ata = get_associated_token_address_with_program_id(&app_state.wallet.pubkey(), &mint, &token_program);
+    let account = app_state.rpc_nonblocking_client.get_token_account(&token_ata).await
+        .map_err(|e| anyhow!("Failed to get token account: {}", e))?
+        .ok_or_else(|| anyhow!("Token account does not exist for mint {}", trade_info.mint))?;
+    let balance = account.token_amount.amount.parse::<u64>()
+        .map_err(|e| anyhow!("Failed to parse token amount: {}", e))?;
+    // Never ask for more than the account holds; the tracked amount can run ahead of the chain by rounding
+    let mut sell_config = sell_config.clone();
+    if sell_config.in_type == crate::engine::swap::SwapInType::Qty {
+        let held_ui = balance as f64 / 10f64.powi(i32::from(account.token_amount.decimals));
+        sell_config.amount_in = sell_config.amount_in.min(held_ui);
+    }
+
+    let pump_swap = crate::dex::pump_swap::PumpSwap::new(
+        app_state.wallet.clone(),
+        Some(app_state.rpc_client.clone()),
+        Some(app_state.rpc_nonblocking_client.clone()),
+    );
+    let (keypair, mut instructions, _price) = pump_swap
+        .build_swap_from_parsed_data_with_balance(trade_info, sell_config.clone(), Some((balance, account.token_amount.decimals)))
+        .await
+        .map_err(|e| anyhow!("PumpSwap build_swap_from_parsed_data_with_balance failed: {}", e))?;
+    let (blockhash, blockhash_mode) = nonce_health::prepare_blockhash(&app_state, &mut instructions).await?;
+    send_or_simulate_sell(trade_info, &sell_config, &app_state, &keypair, instructions, blockhash, blockhash_mode, "PumpSwap", logger).await
+}
+
+/// Execute normal sell (PumpFun, PumpSwap or Raydium) - NO RETRY LOGIC to prevent race conditions
 async fn execute_normal_sell_with_retry(
     trade_info: &TradeInfoFromToken,
     sell_config: SwapConfig,
     app_state: Arc<AppState>,
     logger: &Logger,
 ) -> Result<SellTransactionResult> {
-    // Only try PumpFun for PumpFun tokens
-    if trade_info.dex_type != crate::engine::transaction_parser::DexType::PumpFun {
-        return Err(anyhow!("Not a PumpFun token - skipping normal sell"));
-    }
-    
-    logger.log(format!("🚀 PumpFun sell execution (single attempt, no retries) for token: {}", trade_info.mint).cyan().to_string());
+    use crate::engine::transaction_parser::DexType;
+    let (venue, sent) = match trade_info.dex_type {
+        // A complete curve refuses every PumpFun trade; Jupiter routes through the PumpSwap pool instead
+        DexType::PumpFun if crate::engine::migration::is_migrated(&trade_info.mint) => {
+            return Err(anyhow!("{} migrated to PumpSwap - skipping the PumpFun sell", trade_info.mint));
+        }
+        DexType::PumpFun => {
+            logger.critical(format!("🚀 PumpFun sell execution (single attempt, no retries) for token: {}", trade_info.mint).cyan().to_string());
+            ("PumpFun", execute_pumpfun_sell(trade_info, &sell_config, app_state.clone(), logger).await.map(|(signature, _received_sol, _price)| signature))
+        }
+        DexType::PumpSwap => {
+            logger.critical(format!("🚀 PumpSwap sell execution (single attempt, no retries) for token: {}", trade_info.mint).cyan().to_string());
+            ("PumpSwap", execute_pumpswap_sell(trade_info, &sell_config, app_state.clone(), logger).await)
+        }
+        DexType::RaydiumAmm => {
+            logger.critical(format!("🚀 Raydium sell execution (single attempt, no retries) for token: {}", trade_info.mint).cyan().to_string());
+            ("Raydium", execute_raydium_sell(trade_info, &sell_config, app_state.clone(), logger).await)
+        }
+        _ => return Err(anyhow!("Not a PumpFun, PumpSwap or Raydium token - skipping normal sell")),
+    };
     
-    match execute_pumpfun_sell(trade_info, &sell_config, app_state.clone(), logger).await {
-        Ok((signature, _received_sol, _price)) => {
-            // Transaction sent successfully - wallet monitoring will handle confirmation
-            // No RPC verification needed to reduce latency and bottleneck
-            logger.log(format!("✅ PumpFun sell transaction sent: {} (wallet monitoring will confirm)", signature).green().to_string());
+    match sent {
+        Ok(signature) => {
+            // The sell confirmed on-chain (or was only simulated); wallet monitoring sends the notification
             Ok(SellTransactionResult {
                 success: true,
-                signature: Some(signature),
+                simulated: signature.is_none(),
+                signature,
                 error: None,
                 used_jupiter_fallback: false,
                 attempt_count: 1,
             })
         }
         Err(e) => {
-            logger.log(format!("❌ PumpFun sell failed: {}", e).yellow().to_string());
-            Err(anyhow!("PumpFun sell failed: {}", e))
+            logger.error(format!("❌ {} sell failed: {}", venue, e).yellow().to_string());
+            // Kept as is so the chain can tell a sell that may still land from one that failed
+            if e.is::<SellInDoubt>() {
+                return Err(e);
+            }
+            Err(anyhow!("{} sell failed: {}", venue, e))
         }
     }
 }
 
-/// Execute Jupiter fallback sell
+/// Execute Jupiter fallback sell; None when DRY_RUN only simulated it
 async fn execute_jupiter_fallback_sell(
     trade_info: &TradeInfoFromToken,
     sell_config: &SwapConfig,
     app_state: Arc<AppState>,
     logger: &Logger,
-) -> Result<Signature> {
+) -> Result<Option<Signature>> {
     let (signature, _received_sol, _price) = execute_jupiter_sell(trade_info, sell_config, app_state, logger).await?;
     Ok(signature)
 }
 
+/// A frozen token account can never be sold: mark the position dead and stop the retry chain
+fn frozen_sell_result(trade_info: &TradeInfoFromToken, error: &str, attempt_count: u32) -> Option<SellTransactionResult> {
+    if classify_error(error) != ErrorClass::AccountFrozen {
+        return None;
+    }
+    freeze_guard::mark_frozen(&trade_info.mint, trade_info.coin_creator.as_deref(), "sell error");
+    Some(SellTransactionResult {
+        success: false,
+        signature: None,
+        error: Some(format!("Token account is frozen - giving up on {}", trade_info.mint)),
+        used_jupiter_fallback: false,
+        attempt_count,
+        simulated: false,
+    })
+}
+
+/// A sent sell that may still land ends the chain: a fallback could sell its tokens a second time
+fn in_doubt_sell_result(error: &anyhow::Error, used_jupiter_fallback: bool, attempt_count: u32) -> Option<SellTransactionResult> {
+    let in_doubt = error.downcast_ref::<SellInDoubt>()?;
+    Some(SellTransactionResult {
+        success: false,
+        signature: Some(in_doubt.signature),
+        error: Some(in_doubt.to_string()),
+        used_jupiter_fallback,
+        attempt_count,
+        simulated: false,
+    })
+}
+
+/// One call's hold on a mint's sell: its SELL_LOCKS lock and the PROGRESS_ON_SELLING entry it set
+/// Dropping it clears the entry, then frees the lock, so the entry of a sell that starts next is never cleared.
+#[must_use]
+pub struct SellClaim {
+    mint: String,
+    lock: Option<tokio::sync::OwnedMutexGuard<()>>,
+    keep_in_progress: bool,
+}
+
+impl SellClaim {
+    /// None while another call holds the mint; marked before any RPC or quote work, so selling condition
+    /// checks see the sell from its first await
+    fn take(mint: &str) -> Option<Self> {
+        let lock = SELL_LOCKS.entry(mint.to_string()).or_default().clone();
+        let guard = lock.try_lock_owned().ok()?;
+        PROGRESS_ON_SELLING.insert(mint.to_string(), ());
+        Some(Self { mint: mint.to_string(), lock: Some(guard), keep_in_progress: false })
+    }
+
+    /// Free the lock but leave the entry for wallet monitoring to clear, as the legacy loop expects
+    pub fn keep_in_progress(mut self) {
+        self.keep_in_progress = true;
+    }
+}
+
+impl Drop for SellClaim {
+    fn drop(&mut self) {
+        if !self.keep_in_progress {
+            PROGRESS_ON_SELLING.remove(&self.mint);
+        }
+        drop(self.lock.take());
+        SELL_LOCKS.remove_if(&self.mint, |_, lock| Arc::strong_count(lock) == 1);
+    }
+}
+
+/// Run `chain` under the mint's claim; a call for a mint whose sell is still running returns at once with
+/// "sell already in progress" and no claim
+async fn run_claimed<F>(mint: &str, logger: &Logger, chain: F) -> (Result<SellTransactionResult>, Option<SellClaim>)
+where
+    F: std::future::Future<Output = Result<SellTransactionResult>>,
+{
+    let Some(claim) = SellClaim::take(mint) else {
+        logger.critical(format!("⏭️ Sell of {} already in progress - not sending another", mint).yellow().to_string());
+        return (Ok(SellTransactionResult {
+            success: false,
+            signature: None,
+            error: Some("sell already in progress".to_string()),
+            used_jupiter_fallback: false,
+            attempt_count: 0,
+            simulated: false,
+        }), None);
+    };
+    (chain.await, Some(claim))
+}
+
 /// Execute a selling transaction with retry and Jupiter fallback
+/// A call for a mint whose sell is still running returns at once with "sell already in progress". The mint
+/// stays in PROGRESS_ON_SELLING afterwards; see `execute_claimed_sell` to clear it.
 pub async fn execute_sell_with_retry_and_fallback(
     trade_info: &TradeInfoFromToken,
     sell_config: SwapConfig,
     app_state: Arc<AppState>,
     logger: &Logger,
+) -> Result<SellTransactionResult> {
+    let (result, claim) = execute_claimed_sell(trade_info, sell_config, app_state, logger).await;
+    if let Some(claim) = claim {
+        claim.keep_in_progress();
+    }
+    result
+}
+
+/// `execute_sell_with_retry_and_fallback`, handing back this call's claim; None when another call held the mint,
+/// whose PROGRESS_ON_SELLING entry is then left alone
+pub async fn execute_claimed_sell(
+    trade_info: &TradeInfoFromToken,
+    sell_config: SwapConfig,
+    app_state: Arc<AppState>,
+    logger: &Logger,
+) -> (Result<SellTransactionResult>, Option<SellClaim>) {
+    let chain = execute_sell_chain(trade_info, sell_config, app_state, logger);
+    run_claimed(&trade_info.mint, logger, chain).await
+}
+
+async fn execute_sell_chain(
+    trade_info: &TradeInfoFromToken,
+    sell_config: SwapConfig,
+    app_state: Arc<AppState>,
+    logger: &Logger,
 ) -> Result<SellTransactionResult> {
     let token_mint = &trade_info.mint;
     logger.log(format!("🔄 Starting sell transaction with retry for token: {}", token_mint).cyan().to_string());
@@ -171,13 +491,20 @@ pub async fn execute_sell_with_retry_and_fallback(
                 logger.log(format!("✅ Normal sell succeeded on attempt {} - wallet monitoring will send telegram notification", result.attempt_count).green().to_string());
                 
                 // Don't remove SELL_REASONS here - wallet monitoring will handle notification and cleanup
-                // This ensures wallet monitoring has access to sell reason when it detects the balance change
+                // The reason stays bound to this attempt (sell_reasons) and expires after SELL_REASON_TTL_SECS if never taken
                 
                 return Ok(result);
             }
         }
         Err(e) => {
+            if let Some(result) = in_doubt_sell_result(&e, false, 1) {
+                logger.critical(format!("⚠️ {} - skipping the Jupiter fallback", e).yellow().to_string());
+                return Ok(result);
+            }
             logger.log(format!("❌ Normal sell attempts failed: {}", e).yellow().to_string());
+            if let Some(result) = frozen_sell_result(trade_info, &e.to_string(), 1) {
+                return Ok(result);
+            }
         }
     }
 
@@ -186,40 +513,65 @@ pub async fn execute_sell_with_retry_and_fallback(
     
     match execute_jupiter_fallback_sell(trade_info, &sell_config, app_state.clone(), logger).await {
         Ok(signature) => {
-            logger.log(format!("✅ Jupiter fallback sell succeeded: {} - wallet monitoring will send telegram notification", signature).green().to_string());
+            if let Some(signature) = &signature {
+                logger.critical(format!("✅ Jupiter fallback sell succeeded: {} - wallet monitoring will send telegram notification", signature).green().to_string());
+            }
             
             // Don't remove SELL_REASONS here - wallet monitoring will handle notification and cleanup
-            // This ensures wallet monitoring has access to sell reason when it detects the balance change
+            // The reason stays bound to this attempt (sell_reasons) and expires after SELL_REASON_TTL_SECS if never taken
             
             Ok(SellTransactionResult {
                 success: true,
-                signature: Some(signature),
+                simulated: signature.is_none(),
+                signature,
                 error: None,
                 used_jupiter_fallback: true,
                 attempt_count: MAX_RETRIES + 1,
             })
         }
         Err(e) => {
+            if let Some(result) = in_doubt_sell_result(&e, true, MAX_RETRIES + 1) {
+                logger.critical(format!("⚠️ {}", e).yellow().to_string());
+                return Ok(result);
+            }
             logger.log(format!("❌ Jupiter fallback sell failed: {}", e).red().to_string());
+            if let Some(result) = frozen_sell_result(trade_info, &e.to_string(), MAX_RETRIES + 1) {
+                return Ok(result);
+            }
             Ok(SellTransactionResult {
                 success: false,
                 signature: None,
                 error: Some(format!("All sell attempts failed. Last error: {}", e)),
                 used_jupiter_fallback: true,
                 attempt_count: MAX_RETRIES + 1,
+                simulated: false,
             })
         }
     }
 }
 
+/// Sell `amount` raw tokens of `mint` through the aggregator, accepting any output, into `options`' destination
+async fn send_jupiter_sell(
+    aggregator: &dyn SwapAggregator,
+    keypair: &Keypair,
+    mint: &str,
+    amount: u64,
+    options: &SwapOptions,
+) -> Result<Signature> {
+    let signature = aggregator.sell(mint, amount, SELL_SLIPPAGE_ACCEPT_ANY, SwapMode::ExactIn, keypair, options)
+        .await
+        .map_err(|e| anyhow!("Jupiter API sell failed: {}", e))?;
+    signature.parse::<Signature>().map_err(|e| anyhow!("Failed to parse signature: {}", e))
+}
+
 /// Execute Jupiter API sell (unified selling method for all tokens)
-/// Returns (signature, received_sol, price) for notification
+/// Returns (signature, received_sol, price) for notification; the signature is None when DRY_RUN only simulated it
 async fn execute_jupiter_sell(
     trade_info: &TradeInfoFromToken,
     sell_config: &SwapConfig,
     app_state: Arc<AppState>,
     logger: &Logger,
-) -> Result<(Signature, f64, f64)> {
+) -> Result<(Option<Signature>, f64, f64)> {
     // CRITICAL FIX: Double-check SELL_REASONS before building transaction
     // This is a safety net in case the check in execute_sell_with_retry_and_fallback was bypassed
     use crate::engine::sniper::SELL_REASONS;
@@ -237,10 +589,6 @@ async fn execute_jupiter_sell(
     let token_pubkey = trade_info.mint.parse::<Pubkey>()
         .map_err(|e| anyhow!("Invalid token mint address: {}", e))?;
 
-    // OPTIMIZATION: Always use Token-2022 program (removed RPC call for performance)
-    let token_program_id = Pubkey::from_str("TokenzQdBNbLqP5VEhdkAS6EPFLC1PHnBqCXEpPxuEb")
-        .map_err(|e| anyhow!("Failed to parse Token-2022 program ID: {}", e))?;
-
     // OPTIMIZATION: Prefer TOKEN_HOLDINGS.current_amount, only fetch from RPC if not found (single RPC call)
     use crate::engine::sniper::TOKEN_HOLDINGS;
     let token_amount = if let Some(bought_info) = TOKEN_HOLDINGS.get(&trade_info.mint) {
@@ -248,12 +596,12 @@ async fn execute_jupiter_sell(
         const DECIMALS: u8 = 6;
         (bought_info.current_amount * 10f64.powi(DECIMALS as i32)) as u64
     } else {
-        // Only fetch from RPC if not in TOKEN_HOLDINGS (single RPC call in retry logic)
-        // Get associated token account
+        // Only fetch from RPC if not in TOKEN_HOLDINGS; the mint's program is read once and cached
+        let token_program_id = crate::core::mint_inspector::resolve_token_program(&app_state.read_rpc.client(), &token_pubkey).await?;
         let ata = get_associated_token_address_with_program_id(&wallet_pubkey, &token_pubkey, &token_program_id);
         
         // Get current token balance from RPC (only time we fetch in retry logic)
-        let token_account = app_state.rpc_nonblocking_client.get_token_account(&ata).await
+        let token_account = app_state.read_rpc.client().get_token_account(&ata).await
             .map_err(|e| anyhow!("Failed to get token account: {}", e))?
             .ok_or_else(|| anyhow!("Token account not found"))?;
 
@@ -265,8 +613,10 @@ async fn execute_jupiter_sell(
         return Err(anyhow!("No tokens to sell"));
     }
 
-    // Apply sell percentage based on amount_in field (which represents percentage for sells)
-    let amount_to_sell = if sell_config.amount_in >= 1.0 {
+    // Qty sells a UI token amount (ladder rungs); Pct sells a fraction of the balance
+    let amount_to_sell = if sell_config.in_type == crate::engine::swap::SwapInType::Qty {
+        crate::dex::math::ui_to_raw(sell_config.amount_in, 6).min(token_amount)
+    } else if sell_config.amount_in >= 1.0 {
         token_amount
     } else {
         ((token_amount as f64) * sell_config.amount_in) as u64
@@ -277,13 +627,12 @@ async fn execute_jupiter_sell(
     // OPTIMIZATION: Use shared JupiterClient from AppState (eliminates duplicate initialization)
     // Get quote first to calculate expected SOL output
     const SOL_MINT: &str = "So11111111111111111111111111111111111111112";
-    // Use 15000 bps (150%) slippage to accept any output amount (equivalent to setting output to 0 or 1)
-    const SELL_SLIPPAGE_ACCEPT_ANY: u64 = 15000; // 150% slippage = accept any output
     let quote = app_state.jupiter_client.get_quote(
         &trade_info.mint,
         SOL_MINT,
         amount_to_sell,
         SELL_SLIPPAGE_ACCEPT_ANY,
+        SwapMode::ExactIn,
     ).await.map_err(|e| anyhow!("Jupiter quote failed: {}", e))?;
 
     // Calculate expected SOL output
@@ -296,21 +645,10 @@ async fn execute_jupiter_sell(
         return Err(anyhow!("Expected SOL output too small: {} SOL", expected_sol));
     }
 
-    logger.log(format!("💰 Expected SOL from sale: {:.6}", expected_sol));
-
-    // Execute sell transaction via Jupiter API (this handles signing and sending)
-    let signature_str = app_state.jupiter_client.sell_token_with_jupiter(
-        &trade_info.mint,
-        amount_to_sell,
-        15000, // 150% slippage = accept any output
-        &app_state.wallet,
-    ).await.map_err(|e| anyhow!("Jupiter API sell failed: {}", e))?;
-    
-    // Parse the signature string into a Signature type
-    let signature = signature_str.parse::<anchor_client::solana_sdk::signature::Signature>()
-        .map_err(|e| anyhow!("Failed to parse signature: {}", e))?;
-
-    logger.log(format!("✅ Jupiter transaction sent: {}", signature).green().to_string());
+    // Proceeds land where PumpFun sells put them (the WSOL ATA) unless JUPITER_SELL_DESTINATION=native;
+    // either way they count towards the SOL + WSOL balance, and the quoted amount is net of any platform fee
+    let options = SwapOptions::for_sell(&wallet_pubkey);
+    logger.critical(format!("💰 Expected {} from sale: {:.6}", if options.keeps_wsol() { "WSOL" } else { "SOL" }, expected_sol));
 
     // Calculate price from quote (price per token)
     let price = if amount_to_sell > 0 {
@@ -319,7 +657,154 @@ async fn execute_jupiter_sell(
         trade_info.post_current_price // Fallback to trade_info price
     };
 
-    // Don't wait for verification - just return the signature with expected values
-    // Verification can happen in background if needed, but shouldn't block selling
-    Ok((signature, expected_sol, price))
-} 
\ No newline at end of file
+    // Jupiter's unsigned swap transaction is simulated as built; nothing is signed or sent
+    if sell_config.dry_run {
+        let transaction = app_state.jupiter_client.get_swap_transaction(quote, &wallet_pubkey, &options).await
+            .map_err(|e| anyhow!("Jupiter swap transaction failed: {}", e))?;
+        let result = app_state.rpc_nonblocking_client.simulate_transaction(&transaction).await
+            .map_err(|e| anyhow!("Jupiter sell simulation request failed: {}", e))?
+            .value;
+        if let Some(err) = result.err {
+            return Err(anyhow!("Jupiter sell simulation failed: {:?}\n{}", err, result.logs.unwrap_or_default().join("\n")));
+        }
+        logger.critical(format!("🧪 [DRY RUN] Jupiter sell simulated for {}", trade_info.mint).yellow().to_string());
+        return Ok((None, expected_sol, price));
+    }
+
+    // Execute sell transaction via Jupiter API (this handles signing and sending)
+    let signature = send_jupiter_sell(app_state.jupiter_client.as_ref(), &app_state.wallet, &trade_info.mint, amount_to_sell, &options).await?;
+
+    logger.log(format!("✅ Jupiter transaction sent: {}", signature).green().to_string());
+    confirm_sent_sell(&app_state, &signature, None, BlockhashMode::Recent, "Jupiter", logger).await?;
+    Ok((Some(signature), expected_sol, price))
+} 
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::scripted_clients::{ScriptedSwapAggregator, Step};
+
+    #[tokio::test]
+    async fn jupiter_sells_supply_the_wsol_destination() {
+        let keypair = Keypair::new();
+        let mint = Pubkey::new_unique().to_string();
+        let sent = Signature::new_unique();
+        let aggregator = ScriptedSwapAggregator::new().then_sell(Step::ok(sent.to_string()));
+
+        let options = SwapOptions::for_sell(&keypair.pubkey());
+        let signature = send_jupiter_sell(&aggregator, &keypair, &mint, 5_000, &options).await.unwrap();
+
+        assert_eq!(signature, sent);
+        let wsol_account = get_associated_token_address(&keypair.pubkey(), &spl_token::native_mint::ID);
+        assert_eq!(aggregator.calls(), vec![format!("sell {} 5000 -> {}", mint, wsol_account)]);
+    }
+
+    #[test]
+    fn an_unconfirmed_nonce_sell_stays_in_doubt() {
+        // The nonce hasn't advanced past it, so it can land whatever the clock says
+        for blockhash_valid in [None, Some(true), Some(false)] {
+            assert_eq!(settle_unconfirmed(None, BlockhashMode::Nonce, blockhash_valid), Unconfirmed::InDoubt);
+        }
+    }
+
+    #[test]
+    fn a_recent_blockhash_sell_falls_back_only_once_its_blockhash_expired() {
+        assert_eq!(settle_unconfirmed(None, BlockhashMode::Recent, Some(true)), Unconfirmed::InDoubt);
+        assert_eq!(settle_unconfirmed(None, BlockhashMode::Recent, None), Unconfirmed::InDoubt);
+        assert!(matches!(settle_unconfirmed(None, BlockhashMode::Recent, Some(false)), Unconfirmed::Retryable(_)));
+    }
+
+    #[test]
+    fn a_status_found_on_the_recheck_decides() {
+        for mode in [BlockhashMode::Nonce, BlockhashMode::Recent] {
+            assert_eq!(settle_unconfirmed(Some(Ok(())), mode, None), Unconfirmed::Landed);
+            let Unconfirmed::Retryable(reason) = settle_unconfirmed(Some(Err("custom program error: 0x1772".to_string())), mode, Some(true)) else {
+                panic!("a failed sell can fall back");
+            };
+            assert!(reason.contains("0x1772"), "{}", reason);
+        }
+    }
+
+    #[test]
+    fn an_in_doubt_sell_ends_the_chain_with_its_signature() {
+        let signature = Signature::new_unique();
+        let error = anyhow::Error::new(SellInDoubt {
+            venue: "PumpFun".to_string(),
+            signature,
+            reason: "not confirmed within 30s".to_string(),
+        });
+        let result = in_doubt_sell_result(&error, false, 1).expect("in doubt");
+        assert!(!result.success);
+        assert!(!result.used_jupiter_fallback);
+        assert_eq!(result.signature, Some(signature));
+        assert!(result.error.unwrap().contains("no fallback is sent"));
+
+        assert!(in_doubt_sell_result(&anyhow!("PumpFun sell {} failed on-chain: 0x1772", signature), false, 1).is_none());
+    }
+
+    fn sold() -> Result<SellTransactionResult> {
+        Ok(SellTransactionResult {
+            success: true,
+            signature: Some(Signature::new_unique()),
+            error: None,
+            used_jupiter_fallback: false,
+            attempt_count: 1,
+            simulated: false,
+        })
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn concurrent_sells_only_clear_the_mark_they_set() {
+        const SELLS: usize = 20;
+        let mint = Pubkey::new_unique().to_string();
+        let start = Arc::new(tokio::sync::Barrier::new(SELLS));
+        let finish = Arc::new(tokio::sync::Notify::new());
+        let (turned_away_tx, mut turned_away) = tokio::sync::mpsc::unbounded_channel();
+
+        let sells: Vec<_> = (0..SELLS)
+            .map(|_| {
+                let (mint, start, finish, turned_away_tx) = (mint.clone(), start.clone(), finish.clone(), turned_away_tx.clone());
+                tokio::spawn(async move {
+                    start.wait().await;
+                    let logger = Logger::new(String::new());
+                    let chain = async {
+                        finish.notified().await;
+                        sold()
+                    };
+                    let (result, claim) = run_claimed(&mint, &logger, chain).await;
+                    // As LiveExecutor::sell does once the chain returned
+                    let claimed = claim.is_some();
+                    drop(claim);
+                    let result = result.unwrap();
+                    if !claimed {
+                        // Turned away while the other sell runs: its mark must survive this call
+                        assert_eq!(result.error.as_deref(), Some("sell already in progress"));
+                        turned_away_tx.send(PROGRESS_ON_SELLING.contains_key(&mint)).unwrap();
+                    }
+                    result.success
+                })
+            })
+            .collect();
+        drop(turned_away_tx);
+
+        for _ in 0..SELLS - 1 {
+            assert!(turned_away.recv().await.unwrap(), "a turned-away sell cleared the running sell's mark");
+        }
+        assert!(PROGRESS_ON_SELLING.contains_key(&mint));
+        finish.notify_one();
+
+        let mut succeeded = 0;
+        for sell in sells {
+            succeeded += sell.await.unwrap() as usize;
+        }
+        assert_eq!(succeeded, 1);
+        assert!(!PROGRESS_ON_SELLING.contains_key(&mint));
+        assert!(!SELL_LOCKS.contains_key(&mint));
+
+        // The mint is free for the next sell
+        let (result, claim) = run_claimed(&mint, &Logger::new(String::new()), async { sold() }).await;
+        assert!(result.unwrap().success && claim.is_some());
+        claim.unwrap().keep_in_progress();
+        assert!(PROGRESS_ON_SELLING.contains_key(&mint));
+        PROGRESS_ON_SELLING.remove(&mint);
+    }
+}
@@ -0,0 +1,323 @@
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use async_trait::async_trait;
+use colored::Colorize;
+use dashmap::DashMap;
+
+use crate::common::{clock, logger::Logger};
+use crate::engine::gap_catchup::{GapReader, RpcGapReader};
+use crate::engine::position::{Position, PositionBook, Valuation, ValuationSource};
+use crate::engine::transaction_parser::DexType;
+use crate::services::jupiter_api::{SwapAggregator, SwapMode};
+use crate::services::rpc_pool::RpcHandle;
+
+const SOL_MINT: &str = "So11111111111111111111111111111111111111112";
+/// Pump mints use 6 decimals; tick prices are SOL per UI token at that scale
+const PUMP_TOKEN_DECIMALS: i32 = 6;
+/// Tokens quoted for a valuation: small enough that price impact stays negligible
+const QUOTE_UI_TOKENS: f64 = 1_000.0;
+/// How often stale positions are looked for
+const REFRESH_INTERVAL: Duration = Duration::from_secs(10);
+
+/// SOL/USD price (f64 bits), 0 until set
+static SOL_USD: AtomicU64 = AtomicU64::new(0);
+
+/// Set the SOL/USD price used for USD values
+pub fn set_sol_usd(price: f64) {
+    SOL_USD.store(price.to_bits(), Ordering::Relaxed);
+}
+
+pub fn sol_usd() -> Option<f64> {
+    Some(f64::from_bits(SOL_USD.load(Ordering::Relaxed))).filter(|price| *price > 0.0)
+}
+
+/// VALUATION_STALE_SECS (default: 120) without a tick makes a held valuation stale; at most
+/// VALUATION_MAX_RPC_PER_MIN (default: 6, 0 disables) requests a minute go to refreshing them
+#[derive(Clone, Copy, Debug)]
+pub struct ValuationConfig {
+    pub stale_secs: u64,
+    pub max_rpc_per_min: usize,
+}
+
+impl ValuationConfig {
+    pub fn from_env() -> Self {
+        let stale_secs = std::env::var("VALUATION_STALE_SECS")
+            .ok()
+            .and_then(|v| v.parse::<u64>().ok())
+            .unwrap_or(120);
+        let max_rpc_per_min = std::env::var("VALUATION_MAX_RPC_PER_MIN")
+            .ok()
+            .and_then(|v| v.parse::<usize>().ok())
+            .unwrap_or(6);
+        Self { stale_secs, max_rpc_per_min }
+    }
+
+    pub fn enabled(&self) -> bool {
+        self.max_rpc_per_min > 0
+    }
+}
+
+/// Positions whose valuation is older than `stale_secs`, oldest first
+pub fn stale_positions(positions: Vec<Position>, now: u64, stale_secs: u64) -> Vec<Position> {
+    let mut stale: Vec<Position> = positions
+        .into_iter()
+        .filter(|position| position.remaining_tokens() > 0.0 && position.valuation.age_secs(now) > stale_secs)
+        .collect();
+    stale.sort_by_key(|position| position.valuation.at);
+    stale
+}
+
+/// Sliding one-minute request budget
+pub struct RequestBudget {
+    per_minute: usize,
+    spent: Mutex<VecDeque<u64>>,
+}
+
+impl RequestBudget {
+    pub fn new(per_minute: usize) -> Self {
+        Self { per_minute, spent: Mutex::new(VecDeque::new()) }
+    }
+
+    /// Spend one request at `now` if fewer than `per_minute` were spent in the last 60 seconds
+    pub fn try_take(&self, now: u64) -> bool {
+        let mut spent = self.spent.lock().unwrap_or_else(|e| e.into_inner());
+        while spent.front().is_some_and(|at| *at + 60 <= now) {
+            spent.pop_front();
+        }
+        if spent.len() >= self.per_minute {
+            return false;
+        }
+        spent.push_back(now);
+        true
+    }
+
+    pub fn remaining(&self, now: u64) -> usize {
+        let spent = self.spent.lock().unwrap_or_else(|e| e.into_inner());
+        self.per_minute.saturating_sub(spent.iter().filter(|at| **at + 60 > now).count())
+    }
+}
+
+/// Price reads for quiet positions, in the units of tick prices
+#[async_trait]
+pub trait PriceEstimator: Send + Sync {
+    /// Price at the mint's bonding curve or pool reserves
+    async fn curve_price(&self, mint: &str, dex_type: DexType) -> Result<f64, String>;
+
+    /// Price of a small sell quote
+    async fn quote_price(&self, mint: &str) -> Result<f64, String>;
+}
+
+/// Curve and pool accounts through the read pool, quotes through the swap aggregator
+pub struct RpcPriceEstimator {
+    reader: RpcGapReader,
+    aggregator: Arc<dyn SwapAggregator>,
+}
+
+impl RpcPriceEstimator {
+    pub fn new(read_rpc: RpcHandle, aggregator: Arc<dyn SwapAggregator>) -> Self {
+        Self { reader: RpcGapReader::new(read_rpc), aggregator }
+    }
+}
+
+#[async_trait]
+impl PriceEstimator for RpcPriceEstimator {
+    async fn curve_price(&self, mint: &str, dex_type: DexType) -> Result<f64, String> {
+        let pool = self.reader.pool_state(mint, dex_type).await?;
+        if pool.price <= 0.0 {
+            return Err(format!("no reserves for {}", mint));
+        }
+        Ok(pool.price)
+    }
+
+    async fn quote_price(&self, mint: &str) -> Result<f64, String> {
+        let amount = (QUOTE_UI_TOKENS * 10f64.powi(PUMP_TOKEN_DECIMALS)) as u64;
+        let quote = self
+            .aggregator
+            .get_quote(mint, SOL_MINT, amount, 100, SwapMode::ExactIn)
+            .await
+            .map_err(|e| format!("quote failed for {}: {}", mint, e))?;
+        let out_lamports = quote.out_amount.parse::<u64>().map_err(|e| format!("bad quote out amount: {}", e))?;
+        Ok(out_lamports as f64 / 1_000_000_000.0 / QUOTE_UI_TOKENS)
+    }
+}
+
+/// Prices set by hand (harnesses); a mint without a price fails
+#[derive(Default)]
+pub struct FixedPriceEstimator {
+    curve: DashMap<String, f64>,
+    quote: DashMap<String, f64>,
+}
+
+impl FixedPriceEstimator {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn set_curve(&self, mint: &str, price: f64) {
+        self.curve.insert(mint.to_string(), price);
+    }
+
+    pub fn set_quote(&self, mint: &str, price: f64) {
+        self.quote.insert(mint.to_string(), price);
+    }
+}
+
+#[async_trait]
+impl PriceEstimator for FixedPriceEstimator {
+    async fn curve_price(&self, mint: &str, _dex_type: DexType) -> Result<f64, String> {
+        self.curve.get(mint).map(|price| *price).ok_or_else(|| format!("no curve price for {}", mint))
+    }
+
+    async fn quote_price(&self, mint: &str) -> Result<f64, String> {
+        self.quote.get(mint).map(|price| *price).ok_or_else(|| format!("no quote for {}", mint))
+    }
+}
+
+/// Low-priority refresh of held valuations that ticks stopped updating
+/// Every read spends one unit of the per-minute budget, so a book of quiet positions can't compete with trading;
+/// positions left over wait for the next round, oldest first. Curve positions read their account and fall back to
+/// a quote; anything else is quoted.
+pub struct ValuationRefresher {
+    config: ValuationConfig,
+    budget: RequestBudget,
+    estimator: Arc<dyn PriceEstimator>,
+    logger: Logger,
+}
+
+impl ValuationRefresher {
+    pub fn new(config: ValuationConfig, estimator: Arc<dyn PriceEstimator>) -> Self {
+        Self {
+            config,
+            budget: RequestBudget::new(config.max_rpc_per_min),
+            estimator,
+            logger: Logger::new("[VALUATION] => ".blue().to_string()),
+        }
+    }
+
+    /// Refresh what the budget allows at `now`; returns the valuations stored
+    pub async fn refresh_once(&self, positions: &PositionBook, now: u64) -> Vec<(String, Valuation)> {
+        let mut refreshed = Vec::new();
+        for position in stale_positions(positions.snapshot(), now, self.config.stale_secs) {
+            let Some(valuation) = self.estimate(&position, now).await else {
+                if self.budget.remaining(now) == 0 {
+                    break;
+                }
+                continue;
+            };
+            positions.set_valuation(&position.mint, valuation);
+            refreshed.push((position.mint, valuation));
+        }
+        refreshed
+    }
+
+    async fn estimate(&self, position: &Position, now: u64) -> Option<Valuation> {
+        if matches!(position.dex_type, DexType::PumpFun | DexType::PumpSwap) && self.budget.try_take(now) {
+            match self.estimator.curve_price(&position.mint, position.dex_type).await {
+                Ok(price) => return Some(Valuation { price, source: ValuationSource::Curve, at: now }),
+                Err(e) => self.logger.error(e),
+            }
+        }
+        if !self.budget.try_take(now) {
+            return None;
+        }
+        match self.estimator.quote_price(&position.mint).await {
+            Ok(price) if price > 0.0 => Some(Valuation { price, source: ValuationSource::Quote, at: now }),
+            Ok(_) => None,
+            Err(e) => {
+                self.logger.error(e);
+                None
+            }
+        }
+    }
+
+    pub fn start(self: &Arc<Self>, positions: Arc<PositionBook>) {
+        let refresher = self.clone();
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(REFRESH_INTERVAL);
+            loop {
+                interval.tick().await;
+                refresher.refresh_once(&positions, clock::now_secs()).await;
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::position::Lot;
+    use crate::engine::test_fixtures::{exit, lot, pubkey};
+
+    const NOW: u64 = 1_000_000;
+
+    fn tick(price: f64, at: u64) -> Valuation {
+        Valuation { price, source: ValuationSource::Tick, at }
+    }
+
+    /// A held position of `dex_type` last valued `age` seconds before NOW
+    fn held(positions: &PositionBook, dex_type: DexType, age: u64) -> String {
+        let mint = pubkey();
+        let bought = Lot { timestamp: NOW - age, ..lot(&format!("buy-{}", mint), 1_000.0, 0.1) };
+        positions.record_buy(&mint, dex_type, None, bought);
+        mint
+    }
+
+    #[test]
+    fn only_held_positions_past_the_threshold_are_stale() {
+        let positions = PositionBook::new();
+        let recent = held(&positions, DexType::PumpFun, 30);
+        let old = held(&positions, DexType::PumpFun, 600);
+        let older = held(&positions, DexType::RaydiumAmm, 900);
+        let sold = held(&positions, DexType::PumpFun, 900);
+        positions.record_exit(&sold, exit("sell", 1_000.0, 0.2, "tp"));
+
+        let stale: Vec<String> = stale_positions(positions.snapshot(), NOW, 120).into_iter().map(|position| position.mint).collect();
+        assert_eq!(stale, vec![older, old], "oldest first, recent and sold out excluded");
+        assert!(!stale.contains(&recent));
+    }
+
+    #[test]
+    fn budget_slides_over_a_minute() {
+        let budget = RequestBudget::new(2);
+        assert!(budget.try_take(0));
+        assert!(budget.try_take(10));
+        assert!(!budget.try_take(59));
+        assert_eq!(budget.remaining(59), 0);
+        assert_eq!(budget.remaining(60), 1);
+        assert!(budget.try_take(60));
+        assert!(!budget.try_take(61));
+        assert!(budget.try_take(70));
+    }
+
+    #[tokio::test]
+    async fn refresh_tags_estimates_and_stops_at_the_budget() {
+        let positions = PositionBook::new();
+        // Oldest first: a curve read that fails and falls back to a quote, a curve read, then no budget left
+        let fallback = held(&positions, DexType::PumpFun, 900);
+        let curve = held(&positions, DexType::PumpSwap, 600);
+        let starved = held(&positions, DexType::PumpFun, 300);
+        let estimator = Arc::new(FixedPriceEstimator::new());
+        estimator.set_quote(&fallback, 0.0002);
+        estimator.set_curve(&curve, 0.0003);
+        estimator.set_curve(&starved, 0.0004);
+        let refresher = ValuationRefresher::new(ValuationConfig { stale_secs: 120, max_rpc_per_min: 3 }, estimator);
+
+        let refreshed = refresher.refresh_once(&positions, NOW).await;
+        assert_eq!(refreshed.len(), 2);
+        assert_eq!(
+            positions.get(&fallback).unwrap().valuation,
+            Valuation { price: 0.0002, source: ValuationSource::Quote, at: NOW }
+        );
+        assert_eq!(
+            positions.get(&curve).unwrap().valuation,
+            Valuation { price: 0.0003, source: ValuationSource::Curve, at: NOW }
+        );
+        assert_eq!(positions.get(&starved).unwrap().valuation, tick(0.0001, NOW - 300));
+
+        // The next minute reaches it
+        let refreshed = refresher.refresh_once(&positions, NOW + 60).await;
+        assert_eq!(refreshed, vec![(starved, Valuation { price: 0.0004, source: ValuationSource::Curve, at: NOW + 60 })]);
+    }
+}
@@ -0,0 +1,270 @@
+use std::collections::{BTreeMap, BTreeSet};
+use dashmap::DashMap;
+use once_cell::sync::Lazy;
+use yellowstone_grpc_proto::geyser::SubscribeUpdateTransaction;
+use yellowstone_grpc_proto::prelude::TokenBalance;
+
+use crate::common::clock;
+use crate::engine::position::{ExitFill, Lot, Position, PositionBook, PositionState};
+
+const LAMPORTS_PER_SOL_F64: f64 = 1_000_000_000.0;
+/// Balance differences below one raw unit at 6 decimals are rounding, not a change
+const DUST_TOKENS: f64 = 0.000_001;
+/// How long a signature the bot sent is remembered; far past any landing
+const BOT_SIGNATURE_TTL_SECS: u64 = 600;
+
+/// Signatures of transactions the bot signed, with when; their balance changes are the executor's to record
+static BOT_SIGNATURES: Lazy<DashMap<String, u64>> = Lazy::new(DashMap::new);
+
+/// Remember a transaction the bot is about to send, before it can show up on the stream
+pub fn register_bot_signature(signature: &str) {
+    let now = clock::now_secs();
+    BOT_SIGNATURES.retain(|_, sent_at| now.saturating_sub(*sent_at) < BOT_SIGNATURE_TTL_SECS);
+    BOT_SIGNATURES.insert(signature.to_string(), now);
+}
+
+pub fn is_bot_signature(signature: &str) -> bool {
+    BOT_SIGNATURES.contains_key(signature)
+}
+
+/// Our wallet's balance of one mint after a streamed transaction touching it
+#[derive(Clone, Debug, PartialEq)]
+pub struct BalanceChange {
+    pub signature: String,
+    pub slot: u64,
+    pub mint: String,
+    /// UI tokens across the wallet's accounts of the mint in the transaction
+    pub post_amount: f64,
+    /// SOL the wallet gained in the transaction (negative when it paid), fees included
+    pub sol_delta: f64,
+}
+
+/// Every mint whose balance the transaction changed for `wallet` (base58)
+/// The wallet is expected to hold each mint in one token account, which is then what the post balances add up to.
+pub fn balance_changes(txn: &SubscribeUpdateTransaction, wallet: &str) -> Vec<BalanceChange> {
+    let Some(info) = txn.transaction.as_ref() else {
+        return Vec::new();
+    };
+    let Some(meta) = info.meta.as_ref() else {
+        return Vec::new();
+    };
+    let owned = |balances: &[TokenBalance]| {
+        let mut amounts: BTreeMap<String, f64> = BTreeMap::new();
+        for balance in balances.iter().filter(|balance| balance.owner == wallet) {
+            let amount = balance.ui_token_amount.as_ref().map(|amount| amount.ui_amount).unwrap_or(0.0);
+            *amounts.entry(balance.mint.clone()).or_default() += amount;
+        }
+        amounts
+    };
+    let before = owned(&meta.pre_token_balances);
+    let after = owned(&meta.post_token_balances);
+
+    // Lamport balances are indexed like the static keys; an airdrop may not list the wallet at all
+    let sol_delta = info.transaction.as_ref()
+        .and_then(|transaction| transaction.message.as_ref())
+        .and_then(|message| message.account_keys.iter().position(|key| bs58::encode(key).into_string() == wallet))
+        .and_then(|index| Some((*meta.pre_balances.get(index)?, *meta.post_balances.get(index)?)))
+        .map(|(pre, post)| (post as f64 - pre as f64) / LAMPORTS_PER_SOL_F64)
+        .unwrap_or(0.0);
+    let signature = bs58::encode(&info.signature).into_string();
+
+    let mints: BTreeSet<&String> = before.keys().chain(after.keys()).collect();
+    mints
+        .into_iter()
+        .filter_map(|mint| {
+            let pre_amount = before.get(mint).copied().unwrap_or(0.0);
+            let post_amount = after.get(mint).copied().unwrap_or(0.0);
+            ((post_amount - pre_amount).abs() >= DUST_TOKENS).then(|| BalanceChange {
+                signature: signature.clone(),
+                slot: txn.slot,
+                mint: mint.clone(),
+                post_amount,
+                sol_delta,
+            })
+        })
+        .collect()
+}
+
+/// What a balance change did to the book
+#[derive(Clone, Debug)]
+pub enum Reconciliation {
+    /// Not held, our own transaction, a position mid-trade, or already in line
+    Unchanged,
+    /// Remaining tokens went from `from` to the balance the chain reports
+    Adjusted { from: f64, position: Position },
+    /// The balance reached zero; the position is still in the book for the close path to remove
+    Closed { from: f64, position: Position },
+}
+
+/// Bring an Open position's remaining tokens in line with a change made outside the bot: a manual sell shows up
+/// as an exit priced by the SOL it returned, tokens that arrived as a lot (at entry price when they cost nothing)
+/// Pending and Closing positions are left to the executor, whose own transaction will settle them.
+pub fn reconcile(book: &PositionBook, change: &BalanceChange) -> Reconciliation {
+    if is_bot_signature(&change.signature) {
+        return Reconciliation::Unchanged;
+    }
+    let Some(position) = book.get(&change.mint) else {
+        return Reconciliation::Unchanged;
+    };
+    let recorded = position.lots.iter().any(|lot| lot.signature == change.signature)
+        || position.exits.iter().any(|exit| exit.signature == change.signature);
+    if recorded || position.state != PositionState::Open {
+        return Reconciliation::Unchanged;
+    }
+    let from = position.remaining_tokens();
+    let difference = change.post_amount - from;
+    if difference.abs() < DUST_TOKENS {
+        return Reconciliation::Unchanged;
+    }
+
+    let timestamp = clock::now_secs();
+    let updated = if difference < 0.0 {
+        let sold = -difference;
+        let sol_received = change.sol_delta.max(0.0);
+        book.record_exit(&change.mint, ExitFill {
+            signature: change.signature.clone(),
+            token_amount: sold,
+            sol_received,
+            price: if sol_received > 0.0 { sol_received / sold } else { position.last_price },
+            reason: "external".to_string(),
+            timestamp,
+            costs_sol: 0.0,
+        })
+    } else {
+        let sol_spent = (-change.sol_delta).max(0.0);
+        book.add_lot(&change.mint, Lot {
+            signature: change.signature.clone(),
+            token_amount: difference,
+            sol_spent,
+            price: if sol_spent > 0.0 { sol_spent / difference } else { position.entry_price() },
+            timestamp,
+            costs_sol: 0.0,
+            size_tier: None,
+        })
+    };
+    match updated {
+        Some(position) if position.is_closed() => Reconciliation::Closed { from, position },
+        Some(position) => Reconciliation::Adjusted { from, position },
+        None => Reconciliation::Unchanged,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use anchor_client::solana_sdk::pubkey::Pubkey;
+    use yellowstone_grpc_proto::prelude::{Message, SubscribeUpdateTransactionInfo, Transaction, TransactionStatusMeta, UiTokenAmount};
+
+    use super::*;
+    use crate::engine::test_fixtures::{book_with, pubkey};
+
+    fn balance(owner: &str, mint: &str, ui_amount: f64) -> TokenBalance {
+        TokenBalance {
+            mint: mint.to_string(),
+            owner: owner.to_string(),
+            ui_token_amount: Some(UiTokenAmount { ui_amount, decimals: 6, ..Default::default() }),
+            ..Default::default()
+        }
+    }
+
+    fn change(signature: &str, mint: &str, post_amount: f64, sol_delta: f64) -> BalanceChange {
+        BalanceChange { signature: signature.to_string(), slot: 1, mint: mint.to_string(), post_amount, sol_delta }
+    }
+
+    #[test]
+    fn balance_changes_cover_the_wallets_mints_only() {
+        let (wallet, other) = (Pubkey::new_unique(), Pubkey::new_unique().to_string());
+        let (sold, received, dust) = (pubkey(), pubkey(), pubkey());
+        let wallet_key = wallet.to_string();
+        let txn = SubscribeUpdateTransaction {
+            slot: 42,
+            transaction: Some(SubscribeUpdateTransactionInfo {
+                signature: vec![3; 64],
+                transaction: Some(Transaction {
+                    signatures: Vec::new(),
+                    message: Some(Message {
+                        account_keys: vec![Pubkey::new_unique().to_bytes().to_vec(), wallet.to_bytes().to_vec()],
+                        ..Default::default()
+                    }),
+                }),
+                meta: Some(TransactionStatusMeta {
+                    pre_balances: vec![5_000_000_000, 1_000_000_000],
+                    post_balances: vec![4_500_000_000, 1_495_000_000],
+                    pre_token_balances: vec![
+                        balance(&wallet_key, &sold, 1_000.0),
+                        balance(&wallet_key, &dust, 5.0),
+                        balance(&other, &received, 70.0),
+                    ],
+                    post_token_balances: vec![
+                        balance(&wallet_key, &sold, 250.0),
+                        balance(&wallet_key, &received, 80.0),
+                        balance(&wallet_key, &dust, 5.000_000_1),
+                        balance(&other, &received, 0.0),
+                    ],
+                    ..Default::default()
+                }),
+                ..Default::default()
+            }),
+        };
+
+        let mut changes = balance_changes(&txn, &wallet_key);
+        changes.sort_by(|a, b| a.mint.cmp(&b.mint));
+        let mut expected = vec![
+            BalanceChange { signature: bs58::encode([3; 64]).into_string(), slot: 42, mint: sold, post_amount: 250.0, sol_delta: 0.495 },
+            BalanceChange { signature: bs58::encode([3; 64]).into_string(), slot: 42, mint: received, post_amount: 80.0, sol_delta: 0.495 },
+        ];
+        expected.sort_by(|a, b| a.mint.cmp(&b.mint));
+        assert_eq!(changes, expected);
+
+        assert!(balance_changes(&txn, &pubkey()).is_empty());
+    }
+
+    #[test]
+    fn holdings_converge_on_the_reported_balance() {
+        let mint = pubkey();
+        let book = book_with(&mint, 1_000.0, 1.0);
+        let series = [
+            // Sold by hand for 0.3 SOL, tokens airdropped, bought by hand for 0.2 SOL
+            (change("manual-sell", &mint, 600.0, 0.3), 600.0),
+            (change("airdrop", &mint, 700.0, 0.0), 700.0),
+            (change("manual-buy", &mint, 900.0, -0.2), 900.0),
+            // The same transaction again, and one under a rounding step
+            (change("manual-buy", &mint, 900.0, -0.2), 900.0),
+            (change("rounding", &mint, 900.000_000_5, 0.0), 900.0),
+        ];
+        for (change, expected) in &series {
+            reconcile(&book, change);
+            let remaining = book.get(&mint).unwrap().remaining_tokens();
+            assert!((remaining - expected).abs() < 1e-9, "{}: {} tokens, expected {}", change.signature, remaining, expected);
+        }
+
+        let position = book.get(&mint).unwrap();
+        assert_eq!(position.exits.len(), 1);
+        assert_eq!(position.exits[0].reason, "external");
+        assert!((position.exits[0].price - 0.3 / 400.0).abs() < 1e-12);
+        assert_eq!(position.lots.len(), 3);
+        // The free tokens carry the entry price, the bought ones what they cost
+        assert!((position.lots[1].price - 0.001).abs() < 1e-12 && position.lots[1].sol_spent == 0.0);
+        assert!((position.lots[2].price - 0.001).abs() < 1e-12 && position.lots[2].sol_spent == 0.2);
+
+        let Reconciliation::Closed { from, position } = reconcile(&book, &change("emptied", &mint, 0.0, 0.9)) else {
+            panic!("an empty balance should close the position");
+        };
+        assert_eq!(from, 900.0);
+        assert!(position.is_closed());
+    }
+
+    #[test]
+    fn own_pending_and_unheld_changes_are_left_alone() {
+        let mint = pubkey();
+        let book = book_with(&mint, 1_000.0, 1.0);
+
+        register_bot_signature("bot-sell");
+        assert!(matches!(reconcile(&book, &change("bot-sell", &mint, 0.0, 1.0)), Reconciliation::Unchanged));
+        assert!(matches!(reconcile(&book, &change(&format!("buy-{}", mint), &mint, 0.0, 1.0)), Reconciliation::Unchanged));
+        assert!(matches!(reconcile(&book, &change("elsewhere", &pubkey(), 10.0, 0.0)), Reconciliation::Unchanged));
+
+        book.set_state(&mint, PositionState::Closing);
+        assert!(matches!(reconcile(&book, &change("mid-sell", &mint, 0.0, 1.0)), Reconciliation::Unchanged));
+        assert_eq!(book.get(&mint).unwrap().remaining_tokens(), 1_000.0);
+    }
+}
@@ -0,0 +1,149 @@
+//! Classification of send/confirm failures by their error text
+//!
+//! Errors reach us as strings from several layers (zeroslot JSON-RPC, the Solana RPC client,
+//! Jupiter), so classification is substring based and case-insensitive.
+
+use std::fmt;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum ErrorClass {
+    /// The token account was frozen by the mint's freeze authority; sells can never succeed
+    AccountFrozen,
+    /// Durable nonce problems: not advanced, wrong authority, bad nonce account
+    Nonce,
+    /// The blockhash the transaction was signed with is unknown or expired
+    BlockhashNotFound,
+    InsufficientFunds,
+    SlippageExceeded,
+    /// An IOC buy's Lighthouse slot assertion failed: it landed too late and cancelled itself
+    IocCancelled,
+    /// Timeouts, connection resets, rate limits
+    Network,
+    Other,
+}
+
+impl ErrorClass {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ErrorClass::AccountFrozen => "account_frozen",
+            ErrorClass::Nonce => "nonce",
+            ErrorClass::BlockhashNotFound => "blockhash_not_found",
+            ErrorClass::InsufficientFunds => "insufficient_funds",
+            ErrorClass::SlippageExceeded => "slippage_exceeded",
+            ErrorClass::IocCancelled => "ioc_cancelled",
+            ErrorClass::Network => "network",
+            ErrorClass::Other => "other",
+        }
+    }
+
+    /// Whether this failure points at the durable nonce when the transaction was sent in nonce mode
+    /// A stale cached nonce hash surfaces as "blockhash not found", so that counts too.
+    pub fn is_nonce_related(&self) -> bool {
+        matches!(self, ErrorClass::Nonce | ErrorClass::BlockhashNotFound)
+    }
+}
+
+impl fmt::Display for ErrorClass {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+// SPL token "Error: Account is frozen" log line and the TokenError::AccountFrozen variant name
+const FROZEN_PATTERNS: &[&str] = &["account is frozen", "accountfrozen"];
+
+// Covers NonceNoRecentBlockhashes, "nonce has not been advanced", advance-nonce authority errors, ...
+const NONCE_PATTERNS: &[&str] = &["nonce"];
+
+const BLOCKHASH_PATTERNS: &[&str] = &[
+    "blockhash not found",
+    "blockhashnotfound",
+    "block height exceeded",
+    "transaction expired",
+];
+
+const INSUFFICIENT_FUNDS_PATTERNS: &[&str] = &[
+    "insufficient funds",
+    "insufficientfunds",
+    "insufficient lamports",
+    "custom program error: 0x1\"",
+];
+
+// Lighthouse's program id in the failing instruction's logs, and its AssertionFailed error (6001), by name or
+// by the code a landed transaction's status carries
+const IOC_PATTERNS: &[&str] = &[
+    "l2texmfkdjpn9kozasaurpirfhy9p8sbxoan1qa3s95",
+    "lighthouse",
+    "assertionfailed",
+    "custom program error: 0x1771",
+];
+
+const SLIPPAGE_PATTERNS: &[&str] = &[
+    "slippage",
+    "toomuchsolrequired",
+    "toolittlesolreceived",
+    "custom program error: 0x1772",
+    "custom program error: 0x1773",
+];
+
+const NETWORK_PATTERNS: &[&str] = &[
+    "timed out",
+    "timeout",
+    "connection reset",
+    "connection refused",
+    "broken pipe",
+    "too many requests",
+    "429",
+    "503",
+];
+
+/// Classify an error message; the first matching class wins, frozen and nonce checks first
+pub fn classify_error(message: &str) -> ErrorClass {
+    let message = message.to_lowercase();
+    let matches = |patterns: &[&str]| patterns.iter().any(|pattern| message.contains(pattern));
+
+    if matches(FROZEN_PATTERNS) {
+        ErrorClass::AccountFrozen
+    } else if matches(NONCE_PATTERNS) {
+        ErrorClass::Nonce
+    } else if matches(BLOCKHASH_PATTERNS) {
+        ErrorClass::BlockhashNotFound
+    } else if matches(INSUFFICIENT_FUNDS_PATTERNS) {
+        ErrorClass::InsufficientFunds
+    } else if matches(IOC_PATTERNS) {
+        ErrorClass::IocCancelled
+    } else if matches(SLIPPAGE_PATTERNS) {
+        ErrorClass::SlippageExceeded
+    } else if matches(NETWORK_PATTERNS) {
+        ErrorClass::Network
+    } else {
+        ErrorClass::Other
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn frozen_account_errors_are_recognised() {
+        assert_eq!(classify_error("Program log: Error: Account is frozen"), ErrorClass::AccountFrozen);
+        assert_eq!(classify_error("custom program error: TokenError::AccountFrozen"), ErrorClass::AccountFrozen);
+    }
+
+    #[test]
+    fn frozen_wins_over_other_patterns_in_the_same_message() {
+        assert_eq!(classify_error("simulation failed: account is frozen (slippage exceeded)"), ErrorClass::AccountFrozen);
+    }
+
+    #[test]
+    fn a_landed_assertion_failure_is_an_ioc_cancellation() {
+        assert_eq!(classify_error("Error processing Instruction 4: custom program error: 0x1771"), ErrorClass::IocCancelled);
+        assert_eq!(classify_error("Error processing Instruction 3: custom program error: 0x1772"), ErrorClass::SlippageExceeded);
+    }
+
+    #[test]
+    fn unknown_messages_are_other() {
+        assert_eq!(classify_error("something unexpected"), ErrorClass::Other);
+    }
+}
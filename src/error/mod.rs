@@ -17,6 +17,11 @@
 //! - `SimulationError`: Transaction simulation failed.
 //! - `RateLimitExceeded`: Rate limit exceeded.
 
+pub mod classify;
+pub mod sniper;
+
+pub use sniper::{SniperError, SniperResult};
+
 use serde_json::Error;
 use anchor_client::solana_client::{
     client_error::ClientError as SolanaClientError, pubsub_client::PubsubClientError,
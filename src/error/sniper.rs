@@ -0,0 +1,295 @@
+//! Crate-wide error for the trading and wallet flows
+//!
+//! Failures are sorted into variants where they are raised, from the Solana client's structured error where
+//! there is one, so retry decisions match on the variant instead of the message text.
+
+use anchor_client::solana_client::client_error::{ClientError as SolanaClientError, ClientErrorKind};
+use anchor_client::solana_client::rpc_request::RpcError as SolanaRpcError;
+use anchor_client::solana_sdk::instruction::InstructionError;
+use anchor_client::solana_sdk::transaction::TransactionError;
+
+use crate::error::classify::{classify_error, ErrorClass};
+
+/// PumpFun's TooMuchSolRequired and TooLittleSolReceived
+const SLIPPAGE_ERROR_CODES: &[u32] = &[6002, 6003];
+
+/// JSON-RPC "node is behind" / "node unhealthy", which another attempt or node gets past
+const RETRIABLE_RPC_CODES: &[i64] = &[-32004, -32005];
+
+#[derive(Debug, thiserror::Error)]
+pub enum SniperError {
+    /// The RPC node or HTTP API could not serve the request; `retriable` for timeouts, connection errors and
+    /// unhealthy nodes
+    #[error("{message}")]
+    RpcError { message: String, retriable: bool },
+    /// The transaction's blockhash is unknown to the cluster or expired; re-sign with a fresh one
+    #[error("{0}")]
+    BlockhashExpired(String),
+    /// Not enough SOL for the amount, fees, rent or MIN_SOL_RESERVE
+    #[error("{0}")]
+    InsufficientBalance(String),
+    /// Jupiter returned no usable quote or swap transaction
+    #[error("{0}")]
+    QuoteFailed(String),
+    #[error("{0}")]
+    SlippageExceeded(String),
+    #[error("{0}")]
+    TokenAccountMissing(String),
+    /// The user declined the confirmation prompt, or it could not be shown
+    #[error("{0}")]
+    Cancelled(String),
+    #[error("{0}")]
+    Other(String),
+}
+
+pub type SniperResult<T> = Result<T, SniperError>;
+
+impl SniperError {
+    pub fn rpc(message: impl Into<String>, retriable: bool) -> Self {
+        SniperError::RpcError { message: message.into(), retriable }
+    }
+
+    /// Prefix the message with what was being done, keeping the variant
+    pub fn context(mut self, context: &str) -> Self {
+        let message = self.message_mut();
+        *message = format!("{}: {}", context, message);
+        self
+    }
+
+    fn message_mut(&mut self) -> &mut String {
+        match self {
+            SniperError::RpcError { message, .. } => message,
+            SniperError::BlockhashExpired(message)
+            | SniperError::InsufficientBalance(message)
+            | SniperError::QuoteFailed(message)
+            | SniperError::SlippageExceeded(message)
+            | SniperError::TokenAccountMissing(message)
+            | SniperError::Cancelled(message)
+            | SniperError::Other(message) => message,
+        }
+    }
+
+    /// Whether resending the same request (re-signed, for a blockhash) can succeed
+    pub fn is_retriable(&self) -> bool {
+        match self {
+            SniperError::RpcError { retriable, .. } => *retriable,
+            SniperError::BlockhashExpired(_) => true,
+            _ => false,
+        }
+    }
+
+    /// The send/confirm class, for the error stats and sell retry policy; untyped failures fall back to the text
+    pub fn class(&self) -> ErrorClass {
+        match self {
+            SniperError::RpcError { retriable: true, .. } => ErrorClass::Network,
+            SniperError::BlockhashExpired(_) => ErrorClass::BlockhashNotFound,
+            SniperError::InsufficientBalance(_) => ErrorClass::InsufficientFunds,
+            SniperError::SlippageExceeded(_) => ErrorClass::SlippageExceeded,
+            SniperError::TokenAccountMissing(_) | SniperError::Cancelled(_) => ErrorClass::Other,
+            SniperError::RpcError { message, .. } | SniperError::QuoteFailed(message) | SniperError::Other(message) => {
+                classify_error(message)
+            }
+        }
+    }
+}
+
+/// Class of an error out of an anyhow layer: the variant's when a SniperError is in the chain, else the text's
+pub fn classify_anyhow(error: &anyhow::Error) -> ErrorClass {
+    match error.downcast_ref::<SniperError>() {
+        Some(error) => error.class(),
+        None => classify_error(&error.to_string()),
+    }
+}
+
+impl From<SolanaClientError> for SniperError {
+    fn from(error: SolanaClientError) -> Self {
+        let message = error.to_string();
+        match error.get_transaction_error() {
+            Some(TransactionError::BlockhashNotFound) => return SniperError::BlockhashExpired(message),
+            Some(TransactionError::InsufficientFundsForFee | TransactionError::InsufficientFundsForRent { .. }) => {
+                return SniperError::InsufficientBalance(message);
+            }
+            Some(TransactionError::InstructionError(_, InstructionError::Custom(code))) if SLIPPAGE_ERROR_CODES.contains(&code) => {
+                return SniperError::SlippageExceeded(message);
+            }
+            _ => {}
+        }
+        let retriable = match error.kind() {
+            ClientErrorKind::Io(_) | ClientErrorKind::Reqwest(_) => true,
+            ClientErrorKind::RpcError(SolanaRpcError::RpcResponseError { code, .. }) => RETRIABLE_RPC_CODES.contains(code),
+            _ => false,
+        };
+        SniperError::RpcError { message, retriable }
+    }
+}
+
+impl From<reqwest::Error> for SniperError {
+    fn from(error: reqwest::Error) -> Self {
+        let retriable = error.is_timeout() || error.is_connect();
+        SniperError::RpcError { message: error.to_string(), retriable }
+    }
+}
+
+/// Keeps a SniperError that went through an anyhow layer; anything else becomes Other with its full chain
+impl From<anyhow::Error> for SniperError {
+    fn from(error: anyhow::Error) -> Self {
+        match error.downcast::<SniperError>() {
+            Ok(error) => error,
+            Err(error) => SniperError::Other(format!("{:#}", error)),
+        }
+    }
+}
+
+/// The helpers these flows call still report failures as text
+impl From<String> for SniperError {
+    fn from(message: String) -> Self {
+        SniperError::Other(message)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use anchor_client::solana_client::rpc_request::RpcResponseErrorData;
+    use anchor_client::solana_client::rpc_response::RpcSimulateTransactionResult;
+
+    use super::*;
+
+    fn client_error(kind: ClientErrorKind) -> SolanaClientError {
+        kind.into()
+    }
+
+    fn rpc_response(code: i64, data: RpcResponseErrorData) -> SolanaClientError {
+        client_error(ClientErrorKind::RpcError(SolanaRpcError::RpcResponseError {
+            code,
+            message: "request failed".to_string(),
+            data,
+        }))
+    }
+
+    fn preflight_failure(err: TransactionError) -> SolanaClientError {
+        rpc_response(-32002, RpcResponseErrorData::SendTransactionPreflightFailure(RpcSimulateTransactionResult {
+            err: Some(err),
+            logs: None,
+            accounts: None,
+            units_consumed: None,
+            return_data: None,
+            inner_instructions: None,
+            replacement_blockhash: None,
+        }))
+    }
+
+    #[test]
+    fn every_variant_displays_its_message() {
+        let cases = [
+            (SniperError::rpc("node down", true), "node down"),
+            (SniperError::BlockhashExpired("blockhash gone".to_string()), "blockhash gone"),
+            (SniperError::InsufficientBalance("0.1 SOL short".to_string()), "0.1 SOL short"),
+            (SniperError::QuoteFailed("no route".to_string()), "no route"),
+            (SniperError::SlippageExceeded("price moved".to_string()), "price moved"),
+            (SniperError::TokenAccountMissing("no ATA".to_string()), "no ATA"),
+            (SniperError::Cancelled("declined".to_string()), "declined"),
+            (SniperError::Other("something".to_string()), "something"),
+        ];
+        for (error, expected) in cases {
+            assert_eq!(error.to_string(), expected);
+            assert_eq!(error.context("selling").to_string(), format!("selling: {}", expected));
+        }
+    }
+
+    #[test]
+    fn context_keeps_the_variant() {
+        let error = SniperError::rpc("timed out", false).context("fetching balance").context("buy");
+        assert!(matches!(&error, SniperError::RpcError { message, retriable: false } if message == "buy: fetching balance: timed out"));
+        assert!(matches!(SniperError::SlippageExceeded("x".to_string()).context("sell"), SniperError::SlippageExceeded(_)));
+    }
+
+    #[test]
+    fn only_flagged_rpc_errors_and_stale_blockhashes_are_retriable() {
+        assert!(SniperError::rpc("timeout", true).is_retriable());
+        assert!(!SniperError::rpc("bad request", false).is_retriable());
+        assert!(SniperError::BlockhashExpired(String::new()).is_retriable());
+        assert!(!SniperError::InsufficientBalance(String::new()).is_retriable());
+        assert!(!SniperError::SlippageExceeded(String::new()).is_retriable());
+        assert!(!SniperError::QuoteFailed(String::new()).is_retriable());
+        assert!(!SniperError::Other(String::new()).is_retriable());
+    }
+
+    #[test]
+    fn typed_variants_keep_their_class_and_the_rest_fall_back_to_the_text() {
+        assert_eq!(SniperError::rpc("Account is frozen", true).class(), ErrorClass::Network);
+        assert_eq!(SniperError::BlockhashExpired("anything".to_string()).class(), ErrorClass::BlockhashNotFound);
+        assert_eq!(SniperError::InsufficientBalance("anything".to_string()).class(), ErrorClass::InsufficientFunds);
+        assert_eq!(SniperError::SlippageExceeded("anything".to_string()).class(), ErrorClass::SlippageExceeded);
+        assert_eq!(SniperError::TokenAccountMissing("timeout".to_string()).class(), ErrorClass::Other);
+        assert_eq!(SniperError::Cancelled("timeout".to_string()).class(), ErrorClass::Other);
+
+        assert_eq!(SniperError::rpc("Account is frozen", false).class(), ErrorClass::AccountFrozen);
+        assert_eq!(SniperError::QuoteFailed("429 Too Many Requests".to_string()).class(), ErrorClass::Network);
+        assert_eq!(SniperError::Other("custom program error: 0x1772".to_string()).class(), ErrorClass::SlippageExceeded);
+        assert_eq!(SniperError::Other("no idea".to_string()).class(), ErrorClass::Other);
+    }
+
+    #[test]
+    fn classify_anyhow_finds_a_wrapped_sniper_error() {
+        let wrapped = anyhow::Error::from(SniperError::InsufficientBalance("short".to_string())).context("buying");
+        assert_eq!(classify_anyhow(&wrapped), ErrorClass::InsufficientFunds);
+        assert_eq!(classify_anyhow(&anyhow::anyhow!("Blockhash not found")), ErrorClass::BlockhashNotFound);
+    }
+
+    #[test]
+    fn transaction_errors_become_their_variants() {
+        assert!(matches!(SniperError::from(client_error(TransactionError::BlockhashNotFound.into())), SniperError::BlockhashExpired(_)));
+        assert!(matches!(SniperError::from(client_error(TransactionError::InsufficientFundsForFee.into())), SniperError::InsufficientBalance(_)));
+        let rent = TransactionError::InsufficientFundsForRent { account_index: 2 };
+        assert!(matches!(SniperError::from(client_error(rent.into())), SniperError::InsufficientBalance(_)));
+        for code in [6002, 6003] {
+            let slippage = TransactionError::InstructionError(3, InstructionError::Custom(code));
+            assert!(matches!(SniperError::from(client_error(slippage.into())), SniperError::SlippageExceeded(_)), "{}", code);
+        }
+
+        // Other program errors stay an RPC failure that resending will not fix
+        let other_program = SniperError::from(client_error(TransactionError::InstructionError(3, InstructionError::Custom(6001)).into()));
+        assert!(matches!(other_program, SniperError::RpcError { retriable: false, .. }));
+
+        // Preflight failures carry the transaction error inside the RPC response
+        assert!(matches!(SniperError::from(preflight_failure(TransactionError::BlockhashNotFound)), SniperError::BlockhashExpired(_)));
+        let simulated = TransactionError::InstructionError(0, InstructionError::Custom(6003));
+        assert!(matches!(SniperError::from(preflight_failure(simulated)), SniperError::SlippageExceeded(_)));
+    }
+
+    #[test]
+    fn transport_failures_and_unhealthy_nodes_are_retriable() {
+        let io = client_error(std::io::Error::new(std::io::ErrorKind::ConnectionReset, "reset").into());
+        assert!(matches!(SniperError::from(io), SniperError::RpcError { retriable: true, .. }));
+        let builder = reqwest::Client::new().get("not a url").build().unwrap_err();
+        assert!(matches!(SniperError::from(client_error(builder.into())), SniperError::RpcError { retriable: true, .. }));
+
+        for code in [-32004, -32005] {
+            let error = SniperError::from(rpc_response(code, RpcResponseErrorData::NodeUnhealthy { num_slots_behind: Some(40) }));
+            assert!(matches!(error, SniperError::RpcError { retriable: true, .. }), "{}", code);
+        }
+        let invalid_params = SniperError::from(rpc_response(-32602, RpcResponseErrorData::Empty));
+        assert!(matches!(&invalid_params, SniperError::RpcError { message, retriable: false } if message.contains("-32602")));
+        let custom = SniperError::from(client_error(ClientErrorKind::Custom("unsupported".to_string())));
+        assert!(matches!(custom, SniperError::RpcError { retriable: false, .. }));
+    }
+
+    #[tokio::test]
+    async fn reqwest_errors_are_retriable_only_when_the_request_never_got_through() {
+        let refused = reqwest::get("http://127.0.0.1:1").await.unwrap_err();
+        assert!(matches!(SniperError::from(refused), SniperError::RpcError { retriable: true, .. }));
+        let invalid = reqwest::Client::new().get("not a url").build().unwrap_err();
+        assert!(matches!(SniperError::from(invalid), SniperError::RpcError { retriable: false, .. }));
+    }
+
+    #[test]
+    fn anyhow_keeps_a_sniper_error_and_flattens_anything_else() {
+        let kept = SniperError::from(anyhow::Error::from(SniperError::QuoteFailed("no route".to_string())));
+        assert!(matches!(&kept, SniperError::QuoteFailed(message) if message == "no route"));
+
+        let flattened = SniperError::from(anyhow::anyhow!("connection refused").context("fetching quote"));
+        assert!(matches!(&flattened, SniperError::Other(message) if message == "fetching quote: connection refused"));
+
+        assert!(matches!(SniperError::from("plain text".to_string()), SniperError::Other(message) if message == "plain text"));
+    }
+}
@@ -1,14 +1,15 @@
+use std::collections::HashMap;
 use std::sync::Arc;
 use std::time::{Duration, Instant};
 use tokio::sync::RwLock;
 use solana_sdk::hash::Hash;
 use solana_sdk::pubkey::Pubkey;
 use solana_client::rpc_client::RpcClient;
-use anyhow::{Result, anyhow};
 use colored::Colorize;
 use lazy_static::lazy_static;
-use std::str::FromStr;
 use crate::common::logger::Logger;
+use crate::error::{SniperError, SniperResult};
+use crate::services::nonce_pool::NONCE_POOL;
 
 // Global state for latest blockhash and timestamp (for command mode: --sell, --close, --nonce)
 lazy_static! {
@@ -16,9 +17,13 @@ lazy_static! {
     static ref BLOCKHASH_LAST_UPDATED: Arc<RwLock<Option<Instant>>> = Arc::new(RwLock::new(None));
 }
 
-// Global state for offchain blockhash from nonce account (for normal bot mode)
+// Global state for offchain blockhashes by nonce account (for normal bot mode)
 lazy_static! {
-    static ref OFFCHAIN_BLOCKHASH: Arc<RwLock<Option<Hash>>> = Arc::new(RwLock::new(None));
+    static ref OFFCHAIN_BLOCKHASH: Arc<RwLock<HashMap<Pubkey, Hash>>> = Arc::new(RwLock::new(HashMap::new()));
+}
+
+fn primary_nonce_account() -> SniperResult<Pubkey> {
+    NONCE_POOL.primary().ok_or_else(|| SniperError::Other("NONCE_ACCOUNT environment variable not set".to_string()))
 }
 
 const BLOCKHASH_STALENESS_THRESHOLD: Duration = Duration::from_secs(10);
@@ -30,7 +35,7 @@ pub struct BlockhashProcessor {
 }
 
 impl BlockhashProcessor {
-    pub async fn new(rpc_client: Arc<RpcClient>) -> Result<Self> {
+    pub async fn new(rpc_client: Arc<RpcClient>) -> SniperResult<Self> {
         let logger = Logger::new("[BLOCKHASH-PROCESSOR] => ".cyan().to_string());
         
         Ok(Self {
@@ -39,7 +44,7 @@ impl BlockhashProcessor {
         })
     }
 
-    pub async fn start(&self) -> Result<()> {
+    pub async fn start(&self) -> SniperResult<()> {
         self.logger.log("Starting blockhash processor...".green().to_string());
 
         // Clone necessary components for the background task
@@ -65,16 +70,19 @@ impl BlockhashProcessor {
                     }
                 }
 
-                tokio::time::sleep(UPDATE_INTERVAL).await;
+                tokio::select! {
+                    _ = tokio::time::sleep(UPDATE_INTERVAL) => {}
+                    _ = crate::common::shutdown::cancelled() => break,
+                }
             }
         });
 
         Ok(())
     }
 
-    async fn update_blockhash_from_rpc(rpc_client: &RpcClient) -> Result<Hash> {
+    async fn update_blockhash_from_rpc(rpc_client: &RpcClient) -> SniperResult<Hash> {
         rpc_client.get_latest_blockhash()
-            .map_err(|e| anyhow!("Failed to get blockhash from RPC: {}", e))
+            .map_err(|e| SniperError::from(e).context("Failed to get blockhash from RPC"))
     }
 
     /// Update the latest blockhash and its timestamp
@@ -86,6 +94,11 @@ impl BlockhashProcessor {
         *last_updated = Some(Instant::now());
     }
 
+    /// Time since the background refresh last stored a blockhash; None before the first one
+    pub async fn blockhash_age() -> Option<Duration> {
+        BLOCKHASH_LAST_UPDATED.read().await.map(|instant| instant.elapsed())
+    }
+
     /// Get the latest cached blockhash with freshness check
     pub async fn get_latest_blockhash() -> Option<Hash> {
         // Check if blockhash is stale
@@ -101,7 +114,7 @@ impl BlockhashProcessor {
     }
 
     /// Get a fresh blockhash, falling back to RPC if necessary
-    pub async fn get_fresh_blockhash(&self) -> Result<Hash> {
+    pub async fn get_fresh_blockhash(&self) -> SniperResult<Hash> {
         if let Some(hash) = Self::get_latest_blockhash().await {
             return Ok(hash);
         }
@@ -109,64 +122,87 @@ impl BlockhashProcessor {
         // Fallback to RPC if cached blockhash is stale or missing
         self.logger.log("Cached blockhash is stale or missing, falling back to RPC...".yellow().to_string());
         let new_hash = self.rpc_client.get_latest_blockhash()
-            .map_err(|e| anyhow!("Failed to get blockhash from RPC: {}", e))?;
+            .map_err(|e| SniperError::from(e).context("Failed to get blockhash from RPC"))?;
         
         Self::update_blockhash(new_hash).await;
         Ok(new_hash)
     }
 
-    /// Get offchain blockhash from nonce account
+    /// Get offchain blockhash from the primary nonce account
     /// This should be called when onchain state is updated (after buy/sell)
-    pub async fn get_offchain_blockhash(&self) -> Result<Hash> {
-        // Check if we have a cached offchain blockhash
+    pub async fn get_offchain_blockhash(&self) -> SniperResult<Hash> {
+        self.get_offchain_blockhash_for(&primary_nonce_account()?).await
+    }
+
+    /// Cached offchain blockhash of `nonce_pubkey`, read from the account when missing
+    pub async fn get_offchain_blockhash_for(&self, nonce_pubkey: &Pubkey) -> SniperResult<Hash> {
         let cached = OFFCHAIN_BLOCKHASH.read().await;
-        if let Some(hash) = *cached {
-            return Ok(hash);
+        if let Some(hash) = cached.get(nonce_pubkey) {
+            return Ok(*hash);
         }
         drop(cached);
 
         // Fetch from nonce account
-        self.update_offchain_blockhash().await
+        self.update_offchain_blockhash_for(nonce_pubkey).await
     }
 
-    /// Update offchain blockhash from nonce account
+    /// Update offchain blockhash from the primary nonce account
     /// This should be called:
     /// - When bot starts
     /// - After buying
     /// - After selling
-    pub async fn update_offchain_blockhash(&self) -> Result<Hash> {
-        let nonce_account_str = std::env::var("NONCE_ACCOUNT")
-            .map_err(|_| anyhow!("NONCE_ACCOUNT environment variable not set"))?;
-        
-        let nonce_pubkey = Pubkey::from_str(&nonce_account_str)
-            .map_err(|e| anyhow!("Invalid NONCE_ACCOUNT pubkey: {}", e))?;
+    ///
+    /// Sends through `new_signed_and_send_zeroslot` refresh their nonce on their own, see `nonce_pool::after_send`.
+    pub async fn update_offchain_blockhash(&self) -> SniperResult<Hash> {
+        self.update_offchain_blockhash_for(&primary_nonce_account()?).await
+    }
 
+    pub async fn update_offchain_blockhash_for(&self, nonce_pubkey: &Pubkey) -> SniperResult<Hash> {
         // Get nonce account data
-        let nonce_account = self.rpc_client.get_account(&nonce_pubkey)
-            .map_err(|e| anyhow!("Failed to get nonce account: {}", e))?;
+        let nonce_account = self.rpc_client.get_account(nonce_pubkey)
+            .map_err(|e| SniperError::from(e).context("Failed to get nonce account"))?;
 
         // Parse nonce data to get blockhash
         let nonce_data = solana_rpc_client_nonce_utils::data_from_account(&nonce_account)
-            .map_err(|e| anyhow!("Failed to parse nonce data: {}", e))?;
+            .map_err(|e| SniperError::Other(format!("Failed to parse nonce data: {}", e)))?;
         
         let offchain_blockhash = nonce_data.blockhash();
 
         // Cache the offchain blockhash
         let mut cached = OFFCHAIN_BLOCKHASH.write().await;
-        *cached = Some(offchain_blockhash);
+        cached.insert(*nonce_pubkey, offchain_blockhash);
         
-        self.logger.log(format!("Updated offchain blockhash from nonce account: {}", offchain_blockhash).green().to_string());
+        self.logger.critical(format!("Updated offchain blockhash from nonce account {}: {}", nonce_pubkey, offchain_blockhash).green().to_string());
         
         Ok(offchain_blockhash)
     }
 
-    /// Check if offchain blockhash is available (nonce account is configured)
+    /// Replace the cached offchain blockhash of `nonce_pubkey` (e.g. after re-reading the nonce account)
+    pub async fn set_offchain_blockhash_for(nonce_pubkey: &Pubkey, hash: Hash) {
+        let mut cached = OFFCHAIN_BLOCKHASH.write().await;
+        cached.insert(*nonce_pubkey, hash);
+    }
+
+    /// Drop every cached offchain blockhash so the next transactions re-read their nonce accounts
+    pub async fn invalidate_offchain_blockhash() {
+        let mut cached = OFFCHAIN_BLOCKHASH.write().await;
+        cached.clear();
+    }
+
+    /// Drop the cached offchain blockhash of `nonce_pubkey`
+    /// Call after the nonce has been advanced by a landed transaction.
+    pub async fn invalidate_offchain_blockhash_for(nonce_pubkey: &Pubkey) {
+        let mut cached = OFFCHAIN_BLOCKHASH.write().await;
+        cached.remove(nonce_pubkey);
+    }
+
+    /// Check if offchain blockhash is available (NONCE_ACCOUNT or NONCE_ACCOUNTS is configured)
     pub fn is_offchain_blockhash_available() -> bool {
-        std::env::var("NONCE_ACCOUNT").is_ok()
+        !NONCE_POOL.is_empty()
     }
 
     /// Get blockhash based on mode: offchain for normal bot mode, recent for command mode
-    pub async fn get_blockhash_for_transaction(&self, use_offchain: bool) -> Result<Hash> {
+    pub async fn get_blockhash_for_transaction(&self, use_offchain: bool) -> SniperResult<Hash> {
         if use_offchain && Self::is_offchain_blockhash_available() {
             self.get_offchain_blockhash().await
         } else {
@@ -191,14 +227,14 @@ impl BlockhashProcessor {
 
     /// Get blockhash for transaction (static method that can be called without instance)
     /// Uses offchain blockhash in normal bot mode, recent blockhash in command mode
-    pub async fn get_blockhash_for_transaction_static(rpc_client: Option<Arc<RpcClient>>) -> Result<Hash> {
+    pub async fn get_blockhash_for_transaction_static(rpc_client: Option<Arc<RpcClient>>) -> SniperResult<Hash> {
         let use_offchain = Self::should_use_offchain_blockhash();
         
         if use_offchain {
             // Try to get cached offchain blockhash first
             let cached = OFFCHAIN_BLOCKHASH.read().await;
-            if let Some(hash) = *cached {
-                return Ok(hash);
+            if let Some(hash) = cached.get(&primary_nonce_account()?) {
+                return Ok(*hash);
             }
             drop(cached);
 
@@ -207,7 +243,7 @@ impl BlockhashProcessor {
                 let processor = Self::new(client).await?;
                 return processor.get_offchain_blockhash().await;
             } else {
-                return Err(anyhow!("RPC client required to fetch offchain blockhash"));
+                return Err(SniperError::Other("RPC client required to fetch offchain blockhash".to_string()));
             }
         } else {
             // Command mode: use recent blockhash
@@ -220,7 +256,7 @@ impl BlockhashProcessor {
                 let processor = Self::new(client).await?;
                 return processor.get_fresh_blockhash().await;
             } else {
-                return Err(anyhow!("Failed to get blockhash: no cached value and no RPC client"));
+                return Err(SniperError::Other("Failed to get blockhash: no cached value and no RPC client".to_string()));
             }
         }
     }
@@ -0,0 +1,574 @@
+use std::collections::VecDeque;
+use std::str::FromStr;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use anchor_client::solana_sdk::pubkey::Pubkey;
+use async_trait::async_trait;
+use borsh::BorshDeserialize;
+use spl_associated_token_account::get_associated_token_address_with_program_id;
+use spl_token_2022::extension::{BaseStateWithExtensions, ExtensionType, StateWithExtensions};
+use spl_token_2022::state::Mint;
+use teloxide::prelude::*;
+use teloxide::types::MessageId;
+
+use crate::core::mint_inspector;
+use crate::dex::pump_fun::{get_pda, BondingCurveAccount, PUMP_FUN_PROGRAM};
+use crate::engine::transaction_parser::DexType;
+use crate::services::rpc_pool::RpcHandle;
+use crate::services::telegram;
+
+const METAPLEX_METADATA_PROGRAM: &str = "metaqbxxUerdq28cj1RbAWkYQm3ybzjb6a8bt518x1s";
+/// Tokens a fresh PumpFun curve sells before it completes (6 decimals)
+const INITIAL_REAL_TOKEN_RESERVES: u64 = 793_100_000_000_000;
+/// Holders counted in the concentration figure
+const TOP_HOLDERS: usize = 10;
+
+/// Fields of a buy notification that are usually unknown when the buy lands
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct BuyEnrichment {
+    pub symbol: Option<String>,
+    pub name: Option<String>,
+    /// Share of the curve's tokens sold; 100 once complete
+    pub curve_progress_pct: Option<f64>,
+    /// Largest holder, excluding the curve, as a share of supply
+    pub top_holder_pct: Option<f64>,
+    /// TOP_HOLDERS largest holders together, excluding the curve
+    pub top_holders_pct: Option<f64>,
+}
+
+impl BuyEnrichment {
+    /// Nothing resolved; no follow-up is sent
+    pub fn is_empty(&self) -> bool {
+        self.symbol.is_none()
+            && self.name.is_none()
+            && self.curve_progress_pct.is_none()
+            && self.top_holder_pct.is_none()
+            && self.top_holders_pct.is_none()
+    }
+
+    /// Lines appended to (or replied under) the buy message
+    pub fn format(&self) -> String {
+        let mut lines = Vec::new();
+        match (&self.name, &self.symbol) {
+            (Some(name), Some(symbol)) => lines.push(format!("🏷️ {} (${})", name, symbol)),
+            (Some(name), None) => lines.push(format!("🏷️ {}", name)),
+            (None, Some(symbol)) => lines.push(format!("🏷️ ${}", symbol)),
+            (None, None) => {}
+        }
+        if let Some(progress) = self.curve_progress_pct {
+            lines.push(format!("📈 Curve: {:.1}%", progress));
+        }
+        match (self.top_holder_pct, self.top_holders_pct) {
+            (Some(top), Some(top_n)) => lines.push(format!("👥 Top holder: {:.1}%, top {}: {:.1}%", top, TOP_HOLDERS, top_n)),
+            (Some(top), None) => lines.push(format!("👥 Top holder: {:.1}%", top)),
+            (None, Some(top_n)) => lines.push(format!("👥 Top {}: {:.1}%", TOP_HOLDERS, top_n)),
+            (None, None) => {}
+        }
+        lines.join("\n")
+    }
+}
+
+/// Where the late fields of a buy notification come from
+#[async_trait]
+pub trait EnrichmentSource: Send + Sync {
+    /// Whatever resolves now; fields that fail to resolve stay None
+    async fn enrich(&self, mint: &str, dex_type: DexType) -> BuyEnrichment;
+}
+
+/// Reads metadata, the bonding curve and the largest token accounts through the read pool
+pub struct RpcEnrichmentSource {
+    read_rpc: RpcHandle,
+}
+
+impl RpcEnrichmentSource {
+    pub fn new(read_rpc: RpcHandle) -> Self {
+        Self { read_rpc }
+    }
+
+    /// Token-2022 metadata extension first, then the Metaplex metadata account
+    async fn metadata(&self, mint: &Pubkey) -> Option<(String, String)> {
+        let client = self.read_rpc.client();
+        let account = client.get_account(mint).await.ok()?;
+        if account.owner == spl_token_2022::ID {
+            if let Some(found) = token_metadata_extension(&account.data) {
+                return Some(found);
+            }
+        }
+        let program = Pubkey::from_str(METAPLEX_METADATA_PROGRAM).ok()?;
+        let (metadata, _) = Pubkey::find_program_address(&[b"metadata", program.as_ref(), mint.as_ref()], &program);
+        let data = client.get_account_data(&metadata).await.ok()?;
+        metaplex_name_and_symbol(&data)
+    }
+
+    async fn curve(&self, mint: &Pubkey) -> Option<(Pubkey, BondingCurveAccount)> {
+        let program = Pubkey::from_str(PUMP_FUN_PROGRAM).ok()?;
+        let bonding_curve = get_pda(mint, &program).ok()?;
+        let data = self.read_rpc.client().get_account_data(&bonding_curve).await.ok()?;
+        // Newer curves append fields; only the leading ones are read
+        let curve = BondingCurveAccount::deserialize(&mut data.as_slice()).ok()?;
+        Some((bonding_curve, curve))
+    }
+
+    /// (largest holder %, top TOP_HOLDERS %) of supply, skipping `exclude` (the curve's token account)
+    async fn holders(&self, mint: &Pubkey, exclude: Option<Pubkey>) -> Option<(f64, f64)> {
+        let client = self.read_rpc.client();
+        let supply = client.get_token_supply(mint).await.ok()?.amount.parse::<u64>().ok()?;
+        if supply == 0 {
+            return None;
+        }
+        let exclude = exclude.map(|address| address.to_string());
+        let amounts: Vec<u64> = client
+            .get_token_largest_accounts(mint)
+            .await
+            .ok()?
+            .into_iter()
+            .filter(|holder| Some(&holder.address) != exclude.as_ref())
+            .filter_map(|holder| holder.amount.amount.parse::<u64>().ok())
+            .take(TOP_HOLDERS)
+            .collect();
+        let top = *amounts.first()?;
+        let top_n: u64 = amounts.iter().sum();
+        Some((top as f64 / supply as f64 * 100.0, top_n as f64 / supply as f64 * 100.0))
+    }
+}
+
+#[async_trait]
+impl EnrichmentSource for RpcEnrichmentSource {
+    async fn enrich(&self, mint: &str, dex_type: DexType) -> BuyEnrichment {
+        let Ok(mint_pubkey) = Pubkey::from_str(mint) else {
+            return BuyEnrichment::default();
+        };
+        let mut enrichment = BuyEnrichment::default();
+        if let Some((name, symbol)) = self.metadata(&mint_pubkey).await {
+            enrichment.name = Some(name).filter(|name| !name.is_empty());
+            enrichment.symbol = Some(symbol).filter(|symbol| !symbol.is_empty());
+        }
+
+        let mut curve_account = None;
+        if dex_type == DexType::PumpFun {
+            if let Some((bonding_curve, curve)) = self.curve(&mint_pubkey).await {
+                let sold = INITIAL_REAL_TOKEN_RESERVES.saturating_sub(curve.real_token_reserves);
+                enrichment.curve_progress_pct = Some(if curve.complete {
+                    100.0
+                } else {
+                    (sold as f64 / INITIAL_REAL_TOKEN_RESERVES as f64 * 100.0).min(100.0)
+                });
+                if let Ok(info) = mint_inspector::inspect_mint(&self.read_rpc.client(), &mint_pubkey).await {
+                    curve_account = Some(get_associated_token_address_with_program_id(&bonding_curve, &mint_pubkey, &info.token_program));
+                }
+            }
+        }
+
+        if let Some((top, top_n)) = self.holders(&mint_pubkey, curve_account).await {
+            enrichment.top_holder_pct = Some(top);
+            enrichment.top_holders_pct = Some(top_n);
+        }
+        enrichment
+    }
+}
+
+/// Borsh string: u32 length then bytes; Metaplex pads fixed-size fields with NULs
+fn read_string(data: &[u8], offset: &mut usize) -> Option<String> {
+    let len = u32::from_le_bytes(data.get(*offset..*offset + 4)?.try_into().ok()?) as usize;
+    *offset += 4;
+    let bytes = data.get(*offset..*offset + len)?;
+    *offset += len;
+    Some(String::from_utf8_lossy(bytes).trim_end_matches('\0').trim().to_string())
+}
+
+/// (name, symbol) of a Metaplex metadata account: key, update authority and mint precede them
+fn metaplex_name_and_symbol(data: &[u8]) -> Option<(String, String)> {
+    let mut offset = 1 + 32 + 32;
+    let name = read_string(data, &mut offset)?;
+    let symbol = read_string(data, &mut offset)?;
+    Some((name, symbol))
+}
+
+/// (name, symbol) from the Token-2022 TokenMetadata extension
+/// spl_token_2022 doesn't expose the variable-length type, so the TLV entries are walked by hand.
+fn token_metadata_extension(data: &[u8]) -> Option<(String, String)> {
+    let state = StateWithExtensions::<Mint>::unpack(data).ok()?;
+    let tlv = state.get_tlv_data();
+    let mut offset = 0;
+    while offset + 4 <= tlv.len() {
+        let kind = u16::from_le_bytes([tlv[offset], tlv[offset + 1]]);
+        let len = u16::from_le_bytes([tlv[offset + 2], tlv[offset + 3]]) as usize;
+        let value = tlv.get(offset + 4..offset + 4 + len)?;
+        if kind == ExtensionType::TokenMetadata as u16 {
+            // Update authority and mint precede the name
+            let mut value_offset = 32 + 32;
+            let name = read_string(value, &mut value_offset)?;
+            let symbol = read_string(value, &mut value_offset)?;
+            return Some((name, symbol));
+        }
+        if kind == ExtensionType::Uninitialized as u16 {
+            break;
+        }
+        offset += 4 + len;
+    }
+    None
+}
+
+/// Sends a message and later changes it; the bot live, a recorder in harnesses
+#[async_trait]
+pub trait MessageEditor: Send + Sync {
+    /// Id of the sent message
+    async fn send(&self, text: String) -> Result<i32, String>;
+
+    async fn edit(&self, message_id: i32, text: String) -> Result<(), String>;
+
+    async fn reply(&self, message_id: i32, text: String) -> Result<(), String>;
+}
+
+/// Through the shared bot to TELEGRAM_CHAT_ID, paced by the Telegram rate limiter
+pub struct BotEditor;
+
+#[async_trait]
+impl MessageEditor for BotEditor {
+    async fn send(&self, text: String) -> Result<i32, String> {
+        let (bot, chat_id) = telegram::bot_and_chat()?;
+        telegram::wait_for_send_slot().await;
+        bot.send_message(chat_id, telegram::outgoing(text))
+            .await
+            .map(|message| message.id.0)
+            .map_err(|e| format!("Failed to send message: {}", e))
+    }
+
+    async fn edit(&self, message_id: i32, text: String) -> Result<(), String> {
+        let (bot, chat_id) = telegram::bot_and_chat()?;
+        telegram::wait_for_send_slot().await;
+        bot.edit_message_text(chat_id, MessageId(message_id), telegram::outgoing(text))
+            .await
+            .map(|_| ())
+            .map_err(|e| format!("Failed to edit message {}: {}", message_id, e))
+    }
+
+    async fn reply(&self, message_id: i32, text: String) -> Result<(), String> {
+        let (bot, chat_id) = telegram::bot_and_chat()?;
+        telegram::wait_for_send_slot().await;
+        bot.send_message(chat_id, telegram::outgoing(text))
+            .reply_to_message_id(MessageId(message_id))
+            .await
+            .map(|_| ())
+            .map_err(|e| format!("Failed to reply to message {}: {}", message_id, e))
+    }
+}
+
+/// Something a recorder was asked to do
+#[derive(Clone, Debug, PartialEq)]
+pub enum EditorCall {
+    Send { message_id: i32, text: String },
+    Edit { message_id: i32, text: String },
+    Reply { message_id: i32, text: String },
+}
+
+/// Records every call in memory and hands out sequential message ids (harnesses)
+#[derive(Default)]
+pub struct MemoryEditor {
+    calls: Mutex<Vec<EditorCall>>,
+}
+
+impl MemoryEditor {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn calls(&self) -> Vec<EditorCall> {
+        self.calls.lock().map(|calls| calls.clone()).unwrap_or_default()
+    }
+
+    fn push(&self, call: EditorCall) -> Result<(), String> {
+        self.calls.lock().map_err(|e| e.to_string())?.push(call);
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl MessageEditor for MemoryEditor {
+    async fn send(&self, text: String) -> Result<i32, String> {
+        let message_id = self.calls.lock().map_err(|e| e.to_string())?.len() as i32 + 1;
+        self.push(EditorCall::Send { message_id, text })?;
+        Ok(message_id)
+    }
+
+    async fn edit(&self, message_id: i32, text: String) -> Result<(), String> {
+        self.push(EditorCall::Edit { message_id, text })
+    }
+
+    async fn reply(&self, message_id: i32, text: String) -> Result<(), String> {
+        self.push(EditorCall::Reply { message_id, text })
+    }
+}
+
+/// Returns queued enrichments in order, then empty ones (harnesses simulating late-arriving data)
+#[derive(Default)]
+pub struct ScriptedEnrichment {
+    answers: Mutex<VecDeque<BuyEnrichment>>,
+}
+
+impl ScriptedEnrichment {
+    pub fn new(answers: Vec<BuyEnrichment>) -> Self {
+        Self { answers: Mutex::new(answers.into()) }
+    }
+}
+
+#[async_trait]
+impl EnrichmentSource for ScriptedEnrichment {
+    async fn enrich(&self, _mint: &str, _dex_type: DexType) -> BuyEnrichment {
+        self.answers.lock().ok().and_then(|mut answers| answers.pop_front()).unwrap_or_default()
+    }
+}
+
+/// How the follow-up is delivered
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FollowUpMode {
+    /// editMessageText on the buy message, keeping its text
+    Edit,
+    /// A reply under the buy message with only the new fields
+    Reply,
+}
+
+/// ENRICH_FOLLOWUP (default: false) enables the follow-up, sent ENRICH_FOLLOWUP_DELAY_SECS (default: 45, 30-60)
+/// after the buy message as an edit or a reply (ENRICH_FOLLOWUP_MODE, default: edit). At most
+/// ENRICH_FOLLOWUP_MAX_TRACKED (default: 256) message ids are kept, each for ENRICH_FOLLOWUP_TTL_SECS (default: 600).
+#[derive(Clone, Copy, Debug)]
+pub struct FollowUpConfig {
+    pub delay: Duration,
+    pub mode: FollowUpMode,
+    pub max_tracked: usize,
+    pub ttl: Duration,
+}
+
+impl FollowUpConfig {
+    /// None unless ENRICH_FOLLOWUP=true
+    pub fn from_env() -> Option<Self> {
+        let enabled = std::env::var("ENRICH_FOLLOWUP")
+            .ok()
+            .and_then(|v| v.parse::<bool>().ok())
+            .unwrap_or(false);
+        if !enabled {
+            return None;
+        }
+        let delay_secs = std::env::var("ENRICH_FOLLOWUP_DELAY_SECS")
+            .ok()
+            .and_then(|v| v.parse::<u64>().ok())
+            .unwrap_or(45)
+            .clamp(30, 60);
+        let mode = match std::env::var("ENRICH_FOLLOWUP_MODE").unwrap_or_default().to_lowercase().as_str() {
+            "reply" => FollowUpMode::Reply,
+            _ => FollowUpMode::Edit,
+        };
+        let max_tracked = std::env::var("ENRICH_FOLLOWUP_MAX_TRACKED")
+            .ok()
+            .and_then(|v| v.parse::<usize>().ok())
+            .unwrap_or(256)
+            .max(1);
+        let ttl_secs = std::env::var("ENRICH_FOLLOWUP_TTL_SECS")
+            .ok()
+            .and_then(|v| v.parse::<u64>().ok())
+            .unwrap_or(600);
+        Some(Self { delay: Duration::from_secs(delay_secs), mode, max_tracked, ttl: Duration::from_secs(ttl_secs) })
+    }
+}
+
+/// A sent buy message waiting for its follow-up
+#[derive(Clone, Debug)]
+struct TrackedMessage {
+    mint: String,
+    dex_type: DexType,
+    message_id: i32,
+    text: String,
+    sent_at: Instant,
+}
+
+/// What happened to one follow-up
+#[derive(Clone, Debug, PartialEq)]
+pub enum FollowUpOutcome {
+    Edited,
+    Replied,
+    /// Nothing resolved; the message was left alone
+    NothingNew,
+    /// Evicted or past ENRICH_FOLLOWUP_TTL_SECS
+    NotTracked,
+    Failed(String),
+}
+
+/// Two-stage buy notifications: the message goes out at once and is enriched once, ENRICH_FOLLOWUP_DELAY_SECS later
+pub struct BuyFollowUp {
+    editor: Arc<dyn MessageEditor>,
+    source: Arc<dyn EnrichmentSource>,
+    config: FollowUpConfig,
+    /// Oldest first, at most `config.max_tracked`
+    tracked: Mutex<VecDeque<TrackedMessage>>,
+}
+
+impl BuyFollowUp {
+    pub fn new(editor: Arc<dyn MessageEditor>, source: Arc<dyn EnrichmentSource>, config: FollowUpConfig) -> Self {
+        Self { editor, source, config, tracked: Mutex::new(VecDeque::new()) }
+    }
+
+    /// None unless ENRICH_FOLLOWUP=true
+    pub fn from_env(editor: Arc<dyn MessageEditor>, source: Arc<dyn EnrichmentSource>) -> Option<Self> {
+        FollowUpConfig::from_env().map(|config| Self::new(editor, source, config))
+    }
+
+    /// Send the buy message now; returns its id once it's tracked for the follow-up
+    pub async fn send(&self, mint: &str, dex_type: DexType, text: String) -> Result<i32, String> {
+        let message_id = self.editor.send(text.clone()).await?;
+        let Ok(mut tracked) = self.tracked.lock() else {
+            return Ok(message_id);
+        };
+        tracked.retain(|entry| entry.mint != mint && entry.sent_at.elapsed() < self.config.ttl);
+        while tracked.len() >= self.config.max_tracked {
+            tracked.pop_front();
+        }
+        tracked.push_back(TrackedMessage { mint: mint.to_string(), dex_type, message_id, text, sent_at: Instant::now() });
+        Ok(message_id)
+    }
+
+    /// Enrich the mint's tracked message once and stop tracking it
+    pub async fn follow_up(&self, mint: &str) -> FollowUpOutcome {
+        let entry = self.tracked.lock().ok().and_then(|mut tracked| {
+            let index = tracked.iter().position(|entry| entry.mint == mint)?;
+            tracked.remove(index)
+        });
+        let Some(entry) = entry.filter(|entry| entry.sent_at.elapsed() < self.config.ttl) else {
+            return FollowUpOutcome::NotTracked;
+        };
+
+        let enrichment = self.source.enrich(&entry.mint, entry.dex_type).await;
+        if enrichment.is_empty() {
+            return FollowUpOutcome::NothingNew;
+        }
+        let result = match self.config.mode {
+            FollowUpMode::Edit => self
+                .editor
+                .edit(entry.message_id, format!("{}\n\n{}", entry.text, enrichment.format()))
+                .await
+                .map(|_| FollowUpOutcome::Edited),
+            FollowUpMode::Reply => self
+                .editor
+                .reply(entry.message_id, enrichment.format())
+                .await
+                .map(|_| FollowUpOutcome::Replied),
+        };
+        result.unwrap_or_else(FollowUpOutcome::Failed)
+    }
+
+    /// Send in the background and schedule the follow-up
+    pub fn spawn(self: &Arc<Self>, mint: &str, dex_type: DexType, text: String) {
+        let followup = self.clone();
+        let mint = mint.to_string();
+        tokio::spawn(async move {
+            if let Err(e) = followup.send(&mint, dex_type, text).await {
+                eprintln!("[TELEGRAM] {}", e);
+                return;
+            }
+            tokio::time::sleep(followup.config.delay).await;
+            if let FollowUpOutcome::Failed(e) = followup.follow_up(&mint).await {
+                eprintln!("[TELEGRAM] Follow-up for {} failed: {}", mint, e);
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MINT: &str = "So11111111111111111111111111111111111111112";
+
+    fn config(mode: FollowUpMode) -> FollowUpConfig {
+        FollowUpConfig { delay: Duration::from_secs(45), mode, max_tracked: 4, ttl: Duration::from_secs(600) }
+    }
+
+    fn resolved() -> BuyEnrichment {
+        BuyEnrichment {
+            symbol: Some("PEP".to_string()),
+            name: Some("Pepe".to_string()),
+            curve_progress_pct: Some(12.5),
+            top_holder_pct: Some(4.0),
+            top_holders_pct: Some(21.25),
+        }
+    }
+
+    fn followup(mode: FollowUpMode, answers: Vec<BuyEnrichment>) -> (Arc<BuyFollowUp>, Arc<MemoryEditor>) {
+        let editor = Arc::new(MemoryEditor::new());
+        let followup = BuyFollowUp::new(editor.clone(), Arc::new(ScriptedEnrichment::new(answers)), config(mode));
+        (Arc::new(followup), editor)
+    }
+
+    #[test]
+    fn format_leaves_out_unresolved_fields() {
+        assert_eq!(
+            resolved().format(),
+            "🏷️ Pepe ($PEP)\n📈 Curve: 12.5%\n👥 Top holder: 4.0%, top 10: 21.2%"
+        );
+        let symbol_only = BuyEnrichment { symbol: Some("PEP".to_string()), ..BuyEnrichment::default() };
+        assert_eq!(symbol_only.format(), "🏷️ $PEP");
+        assert!(BuyEnrichment::default().is_empty());
+        assert!(!symbol_only.is_empty());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn late_enrichment_edits_the_buy_message() {
+        let (followup, editor) = followup(FollowUpMode::Edit, vec![resolved()]);
+        followup.spawn(MINT, DexType::PumpFun, "🟢 Bought".to_string());
+
+        tokio::time::sleep(Duration::from_secs(44)).await;
+        assert_eq!(editor.calls(), vec![EditorCall::Send { message_id: 1, text: "🟢 Bought".to_string() }]);
+
+        tokio::time::sleep(Duration::from_secs(2)).await;
+        assert_eq!(
+            editor.calls()[1],
+            EditorCall::Edit { message_id: 1, text: format!("🟢 Bought\n\n{}", resolved().format()) }
+        );
+        // One follow-up per message
+        assert_eq!(followup.follow_up(MINT).await, FollowUpOutcome::NotTracked);
+    }
+
+    #[tokio::test]
+    async fn reply_mode_sends_only_the_new_fields() {
+        let (followup, editor) = followup(FollowUpMode::Reply, vec![resolved()]);
+        let message_id = followup.send(MINT, DexType::PumpFun, "🟢 Bought".to_string()).await.unwrap();
+
+        assert_eq!(followup.follow_up(MINT).await, FollowUpOutcome::Replied);
+        assert_eq!(editor.calls()[1], EditorCall::Reply { message_id, text: resolved().format() });
+    }
+
+    #[tokio::test]
+    async fn nothing_resolved_leaves_the_message_alone() {
+        let (followup, editor) = followup(FollowUpMode::Edit, vec![BuyEnrichment::default()]);
+        followup.send(MINT, DexType::PumpFun, "🟢 Bought".to_string()).await.unwrap();
+
+        assert_eq!(followup.follow_up(MINT).await, FollowUpOutcome::NothingNew);
+        assert_eq!(editor.calls().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn oldest_messages_are_evicted_past_the_bound() {
+        let mints: Vec<String> = (0..5).map(|_| Pubkey::new_unique().to_string()).collect();
+        let (followup, editor) = followup(FollowUpMode::Edit, vec![resolved(); 5]);
+        for mint in &mints {
+            followup.send(mint, DexType::PumpSwap, format!("Bought {}", mint)).await.unwrap();
+        }
+
+        assert_eq!(followup.follow_up(&mints[0]).await, FollowUpOutcome::NotTracked);
+        assert_eq!(followup.follow_up(&mints[4]).await, FollowUpOutcome::Edited);
+        assert_eq!(editor.calls().len(), 6);
+    }
+
+    #[tokio::test]
+    async fn expired_messages_are_not_followed_up() {
+        let editor = Arc::new(MemoryEditor::new());
+        let followup = BuyFollowUp::new(
+            editor.clone(),
+            Arc::new(ScriptedEnrichment::new(vec![resolved()])),
+            FollowUpConfig { ttl: Duration::ZERO, ..config(FollowUpMode::Edit) },
+        );
+        followup.send(MINT, DexType::PumpFun, "🟢 Bought".to_string()).await.unwrap();
+
+        assert_eq!(followup.follow_up(MINT).await, FollowUpOutcome::NotTracked);
+        assert_eq!(editor.calls().len(), 1);
+    }
+}
@@ -59,10 +59,7 @@ async fn perform_cache_cleanup(logger: &Logger) {
             // Prune candles older than retention window (now async with timing)
             // Log removed - routine cleanup
             let candle_start = std::time::Instant::now();
-            let now_secs = std::time::SystemTime::now()
-                .duration_since(std::time::UNIX_EPOCH)
-                .unwrap_or_default()
-                .as_secs();
+            let now_secs = crate::common::clock::now_secs();
             let retention_secs = cleanup_thresholds::CANDLE_RETENTION_SECS;
             let cutoff_ts = now_secs.saturating_sub(retention_secs);
             
@@ -73,10 +70,7 @@ async fn perform_cache_cleanup(logger: &Logger) {
             // Clean up DEAD_TOKEN_LIST
             // OPTIMIZED: Use retain() for in-place removal instead of collect-then-remove
             // Log removed - routine cleanup
-            let now_secs = std::time::SystemTime::now()
-                .duration_since(std::time::UNIX_EPOCH)
-                .unwrap_or_default()
-                .as_secs();
+            let now_secs = crate::common::clock::now_secs();
             let expiration_cutoff = now_secs.saturating_sub(cleanup_thresholds::DEAD_TOKEN_EXPIRATION_SECS);
             
             // Count items before removal
@@ -92,6 +86,12 @@ async fn perform_cache_cleanup(logger: &Logger) {
                 // Log removed - routine cleanup
             }
             
+            // Forget sold mints once their re-entry cooldown ended
+            let _removed_sold_count = crate::engine::reentry::cleanup_expired();
+
+            // Drop MEV tracker slots the stream stopped adding to
+            let _removed_slot_count = crate::engine::mev_tracker::cleanup_stale();
+
             // Cleanup old price drop records to prevent memory leaks
             // Log removed - routine cleanup
             let removed_drops_count = cleanup_old_price_drops(cleanup_thresholds::RECENT_PRICE_DROPS_RETENTION_SECS).await;
@@ -122,6 +122,9 @@ async fn perform_cache_cleanup(logger: &Logger) {
                 // Log removed - routine cleanup
             }
             
+            // Reasons no notification consumed; expiring them warns that a notification path broke
+            crate::engine::sell_reasons::expire_orphans();
+
             Ok::<(), String>(())
         }
     ).await;
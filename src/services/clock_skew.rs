@@ -0,0 +1,79 @@
+use std::time::Duration;
+use anchor_client::solana_client::nonblocking::rpc_client::RpcClient;
+use anchor_client::solana_sdk::commitment_config::CommitmentConfig;
+use colored::Colorize;
+
+use crate::common::{clock, config::AppState, logger::Logger};
+use crate::services::notifier::{Notifier, TelegramNotifier};
+use crate::services::telegram_commands;
+
+/// Skew of the host clock against the block time of a recent confirmed slot, in ms
+/// Block times are whole seconds and trail the slot by up to a second, so expect ±1.5s when in sync.
+pub async fn measure_rpc_skew_ms(client: &RpcClient) -> Result<i64, String> {
+    let slot = client.get_slot_with_commitment(CommitmentConfig::confirmed())
+        .await
+        .map_err(|e| format!("getSlot failed: {}", e))?;
+    let block_time = client.get_block_time(slot)
+        .await
+        .map_err(|e| format!("getBlockTime({}) failed: {}", slot, e))?;
+    Ok(clock::compute_skew_ms(block_time, clock::local_unix_ms()))
+}
+
+fn format_skew(skew_ms: Option<i64>) -> String {
+    skew_ms.map(|ms| format!("{:+.1}s", ms as f64 / 1000.0)).unwrap_or_else(|| "n/a".to_string())
+}
+
+fn clock_status_section() -> String {
+    format!(
+        "Clock: RPC skew {}, event skew {}, {} suspect event timestamps",
+        format_skew(clock::rpc_skew_ms()),
+        format_skew(clock::event_skew_ms()),
+        clock::suspect_event_count()
+    )
+}
+
+/// Measure skew at startup and every CLOCK_SKEW_CHECK_INTERVAL_SECS (default: 300)
+/// The RPC skew corrects `clock::now_secs`; either skew beyond CLOCK_SKEW_WARN_SECS is logged and alerted once
+/// until it recovers. Registers the "clock" /status section.
+pub async fn start_clock_skew_monitor(app_state: &AppState) {
+    telegram_commands::register_status_section("clock", clock_status_section);
+
+    let read_rpc = app_state.read_rpc.clone();
+    let interval_secs = std::env::var("CLOCK_SKEW_CHECK_INTERVAL_SECS")
+        .ok()
+        .and_then(|v| v.parse::<u64>().ok())
+        .unwrap_or(300);
+    let warn_ms = clock::warn_threshold_secs() as i64 * 1000;
+
+    tokio::spawn(async move {
+        let logger = Logger::new("[CLOCK] => ".cyan().to_string());
+        let mut interval = tokio::time::interval(Duration::from_secs(interval_secs.max(1)));
+        let mut alerted = false;
+        loop {
+            interval.tick().await;
+            match tokio::time::timeout(Duration::from_secs(10), measure_rpc_skew_ms(&read_rpc.client())).await {
+                Ok(Ok(skew_ms)) => clock::set_rpc_skew_ms(skew_ms),
+                Ok(Err(e)) => logger.error(format!("Skew check failed: {}", e)),
+                Err(_) => logger.error("Skew check timed out".to_string()),
+            }
+
+            let rpc_skew = clock::rpc_skew_ms().unwrap_or(0);
+            // Events are judged against the corrected clock, so compare them against the RPC reference too
+            let event_skew = clock::event_skew_ms().map(|ms| ms - rpc_skew).unwrap_or(0);
+            let skewed = rpc_skew.abs() > warn_ms || event_skew.abs() > warn_ms;
+            if skewed && !alerted {
+                let message = format!(
+                    "⏱️ Clock skew above {}s: host vs RPC {}, events vs RPC {}. Time-based filters use the RPC-corrected clock.",
+                    warn_ms / 1000,
+                    format_skew(Some(rpc_skew)),
+                    format_skew(Some(event_skew))
+                );
+                logger.error(message.clone());
+                TelegramNotifier.notify(message);
+            } else if !skewed && alerted {
+                logger.error(format!("Clock skew back within {}s", warn_ms / 1000));
+            }
+            alerted = skewed;
+        }
+    });
+}
@@ -0,0 +1,360 @@
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::{Duration, SystemTime};
+use colored::Colorize;
+use once_cell::sync::Lazy;
+
+use crate::common::logger::Logger;
+use crate::engine::exit_strategy::RuntimeParams;
+use crate::services::notifier::Notifier;
+use crate::services::telegram_commands;
+
+/// Keys the running process picks up from the file without a restart
+pub const RELOADABLE_KEYS: &[&str] = &["TAKE_PROFIT_TIERS", "PROFIT_LADDER", "TRAILING_STOP_PCT", "TRAILING_STOP_ARM_SECS", "TRAILING_STOP_MIN_LIQUIDITY", "STOP_LOSS_PCT"];
+
+/// Key fragments whose values never leave the process: keys, tokens and endpoints that embed API keys
+const SECRET_MARKERS: &[&str] = &["PRIVATE_KEY", "TOKEN", "SECRET", "PASSWORD", "API_KEY", "RPC", "URL", "HTTP"];
+
+/// Static keys that differ from what the process started with, for the "config" /status section
+static DRIFTED_KEYS: Lazy<Mutex<Vec<String>>> = Lazy::new(|| Mutex::new(Vec::new()));
+
+/// Secrets show as "<redacted>"; unset as "(unset)"
+pub fn display_value(key: &str, value: Option<&str>) -> String {
+    match value {
+        None => "(unset)".to_string(),
+        Some(_) if SECRET_MARKERS.iter().any(|marker| key.contains(marker)) => "<redacted>".to_string(),
+        Some(value) => value.to_string(),
+    }
+}
+
+/// One static setting whose value in the file no longer matches the process
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ConfigDrift {
+    pub key: String,
+    /// Value the process started with
+    pub running: Option<String>,
+    /// Value the file holds now
+    pub file: Option<String>,
+}
+
+impl ConfigDrift {
+    pub fn format(&self) -> String {
+        format!(
+            "{}: {} → {}",
+            self.key,
+            display_value(&self.key, self.running.as_deref()),
+            display_value(&self.key, self.file.as_deref())
+        )
+    }
+}
+
+/// What one check of the file found
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct WatchOutcome {
+    /// Reloadable keys applied to the running process
+    pub reloaded: Vec<String>,
+    /// Every static key currently drifted, whether or not it was notified before
+    pub drifted: Vec<ConfigDrift>,
+    /// A notification went out for this change-set
+    pub notified: bool,
+}
+
+/// ENV_FILE (default: .env), checked every CONFIG_WATCH_INTERVAL_SECS (default: 5, 0 disables)
+#[derive(Clone, Debug)]
+pub struct ConfigWatchConfig {
+    pub path: String,
+    pub interval: Duration,
+}
+
+impl ConfigWatchConfig {
+    pub fn from_env() -> Option<Self> {
+        let path = std::env::var("ENV_FILE").unwrap_or_else(|_| ".env".to_string());
+        let interval_secs = std::env::var("CONFIG_WATCH_INTERVAL_SECS")
+            .ok()
+            .and_then(|v| v.parse::<u64>().ok())
+            .unwrap_or(5);
+        if interval_secs == 0 {
+            return None;
+        }
+        Some(Self { path, interval: Duration::from_secs(interval_secs) })
+    }
+}
+
+/// Parsed with dotenv's own parser so quoting and comments read the same as at startup
+fn read_env_file(path: &str) -> Result<BTreeMap<String, String>, String> {
+    // Deprecated only in favour of loading into the environment, which is exactly what must not happen here
+    #[allow(deprecated)]
+    let iter = dotenv::from_path_iter(path).map_err(|e| format!("Failed to open {}: {}", path, e))?;
+    let mut values = BTreeMap::new();
+    for item in iter {
+        let (key, value) = item.map_err(|e| format!("Failed to parse {}: {}", path, e))?;
+        values.insert(key, value);
+    }
+    Ok(values)
+}
+
+fn modified_at(path: &str) -> Option<SystemTime> {
+    std::fs::metadata(path).and_then(|meta| meta.modified()).ok()
+}
+
+struct WatchState {
+    /// File contents at the last check, to tell which reloadable keys were edited
+    last_file: BTreeMap<String, String>,
+    last_modified: Option<SystemTime>,
+    /// Change-set the last notification covered; None while in sync
+    notified: Option<Vec<ConfigDrift>>,
+}
+
+/// Applies edits of reloadable keys in the env file and flags the rest as drift
+/// dotenv never overrides variables already set, so keys the shell set at startup can't be changed through the file
+/// and are ignored. A drift notification goes out once per distinct change-set; reverting the file clears the flag.
+pub struct ConfigWatcher {
+    path: String,
+    /// Value of every key the process started with (shell or file)
+    started_with: HashMap<String, String>,
+    /// Keys whose startup value came from the shell rather than the file
+    shell_overrides: BTreeSet<String>,
+    /// Keys the file held at startup
+    started_in_file: BTreeSet<String>,
+    params: Arc<RwLock<RuntimeParams>>,
+    notifier: Arc<dyn Notifier>,
+    state: Mutex<WatchState>,
+    logger: Logger,
+}
+
+impl ConfigWatcher {
+    /// Snapshot `path` and the process environment as the baseline for later checks
+    pub fn new(path: &str, params: Arc<RwLock<RuntimeParams>>, notifier: Arc<dyn Notifier>) -> Self {
+        let logger = Logger::new("[CONFIG-WATCH] => ".cyan().to_string());
+        let file = read_env_file(path).unwrap_or_else(|e| {
+            logger.error(e);
+            BTreeMap::new()
+        });
+        let mut started_with: HashMap<String, String> = std::env::vars().collect();
+        let mut shell_overrides = BTreeSet::new();
+        for (key, value) in &file {
+            match started_with.get(key) {
+                Some(running) if running != value => {
+                    shell_overrides.insert(key.clone());
+                }
+                Some(_) => {}
+                // The file wasn't loaded into this process; take it as the baseline
+                None => {
+                    started_with.insert(key.clone(), value.clone());
+                }
+            }
+        }
+        Self {
+            path: path.to_string(),
+            started_with,
+            shell_overrides,
+            started_in_file: file.keys().cloned().collect(),
+            params,
+            notifier,
+            state: Mutex::new(WatchState { last_file: file, last_modified: modified_at(path), notified: None }),
+            logger,
+        }
+    }
+
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    /// Static keys whose value in `file` differs from the process's
+    pub fn diff(&self, file: &BTreeMap<String, String>) -> Vec<ConfigDrift> {
+        let keys: BTreeSet<&String> = file.keys().chain(self.started_in_file.iter()).collect();
+        keys.into_iter()
+            .filter(|key| !RELOADABLE_KEYS.contains(&key.as_str()) && !self.shell_overrides.contains(*key))
+            .filter_map(|key| {
+                let running = self.started_with.get(key);
+                let in_file = file.get(key);
+                // A key that was never in the file and still isn't says nothing about the file
+                if in_file.is_none() && !self.started_in_file.contains(key) {
+                    return None;
+                }
+                (running != in_file).then(|| ConfigDrift {
+                    key: key.clone(),
+                    running: running.cloned(),
+                    file: in_file.cloned(),
+                })
+            })
+            .collect()
+    }
+
+    /// Re-read the file regardless of its modification time
+    pub fn check_once(&self) -> Result<WatchOutcome, String> {
+        let file = read_env_file(&self.path)?;
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        state.last_modified = modified_at(&self.path);
+
+        let reloaded = self.reload(&state.last_file, &file);
+        let drifted = self.diff(&file);
+        state.last_file = file;
+
+        let notified = if drifted.is_empty() {
+            state.notified = None;
+            false
+        } else if state.notified.as_ref() != Some(&drifted) {
+            let lines: Vec<String> = drifted.iter().map(|drift| format!("• {}", drift.format())).collect();
+            let message = format!("⚠️ {} changed in {}; restart recommended:\n{}", drifted.len(), self.path, lines.join("\n"));
+            println!("{}", message);
+            self.notifier.notify(message);
+            state.notified = Some(drifted.clone());
+            true
+        } else {
+            false
+        };
+        *DRIFTED_KEYS.lock().unwrap_or_else(|e| e.into_inner()) = drifted.iter().map(|drift| drift.key.clone()).collect();
+
+        Ok(WatchOutcome { reloaded, drifted, notified })
+    }
+
+    /// Re-read the file if it was modified since the last check
+    pub fn check_if_modified(&self) -> Result<Option<WatchOutcome>, String> {
+        let modified = modified_at(&self.path);
+        let last = self.state.lock().unwrap_or_else(|e| e.into_inner()).last_modified;
+        if modified.is_some() && modified == last {
+            return Ok(None);
+        }
+        self.check_once().map(Some)
+    }
+
+    /// Apply reloadable keys edited since `previous` and rebuild the runtime params
+    /// Invalid params are logged and the running ones kept.
+    fn reload(&self, previous: &BTreeMap<String, String>, file: &BTreeMap<String, String>) -> Vec<String> {
+        let edited: Vec<&str> = RELOADABLE_KEYS
+            .iter()
+            .copied()
+            .filter(|key| previous.get(*key) != file.get(*key))
+            .collect();
+        if edited.is_empty() {
+            return Vec::new();
+        }
+        let restore: Vec<(&str, Option<String>)> = edited.iter().map(|key| (*key, std::env::var(key).ok())).collect();
+        for key in &edited {
+            match file.get(*key) {
+                Some(value) => std::env::set_var(key, value),
+                None => std::env::remove_var(key),
+            }
+        }
+        let params = match RuntimeParams::try_from_env().and_then(|params| params.validate().map(|_| params)) {
+            Ok(params) => params,
+            Err(e) => {
+                for (key, value) in restore {
+                    match value {
+                        Some(value) => std::env::set_var(key, value),
+                        None => std::env::remove_var(key),
+                    }
+                }
+                self.logger.error(format!("Ignoring edits of {} in {}: {}", edited.join(", "), self.path, e));
+                return Vec::new();
+            }
+        };
+        *self.params.write().unwrap_or_else(|e| e.into_inner()) = params;
+        println!("🔄 Reloaded {} from {}", edited.join(", "), self.path);
+        edited.into_iter().map(str::to_string).collect()
+    }
+
+    /// Check every `interval` in the background; registers the "config" /status section
+    pub fn start(self: &Arc<Self>, interval: Duration) {
+        telegram_commands::register_status_section("config", config_status_section);
+        let watcher = self.clone();
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(interval);
+            loop {
+                interval.tick().await;
+                if let Err(e) = watcher.check_if_modified() {
+                    watcher.logger.error(e);
+                }
+            }
+        });
+    }
+}
+
+/// Keys flagged by the last check; empty while the file matches the process
+pub fn drifted_keys() -> Vec<String> {
+    DRIFTED_KEYS.lock().unwrap_or_else(|e| e.into_inner()).clone()
+}
+
+fn config_status_section() -> String {
+    let keys = drifted_keys();
+    if keys.is_empty() {
+        "Config: in sync with the env file".to_string()
+    } else {
+        format!("Config: ⚠️ restart recommended ({} changed)", keys.join(", "))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::test_fixtures::pubkey;
+    use crate::services::notifier::MemoryNotifier;
+
+    fn write(path: &std::path::Path, lines: &[String]) {
+        std::fs::write(path, lines.join("\n")).unwrap();
+    }
+
+    #[test]
+    fn dynamic_edits_reload_and_static_edits_drift() {
+        let path = std::env::temp_dir().join(format!("config-watch-{}.env", pubkey()));
+        let tag = pubkey().to_uppercase();
+        let mode_key = format!("WATCH_TEST_MODE_{}", tag);
+        let shell_key = format!("WATCH_TEST_SHELL_{}", tag);
+        std::env::set_var(&shell_key, "from-shell");
+        write(&path, &[format!("{}=paper", mode_key), format!("{}=from-file", shell_key), "TRAILING_STOP_ARM_SECS=30".to_string()]);
+
+        let params = Arc::new(RwLock::new(RuntimeParams::default()));
+        let notifier = Arc::new(MemoryNotifier::new());
+        let watcher = ConfigWatcher::new(&path.to_string_lossy(), params.clone(), notifier.clone());
+
+        // A reloadable key is applied, nothing drifts
+        write(&path, &[format!("{}=paper", mode_key), format!("{}=from-file", shell_key), "TRAILING_STOP_ARM_SECS=90".to_string()]);
+        let outcome = watcher.check_once().unwrap();
+        assert_eq!(outcome.reloaded, vec!["TRAILING_STOP_ARM_SECS".to_string()]);
+        assert_eq!(params.read().unwrap().trailing_stop_arm_secs, 90);
+        assert!(outcome.drifted.is_empty());
+        assert!(notifier.messages().is_empty());
+
+        // A static key drifts and is notified once; the shell-set key is ignored
+        let edited = [format!("{}=live", mode_key), format!("{}=edited", shell_key), "TRAILING_STOP_ARM_SECS=90".to_string()];
+        write(&path, &edited);
+        let outcome = watcher.check_once().unwrap();
+        assert!(outcome.reloaded.is_empty());
+        assert_eq!(outcome.drifted, vec![ConfigDrift { key: mode_key.clone(), running: Some("paper".to_string()), file: Some("live".to_string()) }]);
+        assert!(outcome.notified);
+        assert!(drifted_keys().contains(&mode_key));
+        assert!(config_status_section().contains("restart recommended"));
+        let messages = notifier.messages();
+        assert_eq!(messages.len(), 1);
+        assert!(messages[0].contains(&format!("{}: paper → live", mode_key)), "{}", messages[0]);
+
+        let outcome = watcher.check_once().unwrap();
+        assert!(!outcome.notified, "same change-set notified twice");
+        assert_eq!(outcome.drifted.len(), 1);
+
+        // Reverting clears the flag; drifting again notifies again
+        write(&path, &[format!("{}=paper", mode_key), "TRAILING_STOP_ARM_SECS=90".to_string()]);
+        assert!(watcher.check_once().unwrap().drifted.is_empty());
+        assert!(!drifted_keys().contains(&mode_key));
+        write(&path, &edited);
+        assert!(watcher.check_once().unwrap().notified);
+        assert_eq!(notifier.messages().len(), 2);
+
+        std::env::remove_var(&shell_key);
+        std::env::remove_var("TRAILING_STOP_ARM_SECS");
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn secrets_are_redacted() {
+        let drift = ConfigDrift {
+            key: "RPC_HTTP".to_string(),
+            running: Some("https://rpc.example/?api-key=abc".to_string()),
+            file: None,
+        };
+        assert_eq!(drift.format(), "RPC_HTTP: <redacted> → (unset)");
+        assert_eq!(display_value("TELEGRAM_BOT_TOKEN", Some("123:abc")), "<redacted>");
+        assert_eq!(display_value("UNIT_SIZE", Some("0.1")), "0.1");
+    }
+}
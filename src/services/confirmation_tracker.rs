@@ -0,0 +1,181 @@
+use std::sync::Arc;
+use std::time::Duration;
+use anchor_client::solana_client::nonblocking::pubsub_client::PubsubClient;
+use anchor_client::solana_client::rpc_config::RpcSignatureSubscribeConfig;
+use anchor_client::solana_client::rpc_response::RpcSignatureResult;
+use anchor_client::solana_sdk::commitment_config::{CommitmentConfig, CommitmentLevel};
+use anchor_client::solana_sdk::signature::Signature;
+use colored::Colorize;
+use futures::StreamExt;
+use once_cell::sync::OnceCell;
+use solana_transaction_status::TransactionConfirmationStatus;
+use tokio::sync::Mutex;
+use tokio::time::Instant;
+
+use crate::common::logger::Logger;
+use crate::services::rpc_pool::RpcHandle;
+
+/// Status poll cadence; with a websocket it only catches what the subscription missed
+const POLL_INTERVAL: Duration = Duration::from_millis(1500);
+
+/// How a sent transaction ended up
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ConfirmationOutcome {
+    Processed,
+    Confirmed,
+    Finalized,
+    /// Landed with an error; only the fee was spent
+    Failed(String),
+    /// Not seen at the requested commitment before the timeout; a recent-blockhash transaction is past its
+    /// blockhash by then and can no longer land
+    Expired,
+}
+
+impl ConfirmationOutcome {
+    pub fn landed(&self) -> bool {
+        matches!(self, Self::Processed | Self::Confirmed | Self::Finalized)
+    }
+
+    fn reached(level: CommitmentLevel) -> Self {
+        match level {
+            CommitmentLevel::Processed => Self::Processed,
+            CommitmentLevel::Confirmed => Self::Confirmed,
+            CommitmentLevel::Finalized => Self::Finalized,
+        }
+    }
+}
+
+/// CONFIRM_TIMEOUT_SECS (default: 30) a sent trade is waited on before it counts as expired
+pub fn confirm_timeout() -> Duration {
+    let secs = std::env::var("CONFIRM_TIMEOUT_SECS")
+        .ok()
+        .and_then(|v| v.parse::<u64>().ok())
+        .unwrap_or(30);
+    Duration::from_secs(secs.max(1))
+}
+
+/// Waits for signatures through `signatureSubscribe` on RPC_WSS, polling `getSignatureStatuses` alongside
+/// Without RPC_WSS, or while the websocket is down, the poll alone decides.
+pub struct ConfirmationTracker {
+    read_rpc: RpcHandle,
+    wss_url: Option<String>,
+    /// Shared connection, dropped on failure so the next wait reconnects
+    pubsub: Mutex<Option<Arc<PubsubClient>>>,
+    logger: Logger,
+}
+
+static TRACKER: OnceCell<ConfirmationTracker> = OnceCell::new();
+
+/// The process-wide tracker; the first caller's read pool backs the status polls
+pub fn tracker(read_rpc: &RpcHandle) -> &'static ConfirmationTracker {
+    TRACKER.get_or_init(|| ConfirmationTracker::new(read_rpc.clone(), std::env::var("RPC_WSS").ok().filter(|url| !url.trim().is_empty())))
+}
+
+impl ConfirmationTracker {
+    pub fn new(read_rpc: RpcHandle, wss_url: Option<String>) -> Self {
+        Self {
+            read_rpc,
+            wss_url,
+            pubsub: Mutex::new(None),
+            logger: Logger::new("[CONFIRMATION] => ".cyan().to_string()),
+        }
+    }
+
+    async fn pubsub(&self) -> Option<Arc<PubsubClient>> {
+        let url = self.wss_url.as_ref()?;
+        let mut pubsub = self.pubsub.lock().await;
+        if pubsub.is_none() {
+            match PubsubClient::new(url).await {
+                Ok(client) => *pubsub = Some(Arc::new(client)),
+                Err(e) => self.logger.error(format!("RPC_WSS connect failed, polling instead: {}", e)),
+            }
+        }
+        pubsub.clone()
+    }
+
+    async fn drop_pubsub(&self) {
+        self.pubsub.lock().await.take();
+    }
+
+    /// Wait until `signature` reaches `commitment`, fails, or `timeout` passes
+    /// Err only for a request the tracker can't make at all; RPC hiccups are retried until the timeout.
+    pub async fn await_confirmation(&self, signature: &Signature, commitment: CommitmentLevel, timeout: Duration) -> Result<ConfirmationOutcome, String> {
+        let deadline = Instant::now() + timeout;
+        let client = self.pubsub().await;
+        let config = RpcSignatureSubscribeConfig {
+            commitment: Some(CommitmentConfig { commitment }),
+            enable_received_notification: Some(false),
+        };
+        let (mut notifications, unsubscribe) = match &client {
+            Some(client) => match client.signature_subscribe(signature, Some(config)).await {
+                Ok((stream, unsubscribe)) => (Some(stream), Some(unsubscribe)),
+                Err(e) => {
+                    self.logger.error(format!("signatureSubscribe failed, polling instead: {}", e));
+                    self.drop_pubsub().await;
+                    (None, None)
+                }
+            },
+            None => (None, None),
+        };
+
+        let outcome = loop {
+            // Polled first so a signature that landed before the subscription is still seen
+            if let Some(outcome) = self.poll(signature, commitment).await {
+                break outcome;
+            }
+            let now = Instant::now();
+            if now >= deadline {
+                break ConfirmationOutcome::Expired;
+            }
+            let wait = POLL_INTERVAL.min(deadline - now);
+            match notifications.as_mut() {
+                Some(stream) => match tokio::time::timeout(wait, stream.next()).await {
+                    Ok(Some(response)) => {
+                        if let RpcSignatureResult::ProcessedSignature(result) = response.value {
+                            break match result.err {
+                                Some(err) => ConfirmationOutcome::Failed(err.to_string()),
+                                None => ConfirmationOutcome::reached(commitment),
+                            };
+                        }
+                    }
+                    Ok(None) => {
+                        self.logger.error("Signature subscription closed, polling instead".to_string());
+                        notifications = None;
+                        self.drop_pubsub().await;
+                    }
+                    Err(_) => {}
+                },
+                None => tokio::time::sleep(wait).await,
+            }
+        };
+        drop(notifications);
+        if let Some(unsubscribe) = unsubscribe {
+            unsubscribe().await;
+        }
+        Ok(outcome)
+    }
+
+    /// The signature's outcome once it failed or reached `commitment`; None while it hasn't, or the poll failed
+    async fn poll(&self, signature: &Signature, commitment: CommitmentLevel) -> Option<ConfirmationOutcome> {
+        let statuses = match self.read_rpc.client().get_signature_statuses(&[*signature]).await {
+            Ok(response) => response.value,
+            Err(e) => {
+                self.logger.error(format!("getSignatureStatuses for {} failed: {}", signature, e));
+                return None;
+            }
+        };
+        let status = statuses.into_iter().next().flatten()?;
+        if let Some(err) = status.err {
+            return Some(ConfirmationOutcome::Failed(err.to_string()));
+        }
+        if !status.satisfies_commitment(CommitmentConfig { commitment }) {
+            return None;
+        }
+        Some(match status.confirmation_status {
+            Some(TransactionConfirmationStatus::Finalized) => ConfirmationOutcome::Finalized,
+            Some(TransactionConfirmationStatus::Confirmed) => ConfirmationOutcome::Confirmed,
+            Some(TransactionConfirmationStatus::Processed) => ConfirmationOutcome::Processed,
+            None => ConfirmationOutcome::reached(commitment),
+        })
+    }
+}
@@ -0,0 +1,104 @@
+use std::collections::HashMap;
+use std::io::Write;
+use std::sync::Mutex;
+use serde::{Deserialize, Serialize};
+
+/// A trade about to be sent, written before the send so a crash mid-flight leaves a trace
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Intent {
+    pub id: String,
+    pub mint: String,
+    /// "buy" or "sell"
+    pub side: String,
+    /// SOL to spend (buy) or expected (sell)
+    pub sol_amount: f64,
+    /// UI tokens expected (buy) or to sell (sell)
+    pub token_amount: f64,
+    pub price: f64,
+    pub coin_creator: Option<String>,
+    pub created_at: u64,
+    pub session_id: String,
+}
+
+/// One line of the intent log
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum IntentEvent {
+    Open(Intent),
+    /// The executor returned; `outcome` is "filled", "failed: <error>" or a recovery decision
+    Resolved { id: String, outcome: String },
+}
+
+/// Write-ahead log of in-flight trades (INTENT_LOG_FILE, default: intents.jsonl)
+/// Lines are written synchronously: an intent that only reached the background writer would not survive the
+/// crash it exists for. Only trades that reach execution are logged, so the cost is one small write per send.
+pub struct IntentLog {
+    path: String,
+    file: Mutex<()>,
+}
+
+impl IntentLog {
+    pub fn from_env() -> Self {
+        Self::new(std::env::var("INTENT_LOG_FILE").unwrap_or_else(|_| "intents.jsonl".to_string()))
+    }
+
+    pub fn new(path: String) -> Self {
+        Self { path, file: Mutex::new(()) }
+    }
+
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    fn append(&self, event: &IntentEvent) -> std::io::Result<()> {
+        let line = serde_json::to_string(event).map_err(std::io::Error::other)?;
+        let _guard = self.file.lock();
+        let mut file = std::fs::OpenOptions::new().create(true).append(true).open(&self.path)?;
+        writeln!(file, "{}", line)?;
+        file.sync_data()
+    }
+
+    pub fn begin(&self, intent: Intent) -> std::io::Result<()> {
+        self.append(&IntentEvent::Open(intent))
+    }
+
+    pub fn resolve(&self, id: &str, outcome: &str) -> std::io::Result<()> {
+        self.append(&IntentEvent::Resolved { id: id.to_string(), outcome: outcome.to_string() })
+    }
+
+    /// Intents opened but never resolved, oldest first; unreadable lines are skipped
+    pub fn pending(&self) -> Vec<Intent> {
+        let Ok(content) = std::fs::read_to_string(&self.path) else {
+            return Vec::new();
+        };
+        let mut open: HashMap<String, (usize, Intent)> = HashMap::new();
+        for (index, line) in content.lines().enumerate() {
+            match serde_json::from_str::<IntentEvent>(line) {
+                Ok(IntentEvent::Open(intent)) => {
+                    open.insert(intent.id.clone(), (index, intent));
+                }
+                Ok(IntentEvent::Resolved { id, .. }) => {
+                    open.remove(&id);
+                }
+                Err(_) => {}
+            }
+        }
+        let mut pending: Vec<(usize, Intent)> = open.into_values().collect();
+        pending.sort_by_key(|(index, _)| *index);
+        pending.into_iter().map(|(_, intent)| intent).collect()
+    }
+
+    /// Drop resolved history, keeping only the pending intents
+    pub fn compact(&self) -> std::io::Result<()> {
+        let pending = self.pending();
+        let _guard = self.file.lock();
+        let mut lines = String::new();
+        for intent in pending {
+            let line = serde_json::to_string(&IntentEvent::Open(intent))
+                .map_err(std::io::Error::other)?;
+            lines.push_str(&line);
+            lines.push('\n');
+        }
+        std::fs::write(&self.path, lines)
+    }
+}
@@ -1,36 +1,54 @@
-use std::sync::Arc;
 use std::str::FromStr;
-use anyhow::{anyhow, Result};
+use anyhow::Result;
+use async_trait::async_trait;
 use colored::Colorize;
 use reqwest::Client;
 use serde::{Deserialize, Serialize};
 use anchor_client::solana_sdk::{
     signature::Keypair,
+    signer::Signer,
     pubkey::Pubkey,
     transaction::VersionedTransaction,
 };
-use anchor_client::solana_client::nonblocking::rpc_client::RpcClient;
 use tokio::time::Duration;
-use spl_associated_token_account::get_associated_token_address_with_program_id;
+use spl_associated_token_account::{get_associated_token_address, get_associated_token_address_with_program_id};
 
 use crate::common::logger::Logger;
+use crate::core::mint_inspector;
+use crate::error::{SniperError, SniperResult};
+use crate::services::rpc_pool::RpcHandle;
 
 const JUPITER_API_URL: &str = "https://lite-api.jup.ag/swap/v1";
 const JUPITER_SWAP_API_URL: &str = "https://lite-api.jup.ag/swap/v1";
 const SOL_MINT: &str = "So11111111111111111111111111111111111111112";
 
+/// Which side of a quote `amount` fixes
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SwapMode {
+    /// `amount` is what goes in; the output floats within the slippage
+    #[default]
+    ExactIn,
+    /// `amount` is what must come out, e.g. an exact SOL amount when rebalancing; the input floats
+    ExactOut,
+}
+
+/// Query string of GET /quote
 #[derive(Debug, Serialize)]
 struct QuoteRequest {
     #[serde(rename = "inputMint")]
     input_mint: String,
     #[serde(rename = "outputMint")]
     output_mint: String,
-    amount: String,
+    amount: u64,
     #[serde(rename = "slippageBps")]
     slippage_bps: u64,
+    #[serde(rename = "swapMode")]
+    swap_mode: SwapMode,
+    #[serde(rename = "platformFeeBps", skip_serializing_if = "Option::is_none")]
+    platform_fee_bps: Option<u64>,
 }
 
-#[derive(Debug, Deserialize, Serialize)] // Add Serialize derive
+#[derive(Clone, Debug, Deserialize, Serialize)] // Add Serialize derive
 pub struct QuoteResponse {
     #[serde(rename = "inputMint")]
     pub input_mint: String,
@@ -43,7 +61,7 @@ pub struct QuoteResponse {
     #[serde(rename = "otherAmountThreshold")]
     pub other_amount_threshold: String,
     #[serde(rename = "swapMode")]
-    pub swap_mode: String,
+    pub swap_mode: SwapMode,
     #[serde(rename = "slippageBps")]
     pub slippage_bps: u64,
     #[serde(rename = "platformFee")]
@@ -93,6 +111,55 @@ pub struct SwapInfo {
     pub fee_mint: Option<String>,
 }
 
+/// Optional swap request fields; the defaults leave every one of them to Jupiter
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct SwapOptions {
+    /// Output goes to this existing token account instead of the user's ATA (and stays wrapped if it is WSOL)
+    pub destination_token_account: Option<Pubkey>,
+    /// Platform fee: bps added to the quote and the token account collecting it
+    pub platform_fee: Option<(u64, Pubkey)>,
+    pub use_shared_accounts: Option<bool>,
+}
+
+impl SwapOptions {
+    /// JUPITER_PLATFORM_FEE_BPS with JUPITER_FEE_ACCOUNT (both or neither), JUPITER_USE_SHARED_ACCOUNTS (default: Jupiter's choice)
+    pub fn from_env() -> Self {
+        let fee_bps = std::env::var("JUPITER_PLATFORM_FEE_BPS")
+            .ok()
+            .and_then(|v| v.parse::<u64>().ok())
+            .filter(|bps| *bps > 0);
+        let fee_account = std::env::var("JUPITER_FEE_ACCOUNT")
+            .ok()
+            .and_then(|v| Pubkey::from_str(v.trim()).ok());
+        let use_shared_accounts = std::env::var("JUPITER_USE_SHARED_ACCOUNTS")
+            .ok()
+            .and_then(|v| v.parse::<bool>().ok());
+        Self {
+            destination_token_account: None,
+            platform_fee: fee_bps.zip(fee_account),
+            use_shared_accounts,
+        }
+    }
+
+    /// Options for selling into SOL: JUPITER_SELL_DESTINATION=wsol (default) sends proceeds to the wallet's
+    /// WSOL ATA, where the PumpFun sells put them; native lets Jupiter unwrap them into the wallet
+    pub fn for_sell(wallet: &Pubkey) -> Self {
+        let native = std::env::var("JUPITER_SELL_DESTINATION")
+            .map(|v| v.trim().eq_ignore_ascii_case("native"))
+            .unwrap_or(false);
+        let mut options = Self::from_env();
+        if !native {
+            options.destination_token_account = Some(get_associated_token_address(wallet, &spl_token::native_mint::ID));
+        }
+        options
+    }
+
+    /// Whether sale proceeds stay wrapped as WSOL
+    pub fn keeps_wsol(&self) -> bool {
+        self.destination_token_account.is_some()
+    }
+}
+
 #[derive(Debug, Serialize)]
 struct SwapRequest {
     #[serde(rename = "quoteResponse")]
@@ -105,6 +172,64 @@ struct SwapRequest {
     dynamic_compute_unit_limit: bool,
     #[serde(rename = "prioritizationFeeLamports")]
     prioritization_fee_lamports: PrioritizationFee,
+    #[serde(rename = "destinationTokenAccount", skip_serializing_if = "Option::is_none")]
+    destination_token_account: Option<String>,
+    #[serde(rename = "feeAccount", skip_serializing_if = "Option::is_none")]
+    fee_account: Option<String>,
+    #[serde(rename = "useSharedAccounts", skip_serializing_if = "Option::is_none")]
+    use_shared_accounts: Option<bool>,
+}
+
+impl SwapRequest {
+    fn new(quote_response: QuoteResponse, user_public_key: &Pubkey) -> Self {
+        Self {
+            quote_response,
+            user_public_key: user_public_key.to_string(),
+            wrap_and_unwrap_sol: true,
+            dynamic_compute_unit_limit: true,
+            prioritization_fee_lamports: PrioritizationFee {
+                priority_level_with_max_lamports: PriorityLevel {
+                    max_lamports: 1_000_000, // 0.001 SOL max priority fee
+                    priority_level: "high".to_string(),
+                },
+            },
+            destination_token_account: None,
+            fee_account: None,
+            use_shared_accounts: None,
+        }
+    }
+
+    /// An explicit destination keeps WSOL output wrapped
+    fn destination_token_account(mut self, account: Pubkey) -> Self {
+        self.destination_token_account = Some(account.to_string());
+        self.wrap_and_unwrap_sol = false;
+        self
+    }
+
+    fn fee_account(mut self, account: Pubkey) -> Self {
+        self.fee_account = Some(account.to_string());
+        self
+    }
+
+    fn use_shared_accounts(mut self, shared: bool) -> Self {
+        self.use_shared_accounts = Some(shared);
+        self
+    }
+
+    fn with_options(self, options: &SwapOptions) -> Self {
+        let request = match options.destination_token_account {
+            Some(account) => self.destination_token_account(account),
+            None => self,
+        };
+        let request = match options.platform_fee {
+            Some((_, account)) => request.fee_account(account),
+            None => request,
+        };
+        match options.use_shared_accounts {
+            Some(shared) => request.use_shared_accounts(shared),
+            None => request,
+        }
+    }
 }
 
 #[derive(Debug, Serialize)]
@@ -130,12 +255,13 @@ struct SwapResponse {
 #[derive(Clone)]
 pub struct JupiterClient {
     client: Client,
-    rpc_client: Arc<RpcClient>,
+    read_rpc: RpcHandle,
+    send_rpc: RpcHandle,
     logger: Logger,
 }
 
 impl JupiterClient {
-    pub fn new(rpc_client: Arc<RpcClient>) -> Self {
+    pub fn new(read_rpc: RpcHandle, send_rpc: RpcHandle) -> Self {
         let client = Client::builder()
             .timeout(Duration::from_secs(5))
             .build()
@@ -143,44 +269,51 @@ impl JupiterClient {
             
         Self {
             client,
-            rpc_client,
+            read_rpc,
+            send_rpc,
             logger: Logger::new("[JUPITER] => ".magenta().to_string()),
         }
     }
 
-    /// Get a quote for swapping tokens
+    /// Open a keep-alive connection to the API; any HTTP response counts
+    pub async fn warm_up(&self) -> SniperResult<()> {
+        self.client.get(JUPITER_API_URL).send().await
+            .map_err(|e| SniperError::from(e).context("Jupiter warm-up failed"))?;
+        Ok(())
+    }
+
+    /// Get a quote for swapping tokens; `amount` is the input for ExactIn and the output for ExactOut
+    /// A configured platform fee is added to every quote; the quoted out amount is net of it.
     pub async fn get_quote(
         &self,
         input_mint: &str,
         output_mint: &str,
         amount: u64,
         slippage_bps: u64,
-    ) -> Result<QuoteResponse> {
-        self.logger.log(format!("Getting Jupiter quote: {} -> {} (amount: {}, slippage: {}bps)", 
-            input_mint, output_mint, amount, slippage_bps));
+        swap_mode: SwapMode,
+    ) -> SniperResult<QuoteResponse> {
+        self.logger.critical(format!("Getting Jupiter quote: {} -> {} (amount: {} {:?}, slippage: {}bps)", 
+            input_mint, output_mint, amount, swap_mode, slippage_bps));
 
         let quote_request = QuoteRequest {
             input_mint: input_mint.to_string(),
             output_mint: output_mint.to_string(),
-            amount: amount.to_string(),
-            slippage_bps: 15000,  // fix to 15000 bps
+            amount,
+            slippage_bps,
+            swap_mode,
+            platform_fee_bps: SwapOptions::from_env().platform_fee.map(|(fee_bps, _)| fee_bps),
         };
 
         let url = format!("{}/quote", JUPITER_API_URL);
         let response = self.client
             .get(&url)
-            .query(&[
-                ("inputMint", &quote_request.input_mint),
-                ("outputMint", &quote_request.output_mint),
-                ("amount", &quote_request.amount),
-                ("slippageBps", &slippage_bps.to_string()), // Use the actual slippage parameter
-            ])
+            .query(&quote_request)
             .send()
             .await?;
 
         if !response.status().is_success() {
             let error_text = response.text().await.unwrap_or_else(|_| "Unknown error".to_string());
-            return Err(anyhow!("Jupiter quote API error: {}", error_text));
+            return Err(SniperError::QuoteFailed(format!("Jupiter quote API error: {}", error_text)));
         }
 
         // Log the raw response for debugging
@@ -188,7 +321,7 @@ impl JupiterClient {
         self.logger.log(format!("Raw quote response: {}", &response_text[..std::cmp::min(500, response_text.len())]));
         
         let quote: QuoteResponse = serde_json::from_str(&response_text)
-            .map_err(|e| anyhow!("Failed to parse quote response: {}. Response: {}", e, &response_text[..std::cmp::min(200, response_text.len())]))?;
+            .map_err(|e| SniperError::QuoteFailed(format!("Failed to parse quote response: {}. Response: {}", e, &response_text[..std::cmp::min(200, response_text.len())])))?;
         
         self.logger.log(format!("Jupiter quote received: {} {} -> {} {} (price impact: {}%)", 
             quote.in_amount, input_mint, quote.out_amount, output_mint, quote.price_impact_pct));
@@ -201,21 +334,11 @@ impl JupiterClient {
         &self,
         quote: QuoteResponse,
         user_public_key: &Pubkey,
-    ) -> Result<VersionedTransaction> {
+        options: &SwapOptions,
+    ) -> SniperResult<VersionedTransaction> {
         self.logger.log(format!("Getting Jupiter swap transaction for user: {}", user_public_key));
 
-        let swap_request = SwapRequest {
-            quote_response: quote,
-            user_public_key: user_public_key.to_string(),
-            wrap_and_unwrap_sol: true,
-            dynamic_compute_unit_limit: true,
-            prioritization_fee_lamports: PrioritizationFee {
-                priority_level_with_max_lamports: PriorityLevel {
-                    max_lamports: 1_000_000, // 0.001 SOL max priority fee
-                    priority_level: "high".to_string(),
-                },
-            },
-        };
+        let swap_request = SwapRequest::new(quote, user_public_key).with_options(options);
 
         let url = format!("{}/swap", JUPITER_SWAP_API_URL);
         
@@ -229,14 +352,16 @@ impl JupiterClient {
             let status = response.status();
             let error_text = response.text().await.unwrap_or_else(|_| "Unknown error".to_string());
             self.logger.log(format!("Jupiter swap API error: Status {}, Response: {}", status, error_text).red().to_string());
-            return Err(anyhow!("Swap API returned status: {} - {}", status, error_text));
+            return Err(SniperError::QuoteFailed(format!("Swap API returned status: {} - {}", status, error_text)));
         }
 
         let swap_response: SwapResponse = response.json().await?;
         
         // Decode the base64 transaction
-        let transaction_bytes = base64::decode(&swap_response.swap_transaction)?;
-        let transaction: VersionedTransaction = bincode::deserialize(&transaction_bytes)?;
+        let transaction_bytes = base64::decode(&swap_response.swap_transaction)
+            .map_err(|e| SniperError::QuoteFailed(format!("Swap transaction is not base64: {}", e)))?;
+        let transaction: VersionedTransaction = bincode::deserialize(&transaction_bytes)
+            .map_err(|e| SniperError::QuoteFailed(format!("Failed to decode swap transaction: {}", e)))?;
 
         self.logger.log("Jupiter swap transaction received and decoded successfully".to_string());
 
@@ -244,45 +369,75 @@ impl JupiterClient {
     }
 
     /// Execute a token sell using Jupiter (complete flow)
+    /// `amount` is raw tokens to sell for ExactIn, lamports to receive for ExactOut.
+    /// The destination account in `options`, if any, is created first since Jupiter expects it to exist.
     pub async fn sell_token_with_jupiter(
         &self,
         token_mint: &str,
-        token_amount: u64,
+        amount: u64,
         slippage_bps: u64,
+        swap_mode: SwapMode,
         keypair: &Keypair,
-    ) -> Result<String> {
-        use tokio::time::{timeout, Duration};
-        
-        self.logger.log(format!("Starting Jupiter sell for token {} (amount: {}, slippage: {}bps)", 
-            token_mint, token_amount, slippage_bps));
+        options: &SwapOptions,
+    ) -> SniperResult<String> {
+        self.logger.critical(format!("Starting Jupiter sell for token {} (amount: {} {:?}, slippage: {}bps)", 
+            token_mint, amount, swap_mode, slippage_bps));
 
-        // CRITICAL FIX: Add timeout to prevent hanging on slow RPC
-        const RPC_TIMEOUT: Duration = Duration::from_secs(5);
-
-        // All tokens are Token-2022, ensure ATA exists with Token-2022 program
         let mint_pubkey = Pubkey::from_str(token_mint)
-            .map_err(|e| anyhow!("Invalid mint address: {}", e))?;
+            .map_err(|e| SniperError::Other(format!("Invalid mint address: {}", e)))?;
         
-        self.logger.log(format!("✅ Ensuring Token-2022 ATA exists...").green().to_string());
+        // The ATA holding the balance derives from the mint's own token program
+        let token_program = mint_inspector::resolve_token_program(&self.read_rpc.client(), &mint_pubkey).await?;
+        self.logger.critical(format!("✅ Ensuring ATA exists ({})...", if token_program == spl_token_2022::ID { "Token-2022" } else { "Token" }).green().to_string());
         
-        // Ensure ATA exists using Token-2022 program
-        let token_2022_program = Pubkey::from_str("TokenzQdBNbLqP5VEhdkAS6EPFLC1PHnBqCXEpPxuEb")
-            .map_err(|e| anyhow!("Failed to parse Token-2022 program ID: {}", e))?;
+        self.ensure_ata(keypair, &mint_pubkey, &token_program).await?;
+        if let Some(destination) = options.destination_token_account {
+            if destination == get_associated_token_address(&keypair.pubkey(), &spl_token::native_mint::ID) {
+                self.ensure_ata(keypair, &spl_token::native_mint::ID, &spl_token::ID).await?;
+            }
+        }
+
+        // Get quote
+        self.logger.critical("Getting Jupiter quote...".to_string());
+        let quote = self.get_quote(
+            token_mint,
+            SOL_MINT,
+            amount,
+            slippage_bps,
+            swap_mode,
+        ).await?;
+
+        self.logger.critical("Quote received, getting swap transaction...".to_string());
         
+        // Get swap transaction
+        let transaction = self.get_swap_transaction(quote, &keypair.pubkey(), options).await?;
+        let signature = self.sign_and_send(transaction, keypair).await?;
+
+        self.logger.critical(format!("Jupiter sell transaction sent: {}", signature).green().to_string());
+
+        Ok(signature)
+    }
+
+    /// Create the owner's ATA for `mint` unless it already exists; creation failures are logged, not fatal
+    async fn ensure_ata(&self, keypair: &Keypair, mint_pubkey: &Pubkey, token_program: &Pubkey) -> SniperResult<()> {
+        use tokio::time::{timeout, Duration};
+
+        const RPC_TIMEOUT: Duration = Duration::from_secs(5);
+
         let ata = get_associated_token_address_with_program_id(
             &keypair.pubkey(),
-            &mint_pubkey,
-            &token_2022_program,
+            mint_pubkey,
+            token_program,
         );
         
         // CRITICAL FIX: Add timeout to ATA check
-        match timeout(RPC_TIMEOUT, self.rpc_client.get_account(&ata)).await {
+        match timeout(RPC_TIMEOUT, self.read_rpc.client().get_account(&ata)).await {
             Ok(Ok(_)) => {
-                self.logger.log(format!("✅ Token-2022 ATA already exists: {}", ata).green().to_string());
+                self.logger.critical(format!("✅ ATA already exists: {}", ata).green().to_string());
             }
             Ok(Err(_)) | Err(_) => {
-                // Create ATA using Token-2022 program
-                self.logger.log(format!("Creating Token-2022 ATA: {}", ata).yellow().to_string());
+                // Idempotent, so a concurrent creation is harmless
+                self.logger.critical(format!("Creating ATA: {}", ata).yellow().to_string());
                 
                 use anchor_client::solana_sdk::transaction::Transaction;
                 use spl_associated_token_account::instruction::create_associated_token_account_idempotent;
@@ -290,15 +445,15 @@ impl JupiterClient {
                 let create_ata_ix = create_associated_token_account_idempotent(
                     &keypair.pubkey(),
                     &keypair.pubkey(),
-                    &mint_pubkey,
-                    &token_2022_program,
+                    mint_pubkey,
+                    token_program,
                 );
                 
                 // CRITICAL FIX: Add timeout to get_latest_blockhash
-                let recent_blockhash = match timeout(RPC_TIMEOUT, self.rpc_client.get_latest_blockhash()).await {
+                let recent_blockhash = match timeout(RPC_TIMEOUT, self.read_rpc.client().get_latest_blockhash()).await {
                     Ok(Ok(bh)) => bh,
-                    Ok(Err(e)) => return Err(anyhow!("Failed to get blockhash for ATA creation: {}", e)),
-                    Err(_) => return Err(anyhow!("Blockhash request timed out for ATA creation")),
+                    Ok(Err(e)) => return Err(SniperError::from(e).context("Failed to get blockhash for ATA creation")),
+                    Err(_) => return Err(SniperError::rpc("Blockhash request timed out for ATA creation", true)),
                 };
                 
                 let mut tx = Transaction::new_with_payer(
@@ -306,17 +461,18 @@ impl JupiterClient {
                     Some(&keypair.pubkey()),
                 );
                 tx.sign(&[keypair], recent_blockhash);
+                crate::engine::wallet_sync::register_bot_signature(&tx.signatures[0].to_string());
                 
                 // CRITICAL FIX: Use send_transaction (non-blocking) with timeout instead of send_and_confirm_transaction
                 // This prevents the bot from getting stuck if ATA creation hangs
                 let send_result = timeout(
                     Duration::from_secs(2),
-                    self.rpc_client.send_transaction(&tx)
+                    self.send_rpc.client().send_transaction(&tx)
                 ).await;
                 
                 match send_result {
                     Ok(Ok(sig)) => {
-                        self.logger.log(format!("✅ Token-2022 ATA creation sent: {}", sig).green().to_string());
+                        self.logger.critical(format!("✅ ATA creation sent: {}", sig).green().to_string());
                     }
                     Ok(Err(e)) => {
                         // If ATA creation fails, it might already exist (idempotent), continue anyway
@@ -328,32 +484,46 @@ impl JupiterClient {
                 }
             }
         }
+        Ok(())
+    }
 
-        // Get quote
-        self.logger.log("Getting Jupiter quote...".to_string());
-        let quote = self.get_quote(
-            token_mint,
-            SOL_MINT,
-            token_amount,
-            slippage_bps,
-        ).await?;
+    /// Execute a token buy for `sol_lamports` of native SOL using Jupiter (complete flow)
+    pub async fn buy_token_with_jupiter(
+        &self,
+        token_mint: &str,
+        sol_lamports: u64,
+        slippage_bps: u64,
+        keypair: &Keypair,
+    ) -> SniperResult<String> {
+        self.logger.critical(format!("Starting Jupiter buy for token {} ({} lamports, slippage: {}bps)",
+            token_mint, sol_lamports, slippage_bps));
 
-        self.logger.log(format!("Quote received, getting swap transaction..."));
-        
-        // Get swap transaction
-        let mut transaction = self.get_swap_transaction(quote, &keypair.pubkey()).await?;
+        // wrapAndUnwrapSol lets Jupiter spend native SOL and create the output ATA itself
+        let quote = self.get_quote(SOL_MINT, token_mint, sol_lamports, slippage_bps, SwapMode::ExactIn).await?;
+        let transaction = self.get_swap_transaction(quote, &keypair.pubkey(), &SwapOptions::from_env()).await?;
+        let signature = self.sign_and_send(transaction, keypair).await?;
+
+        self.logger.critical(format!("Jupiter buy transaction sent: {}", signature).green().to_string());
+
+        Ok(signature)
+    }
+
+    /// Put a fresh blockhash on a Jupiter swap transaction, sign it and send it through the send pool
+    async fn sign_and_send(&self, mut transaction: VersionedTransaction, keypair: &Keypair) -> SniperResult<String> {
+        use tokio::time::timeout;
+
+        const RPC_TIMEOUT: Duration = Duration::from_secs(5);
 
         // CRITICAL FIX: Add timeout to get_latest_blockhash - this is a common bottleneck
         self.logger.log("Getting recent blockhash...".to_string());
-        let recent_blockhash = match timeout(RPC_TIMEOUT, self.rpc_client.get_latest_blockhash()).await {
+        let recent_blockhash = match timeout(RPC_TIMEOUT, self.read_rpc.client().get_latest_blockhash()).await {
             Ok(Ok(bh)) => bh,
-            Ok(Err(e)) => return Err(anyhow!("Failed to get recent blockhash: {}", e)),
-            Err(_) => return Err(anyhow!("Blockhash request timed out after {}s", RPC_TIMEOUT.as_secs())),
+            Ok(Err(e)) => return Err(SniperError::from(e).context("Failed to get recent blockhash")),
+            Err(_) => return Err(SniperError::rpc(format!("Blockhash request timed out after {}s", RPC_TIMEOUT.as_secs()), true)),
         };
         transaction.message.set_recent_blockhash(recent_blockhash);
 
         // For VersionedTransaction, we need to manually create the signature
-        use anchor_client::solana_sdk::signer::Signer;
         let message_data = transaction.message.serialize();
         let signature = keypair.sign_message(&message_data);
         
@@ -366,19 +536,197 @@ impl JupiterClient {
             }
             transaction.signatures[signer_index] = signature;
         } else {
-            return Err(anyhow!("Keypair not found in transaction account keys"));
+            return Err(SniperError::Other("Keypair not found in transaction account keys".to_string()));
         }
+        crate::core::tx_decode::debug_print_versioned("Jupiter swap send", &transaction);
+        crate::engine::wallet_sync::register_bot_signature(&signature.to_string());
 
         // CRITICAL FIX: Add timeout to send_transaction - this is the final bottleneck
         self.logger.log("Sending transaction to network...".to_string());
-        let signature = match timeout(RPC_TIMEOUT, self.rpc_client.send_transaction(&transaction)).await {
+        let signature = match timeout(RPC_TIMEOUT, self.send_rpc.client().send_transaction(&transaction)).await {
             Ok(Ok(sig)) => sig,
-            Ok(Err(e)) => return Err(anyhow!("Failed to send transaction: {}", e)),
-            Err(_) => return Err(anyhow!("Transaction send timed out after {}s", RPC_TIMEOUT.as_secs())),
+            Ok(Err(e)) => return Err(SniperError::from(e).context("Failed to send transaction")),
+            Err(_) => return Err(SniperError::rpc(format!("Transaction send timed out after {}s", RPC_TIMEOUT.as_secs()), true)),
         };
 
-        self.logger.log(format!("Jupiter sell transaction sent: {}", signature).green().to_string());
-
         Ok(signature.to_string())
     }
-} 
\ No newline at end of file
+} 
+/// Swap routing used by sell fallbacks and one-off CLI sells; JupiterClient live, scripted doubles in harnesses
+#[async_trait]
+pub trait SwapAggregator: Send + Sync {
+    async fn warm_up(&self) -> Result<()>;
+
+    async fn get_quote(&self, input_mint: &str, output_mint: &str, amount: u64, slippage_bps: u64, swap_mode: SwapMode) -> Result<QuoteResponse>;
+
+    async fn get_swap_transaction(&self, quote: QuoteResponse, user_public_key: &Pubkey, options: &SwapOptions) -> Result<VersionedTransaction>;
+
+    /// Sell for SOL into `options.destination_token_account` (native SOL if None); returns the signature
+    /// `amount` is raw tokens in for ExactIn, lamports out for ExactOut.
+    async fn sell(&self, token_mint: &str, amount: u64, slippage_bps: u64, swap_mode: SwapMode, keypair: &Keypair, options: &SwapOptions) -> Result<String>;
+
+    /// Buy `token_mint` with `sol_lamports` of SOL; returns the signature
+    async fn buy(&self, token_mint: &str, sol_lamports: u64, slippage_bps: u64, keypair: &Keypair) -> Result<String>;
+}
+
+#[async_trait]
+impl SwapAggregator for JupiterClient {
+    async fn warm_up(&self) -> Result<()> {
+        Ok(JupiterClient::warm_up(self).await?)
+    }
+
+    async fn get_quote(&self, input_mint: &str, output_mint: &str, amount: u64, slippage_bps: u64, swap_mode: SwapMode) -> Result<QuoteResponse> {
+        Ok(JupiterClient::get_quote(self, input_mint, output_mint, amount, slippage_bps, swap_mode).await?)
+    }
+
+    async fn get_swap_transaction(&self, quote: QuoteResponse, user_public_key: &Pubkey, options: &SwapOptions) -> Result<VersionedTransaction> {
+        Ok(JupiterClient::get_swap_transaction(self, quote, user_public_key, options).await?)
+    }
+
+    async fn sell(&self, token_mint: &str, amount: u64, slippage_bps: u64, swap_mode: SwapMode, keypair: &Keypair, options: &SwapOptions) -> Result<String> {
+        Ok(self.sell_token_with_jupiter(token_mint, amount, slippage_bps, swap_mode, keypair, options).await?)
+    }
+
+    async fn buy(&self, token_mint: &str, sol_lamports: u64, slippage_bps: u64, keypair: &Keypair) -> Result<String> {
+        Ok(self.buy_token_with_jupiter(token_mint, sol_lamports, slippage_bps, keypair).await?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::Value;
+
+    fn quote() -> QuoteResponse {
+        QuoteResponse {
+            input_mint: Pubkey::new_unique().to_string(),
+            in_amount: "1000000".to_string(),
+            output_mint: SOL_MINT.to_string(),
+            out_amount: "30000".to_string(),
+            other_amount_threshold: "29700".to_string(),
+            swap_mode: SwapMode::ExactIn,
+            slippage_bps: 100,
+            platform_fee: None,
+            price_impact_pct: "0".to_string(),
+            route_plan: Vec::new(),
+            context_slot: 0,
+        }
+    }
+
+    fn request_json(options: &SwapOptions) -> Value {
+        let request = SwapRequest::new(quote(), &Pubkey::new_unique()).with_options(options);
+        serde_json::to_value(request).unwrap()
+    }
+
+    #[test]
+    fn default_options_leave_the_optional_fields_out() {
+        let json = request_json(&SwapOptions::default());
+        assert_eq!(json["wrapAndUnwrapSol"], Value::Bool(true));
+        for field in ["destinationTokenAccount", "feeAccount", "useSharedAccounts"] {
+            assert!(json.get(field).is_none(), "{} serialized: {}", field, json);
+        }
+    }
+
+    #[test]
+    fn destination_keeps_the_output_wrapped() {
+        let account = Pubkey::new_unique();
+        let json = request_json(&SwapOptions { destination_token_account: Some(account), ..SwapOptions::default() });
+        assert_eq!(json["destinationTokenAccount"], Value::String(account.to_string()));
+        assert_eq!(json["wrapAndUnwrapSol"], Value::Bool(false));
+        assert!(json.get("feeAccount").is_none());
+    }
+
+    #[test]
+    fn fee_account_and_shared_accounts_are_passed_through() {
+        let fee_account = Pubkey::new_unique();
+        let json = request_json(&SwapOptions { platform_fee: Some((25, fee_account)), ..SwapOptions::default() });
+        assert_eq!(json["feeAccount"], Value::String(fee_account.to_string()));
+        assert!(json.get("useSharedAccounts").is_none());
+
+        let json = request_json(&SwapOptions { use_shared_accounts: Some(false), ..SwapOptions::default() });
+        assert_eq!(json["useSharedAccounts"], Value::Bool(false));
+        assert!(json.get("feeAccount").is_none());
+    }
+
+    #[test]
+    fn sells_default_to_the_wsol_account() {
+        let wallet = Pubkey::new_unique();
+        let options = SwapOptions::for_sell(&wallet);
+        assert_eq!(options.destination_token_account, Some(get_associated_token_address(&wallet, &spl_token::native_mint::ID)));
+        assert!(options.keeps_wsol());
+    }
+
+    fn quote_json(swap_mode: &str, swap_info: Value) -> Value {
+        serde_json::json!({
+            "inputMint": SOL_MINT,
+            "inAmount": "31000000",
+            "outputMint": "EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v",
+            "outAmount": "1000000000",
+            "otherAmountThreshold": "31310000",
+            "swapMode": swap_mode,
+            "slippageBps": 100,
+            "platformFee": null,
+            "priceImpactPct": "0.0012",
+            "routePlan": [{ "swapInfo": swap_info, "percent": 100 }],
+            "contextSlot": 123,
+            "timeTaken": 0.01,
+        })
+    }
+
+    fn swap_info() -> Value {
+        serde_json::json!({
+            "label": "Pump.fun Amm",
+            "ammKey": Pubkey::new_unique().to_string(),
+            "inputMint": SOL_MINT,
+            "outputMint": "EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v",
+            "inAmount": "31000000",
+            "outAmount": "1000000000",
+        })
+    }
+
+    #[test]
+    fn quotes_parse_in_both_modes_without_route_fees() {
+        let exact_in: QuoteResponse = serde_json::from_value(quote_json("ExactIn", swap_info())).unwrap();
+        assert_eq!(exact_in.swap_mode, SwapMode::ExactIn);
+        assert_eq!(exact_in.route_plan[0].swap_info.fee_amount, None);
+        assert_eq!(exact_in.route_plan[0].swap_info.fee_mint, None);
+
+        let mut with_fees = swap_info();
+        with_fees["feeAmount"] = Value::String("2500".to_string());
+        with_fees["feeMint"] = Value::String(SOL_MINT.to_string());
+        let exact_out: QuoteResponse = serde_json::from_value(quote_json("ExactOut", with_fees)).unwrap();
+        assert_eq!(exact_out.swap_mode, SwapMode::ExactOut);
+        assert_eq!(exact_out.route_plan[0].swap_info.fee_amount.as_deref(), Some("2500"));
+        assert_eq!(exact_out.route_plan[0].swap_info.fee_mint.as_deref(), Some(SOL_MINT));
+        assert_eq!(exact_out.context_slot, 123);
+
+        assert!(serde_json::from_value::<QuoteResponse>(quote_json("Both", swap_info())).is_err());
+    }
+
+    #[test]
+    fn quotes_reserialize_without_the_missing_route_fees() {
+        let parsed: QuoteResponse = serde_json::from_value(quote_json("ExactOut", swap_info())).unwrap();
+        let json = serde_json::to_value(&parsed).unwrap();
+        assert_eq!(json["swapMode"], Value::String("ExactOut".to_string()));
+        let swap_info = &json["routePlan"][0]["swapInfo"];
+        assert!(swap_info.get("feeAmount").is_none() && swap_info.get("feeMint").is_none(), "{}", swap_info);
+    }
+
+    #[test]
+    fn quote_queries_carry_the_callers_slippage_and_mode() {
+        let request = QuoteRequest {
+            input_mint: "in".to_string(),
+            output_mint: "out".to_string(),
+            amount: 5_000,
+            slippage_bps: 50,
+            swap_mode: SwapMode::ExactOut,
+            platform_fee_bps: None,
+        };
+        let built = reqwest::Client::new().get("https://quote.test/quote").query(&request).build().unwrap();
+        assert_eq!(built.url().query(), Some("inputMint=in&outputMint=out&amount=5000&slippageBps=50&swapMode=ExactOut"));
+
+        let with_fee = QuoteRequest { platform_fee_bps: Some(25), swap_mode: SwapMode::ExactIn, ..request };
+        let built = reqwest::Client::new().get("https://quote.test/quote").query(&with_fee).build().unwrap();
+        assert!(built.url().query().unwrap().ends_with("swapMode=ExactIn&platformFeeBps=25"), "{:?}", built.url().query());
+    }
+}
@@ -0,0 +1,138 @@
+use std::sync::Mutex;
+use colored::Colorize;
+use serde::{Deserialize, Serialize};
+
+use crate::common::logger::Logger;
+use crate::services::trade_costs::TradeCosts;
+use crate::services::line_writer::LineWriter;
+
+/// One executed (or simulated) fill
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct LedgerEntry {
+    pub timestamp: u64,
+    pub mint: String,
+    /// "buy" or "sell"
+    pub side: String,
+    pub signature: String,
+    pub token_amount: f64,
+    pub sol_amount: f64,
+    pub price: f64,
+    pub reason: String,
+    pub simulated: bool,
+    /// "nonce" or "recent" for live trades, None for simulated ones
+    #[serde(default)]
+    pub blockhash_mode: Option<String>,
+    /// Run that produced the fill, see `session_report::session_id`
+    #[serde(default)]
+    pub session_id: String,
+    /// Fee, priority fee, tip and rent columns; all zero for simulated fills
+    #[serde(flatten)]
+    pub costs: TradeCosts,
+    /// Rebuilt from chain by --import-history rather than recorded when the trade was made
+    #[serde(default)]
+    pub imported: bool,
+    /// Buy size before and after AMOUNT_JITTER_PCT; None without jitter
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pre_jitter_sol: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub post_jitter_sol: Option<f64>,
+    /// Effective slippage of the fill (see `slippage::SlippageBasis`); None until measured
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub slippage_pct: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub slippage_sol: Option<f64>,
+}
+
+impl LedgerEntry {
+    /// SOL out of the wallet for a buy, into it for a sell, after costs
+    pub fn net_sol_amount(&self) -> f64 {
+        if self.side == "buy" {
+            self.sol_amount + self.costs.total()
+        } else {
+            self.sol_amount - self.costs.total()
+        }
+    }
+}
+
+/// LEDGER_FILE (default: ledger.jsonl)
+pub fn ledger_path() -> String {
+    std::env::var("LEDGER_FILE").unwrap_or_else(|_| "ledger.jsonl".to_string())
+}
+
+/// Append one entry to LEDGER_FILE synchronously, for CLI commands that exit right after
+pub fn append_now(entry: &LedgerEntry) -> std::io::Result<()> {
+    use std::io::Write;
+    let line = serde_json::to_string(entry).map_err(std::io::Error::other)?;
+    let mut file = std::fs::OpenOptions::new().create(true).append(true).open(ledger_path())?;
+    writeln!(file, "{}", line)
+}
+
+/// Every entry of a ledger file; unreadable lines are skipped
+pub fn read_entries(path: &str) -> std::io::Result<Vec<LedgerEntry>> {
+    use std::io::BufRead;
+    let file = std::fs::File::open(path)?;
+    Ok(std::io::BufReader::new(file)
+        .lines()
+        .map_while(Result::ok)
+        .filter_map(|line| serde_json::from_str::<LedgerEntry>(&line).ok())
+        .collect())
+}
+
+/// Append-only record of fills
+pub trait Ledger: Send + Sync {
+    fn record(&self, entry: LedgerEntry);
+}
+
+/// Writes entries as JSON lines from a background task so callers never block on disk
+pub struct JsonlLedger {
+    writer: LineWriter,
+    logger: Logger,
+}
+
+impl JsonlLedger {
+    /// Path from LEDGER_FILE (default: ledger.jsonl)
+    pub fn from_env() -> Self {
+        Self::new(ledger_path())
+    }
+
+    pub fn new(path: String) -> Self {
+        let logger = Logger::new("[LEDGER] => ".cyan().to_string());
+        Self {
+            writer: LineWriter::new(path, logger.clone()),
+            logger,
+        }
+    }
+}
+
+impl Ledger for JsonlLedger {
+    fn record(&self, entry: LedgerEntry) {
+        match serde_json::to_string(&entry) {
+            Ok(line) => self.writer.write_line(line),
+            Err(e) => self.logger.error(format!("Failed to serialize ledger entry: {}", e)),
+        }
+    }
+}
+
+/// Keeps entries in memory (paper runs and harnesses)
+#[derive(Default)]
+pub struct MemoryLedger {
+    entries: Mutex<Vec<LedgerEntry>>,
+}
+
+impl MemoryLedger {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn entries(&self) -> Vec<LedgerEntry> {
+        self.entries.lock().map(|entries| entries.clone()).unwrap_or_default()
+    }
+}
+
+impl Ledger for MemoryLedger {
+    fn record(&self, entry: LedgerEntry) {
+        if let Ok(mut entries) = self.entries.lock() {
+            entries.push(entry);
+        }
+    }
+}
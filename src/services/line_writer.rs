@@ -0,0 +1,150 @@
+use std::io::Write;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::{Duration, Instant};
+use tokio::sync::mpsc;
+
+use crate::common::logger::Logger;
+
+/// Size bound for a `LineWriter` file
+#[derive(Clone, Copy, Debug)]
+pub struct Rotation {
+    /// The file is rotated before a write once it reaches this size
+    pub max_bytes: u64,
+    /// Rotated files kept as `<path>.1` (newest) .. `<path>.<keep>` (oldest)
+    pub keep: usize,
+}
+
+/// Path of the `index`-th rotated file (0 is the live file)
+pub fn rotated_path(path: &str, index: usize) -> String {
+    if index == 0 {
+        path.to_string()
+    } else {
+        format!("{}.{}", path, index)
+    }
+}
+
+/// Shift `<path>` to `<path>.1`, `<path>.1` to `<path>.2` and so on, dropping the oldest
+fn rotate(path: &str, rotation: Rotation) -> std::io::Result<()> {
+    if rotation.keep == 0 {
+        return std::fs::remove_file(path);
+    }
+    let _ = std::fs::remove_file(rotated_path(path, rotation.keep));
+    for index in (0..rotation.keep).rev() {
+        let from = rotated_path(path, index);
+        if std::path::Path::new(&from).exists() {
+            std::fs::rename(&from, rotated_path(path, index + 1))?;
+        }
+    }
+    Ok(())
+}
+
+/// Lines handed to any writer and not yet on disk
+static PENDING_LINES: AtomicUsize = AtomicUsize::new(0);
+
+/// Wait until every writer has written what it was given, or `timeout` passes; the lines still pending
+pub async fn flush_all(timeout: Duration) -> usize {
+    let deadline = Instant::now() + timeout;
+    loop {
+        let pending = PENDING_LINES.load(Ordering::SeqCst);
+        if pending == 0 || Instant::now() >= deadline {
+            return pending;
+        }
+        tokio::time::sleep(Duration::from_millis(50)).await;
+    }
+}
+
+/// Appends lines to a file from a background task so callers never block on disk
+#[derive(Clone)]
+pub struct LineWriter {
+    sender: mpsc::UnboundedSender<String>,
+}
+
+impl LineWriter {
+    /// `logger` reports write failures under the owner's prefix
+    pub fn new(path: String, logger: Logger) -> Self {
+        Self::spawn(path, None, logger)
+    }
+
+    /// Like `new`, but the file is rotated once it reaches `rotation.max_bytes`
+    pub fn with_rotation(path: String, rotation: Rotation, logger: Logger) -> Self {
+        Self::spawn(path, Some(rotation), logger)
+    }
+
+    fn spawn(path: String, rotation: Option<Rotation>, logger: Logger) -> Self {
+        let (sender, mut receiver) = mpsc::unbounded_channel::<String>();
+
+        tokio::spawn(async move {
+            while let Some(line) = receiver.recv().await {
+                let path = path.clone();
+                let result = tokio::task::spawn_blocking(move || -> std::io::Result<()> {
+                    if let Some(rotation) = rotation {
+                        let size = std::fs::metadata(&path).map(|meta| meta.len()).unwrap_or(0);
+                        if size > 0 && size >= rotation.max_bytes {
+                            rotate(&path, rotation)?;
+                        }
+                    }
+                    let mut file = std::fs::OpenOptions::new().create(true).append(true).open(&path)?;
+                    writeln!(file, "{}", line)
+                }).await;
+                match result {
+                    Ok(Ok(())) => {},
+                    Ok(Err(e)) => logger.error(format!("Failed to write line: {}", e)),
+                    Err(e) => logger.error(format!("Writer task error: {}", e)),
+                }
+                PENDING_LINES.fetch_sub(1, Ordering::SeqCst);
+            }
+        });
+
+        Self { sender }
+    }
+
+    pub fn write_line(&self, line: String) {
+        PENDING_LINES.fetch_add(1, Ordering::SeqCst);
+        if self.sender.send(line).is_err() {
+            PENDING_LINES.fetch_sub(1, Ordering::SeqCst);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_path() -> String {
+        let name = format!("lines-{}.jsonl", anchor_client::solana_sdk::pubkey::Pubkey::new_unique());
+        std::env::temp_dir().join(name).to_string_lossy().into_owned()
+    }
+
+    #[test]
+    fn rotation_shifts_files_and_drops_the_oldest() {
+        let path = temp_path();
+        let rotation = Rotation { max_bytes: 1, keep: 2 };
+        for generation in ["first", "second", "third"] {
+            std::fs::write(&path, generation).unwrap();
+            rotate(&path, rotation).unwrap();
+        }
+        assert!(!std::path::Path::new(&path).exists());
+        assert_eq!(std::fs::read_to_string(rotated_path(&path, 1)).unwrap(), "third");
+        assert_eq!(std::fs::read_to_string(rotated_path(&path, 2)).unwrap(), "second");
+        assert!(!std::path::Path::new(&rotated_path(&path, 3)).exists());
+        for index in 0..=2 {
+            let _ = std::fs::remove_file(rotated_path(&path, index));
+        }
+    }
+
+    #[tokio::test]
+    async fn writer_rotates_once_the_file_is_full() {
+        let path = temp_path();
+        let writer = LineWriter::with_rotation(path.clone(), Rotation { max_bytes: 4, keep: 1 }, Logger::new(String::new()));
+        writer.write_line("one".to_string());
+        writer.write_line("two".to_string());
+        writer.write_line("three".to_string());
+        flush_all(Duration::from_secs(5)).await;
+
+        // "one\n" filled the file, so "two" started a new one, which "three" then rotated out in turn
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "three\n");
+        assert_eq!(std::fs::read_to_string(rotated_path(&path, 1)).unwrap(), "two\n");
+        let _ = std::fs::remove_file(&path);
+        let _ = std::fs::remove_file(rotated_path(&path, 1));
+    }
+}
@@ -16,7 +16,10 @@ pub async fn start_memory_monitor() {
         // Log removed for performance - only critical warnings logged
         
         loop {
-            interval.tick().await;
+            tokio::select! {
+                _ = interval.tick() => {}
+                _ = crate::common::shutdown::cancelled() => break,
+            }
             
             // Collect cache statistics
             let candle_count = TRADE_METRICS.total_candle_count();
@@ -1,11 +1,34 @@
 pub mod blockhash_processor;
 pub mod cache_maintenance;
 pub mod rpc_client;
+pub mod rpc_cache;
+pub mod rpc_pool;
 pub mod zeroslot;
+pub mod scripted_clients;
 pub mod jupiter_api;
 pub mod telegram;
+pub mod telegram_chunks;
 pub mod memory_monitor;
 pub mod task_monitor;
+pub mod line_writer;
+pub mod ledger;
+pub mod intent_log;
+pub mod session_report;
+pub mod notifier;
+pub mod buy_followup;
+pub mod config_watch;
+pub mod telegram_commands;
+pub mod status_api;
+pub mod nonce_health;
+pub mod nonce_pool;
+pub mod confirmation_tracker;
+pub mod zeroslot_pool;
+pub mod priority_fee;
+pub mod warmup;
+pub mod clock_skew;
+pub mod trade_costs;
+pub mod pnl_tracker;
+pub mod slippage;
 
 // Re-export commonly used cache maintenance functions
 pub use cache_maintenance::{
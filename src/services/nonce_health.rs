@@ -0,0 +1,454 @@
+use std::fmt;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use anyhow::{anyhow, Result};
+use colored::Colorize;
+use dashmap::DashMap;
+use once_cell::sync::Lazy;
+use anchor_client::solana_sdk::{
+    hash::Hash,
+    instruction::Instruction,
+    pubkey::Pubkey,
+    signature::Signer,
+    system_instruction,
+    transaction::Transaction,
+};
+
+use crate::common::{config::AppState, logger::Logger};
+use crate::error::classify::classify_error;
+use crate::services::blockhash_processor::BlockhashProcessor;
+use crate::services::nonce_pool::{self, NONCE_POOL};
+use crate::services::notifier::{Notifier, TelegramNotifier};
+use crate::services::telegram_commands;
+
+/// Which blockhash a wallet's transactions are signed with
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BlockhashMode {
+    /// Durable nonce hash with an advance_nonce instruction first
+    Nonce,
+    /// Latest cluster blockhash from the blockhash processor
+    Recent,
+}
+
+impl BlockhashMode {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            BlockhashMode::Nonce => "nonce",
+            BlockhashMode::Recent => "recent",
+        }
+    }
+}
+
+impl fmt::Display for BlockhashMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+/// Mode changes the caller should alert on
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum NonceTransition {
+    SwitchedToRecent,
+    RestoredNonce,
+}
+
+#[derive(Clone, Debug)]
+struct WalletNonceState {
+    mode: BlockhashMode,
+    consecutive_failures: u32,
+    last_error: Option<String>,
+    switched_at: Option<Instant>,
+}
+
+impl Default for WalletNonceState {
+    fn default() -> Self {
+        Self {
+            mode: BlockhashMode::Nonce,
+            consecutive_failures: 0,
+            last_error: None,
+            switched_at: None,
+        }
+    }
+}
+
+/// Per-wallet durable nonce health
+/// After `threshold` consecutive nonce-classified failures a wallet drops to recent-blockhash
+/// mode until the repair task (or /resume-nonce) restores it.
+pub struct NonceHealth {
+    wallets: DashMap<String, WalletNonceState>,
+    threshold: u32,
+}
+
+impl NonceHealth {
+    pub fn new(threshold: u32) -> Self {
+        Self {
+            wallets: DashMap::new(),
+            threshold: threshold.max(1),
+        }
+    }
+
+    /// Threshold from NONCE_FAILURE_THRESHOLD (default: 3)
+    pub fn from_env() -> Self {
+        let threshold = std::env::var("NONCE_FAILURE_THRESHOLD")
+            .ok()
+            .and_then(|v| v.parse::<u32>().ok())
+            .unwrap_or(3);
+        Self::new(threshold)
+    }
+
+    pub fn mode(&self, wallet: &str) -> BlockhashMode {
+        self.wallets.get(wallet).map(|state| state.mode).unwrap_or(BlockhashMode::Nonce)
+    }
+
+    pub fn consecutive_failures(&self, wallet: &str) -> u32 {
+        self.wallets.get(wallet).map(|state| state.consecutive_failures).unwrap_or(0)
+    }
+
+    /// A transaction sent in `mode` was accepted
+    pub fn record_success(&self, wallet: &str, mode: BlockhashMode) {
+        if mode != BlockhashMode::Nonce {
+            return;
+        }
+        if let Some(mut state) = self.wallets.get_mut(wallet) {
+            state.consecutive_failures = 0;
+        }
+    }
+
+    /// A transaction sent in `mode` failed with `error`
+    /// Only nonce-related failures of nonce-mode transactions count towards the switch; any other failure
+    /// ends the run of consecutive ones.
+    pub fn record_failure(&self, wallet: &str, mode: BlockhashMode, error: &str) -> Option<NonceTransition> {
+        if mode != BlockhashMode::Nonce {
+            return None;
+        }
+        if !classify_error(error).is_nonce_related() {
+            self.record_success(wallet, mode);
+            return None;
+        }
+        let mut state = self.wallets.entry(wallet.to_string()).or_default();
+        if state.mode != BlockhashMode::Nonce {
+            return None;
+        }
+        state.consecutive_failures += 1;
+        state.last_error = Some(error.to_string());
+        if state.consecutive_failures < self.threshold {
+            return None;
+        }
+        state.mode = BlockhashMode::Recent;
+        state.switched_at = Some(Instant::now());
+        Some(NonceTransition::SwitchedToRecent)
+    }
+
+    /// Put a wallet back into nonce mode (after a successful probe or a manual resume)
+    pub fn restore(&self, wallet: &str) -> Option<NonceTransition> {
+        let mut state = self.wallets.get_mut(wallet)?;
+        if state.mode == BlockhashMode::Nonce {
+            return None;
+        }
+        *state = WalletNonceState::default();
+        Some(NonceTransition::RestoredNonce)
+    }
+
+    /// Book the outcome of a send (`error` is None on success) and alert `notifier` when the wallet
+    /// switches to recent-blockhash mode
+    pub fn book_send_outcome(&self, wallet: &str, mode: BlockhashMode, error: Option<&str>, notifier: &dyn Notifier) -> Option<NonceTransition> {
+        let Some(error) = error else {
+            self.record_success(wallet, mode);
+            return None;
+        };
+        let transition = self.record_failure(wallet, mode, error)?;
+        let message = format!(
+            "⚠️ Durable nonce failing for {} - switched to recent-blockhash mode after {} consecutive nonce errors. Last error: {}",
+            wallet, self.threshold, error
+        );
+        Logger::new("[NONCE-HEALTH] => ".yellow().to_string()).error(message.clone());
+        notifier.notify(message);
+        Some(transition)
+    }
+
+    /// Wallets currently running in recent-blockhash mode
+    pub fn degraded_wallets(&self) -> Vec<String> {
+        self.wallets
+            .iter()
+            .filter(|entry| entry.value().mode == BlockhashMode::Recent)
+            .map(|entry| entry.key().clone())
+            .collect()
+    }
+
+    /// Wallets whose nonce sends are suspended, with the error that suspended them
+    pub fn degraded_reasons(&self) -> Vec<(String, String)> {
+        let mut reasons: Vec<(String, String)> = self.wallets
+            .iter()
+            .filter(|entry| entry.value().mode == BlockhashMode::Recent)
+            .map(|entry| (entry.key().clone(), entry.value().last_error.clone().unwrap_or_else(|| "-".to_string())))
+            .collect();
+        reasons.sort();
+        reasons
+    }
+
+    pub fn status_lines(&self) -> Vec<String> {
+        let mut lines: Vec<String> = self.wallets
+            .iter()
+            .map(|entry| {
+                let state = entry.value();
+                match state.mode {
+                    BlockhashMode::Nonce => format!(
+                        "{}: nonce ({} consecutive nonce failures)",
+                        entry.key(), state.consecutive_failures
+                    ),
+                    BlockhashMode::Recent => format!(
+                        "{}: recent for {}s, repair pending (last error: {})",
+                        entry.key(),
+                        state.switched_at.map(|at| at.elapsed().as_secs()).unwrap_or(0),
+                        state.last_error.as_deref().unwrap_or("-")
+                    ),
+                }
+            })
+            .collect();
+        lines.sort();
+        lines
+    }
+}
+
+pub static NONCE_HEALTH: Lazy<NonceHealth> = Lazy::new(NonceHealth::from_env);
+
+/// Mode the next transaction of `wallet` should use
+/// Command mode and a missing NONCE_ACCOUNT/NONCE_ACCOUNTS always use recent blockhashes.
+pub fn effective_mode(wallet: &Pubkey) -> BlockhashMode {
+    if BlockhashProcessor::should_use_offchain_blockhash() {
+        NONCE_HEALTH.mode(&wallet.to_string())
+    } else {
+        BlockhashMode::Recent
+    }
+}
+
+/// Pick the blockhash for a transaction and, in nonce mode, prepend the advance_nonce instruction
+/// The nonce account is claimed from the pool until the send settles it (`nonce_pool::after_send`).
+/// A nonce hash that can't be read counts as a nonce failure, and with every account in flight the
+/// transaction uses a recent blockhash rather than wait.
+pub async fn prepare_blockhash(app_state: &AppState, instructions: &mut Vec<Instruction>) -> Result<(Hash, BlockhashMode)> {
+    let wallet = app_state.wallet.pubkey();
+    if effective_mode(&wallet) == BlockhashMode::Nonce {
+        match NONCE_POOL.claim() {
+            Some(nonce_pubkey) => {
+                let processor = BlockhashProcessor::new(app_state.rpc_client.clone()).await?;
+                match processor.get_offchain_blockhash_for(&nonce_pubkey).await {
+                    Ok(nonce_hash) => {
+                        instructions.insert(0, system_instruction::advance_nonce_account(&nonce_pubkey, &wallet));
+                        return Ok((nonce_hash, BlockhashMode::Nonce));
+                    }
+                    Err(e) => {
+                        NONCE_POOL.release(&nonce_pubkey);
+                        record_send_outcome(&wallet, BlockhashMode::Nonce, Some(&e.to_string()));
+                    }
+                }
+            }
+            None => Logger::new("[NONCE-HEALTH] => ".yellow().to_string())
+                .critical(format!("All {} nonce accounts in flight - using a recent blockhash", NONCE_POOL.accounts().len())),
+        }
+    }
+
+    let recent_blockhash = BlockhashProcessor::get_latest_blockhash().await
+        .ok_or_else(|| anyhow!("Failed to get real-time blockhash"))?;
+    Ok((recent_blockhash, BlockhashMode::Recent))
+}
+
+/// Book the outcome of a send (`error` is None on success) and alert when the wallet changes mode
+/// The spent nonce value of a success is refreshed by the send itself, see `nonce_pool::after_send`.
+pub fn record_send_outcome(wallet: &Pubkey, mode: BlockhashMode, error: Option<&str>) {
+    if NONCE_HEALTH.book_send_outcome(&wallet.to_string(), mode, error, &TelegramNotifier) == Some(NonceTransition::SwitchedToRecent) {
+        tokio::spawn(BlockhashProcessor::invalidate_offchain_blockhash());
+    }
+}
+
+/// Repair every pooled nonce account; the first failure keeps the wallet degraded
+async fn repair_nonces(app_state: &AppState) -> Result<()> {
+    if NONCE_POOL.is_empty() {
+        return Err(anyhow!("NONCE_ACCOUNT not configured"));
+    }
+    for nonce_pubkey in NONCE_POOL.accounts() {
+        repair_nonce(app_state, nonce_pubkey).await
+            .map_err(|e| anyhow!("{}: {}", nonce_pubkey, e))?;
+    }
+    Ok(())
+}
+
+/// Re-read the nonce account, verify its authority, refresh the cached hash and land a probe advance
+async fn repair_nonce(app_state: &AppState, nonce_pubkey: &Pubkey) -> Result<()> {
+    let wallet = app_state.wallet.pubkey();
+    let nonce_pubkey = *nonce_pubkey;
+
+    let account = app_state.read_rpc.client().get_account(&nonce_pubkey).await
+        .map_err(|e| anyhow!("Failed to get nonce account: {}", e))?;
+    let data = solana_rpc_client_nonce_utils::data_from_account(&account)
+        .map_err(|e| anyhow!("Failed to parse nonce data: {}", e))?;
+    if data.authority != wallet {
+        return Err(anyhow!("Nonce authority is {}, expected wallet {}", data.authority, wallet));
+    }
+    BlockhashProcessor::set_offchain_blockhash_for(&nonce_pubkey, data.blockhash()).await;
+
+    let probe = Transaction::new_signed_with_payer(
+        &[system_instruction::advance_nonce_account(&nonce_pubkey, &wallet)],
+        Some(&wallet),
+        &[app_state.wallet.as_ref()],
+        data.blockhash(),
+    );
+    app_state.send_rpc.client().send_and_confirm_transaction(&probe).await
+        .map_err(|e| anyhow!("Nonce probe transaction failed: {}", e))?;
+
+    // The probe advanced the nonce
+    BlockhashProcessor::invalidate_offchain_blockhash_for(&nonce_pubkey).await;
+    Ok(())
+}
+
+fn nonce_status_section() -> String {
+    let lines = NONCE_HEALTH.status_lines();
+    let mode = if lines.is_empty() {
+        "Blockhash mode: nonce (no failures)".to_string()
+    } else {
+        format!("Blockhash mode:\n{}", lines.join("\n"))
+    };
+    if NONCE_POOL.accounts().len() > 1 {
+        format!("{}\n{}", mode, NONCE_POOL.status_line())
+    } else {
+        mode
+    }
+}
+
+fn resume_nonce_command(_args: &str) -> String {
+    resume_nonce(&NONCE_HEALTH)
+}
+
+fn resume_nonce(health: &NonceHealth) -> String {
+    let restored: Vec<String> = health
+        .degraded_wallets()
+        .into_iter()
+        .filter(|wallet| health.restore(wallet).is_some())
+        .collect();
+    if restored.is_empty() {
+        "All wallets already use nonce mode".to_string()
+    } else {
+        format!("Nonce mode resumed for {}", restored.join(", "))
+    }
+}
+
+/// Start the background repair task and register /status and /resume-nonce
+/// Repair runs every NONCE_REPAIR_INTERVAL_SECS (default: 30) while the wallet is degraded.
+pub async fn start_nonce_repair(app_state: Arc<AppState>) {
+    telegram_commands::register_status_section("nonce", nonce_status_section);
+    telegram_commands::register_command("/resume-nonce", resume_nonce_command);
+    nonce_pool::set_refresh_rpc(app_state.read_rpc.clone());
+
+    if !BlockhashProcessor::is_offchain_blockhash_available() {
+        return;
+    }
+
+    let interval_secs = std::env::var("NONCE_REPAIR_INTERVAL_SECS")
+        .ok()
+        .and_then(|v| v.parse::<u64>().ok())
+        .unwrap_or(30);
+
+    tokio::spawn(async move {
+        let logger = Logger::new("[NONCE-REPAIR] => ".yellow().to_string());
+        let wallet = app_state.wallet.pubkey().to_string();
+        let mut interval = tokio::time::interval(Duration::from_secs(interval_secs.max(1)));
+
+        loop {
+            interval.tick().await;
+            if NONCE_HEALTH.mode(&wallet) != BlockhashMode::Recent {
+                continue;
+            }
+
+            match repair_nonces(&app_state).await {
+                Ok(()) => {
+                    if NONCE_HEALTH.restore(&wallet).is_some() {
+                        let message = format!("✅ Durable nonce repaired for {} - back to nonce mode", wallet);
+                        logger.critical(message.clone().green().to_string());
+                        TelegramNotifier.notify(message);
+                    }
+                }
+                Err(e) => logger.error(format!("Nonce repair failed: {} (retrying in {}s)", e, interval_secs)),
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::notifier::MemoryNotifier;
+
+    const WALLET: &str = "wallet";
+    const NONCE_ERROR: &str = "Transaction failed: nonce has not been advanced";
+
+    #[test]
+    fn threshold_nonce_failures_switch_to_recent_with_one_alert() {
+        let health = NonceHealth::new(3);
+        let notifier = MemoryNotifier::new();
+        for _ in 0..2 {
+            assert_eq!(health.book_send_outcome(WALLET, BlockhashMode::Nonce, Some(NONCE_ERROR), &notifier), None);
+        }
+        assert_eq!(health.mode(WALLET), BlockhashMode::Nonce);
+        assert_eq!(
+            health.book_send_outcome(WALLET, BlockhashMode::Nonce, Some(NONCE_ERROR), &notifier),
+            Some(NonceTransition::SwitchedToRecent)
+        );
+        assert_eq!(health.mode(WALLET), BlockhashMode::Recent);
+        assert_eq!(health.degraded_reasons(), vec![(WALLET.to_string(), NONCE_ERROR.to_string())]);
+
+        // Recent-mode failures neither count nor alert again
+        health.book_send_outcome(WALLET, BlockhashMode::Recent, Some(NONCE_ERROR), &notifier);
+        assert_eq!(health.record_failure(WALLET, BlockhashMode::Nonce, NONCE_ERROR), None);
+        let messages = notifier.messages();
+        assert_eq!(messages.len(), 1, "{:?}", messages);
+        assert!(messages[0].contains("after 3 consecutive nonce errors"), "{}", messages[0]);
+    }
+
+    #[test]
+    fn other_errors_and_successes_reset_the_count() {
+        let health = NonceHealth::new(3);
+        let notifier = MemoryNotifier::new();
+        health.book_send_outcome(WALLET, BlockhashMode::Nonce, Some(NONCE_ERROR), &notifier);
+        health.book_send_outcome(WALLET, BlockhashMode::Nonce, Some("Blockhash not found"), &notifier);
+        assert_eq!(health.consecutive_failures(WALLET), 2);
+        health.book_send_outcome(WALLET, BlockhashMode::Nonce, Some("custom program error: 0x1772"), &notifier);
+        assert_eq!(health.consecutive_failures(WALLET), 0);
+
+        health.book_send_outcome(WALLET, BlockhashMode::Nonce, Some(NONCE_ERROR), &notifier);
+        health.book_send_outcome(WALLET, BlockhashMode::Nonce, None, &notifier);
+        assert_eq!(health.consecutive_failures(WALLET), 0);
+        for _ in 0..2 {
+            health.book_send_outcome(WALLET, BlockhashMode::Nonce, Some(NONCE_ERROR), &notifier);
+        }
+        assert_eq!(health.mode(WALLET), BlockhashMode::Nonce);
+        assert!(notifier.messages().is_empty());
+    }
+
+    #[test]
+    fn restore_flips_a_degraded_wallet_back() {
+        let health = NonceHealth::new(1);
+        assert_eq!(health.restore(WALLET), None);
+        health.record_failure(WALLET, BlockhashMode::Nonce, NONCE_ERROR);
+        assert_eq!(health.mode(WALLET), BlockhashMode::Recent);
+
+        assert_eq!(health.restore(WALLET), Some(NonceTransition::RestoredNonce));
+        assert_eq!(health.mode(WALLET), BlockhashMode::Nonce);
+        assert_eq!(health.consecutive_failures(WALLET), 0);
+        assert_eq!(health.restore(WALLET), None);
+    }
+
+    #[test]
+    fn resume_nonce_restores_every_degraded_wallet() {
+        let health = NonceHealth::new(1);
+        assert_eq!(resume_nonce(&health), "All wallets already use nonce mode");
+        health.record_failure("a", BlockhashMode::Nonce, NONCE_ERROR);
+        health.record_failure("b", BlockhashMode::Nonce, NONCE_ERROR);
+        health.record_success("c", BlockhashMode::Nonce);
+
+        let reply = resume_nonce(&health);
+        assert!(reply.starts_with("Nonce mode resumed for "), "{}", reply);
+        assert!(reply.contains('a') && reply.contains('b'), "{}", reply);
+        assert!(health.degraded_wallets().is_empty());
+        assert_eq!(resume_nonce(&health), "All wallets already use nonce mode");
+    }
+}
@@ -0,0 +1,177 @@
+use std::str::FromStr;
+use std::time::{Duration, Instant};
+use colored::Colorize;
+use dashmap::DashMap;
+use once_cell::sync::{Lazy, OnceCell};
+use anchor_client::solana_sdk::{hash::Hash, instruction::Instruction, pubkey::Pubkey, system_program};
+
+use crate::common::logger::Logger;
+use crate::services::blockhash_processor::BlockhashProcessor;
+use crate::services::rpc_pool::RpcHandle;
+
+/// A claim still held after this long belongs to a send that never reported back
+const CLAIM_TIMEOUT: Duration = Duration::from_secs(30);
+/// How long the refresh waits for the advanced nonce value to become readable
+const REFRESH_TIMEOUT: Duration = Duration::from_secs(20);
+const REFRESH_POLL_INTERVAL: Duration = Duration::from_millis(400);
+/// `SystemInstruction::AdvanceNonceAccount` as serialized by bincode
+const ADVANCE_NONCE_TAG: [u8; 4] = [4, 0, 0, 0];
+
+/// Nonce accounts from NONCE_ACCOUNTS (comma-separated), or the single NONCE_ACCOUNT
+/// Entries that aren't pubkeys are skipped; duplicates keep their first position.
+pub fn nonce_accounts_from_env() -> Vec<Pubkey> {
+    let raw = std::env::var("NONCE_ACCOUNTS")
+        .ok()
+        .filter(|v| !v.trim().is_empty())
+        .or_else(|| std::env::var("NONCE_ACCOUNT").ok())
+        .unwrap_or_default();
+    let mut accounts: Vec<Pubkey> = Vec::new();
+    for entry in raw.split(',').map(str::trim).filter(|entry| !entry.is_empty()) {
+        match Pubkey::from_str(entry) {
+            Ok(pubkey) if !accounts.contains(&pubkey) => accounts.push(pubkey),
+            Ok(_) => {}
+            Err(e) => Logger::new("[NONCE-POOL] => ".yellow().to_string()).error(format!("Ignoring nonce account '{}': {}", entry, e)),
+        }
+    }
+    accounts
+}
+
+/// Durable nonce accounts with a per-account in-flight claim
+/// A nonce value can only be consumed once, so a claimed account stays unavailable until the
+/// transaction using it has failed to send or its advanced value has been read back.
+pub struct NoncePool {
+    accounts: Vec<Pubkey>,
+    in_flight: DashMap<Pubkey, Instant>,
+}
+
+impl NoncePool {
+    pub fn new(accounts: Vec<Pubkey>) -> Self {
+        Self {
+            accounts,
+            in_flight: DashMap::new(),
+        }
+    }
+
+    pub fn from_env() -> Self {
+        Self::new(nonce_accounts_from_env())
+    }
+
+    pub fn accounts(&self) -> &[Pubkey] {
+        &self.accounts
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.accounts.is_empty()
+    }
+
+    /// Account the single-nonce paths (command mode, repair) read
+    pub fn primary(&self) -> Option<Pubkey> {
+        self.accounts.first().copied()
+    }
+
+    /// Claim the first free account; None when every account is in flight
+    pub fn claim(&self) -> Option<Pubkey> {
+        self.claim_at(Instant::now())
+    }
+
+    fn claim_at(&self, now: Instant) -> Option<Pubkey> {
+        for account in &self.accounts {
+            let mut claimed = false;
+            self.in_flight
+                .entry(*account)
+                .and_modify(|since| {
+                    if now.duration_since(*since) >= CLAIM_TIMEOUT {
+                        *since = now;
+                        claimed = true;
+                    }
+                })
+                .or_insert_with(|| {
+                    claimed = true;
+                    now
+                });
+            if claimed {
+                return Some(*account);
+            }
+        }
+        None
+    }
+
+    pub fn release(&self, account: &Pubkey) {
+        self.in_flight.remove(account);
+    }
+
+    pub fn in_flight(&self) -> usize {
+        self.in_flight.len()
+    }
+
+    pub fn status_line(&self) -> String {
+        format!("Nonce pool: {}/{} accounts in flight", self.in_flight(), self.accounts.len())
+    }
+}
+
+pub static NONCE_POOL: Lazy<NoncePool> = Lazy::new(NoncePool::from_env);
+
+static REFRESH_RPC: OnceCell<RpcHandle> = OnceCell::new();
+
+/// RPC the post-send refresh reads nonce accounts through; set once at startup
+pub fn set_refresh_rpc(read_rpc: RpcHandle) {
+    let _ = REFRESH_RPC.set(read_rpc);
+}
+
+/// Nonce account advanced by the transaction's first instruction, if it is nonce-backed
+pub fn advanced_nonce(instructions: &[Instruction]) -> Option<Pubkey> {
+    let first = instructions.first()?;
+    if first.program_id != system_program::id() || !first.data.starts_with(&ADVANCE_NONCE_TAG) {
+        return None;
+    }
+    first.accounts.first().map(|meta| meta.pubkey)
+}
+
+/// Settle the claim on `nonce` after a send with `used_hash`
+/// A transaction that never left the process frees the account at once. A sent one spawns a
+/// refresh that waits for the account to hold a new value, caches it and only then frees it.
+pub fn after_send(nonce: Pubkey, used_hash: Hash, sent: bool) {
+    if !sent {
+        NONCE_POOL.release(&nonce);
+        return;
+    }
+    let Some(read_rpc) = REFRESH_RPC.get().cloned() else {
+        // No reader yet: the next claim re-reads the account
+        tokio::spawn(async move {
+            BlockhashProcessor::invalidate_offchain_blockhash_for(&nonce).await;
+            NONCE_POOL.release(&nonce);
+        });
+        return;
+    };
+    tokio::spawn(async move {
+        let logger = Logger::new("[NONCE-POOL] => ".yellow().to_string());
+        let started = Instant::now();
+        loop {
+            match read_nonce_hash(&read_rpc, &nonce).await {
+                Ok(hash) if hash != used_hash => {
+                    BlockhashProcessor::set_offchain_blockhash_for(&nonce, hash).await;
+                    break;
+                }
+                Ok(_) => {}
+                Err(e) => logger.error(format!("Nonce refresh for {}: {}", nonce, e)),
+            }
+            if started.elapsed() >= REFRESH_TIMEOUT {
+                // The transaction most likely never landed, so the cached value may still be current;
+                // dropping it makes the next claim read whatever the account holds
+                logger.error(format!("Nonce {} did not advance within {}s", nonce, REFRESH_TIMEOUT.as_secs()));
+                BlockhashProcessor::invalidate_offchain_blockhash_for(&nonce).await;
+                break;
+            }
+            tokio::time::sleep(REFRESH_POLL_INTERVAL).await;
+        }
+        NONCE_POOL.release(&nonce);
+    });
+}
+
+async fn read_nonce_hash(read_rpc: &RpcHandle, nonce: &Pubkey) -> Result<Hash, String> {
+    let account = read_rpc.client().get_account(nonce).await
+        .map_err(|e| format!("failed to read nonce account: {}", e))?;
+    let data = solana_rpc_client_nonce_utils::data_from_account(&account)
+        .map_err(|e| format!("failed to parse nonce data: {}", e))?;
+    Ok(data.blockhash())
+}
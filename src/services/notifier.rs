@@ -0,0 +1,44 @@
+use std::sync::Mutex;
+
+use crate::services::telegram;
+
+/// Fire-and-forget user notifications
+pub trait Notifier: Send + Sync {
+    fn notify(&self, text: String);
+}
+
+/// Queues notifications for the rate-limited Telegram sender
+pub struct TelegramNotifier;
+
+impl Notifier for TelegramNotifier {
+    fn notify(&self, text: String) {
+        if !telegram::is_configured() {
+            return;
+        }
+        let _ = telegram::enqueue(text);
+    }
+}
+
+/// Collects notifications in memory (paper runs and harnesses)
+#[derive(Default)]
+pub struct MemoryNotifier {
+    messages: Mutex<Vec<String>>,
+}
+
+impl MemoryNotifier {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn messages(&self) -> Vec<String> {
+        self.messages.lock().map(|messages| messages.clone()).unwrap_or_default()
+    }
+}
+
+impl Notifier for MemoryNotifier {
+    fn notify(&self, text: String) {
+        if let Ok(mut messages) = self.messages.lock() {
+            messages.push(text);
+        }
+    }
+}
@@ -0,0 +1,298 @@
+use std::io::BufRead;
+use std::time::Duration;
+use colored::Colorize;
+use serde::{Deserialize, Serialize};
+
+use crate::common::logger::Logger;
+use crate::engine::position::Position;
+use crate::engine::position_archive::ClosedPosition;
+use crate::engine::valuation;
+use crate::services::line_writer::LineWriter;
+use crate::services::telegram;
+
+const DAY_SECS: u64 = 24 * 60 * 60;
+
+/// Realized result of one position from its first buy to its last sell
+/// Partial exits from the profit ladder are summed into the one record written when the position closes.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct RoundTrip {
+    pub mint: String,
+    /// "PumpFun", "PumpSwap", ...
+    pub protocol: String,
+    pub opened_at: u64,
+    pub closed_at: u64,
+    pub hold_secs: u64,
+    /// Buys plus the fees, priority fees, tips and rent of every fill
+    pub sol_spent: f64,
+    pub sol_received: f64,
+    pub costs_sol: f64,
+    /// Before costs
+    pub gross_pnl_sol: f64,
+    /// After costs
+    pub net_pnl_sol: f64,
+    pub buys: usize,
+    pub sells: usize,
+}
+
+impl RoundTrip {
+    pub fn from_position(position: &Position) -> Self {
+        let closed = ClosedPosition::from_position(position);
+        Self {
+            mint: closed.mint,
+            protocol: closed.protocol,
+            opened_at: closed.opened_at,
+            closed_at: closed.closed_at,
+            hold_secs: closed.hold_secs,
+            sol_spent: closed.sol_invested + closed.costs_sol,
+            sol_received: closed.sol_realized,
+            costs_sol: closed.costs_sol,
+            gross_pnl_sol: closed.gross_pnl_sol,
+            net_pnl_sol: closed.pnl_sol,
+            buys: closed.lots.len(),
+            sells: closed.exits.len(),
+        }
+    }
+
+    pub fn is_win(&self) -> bool {
+        self.net_pnl_sol > 0.0
+    }
+}
+
+/// PNL_FILE (default: pnl.jsonl)
+pub fn pnl_path() -> String {
+    std::env::var("PNL_FILE").unwrap_or_else(|_| "pnl.jsonl".to_string())
+}
+
+/// Appends a round trip to PNL_FILE for every position that closes
+pub struct PnlTracker {
+    writer: LineWriter,
+    logger: Logger,
+}
+
+impl PnlTracker {
+    pub fn new(path: String) -> Self {
+        let logger = Logger::new("[PNL] => ".green().to_string());
+        Self { writer: LineWriter::new(path, logger.clone()), logger }
+    }
+
+    pub fn from_env() -> Self {
+        Self::new(pnl_path())
+    }
+
+    pub fn record(&self, position: &Position) {
+        match serde_json::to_string(&RoundTrip::from_position(position)) {
+            Ok(line) => self.writer.write_line(line),
+            Err(e) => self.logger.error(format!("Failed to serialize round trip {}: {}", position.mint, e)),
+        }
+    }
+}
+
+/// Every round trip in a PnL file; unreadable lines are skipped
+pub fn read_round_trips(path: &str) -> std::io::Result<Vec<RoundTrip>> {
+    let file = std::fs::File::open(path)?;
+    Ok(std::io::BufReader::new(file)
+        .lines()
+        .map_while(Result::ok)
+        .filter_map(|line| serde_json::from_str::<RoundTrip>(&line).ok())
+        .collect())
+}
+
+/// Aggregate over the round trips that closed in a reporting window
+#[derive(Clone, Debug)]
+pub struct PnlDigest {
+    pub trades: usize,
+    pub wins: usize,
+    pub gross_pnl_sol: f64,
+    pub net_pnl_sol: f64,
+    pub costs_sol: f64,
+    pub best: Option<RoundTrip>,
+    pub worst: Option<RoundTrip>,
+}
+
+impl PnlDigest {
+    pub fn from_round_trips(trips: &[RoundTrip]) -> Self {
+        let by_net = |a: &&RoundTrip, b: &&RoundTrip| a.net_pnl_sol.total_cmp(&b.net_pnl_sol);
+        Self {
+            trades: trips.len(),
+            wins: trips.iter().filter(|trip| trip.is_win()).count(),
+            gross_pnl_sol: trips.iter().map(|trip| trip.gross_pnl_sol).sum(),
+            net_pnl_sol: trips.iter().map(|trip| trip.net_pnl_sol).sum(),
+            costs_sol: trips.iter().map(|trip| trip.costs_sol).sum(),
+            best: trips.iter().max_by(by_net).cloned(),
+            worst: trips.iter().min_by(by_net).cloned(),
+        }
+    }
+
+    pub fn win_rate_pct(&self) -> f64 {
+        if self.trades == 0 {
+            0.0
+        } else {
+            self.wins as f64 / self.trades as f64 * 100.0
+        }
+    }
+
+    /// Telegram digest; USD values only when a SOL price is known
+    pub fn format_text(&self, title: &str, sol_usd: Option<f64>) -> String {
+        let usd = |sol: f64| sol_usd.map(|price| format!(" (${:+.2})", sol * price)).unwrap_or_default();
+        let mut lines = vec![
+            format!("📊 {}", title),
+            format!("Trades: {} | wins: {} ({:.1}%)", self.trades, self.wins, self.win_rate_pct()),
+            format!("Gross PnL: {:+.6} SOL{}", self.gross_pnl_sol, usd(self.gross_pnl_sol)),
+            format!("Costs: {:.6} SOL{}", self.costs_sol, usd(-self.costs_sol)),
+            format!("Net PnL: {:+.6} SOL{}", self.net_pnl_sol, usd(self.net_pnl_sol)),
+        ];
+        if let Some(best) = &self.best {
+            lines.push(format!("Best: {} ({}) {:+.6} SOL{}", best.mint, best.protocol, best.net_pnl_sol, usd(best.net_pnl_sol)));
+        }
+        if let Some(worst) = &self.worst {
+            lines.push(format!("Worst: {} ({}) {:+.6} SOL{}", worst.mint, worst.protocol, worst.net_pnl_sol, usd(worst.net_pnl_sol)));
+        }
+        lines.join("\n")
+    }
+}
+
+/// Digest of the round trips in `path` that closed in the 24h up to `until`
+pub fn daily_digest(path: &str, until: u64) -> std::io::Result<PnlDigest> {
+    let since = until.saturating_sub(DAY_SECS);
+    let trips: Vec<RoundTrip> = read_round_trips(path)?
+        .into_iter()
+        .filter(|trip| trip.closed_at > since && trip.closed_at <= until)
+        .collect();
+    Ok(PnlDigest::from_round_trips(&trips))
+}
+
+/// Seconds from `now` (unix) until the next `hour`:00 UTC; a full day when it is that time exactly
+pub fn secs_until_hour_utc(now: u64, hour: u8) -> u64 {
+    let target = u64::from(hour % 24) * 3600;
+    match (target + DAY_SECS - now % DAY_SECS) % DAY_SECS {
+        0 => DAY_SECS,
+        secs => secs,
+    }
+}
+
+/// Send the last 24h digest to Telegram every day at PNL_REPORT_HOUR_UTC (default: unset, no digest)
+pub fn start_daily_report() {
+    let Some(hour) = std::env::var("PNL_REPORT_HOUR_UTC").ok().and_then(|v| v.parse::<u8>().ok()) else {
+        return;
+    };
+    if hour > 23 {
+        Logger::new("[PNL] => ".green().to_string()).error(format!("Ignoring PNL_REPORT_HOUR_UTC={}: must be 0-23", hour));
+        return;
+    }
+    if !telegram::is_configured() {
+        return;
+    }
+    tokio::spawn(async move {
+        let logger = Logger::new("[PNL] => ".green().to_string());
+        let path = pnl_path();
+        loop {
+            let now = crate::common::clock::now_secs();
+            tokio::time::sleep(Duration::from_secs(secs_until_hour_utc(now, hour))).await;
+            let until = crate::common::clock::now_secs();
+            let digest = match daily_digest(&path, until) {
+                Ok(digest) => digest,
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => PnlDigest::from_round_trips(&[]),
+                Err(e) => {
+                    logger.error(format!("Failed to read {}: {}", path, e));
+                    continue;
+                }
+            };
+            let text = digest.format_text("Daily PnL (last 24h)", valuation::sol_usd());
+            if let Err(e) = telegram::enqueue(text) {
+                logger.error(format!("Failed to send daily PnL digest: {}", e));
+            }
+        }
+    });
+    println!("✅ Daily PnL digest scheduled for {:02}:00 UTC", hour);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::test_fixtures::{book_with, exit, pubkey};
+
+    fn trip(net_pnl_sol: f64, closed_at: u64) -> RoundTrip {
+        RoundTrip {
+            mint: pubkey(),
+            protocol: "PumpFun".to_string(),
+            opened_at: closed_at.saturating_sub(60),
+            closed_at,
+            hold_secs: 60,
+            sol_spent: 1.01,
+            sol_received: 1.0 + net_pnl_sol + 0.01,
+            costs_sol: 0.01,
+            gross_pnl_sol: net_pnl_sol + 0.01,
+            net_pnl_sol,
+            buys: 1,
+            sells: 1,
+        }
+    }
+
+    #[test]
+    fn ladder_exits_sum_into_one_round_trip() {
+        let mint = pubkey();
+        let book = book_with(&mint, 1_000.0, 1.0);
+        book.record_exit(&mint, exit("tier-1", 300.0, 0.6, "Profit tier 1"));
+        book.record_exit(&mint, exit("tier-2", 300.0, 0.9, "Profit tier 2"));
+        let mut position = book.record_exit(&mint, exit("stop", 400.0, 0.3, "stop_loss")).unwrap();
+        position.lots[0].costs_sol = 0.02;
+        position.exits[0].costs_sol = 0.01;
+        let trip = RoundTrip::from_position(&position);
+
+        assert_eq!(trip.mint, mint);
+        assert_eq!((trip.buys, trip.sells), (1, 3));
+        assert!((trip.sol_spent - 1.03).abs() < 1e-12);
+        assert!((trip.sol_received - 1.8).abs() < 1e-12);
+        assert!((trip.costs_sol - 0.03).abs() < 1e-12);
+        assert!((trip.gross_pnl_sol - 0.8).abs() < 1e-12);
+        assert!((trip.net_pnl_sol - 0.77).abs() < 1e-12);
+        assert!(trip.is_win());
+    }
+
+    #[test]
+    fn digest_counts_wins_and_picks_best_and_worst() {
+        let trips = vec![trip(0.5, 10), trip(-0.2, 20), trip(0.1, 30), trip(0.0, 40)];
+        let digest = PnlDigest::from_round_trips(&trips);
+
+        assert_eq!((digest.trades, digest.wins), (4, 2));
+        assert!((digest.win_rate_pct() - 50.0).abs() < 1e-9);
+        assert!((digest.net_pnl_sol - 0.4).abs() < 1e-12);
+        assert!((digest.costs_sol - 0.04).abs() < 1e-12);
+        assert_eq!(digest.best.unwrap().mint, trips[0].mint);
+        assert_eq!(digest.worst.unwrap().mint, trips[1].mint);
+
+        let empty = PnlDigest::from_round_trips(&[]);
+        assert_eq!(empty.win_rate_pct(), 0.0);
+        assert!(empty.best.is_none() && empty.worst.is_none());
+    }
+
+    #[test]
+    fn daily_digest_covers_the_24h_up_to_until() {
+        let path = std::env::temp_dir().join(format!("pnl-{}.jsonl", pubkey()));
+        let until = 10 * DAY_SECS;
+        let trips = [
+            trip(1.0, until - DAY_SECS),
+            trip(2.0, until - DAY_SECS + 1),
+            trip(4.0, until),
+            trip(8.0, until + 1),
+        ];
+        let lines: Vec<String> = trips.iter().map(|trip| serde_json::to_string(trip).unwrap()).collect();
+        std::fs::write(&path, format!("{}\nnot json\n", lines.join("\n"))).unwrap();
+
+        let digest = daily_digest(&path.to_string_lossy(), until).unwrap();
+        assert_eq!(digest.trades, 2);
+        assert!((digest.net_pnl_sol - 6.0).abs() < 1e-12);
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn next_report_hour_wraps_to_tomorrow() {
+        let midnight = 20_000 * DAY_SECS;
+        assert_eq!(secs_until_hour_utc(midnight, 9), 9 * 3600);
+        assert_eq!(secs_until_hour_utc(midnight + 9 * 3600 - 1, 9), 1);
+        assert_eq!(secs_until_hour_utc(midnight + 9 * 3600, 9), DAY_SECS);
+        assert_eq!(secs_until_hour_utc(midnight + 9 * 3600 + 1, 9), DAY_SECS - 1);
+        assert_eq!(secs_until_hour_utc(midnight, 0), DAY_SECS);
+        assert_eq!(secs_until_hour_utc(midnight + 23 * 3600, 0), 3600);
+    }
+}
@@ -0,0 +1,269 @@
+use std::collections::BTreeMap;
+use std::str::FromStr;
+use std::sync::Mutex;
+use std::time::Duration;
+use anchor_client::solana_sdk::pubkey::Pubkey;
+use colored::Colorize;
+use once_cell::sync::Lazy;
+
+use crate::common::{config::AppState, logger::Logger};
+use crate::services::telegram_commands;
+
+/// Slots of fee history the estimate covers; getRecentPrioritizationFees returns the last 150 per call
+const HISTORY_SLOTS: u64 = 450;
+
+/// How the compute unit price of a send is chosen
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum PriorityFeeMode {
+    /// UNIT_PRICE as configured
+    Static,
+    /// This percentile (1-100) of recent fees, e.g. `p95`
+    Percentile(u8),
+    /// The median of recent fees times this factor, e.g. `median*1.5`
+    MedianMultiplier(f64),
+}
+
+impl FromStr for PriorityFeeMode {
+    type Err = String;
+
+    fn from_str(raw: &str) -> Result<Self, String> {
+        let raw = raw.trim().to_ascii_lowercase();
+        if raw == "static" {
+            return Ok(Self::Static);
+        }
+        if let Some(factor) = raw.strip_prefix("median*").or_else(|| raw.strip_prefix("median:")) {
+            let factor: f64 = factor.parse().map_err(|_| format!("bad median multiplier '{}'", factor))?;
+            if !factor.is_finite() || factor <= 0.0 {
+                return Err(format!("median multiplier must be positive, got {}", factor));
+            }
+            return Ok(Self::MedianMultiplier(factor));
+        }
+        if raw == "median" {
+            return Ok(Self::Percentile(50));
+        }
+        if let Some(percentile) = raw.strip_prefix('p') {
+            let percentile: u8 = percentile.parse().map_err(|_| format!("bad percentile '{}'", raw))?;
+            if !(1..=100).contains(&percentile) {
+                return Err(format!("percentile must be 1-100, got {}", percentile));
+            }
+            return Ok(Self::Percentile(percentile));
+        }
+        Err(format!("unknown priority fee mode '{}' (static, p50..p100 or median*<factor>)", raw))
+    }
+}
+
+/// Nearest-rank `percentile` (1-100) of `fees`; None for an empty history
+pub fn percentile(fees: &[u64], percentile: u8) -> Option<u64> {
+    if fees.is_empty() {
+        return None;
+    }
+    let mut sorted = fees.to_vec();
+    sorted.sort_unstable();
+    let rank = (u64::from(percentile.clamp(1, 100)) * sorted.len() as u64).div_ceil(100);
+    Some(sorted[(rank.max(1) - 1) as usize])
+}
+
+/// Priority fees by slot for the programs our transactions write to
+pub struct FeeHistory {
+    by_slot: Mutex<BTreeMap<u64, u64>>,
+}
+
+impl FeeHistory {
+    pub fn new() -> Self {
+        Self { by_slot: Mutex::new(BTreeMap::new()) }
+    }
+
+    /// Merge one getRecentPrioritizationFees answer; overlapping slots from earlier calls are replaced
+    pub fn record(&self, samples: impl IntoIterator<Item = (u64, u64)>) {
+        let Ok(mut by_slot) = self.by_slot.lock() else {
+            return;
+        };
+        by_slot.extend(samples);
+        if let Some(&newest) = by_slot.keys().next_back() {
+            let oldest_kept = newest.saturating_sub(HISTORY_SLOTS);
+            by_slot.retain(|slot, _| *slot > oldest_kept);
+        }
+    }
+
+    pub fn fees(&self) -> Vec<u64> {
+        self.by_slot.lock().map(|by_slot| by_slot.values().copied().collect()).unwrap_or_default()
+    }
+
+    /// Micro-lamports per compute unit for `mode`, capped at `max_unit_price`; None means no estimate yet
+    pub fn unit_price(&self, mode: PriorityFeeMode, max_unit_price: u64) -> Option<u64> {
+        let price = match mode {
+            PriorityFeeMode::Static => crate::core::tx::get_unit_price(),
+            PriorityFeeMode::Percentile(p) => percentile(&self.fees(), p)?,
+            PriorityFeeMode::MedianMultiplier(factor) => (percentile(&self.fees(), 50)? as f64 * factor).round() as u64,
+        };
+        Some(price.min(max_unit_price))
+    }
+}
+
+impl Default for FeeHistory {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+pub static FEE_HISTORY: Lazy<FeeHistory> = Lazy::new(FeeHistory::new);
+
+/// Send-side settings: PRIORITY_FEE_BUY_MODE / PRIORITY_FEE_SELL_MODE (default: unset, no compute budget
+/// instructions) and MAX_UNIT_PRICE (default: 1000000 micro-lamports)
+pub struct PriorityFeeConfig {
+    pub buy_mode: Option<PriorityFeeMode>,
+    pub sell_mode: Option<PriorityFeeMode>,
+    pub max_unit_price: u64,
+}
+
+impl PriorityFeeConfig {
+    pub fn from_env() -> Self {
+        let logger = Logger::new("[PRIORITY-FEE] => ".yellow().to_string());
+        let mode = |key: &str| {
+            let raw = std::env::var(key).ok().filter(|v| !v.trim().is_empty())?;
+            raw.parse::<PriorityFeeMode>()
+                .inspect_err(|e| logger.error(format!("Ignoring {}: {}", key, e)))
+                .ok()
+        };
+        Self {
+            buy_mode: mode("PRIORITY_FEE_BUY_MODE"),
+            sell_mode: mode("PRIORITY_FEE_SELL_MODE"),
+            max_unit_price: std::env::var("MAX_UNIT_PRICE")
+                .ok()
+                .and_then(|v| v.parse::<u64>().ok())
+                .unwrap_or(1_000_000),
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.buy_mode.is_some() || self.sell_mode.is_some()
+    }
+
+    /// Mode that applies to a buy or a sell
+    pub fn mode(&self, is_buy: bool) -> Option<PriorityFeeMode> {
+        if is_buy { self.buy_mode } else { self.sell_mode }
+    }
+}
+
+pub static PRIORITY_FEE_CONFIG: Lazy<PriorityFeeConfig> = Lazy::new(PriorityFeeConfig::from_env);
+
+/// Compute unit price for a send, None when its mode is unset
+/// A percentile mode without samples yet falls back to UNIT_PRICE, still under the cap.
+pub fn unit_price_for(is_buy: bool) -> Option<u64> {
+    let config = &*PRIORITY_FEE_CONFIG;
+    let mode = config.mode(is_buy)?;
+    FEE_HISTORY
+        .unit_price(mode, config.max_unit_price)
+        .or_else(|| FEE_HISTORY.unit_price(PriorityFeeMode::Static, config.max_unit_price))
+}
+
+/// Programs whose write locks set the fees our buys and sells compete on
+fn fee_accounts() -> Vec<Pubkey> {
+    [
+        crate::dex::pump_fun::PUMP_FUN_PROGRAM,
+        crate::engine::event_stream::PUMP_SWAP_PROGRAM,
+        crate::dex::raydium::RAYDIUM_AMM_PROGRAM,
+    ]
+    .iter()
+    .filter_map(|program| Pubkey::from_str(program).ok())
+    .collect()
+}
+
+fn priority_fee_status_section() -> String {
+    let config = &*PRIORITY_FEE_CONFIG;
+    let fees = FEE_HISTORY.fees();
+    let describe = |mode: Option<PriorityFeeMode>, is_buy: bool| match mode {
+        Some(mode) => format!("{:?} -> {}", mode, unit_price_for(is_buy).unwrap_or(0)),
+        None => "off".to_string(),
+    };
+    format!(
+        "Priority fee: buys {}, sells {} (cap {}); {} slots sampled, p50 {} / p95 {}",
+        describe(config.buy_mode, true),
+        describe(config.sell_mode, false),
+        config.max_unit_price,
+        fees.len(),
+        percentile(&fees, 50).unwrap_or(0),
+        percentile(&fees, 95).unwrap_or(0),
+    )
+}
+
+/// Sample getRecentPrioritizationFees every PRIORITY_FEE_INTERVAL_SECS (default: 10) while a fee mode is set
+pub async fn start_priority_fee_sampler(app_state: &AppState) {
+    let config = &*PRIORITY_FEE_CONFIG;
+    if !config.is_enabled() {
+        return;
+    }
+    telegram_commands::register_status_section("priority-fee", priority_fee_status_section);
+
+    let read_rpc = app_state.read_rpc.clone();
+    let accounts = fee_accounts();
+    let interval_secs = std::env::var("PRIORITY_FEE_INTERVAL_SECS")
+        .ok()
+        .and_then(|v| v.parse::<u64>().ok())
+        .unwrap_or(10);
+    tokio::spawn(async move {
+        let logger = Logger::new("[PRIORITY-FEE] => ".yellow().to_string());
+        let mut interval = tokio::time::interval(Duration::from_secs(interval_secs.max(1)));
+        loop {
+            interval.tick().await;
+            match tokio::time::timeout(Duration::from_secs(5), read_rpc.client().get_recent_prioritization_fees(&accounts)).await {
+                Ok(Ok(fees)) => FEE_HISTORY.record(fees.into_iter().map(|fee| (fee.slot, fee.prioritization_fee))),
+                Ok(Err(e)) => logger.error(format!("getRecentPrioritizationFees failed: {}", e)),
+                Err(_) => logger.error("getRecentPrioritizationFees timed out".to_string()),
+            }
+        }
+    });
+    println!("✅ Priority fee sampler started ({}s interval)", interval_secs.max(1));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn percentiles_over_a_synthetic_history() {
+        // 1..=100 micro-lamports, shuffled: the nearest rank is the percentile itself
+        let fees: Vec<u64> = (1..=100).map(|fee| (fee * 37) % 101).collect();
+        assert_eq!(percentile(&fees, 50), Some(50));
+        assert_eq!(percentile(&fees, 75), Some(75));
+        assert_eq!(percentile(&fees, 95), Some(95));
+        assert_eq!(percentile(&fees, 100), Some(100));
+        assert_eq!(percentile(&fees, 1), Some(1));
+
+        assert_eq!(percentile(&[7], 95), Some(7));
+        assert_eq!(percentile(&[10, 20, 30], 50), Some(20));
+        assert_eq!(percentile(&[10, 20, 30], 0), Some(10));
+        assert_eq!(percentile(&[], 50), None);
+    }
+
+    #[test]
+    fn modes_parse() {
+        assert_eq!("static".parse::<PriorityFeeMode>(), Ok(PriorityFeeMode::Static));
+        assert_eq!(" P95 ".parse::<PriorityFeeMode>(), Ok(PriorityFeeMode::Percentile(95)));
+        assert_eq!("median".parse::<PriorityFeeMode>(), Ok(PriorityFeeMode::Percentile(50)));
+        assert_eq!("median*1.5".parse::<PriorityFeeMode>(), Ok(PriorityFeeMode::MedianMultiplier(1.5)));
+        assert_eq!("median:2".parse::<PriorityFeeMode>(), Ok(PriorityFeeMode::MedianMultiplier(2.0)));
+        for bad in ["p0", "p101", "pfast", "median*0", "median*-1", "fastest"] {
+            assert!(bad.parse::<PriorityFeeMode>().is_err(), "{}", bad);
+        }
+    }
+
+    #[test]
+    fn history_keeps_the_latest_slots_and_caps_the_price() {
+        let history = FeeHistory::new();
+        assert_eq!(history.unit_price(PriorityFeeMode::Percentile(95), u64::MAX), None);
+
+        history.record((1..=150).map(|slot| (slot, 1_000_000)));
+        history.record((500..=600).map(|slot| (slot, slot)));
+        // Everything at or before slot 600 - HISTORY_SLOTS is dropped
+        assert_eq!(history.fees().len(), 101);
+        assert_eq!(history.unit_price(PriorityFeeMode::Percentile(100), u64::MAX), Some(600));
+        assert_eq!(history.unit_price(PriorityFeeMode::MedianMultiplier(2.0), u64::MAX), Some(1_100));
+        assert_eq!(history.unit_price(PriorityFeeMode::Percentile(100), 550), Some(550));
+
+        // A later answer for the same slot replaces it
+        history.record([(600, 7)]);
+        assert_eq!(history.fees().len(), 101);
+        assert_eq!(history.unit_price(PriorityFeeMode::Percentile(100), u64::MAX), Some(599));
+    }
+}
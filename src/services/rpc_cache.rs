@@ -0,0 +1,249 @@
+use std::io::{Read, Write};
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::time::SystemTime;
+use colored::Colorize;
+use flate2::{read::GzDecoder, write::GzEncoder, Compression};
+use once_cell::sync::Lazy;
+use solana_sdk::hash::hashv;
+
+use crate::common::logger::Logger;
+
+/// Entry layout: MAGIC, sha256 of the uncompressed payload, gzip(payload)
+const MAGIC: &[u8; 4] = b"RPC1";
+const HEADER_LEN: usize = MAGIC.len() + 32;
+const ENTRY_EXTENSION: &str = "bin";
+
+/// Content-addressed disk cache for RPC responses that never change (transactions by signature, block times)
+/// Entries are keyed by a hash of the request, checksummed, and evicted least-recently-used past the size cap.
+pub struct DiskCache {
+    dir: PathBuf,
+    max_bytes: u64,
+    /// Serializes eviction scans; reads and writes of single entries don't need it
+    eviction: Mutex<()>,
+    logger: Logger,
+}
+
+impl DiskCache {
+    pub fn new(dir: PathBuf, max_bytes: u64) -> std::io::Result<Self> {
+        std::fs::create_dir_all(&dir)?;
+        Ok(Self {
+            dir,
+            max_bytes,
+            eviction: Mutex::new(()),
+            logger: Logger::new("[RPC-CACHE] => ".cyan().to_string()),
+        })
+    }
+
+    /// RPC_CACHE_DIR (default: .rpc_cache) and RPC_CACHE_MAX_MB (default: 256); RPC_CACHE_MAX_MB=0 disables the cache
+    pub fn from_env() -> Option<Self> {
+        let dir = std::env::var("RPC_CACHE_DIR").unwrap_or_else(|_| ".rpc_cache".to_string());
+        let max_mb = std::env::var("RPC_CACHE_MAX_MB")
+            .ok()
+            .and_then(|v| v.parse::<u64>().ok())
+            .unwrap_or(256);
+        if max_mb == 0 {
+            return None;
+        }
+        match Self::new(PathBuf::from(&dir), max_mb * 1024 * 1024) {
+            Ok(cache) => Some(cache),
+            Err(e) => {
+                eprintln!("RPC cache disabled - cannot create {}: {}", dir, e);
+                None
+            }
+        }
+    }
+
+    /// Process-wide cache shared by every RPC gateway
+    pub fn shared() -> Option<Arc<DiskCache>> {
+        static SHARED: Lazy<Option<Arc<DiskCache>>> = Lazy::new(|| DiskCache::from_env().map(Arc::new));
+        SHARED.clone()
+    }
+
+    fn entry_path(&self, request: &str) -> PathBuf {
+        let key = hashv(&[request.as_bytes()]);
+        self.dir.join(format!("{}.{}", hex_of(key.as_ref()), ENTRY_EXTENSION))
+    }
+
+    /// Cached payload for `request`; corrupt or truncated entries are removed and reported as a miss
+    pub fn get(&self, request: &str) -> Option<Vec<u8>> {
+        let path = self.entry_path(request);
+        let raw = std::fs::read(&path).ok()?;
+        match decode_entry(&raw) {
+            Some(payload) => {
+                // Bump the mtime so eviction sees this entry as recently used
+                if let Ok(file) = std::fs::File::options().write(true).open(&path) {
+                    let _ = file.set_modified(SystemTime::now());
+                }
+                Some(payload)
+            }
+            None => {
+                self.logger.error(format!("Discarding corrupt cache entry {}", path.display()));
+                let _ = std::fs::remove_file(&path);
+                None
+            }
+        }
+    }
+
+    /// Store `payload` for `request`, then evict down to the size cap
+    pub fn put(&self, request: &str, payload: &[u8]) {
+        let path = self.entry_path(request);
+        let result = encode_entry(payload).and_then(|entry| {
+            // Write-then-rename so readers never see a half-written entry
+            let tmp = path.with_extension("tmp");
+            std::fs::write(&tmp, entry)?;
+            std::fs::rename(&tmp, &path)
+        });
+        if let Err(e) = result {
+            self.logger.error(format!("Failed to write cache entry {}: {}", path.display(), e));
+            return;
+        }
+        self.evict();
+    }
+
+    /// Remove least-recently-used entries until the total size fits under the cap
+    fn evict(&self) {
+        let Ok(_guard) = self.eviction.lock() else {
+            return;
+        };
+        let Ok(dir) = std::fs::read_dir(&self.dir) else {
+            return;
+        };
+        let mut entries: Vec<(PathBuf, u64, SystemTime)> = dir
+            .filter_map(|entry| entry.ok())
+            .filter(|entry| entry.path().extension().map(|ext| ext == ENTRY_EXTENSION).unwrap_or(false))
+            .filter_map(|entry| {
+                let meta = entry.metadata().ok()?;
+                Some((entry.path(), meta.len(), meta.modified().unwrap_or(SystemTime::UNIX_EPOCH)))
+            })
+            .collect();
+
+        let mut total: u64 = entries.iter().map(|(_, size, _)| size).sum();
+        if total <= self.max_bytes {
+            return;
+        }
+        entries.sort_by_key(|(_, _, modified)| *modified);
+        for (path, size, _) in entries {
+            if total <= self.max_bytes {
+                break;
+            }
+            if std::fs::remove_file(&path).is_ok() {
+                total = total.saturating_sub(size);
+            }
+        }
+    }
+
+    /// Total bytes currently on disk
+    pub fn size_on_disk(&self) -> u64 {
+        std::fs::read_dir(&self.dir)
+            .map(|dir| {
+                dir.filter_map(|entry| entry.ok())
+                    .filter_map(|entry| entry.metadata().ok())
+                    .map(|meta| meta.len())
+                    .sum()
+            })
+            .unwrap_or(0)
+    }
+}
+
+fn hex_of(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+fn encode_entry(payload: &[u8]) -> std::io::Result<Vec<u8>> {
+    let mut entry = Vec::with_capacity(HEADER_LEN + payload.len() / 2);
+    entry.extend_from_slice(MAGIC);
+    entry.extend_from_slice(hashv(&[payload]).as_ref());
+    let mut encoder = GzEncoder::new(entry, Compression::default());
+    encoder.write_all(payload)?;
+    encoder.finish()
+}
+
+/// Payload of a well-formed entry whose checksum matches
+fn decode_entry(raw: &[u8]) -> Option<Vec<u8>> {
+    if raw.len() < HEADER_LEN || &raw[..MAGIC.len()] != MAGIC {
+        return None;
+    }
+    let checksum = &raw[MAGIC.len()..HEADER_LEN];
+    let mut payload = Vec::new();
+    GzDecoder::new(&raw[HEADER_LEN..]).read_to_end(&mut payload).ok()?;
+    if hashv(&[&payload]).as_ref() != checksum {
+        return None;
+    }
+    Some(payload)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    fn cache(max_bytes: u64) -> DiskCache {
+        let dir = std::env::temp_dir().join(format!("rpc-cache-{}", uuid::Uuid::new_v4()));
+        DiskCache::new(dir, max_bytes).unwrap()
+    }
+
+    #[test]
+    fn round_trips_by_request() {
+        let cache = cache(1024 * 1024);
+        assert_eq!(cache.get("getBlockTime:1"), None);
+        cache.put("getBlockTime:1", b"1700000000");
+        cache.put("getBlockTime:2", b"1700000001");
+        assert_eq!(cache.get("getBlockTime:1").as_deref(), Some(&b"1700000000"[..]));
+        assert_eq!(cache.get("getBlockTime:2").as_deref(), Some(&b"1700000001"[..]));
+        let _ = std::fs::remove_dir_all(&cache.dir);
+    }
+
+    #[test]
+    fn corrupt_entries_are_a_miss_and_removed() {
+        let cache = cache(1024 * 1024);
+        cache.put("getTransaction:sig", b"{\"slot\":1}");
+        let path = cache.entry_path("getTransaction:sig");
+        let mut raw = std::fs::read(&path).unwrap();
+        let last = raw.len() - 1;
+        raw[last] ^= 0xff;
+        std::fs::write(&path, &raw).unwrap();
+
+        assert_eq!(cache.get("getTransaction:sig"), None);
+        assert!(!path.exists());
+
+        std::fs::write(&path, b"RPC1 truncated").unwrap();
+        assert_eq!(cache.get("getTransaction:sig"), None);
+        let _ = std::fs::remove_dir_all(&cache.dir);
+    }
+
+    #[test]
+    fn checksum_covers_the_payload() {
+        let entry = encode_entry(b"payload").unwrap();
+        assert_eq!(decode_entry(&entry).as_deref(), Some(&b"payload"[..]));
+
+        let mut wrong_checksum = entry.clone();
+        wrong_checksum[MAGIC.len()] ^= 1;
+        assert_eq!(decode_entry(&wrong_checksum), None);
+        let mut wrong_magic = entry;
+        wrong_magic[0] = b'X';
+        assert_eq!(decode_entry(&wrong_magic), None);
+    }
+
+    #[test]
+    fn eviction_drops_the_least_recently_used() {
+        let payload = vec![7u8; 64];
+        let entry_size = encode_entry(&payload).unwrap().len() as u64;
+        let cache = cache(entry_size * 2);
+        cache.put("old", &payload);
+        cache.put("used", &payload);
+        // Backdate both so the read below is what sets "used" apart, even on coarse mtime filesystems
+        let past = SystemTime::now() - Duration::from_secs(60);
+        for request in ["old", "used"] {
+            std::fs::File::options().write(true).open(cache.entry_path(request)).unwrap().set_modified(past).unwrap();
+        }
+        assert!(cache.get("used").is_some());
+
+        cache.put("new", &payload);
+        assert!(cache.size_on_disk() <= entry_size * 2);
+        assert_eq!(cache.get("old"), None);
+        assert!(cache.get("used").is_some());
+        assert!(cache.get("new").is_some());
+        let _ = std::fs::remove_dir_all(&cache.dir);
+    }
+}
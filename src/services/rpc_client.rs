@@ -1,19 +1,27 @@
 use std::collections::HashMap;
 use std::sync::Arc;
 use anchor_client::solana_client::nonblocking::rpc_client::RpcClient;
-use anchor_client::solana_sdk::pubkey::Pubkey;
+use anchor_client::solana_client::rpc_config::RpcTransactionConfig;
+use anchor_client::solana_sdk::{account::Account as RawAccount, commitment_config::CommitmentConfig, pubkey::Pubkey, signature::Signature};
+use solana_transaction_status::{EncodedConfirmedTransactionWithStatusMeta, UiTransactionEncoding};
 use spl_token_2022::extension::StateWithExtensionsOwned;
 use spl_token_2022::state::{Account, Mint};
-use anyhow::Result;
+use anyhow::{anyhow, Result};
 use colored::Colorize;
 use tokio::sync::RwLock;
 
 use crate::common::logger::Logger;
+use crate::services::rpc_cache::DiskCache;
+
+/// getMultipleAccounts accepts at most this many keys per request
+pub const ACCOUNTS_PER_REQUEST: usize = 100;
 
 /// BatchRpcClient provides optimized methods for fetching multiple accounts in a single RPC call
 pub struct BatchRpcClient {
     rpc_client: Arc<RpcClient>,
     connection_pool: Arc<RwLock<Vec<Arc<RpcClient>>>>,
+    /// Immutable responses (transactions, block times); None when RPC_CACHE_MAX_MB=0
+    disk_cache: Option<Arc<DiskCache>>,
     logger: Logger,
 }
 
@@ -26,6 +34,7 @@ impl BatchRpcClient {
         Self {
             rpc_client,
             connection_pool: Arc::new(RwLock::new(pool)),
+            disk_cache: DiskCache::shared(),
             logger: Logger::new("[BATCH-RPC] => ".cyan().to_string()),
         }
     }
@@ -51,6 +60,81 @@ impl BatchRpcClient {
         pool.push(client);
     }
     
+    async fn cache_get(&self, request: String) -> Option<Vec<u8>> {
+        let cache = self.disk_cache.clone()?;
+        tokio::task::spawn_blocking(move || cache.get(&request)).await.ok().flatten()
+    }
+
+    async fn cache_put(&self, request: String, payload: Vec<u8>) {
+        if let Some(cache) = self.disk_cache.clone() {
+            let _ = tokio::task::spawn_blocking(move || cache.put(&request, &payload)).await;
+        }
+    }
+
+    /// Confirmed transaction by signature, served from the disk cache when possible
+    pub async fn get_transaction_cached(&self, signature: &Signature) -> Result<EncodedConfirmedTransactionWithStatusMeta> {
+        let request = format!("getTransaction:{}:base64:v0", signature);
+        if let Some(payload) = self.cache_get(request.clone()).await {
+            if let Ok(transaction) = serde_json::from_slice(&payload) {
+                return Ok(transaction);
+            }
+        }
+
+        let config = RpcTransactionConfig {
+            encoding: Some(UiTransactionEncoding::Base64),
+            commitment: Some(CommitmentConfig::confirmed()),
+            max_supported_transaction_version: Some(0),
+        };
+        let transaction = self.get_client().await
+            .get_transaction_with_config(signature, config)
+            .await
+            .map_err(|e| anyhow!("Failed to get transaction {}: {}", signature, e))?;
+        if let Ok(payload) = serde_json::to_vec(&transaction) {
+            self.cache_put(request, payload).await;
+        }
+        Ok(transaction)
+    }
+
+    /// Block time (unix seconds) of a slot, served from the disk cache when possible
+    pub async fn get_block_time_cached(&self, slot: u64) -> Result<i64> {
+        let request = format!("getBlockTime:{}", slot);
+        if let Some(payload) = self.cache_get(request.clone()).await {
+            if let Ok(block_time) = serde_json::from_slice(&payload) {
+                return Ok(block_time);
+            }
+        }
+
+        let block_time = self.get_client().await
+            .get_block_time(slot)
+            .await
+            .map_err(|e| anyhow!("Failed to get block time for slot {}: {}", slot, e))?;
+        if let Ok(payload) = serde_json::to_vec(&block_time) {
+            self.cache_put(request, payload).await;
+        }
+        Ok(block_time)
+    }
+
+    /// Raw accounts for any number of keys, ACCOUNTS_PER_REQUEST per getMultipleAccounts call
+    /// Keys that don't exist are absent from the map. A failed request is logged and its keys are
+    /// left out, so one bad chunk doesn't hide the rest.
+    pub async fn get_accounts_chunked(&self, pubkeys: &[Pubkey]) -> HashMap<Pubkey, RawAccount> {
+        let mut result = HashMap::with_capacity(pubkeys.len());
+        let client = self.get_client().await;
+        for chunk in pubkeys.chunks(ACCOUNTS_PER_REQUEST) {
+            match client.get_multiple_accounts(chunk).await {
+                Ok(accounts) => {
+                    for (pubkey, account) in chunk.iter().zip(accounts) {
+                        if let Some(account) = account {
+                            result.insert(*pubkey, account);
+                        }
+                    }
+                }
+                Err(e) => self.logger.error(format!("Failed to fetch {} accounts: {}", chunk.len(), e).red().to_string()),
+            }
+        }
+        result
+    }
+
     /// Get multiple token accounts in a single RPC call
     pub async fn get_multiple_token_accounts(
         &self, 
@@ -137,4 +221,86 @@ impl BatchRpcClient {
 /// Create a batch RPC client from an existing RPC client
 pub fn create_batch_client(rpc_client: Arc<RpcClient>) -> BatchRpcClient {
     BatchRpcClient::new(rpc_client)
-} 
\ No newline at end of file
+} 
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use anchor_client::solana_client::client_error::Result as ClientResult;
+    use anchor_client::solana_client::rpc_client::RpcClientConfig;
+    use anchor_client::solana_client::rpc_request::RpcRequest;
+    use anchor_client::solana_client::rpc_sender::{RpcSender, RpcTransportStats};
+    use async_trait::async_trait;
+    use serde_json::{json, Value};
+
+    /// Answers getMultipleAccounts from `accounts`, counting the requests and the widest one
+    struct CountingSender {
+        accounts: HashMap<Pubkey, RawAccount>,
+        calls: Arc<AtomicUsize>,
+        widest: Arc<AtomicUsize>,
+    }
+
+    #[async_trait]
+    impl RpcSender for CountingSender {
+        async fn send(&self, request: RpcRequest, params: Value) -> ClientResult<Value> {
+            assert_eq!(request, RpcRequest::GetMultipleAccounts);
+            let keys = params[0].as_array().expect("a key list");
+            self.calls.fetch_add(1, Ordering::SeqCst);
+            self.widest.fetch_max(keys.len(), Ordering::SeqCst);
+            let value: Vec<Value> = keys
+                .iter()
+                .map(|key| {
+                    let key: Pubkey = key.as_str().unwrap().parse().unwrap();
+                    match self.accounts.get(&key) {
+                        Some(account) => json!({
+                            "lamports": account.lamports,
+                            "data": [base64::encode(&account.data), "base64"],
+                            "owner": account.owner.to_string(),
+                            "executable": false,
+                            "rentEpoch": 0,
+                            "space": account.data.len(),
+                        }),
+                        None => Value::Null,
+                    }
+                })
+                .collect();
+            Ok(json!({ "context": { "slot": 1 }, "value": value }))
+        }
+
+        fn get_transport_stats(&self) -> RpcTransportStats {
+            RpcTransportStats::default()
+        }
+
+        fn url(&self) -> String {
+            "counting-sender".to_string()
+        }
+    }
+
+    #[tokio::test]
+    async fn accounts_are_read_a_hundred_keys_per_request() {
+        let keys: Vec<Pubkey> = (0..250).map(|_| Pubkey::new_unique()).collect();
+        // Every third key has no account
+        let accounts: HashMap<Pubkey, RawAccount> = keys
+            .iter()
+            .enumerate()
+            .filter(|(index, _)| index % 3 != 0)
+            .map(|(index, key)| (*key, RawAccount { lamports: index as u64, data: vec![index as u8; 8], owner: spl_token::ID, executable: false, rent_epoch: 0 }))
+            .collect();
+        let calls = Arc::new(AtomicUsize::new(0));
+        let widest = Arc::new(AtomicUsize::new(0));
+        let sender = CountingSender { accounts: accounts.clone(), calls: calls.clone(), widest: widest.clone() };
+        let client = BatchRpcClient::new(Arc::new(RpcClient::new_sender(sender, RpcClientConfig::with_commitment(CommitmentConfig::confirmed()))));
+
+        let fetched = client.get_accounts_chunked(&keys).await;
+        assert_eq!(calls.load(Ordering::SeqCst), 3, "250 keys take three requests");
+        assert_eq!(widest.load(Ordering::SeqCst), ACCOUNTS_PER_REQUEST);
+        assert_eq!(fetched.len(), accounts.len());
+        for (key, account) in &accounts {
+            assert_eq!(fetched[key].data, account.data);
+            assert_eq!(fetched[key].lamports, account.lamports);
+        }
+
+        assert!(client.get_accounts_chunked(&[]).await.is_empty());
+        assert_eq!(calls.load(Ordering::SeqCst), 3, "no keys, no request");
+    }
+}
@@ -0,0 +1,302 @@
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use anchor_client::solana_client::{nonblocking::rpc_client::RpcClient, rpc_client::RpcClient as BlockingRpcClient};
+use anchor_client::solana_sdk::commitment_config::CommitmentConfig;
+use colored::Colorize;
+
+use crate::common::{config::AppState, logger::Logger};
+use crate::error::classify::ErrorClass;
+use crate::services::{session_report, telegram_commands};
+
+const RPC_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// One endpoint of a pool with both client flavours
+pub struct PoolEndpoint {
+    pub url: String,
+    pub client: Arc<RpcClient>,
+    pub blocking_client: Arc<BlockingRpcClient>,
+    healthy: AtomicBool,
+}
+
+impl PoolEndpoint {
+    pub fn new(url: String) -> Self {
+        Self {
+            client: Arc::new(RpcClient::new_with_timeout_and_commitment(url.clone(), RPC_TIMEOUT, CommitmentConfig::processed())),
+            blocking_client: Arc::new(BlockingRpcClient::new_with_timeout_and_commitment(url.clone(), RPC_TIMEOUT, CommitmentConfig::processed())),
+            url,
+            healthy: AtomicBool::new(true),
+        }
+    }
+
+    pub fn is_healthy(&self) -> bool {
+        self.healthy.load(Ordering::Relaxed)
+    }
+}
+
+/// Round-robin group of endpoints serving one purpose ("send" or "read")
+pub struct RpcPool {
+    label: &'static str,
+    endpoints: Vec<PoolEndpoint>,
+    cursor: AtomicUsize,
+    /// Clients handed out for this pool's own purpose
+    handouts: AtomicU64,
+    /// Clients handed out to the other purpose because its pool was fully unhealthy
+    fallback_handouts: AtomicU64,
+}
+
+impl RpcPool {
+    /// `urls` must not be empty
+    pub fn new(label: &'static str, urls: Vec<String>) -> Self {
+        assert!(!urls.is_empty(), "RPC pool '{}' needs at least one endpoint", label);
+        Self {
+            label,
+            endpoints: urls.into_iter().map(PoolEndpoint::new).collect(),
+            cursor: AtomicUsize::new(0),
+            handouts: AtomicU64::new(0),
+            fallback_handouts: AtomicU64::new(0),
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        self.label
+    }
+
+    pub fn endpoints(&self) -> &[PoolEndpoint] {
+        &self.endpoints
+    }
+
+    pub fn primary(&self) -> &PoolEndpoint {
+        &self.endpoints[0]
+    }
+
+    pub fn is_healthy(&self) -> bool {
+        self.endpoints.iter().any(|endpoint| endpoint.is_healthy())
+    }
+
+    pub fn set_healthy(&self, index: usize, healthy: bool) {
+        if let Some(endpoint) = self.endpoints.get(index) {
+            endpoint.healthy.store(healthy, Ordering::Relaxed);
+        }
+    }
+
+    /// Next healthy endpoint in round-robin order, or the next one at all when none is healthy
+    fn next_endpoint(&self) -> &PoolEndpoint {
+        let len = self.endpoints.len();
+        let start = self.cursor.fetch_add(1, Ordering::Relaxed);
+        (0..len)
+            .map(|offset| &self.endpoints[(start + offset) % len])
+            .find(|endpoint| endpoint.is_healthy())
+            .unwrap_or(&self.endpoints[start % len])
+    }
+
+    fn status_line(&self) -> String {
+        let healthy = self.endpoints.iter().filter(|endpoint| endpoint.is_healthy()).count();
+        format!(
+            "{} pool: {}/{} healthy, {} handouts, {} fallback handouts",
+            self.label,
+            healthy,
+            self.endpoints.len(),
+            self.handouts.load(Ordering::Relaxed),
+            self.fallback_handouts.load(Ordering::Relaxed)
+        )
+    }
+}
+
+/// RPC access for one purpose; falls back to the other pool only while its own pool is fully unhealthy
+#[derive(Clone)]
+pub struct RpcHandle {
+    primary: Arc<RpcPool>,
+    fallback: Arc<RpcPool>,
+}
+
+impl RpcHandle {
+    pub fn new(primary: Arc<RpcPool>, fallback: Arc<RpcPool>) -> Self {
+        Self { primary, fallback }
+    }
+
+    pub fn pool(&self) -> &Arc<RpcPool> {
+        &self.primary
+    }
+
+    fn endpoint(&self) -> &PoolEndpoint {
+        if !self.primary.is_healthy() && self.fallback.is_healthy() && !Arc::ptr_eq(&self.primary, &self.fallback) {
+            self.fallback.fallback_handouts.fetch_add(1, Ordering::Relaxed);
+            return self.fallback.next_endpoint();
+        }
+        self.primary.handouts.fetch_add(1, Ordering::Relaxed);
+        self.primary.next_endpoint()
+    }
+
+    pub fn client(&self) -> Arc<RpcClient> {
+        self.endpoint().client.clone()
+    }
+
+    pub fn blocking_client(&self) -> Arc<BlockingRpcClient> {
+        self.endpoint().blocking_client.clone()
+    }
+}
+
+/// Comma-separated endpoint list from `key`, None if unset or empty
+fn urls_from_env(key: &str) -> Option<Vec<String>> {
+    let urls: Vec<String> = std::env::var(key)
+        .ok()?
+        .split(',')
+        .map(|url| url.trim().to_string())
+        .filter(|url| !url.is_empty())
+        .collect();
+    (!urls.is_empty()).then_some(urls)
+}
+
+/// (send, read) pools from SEND_RPC_HTTP / READ_RPC_HTTP, each defaulting to RPC_HTTP
+/// With neither set both are the same pool, so routing is a no-op.
+pub fn pools_from_env() -> (Arc<RpcPool>, Arc<RpcPool>) {
+    let send_urls = urls_from_env("SEND_RPC_HTTP");
+    let read_urls = urls_from_env("READ_RPC_HTTP");
+    let default_urls = || urls_from_env("RPC_HTTP").unwrap_or_else(|| vec![crate::common::config::import_env_var("RPC_HTTP")]);
+
+    match (send_urls, read_urls) {
+        (None, None) => {
+            let shared = Arc::new(RpcPool::new("shared", default_urls()));
+            (shared.clone(), shared)
+        }
+        (send, read) => {
+            let send = Arc::new(RpcPool::new("send", send.unwrap_or_else(default_urls)));
+            let read = Arc::new(RpcPool::new("read", read.unwrap_or_else(default_urls)));
+            (send, read)
+        }
+    }
+}
+
+fn rpc_status_section() -> String {
+    let Some(pools) = STATUS_POOLS.get() else {
+        return "RPC: pools not initialized".to_string();
+    };
+    let mut lines = vec!["RPC:".to_string(), pools.0.status_line()];
+    if !Arc::ptr_eq(&pools.0, &pools.1) {
+        lines.push(pools.1.status_line());
+    }
+    lines.join("\n")
+}
+
+static STATUS_POOLS: once_cell::sync::OnceCell<(Arc<RpcPool>, Arc<RpcPool>)> = once_cell::sync::OnceCell::new();
+
+/// The pools the health probe watches, once started; one entry when send and read share a pool
+pub fn status_pools() -> Vec<Arc<RpcPool>> {
+    match STATUS_POOLS.get() {
+        Some((send, read)) if Arc::ptr_eq(send, read) => vec![send.clone()],
+        Some((send, read)) => vec![send.clone(), read.clone()],
+        None => Vec::new(),
+    }
+}
+
+/// Probe every endpoint every RPC_HEALTH_INTERVAL_SECS (default: 10) and register the "rpc" /status section
+/// An endpoint is unhealthy while getSlot fails or takes longer than 3s.
+pub async fn start_rpc_health_probe(app_state: &AppState) {
+    let send = app_state.send_rpc.pool().clone();
+    let read = app_state.read_rpc.pool().clone();
+    let _ = STATUS_POOLS.set((send.clone(), read.clone()));
+    telegram_commands::register_status_section("rpc", rpc_status_section);
+
+    let interval_secs = std::env::var("RPC_HEALTH_INTERVAL_SECS")
+        .ok()
+        .and_then(|v| v.parse::<u64>().ok())
+        .unwrap_or(10);
+    let mut pools = vec![send];
+    if !Arc::ptr_eq(&pools[0], &read) {
+        pools.push(read);
+    }
+
+    tokio::spawn(async move {
+        let logger = Logger::new("[RPC-HEALTH] => ".cyan().to_string());
+        let mut interval = tokio::time::interval(Duration::from_secs(interval_secs.max(1)));
+        loop {
+            interval.tick().await;
+            for pool in &pools {
+                for (index, endpoint) in pool.endpoints().iter().enumerate() {
+                    let healthy = matches!(
+                        tokio::time::timeout(Duration::from_secs(3), endpoint.client.get_slot()).await,
+                        Ok(Ok(_))
+                    );
+                    if !healthy {
+                        session_report::record_error(ErrorClass::Network);
+                    }
+                    if healthy != endpoint.is_healthy() {
+                        let state = if healthy { "healthy again" } else { "unhealthy" };
+                        logger.error(format!("{} pool endpoint {} is {}", pool.label(), endpoint.url, state));
+                    }
+                    pool.set_healthy(index, healthy);
+                }
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pool(label: &'static str, urls: &[&str]) -> Arc<RpcPool> {
+        Arc::new(RpcPool::new(label, urls.iter().map(|url| url.to_string()).collect()))
+    }
+
+    fn next_url(pool: &RpcPool) -> String {
+        pool.next_endpoint().url.clone()
+    }
+
+    #[test]
+    fn round_robin_skips_unhealthy_endpoints() {
+        let pool = pool("read", &["http://a", "http://b", "http://c"]);
+        assert_eq!([next_url(&pool), next_url(&pool), next_url(&pool)], ["http://a", "http://b", "http://c"]);
+
+        pool.set_healthy(1, false);
+        let urls: Vec<String> = (0..4).map(|_| next_url(&pool)).collect();
+        assert!(!urls.contains(&"http://b".to_string()), "{:?}", urls);
+
+        // With none healthy the rotation continues rather than failing
+        (0..3).for_each(|index| pool.set_healthy(index, false));
+        assert!(!pool.is_healthy());
+        let urls: std::collections::HashSet<String> = (0..3).map(|_| next_url(&pool)).collect();
+        assert_eq!(urls.len(), 3);
+    }
+
+    #[test]
+    fn handle_falls_back_only_while_its_pool_is_down() {
+        let send = pool("send", &["http://send"]);
+        let read = pool("read", &["http://read"]);
+        let handle = RpcHandle::new(send.clone(), read.clone());
+        assert_eq!(handle.endpoint().url, "http://send");
+
+        send.set_healthy(0, false);
+        assert_eq!(handle.endpoint().url, "http://read");
+        // Both down: stay on our own pool
+        read.set_healthy(0, false);
+        assert_eq!(handle.endpoint().url, "http://send");
+
+        send.set_healthy(0, true);
+        assert_eq!(handle.endpoint().url, "http://send");
+        assert_eq!(send.handouts.load(Ordering::Relaxed), 3);
+        assert_eq!(read.fallback_handouts.load(Ordering::Relaxed), 1);
+        assert!(send.status_line().starts_with("send pool: 1/1 healthy, 3 handouts"));
+    }
+
+    #[test]
+    fn shared_pool_never_falls_back_to_itself() {
+        let shared = pool("shared", &["http://only"]);
+        let handle = RpcHandle::new(shared.clone(), shared.clone());
+        shared.set_healthy(0, false);
+        assert_eq!(handle.endpoint().url, "http://only");
+        assert_eq!(shared.fallback_handouts.load(Ordering::Relaxed), 0);
+    }
+
+    #[test]
+    fn url_lists_are_trimmed_and_empty_entries_dropped() {
+        let key = "RPC_POOL_TEST_URLS";
+        std::env::set_var(key, " http://a , ,http://b,");
+        assert_eq!(urls_from_env(key), Some(vec!["http://a".to_string(), "http://b".to_string()]));
+        std::env::set_var(key, " , ");
+        assert_eq!(urls_from_env(key), None);
+        std::env::remove_var(key);
+        assert_eq!(urls_from_env(key), None);
+    }
+}
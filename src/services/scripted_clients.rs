@@ -0,0 +1,297 @@
+use std::collections::VecDeque;
+use std::sync::Mutex;
+use std::time::Duration;
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use anchor_client::solana_sdk::{
+    pubkey::Pubkey,
+    signature::{Keypair, Signature},
+    transaction::VersionedTransaction,
+};
+
+use crate::error::classify::ErrorClass;
+use crate::error::ClientError;
+use crate::services::jupiter_api::{QuoteResponse, SwapAggregator, SwapMode, SwapOptions};
+use crate::services::zeroslot::TipLandingClient;
+
+/// One scripted response: after `delay`, either the value or an error message
+pub struct Step<T> {
+    pub delay: Duration,
+    pub result: Result<T, String>,
+}
+
+impl<T> Step<T> {
+    pub fn ok(value: T) -> Self {
+        Self { delay: Duration::ZERO, result: Ok(value) }
+    }
+
+    pub fn err(message: impl Into<String>) -> Self {
+        Self { delay: Duration::ZERO, result: Err(message.into()) }
+    }
+
+    /// An error that `classify_error` maps back to `class`
+    pub fn err_class(class: ErrorClass) -> Self {
+        Self::err(sample_message(class))
+    }
+
+    pub fn delayed(mut self, delay: Duration) -> Self {
+        self.delay = delay;
+        self
+    }
+}
+
+/// Representative message for each error class
+pub fn sample_message(class: ErrorClass) -> &'static str {
+    match class {
+        ErrorClass::AccountFrozen => "Error: Account is frozen",
+        ErrorClass::Nonce => "Transaction nonce has not been advanced",
+        ErrorClass::BlockhashNotFound => "Blockhash not found",
+        ErrorClass::InsufficientFunds => "Insufficient funds for fee",
+        ErrorClass::SlippageExceeded => "custom program error: 0x1772 (slippage)",
+        ErrorClass::IocCancelled => "Program L2TExMFKdjpN9kozasaurPirfHy9P8sbXoAN1qA3S95 failed: AssertionFailed",
+        ErrorClass::Network => "operation timed out",
+        ErrorClass::Other => "scripted failure",
+    }
+}
+
+/// Steps served in order; an exhausted script fails every further call
+struct Script<T> {
+    steps: Mutex<VecDeque<Step<T>>>,
+}
+
+impl<T> Script<T> {
+    fn new() -> Self {
+        Self { steps: Mutex::new(VecDeque::new()) }
+    }
+
+    fn push(&self, step: Step<T>) {
+        if let Ok(mut steps) = self.steps.lock() {
+            steps.push_back(step);
+        }
+    }
+
+    async fn next(&self, call: &str) -> Result<T, String> {
+        let step = self.steps.lock().ok().and_then(|mut steps| steps.pop_front());
+        let Some(step) = step else {
+            return Err(format!("{}: script exhausted", call));
+        };
+        if !step.delay.is_zero() {
+            tokio::time::sleep(step.delay).await;
+        }
+        step.result
+    }
+}
+
+/// Names of the calls made, in order
+#[derive(Default)]
+struct CallLog {
+    calls: Mutex<Vec<String>>,
+}
+
+impl CallLog {
+    fn record(&self, call: String) {
+        if let Ok(mut calls) = self.calls.lock() {
+            calls.push(call);
+        }
+    }
+
+    fn calls(&self) -> Vec<String> {
+        self.calls.lock().map(|calls| calls.clone()).unwrap_or_default()
+    }
+}
+
+/// Tip landing double: each send takes the next scripted step (harnesses)
+pub struct ScriptedTipClient {
+    sends: Script<Signature>,
+    log: CallLog,
+}
+
+impl ScriptedTipClient {
+    pub fn new() -> Self {
+        Self { sends: Script::new(), log: CallLog::default() }
+    }
+
+    pub fn then(self, step: Step<Signature>) -> Self {
+        self.sends.push(step);
+        self
+    }
+
+    /// One entry per send_transaction call
+    pub fn calls(&self) -> Vec<String> {
+        self.log.calls()
+    }
+}
+
+impl Default for ScriptedTipClient {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[async_trait]
+impl TipLandingClient for ScriptedTipClient {
+    async fn warm_up(&self) -> Result<()> {
+        Ok(())
+    }
+
+    async fn send_transaction(&self, transaction: &VersionedTransaction) -> Result<Signature, ClientError> {
+        let signature = transaction.signatures.first().copied().unwrap_or_default();
+        self.log.record(format!("send_transaction {}", signature));
+        self.sends.next("send_transaction").await.map_err(ClientError::Other)
+    }
+}
+
+fn destination_label(options: &SwapOptions) -> String {
+    options.destination_token_account.map(|account| account.to_string()).unwrap_or_else(|| "native".to_string())
+}
+
+/// Swap aggregator double with a separate script per call kind (harnesses)
+pub struct ScriptedSwapAggregator {
+    quotes: Script<QuoteResponse>,
+    swap_transactions: Script<VersionedTransaction>,
+    sells: Script<String>,
+    buys: Script<String>,
+    log: CallLog,
+}
+
+impl ScriptedSwapAggregator {
+    pub fn new() -> Self {
+        Self {
+            quotes: Script::new(),
+            swap_transactions: Script::new(),
+            sells: Script::new(),
+            buys: Script::new(),
+            log: CallLog::default(),
+        }
+    }
+
+    pub fn then_quote(self, step: Step<QuoteResponse>) -> Self {
+        self.quotes.push(step);
+        self
+    }
+
+    pub fn then_swap_transaction(self, step: Step<VersionedTransaction>) -> Self {
+        self.swap_transactions.push(step);
+        self
+    }
+
+    pub fn then_sell(self, step: Step<String>) -> Self {
+        self.sells.push(step);
+        self
+    }
+
+    pub fn then_buy(self, step: Step<String>) -> Self {
+        self.buys.push(step);
+        self
+    }
+
+    /// e.g. "get_quote <in> <out> <amount>", "sell <mint> <amount> -> <destination or native>"
+    pub fn calls(&self) -> Vec<String> {
+        self.log.calls()
+    }
+}
+
+impl Default for ScriptedSwapAggregator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[async_trait]
+impl SwapAggregator for ScriptedSwapAggregator {
+    async fn warm_up(&self) -> Result<()> {
+        Ok(())
+    }
+
+    async fn get_quote(&self, input_mint: &str, output_mint: &str, amount: u64, _slippage_bps: u64, _swap_mode: SwapMode) -> Result<QuoteResponse> {
+        self.log.record(format!("get_quote {} {} {}", input_mint, output_mint, amount));
+        self.quotes.next("get_quote").await.map_err(|e| anyhow!(e))
+    }
+
+    async fn get_swap_transaction(&self, _quote: QuoteResponse, user_public_key: &Pubkey, options: &SwapOptions) -> Result<VersionedTransaction> {
+        self.log.record(format!("get_swap_transaction {} -> {}", user_public_key, destination_label(options)));
+        self.swap_transactions.next("get_swap_transaction").await.map_err(|e| anyhow!(e))
+    }
+
+    async fn sell(&self, token_mint: &str, token_amount: u64, _slippage_bps: u64, _swap_mode: SwapMode, _keypair: &Keypair, options: &SwapOptions) -> Result<String> {
+        self.log.record(format!("sell {} {} -> {}", token_mint, token_amount, destination_label(options)));
+        self.sells.next("sell").await.map_err(|e| anyhow!(e))
+    }
+
+    async fn buy(&self, token_mint: &str, sol_lamports: u64, _slippage_bps: u64, _keypair: &Keypair) -> Result<String> {
+        self.log.record(format!("buy {} {}", token_mint, sol_lamports));
+        self.buys.next("buy").await.map_err(|e| anyhow!(e))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::classify::classify_error;
+
+    #[test]
+    fn sample_messages_classify_back_to_their_class() {
+        for class in [
+            ErrorClass::AccountFrozen,
+            ErrorClass::Nonce,
+            ErrorClass::BlockhashNotFound,
+            ErrorClass::InsufficientFunds,
+            ErrorClass::SlippageExceeded,
+            ErrorClass::IocCancelled,
+            ErrorClass::Network,
+            ErrorClass::Other,
+        ] {
+            assert_eq!(classify_error(sample_message(class)), class, "{}", sample_message(class));
+        }
+    }
+
+    #[tokio::test]
+    async fn tip_client_serves_steps_in_order_then_fails() {
+        let signature = Signature::new_unique();
+        let client: Box<dyn TipLandingClient> = Box::new(
+            ScriptedTipClient::new()
+                .then(Step::err_class(ErrorClass::BlockhashNotFound))
+                .then(Step::ok(signature)),
+        );
+        let transaction = VersionedTransaction::default();
+
+        let first = client.send_transaction(&transaction).await.unwrap_err();
+        assert_eq!(classify_error(&first.to_string()), ErrorClass::BlockhashNotFound);
+        assert_eq!(client.send_transaction_via(&transaction).await.unwrap(), (signature, String::new()));
+        let exhausted = client.send_transaction(&transaction).await.unwrap_err();
+        assert!(exhausted.to_string().contains("script exhausted"), "{}", exhausted);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn delayed_steps_wait_before_answering() {
+        let client = ScriptedTipClient::new().then(Step::ok(Signature::new_unique()).delayed(Duration::from_secs(3)));
+        let started = tokio::time::Instant::now();
+        client.send_transaction(&VersionedTransaction::default()).await.unwrap();
+        assert_eq!(started.elapsed(), Duration::from_secs(3));
+        assert_eq!(client.calls().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn aggregator_scripts_each_call_kind_separately() {
+        let destination = Pubkey::new_unique();
+        let aggregator = ScriptedSwapAggregator::new()
+            .then_sell(Step::err("route not found"))
+            .then_sell(Step::ok("sell-sig".to_string()))
+            .then_buy(Step::ok("buy-sig".to_string()));
+        let keypair = Keypair::new();
+        let native = SwapOptions::default();
+        let wrapped = SwapOptions { destination_token_account: Some(destination), ..Default::default() };
+
+        assert!(aggregator.sell("MINT", 10, 100, SwapMode::ExactIn, &keypair, &native).await.is_err());
+        assert_eq!(aggregator.buy("MINT", 5, 100, &keypair).await.unwrap(), "buy-sig");
+        assert_eq!(aggregator.sell("MINT", 10, 100, SwapMode::ExactIn, &keypair, &wrapped).await.unwrap(), "sell-sig");
+        assert!(aggregator.get_quote("A", "B", 1, 100, SwapMode::ExactIn).await.is_err());
+
+        assert_eq!(aggregator.calls(), vec![
+            "sell MINT 10 -> native".to_string(),
+            "buy MINT 5".to_string(),
+            format!("sell MINT 10 -> {}", destination),
+            "get_quote A B 1".to_string(),
+        ]);
+    }
+}
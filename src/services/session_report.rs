@@ -0,0 +1,420 @@
+use std::collections::{BTreeMap, VecDeque};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use once_cell::sync::Lazy;
+use serde::Serialize;
+
+use crate::common::clock;
+use crate::engine::executor::ExecutionReport;
+use crate::engine::position::{Position, PositionBook, PositionState, ValuationSource, POSITION_BOOK};
+use crate::engine::valuation;
+use crate::engine::position_archive::ArchiveConfig;
+use crate::engine::transaction_parser::DexType;
+use crate::error::classify::ErrorClass;
+use crate::services::telegram_chunks::{self, ChunkFormat};
+use crate::services::slippage::{self, SlippageSample, SlippageStats};
+use crate::services::trade_costs::TradeCosts;
+use crate::services::ledger::{self, LedgerEntry};
+use crate::services::telegram;
+
+/// Random id of this run, stamped into every ledger entry so sessions can be joined later
+static SESSION_ID: Lazy<String> = Lazy::new(|| uuid::Uuid::new_v4().to_string());
+static STARTED_AT: Lazy<(Instant, u64)> = Lazy::new(|| (Instant::now(), unix_now()));
+static COUNTERS: Lazy<Mutex<SessionCounters>> = Lazy::new(|| Mutex::new(SessionCounters::default()));
+static RECENT: Lazy<Mutex<VecDeque<LedgerEntry>>> = Lazy::new(|| Mutex::new(VecDeque::new()));
+/// Fills `recent_fills` keeps
+const RECENT_FILLS: usize = 50;
+static REJECTIONS: Lazy<Mutex<VecDeque<Rejection>>> = Lazy::new(|| Mutex::new(VecDeque::new()));
+/// Rejections `get_recent_rejections` keeps
+const RECENT_REJECTIONS: usize = 50;
+/// Set by the first `finish_session`; later exit paths don't report twice
+static FINISHED: AtomicBool = AtomicBool::new(false);
+
+fn unix_now() -> u64 {
+    clock::now_secs()
+}
+
+/// Fix the session id and start time; call once at startup
+pub fn init() {
+    Lazy::force(&STARTED_AT);
+    Lazy::force(&SESSION_ID);
+}
+
+pub fn session_id() -> &'static str {
+    &SESSION_ID
+}
+
+/// Activity counted since startup
+#[derive(Clone, Debug, Default, Serialize)]
+pub struct SessionCounters {
+    pub events_by_dex: BTreeMap<String, u64>,
+    pub buys: u64,
+    pub sells: u64,
+    pub sol_spent: f64,
+    pub sol_received: f64,
+    pub positions_closed: u64,
+    /// PnL of positions closed this session, after costs
+    pub realized_pnl_sol: f64,
+    /// The same before costs
+    pub realized_gross_pnl_sol: f64,
+    /// Base signature fees
+    pub fees_sol: f64,
+    pub priority_fees_sol: f64,
+    pub tips_sol: f64,
+    /// Token account rent paid less rent reclaimed
+    pub rent_sol: f64,
+    pub rejections_by_filter: BTreeMap<String, u64>,
+    pub stream_disconnects: u64,
+    /// Failed buys/sells and RPC probes by `ErrorClass`
+    pub errors_by_class: BTreeMap<String, u64>,
+    /// Measured fills; the report carries their distribution rather than the list
+    #[serde(skip)]
+    pub slippage: Vec<SlippageSample>,
+}
+
+fn update(apply: impl FnOnce(&mut SessionCounters)) {
+    if let Ok(mut counters) = COUNTERS.lock() {
+        apply(&mut counters);
+    }
+}
+
+pub fn record_event(dex_type: DexType) {
+    update(|c| *c.events_by_dex.entry(format!("{:?}", dex_type)).or_default() += 1);
+}
+
+pub fn record_rejection(filter: &str) {
+    update(|c| *c.rejections_by_filter.entry(filter.to_string()).or_default() += 1);
+}
+
+/// A buy candidate the pre-trade checks turned down
+#[derive(Clone, Debug, Serialize)]
+pub struct Rejection {
+    pub mint: String,
+    pub filter: String,
+    /// Reason with the values it was judged on
+    pub reason: String,
+    pub at: u64,
+}
+
+pub fn record_rejected_buy(mint: &str, filter: &str, reason: &str) {
+    if let Ok(mut recent) = REJECTIONS.lock() {
+        if recent.len() >= RECENT_REJECTIONS {
+            recent.pop_front();
+        }
+        recent.push_back(Rejection { mint: mint.to_string(), filter: filter.to_string(), reason: reason.to_string(), at: unix_now() });
+    }
+}
+
+/// Latest rejected buy candidates, newest first
+pub fn get_recent_rejections() -> Vec<Rejection> {
+    REJECTIONS.lock().map(|recent| recent.iter().rev().cloned().collect()).unwrap_or_default()
+}
+
+pub fn record_buy(report: &ExecutionReport) {
+    update(|c| {
+        c.buys += 1;
+        c.sol_spent += report.sol_amount;
+    });
+}
+
+pub fn record_sell(report: &ExecutionReport) {
+    update(|c| {
+        c.sells += 1;
+        c.sol_received += report.sol_amount;
+    });
+}
+
+/// Costs of one fill as written to the ledger
+pub fn record_costs(costs: &TradeCosts) {
+    update(|c| {
+        c.fees_sol += costs.network_fee_sol;
+        c.priority_fees_sol += costs.priority_fee_sol;
+        c.tips_sol += costs.tip_sol;
+        c.rent_sol += costs.rent_sol;
+    });
+}
+
+/// A fill as written to the ledger: its costs, and the RECENT_FILLS kept for the status API
+pub fn record_fill(entry: &LedgerEntry) {
+    record_costs(&entry.costs);
+    if let Ok(mut recent) = RECENT.lock() {
+        if recent.len() >= RECENT_FILLS {
+            recent.pop_front();
+        }
+        recent.push_back(entry.clone());
+    }
+}
+
+/// Latest fills of this session, newest first
+pub fn recent_fills() -> Vec<LedgerEntry> {
+    RECENT.lock().map(|recent| recent.iter().rev().cloned().collect()).unwrap_or_default()
+}
+
+pub fn record_close(gross_pnl_sol: f64, net_pnl_sol: f64) {
+    update(|c| {
+        c.positions_closed += 1;
+        c.realized_gross_pnl_sol += gross_pnl_sol;
+        c.realized_pnl_sol += net_pnl_sol;
+    });
+}
+
+pub fn record_slippage(sample: SlippageSample) {
+    update(|c| c.slippage.push(sample));
+}
+
+pub fn record_error(class: ErrorClass) {
+    update(|c| *c.errors_by_class.entry(class.as_str().to_string()).or_default() += 1);
+}
+
+pub fn record_stream_disconnect() {
+    update(|c| c.stream_disconnects += 1);
+}
+
+pub fn counters() -> SessionCounters {
+    COUNTERS.lock().map(|c| c.clone()).unwrap_or_default()
+}
+
+/// An open position, valued at its latest estimate (see `engine::valuation`)
+#[derive(Clone, Debug, Serialize)]
+pub struct OpenPositionSummary {
+    pub mint: String,
+    pub remaining_tokens: f64,
+    pub sol_invested: f64,
+    pub entry_price: f64,
+    pub peak_price: f64,
+    pub last_price: f64,
+    /// Realized plus marked-to-market value minus cost
+    pub unrealized_pnl_sol: f64,
+    /// Remaining tokens at the valuation price
+    pub value_sol: f64,
+    /// `value_sol` at the SOL/USD price, when known
+    pub value_usd: Option<f64>,
+    /// tick, quote or curve
+    pub valuation_source: ValuationSource,
+    pub valuation_age_secs: u64,
+    pub state: PositionState,
+}
+
+impl OpenPositionSummary {
+    pub fn from_position(position: &Position) -> Self {
+        let remaining_tokens = position.remaining_tokens();
+        let value_sol = remaining_tokens * position.valuation.price;
+        Self {
+            mint: position.mint.clone(),
+            remaining_tokens,
+            sol_invested: position.sol_invested(),
+            entry_price: position.entry_price(),
+            peak_price: position.peak_price,
+            last_price: position.last_price,
+            unrealized_pnl_sol: value_sol + position.net_pnl_sol(),
+            value_sol,
+            value_usd: valuation::sol_usd().map(|sol_usd| value_sol * sol_usd),
+            valuation_source: position.valuation.source,
+            valuation_age_secs: position.valuation.age_secs(clock::now_secs()),
+            state: position.state,
+        }
+    }
+}
+
+/// Final snapshot of a session
+#[derive(Clone, Debug, Serialize)]
+pub struct SessionReport {
+    pub session_id: String,
+    pub exit_reason: String,
+    pub started_at: u64,
+    pub ended_at: u64,
+    pub uptime_secs: u64,
+    #[serde(flatten)]
+    pub counters: SessionCounters,
+    pub open_positions: Vec<OpenPositionSummary>,
+    pub unrealized_pnl_sol: f64,
+    /// Effective slippage of this session's fills against BUY_SLIPPAGE
+    pub slippage: SlippageStats,
+    /// Ledger, position archive and this report
+    pub files: Vec<String>,
+}
+
+impl SessionReport {
+    pub fn snapshot(book: &PositionBook, exit_reason: &str) -> Self {
+        let open_positions: Vec<OpenPositionSummary> = book.snapshot()
+            .iter()
+            .map(OpenPositionSummary::from_position)
+            .collect();
+
+        let archive = ArchiveConfig::from_env();
+        let files = vec![
+            ledger::ledger_path(),
+            archive.path,
+            report_path().display().to_string(),
+        ];
+
+        let counters = counters();
+        Self {
+            session_id: session_id().to_string(),
+            exit_reason: exit_reason.to_string(),
+            started_at: STARTED_AT.1,
+            ended_at: unix_now(),
+            uptime_secs: STARTED_AT.0.elapsed().as_secs(),
+            slippage: SlippageStats::from_samples(&counters.slippage, slippage::tolerance_pct_from_env()),
+            counters,
+            unrealized_pnl_sol: open_positions.iter().map(|p| p.unrealized_pnl_sol).sum(),
+            open_positions,
+            files,
+        }
+    }
+
+    /// Human-readable form for stdout and Telegram
+    pub fn format_text(&self) -> String {
+        let c = &self.counters;
+        let join = |map: &BTreeMap<String, u64>| {
+            if map.is_empty() {
+                "none".to_string()
+            } else {
+                map.iter().map(|(key, count)| format!("{} {}", key, count)).collect::<Vec<_>>().join(", ")
+            }
+        };
+        let mut lines = vec![
+            format!("📋 SESSION REPORT {}", self.session_id),
+            format!("Exit: {} after {}s", self.exit_reason, self.uptime_secs),
+            format!("Events: {}", join(&c.events_by_dex)),
+            format!(
+                "Trades: {} buys ({:.6} SOL), {} sells ({:.6} SOL), {} closed",
+                c.buys, c.sol_spent, c.sells, c.sol_received, c.positions_closed
+            ),
+            format!("Realized PnL: gross {:+.6} SOL, net {:+.6} SOL", c.realized_gross_pnl_sol, c.realized_pnl_sol),
+            format!(
+                "Costs: fees {:.6} SOL, priority {:.6} SOL, tips {:.6} SOL, rent {:+.6} SOL",
+                c.fees_sol, c.priority_fees_sol, c.tips_sol, c.rent_sol
+            ),
+            format!("Rejections: {}", join(&c.rejections_by_filter)),
+            format!("Stream disconnects: {}", c.stream_disconnects),
+            format!("Errors: {}", join(&c.errors_by_class)),
+            self.slippage.format(),
+            format!("Open positions: {} (unrealized {:+.6} SOL)", self.open_positions.len(), self.unrealized_pnl_sol),
+        ];
+        for position in &self.open_positions {
+            let usd = position.value_usd.map(|usd| format!(" (~${:.2})", usd)).unwrap_or_default();
+            lines.push(format!(
+                "  {} ({}) {:.2} tokens worth {:.6} SOL{}, {:+.6} SOL [{} {}s ago]",
+                position.mint,
+                position.state,
+                position.remaining_tokens,
+                position.value_sol,
+                usd,
+                position.unrealized_pnl_sol,
+                position.valuation_source.as_str(),
+                position.valuation_age_secs
+            ));
+        }
+        lines.push(format!("Files: {}", self.files.join(", ")));
+        lines.join("\n")
+    }
+
+    /// Write the report as pretty JSON to `report_path()`
+    pub fn write(&self) -> std::io::Result<PathBuf> {
+        let path = report_path();
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        let json = serde_json::to_string_pretty(self).map_err(std::io::Error::other)?;
+        std::fs::write(&path, json)?;
+        Ok(path)
+    }
+}
+
+/// SESSION_REPORT_DIR (default: session_reports)/session-<id>.json
+pub fn report_path() -> PathBuf {
+    let dir = std::env::var("SESSION_REPORT_DIR").unwrap_or_else(|_| "session_reports".to_string());
+    PathBuf::from(dir).join(format!("session-{}.json", session_id()))
+}
+
+/// Print, write and send the session report; only the first call per process does anything
+pub async fn finish_session(exit_reason: &str) {
+    if FINISHED.swap(true, Ordering::SeqCst) {
+        return;
+    }
+    let report = SessionReport::snapshot(&POSITION_BOOK, exit_reason);
+    let text = report.format_text();
+    println!("\n{}\n", text);
+    if let Err(e) = report.write() {
+        eprintln!("Failed to write session report {}: {}", report_path().display(), e);
+    }
+    if telegram::is_configured() {
+        // Bounded so a dead Telegram API can't hold up the exit
+        let send = telegram_chunks::send_long_message("Session report", &text, ChunkFormat::Plain);
+        let _ = tokio::time::timeout(Duration::from_secs(15), send).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::position::Valuation;
+    use crate::engine::test_fixtures::{book_with, exit, pubkey};
+
+    #[test]
+    fn open_position_is_marked_at_its_valuation() {
+        let mint = pubkey();
+        let book = book_with(&mint, 1_000.0, 1.0);
+        book.record_exit(&mint, exit("partial", 400.0, 0.6, "tier"));
+        book.set_valuation(&mint, Valuation { price: 0.002, source: ValuationSource::Quote, at: clock::now_secs() });
+        let summary = OpenPositionSummary::from_position(&book.get(&mint).unwrap());
+
+        assert!((summary.remaining_tokens - 600.0).abs() < 1e-9);
+        assert!((summary.value_sol - 1.2).abs() < 1e-9);
+        // 1.2 SOL held + 0.6 SOL realized - 1 SOL invested
+        assert!((summary.unrealized_pnl_sol - 0.8).abs() < 1e-9);
+        assert_eq!(summary.valuation_source, ValuationSource::Quote);
+    }
+
+    #[test]
+    fn snapshot_and_text_cover_the_book() {
+        let mint = pubkey();
+        let book = book_with(&mint, 1_000.0, 1.0);
+        let report = SessionReport::snapshot(&book, "ctrl-c");
+        assert_eq!(report.session_id, session_id());
+        assert_eq!(report.open_positions.len(), 1);
+        assert!((report.unrealized_pnl_sol - report.open_positions[0].unrealized_pnl_sol).abs() < 1e-12);
+        assert_eq!(report.files.last().unwrap(), &report_path().display().to_string());
+
+        let text = report.format_text();
+        assert!(text.starts_with(&format!("📋 SESSION REPORT {}", session_id())));
+        assert!(text.contains("Exit: ctrl-c after"));
+        assert!(text.contains("Open positions: 1"));
+        assert!(text.contains(&mint));
+    }
+
+    #[test]
+    fn counters_accumulate() {
+        let before = counters();
+        record_event(DexType::RaydiumAmm);
+        record_rejection("session-report-test");
+        record_error(ErrorClass::Network);
+        record_stream_disconnect();
+        record_close(0.5, 0.4);
+        let after = counters();
+
+        let count = |map: &BTreeMap<String, u64>, key: &str| map.get(key).copied().unwrap_or(0);
+        assert!(count(&after.events_by_dex, "RaydiumAmm") > count(&before.events_by_dex, "RaydiumAmm"));
+        assert_eq!(count(&after.rejections_by_filter, "session-report-test"), 1);
+        assert!(count(&after.errors_by_class, ErrorClass::Network.as_str()) > count(&before.errors_by_class, ErrorClass::Network.as_str()));
+        assert!(after.stream_disconnects > before.stream_disconnects);
+        assert!(after.positions_closed > before.positions_closed);
+    }
+
+    #[test]
+    fn rejections_are_kept_newest_first_and_bounded() {
+        let mints: Vec<String> = (0..RECENT_REJECTIONS + 5).map(|_| pubkey()).collect();
+        for mint in &mints {
+            record_rejected_buy(mint, "liquidity", "0.5 SOL < 1 SOL");
+        }
+        let recent = get_recent_rejections();
+        assert!(recent.len() <= RECENT_REJECTIONS);
+        let newest = recent.iter().position(|rejection| &rejection.mint == mints.last().unwrap()).unwrap();
+        let older = recent.iter().position(|rejection| rejection.mint == mints[mints.len() - 2]).unwrap();
+        assert!(newest < older);
+        assert!(!recent.iter().any(|rejection| rejection.mint == mints[0]));
+    }
+}
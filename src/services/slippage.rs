@@ -0,0 +1,377 @@
+use std::collections::BTreeMap;
+use chrono::{TimeZone, Timelike, Utc};
+use serde::Serialize;
+
+use crate::common::logger::Logger;
+use crate::services::ledger::LedgerEntry;
+
+/// Worst offenders listed by `SlippageStats::format`
+const WORST_MINTS: usize = 5;
+
+/// Tolerance from BUY_SLIPPAGE (default: 700 bps, capped at 50000 like the config), in percent
+pub fn tolerance_pct_from_env() -> f64 {
+    let bps = std::env::var("BUY_SLIPPAGE")
+        .ok()
+        .and_then(|v| v.parse::<u64>().ok())
+        .unwrap_or(700)
+        .min(50_000);
+    bps as f64 / 100.0
+}
+
+/// What a fill was expected to give, captured when it was sent
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum SlippageBasis {
+    /// post_current_price of the trigger event
+    Buy { expected_price: f64 },
+    /// Output estimated at decision time for `token_amount` tokens
+    Sell { expected_sol: f64, token_amount: f64 },
+}
+
+/// Price given up against the basis; positive is worse than expected
+#[derive(Clone, Copy, Debug, PartialEq, Serialize)]
+pub struct EffectiveSlippage {
+    pub pct: f64,
+    /// SOL paid above (buy) or received below (sell) the expectation
+    pub cost_sol: f64,
+}
+
+impl SlippageBasis {
+    /// Slippage of a fill of `token_amount` tokens for `sol_amount` SOL, before costs
+    /// A sell that filled fewer tokens than expected is measured against the expected output scaled to the tokens
+    /// it sold, so a partial fill isn't mistaken for slippage. None when either side is empty.
+    pub fn measure(&self, sol_amount: f64, token_amount: f64) -> Option<EffectiveSlippage> {
+        if !sol_amount.is_finite() || !token_amount.is_finite() || token_amount <= 0.0 {
+            return None;
+        }
+        match *self {
+            Self::Buy { expected_price } => {
+                if expected_price <= 0.0 || sol_amount <= 0.0 {
+                    return None;
+                }
+                let realized_price = sol_amount / token_amount;
+                Some(EffectiveSlippage {
+                    pct: (realized_price / expected_price - 1.0) * 100.0,
+                    cost_sol: sol_amount - token_amount * expected_price,
+                })
+            }
+            Self::Sell { expected_sol, token_amount: expected_tokens } => {
+                if expected_sol <= 0.0 || expected_tokens <= 0.0 {
+                    return None;
+                }
+                let expected = expected_sol * token_amount / expected_tokens;
+                Some(EffectiveSlippage {
+                    pct: (1.0 - sol_amount / expected) * 100.0,
+                    cost_sol: expected - sol_amount,
+                })
+            }
+        }
+    }
+}
+
+impl EffectiveSlippage {
+    pub fn exceeds(&self, tolerance_pct: f64) -> bool {
+        self.pct > tolerance_pct + 1e-9
+    }
+
+    /// Line for trade notifications
+    pub fn format_line(&self, tolerance_pct: f64) -> String {
+        let flag = if self.exceeds(tolerance_pct) { " ⚠️ over tolerance" } else { "" };
+        format!("🎯 Slippage: {:+.2}% ({:+.6} SOL), tolerance {:.2}%{}", self.pct, self.cost_sol, tolerance_pct, flag)
+    }
+}
+
+/// Log a fill whose slippage exceeded the tolerance; returns whether it did
+/// A buy can't legitimately exceed it while max_sol_cost is built from the same tolerance, so that is critical.
+pub fn check_tolerance(side: &str, mint: &str, signature: &str, slippage: &EffectiveSlippage, tolerance_pct: f64, logger: &Logger) -> bool {
+    if !slippage.exceeds(tolerance_pct) {
+        return false;
+    }
+    if side == "buy" {
+        let message = format!(
+            "🚨 CRITICAL: buy of {} ({}) slipped {:+.2}% past the {:.2}% tolerance; max_sol_cost is not bounding buys",
+            mint, signature, slippage.pct, tolerance_pct
+        );
+        println!("{}", message);
+        logger.error(message);
+    } else {
+        logger.error(format!(
+            "Sell of {} ({}) slipped {:+.2}% ({:+.6} SOL), past the {:.2}% tolerance",
+            mint, signature, slippage.pct, slippage.cost_sol, tolerance_pct
+        ));
+    }
+    true
+}
+
+/// One measured fill
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct SlippageSample {
+    pub mint: String,
+    pub side: String,
+    pub timestamp: u64,
+    pub pct: f64,
+    pub cost_sol: f64,
+}
+
+impl SlippageSample {
+    /// The sample a ledger entry carries; None for entries recorded without slippage
+    pub fn from_entry(entry: &LedgerEntry) -> Option<Self> {
+        Some(Self {
+            mint: entry.mint.clone(),
+            side: entry.side.clone(),
+            timestamp: entry.timestamp,
+            pct: entry.slippage_pct?,
+            cost_sol: entry.slippage_sol?,
+        })
+    }
+}
+
+/// Distribution for one side
+#[derive(Clone, Debug, Default, PartialEq, Serialize)]
+pub struct SideStats {
+    pub count: usize,
+    pub p50_pct: f64,
+    pub p90_pct: f64,
+    pub worst_pct: f64,
+    pub total_cost_sol: f64,
+    /// Fills past the tolerance
+    pub exceeded: usize,
+}
+
+/// Slippage given up by one mint or in one hour
+#[derive(Clone, Debug, Default, PartialEq, Serialize)]
+pub struct SlippageGroup {
+    pub count: usize,
+    pub mean_pct: f64,
+    pub worst_pct: f64,
+    pub total_cost_sol: f64,
+}
+
+/// Effective slippage over a set of fills (--stats and the session report)
+#[derive(Clone, Debug, Default, PartialEq, Serialize)]
+pub struct SlippageStats {
+    pub tolerance_pct: f64,
+    pub buys: SideStats,
+    pub sells: SideStats,
+    /// Highest total cost first
+    pub worst_mints: Vec<(String, SlippageGroup)>,
+    /// By UTC hour of day
+    pub by_hour: BTreeMap<u32, SlippageGroup>,
+}
+
+/// Nearest-rank percentile of sorted values
+fn percentile(sorted: &[f64], pct: f64) -> f64 {
+    if sorted.is_empty() {
+        return 0.0;
+    }
+    let rank = ((pct / 100.0) * sorted.len() as f64).ceil() as usize;
+    sorted[rank.clamp(1, sorted.len()) - 1]
+}
+
+fn side_stats(samples: &[&SlippageSample], tolerance_pct: f64) -> SideStats {
+    let mut pcts: Vec<f64> = samples.iter().map(|sample| sample.pct).collect();
+    pcts.sort_by(|a, b| a.total_cmp(b));
+    SideStats {
+        count: pcts.len(),
+        p50_pct: percentile(&pcts, 50.0),
+        p90_pct: percentile(&pcts, 90.0),
+        worst_pct: pcts.last().copied().unwrap_or(0.0),
+        total_cost_sol: samples.iter().map(|sample| sample.cost_sol).sum(),
+        exceeded: pcts.iter().filter(|pct| **pct > tolerance_pct + 1e-9).count(),
+    }
+}
+
+fn add_to_group(group: &mut SlippageGroup, sample: &SlippageSample) {
+    group.mean_pct = (group.mean_pct * group.count as f64 + sample.pct) / (group.count + 1) as f64;
+    group.worst_pct = if group.count == 0 { sample.pct } else { group.worst_pct.max(sample.pct) };
+    group.total_cost_sol += sample.cost_sol;
+    group.count += 1;
+}
+
+impl SlippageStats {
+    pub fn from_samples(samples: &[SlippageSample], tolerance_pct: f64) -> Self {
+        let of_side = |side: &str| samples.iter().filter(|sample| sample.side == side).collect::<Vec<_>>();
+        let mut by_mint: BTreeMap<String, SlippageGroup> = BTreeMap::new();
+        let mut by_hour: BTreeMap<u32, SlippageGroup> = BTreeMap::new();
+        for sample in samples {
+            add_to_group(by_mint.entry(sample.mint.clone()).or_default(), sample);
+            let hour = Utc.timestamp_opt(sample.timestamp as i64, 0).single().map(|time| time.hour()).unwrap_or(0);
+            add_to_group(by_hour.entry(hour).or_default(), sample);
+        }
+        let mut worst_mints: Vec<(String, SlippageGroup)> = by_mint.into_iter().collect();
+        worst_mints.sort_by(|a, b| b.1.total_cost_sol.total_cmp(&a.1.total_cost_sol));
+        Self {
+            tolerance_pct,
+            buys: side_stats(&of_side("buy"), tolerance_pct),
+            sells: side_stats(&of_side("sell"), tolerance_pct),
+            worst_mints,
+            by_hour,
+        }
+    }
+
+    pub fn from_entries(entries: &[LedgerEntry], tolerance_pct: f64) -> Self {
+        let samples: Vec<SlippageSample> = entries.iter().filter_map(SlippageSample::from_entry).collect();
+        Self::from_samples(&samples, tolerance_pct)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.buys.count == 0 && self.sells.count == 0
+    }
+
+    pub fn format(&self) -> String {
+        if self.is_empty() {
+            return "Slippage: no measured fills".to_string();
+        }
+        let side = |name: &str, stats: &SideStats| {
+            format!(
+                "  {}: {} fills, p50 {:+.2}%, p90 {:+.2}%, worst {:+.2}%, {:+.6} SOL given up, {} over tolerance",
+                name, stats.count, stats.p50_pct, stats.p90_pct, stats.worst_pct, stats.total_cost_sol, stats.exceeded
+            )
+        };
+        let mut lines = vec![
+            format!("Slippage (tolerance {:.2}%):", self.tolerance_pct),
+            side("Buys", &self.buys),
+            side("Sells", &self.sells),
+            "  Worst mints:".to_string(),
+        ];
+        for (mint, group) in self.worst_mints.iter().take(WORST_MINTS) {
+            lines.push(format!(
+                "    {} {:+.6} SOL over {} fills (mean {:+.2}%, worst {:+.2}%)",
+                mint, group.total_cost_sol, group.count, group.mean_pct, group.worst_pct
+            ));
+        }
+        lines.push("  By hour (UTC):".to_string());
+        for (hour, group) in &self.by_hour {
+            lines.push(format!(
+                "    {:02}:00 {} fills, mean {:+.2}%, {:+.6} SOL",
+                hour, group.count, group.mean_pct, group.total_cost_sol
+            ));
+        }
+        lines.join("\n")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn close(actual: f64, expected: f64) -> bool {
+        (actual - expected).abs() < 1e-9
+    }
+
+    fn sample(mint: &str, side: &str, hour: u64, pct: f64, cost_sol: f64) -> SlippageSample {
+        SlippageSample { mint: mint.to_string(), side: side.to_string(), timestamp: 1_700_006_400 + hour * 3_600, pct, cost_sol }
+    }
+
+    #[test]
+    fn buys_compare_the_realized_price_with_the_trigger() {
+        // 0.105 SOL for 1000 tokens expected at 0.0001: 5% above, 0.005 SOL given up
+        let slippage = SlippageBasis::Buy { expected_price: 0.000_1 }.measure(0.105, 1_000.0).unwrap();
+        assert!(close(slippage.pct, 5.0), "{:?}", slippage);
+        assert!(close(slippage.cost_sol, 0.005));
+
+        // A better fill than the trigger is negative
+        let better = SlippageBasis::Buy { expected_price: 0.000_1 }.measure(0.095, 1_000.0).unwrap();
+        assert!(close(better.pct, -5.0));
+    }
+
+    #[test]
+    fn a_partial_sell_is_measured_against_the_tokens_it_sold() {
+        let basis = SlippageBasis::Sell { expected_sol: 1.0, token_amount: 10_000.0 };
+        // Half the tokens sold for 0.45 SOL: 10% under the 0.5 SOL those tokens were expected to give
+        let partial = basis.measure(0.45, 5_000.0).unwrap();
+        assert!(close(partial.pct, 10.0), "{:?}", partial);
+        assert!(close(partial.cost_sol, 0.05));
+
+        let full = basis.measure(0.98, 10_000.0).unwrap();
+        assert!(close(full.pct, 2.0));
+        assert!(close(full.cost_sol, 0.02));
+    }
+
+    #[test]
+    fn empty_fills_have_no_slippage() {
+        let buy = SlippageBasis::Buy { expected_price: 0.000_1 };
+        assert_eq!(buy.measure(0.1, 0.0), None);
+        assert_eq!(buy.measure(0.0, 1_000.0), None);
+        assert_eq!(buy.measure(f64::NAN, 1_000.0), None);
+        assert_eq!(SlippageBasis::Buy { expected_price: 0.0 }.measure(0.1, 1_000.0), None);
+        assert_eq!(SlippageBasis::Sell { expected_sol: 0.0, token_amount: 1.0 }.measure(0.1, 1.0), None);
+    }
+
+    #[test]
+    fn tolerance_flags_only_what_exceeds_it() {
+        let at = EffectiveSlippage { pct: 7.0, cost_sol: 0.007 };
+        let over = EffectiveSlippage { pct: 7.5, cost_sol: 0.007_5 };
+        assert!(!at.exceeds(7.0));
+        assert!(over.exceeds(7.0));
+        assert!(!at.format_line(7.0).contains("over tolerance"));
+        assert_eq!(over.format_line(7.0), "🎯 Slippage: +7.50% (+0.007500 SOL), tolerance 7.00% ⚠️ over tolerance");
+
+        let logger = Logger::new(String::new());
+        assert!(!check_tolerance("buy", "mint", "sig", &at, 7.0, &logger));
+        assert!(check_tolerance("sell", "mint", "sig", &over, 7.0, &logger));
+    }
+
+    #[test]
+    fn stats_give_percentiles_worst_mints_and_hours() {
+        let samples: Vec<SlippageSample> = (1..=10)
+            .map(|pct| sample("steady", "buy", 0, pct as f64, pct as f64 / 1_000.0))
+            .chain([
+                sample("rough", "sell", 5, 12.0, 0.2),
+                sample("rough", "sell", 5, -2.0, -0.01),
+                sample("steady", "sell", 14, 3.0, 0.003),
+            ])
+            .collect();
+        let stats = SlippageStats::from_samples(&samples, 7.0);
+
+        assert_eq!(stats.buys.count, 10);
+        assert_eq!((stats.buys.p50_pct, stats.buys.p90_pct, stats.buys.worst_pct), (5.0, 9.0, 10.0));
+        assert_eq!(stats.buys.exceeded, 3);
+        assert!(close(stats.buys.total_cost_sol, 0.055));
+        assert_eq!(stats.sells.count, 3);
+        assert_eq!((stats.sells.p50_pct, stats.sells.worst_pct, stats.sells.exceeded), (3.0, 12.0, 1));
+
+        // The rough mint gave up more SOL over fewer fills
+        assert_eq!(stats.worst_mints[0].0, "rough");
+        let rough = &stats.worst_mints[0].1;
+        assert_eq!((rough.count, rough.worst_pct), (2, 12.0));
+        assert!(close(rough.mean_pct, 5.0));
+        assert!(close(rough.total_cost_sol, 0.19));
+
+        assert_eq!(stats.by_hour.keys().copied().collect::<Vec<_>>(), vec![0, 5, 14]);
+        assert_eq!(stats.by_hour[&0].count, 10);
+        assert!(stats.format().contains("Buys: 10 fills, p50 +5.00%, p90 +9.00%, worst +10.00%"));
+    }
+
+    fn entry(side: &str, slippage: Option<EffectiveSlippage>) -> LedgerEntry {
+        LedgerEntry {
+            timestamp: 1_700_006_400,
+            mint: "mint".to_string(),
+            side: side.to_string(),
+            signature: String::new(),
+            token_amount: 1_000.0,
+            sol_amount: 0.1,
+            price: 0.000_1,
+            reason: String::new(),
+            simulated: false,
+            blockhash_mode: None,
+            session_id: String::new(),
+            costs: Default::default(),
+            imported: false,
+            pre_jitter_sol: None,
+            post_jitter_sol: None,
+            slippage_pct: slippage.map(|slippage| slippage.pct),
+            slippage_sol: slippage.map(|slippage| slippage.cost_sol),
+        }
+    }
+
+    #[test]
+    fn entries_without_slippage_are_left_out() {
+        let measured = EffectiveSlippage { pct: 4.0, cost_sol: 0.004 };
+        let stats = SlippageStats::from_entries(&[entry("buy", Some(measured)), entry("buy", None), entry("sell", None)], 7.0);
+        assert_eq!((stats.buys.count, stats.sells.count), (1, 0));
+        assert_eq!(stats.buys.p50_pct, 4.0);
+
+        assert!(SlippageStats::from_entries(&[entry("sell", None)], 7.0).is_empty());
+        assert_eq!(SlippageStats::default().format(), "Slippage: no measured fills");
+    }
+}
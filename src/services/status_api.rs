@@ -0,0 +1,452 @@
+use std::sync::Arc;
+use axum::extract::ws::{Message, WebSocket, WebSocketUpgrade};
+use axum::extract::{Request, State};
+use axum::http::header::{CACHE_CONTROL, WWW_AUTHENTICATE, X_CONTENT_TYPE_OPTIONS};
+use axum::http::{HeaderMap, HeaderValue, Method, StatusCode};
+use axum::middleware::{self, Next};
+use axum::response::{Html, IntoResponse, Response};
+use axum::routing::get;
+use axum::{Json, Router};
+use colored::Colorize;
+use serde_json::json;
+use subtle::ConstantTimeEq;
+use tokio::net::TcpListener;
+use tokio::sync::broadcast::error::RecvError;
+
+use crate::common::cache::TRADE_METRICS;
+use crate::common::config::{AppState, SwapConfig};
+use crate::common::{clock, logger::Logger};
+use crate::engine::event_stream;
+use crate::engine::position::POSITION_BOOK;
+use crate::engine::position_updates::{PositionUpdates, POSITION_UPDATES};
+use crate::engine::sniper::TOKEN_HOLDINGS;
+use crate::engine::valuation;
+use crate::services::blockhash_processor::BlockhashProcessor;
+use crate::services::nonce_health::NONCE_HEALTH;
+use crate::services::session_report::{self, OpenPositionSummary, SessionReport};
+use crate::services::{rpc_pool, task_monitor, telegram_commands};
+
+pub const POSITIONS_WS_PATH: &str = "/ws/positions";
+pub const DASHBOARD_PATH: &str = "/dashboard";
+/// JSON endpoints the dashboard polls, all GET
+pub const API_PATHS: &[&str] = &["/api/summary", "/api/trades", "/api/health"];
+/// JSON endpoints for headless hosts, all GET
+pub const STATUS_PATHS: &[&str] = &["/holdings", "/health", "/config", "/rejections"];
+/// Embedded at compile time; it polls `API_PATHS` and needs nothing else
+pub const DASHBOARD_HTML: &str = include_str!("dashboard.html");
+
+/// Listen address from STATUS_API_ADDR (e.g. 127.0.0.1:8787), else 127.0.0.1:STATUS_HTTP_PORT; None keeps the
+/// server off
+pub fn status_api_addr() -> Option<String> {
+    let addr = std::env::var("STATUS_API_ADDR").ok().filter(|addr| !addr.trim().is_empty());
+    addr.or_else(|| status_http_port().map(|port| format!("127.0.0.1:{}", port)))
+}
+
+/// STATUS_HTTP_PORT (default: off)
+fn status_http_port() -> Option<u16> {
+    std::env::var("STATUS_HTTP_PORT").ok().and_then(|v| v.trim().parse().ok()).filter(|port| *port != 0)
+}
+
+/// STATUS_API_TOKEN (default: none) required as `Authorization: Bearer <token>` on every endpoint but the page,
+/// and STATUS_DASHBOARD (default: false) serving the dashboard page
+#[derive(Clone, Debug, Default)]
+pub struct StatusApiConfig {
+    pub token: Option<String>,
+    pub dashboard: bool,
+}
+
+impl StatusApiConfig {
+    pub fn from_env() -> Self {
+        Self {
+            token: std::env::var("STATUS_API_TOKEN").ok().filter(|token| !token.trim().is_empty()),
+            dashboard: std::env::var("STATUS_DASHBOARD").map(|v| v == "true").unwrap_or(false),
+        }
+    }
+
+    /// Whether the Authorization header carries the token, compared in constant time; always true without one
+    /// Only the header counts: a token in the query string ends up in proxy and shell history.
+    pub(crate) fn authorized(&self, headers: &HeaderMap) -> bool {
+        let Some(token) = &self.token else {
+            return true;
+        };
+        let Some(bearer) = header(headers, "authorization").and_then(|value| value.strip_prefix("Bearer ")) else {
+            return false;
+        };
+        bool::from(bearer.trim().as_bytes().ct_eq(token.as_bytes()))
+    }
+}
+
+/// API paths the dashboard fetches that the server doesn't serve; empty unless the page and `API_PATHS` drift apart
+pub fn dashboard_unknown_endpoints() -> Vec<String> {
+    DASHBOARD_HTML
+        .split('"')
+        .filter(|literal| literal.starts_with("/api/"))
+        .filter(|path| !API_PATHS.contains(path))
+        .map(str::to_string)
+        .collect()
+}
+
+/// What the handlers read besides the live globals
+#[derive(Clone)]
+struct StatusState {
+    config: Arc<StatusApiConfig>,
+    updates: Arc<PositionUpdates>,
+    /// Swap settings and public identifiers, fixed at startup
+    settings: Arc<serde_json::Value>,
+}
+
+/// Swap settings and public identifiers only: no key material and no RPC URLs, which often embed API keys
+fn settings(app_state: &AppState, swap_config: &SwapConfig) -> serde_json::Value {
+    use anchor_client::solana_sdk::signer::Signer;
+    json!({
+        "wallet": app_state.wallet.pubkey().to_string(),
+        "protocol_preference": format!("{:?}", app_state.protocol_preference),
+        "swap_direction": format!("{:?}", swap_config.swap_direction),
+        "in_type": format!("{:?}", swap_config.in_type),
+        "amount_in": swap_config.amount_in,
+        "buy_slippage_bps": swap_config.buy_slippage,
+        "reverse": swap_config.reverse,
+        "dry_run": swap_config.dry_run,
+    })
+}
+
+/// Serve the status API when STATUS_API_ADDR or STATUS_HTTP_PORT is set
+/// `/ws/positions` streams every published `PositionUpdate` as JSON text frames; the JSON endpoints, and with
+/// STATUS_DASHBOARD the dashboard page, share the same listener and token.
+pub async fn start_status_api(app_state: &AppState, swap_config: &SwapConfig) -> Result<(), String> {
+    let Some(addr) = status_api_addr() else {
+        return Ok(());
+    };
+    let config = StatusApiConfig::from_env();
+    let listener = TcpListener::bind(&addr)
+        .await
+        .map_err(|e| format!("Failed to bind status API on {}: {}", addr, e))?;
+    println!("✅ Status API listening on http://{} ({}, ws {})", addr, STATUS_PATHS.join(", "), POSITIONS_WS_PATH);
+    if config.dashboard {
+        println!("✅ Dashboard at http://{}{}", addr, DASHBOARD_PATH);
+    }
+
+    let logger = Logger::new("[STATUS-API] => ".cyan().to_string());
+    let unknown = dashboard_unknown_endpoints();
+    if config.dashboard && !unknown.is_empty() {
+        logger.error(format!("Dashboard fetches endpoints the API doesn't serve: {}", unknown.join(", ")));
+    }
+    let app = router(config, POSITION_UPDATES.clone(), settings(app_state, swap_config));
+    tokio::spawn(async move {
+        if let Err(e) = axum::serve(listener, app).await {
+            logger.error(format!("Status API stopped: {}", e));
+        }
+    });
+    Ok(())
+}
+
+fn router(config: StatusApiConfig, updates: Arc<PositionUpdates>, settings: serde_json::Value) -> Router {
+    let state = StatusState { config: Arc::new(config), updates, settings: Arc::new(settings) };
+    Router::new()
+        .route(POSITIONS_WS_PATH, get(positions_ws))
+        .route(DASHBOARD_PATH, get(dashboard))
+        .route("/api/summary", get(|| async { Json(serde_json::to_value(SessionReport::snapshot(&POSITION_BOOK, "running")).unwrap_or_default()) }))
+        .route("/api/trades", get(|| async { Json(serde_json::to_value(session_report::recent_fills()).unwrap_or_default()) }))
+        .route("/api/health", get(|| async { Json(api_health()) }))
+        .route("/holdings", get(|| async { Json(holdings()) }))
+        .route("/health", get(|| async { Json(node_health().await) }))
+        .route("/config", get(config_view))
+        .route("/rejections", get(|| async { Json(json!(session_report::get_recent_rejections())) }))
+        .fallback(|| async { error(StatusCode::NOT_FOUND, "unknown path") })
+        .layer(middleware::from_fn_with_state(state.clone(), guard))
+        .with_state(state)
+}
+
+fn header<'a>(headers: &'a HeaderMap, name: &str) -> Option<&'a str> {
+    headers.get(name).and_then(|value| value.to_str().ok())
+}
+
+fn error(status: StatusCode, message: &str) -> Response {
+    let mut response = (status, Json(json!({ "error": message }))).into_response();
+    if status == StatusCode::UNAUTHORIZED {
+        response.headers_mut().insert(WWW_AUTHENTICATE, HeaderValue::from_static("Bearer"));
+    }
+    response
+}
+
+/// A cross-origin browser request: an Origin (or Sec-Fetch-Site) that isn't this host
+fn is_cross_origin(headers: &HeaderMap) -> bool {
+    if header(headers, "sec-fetch-site") == Some("cross-site") {
+        return true;
+    }
+    match (header(headers, "origin"), header(headers, "host")) {
+        (Some(origin), Some(host)) => {
+            let origin_host = origin.split("://").nth(1).unwrap_or(origin);
+            origin_host != host
+        }
+        (Some(_), None) => true,
+        (None, _) => false,
+    }
+}
+
+/// GET only, same origin only, and the token on everything but the page
+/// The page itself carries no data and is served without the token; it asks for one when the API answers 401.
+/// Responses carry no CORS headers, so browsers only let same-origin pages read them.
+async fn guard(State(state): State<StatusState>, request: Request, next: Next) -> Response {
+    if request.method() != Method::GET {
+        return error(StatusCode::METHOD_NOT_ALLOWED, "only GET is served");
+    }
+    if is_cross_origin(request.headers()) {
+        return error(StatusCode::FORBIDDEN, "cross-origin requests are not served");
+    }
+    if request.uri().path() != DASHBOARD_PATH && !state.config.authorized(request.headers()) {
+        return error(StatusCode::UNAUTHORIZED, "missing or wrong bearer token");
+    }
+    let mut response = next.run(request).await;
+    let headers = response.headers_mut();
+    headers.insert(CACHE_CONTROL, HeaderValue::from_static("no-store"));
+    headers.insert(X_CONTENT_TYPE_OPTIONS, HeaderValue::from_static("nosniff"));
+    response
+}
+
+async fn dashboard(State(state): State<StatusState>) -> Response {
+    if !state.config.dashboard {
+        return error(StatusCode::NOT_FOUND, "dashboard disabled; set STATUS_DASHBOARD=true");
+    }
+    Html(DASHBOARD_HTML).into_response()
+}
+
+/// Stream and RPC health, active suppressions and the /status sections
+fn api_health() -> serde_json::Value {
+    let stream = event_stream::stream_health();
+    let secs_ago = |at: Option<u64>| at.map(|at| clock::now_secs().saturating_sub(at));
+    let rpc: Vec<serde_json::Value> = rpc_pool::status_pools()
+        .iter()
+        .map(|pool| json!({
+            "label": pool.label(),
+            "healthy": pool.endpoints().iter().filter(|endpoint| endpoint.is_healthy()).count(),
+            "endpoints": pool.endpoints().len(),
+        }))
+        .collect();
+    let suppressions: Vec<serde_json::Value> = NONCE_HEALTH
+        .degraded_reasons()
+        .into_iter()
+        .map(|(wallet, reason)| json!({ "kind": "nonce sends", "subject": wallet, "reason": reason }))
+        .collect();
+    let sections: Vec<serde_json::Value> = telegram_commands::status_sections()
+        .into_iter()
+        .map(|(name, text)| json!({ "name": name, "text": text }))
+        .collect();
+    json!({
+        "stream": {
+            "connected": stream.is_connected(),
+            "state": stream.state.map(|state| state.as_str()),
+            "consecutive_failures": stream.consecutive_failures,
+            "last_message_secs_ago": secs_ago(stream.last_message_at),
+            "last_transaction_secs_ago": secs_ago(stream.last_transaction_at),
+            "disconnects": session_report::counters().stream_disconnects,
+        },
+        "rpc": rpc,
+        "suppressions": suppressions,
+        "sections": sections,
+    })
+}
+
+/// Blockhash age, active tasks, zeroslot failovers and candle count
+async fn node_health() -> serde_json::Value {
+    json!({
+        "blockhash_age_ms": BlockhashProcessor::blockhash_age().await.map(|age| age.as_millis() as u64),
+        "active_tasks": task_monitor::active_task_count(),
+        "zeroslot_failovers": crate::services::zeroslot_pool::failover_count(),
+        "candles": TRADE_METRICS.total_candle_count(),
+    })
+}
+
+async fn config_view(State(state): State<StatusState>) -> Json<serde_json::Value> {
+    let mut view = state.settings.as_ref().clone();
+    view["sol_usd"] = json!(valuation::sol_usd());
+    Json(view)
+}
+
+/// Open positions with entry price, valuation price and unrealized PnL
+/// Pipeline positions come from the position book; mints only the legacy sniper holds are listed with their amount.
+fn holdings() -> serde_json::Value {
+    let mut mints = Vec::new();
+    let mut holdings: Vec<serde_json::Value> = POSITION_BOOK
+        .snapshot()
+        .iter()
+        .map(|position| {
+            mints.push(position.mint.clone());
+            let summary = OpenPositionSummary::from_position(position);
+            json!({
+                "mint": summary.mint,
+                "state": summary.state,
+                "tokens": summary.remaining_tokens,
+                "sol_invested": summary.sol_invested,
+                "entry_price": summary.entry_price,
+                "current_price": position.valuation.price,
+                "valuation_source": summary.valuation_source,
+                "valuation_age_secs": summary.valuation_age_secs,
+                "value_sol": summary.value_sol,
+                "unrealized_pnl_sol": summary.unrealized_pnl_sol,
+                "unrealized_pnl_usd": valuation::sol_usd().map(|sol_usd| summary.unrealized_pnl_sol * sol_usd),
+            })
+        })
+        .collect();
+    for entry in TOKEN_HOLDINGS.iter() {
+        if !mints.contains(entry.key()) {
+            holdings.push(json!({ "mint": entry.key(), "tokens": entry.value().current_amount }));
+        }
+    }
+    serde_json::Value::Array(holdings)
+}
+
+async fn positions_ws(State(state): State<StatusState>, ws: WebSocketUpgrade) -> Response {
+    ws.on_upgrade(move |socket| async move {
+        if let Err(e) = serve_positions(socket, state.updates).await {
+            Logger::new("[STATUS-API] => ".cyan().to_string()).debug(format!("WebSocket client disconnected: {}", e));
+        }
+    })
+}
+
+/// Forward updates to one client from its own receiver
+/// A client that falls more than the channel capacity behind skips the oldest updates and gets a `lagged` notice;
+/// the publisher never waits on it.
+async fn serve_positions(mut socket: WebSocket, updates: Arc<PositionUpdates>) -> Result<(), String> {
+    let mut receiver = updates.subscribe();
+    loop {
+        tokio::select! {
+            update = receiver.recv() => {
+                let text = match update {
+                    Ok(update) => serde_json::to_string(update.as_ref()).map_err(|e| e.to_string())?,
+                    Err(RecvError::Lagged(skipped)) => format!("{{\"kind\":\"lagged\",\"skipped\":{}}}", skipped),
+                    Err(RecvError::Closed) => return Ok(()),
+                };
+                socket.send(Message::Text(text)).await.map_err(|e| e.to_string())?;
+            }
+            message = socket.recv() => match message {
+                Some(Ok(Message::Close(_))) | None => return Ok(()),
+                Some(Err(e)) => return Err(e.to_string()),
+                // Pings are answered by axum; anything else from the client is ignored
+                Some(Ok(_)) => {}
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    const TOKEN: &str = "secret";
+
+    /// A server on an ephemeral localhost port; its base URL
+    async fn serve(config: StatusApiConfig) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let updates = Arc::new(PositionUpdates::new(16, Duration::ZERO, 0.0));
+        let app = router(config, updates, json!({ "wallet": "wallet-pubkey", "dry_run": true }));
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+        format!("http://{}", addr)
+    }
+
+    fn guarded() -> StatusApiConfig {
+        StatusApiConfig { token: Some(TOKEN.to_string()), dashboard: true }
+    }
+
+    async fn get(url: &str, headers: &[(&str, &str)]) -> reqwest::Response {
+        let request = headers.iter().fold(reqwest::Client::new().get(url), |request, (name, value)| request.header(*name, *value));
+        request.send().await.unwrap()
+    }
+
+    fn bearer(token: &str) -> (&'static str, String) {
+        ("authorization", format!("Bearer {}", token))
+    }
+
+    #[tokio::test]
+    async fn the_dashboard_page_is_served_when_enabled() {
+        let base = serve(guarded()).await;
+        let page = get(&format!("{}{}", base, DASHBOARD_PATH), &[]).await;
+        assert_eq!(page.status(), 200);
+        assert!(page.headers()["content-type"].to_str().unwrap().starts_with("text/html"));
+        assert_eq!(page.text().await.unwrap(), DASHBOARD_HTML);
+
+        let disabled = serve(StatusApiConfig::default()).await;
+        assert_eq!(get(&format!("{}{}", disabled, DASHBOARD_PATH), &[]).await.status(), 404);
+    }
+
+    #[test]
+    fn the_dashboard_only_fetches_served_endpoints() {
+        assert!(dashboard_unknown_endpoints().is_empty(), "{:?}", dashboard_unknown_endpoints());
+        for path in API_PATHS {
+            assert!(DASHBOARD_HTML.contains(&format!("\"{}\"", path)), "the page never fetches {}", path);
+        }
+    }
+
+    #[tokio::test]
+    async fn every_endpoint_answers_json_with_the_token() {
+        let base = serve(guarded()).await;
+        let (name, value) = bearer(TOKEN);
+        for path in API_PATHS.iter().chain(STATUS_PATHS) {
+            let response = get(&format!("{}{}", base, path), &[(name, &value)]).await;
+            assert_eq!(response.status(), 200, "{}", path);
+            assert_eq!(response.headers()["cache-control"], "no-store");
+            assert!(response.headers().get("access-control-allow-origin").is_none());
+            let body: serde_json::Value = response.json().await.unwrap();
+            assert!(body.is_object() || body.is_array(), "{} answered {}", path, body);
+        }
+
+        let health: serde_json::Value = get(&format!("{}/api/health", base), &[(name, &value)]).await.json().await.unwrap();
+        assert!(health["stream"].is_object() && health["rpc"].is_array(), "{}", health);
+        let config: serde_json::Value = get(&format!("{}/config", base), &[(name, &value)]).await.json().await.unwrap();
+        assert_eq!((config["wallet"].as_str(), config["dry_run"].as_bool()), (Some("wallet-pubkey"), Some(true)));
+        assert!(config.get("sol_usd").is_some());
+    }
+
+    #[tokio::test]
+    async fn the_token_is_only_taken_from_the_authorization_header() {
+        let base = serve(guarded()).await;
+        let missing = get(&format!("{}/holdings", base), &[]).await;
+        assert_eq!(missing.status(), 401);
+        assert_eq!(missing.headers()["www-authenticate"], "Bearer");
+
+        let (name, wrong) = bearer("secres");
+        assert_eq!(get(&format!("{}/api/trades", base), &[(name, &wrong)]).await.status(), 401);
+        let (_, longer) = bearer("secret-and-more");
+        assert_eq!(get(&format!("{}/api/trades", base), &[(name, &longer)]).await.status(), 401);
+        assert_eq!(get(&format!("{}/api/trades?token={}", base, TOKEN), &[]).await.status(), 401);
+        assert_eq!(get(&format!("{}{}?token={}", base, POSITIONS_WS_PATH, TOKEN), &[]).await.status(), 401);
+
+        // Without a configured token everything is open
+        let open = serve(StatusApiConfig::default()).await;
+        assert_eq!(get(&format!("{}/rejections", open), &[]).await.status(), 200);
+    }
+
+    #[tokio::test]
+    async fn cross_origin_other_methods_and_unknown_paths_are_refused() {
+        let base = serve(guarded()).await;
+        let host = base.trim_start_matches("http://").to_string();
+        let (name, value) = bearer(TOKEN);
+
+        let cross = get(&format!("{}/api/summary", base), &[(name, &value), ("origin", "https://evil.example")]).await;
+        assert_eq!(cross.status(), 403);
+        let same_origin = format!("http://{}", host);
+        assert_eq!(get(&format!("{}/api/summary", base), &[(name, &value), ("origin", &same_origin)]).await.status(), 200);
+        assert_eq!(get(&format!("{}/api/summary", base), &[(name, &value), ("sec-fetch-site", "cross-site")]).await.status(), 403);
+
+        let post = reqwest::Client::new().post(format!("{}/api/summary", base)).header(name, &value).send().await.unwrap();
+        assert_eq!(post.status(), 405);
+        assert_eq!(get(&format!("{}/api/unknown", base), &[(name, &value)]).await.status(), 404);
+    }
+
+    #[test]
+    fn authorization_needs_the_exact_bearer_token() {
+        let config = guarded();
+        let with = |value: &str| {
+            let mut headers = HeaderMap::new();
+            headers.insert("authorization", HeaderValue::from_str(value).unwrap());
+            headers
+        };
+        assert!(config.authorized(&with("Bearer secret")));
+        assert!(!config.authorized(&with("Bearer secre")));
+        assert!(!config.authorized(&with("Basic secret")));
+        assert!(!config.authorized(&HeaderMap::new()));
+        assert!(StatusApiConfig::default().authorized(&HeaderMap::new()));
+    }
+}
@@ -32,9 +32,27 @@ pub async fn start_task_monitor() {
         let logger = Logger::new("[TASK-MONITOR] => ".cyan().bold().to_string());
         
         // Log removed for performance - only zombie tasks logged
+        let mut reported_failovers = 0;
         
         loop {
-            interval.tick().await;
+            tokio::select! {
+                _ = interval.tick() => {}
+                _ = crate::common::shutdown::cancelled() => break,
+            }
+            
+            // Zeroslot sends that had to move on to another endpoint since the last tick
+            let failovers = crate::services::zeroslot_pool::failover_count();
+            if failovers > reported_failovers {
+                logger.critical(format!("{} zeroslot failover(s) in the last 5 minutes ({} total)", failovers - reported_failovers, failovers));
+                reported_failovers = failovers;
+            }
+            
+            // The pipeline's stream reconnects itself; a long outage is still worth a line here
+            let stream = crate::engine::event_stream::stream_health();
+            if let Some(state) = stream.state.filter(|_| !stream.is_connected()) {
+                let silent_secs = stream.last_message_at.map(|at| crate::common::clock::now_secs().saturating_sub(at)).unwrap_or(0);
+                logger.error(format!("gRPC stream {} ({} failed reconnects, last message {}s ago)", state.as_str(), stream.consecutive_failures, silent_secs).yellow().to_string());
+            }
             
             let zombie_threshold = Duration::from_secs(600); // 10 minutes
             
@@ -1,59 +1,359 @@
-use once_cell::sync::Lazy;
+use async_trait::async_trait;
+use once_cell::sync::{Lazy, OnceCell};
 use teloxide::prelude::*;
+use teloxide::types::{InputFile, ParseMode};
+use teloxide::RequestError;
+use std::collections::VecDeque;
 use std::env;
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::sync::mpsc;
+
+use crate::services::telegram_chunks::TELEGRAM_MAX_MESSAGE_LEN;
 
 static BOT_TOKEN: Lazy<Option<String>> = Lazy::new(|| env::var("TELEGRAM_BOT_TOKEN").ok());
 static CHAT_ID: Lazy<Option<i64>> = Lazy::new(|| env::var("TELEGRAM_CHAT_ID").ok().and_then(|v| v.parse::<i64>().ok()));
+// One bot (and HTTP connection pool) for every message instead of a new one per send
+static BOT: Lazy<Option<Bot>> = Lazy::new(|| BOT_TOKEN.clone().map(Bot::new));
+static SEND_QUEUE: Lazy<SendQueue> = Lazy::new(|| SendQueue::new(SendLimiter::from_env(now())));
+static DRY_RUN: AtomicBool = AtomicBool::new(false);
 
-pub async fn send_message_async(text: String) -> Result<(), String> {
-    let Some(token) = BOT_TOKEN.clone() else {
-        let err = "TELEGRAM_BOT_TOKEN not configured".to_string();
-        // Log removed for performance
-        return Err(err);
+/// Messages the queue holds; past that new ones are dropped rather than held in memory
+const QUEUE_CAPACITY: usize = 500;
+/// Sends of one message (or batch) before it is given up, flood-control waits included
+const MAX_SEND_ATTEMPTS: u32 = 4;
+const BATCH_SEPARATOR: &str = "\n\n──────────\n\n";
+
+/// Started by the first enqueue, so it always runs inside the runtime
+static QUEUE: OnceCell<mpsc::Sender<QueuedMessage>> = OnceCell::new();
+
+/// Mark every message from now on as coming from a dry run (DRY_RUN)
+pub fn set_dry_run(enabled: bool) {
+    DRY_RUN.store(enabled, Ordering::Relaxed);
+}
+
+/// `text` as it goes out: prefixed with "[DRY RUN]" in dry-run mode so paper fills can't pass for real ones
+pub fn outgoing(text: String) -> String {
+    if DRY_RUN.load(Ordering::Relaxed) {
+        format!("[DRY RUN] {}", text)
+    } else {
+        text
+    }
+}
+
+/// Pacing of everything sent to the chat: a minimum spacing plus a per-minute token bucket, with Telegram's
+/// flood-control pauses on top
+/// TELEGRAM_MIN_SEND_INTERVAL_MS (default: 1000), TELEGRAM_MAX_MESSAGES_PER_MINUTE (default: 20)
+#[derive(Clone, Debug)]
+pub struct SendLimiter {
+    min_interval: Duration,
+    per_minute: f64,
+    tokens: f64,
+    /// When `tokens` was last brought up to date; ahead of now once slots are reserved in advance
+    refilled_at: Instant,
+    next_at: Instant,
+}
+
+impl SendLimiter {
+    pub fn new(min_interval: Duration, per_minute: u32, now: Instant) -> Self {
+        let per_minute = f64::from(per_minute.max(1));
+        Self { min_interval, per_minute, tokens: per_minute, refilled_at: now, next_at: now }
+    }
+
+    pub fn from_env(now: Instant) -> Self {
+        let min_interval_ms = env::var("TELEGRAM_MIN_SEND_INTERVAL_MS").ok().and_then(|v| v.parse::<u64>().ok()).unwrap_or(1000);
+        let per_minute = env::var("TELEGRAM_MAX_MESSAGES_PER_MINUTE").ok().and_then(|v| v.parse::<u32>().ok()).unwrap_or(20);
+        Self::new(Duration::from_millis(min_interval_ms), per_minute, now)
+    }
+
+    fn refill(&mut self, at: Instant) {
+        if at > self.refilled_at {
+            let earned = at.duration_since(self.refilled_at).as_secs_f64() * self.per_minute / 60.0;
+            self.tokens = (self.tokens + earned).min(self.per_minute);
+            self.refilled_at = at;
+        }
+    }
+
+    /// Take the first free send slot at or after `now`; when it is
+    pub fn reserve(&mut self, now: Instant) -> Instant {
+        let mut slot = self.next_at.max(now);
+        self.refill(slot);
+        if self.tokens < 1.0 {
+            slot += Duration::from_secs_f64((1.0 - self.tokens) * 60.0 / self.per_minute);
+            self.refilled_at = slot;
+            self.tokens = 1.0;
+        }
+        self.tokens -= 1.0;
+        self.next_at = slot + self.min_interval;
+        slot
+    }
+
+    /// Hold every send until `until`, e.g. the retry-after of a 429
+    pub fn pause_until(&mut self, until: Instant) {
+        self.next_at = self.next_at.max(until);
+    }
+}
+
+/// The runtime's clock, so the pacing follows tokio's paused time in tests
+fn now() -> Instant {
+    tokio::time::Instant::now().into_std()
+}
+
+/// Wait for the next send slot; queued sends, edits and replies all share it
+pub async fn wait_for_send_slot() {
+    SEND_QUEUE.wait_for_slot().await;
+}
+
+/// Shared bot and target chat, or why Telegram isn't usable
+pub fn bot_and_chat() -> Result<(Bot, ChatId), String> {
+    let Some(bot) = BOT.clone() else {
+        return Err("TELEGRAM_BOT_TOKEN not configured".to_string());
     };
-    let Some(chat_id) = CHAT_ID.clone() else {
-        let err = "TELEGRAM_CHAT_ID not configured".to_string();
-        // Log removed for performance
-        return Err(err);
+    let Some(chat_id) = *CHAT_ID else {
+        return Err("TELEGRAM_CHAT_ID not configured".to_string());
     };
-    
-    let bot = Bot::new(token);
-    
-    match bot.send_message(ChatId(chat_id), text).await {
-        Ok(_) => Ok(()),
-        Err(e) => {
-            let err_msg = format!("Failed to send message: {}", e);
-            // Critical error - keep this log
-            eprintln!("[TELEGRAM] {}", err_msg);
-            Err(err_msg)
+    Ok((bot, ChatId(chat_id)))
+}
+
+/// Why a queued send failed
+#[derive(Clone, Debug, PartialEq)]
+pub enum SendFailure {
+    /// Flood control (429): nothing may be sent to the chat for this long
+    RetryAfter(Duration),
+    Failed(String),
+}
+
+/// One delivery the queue holds, in the order it was enqueued
+#[derive(Clone, Debug, PartialEq)]
+pub enum QueuedMessage {
+    Text { text: String, html: bool },
+    Document { file_name: String, contents: Vec<u8>, caption: String },
+}
+
+impl QueuedMessage {
+    fn plain(text: String) -> Self {
+        QueuedMessage::Text { text, html: false }
+    }
+}
+
+/// Where the queue delivers; the bot live, a recorder in harnesses
+#[async_trait]
+pub trait MessageSender: Send + Sync {
+    async fn send(&self, message: QueuedMessage) -> Result<(), SendFailure>;
+}
+
+/// Sends through the shared bot to TELEGRAM_CHAT_ID
+pub struct BotSender;
+
+#[async_trait]
+impl MessageSender for BotSender {
+    async fn send(&self, message: QueuedMessage) -> Result<(), SendFailure> {
+        let (bot, chat_id) = bot_and_chat().map_err(SendFailure::Failed)?;
+        let result = match message {
+            QueuedMessage::Text { text, html } => {
+                let request = bot.send_message(chat_id, text);
+                let request = if html { request.parse_mode(ParseMode::Html) } else { request };
+                request.await.map(|_| ())
+            }
+            QueuedMessage::Document { file_name, contents, caption } => bot
+                .send_document(chat_id, InputFile::memory(contents).file_name(file_name))
+                .caption(caption)
+                .await
+                .map(|_| ()),
+        };
+        match result {
+            Ok(()) => Ok(()),
+            Err(RequestError::RetryAfter(wait)) => Err(SendFailure::RetryAfter(wait)),
+            Err(e) => Err(SendFailure::Failed(e.to_string())),
         }
     }
 }
 
-/// Send message with retry logic
-pub async fn send_message_with_retry(text: String, max_retries: u32) -> Result<(), String> {
-    let mut last_error = None;
-    
-    for attempt in 1..=max_retries {
-        match send_message_async(text.clone()).await {
-            Ok(_) => {
-                if attempt > 1 {
-                    // Log removed for performance
+/// Join messages off the front of `pending` into one of at most `max_len` UTF-16 units, with how many it holds
+/// The first message always goes, whole, even when it alone is over the limit. Only texts of the same format
+/// are joined; a document, or a change between plain and HTML, ends the batch.
+pub fn take_batch(pending: &mut VecDeque<QueuedMessage>, max_len: usize) -> Option<(QueuedMessage, usize)> {
+    let len = |text: &str| text.encode_utf16().count();
+    let first = pending.pop_front()?;
+    let QueuedMessage::Text { text: mut batch, html } = first else {
+        return Some((first, 1));
+    };
+    let mut batch_len = len(&batch);
+    let mut count = 1;
+    while let Some(QueuedMessage::Text { text: next, html: next_html }) = pending.front() {
+        let joined_len = batch_len + len(BATCH_SEPARATOR) + len(next);
+        if *next_html != html || joined_len > max_len {
+            break;
+        }
+        batch.push_str(BATCH_SEPARATOR);
+        batch.push_str(next);
+        batch_len = joined_len;
+        count += 1;
+        pending.pop_front();
+    }
+    Some((QueuedMessage::Text { text: batch, html }, count))
+}
+
+/// Pacing and counters of the outgoing queue: one for the process, its own in each harness
+pub struct SendQueue {
+    limiter: Mutex<SendLimiter>,
+    /// Messages enqueued and not yet delivered or given up
+    pending: AtomicUsize,
+    dropped: AtomicU64,
+}
+
+impl SendQueue {
+    pub fn new(limiter: SendLimiter) -> Self {
+        Self { limiter: Mutex::new(limiter), pending: AtomicUsize::new(0), dropped: AtomicU64::new(0) }
+    }
+
+    async fn wait_for_slot(&self) {
+        let slot = {
+            let Ok(mut limiter) = self.limiter.lock() else {
+                return;
+            };
+            limiter.reserve(now())
+        };
+        tokio::time::sleep_until(slot.into()).await;
+    }
+
+    fn pause(&self, wait: Duration) {
+        if let Ok(mut limiter) = self.limiter.lock() {
+            limiter.pause_until(now() + wait);
+        }
+    }
+
+    /// Hand `message` to the queue's channel, counting it as pending; Err when the channel is full or closed
+    pub fn push(&self, queue: &mpsc::Sender<QueuedMessage>, message: QueuedMessage) -> Result<(), String> {
+        self.pending.fetch_add(1, Ordering::SeqCst);
+        if let Err(e) = queue.try_send(message) {
+            self.pending.fetch_sub(1, Ordering::SeqCst);
+            self.dropped.fetch_add(1, Ordering::Relaxed);
+            return Err(match e {
+                mpsc::error::TrySendError::Full(_) => format!("Telegram queue full ({} messages), message dropped", QUEUE_CAPACITY),
+                mpsc::error::TrySendError::Closed(_) => "Telegram queue closed, message dropped".to_string(),
+            });
+        }
+        Ok(())
+    }
+
+    /// Deliver queued messages one send slot at a time; whatever piled up while waiting for a slot goes out combined
+    pub async fn run(&self, mut receiver: mpsc::Receiver<QueuedMessage>, sender: Arc<dyn MessageSender>) {
+        let mut pending: VecDeque<QueuedMessage> = VecDeque::new();
+        loop {
+            if pending.is_empty() {
+                match receiver.recv().await {
+                    Some(message) => pending.push_back(message),
+                    None => return,
                 }
-                return Ok(());
             }
-            Err(e) => {
-                last_error = Some(e.clone());
-                if attempt < max_retries {
-                    let delay_ms = 500 * attempt; // Exponential backoff: 500ms, 1000ms, 1500ms...
-                    // Log removed for performance
-                    tokio::time::sleep(tokio::time::Duration::from_millis(delay_ms as u64)).await;
+            self.wait_for_slot().await;
+            while let Ok(message) = receiver.try_recv() {
+                pending.push_back(message);
+            }
+            let Some((message, count)) = take_batch(&mut pending, TELEGRAM_MAX_MESSAGE_LEN) else {
+                continue;
+            };
+            self.deliver(sender.as_ref(), message, count).await;
+            self.pending.fetch_sub(count, Ordering::SeqCst);
+        }
+    }
+
+    /// Send with retries: a 429 pauses every send for its retry-after, other failures back off 500ms, 1s, ...
+    async fn deliver(&self, sender: &dyn MessageSender, message: QueuedMessage, count: usize) {
+        for attempt in 1..=MAX_SEND_ATTEMPTS {
+            let error = match sender.send(message.clone()).await {
+                Ok(()) => return,
+                Err(SendFailure::RetryAfter(wait)) => {
+                    self.pause(wait);
+                    format!("rate limited, retry after {}s", wait.as_secs())
+                }
+                Err(SendFailure::Failed(e)) => {
+                    if attempt < MAX_SEND_ATTEMPTS {
+                        tokio::time::sleep(Duration::from_millis(500 * u64::from(attempt))).await;
+                    }
+                    e
                 }
+            };
+            if attempt == MAX_SEND_ATTEMPTS {
+                // Critical error - keep this log
+                eprintln!("[TELEGRAM] Dropping {} message(s) after {} attempts: {}", count, MAX_SEND_ATTEMPTS, error);
+                self.dropped.fetch_add(count as u64, Ordering::Relaxed);
+                return;
             }
+            self.wait_for_slot().await;
         }
     }
-    
-    Err(last_error.unwrap_or_else(|| "All retry attempts failed".to_string()))
+
+    /// Messages queued and not yet delivered, and messages dropped
+    pub fn stats(&self) -> (usize, u64) {
+        (self.pending.load(Ordering::SeqCst), self.dropped.load(Ordering::Relaxed))
+    }
+
+    /// Wait until everything queued has been delivered (or given up on), or `timeout` passes; the messages still
+    /// pending
+    pub async fn flush(&self, timeout: Duration) -> usize {
+        let deadline = now() + timeout;
+        loop {
+            let pending = self.pending.load(Ordering::SeqCst);
+            if pending == 0 || now() >= deadline {
+                return pending;
+            }
+            tokio::time::sleep(Duration::from_millis(50)).await;
+        }
+    }
+}
+
+/// Deliver queued messages through the process-wide pacing, see `SendQueue::run`
+pub async fn run_queue(receiver: mpsc::Receiver<QueuedMessage>, sender: Arc<dyn MessageSender>) {
+    SEND_QUEUE.run(receiver, sender).await;
+}
+
+/// Queue `text` for the background sender without waiting; Err when Telegram isn't configured or the queue is full
+pub fn enqueue(text: String) -> Result<(), String> {
+    enqueue_message(QueuedMessage::plain(text))
+}
+
+/// `enqueue` for any message, HTML texts and documents included
+pub fn enqueue_message(message: QueuedMessage) -> Result<(), String> {
+    let _ = bot_and_chat()?;
+    let message = match message {
+        QueuedMessage::Text { text, html } => QueuedMessage::Text { text: outgoing(text), html },
+        QueuedMessage::Document { file_name, contents, caption } => {
+            QueuedMessage::Document { file_name, contents, caption: outgoing(caption) }
+        }
+    };
+    let queue = QUEUE.get_or_init(|| {
+        let (sender, receiver) = mpsc::channel(QUEUE_CAPACITY);
+        tokio::spawn(run_queue(receiver, Arc::new(BotSender)));
+        sender
+    });
+    SEND_QUEUE.push(queue, message).inspect_err(|err_msg| eprintln!("[TELEGRAM] {}", err_msg))
+}
+
+/// Non-blocking: the message is queued and sent in the background, see `enqueue`
+pub async fn send_message_async(text: String) -> Result<(), String> {
+    enqueue(text)
+}
+
+/// Messages queued and not yet delivered, and messages dropped since startup
+pub fn queue_stats() -> (usize, u64) {
+    SEND_QUEUE.stats()
+}
+
+fn telegram_status_section() -> String {
+    let (pending, dropped) = queue_stats();
+    format!("Telegram: {} message(s) queued, {} dropped", pending, dropped)
+}
+
+pub fn register_status_section() {
+    crate::services::telegram_commands::register_status_section("telegram", telegram_status_section);
+}
+
+/// Wait until the queue has delivered (or given up on) everything, or `timeout` passes; the messages still pending
+pub async fn flush(timeout: Duration) -> usize {
+    SEND_QUEUE.flush(timeout).await
 }
 
 pub fn format_sell_message(mint: &str, received_sol: f64, price: f64, reason: &str, signature: &str, protocol: &str, token_age_secs: Option<u64>) -> String {
@@ -132,6 +432,16 @@ pub fn format_buy_message(mint: &str, spent_sol: f64, price: f64, reason: &str,
     )
 }
 
+/// Open the bot's connection with a getMe call (no-op when Telegram isn't configured)
+pub async fn warm_up() -> Result<(), String> {
+    let Some(bot) = BOT.clone() else {
+        return Ok(());
+    };
+    bot.get_me().await
+        .map(|_| ())
+        .map_err(|e| format!("Telegram warm-up failed: {}", e))
+}
+
 /// Check if Telegram is properly configured
 pub fn is_configured() -> bool {
     BOT_TOKEN.is_some() && CHAT_ID.is_some()
@@ -157,3 +467,163 @@ pub fn log_config_status() {
         // Log removed - configuration check
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use tokio::task::JoinHandle;
+
+    use super::*;
+
+    fn text(text: &str, html: bool) -> QueuedMessage {
+        QueuedMessage::Text { text: text.to_string(), html }
+    }
+
+    #[test]
+    fn batches_keep_formats_and_documents_apart() {
+        let document = QueuedMessage::Document { file_name: "report.txt".to_string(), contents: b"rows".to_vec(), caption: "Report".to_string() };
+        let mut pending: VecDeque<QueuedMessage> = vec![
+            text("[1/2]\n<pre>a</pre>", true),
+            text("[2/2]\n<pre>b</pre>", true),
+            text("plain", false),
+            document.clone(),
+            text("after", false),
+        ].into();
+
+        let joined = format!("[1/2]\n<pre>a</pre>{}[2/2]\n<pre>b</pre>", BATCH_SEPARATOR);
+        assert_eq!(take_batch(&mut pending, TELEGRAM_MAX_MESSAGE_LEN), Some((text(&joined, true), 2)));
+        assert_eq!(take_batch(&mut pending, TELEGRAM_MAX_MESSAGE_LEN), Some((text("plain", false), 1)));
+        assert_eq!(take_batch(&mut pending, TELEGRAM_MAX_MESSAGE_LEN), Some((document, 1)));
+        assert_eq!(take_batch(&mut pending, TELEGRAM_MAX_MESSAGE_LEN), Some((text("after", false), 1)));
+        assert_eq!(take_batch(&mut pending, TELEGRAM_MAX_MESSAGE_LEN), None);
+    }
+
+    fn document(name: &str) -> QueuedMessage {
+        QueuedMessage::Document { file_name: name.to_string(), contents: Vec::new(), caption: String::new() }
+    }
+
+    /// Records every send attempt with when it was made; answers from `failures` first, then succeeds
+    struct RecordingSender {
+        started: tokio::time::Instant,
+        failures: Mutex<VecDeque<SendFailure>>,
+        attempts: Mutex<Vec<(Duration, QueuedMessage, bool)>>,
+    }
+
+    impl RecordingSender {
+        fn new(failures: Vec<SendFailure>) -> Arc<Self> {
+            Arc::new(Self { started: tokio::time::Instant::now(), failures: Mutex::new(failures.into()), attempts: Mutex::new(Vec::new()) })
+        }
+
+        /// (seconds since start, message, delivered) of every attempt
+        fn attempts(&self) -> Vec<(u64, QueuedMessage, bool)> {
+            self.attempts.lock().unwrap().iter().map(|(at, message, ok)| (at.as_secs(), message.clone(), *ok)).collect()
+        }
+    }
+
+    #[async_trait]
+    impl MessageSender for RecordingSender {
+        async fn send(&self, message: QueuedMessage) -> Result<(), SendFailure> {
+            let failure = self.failures.lock().unwrap().pop_front();
+            self.attempts.lock().unwrap().push((self.started.elapsed(), message, failure.is_none()));
+            failure.map_or(Ok(()), Err)
+        }
+    }
+
+    fn queue(min_interval_secs: u64, per_minute: u32) -> Arc<SendQueue> {
+        Arc::new(SendQueue::new(SendLimiter::new(Duration::from_secs(min_interval_secs), per_minute, now())))
+    }
+
+    fn spawn_run(queue: &Arc<SendQueue>, sender: &Arc<RecordingSender>) -> (mpsc::Sender<QueuedMessage>, JoinHandle<()>) {
+        let (channel, receiver) = mpsc::channel(QUEUE_CAPACITY);
+        let (queue, sender) = (queue.clone(), sender.clone());
+        (channel, tokio::spawn(async move { queue.run(receiver, sender).await }))
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn sends_are_spaced_and_held_to_the_per_minute_budget() {
+        let queue = queue(1, 3);
+        let sender = RecordingSender::new(Vec::new());
+        let (channel, run) = spawn_run(&queue, &sender);
+        for name in ["a", "b", "c", "d"] {
+            queue.push(&channel, document(name)).unwrap();
+        }
+        drop(channel);
+        run.await.unwrap();
+
+        // Three from the full bucket a second apart, then the fourth waits for a whole token to refill
+        let times: Vec<u64> = sender.attempts().into_iter().map(|(at, _, _)| at).collect();
+        assert_eq!(times, vec![0, 1, 2, 20]);
+        assert_eq!(queue.stats(), (0, 0));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn messages_piling_up_behind_a_slot_go_out_as_one() {
+        let queue = queue(1, 60);
+        let sender = RecordingSender::new(Vec::new());
+        let (channel, run) = spawn_run(&queue, &sender);
+        queue.push(&channel, text("first", false)).unwrap();
+        tokio::time::sleep(Duration::from_millis(10)).await;
+
+        // These arrive while the queue waits for the next slot
+        queue.push(&channel, text("second", false)).unwrap();
+        queue.push(&channel, text("third", false)).unwrap();
+        queue.push(&channel, text("<b>html</b>", true)).unwrap();
+        drop(channel);
+        run.await.unwrap();
+
+        assert_eq!(sender.attempts(), vec![
+            (0, text("first", false), true),
+            (1, text(&format!("second{}third", BATCH_SEPARATOR), false), true),
+            (2, text("<b>html</b>", true), true),
+        ]);
+        assert_eq!(queue.stats(), (0, 0));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn flood_control_holds_every_send_for_the_retry_after() {
+        let queue = queue(1, 60);
+        let sender = RecordingSender::new(vec![SendFailure::RetryAfter(Duration::from_secs(30))]);
+        let (channel, run) = spawn_run(&queue, &sender);
+        queue.push(&channel, text("limited", false)).unwrap();
+        queue.push(&channel, document("next")).unwrap();
+        drop(channel);
+        run.await.unwrap();
+
+        assert_eq!(sender.attempts(), vec![
+            (0, text("limited", false), false),
+            (30, text("limited", false), true),
+            (31, document("next"), true),
+        ]);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn flush_waits_out_retries_and_reports_what_is_left() {
+        let queue = queue(1, 60);
+        let failures = (0..MAX_SEND_ATTEMPTS).map(|attempt| SendFailure::Failed(format!("attempt {}", attempt))).collect();
+        let sender = RecordingSender::new(failures);
+        let (channel, _run) = spawn_run(&queue, &sender);
+        queue.push(&channel, document("lost")).unwrap();
+        queue.push(&channel, document("kept")).unwrap();
+        assert_eq!(queue.stats(), (2, 0));
+
+        // The first message's retries back off 0.5s, 1s and 1.5s on top of the slot spacing
+        assert_eq!(queue.flush(Duration::from_secs(2)).await, 2);
+        assert_eq!(queue.flush(Duration::from_secs(60)).await, 0);
+
+        let attempts = sender.attempts();
+        assert_eq!(attempts.len(), MAX_SEND_ATTEMPTS as usize + 1);
+        assert!(attempts[..MAX_SEND_ATTEMPTS as usize].iter().all(|(_, message, ok)| *message == document("lost") && !ok));
+        assert_eq!(attempts.last().map(|(_, message, ok)| (message.clone(), *ok)), Some((document("kept"), true)));
+        assert_eq!(queue.stats(), (0, 1));
+    }
+
+    #[tokio::test]
+    async fn a_full_or_closed_channel_drops_the_message() {
+        let queue = queue(1, 60);
+        let (channel, receiver) = mpsc::channel(1);
+        queue.push(&channel, text("held", false)).unwrap();
+        assert!(queue.push(&channel, text("overflow", false)).unwrap_err().contains("queue full"));
+        drop(receiver);
+        assert!(queue.push(&channel, text("late", false)).unwrap_err().contains("queue closed"));
+        assert_eq!(queue.stats(), (1, 2));
+    }
+}
@@ -0,0 +1,222 @@
+use std::sync::Mutex;
+use std::time::Duration;
+use async_trait::async_trait;
+
+use crate::services::telegram::{self, QueuedMessage};
+
+/// Telegram's per-message limit, counted in UTF-16 code units
+pub const TELEGRAM_MAX_MESSAGE_LEN: usize = 4096;
+
+/// Room left per part for the "[i/n]" prefix
+const PART_PREFIX_RESERVE: usize = 16;
+const PRE_OPEN: &str = "<pre>";
+const PRE_CLOSE: &str = "</pre>";
+
+/// How each part is rendered
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ChunkFormat {
+    Plain,
+    /// Each part in its own `<pre>` block so column alignment survives
+    Monospace,
+}
+
+/// Where long messages go; the bot live, a recorder in harnesses
+#[async_trait]
+pub trait TelegramTransport: Send + Sync {
+    async fn send_text(&self, text: String, html: bool) -> Result<(), String>;
+
+    async fn send_document(&self, file_name: String, contents: Vec<u8>, caption: String) -> Result<(), String>;
+}
+
+/// Sends to TELEGRAM_CHAT_ID through the rate-limited queue, which keeps the parts in order
+pub struct BotTransport;
+
+#[async_trait]
+impl TelegramTransport for BotTransport {
+    async fn send_text(&self, text: String, html: bool) -> Result<(), String> {
+        telegram::enqueue_message(QueuedMessage::Text { text, html })
+    }
+
+    async fn send_document(&self, file_name: String, contents: Vec<u8>, caption: String) -> Result<(), String> {
+        telegram::enqueue_message(QueuedMessage::Document { file_name, contents, caption })
+    }
+}
+
+/// Something a transport was asked to deliver
+#[derive(Clone, Debug, PartialEq)]
+pub enum SentItem {
+    Text { text: String, html: bool },
+    Document { file_name: String, contents: Vec<u8>, caption: String },
+}
+
+/// Records every send in memory (harnesses)
+#[derive(Default)]
+pub struct MemoryTransport {
+    sent: Mutex<Vec<SentItem>>,
+}
+
+impl MemoryTransport {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn sent(&self) -> Vec<SentItem> {
+        self.sent.lock().map(|sent| sent.clone()).unwrap_or_default()
+    }
+
+    fn push(&self, item: SentItem) {
+        if let Ok(mut sent) = self.sent.lock() {
+            sent.push(item);
+        }
+    }
+}
+
+#[async_trait]
+impl TelegramTransport for MemoryTransport {
+    async fn send_text(&self, text: String, html: bool) -> Result<(), String> {
+        self.push(SentItem::Text { text, html });
+        Ok(())
+    }
+
+    async fn send_document(&self, file_name: String, contents: Vec<u8>, caption: String) -> Result<(), String> {
+        self.push(SentItem::Document { file_name, contents, caption });
+        Ok(())
+    }
+}
+
+/// Limits for long messages
+#[derive(Clone, Debug)]
+pub struct LongMessagePolicy {
+    /// Reports longer than this (UTF-16 units) go out as one document instead of many parts
+    pub document_threshold: usize,
+    /// Pause between parts to stay under Telegram's per-chat rate limit
+    pub part_delay: Duration,
+}
+
+impl LongMessagePolicy {
+    /// TELEGRAM_DOCUMENT_THRESHOLD_CHARS (default: 16000) and TELEGRAM_PART_DELAY_MS (default: 1000)
+    pub fn from_env() -> Self {
+        let document_threshold = std::env::var("TELEGRAM_DOCUMENT_THRESHOLD_CHARS")
+            .ok()
+            .and_then(|v| v.parse::<usize>().ok())
+            .unwrap_or(16_000);
+        let part_delay_ms = std::env::var("TELEGRAM_PART_DELAY_MS")
+            .ok()
+            .and_then(|v| v.parse::<u64>().ok())
+            .unwrap_or(1_000);
+        Self { document_threshold, part_delay: Duration::from_millis(part_delay_ms) }
+    }
+}
+
+fn utf16_len(text: &str) -> usize {
+    text.chars().map(char::len_utf16).sum()
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;")
+}
+
+/// Cost of a char once rendered in `format`
+fn rendered_len(c: char, format: ChunkFormat) -> usize {
+    match (format, c) {
+        (ChunkFormat::Monospace, '&') => 5,
+        (ChunkFormat::Monospace, '<') | (ChunkFormat::Monospace, '>') => 4,
+        _ => c.len_utf16(),
+    }
+}
+
+/// Split `text` into parts whose rendered length is at most `limit`
+/// Prefers blank lines (section breaks), then line breaks; a single line longer than the limit is cut at a
+/// char boundary, so multibyte characters are never split.
+pub fn split_message(text: &str, limit: usize, format: ChunkFormat) -> Vec<String> {
+    let limit = limit.max(1);
+    let cost = |s: &str| s.chars().map(|c| rendered_len(c, format)).sum::<usize>();
+
+    let mut parts = Vec::new();
+    let mut rest = text.trim_end_matches('\n');
+    while !rest.is_empty() {
+        if cost(rest) <= limit {
+            parts.push(rest.to_string());
+            break;
+        }
+
+        // Longest prefix that fits, as a byte index on a char boundary
+        let mut fit = 0;
+        let mut used = 0;
+        for (index, c) in rest.char_indices() {
+            let width = rendered_len(c, format);
+            if used + width > limit {
+                break;
+            }
+            used += width;
+            fit = index + c.len_utf8();
+        }
+        let window = &rest[..fit];
+        let cut = window.rfind("\n\n")
+            .filter(|index| *index > 0)
+            .or_else(|| window.rfind('\n').filter(|index| *index > 0))
+            .unwrap_or(fit.max(rest.chars().next().map(char::len_utf8).unwrap_or(1)));
+
+        parts.push(rest[..cut].trim_end_matches('\n').to_string());
+        rest = rest[cut..].trim_start_matches('\n');
+    }
+    parts
+}
+
+/// Parts ready to send: numbered when there is more than one, wrapped for `format`
+pub fn render_parts(text: &str, format: ChunkFormat) -> Vec<String> {
+    let wrapper = match format {
+        ChunkFormat::Plain => 0,
+        ChunkFormat::Monospace => PRE_OPEN.len() + PRE_CLOSE.len(),
+    };
+    let parts = split_message(text, TELEGRAM_MAX_MESSAGE_LEN - PART_PREFIX_RESERVE - wrapper, format);
+    let total = parts.len();
+    parts
+        .into_iter()
+        .enumerate()
+        .map(|(index, part)| {
+            let body = match format {
+                ChunkFormat::Plain => part,
+                ChunkFormat::Monospace => format!("{}{}{}", PRE_OPEN, escape_html(&part), PRE_CLOSE),
+            };
+            if total > 1 {
+                format!("[{}/{}]\n{}", index + 1, total, body)
+            } else {
+                body
+            }
+        })
+        .collect()
+}
+
+/// Send `text` of any length: in order as numbered parts, or as a document with a one-line summary when it
+/// exceeds the policy's threshold. `title` names the document and heads the summary.
+pub async fn send_long_message_with(
+    transport: &dyn TelegramTransport,
+    policy: &LongMessagePolicy,
+    title: &str,
+    text: &str,
+    format: ChunkFormat,
+) -> Result<(), String> {
+    if utf16_len(text) > policy.document_threshold {
+        let file_name = format!("{}.txt", title.to_lowercase().replace(' ', "_"));
+        let caption = format!("{} ({} lines, attached)", title, text.lines().count());
+        return transport.send_document(file_name, text.as_bytes().to_vec(), caption).await;
+    }
+
+    let html = format == ChunkFormat::Monospace;
+    for (index, part) in render_parts(text, format).into_iter().enumerate() {
+        if index > 0 && !policy.part_delay.is_zero() {
+            tokio::time::sleep(policy.part_delay).await;
+        }
+        transport.send_text(part, html).await?;
+    }
+    Ok(())
+}
+
+/// `send_long_message_with` through the bot with the env policy; no-op when Telegram isn't configured
+pub async fn send_long_message(title: &str, text: &str, format: ChunkFormat) -> Result<(), String> {
+    if !telegram::is_configured() {
+        return Ok(());
+    }
+    send_long_message_with(&BotTransport, &LongMessagePolicy::from_env(), title, text, format).await
+}
@@ -0,0 +1,113 @@
+use std::env;
+use colored::Colorize;
+use dashmap::DashMap;
+use once_cell::sync::Lazy;
+use teloxide::prelude::*;
+use teloxide::types::UpdateKind;
+
+use crate::common::logger::Logger;
+
+/// Handler for one bot command; gets the text after the command and returns the reply
+pub type CommandHandler = fn(&str) -> String;
+
+/// Produces one section of the /status reply
+pub type StatusSection = fn() -> String;
+
+static COMMANDS: Lazy<DashMap<String, CommandHandler>> = Lazy::new(DashMap::new);
+static STATUS_SECTIONS: Lazy<DashMap<String, StatusSection>> = Lazy::new(DashMap::new);
+
+/// Register a command such as "/resume-nonce"; re-registering replaces the handler
+pub fn register_command(command: &str, handler: CommandHandler) {
+    COMMANDS.insert(command.to_lowercase(), handler);
+}
+
+/// Register a section shown by /status, ordered by name
+pub fn register_status_section(name: &str, section: StatusSection) {
+    STATUS_SECTIONS.insert(name.to_string(), section);
+}
+
+/// Every registered /status section rendered now, ordered by name
+pub fn status_sections() -> Vec<(String, String)> {
+    let mut sections: Vec<(String, StatusSection)> = STATUS_SECTIONS
+        .iter()
+        .map(|entry| (entry.key().clone(), *entry.value()))
+        .collect();
+    sections.sort_by(|a, b| a.0.cmp(&b.0));
+    sections.into_iter().map(|(name, section)| (name, section())).collect()
+}
+
+/// Build the /status reply from all registered sections
+pub fn status_report() -> String {
+    let mut sections: Vec<(String, StatusSection)> = STATUS_SECTIONS
+        .iter()
+        .map(|entry| (entry.key().clone(), *entry.value()))
+        .collect();
+    if sections.is_empty() {
+        return "No status available".to_string();
+    }
+    sections.sort_by(|a, b| a.0.cmp(&b.0));
+    sections
+        .into_iter()
+        .map(|(_, section)| section())
+        .collect::<Vec<_>>()
+        .join("\n\n")
+}
+
+/// Dispatch a message text to its command handler, None if it isn't a known command
+pub fn dispatch(text: &str) -> Option<String> {
+    let text = text.trim();
+    if !text.starts_with('/') {
+        return None;
+    }
+    let (command, args) = text.split_once(char::is_whitespace).unwrap_or((text, ""));
+    // Commands sent in groups carry a "@botname" suffix
+    let command = command.split('@').next().unwrap_or(command).to_lowercase();
+    if command == "/status" {
+        return Some(status_report());
+    }
+    COMMANDS.get(&command).map(|handler| (*handler.value())(args.trim()))
+}
+
+/// Poll the bot for commands from TELEGRAM_CHAT_ID and answer them
+/// Messages from any other chat are ignored.
+pub async fn start_command_listener() {
+    let (Some(token), Some(chat_id)) = (
+        env::var("TELEGRAM_BOT_TOKEN").ok(),
+        env::var("TELEGRAM_CHAT_ID").ok().and_then(|v| v.parse::<i64>().ok()),
+    ) else {
+        return;
+    };
+
+    tokio::spawn(async move {
+        let logger = Logger::new("[TELEGRAM-COMMANDS] => ".blue().to_string());
+        let bot = Bot::new(token);
+        let mut offset: i32 = 0;
+
+        loop {
+            let updates = match bot.get_updates().offset(offset).timeout(30).await {
+                Ok(updates) => updates,
+                Err(e) => {
+                    logger.error(format!("Failed to poll commands: {} (retrying in 5s)", e));
+                    tokio::time::sleep(tokio::time::Duration::from_secs(5)).await;
+                    continue;
+                }
+            };
+
+            for update in updates {
+                offset = offset.max(update.id + 1);
+                let UpdateKind::Message(message) = update.kind else {
+                    continue;
+                };
+                if message.chat.id.0 != chat_id {
+                    continue;
+                }
+                let Some(reply) = message.text().and_then(dispatch) else {
+                    continue;
+                };
+                if let Err(e) = bot.send_message(message.chat.id, reply).await {
+                    logger.error(format!("Failed to answer command: {}", e));
+                }
+            }
+        }
+    });
+}
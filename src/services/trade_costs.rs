@@ -0,0 +1,270 @@
+use std::collections::{BTreeMap, HashSet};
+use std::str::FromStr;
+use std::time::{Duration, Instant};
+use anchor_client::solana_sdk::{pubkey::Pubkey, signature::Signature};
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use solana_transaction_status::{option_serializer::OptionSerializer, EncodedConfirmedTransactionWithStatusMeta, UiTransactionTokenBalance};
+
+use crate::core::tx_decode;
+use crate::services::rpc_client::BatchRpcClient;
+use crate::services::rpc_pool::RpcHandle;
+
+const LAMPORTS_PER_SOL_F64: f64 = 1_000_000_000.0;
+pub const WSOL_MINT: &str = "So11111111111111111111111111111111111111112";
+/// Base fee per required signature
+pub const SIGNATURE_FEE_LAMPORTS: u64 = 5_000;
+/// Pause between lookups of a transaction that hasn't landed yet
+const CONFIRM_POLL: Duration = Duration::from_secs(2);
+
+/// Costs around one fill, in SOL, written to the ledger as columns of their own
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct TradeCosts {
+    /// Base signature fee
+    #[serde(default)]
+    pub network_fee_sol: f64,
+    /// Compute unit price times units
+    #[serde(default)]
+    pub priority_fee_sol: f64,
+    /// zeroslot tip transfer
+    #[serde(default)]
+    pub tip_sol: f64,
+    /// Token account rent paid (positive) or reclaimed by closing the account (negative)
+    #[serde(default)]
+    pub rent_sol: f64,
+    /// Read from the landed transaction rather than estimated at send time
+    #[serde(default)]
+    pub costs_confirmed: bool,
+}
+
+impl TradeCosts {
+    /// Estimate for a live send with one signer, the tip and the priority fee its compute budget allows
+    /// The confirmed fee replaces the priority fee estimate once the transaction is read back.
+    pub fn estimated(tip_sol: f64, priority_fee_sol: f64) -> Self {
+        Self {
+            network_fee_sol: SIGNATURE_FEE_LAMPORTS as f64 / LAMPORTS_PER_SOL_F64,
+            priority_fee_sol,
+            tip_sol,
+            rent_sol: 0.0,
+            costs_confirmed: false,
+        }
+    }
+
+    /// Rent returned by closing a token account outside a trade (--close)
+    pub fn rent_reclaimed(lamports: u64) -> Self {
+        Self { rent_sol: -(lamports as f64) / LAMPORTS_PER_SOL_F64, costs_confirmed: true, ..Self::default() }
+    }
+
+    /// Net cost of the fill; negative when reclaimed rent outweighs the fees
+    pub fn total(&self) -> f64 {
+        self.network_fee_sol + self.priority_fee_sol + self.tip_sol + self.rent_sol
+    }
+}
+
+/// Actual costs of a landed transaction for `wallet`; the tip is read from its transfer to a tip account, else kept
+/// from `estimate` (the meta doesn't separate it). TIP_JITTER_PCT makes the sent tip differ from the estimate.
+/// The fee in the meta is base plus priority fee. Token accounts of the wallet that appear only in the post balances
+/// were created (rent paid); ones that appear only in the pre balances were closed (rent reclaimed). Temporary
+/// accounts opened and closed in the same transaction appear in neither and cost nothing.
+pub fn costs_from_meta(confirmed: &EncodedConfirmedTransactionWithStatusMeta, wallet: &Pubkey, estimate: &TradeCosts) -> Option<TradeCosts> {
+    let meta = confirmed.transaction.meta.as_ref()?;
+    let transaction = confirmed.transaction.transaction.decode();
+    let signers = transaction
+        .as_ref()
+        .map(|tx| tx.message.header().num_required_signatures as u64)
+        .unwrap_or(1);
+    let tip_lamports = transaction.as_ref().map(tx_decode::tip_lamports).unwrap_or(0);
+    let network_lamports = (SIGNATURE_FEE_LAMPORTS * signers).min(meta.fee);
+
+    let wallet = wallet.to_string();
+    let owned_indexes = |balances: &OptionSerializer<Vec<UiTransactionTokenBalance>>| -> HashSet<usize> {
+        match balances {
+            OptionSerializer::Some(balances) => balances
+                .iter()
+                .filter(|balance| matches!(&balance.owner, OptionSerializer::Some(owner) if *owner == wallet))
+                .map(|balance| balance.account_index as usize)
+                .collect(),
+            _ => HashSet::new(),
+        }
+    };
+    let before = owned_indexes(&meta.pre_token_balances);
+    let after = owned_indexes(&meta.post_token_balances);
+    let created: u64 = after.difference(&before).filter_map(|index| meta.post_balances.get(*index)).sum();
+    let closed: u64 = before.difference(&after).filter_map(|index| meta.pre_balances.get(*index)).sum();
+
+    Some(TradeCosts {
+        network_fee_sol: network_lamports as f64 / LAMPORTS_PER_SOL_F64,
+        priority_fee_sol: (meta.fee - network_lamports) as f64 / LAMPORTS_PER_SOL_F64,
+        tip_sol: if tip_lamports > 0 { tip_lamports as f64 / LAMPORTS_PER_SOL_F64 } else { estimate.tip_sol },
+        rent_sol: (created as f64 - closed as f64) / LAMPORTS_PER_SOL_F64,
+        costs_confirmed: true,
+    })
+}
+
+/// Raw token amounts and decimals per mint held by `wallet` in one set of balances
+pub fn owned_token_amounts(balances: &OptionSerializer<Vec<UiTransactionTokenBalance>>, wallet: &str) -> BTreeMap<String, (i128, u8)> {
+    let mut amounts = BTreeMap::new();
+    if let OptionSerializer::Some(balances) = balances {
+        for balance in balances {
+            if !matches!(&balance.owner, OptionSerializer::Some(owner) if owner == wallet) {
+                continue;
+            }
+            let raw = balance.ui_token_amount.amount.parse::<i128>().unwrap_or(0);
+            let entry = amounts.entry(balance.mint.clone()).or_insert((0, balance.ui_token_amount.decimals));
+            entry.0 += raw;
+        }
+    }
+    amounts
+}
+
+/// What a landed trade actually moved for the wallet
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ConfirmedFill {
+    /// SOL spent (buy) or received (sell), excluding `costs`
+    pub sol_amount: f64,
+    /// UI tokens received (buy) or given up (sell)
+    pub token_amount: f64,
+}
+
+impl ConfirmedFill {
+    /// SOL per UI token; None when no tokens moved or the amounts aren't finite, so the fill can't be priced
+    pub fn price(&self) -> Option<f64> {
+        if !self.token_amount.is_finite() || self.token_amount <= 0.0 || !self.sol_amount.is_finite() {
+            return None;
+        }
+        Some(self.sol_amount / self.token_amount).filter(|price| price.is_finite())
+    }
+}
+
+/// The fill of `mint` in a landed transaction, from the wallet's balance deltas
+/// SOL includes WSOL the wallet holds; `costs` (as from `costs_from_meta`) are taken out so the amount is the swap
+/// alone. None if the token balance didn't move or the wallet isn't an account of the transaction.
+pub fn fill_from_meta(confirmed: &EncodedConfirmedTransactionWithStatusMeta, wallet: &Pubkey, mint: &str, costs: &TradeCosts) -> Option<ConfirmedFill> {
+    let meta = confirmed.transaction.meta.as_ref()?;
+    let transaction = confirmed.transaction.transaction.decode()?;
+    let owner = wallet.to_string();
+    let before = owned_token_amounts(&meta.pre_token_balances, &owner);
+    let after = owned_token_amounts(&meta.post_token_balances, &owner);
+    let delta = |mint: &str| -> f64 {
+        let (pre, decimals) = before.get(mint).copied().unwrap_or((0, 0));
+        let (post, post_decimals) = after.get(mint).copied().unwrap_or((0, decimals));
+        (post - pre) as f64 / 10f64.powi(post_decimals.max(decimals) as i32)
+    };
+    let token_delta = delta(mint);
+    if token_delta == 0.0 {
+        return None;
+    }
+    let wallet_index = transaction.message.static_account_keys().iter().position(|key| key == wallet)?;
+    let native_delta = (*meta.post_balances.get(wallet_index)? as f64 - *meta.pre_balances.get(wallet_index)? as f64) / LAMPORTS_PER_SOL_F64;
+    let sol_delta = native_delta + delta(WSOL_MINT);
+    let sol_amount = if token_delta > 0.0 { -sol_delta - costs.total() } else { sol_delta + costs.total() };
+    Some(ConfirmedFill { sol_amount: sol_amount.max(0.0), token_amount: token_delta.abs() })
+}
+
+/// Costs of a landed trade and, when its balances show it, the fill itself
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct FillConfirmation {
+    pub costs: TradeCosts,
+    pub fill: Option<ConfirmedFill>,
+}
+
+/// Where the actual costs of a sent trade come from once it lands
+#[async_trait]
+pub trait FillCostSource: Send + Sync {
+    /// Costs read from the landed transaction; Err if it can't be read in time
+    async fn confirmed_costs(&self, signature: &str, estimate: &TradeCosts) -> Result<TradeCosts, String>;
+
+    /// Costs plus the actual fill of `mint`; sources that can't read balances report costs only
+    async fn confirmed_fill(&self, signature: &str, _mint: &str, estimate: &TradeCosts) -> Result<FillConfirmation, String> {
+        Ok(FillConfirmation { costs: self.confirmed_costs(signature, estimate).await?, fill: None })
+    }
+}
+
+/// Polls the read pool for the transaction until FILL_CONFIRM_TIMEOUT_SECS (default: 60)
+pub struct RpcFillCostSource {
+    read_rpc: RpcHandle,
+    wallet: Pubkey,
+    timeout: Duration,
+}
+
+impl RpcFillCostSource {
+    pub fn new(read_rpc: RpcHandle, wallet: Pubkey) -> Self {
+        let timeout_secs = std::env::var("FILL_CONFIRM_TIMEOUT_SECS")
+            .ok()
+            .and_then(|v| v.parse::<u64>().ok())
+            .unwrap_or(60);
+        Self { read_rpc, wallet, timeout: Duration::from_secs(timeout_secs) }
+    }
+
+    async fn landed(&self, signature: &str) -> Result<EncodedConfirmedTransactionWithStatusMeta, String> {
+        let signature = Signature::from_str(signature).map_err(|e| format!("invalid signature {}: {}", signature, e))?;
+        let deadline = Instant::now() + self.timeout;
+        loop {
+            let lookup = BatchRpcClient::new(self.read_rpc.client()).get_transaction_cached(&signature).await;
+            match lookup {
+                Ok(confirmed) => return Ok(confirmed),
+                Err(e) if Instant::now() + CONFIRM_POLL >= deadline => {
+                    return Err(format!("not confirmed within {}s: {}", self.timeout.as_secs(), e));
+                }
+                Err(_) => tokio::time::sleep(CONFIRM_POLL).await,
+            }
+        }
+    }
+}
+
+#[async_trait]
+impl FillCostSource for RpcFillCostSource {
+    async fn confirmed_costs(&self, signature: &str, estimate: &TradeCosts) -> Result<TradeCosts, String> {
+        let confirmed = self.landed(signature).await?;
+        costs_from_meta(&confirmed, &self.wallet, estimate).ok_or_else(|| format!("transaction {} has no meta", signature))
+    }
+
+    async fn confirmed_fill(&self, signature: &str, mint: &str, estimate: &TradeCosts) -> Result<FillConfirmation, String> {
+        let confirmed = self.landed(signature).await?;
+        let costs = costs_from_meta(&confirmed, &self.wallet, estimate).ok_or_else(|| format!("transaction {} has no meta", signature))?;
+        Ok(FillConfirmation { costs, fill: fill_from_meta(&confirmed, &self.wallet, mint, &costs) })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fill_price_is_sol_per_token() {
+        let fill = ConfirmedFill { sol_amount: 0.5, token_amount: 1_000.0 };
+        assert_eq!(fill.price(), Some(0.0005));
+    }
+
+    #[test]
+    fn fill_without_tokens_has_no_price() {
+        assert_eq!(ConfirmedFill { sol_amount: 0.5, token_amount: 0.0 }.price(), None);
+        assert_eq!(ConfirmedFill { sol_amount: 0.0, token_amount: 0.0 }.price(), None);
+        assert_eq!(ConfirmedFill { sol_amount: 0.5, token_amount: -1.0 }.price(), None);
+    }
+
+    #[test]
+    fn fill_with_non_finite_amounts_has_no_price() {
+        assert_eq!(ConfirmedFill { sol_amount: f64::NAN, token_amount: 1.0 }.price(), None);
+        assert_eq!(ConfirmedFill { sol_amount: 1.0, token_amount: f64::INFINITY }.price(), None);
+        // Subnormal token amounts overflow the division
+        assert_eq!(ConfirmedFill { sol_amount: 1e300, token_amount: 1e-300 }.price(), None);
+    }
+
+    #[test]
+    fn reclaimed_rent_counts_against_the_fees() {
+        let estimate = TradeCosts::estimated(0.001, 0.000_02);
+        assert!((estimate.total() - 0.001_025).abs() < 1e-12);
+        assert!(!estimate.costs_confirmed);
+
+        let reclaimed = TradeCosts::rent_reclaimed(2_039_280);
+        assert_eq!(reclaimed.rent_sol, -0.002_039_28);
+        assert!(reclaimed.total() < 0.0 && reclaimed.costs_confirmed);
+    }
+
+    #[test]
+    fn cost_columns_default_for_older_ledger_lines() {
+        let costs: TradeCosts = serde_json::from_str(r#"{"tip_sol":0.001}"#).unwrap();
+        assert_eq!(costs, TradeCosts { tip_sol: 0.001, ..TradeCosts::default() });
+    }
+}
@@ -0,0 +1,336 @@
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use anchor_client::solana_sdk::{signature::Signer, system_instruction};
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use colored::Colorize;
+use futures::future::join_all;
+
+use crate::common::{config::AppState, logger::Logger};
+use crate::core::tx;
+use crate::dex::pump_fun;
+use crate::services::{nonce_health, telegram};
+
+/// One thing to exercise before the first trade
+#[async_trait]
+pub trait WarmupTarget: Send + Sync {
+    fn name(&self) -> &str;
+    async fn warm(&self) -> Result<()>;
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum WarmupOutcome {
+    Ok,
+    Failed(String),
+    TimedOut,
+}
+
+#[derive(Clone, Debug)]
+pub struct WarmupTiming {
+    pub name: String,
+    pub elapsed: Duration,
+    pub outcome: WarmupOutcome,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct WarmupConfig {
+    pub enabled: bool,
+    /// Cap for a single item
+    pub item_timeout: Duration,
+    /// Cap for the whole phase; items run concurrently so this bounds every item too
+    pub deadline: Duration,
+    pub probe_tx: bool,
+}
+
+impl WarmupConfig {
+    /// WARMUP_ENABLED (default: true), WARMUP_ITEM_TIMEOUT_MS (default: 1500), WARMUP_DEADLINE_MS (default: 3000),
+    /// WARMUP_PROBE_TX (default: false)
+    pub fn from_env() -> Self {
+        let parse_bool = |key: &str, default: bool| {
+            std::env::var(key).ok().and_then(|v| v.parse::<bool>().ok()).unwrap_or(default)
+        };
+        let parse_ms = |key: &str, default: u64| {
+            Duration::from_millis(std::env::var(key).ok().and_then(|v| v.parse::<u64>().ok()).unwrap_or(default))
+        };
+        Self {
+            enabled: parse_bool("WARMUP_ENABLED", true),
+            item_timeout: parse_ms("WARMUP_ITEM_TIMEOUT_MS", 1500),
+            deadline: parse_ms("WARMUP_DEADLINE_MS", 3000),
+            probe_tx: parse_bool("WARMUP_PROBE_TX", false),
+        }
+    }
+}
+
+/// Run every target concurrently, each bounded by the item timeout and the overall deadline
+/// Failures and timeouts are reported, never fatal.
+pub async fn run_warmup(targets: &[Arc<dyn WarmupTarget>], config: &WarmupConfig) -> Vec<WarmupTiming> {
+    let limit = config.item_timeout.min(config.deadline);
+    join_all(targets.iter().map(|target| async move {
+        let started = Instant::now();
+        let outcome = match tokio::time::timeout(limit, target.warm()).await {
+            Ok(Ok(())) => WarmupOutcome::Ok,
+            Ok(Err(e)) => WarmupOutcome::Failed(e.to_string()),
+            Err(_) => WarmupOutcome::TimedOut,
+        };
+        WarmupTiming {
+            name: target.name().to_string(),
+            elapsed: started.elapsed(),
+            outcome,
+        }
+    }))
+    .await
+}
+
+/// Read RPC: one cheap call opens the connection and resolves DNS
+struct RpcWarmup {
+    app_state: Arc<AppState>,
+}
+
+#[async_trait]
+impl WarmupTarget for RpcWarmup {
+    fn name(&self) -> &str {
+        "rpc"
+    }
+
+    async fn warm(&self) -> Result<()> {
+        self.app_state.rpc_nonblocking_client.get_slot().await?;
+        Ok(())
+    }
+}
+
+/// The blocking client has its own connection pool
+struct BlockingRpcWarmup {
+    app_state: Arc<AppState>,
+}
+
+#[async_trait]
+impl WarmupTarget for BlockingRpcWarmup {
+    fn name(&self) -> &str {
+        "rpc-blocking"
+    }
+
+    async fn warm(&self) -> Result<()> {
+        let client = self.app_state.rpc_client.clone();
+        tokio::task::spawn_blocking(move || client.get_slot().map_err(anyhow::Error::from)).await??;
+        Ok(())
+    }
+}
+
+struct ZeroSlotWarmup {
+    app_state: Arc<AppState>,
+}
+
+#[async_trait]
+impl WarmupTarget for ZeroSlotWarmup {
+    fn name(&self) -> &str {
+        "zeroslot"
+    }
+
+    async fn warm(&self) -> Result<()> {
+        self.app_state.zeroslot_rpc_client.warm_up().await
+    }
+}
+
+struct JupiterWarmup {
+    app_state: Arc<AppState>,
+}
+
+#[async_trait]
+impl WarmupTarget for JupiterWarmup {
+    fn name(&self) -> &str {
+        "jupiter"
+    }
+
+    async fn warm(&self) -> Result<()> {
+        self.app_state.jupiter_client.warm_up().await
+    }
+}
+
+struct TelegramWarmup;
+
+#[async_trait]
+impl WarmupTarget for TelegramWarmup {
+    fn name(&self) -> &str {
+        "telegram"
+    }
+
+    async fn warm(&self) -> Result<()> {
+        telegram::warm_up().await.map_err(|e| anyhow!(e))
+    }
+}
+
+/// WSOL ATA and the mint-independent PumpFun PDAs
+struct AddressWarmup {
+    app_state: Arc<AppState>,
+}
+
+#[async_trait]
+impl WarmupTarget for AddressWarmup {
+    fn name(&self) -> &str {
+        "addresses"
+    }
+
+    async fn warm(&self) -> Result<()> {
+        pump_fun::prederive_static_addresses(&self.app_state.wallet.pubkey())
+    }
+}
+
+/// Build the lazily initialized globals the hot path touches
+struct StaticsWarmup;
+
+#[async_trait]
+impl WarmupTarget for StaticsWarmup {
+    fn name(&self) -> &str {
+        "statics"
+    }
+
+    async fn warm(&self) -> Result<()> {
+        let _ = crate::services::zeroslot::ZERO_SLOT_URLS.len();
+        let _ = crate::engine::sniper::TOKEN_HOLDINGS.len();
+        let _ = crate::engine::sniper::SELL_REASONS.len();
+        let _ = crate::common::cache::PROGRESS_ON_BUYING.len();
+        let _ = crate::common::cache::PROGRESS_ON_SELLING.len();
+        let _ = crate::common::cache::DEAD_TOKEN_LIST.len();
+        let _ = crate::engine::position::POSITION_BOOK.len();
+        once_cell::sync::Lazy::force(&nonce_health::NONCE_HEALTH);
+        once_cell::sync::Lazy::force(&crate::common::log_dedup::LOG_THROTTLE);
+        Ok(())
+    }
+}
+
+/// A zero-lamport self-transfer through the live buy send path (WARMUP_PROBE_TX=true)
+/// Costs one network fee plus the zeroslot tip.
+struct ProbeTxWarmup {
+    app_state: Arc<AppState>,
+}
+
+#[async_trait]
+impl WarmupTarget for ProbeTxWarmup {
+    fn name(&self) -> &str {
+        "probe-tx"
+    }
+
+    async fn warm(&self) -> Result<()> {
+        let keypair = self.app_state.wallet.clone();
+        let wallet = keypair.pubkey();
+        let mut instructions = vec![system_instruction::transfer(&wallet, &wallet, 0)];
+        let (blockhash, mode) = nonce_health::prepare_blockhash(&self.app_state, &mut instructions).await?;
+        let logger = Logger::new("[WARMUP] => ".cyan().to_string());
+        let result = tx::new_signed_and_send_zeroslot(
+            self.app_state.zeroslot_rpc_client.clone(),
+            blockhash,
+            &keypair,
+            instructions,
+            &logger,
+            false,
+            None,
+        )
+        .await;
+        let error = result.as_ref().err().map(|e| e.to_string());
+        nonce_health::record_send_outcome(&wallet, mode, error.as_deref());
+        result.map(|_| ())
+    }
+}
+
+/// Warm every client, cache and static the first buy would otherwise pay for
+/// Runs after config load and before the subscriptions go live, printing one timing line per item.
+pub async fn warm_up(app_state: &AppState) {
+    let config = WarmupConfig::from_env();
+    if !config.enabled {
+        return;
+    }
+
+    let app_state = Arc::new(app_state.clone());
+    let mut targets: Vec<Arc<dyn WarmupTarget>> = vec![
+        Arc::new(RpcWarmup { app_state: app_state.clone() }),
+        Arc::new(BlockingRpcWarmup { app_state: app_state.clone() }),
+        Arc::new(ZeroSlotWarmup { app_state: app_state.clone() }),
+        Arc::new(JupiterWarmup { app_state: app_state.clone() }),
+        Arc::new(TelegramWarmup),
+        Arc::new(AddressWarmup { app_state: app_state.clone() }),
+        Arc::new(StaticsWarmup),
+    ];
+    if config.probe_tx {
+        targets.push(Arc::new(ProbeTxWarmup { app_state }));
+    }
+
+    println!("\n🔥 Warming up connections and caches...");
+    let started = Instant::now();
+    let timings = run_warmup(&targets, &config).await;
+    for timing in &timings {
+        let line = format!("   {:<14} {:>6} ms", timing.name, timing.elapsed.as_millis());
+        match &timing.outcome {
+            WarmupOutcome::Ok => println!("{} {}", line, "ok".green()),
+            WarmupOutcome::Failed(e) => println!("{} {} ({})", line, "failed".red(), e),
+            WarmupOutcome::TimedOut => println!("{} {}", line, "timed out".yellow()),
+        }
+    }
+    println!("✅ Warm-up finished in {} ms", started.elapsed().as_millis());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Sleeps for `delay`, then succeeds or fails
+    struct FakeTarget {
+        name: &'static str,
+        delay: Duration,
+        fail: bool,
+    }
+
+    #[async_trait]
+    impl WarmupTarget for FakeTarget {
+        fn name(&self) -> &str {
+            self.name
+        }
+
+        async fn warm(&self) -> Result<()> {
+            tokio::time::sleep(self.delay).await;
+            if self.fail {
+                Err(anyhow!("refused"))
+            } else {
+                Ok(())
+            }
+        }
+    }
+
+    fn target(name: &'static str, delay_ms: u64, fail: bool) -> Arc<dyn WarmupTarget> {
+        Arc::new(FakeTarget { name, delay: Duration::from_millis(delay_ms), fail })
+    }
+
+    fn config(item_timeout_ms: u64, deadline_ms: u64) -> WarmupConfig {
+        WarmupConfig {
+            enabled: true,
+            item_timeout: Duration::from_millis(item_timeout_ms),
+            deadline: Duration::from_millis(deadline_ms),
+            probe_tx: false,
+        }
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn outcomes_are_reported_in_target_order() {
+        let targets = vec![target("fast", 10, false), target("broken", 10, true), target("slow", 5_000, false)];
+        let timings = run_warmup(&targets, &config(1_500, 3_000)).await;
+
+        let outcomes: Vec<(&str, &WarmupOutcome)> = timings.iter().map(|timing| (timing.name.as_str(), &timing.outcome)).collect();
+        assert_eq!(outcomes, vec![
+            ("fast", &WarmupOutcome::Ok),
+            ("broken", &WarmupOutcome::Failed("refused".to_string())),
+            ("slow", &WarmupOutcome::TimedOut),
+        ]);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn targets_run_concurrently_within_the_deadline() {
+        let targets = vec![target("a", 800, false), target("b", 800, false), target("c", 800, false)];
+        let started = tokio::time::Instant::now();
+        let timings = run_warmup(&targets, &config(1_500, 1_000)).await;
+        assert!(timings.iter().all(|timing| timing.outcome == WarmupOutcome::Ok));
+        assert_eq!(started.elapsed(), Duration::from_millis(800));
+
+        // A deadline shorter than the item timeout bounds every item
+        let timings = run_warmup(&[target("late", 1_200, false)], &config(1_500, 1_000)).await;
+        assert_eq!(timings[0].outcome, WarmupOutcome::TimedOut);
+    }
+}
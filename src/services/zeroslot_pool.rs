@@ -0,0 +1,216 @@
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use anchor_client::solana_sdk::{signature::Signature, transaction::VersionedTransaction};
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use colored::Colorize;
+
+use crate::common::logger::Logger;
+use crate::error::ClientError;
+use crate::services::telegram_commands;
+use crate::services::zeroslot::{TipLandingClient, ZeroSlotClient};
+
+/// A send still unanswered after this long moves on to the next endpoint
+const SEND_TIMEOUT: Duration = Duration::from_millis(1500);
+const HEALTH_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Sends that moved on from an endpoint that errored or timed out, since startup
+static FAILOVERS: AtomicU64 = AtomicU64::new(0);
+
+pub fn failover_count() -> u64 {
+    FAILOVERS.load(Ordering::Relaxed)
+}
+
+/// One zeroslot region and what its last health check saw
+struct PoolEndpoint {
+    url: String,
+    health_url: String,
+    client: ZeroSlotClient,
+    healthy: AtomicBool,
+    /// Last health check round trip; u64::MAX until the first one answers
+    latency_ms: AtomicU64,
+}
+
+/// Health URL for `endpoint`: its origin plus `/health`, as ZERO_SLOT_HEALTH is for the default region
+pub fn health_url_for(endpoint: &str) -> String {
+    match reqwest::Url::parse(endpoint) {
+        Ok(url) => format!("{}/health", url.origin().ascii_serialization()),
+        Err(_) => format!("{}/health", endpoint.trim_end_matches('/')),
+    }
+}
+
+/// zeroslot endpoints from ZERO_SLOT_URLS (comma-separated), or the single ZERO_SLOT_URL
+/// Sends go to the fastest healthy endpoint and fail over down the list; the identical signed
+/// transaction is resent, so at most one copy can land.
+pub struct ZeroSlotPool {
+    endpoints: Vec<PoolEndpoint>,
+    /// Endpoint indexes, healthy ones first by latency
+    order: Mutex<Vec<usize>>,
+    http: reqwest::Client,
+}
+
+impl ZeroSlotPool {
+    /// `urls` must not be empty
+    pub fn new(urls: Vec<String>) -> Self {
+        assert!(!urls.is_empty(), "zeroslot pool needs at least one endpoint");
+        // A lone ZERO_SLOT_URL keeps checking ZERO_SLOT_HEALTH, which may point at another host
+        let single = urls.len() == 1;
+        let endpoints: Vec<PoolEndpoint> = urls
+            .into_iter()
+            .map(|url| PoolEndpoint {
+                health_url: if single { crate::common::config::get_zero_slot_health_url() } else { health_url_for(&url) },
+                client: ZeroSlotClient::new(&url),
+                url,
+                healthy: AtomicBool::new(true),
+                latency_ms: AtomicU64::new(u64::MAX),
+            })
+            .collect();
+        let order = (0..endpoints.len()).collect();
+        Self {
+            endpoints,
+            order: Mutex::new(order),
+            http: reqwest::Client::new(),
+        }
+    }
+
+    pub fn from_env() -> Self {
+        Self::new(crate::services::zeroslot::ZERO_SLOT_URLS.clone())
+    }
+
+    /// The process-wide pool AppState sends through
+    pub fn shared() -> Arc<ZeroSlotPool> {
+        POOL.get_or_init(|| Arc::new(Self::from_env())).clone()
+    }
+
+    pub fn len(&self) -> usize {
+        self.endpoints.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.endpoints.is_empty()
+    }
+
+    fn ordered(&self) -> Vec<usize> {
+        self.order.lock().map(|order| order.clone()).unwrap_or_else(|_| (0..self.endpoints.len()).collect())
+    }
+
+    /// Check every endpoint once and re-rank them
+    async fn check_health(&self, logger: &Logger) {
+        for endpoint in &self.endpoints {
+            let started = Instant::now();
+            let healthy = matches!(
+                self.http.get(&endpoint.health_url).timeout(HEALTH_TIMEOUT).send().await,
+                Ok(response) if response.status().is_success()
+            );
+            let latency = if healthy { started.elapsed().as_millis() as u64 } else { u64::MAX };
+            if healthy != endpoint.healthy.load(Ordering::Relaxed) {
+                let state = if healthy { "healthy again" } else { "unhealthy" };
+                logger.error(format!("zeroslot endpoint {} is {}", endpoint.url, state));
+            }
+            endpoint.healthy.store(healthy, Ordering::Relaxed);
+            endpoint.latency_ms.store(latency, Ordering::Relaxed);
+        }
+        let mut order: Vec<usize> = (0..self.endpoints.len()).collect();
+        // Unhealthy endpoints stay at the end as a last resort, in configured order
+        order.sort_by_key(|index| {
+            let endpoint = &self.endpoints[*index];
+            (!endpoint.healthy.load(Ordering::Relaxed), endpoint.latency_ms.load(Ordering::Relaxed))
+        });
+        if let Ok(mut current) = self.order.lock() {
+            *current = order;
+        }
+    }
+
+    /// Send `transaction` down the ranked endpoints; Ok with the signature and the endpoint that took it
+    pub async fn send_ranked(&self, transaction: &VersionedTransaction) -> Result<(Signature, String), ClientError> {
+        let order = self.ordered();
+        // Kept as text: ClientError isn't Send, and error classification only reads the message
+        let mut last_error = "no zeroslot endpoint configured".to_string();
+        for (attempt, index) in order.iter().enumerate() {
+            let endpoint = &self.endpoints[*index];
+            if attempt > 0 {
+                FAILOVERS.fetch_add(1, Ordering::Relaxed);
+            }
+            match tokio::time::timeout(SEND_TIMEOUT, endpoint.client.send_transaction(transaction)).await {
+                Ok(Ok(signature)) => return Ok((signature, endpoint.url.clone())),
+                Ok(Err(e)) => last_error = format!("{}: {}", endpoint.url, e),
+                Err(_) => last_error = format!("{} did not answer within {}ms", endpoint.url, SEND_TIMEOUT.as_millis()),
+            }
+        }
+        Err(ClientError::Other(last_error))
+    }
+
+    fn status_line(&self) -> String {
+        let ranked: Vec<String> = self
+            .ordered()
+            .into_iter()
+            .map(|index| {
+                let endpoint = &self.endpoints[index];
+                match endpoint.latency_ms.load(Ordering::Relaxed) {
+                    _ if !endpoint.healthy.load(Ordering::Relaxed) => format!("{} (down)", endpoint.url),
+                    u64::MAX => format!("{} (unchecked)", endpoint.url),
+                    ms => format!("{} ({}ms)", endpoint.url, ms),
+                }
+            })
+            .collect();
+        format!("Zeroslot: {}; {} failover(s)", ranked.join(", "), failover_count())
+    }
+}
+
+#[async_trait]
+impl TipLandingClient for ZeroSlotPool {
+    async fn warm_up(&self) -> Result<()> {
+        let mut errors = Vec::new();
+        for endpoint in &self.endpoints {
+            if let Err(e) = endpoint.client.warm_up().await {
+                errors.push(format!("{}: {}", endpoint.url, e));
+            }
+        }
+        // One warm region is enough to send through
+        if errors.len() == self.endpoints.len() {
+            return Err(anyhow!("zeroslot warm-up failed: {}", errors.join("; ")));
+        }
+        Ok(())
+    }
+
+    async fn send_transaction(&self, transaction: &VersionedTransaction) -> Result<Signature, ClientError> {
+        self.send_ranked(transaction).await.map(|(signature, _)| signature)
+    }
+
+    async fn send_transaction_via(&self, transaction: &VersionedTransaction) -> Result<(Signature, String), ClientError> {
+        self.send_ranked(transaction).await
+    }
+}
+
+static POOL: once_cell::sync::OnceCell<Arc<ZeroSlotPool>> = once_cell::sync::OnceCell::new();
+
+fn zeroslot_status_section() -> String {
+    match POOL.get() {
+        Some(pool) => pool.status_line(),
+        None => "Zeroslot: pool not initialized".to_string(),
+    }
+}
+
+/// Check every endpoint every ZERO_SLOT_HEALTH_INTERVAL_SECS (default: 5) and register the "zeroslot" /status section
+/// A single endpoint has nothing to fail over to, so it gets the section but no checks.
+pub fn start_zeroslot_health_probe() {
+    let pool = ZeroSlotPool::shared();
+    telegram_commands::register_status_section("zeroslot", zeroslot_status_section);
+    if pool.len() < 2 {
+        return;
+    }
+
+    let interval_secs = std::env::var("ZERO_SLOT_HEALTH_INTERVAL_SECS")
+        .ok()
+        .and_then(|v| v.parse::<u64>().ok())
+        .unwrap_or(5);
+    tokio::spawn(async move {
+        let logger = Logger::new("[ZEROSLOT-HEALTH] => ".cyan().to_string());
+        let mut interval = tokio::time::interval(Duration::from_secs(interval_secs.max(1)));
+        loop {
+            interval.tick().await;
+            pool.check_health(&logger).await;
+        }
+    });
+}
@@ -0,0 +1,108 @@
+//! A paper session through the orchestrator: trigger buy, tier exit, stop loss, close
+
+use std::sync::Arc;
+use anchor_client::solana_sdk::pubkey::Pubkey;
+
+use solana_vntr_sniper::common::config::SwapConfig;
+use solana_vntr_sniper::dex::pump_fun::Pump;
+use solana_vntr_sniper::engine::event_stream::ScriptedEventSource;
+use solana_vntr_sniper::engine::executor::PaperExecutor;
+use solana_vntr_sniper::engine::exit_strategy::{parse_profit_tiers, RuntimeParams};
+use solana_vntr_sniper::engine::orchestrator::Orchestrator;
+use solana_vntr_sniper::engine::position::PositionBook;
+use solana_vntr_sniper::engine::swap::{SwapDirection, SwapInType};
+use solana_vntr_sniper::engine::transaction_parser::{DexType, TradeInfoFromToken};
+use solana_vntr_sniper::services::ledger::MemoryLedger;
+use solana_vntr_sniper::services::notifier::MemoryNotifier;
+
+const VIRTUAL_SOL: u64 = 30_000_000_000;
+const VIRTUAL_TOKENS: u64 = 1_073_000_000_000_000;
+
+/// Someone else's trade of `mint` leaving the curve at `virtual_sol` lamports
+fn trade(mint: &str, slot: u64, is_buy: bool, virtual_sol: u64) -> TradeInfoFromToken {
+    let price = Pump::calculate_price_from_virtual_reserves(virtual_sol, VIRTUAL_TOKENS);
+    TradeInfoFromToken {
+        dex_type: DexType::PumpFun,
+        slot,
+        signature: format!("trade-{}", slot),
+        pool_id: String::new(),
+        mint: mint.to_string(),
+        timestamp: solana_vntr_sniper::common::clock::now_secs(),
+        is_buy,
+        post_current_price: price,
+        pre_current_price: price,
+        is_reverse_when_pump_swap: false,
+        coin_creator: Some(Pubkey::new_unique().to_string()),
+        sol_change: if is_buy { 1.0 } else { -1.0 },
+        target_transaction_token_change: 0.0,
+        token_decimals: 6,
+        liquidity: virtual_sol as f64 / 1e9,
+        virtual_sol_reserves: virtual_sol,
+        virtual_token_reserves: VIRTUAL_TOKENS,
+        buy_sell_in_same_tx: false,
+        trader: Pubkey::new_unique().to_string(),
+        is_own_trade: false,
+        timestamp_suspect: false,
+        gap_replayed: false,
+        curve_complete: false,
+        suspected_mev: false,
+    }
+}
+
+#[tokio::test]
+async fn paper_position_runs_from_buy_to_close() {
+    let mint = Pubkey::new_unique().to_string();
+    let positions = Arc::new(PositionBook::new());
+    let ledger = Arc::new(MemoryLedger::new());
+    let notifier = Arc::new(MemoryNotifier::new());
+    let swap_config = SwapConfig {
+        swap_direction: SwapDirection::Buy,
+        in_type: SwapInType::Qty,
+        amount_in: 0.1,
+        buy_slippage: 700,
+        reverse: false,
+        dry_run: false,
+    };
+    let params = RuntimeParams {
+        profit_tiers: parse_profit_tiers("30:50"),
+        trailing_stop_pct: None,
+        stop_loss_pct: Some(20.0),
+        ..RuntimeParams::default()
+    };
+    let orchestrator = Orchestrator::new(
+        Arc::new(PaperExecutor::new()),
+        positions.clone(),
+        ledger.clone(),
+        notifier.clone(),
+        swap_config,
+        params,
+    );
+
+    // Trigger, +50% (first tier sells half), then -40% from entry (stop loss sells the rest)
+    let mut source = ScriptedEventSource::new(vec![
+        trade(&mint, 1, true, VIRTUAL_SOL),
+        trade(&mint, 2, true, VIRTUAL_SOL * 3 / 2),
+        trade(&mint, 3, false, VIRTUAL_SOL * 6 / 10),
+    ]);
+    orchestrator.run(&mut source).await;
+
+    assert!(positions.get(&mint).is_none(), "closed position left in the book");
+    let entries = ledger.entries();
+    let sides: Vec<(&str, &str)> = entries.iter().map(|entry| (entry.side.as_str(), entry.reason.as_str())).collect();
+    assert_eq!(sides.len(), 3, "{:?}", sides);
+    assert_eq!(sides[0], ("buy", "Qualifying buy"));
+    assert_eq!(sides[1].0, "sell");
+    assert_eq!(sides[2].0, "sell");
+    assert!(entries.iter().all(|entry| entry.simulated));
+
+    // Both sells together hand back every token bought
+    let bought = entries[0].token_amount;
+    let sold: f64 = entries[1..].iter().map(|entry| entry.token_amount).sum();
+    assert!((bought - sold).abs() < 1e-6, "bought {} sold {}", bought, sold);
+    assert!((entries[1].token_amount - bought / 2.0).abs() < 1e-6);
+    // The tier sold above entry, the stop below it
+    assert!(entries[1].price > entries[0].price);
+    assert!(entries[2].price < entries[0].price);
+
+    assert_eq!(notifier.messages().len(), 3);
+}
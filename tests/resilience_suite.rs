@@ -0,0 +1,13 @@
+//! The chaos resilience suite under its default faults, without CHAOS_MODE or PAPER_TRADING
+//! It reads process-wide session counters, so it lives in its own binary, away from the unit tests.
+
+use solana_vntr_sniper::engine::chaos::{run_resilience_suite, ChaosConfig};
+
+#[tokio::test]
+async fn invariants_hold_under_the_default_faults() {
+    for seed in [1, 7, 42] {
+        let report = run_resilience_suite(ChaosConfig { seed, ..ChaosConfig::default() }).await;
+        assert!(report.passed(), "{}", report.format());
+        assert!(report.events > 0 && report.buys > 0, "{}", report.format());
+    }
+}